@@ -0,0 +1,106 @@
+// Deterministic inputs shared by the benchmarks, so results are comparable
+// across machines and commits.
+#![allow(dead_code)]
+
+/// Shape of a generated robots.txt.
+#[derive(Clone, Copy, Debug)]
+pub struct Synthetic {
+    /// Approximate size of the file in bytes; generation stops at the first
+    /// group boundary past it.
+    pub bytes: usize,
+    /// Rules per group.
+    pub rules_per_group: usize,
+    /// One rule in this many uses `*` or `$`; 0 for none.
+    pub wildcard_every: usize,
+    /// Seed for the generator, so different inputs of the same shape can be
+    /// produced on purpose.
+    pub seed: u64,
+}
+
+impl Synthetic {
+    pub const fn new(bytes: usize) -> Self {
+        Self {
+            bytes,
+            rules_per_group: 200,
+            wildcard_every: 10,
+            seed: 0x5eed,
+        }
+    }
+
+    pub const fn rules_per_group(mut self, rules: usize) -> Self {
+        self.rules_per_group = rules;
+        self
+    }
+
+    pub const fn wildcard_every(mut self, every: usize) -> Self {
+        self.wildcard_every = every;
+        self
+    }
+
+    /// Generate the file. The same `Synthetic` always yields the same bytes.
+    pub fn generate(&self) -> String {
+        let mut rng = SplitMix64(self.seed);
+        let mut out = String::with_capacity(self.bytes + 4096);
+        let mut group = 0;
+        while out.len() < self.bytes {
+            out.push_str(&format!("User-agent: {}\n", user_agent(group)));
+            if group % 3 == 0 {
+                out.push_str(&format!("User-agent: {}\n", user_agent(group + 1000)));
+            }
+            for i in 0..self.rules_per_group {
+                let directive = if rng.next() % 3 == 0 {
+                    "Allow"
+                } else {
+                    "Disallow"
+                };
+                let section = rng.next() % 10_000;
+                let pattern = if self.wildcard_every > 0 && i % self.wildcard_every == 0 {
+                    match rng.next() % 3 {
+                        0 => format!("/*/section-{section}/*.pdf$"),
+                        1 => format!("/section-{section}/*/private"),
+                        _ => format!("/section-{section}/index.html$"),
+                    }
+                } else {
+                    format!("/section-{section}/page-{}/", rng.next() % 100)
+                };
+                out.push_str(&format!("{directive}: {pattern}\n"));
+            }
+            if group % 5 == 0 {
+                out.push_str(&format!("Crawl-delay: {}\n", group % 10));
+            }
+            out.push('\n');
+            group += 1;
+        }
+        out.push_str(
+            "User-agent: *\nDisallow: /private/\n\nSitemap: https://example.com/sitemap.xml\n",
+        );
+        out
+    }
+}
+
+/// Labelled paths that hit, miss, and exercise wildcards against generated
+/// files.
+pub const PATHS: &[(&str, &str)] = &[
+    ("literal", "/section-42/page-7/"),
+    ("anchored_wildcard", "/2024/section-42/report.pdf"),
+    ("inner_wildcard", "/section-42/a/b/private/file"),
+    ("no_match", "/unlisted/page.html"),
+];
+
+/// The user-agent token of generated group `n`.
+pub fn user_agent(n: usize) -> String {
+    format!("Crawler{n}")
+}
+
+/// Small, fast, and stable across platforms, unlike `rand`'s defaults.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use std::hint::black_box;
+
+use common::Synthetic;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use robots_server::fetcher::{CappedBody, MAX_ROBOTS_TXT_SIZE};
+
+/// Feed `body` to the fetcher's truncation loop in `chunk_size` pieces, as a
+/// streamed response would arrive.
+fn read(body: &[u8], chunk_size: usize) -> (String, bool) {
+    let mut reader = CappedBody::new();
+    for chunk in body.chunks(chunk_size) {
+        if !reader.push(chunk) {
+            break;
+        }
+    }
+    reader.finish()
+}
+
+fn bench_capped_body(c: &mut Criterion) {
+    let mut group = c.benchmark_group("capped_body");
+    for (name, size) in [
+        ("under_limit", 256 * 1024),
+        ("over_limit", 2 * MAX_ROBOTS_TXT_SIZE),
+    ] {
+        let body = Synthetic::new(size).generate().into_bytes();
+        group.throughput(Throughput::Bytes(body.len().min(MAX_ROBOTS_TXT_SIZE) as u64));
+        for chunk_size in [1024, 16 * 1024] {
+            group.bench_with_input(
+                BenchmarkId::new(name, chunk_size),
+                &chunk_size,
+                |b, &chunk_size| b.iter(|| read(black_box(&body), chunk_size)),
+            );
+        }
+    }
+    // Newline-free bodies in small chunks are the worst case for finding
+    // where to cut
+    let body = "User-agent: bot".repeat(2 * MAX_ROBOTS_TXT_SIZE / 15);
+    group.throughput(Throughput::Bytes(MAX_ROBOTS_TXT_SIZE as u64));
+    for chunk_size in [7, 1024] {
+        group.bench_with_input(
+            BenchmarkId::new("no_newlines", chunk_size),
+            &chunk_size,
+            |b, &chunk_size| b.iter(|| read(black_box(body.as_bytes()), chunk_size)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_capped_body);
+criterion_main!(benches);
@@ -0,0 +1,73 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::time::SystemTime;
+
+use common::{Synthetic, user_agent};
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::fetcher::RobotsText;
+
+const TARGET_URL: &str = "https://example.com/";
+const PATH: &str = "/section-42/page-7/";
+
+fn text(bytes: usize) -> RobotsText {
+    let body = Synthetic::new(bytes).generate();
+    RobotsText {
+        robots_txt_url: "https://example.com/robots.txt".to_string(),
+        http_status_code: 200,
+        content_length_bytes: body.len() as u64,
+        body,
+        truncated: false,
+        fetched_at: SystemTime::now(),
+        http_version: "HTTP/1.1".to_string(),
+        upstream_headers: BTreeMap::new(),
+        max_age: None,
+        resolved_address: None,
+    }
+}
+
+fn bench_lazy_parse(c: &mut Criterion) {
+    let agent = user_agent(0);
+    for (name, bytes) in [("10kb", 10 * 1024), ("500kb", 500 * 1024)] {
+        let text = text(bytes);
+        let mut group = c.benchmark_group(format!("lazy_parse_{name}"));
+        // On a miss both modes parse once to answer the request; lazy mode
+        // also keeps the text instead of the parsed rules
+        group.bench_function("miss_eager", |b| {
+            b.iter_batched(
+                || text.clone(),
+                |text| CachedRobots::from(text.parse(TARGET_URL)),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function("miss_lazy", |b| {
+            b.iter_batched(
+                || text.clone(),
+                |text| {
+                    let data = text.parse(TARGET_URL);
+                    (data, CachedRobots::raw(text, TARGET_URL))
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        // The first hit on a lazy entry pays for a second parse
+        group.bench_function("first_hit_lazy", |b| {
+            b.iter_batched(
+                || CachedRobots::raw(text.clone(), TARGET_URL),
+                |entry| entry.data().is_allowed(&agent, PATH),
+                BatchSize::LargeInput,
+            )
+        });
+        // After that, hits cost the same in both modes
+        let parsed = CachedRobots::from(text.parse(TARGET_URL));
+        group.bench_function("hit", |b| {
+            b.iter(|| black_box(&parsed).data().is_allowed(&agent, PATH))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_lazy_parse);
+criterion_main!(benches);
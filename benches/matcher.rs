@@ -0,0 +1,66 @@
+mod common;
+
+use std::hint::black_box;
+
+use common::{PATHS, Synthetic, user_agent};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use robots_server::parser::parse;
+
+fn bench_path_matching(c: &mut Criterion) {
+    let mut group = c.benchmark_group("is_allowed");
+    for (name, shape) in [
+        (
+            "small_literal",
+            Synthetic::new(2_000).rules_per_group(20).wildcard_every(0),
+        ),
+        (
+            "small_wildcard",
+            Synthetic::new(2_000).rules_per_group(20).wildcard_every(2),
+        ),
+        (
+            "large_literal",
+            Synthetic::new(200_000)
+                .rules_per_group(4_000)
+                .wildcard_every(0),
+        ),
+        (
+            "large_wildcard",
+            Synthetic::new(200_000)
+                .rules_per_group(4_000)
+                .wildcard_every(2),
+        ),
+    ] {
+        let data = parse(&shape.generate());
+        let agent = user_agent(0);
+        for (label, path) in PATHS {
+            group.bench_with_input(BenchmarkId::new(name, label), path, |b, path| {
+                b.iter(|| black_box(&data).is_allowed(black_box(&agent), black_box(path)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_group_selection(c: &mut Criterion) {
+    // Many small groups, so the cost is in picking groups rather than rules
+    let data = parse(&Synthetic::new(200_000).rules_per_group(5).generate());
+    let mut group = c.benchmark_group("group_selection");
+    for (name, agent) in [
+        ("first_group", user_agent(0)),
+        ("late_group", user_agent(2_000)),
+        (
+            "product_token",
+            format!("Mozilla/5.0 (compatible; {}/2.1)", user_agent(10)),
+        ),
+        ("wildcard_fallback", "UnknownBot/1.0".to_string()),
+    ] {
+        // crawl_delay only selects groups, so it isolates that step
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(&data).crawl_delay(black_box(&agent)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_path_matching, bench_group_selection);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+mod common;
+
+use std::hint::black_box;
+
+use common::Synthetic;
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use robots_server::parser::parse;
+use robots_server::robots_data::RobotsData;
+use robotstxt_rs::RobotsTxt;
+
+fn bench_parse(c: &mut Criterion) {
+    let body = Synthetic::new(500 * 1024).generate();
+    let mut group = c.benchmark_group("parse_500kb");
+    group.throughput(Throughput::Bytes(body.len() as u64));
+    group.bench_function("parser", |b| b.iter(|| parse(black_box(&body))));
+    group.bench_function("robotstxt_conversion", |b| {
+        b.iter_batched(
+            || RobotsTxt::parse(&body),
+            RobotsData::from,
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
@@ -1,6 +1,36 @@
+use std::path::Path;
+use std::process::Command;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // robots/v1 is the canonical package; robots.proto is the deprecated
+    // unversioned service over the same messages
+    #[cfg(feature = "server")]
     tonic_prost_build::configure()
         .out_dir("src/generated")
-        .compile_protos(&["proto/robots.proto"], &["proto"])?;
+        .file_descriptor_set_path(
+            std::path::PathBuf::from(std::env::var("OUT_DIR")?).join("robots_descriptor.bin"),
+        )
+        .compile_protos(
+            &["proto/robots/v1/robots.proto", "proto/robots.proto"],
+            &["proto"],
+        )?;
+
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=ROBOTS_GIT_HASH={git_hash}");
+    // HEAD moves on checkout, and the branch it names on each commit, as a
+    // file under refs/heads or a line in packed-refs. Cargo reruns on every
+    // build for a path that does not exist, so only those present are watched
+    for path in [".git/HEAD", ".git/refs/heads", ".git/packed-refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,78 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use tonic::transport::server::TcpConnectInfo;
+use tower::{Layer, Service};
+use tracing::info;
+
+/// Tracing target of access log events, for routing them to a log sink.
+pub const ACCESS_TARGET: &str = "access";
+
+/// Tower layer that emits one `access` event per gRPC call with the peer,
+/// method, status, and latency.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AccessLogLayer;
+
+impl<S> Layer<S> for AccessLogLayer {
+    type Service = AccessLog<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AccessLog { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct AccessLog<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for AccessLog<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let started = Instant::now();
+        let method = request.uri().path().to_string();
+        let peer = request
+            .extensions()
+            .get::<TcpConnectInfo>()
+            .and_then(TcpConnectInfo::remote_addr)
+            .map(|addr| addr.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let result = future.await;
+            // Errors are sent trailers-only, so a missing header means OK or
+            // a status that arrives later in the trailers
+            let grpc_status = match &result {
+                Ok(response) => response
+                    .headers()
+                    .get("grpc-status")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0),
+                Err(_) => -1,
+            };
+            info!(
+                target: ACCESS_TARGET,
+                peer,
+                method,
+                grpc_status,
+                duration_ms = started.elapsed().as_secs_f64() * 1000.0,
+                "request"
+            );
+            result
+        })
+    }
+}
@@ -0,0 +1,321 @@
+use std::io::Write;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{Map, Value, json};
+
+use crate::client::{ClientError, RobotsClient};
+use crate::service::robots::v1::{AccessResult, GetServerInfoResponse};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "robots-admin",
+    version,
+    about = "Operator commands for a running robots-server"
+)]
+pub struct AdminCli {
+    /// Server endpoint
+    #[arg(long, env = "ROBOTS_ENDPOINT", default_value = "http://[::1]:50051")]
+    pub endpoint: String,
+    /// Deadline for each call in seconds
+    #[arg(long, default_value_t = 10.0)]
+    pub timeout: f64,
+    /// API key sent as x-api-key metadata
+    #[arg(long, env = "ROBOTS_API_KEY")]
+    pub api_key: Option<String>,
+    /// How results are printed
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+    #[command(subcommand)]
+    pub command: AdminCommand,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns for reading
+    #[default]
+    Table,
+    /// Pretty-printed JSON for scripts
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AdminCommand {
+    /// Evict the cached robots.txt for each URL's host
+    Invalidate {
+        #[arg(required = true)]
+        urls: Vec<String>,
+    },
+    /// Refetch the robots.txt of each URL's host
+    Refresh {
+        #[arg(required = true)]
+        urls: Vec<String>,
+        /// Fetch even within the minimum refresh interval (needs an admin API key)
+        #[arg(long)]
+        force: bool,
+    },
+    /// Drop every robots.txt the server has cached
+    ClearCache,
+    /// List the robots.txt the server has cached, with their age in seconds
+    ListCache,
+    /// Show the server's version, limits, cache and fetch counters
+    Stats,
+    /// Warm the cache for each URL's host in the background
+    Prefetch {
+        #[arg(required = true)]
+        urls: Vec<String>,
+    },
+    /// Show the progress of a prefetch job
+    PrefetchStatus { job_id: u64 },
+}
+
+/// A command's result. A record prints as one `field value` line per field,
+/// or as a JSON object; rows print under a header line, or as a JSON array
+/// of objects.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Output {
+    Record(Vec<(&'static str, Value)>),
+    Rows {
+        columns: Vec<&'static str>,
+        rows: Vec<Vec<Value>>,
+    },
+}
+
+impl Output {
+    pub fn to_json(&self) -> Value {
+        match self {
+            Output::Record(fields) => object(fields.iter().map(|(name, value)| (name, value))),
+            Output::Rows { columns, rows } => Value::Array(
+                rows.iter()
+                    .map(|row| object(columns.iter().zip(row)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The output as aligned plain-text columns.
+    pub fn to_table(&self) -> String {
+        let (header, rows): (Option<Vec<String>>, Vec<Vec<String>>) = match self {
+            Output::Record(fields) => (
+                None,
+                fields
+                    .iter()
+                    .map(|(name, value)| vec![name.to_string(), cell(value)])
+                    .collect(),
+            ),
+            Output::Rows { columns, rows } => (
+                Some(columns.iter().map(|c| c.to_uppercase()).collect()),
+                rows.iter()
+                    .map(|row| row.iter().map(cell).collect())
+                    .collect(),
+            ),
+        };
+        let lines: Vec<&Vec<String>> = header.iter().chain(&rows).collect();
+        let widths = lines
+            .iter()
+            .fold(Vec::new(), |mut widths: Vec<usize>, line| {
+                for (i, value) in line.iter().enumerate() {
+                    match widths.get_mut(i) {
+                        Some(width) => *width = (*width).max(value.len()),
+                        None => widths.push(value.len()),
+                    }
+                }
+                widths
+            });
+        let mut table = String::new();
+        for line in lines {
+            let cells = line
+                .iter()
+                .zip(&widths)
+                .map(|(value, width)| format!("{value:<width$}"))
+                .collect::<Vec<_>>();
+            table.push_str(cells.join("  ").trim_end());
+            table.push('\n');
+        }
+        table
+    }
+
+    pub fn write(&self, format: OutputFormat, out: &mut impl Write) -> std::io::Result<()> {
+        match format {
+            OutputFormat::Table => out.write_all(self.to_table().as_bytes()),
+            OutputFormat::Json => writeln!(
+                out,
+                "{}",
+                serde_json::to_string_pretty(&self.to_json())
+                    .expect("JSON values always serialize")
+            ),
+        }
+    }
+}
+
+fn object<'a>(fields: impl Iterator<Item = (&'a &'static str, &'a Value)>) -> Value {
+    Value::Object(
+        fields
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+/// A JSON value as a table cell, without quotes around strings.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "-".to_string(),
+        Value::Array(values) => values.iter().map(cell).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
+
+/// Connect to the server as configured by `args`.
+pub async fn connect(args: &AdminCli) -> Result<RobotsClient, ClientError> {
+    let mut client = RobotsClient::connect(args.endpoint.clone())
+        .await?
+        .with_deadline(Duration::from_secs_f64(args.timeout));
+    if let Some(api_key) = &args.api_key {
+        client = client.with_api_key(api_key)?;
+    }
+    Ok(client)
+}
+
+/// Connect to the server and run one command.
+pub async fn run(args: &AdminCli) -> Result<Output, ClientError> {
+    let client = connect(args).await?;
+    match &args.command {
+        AdminCommand::Invalidate { urls } => invalidate(&client, urls).await,
+        AdminCommand::Refresh { urls, force } => refresh(&client, urls, *force).await,
+        AdminCommand::ClearCache => clear_cache(&client).await,
+        AdminCommand::ListCache => list_cache(&client).await,
+        AdminCommand::Stats => stats(&client).await,
+        AdminCommand::Prefetch { urls } => prefetch(&client, urls).await,
+        AdminCommand::PrefetchStatus { job_id } => prefetch_status(&client, *job_id).await,
+    }
+}
+
+/// Invalidate each URL in turn, stopping at the first that fails.
+pub async fn invalidate(client: &RobotsClient, urls: &[String]) -> Result<Output, ClientError> {
+    let mut rows = Vec::with_capacity(urls.len());
+    for url in urls {
+        let response = client.invalidate_url(url).await?;
+        rows.push(vec![
+            json!(response.robots_txt_url),
+            json!(response.existed),
+        ]);
+    }
+    Ok(Output::Rows {
+        columns: vec!["robots_txt_url", "existed"],
+        rows,
+    })
+}
+
+/// Refresh each URL in turn, stopping at the first that fails.
+pub async fn refresh(
+    client: &RobotsClient,
+    urls: &[String],
+    force: bool,
+) -> Result<Output, ClientError> {
+    let mut rows = Vec::with_capacity(urls.len());
+    for url in urls {
+        let response = client.refresh_robots(url, force).await?;
+        rows.push(vec![
+            json!(response.robots_txt_url),
+            json!(response.http_status_code),
+            json!(response.throttled),
+        ]);
+    }
+    Ok(Output::Rows {
+        columns: vec!["robots_txt_url", "http_status_code", "throttled"],
+        rows,
+    })
+}
+
+pub async fn clear_cache(client: &RobotsClient) -> Result<Output, ClientError> {
+    let response = client.clear_cache().await?;
+    Ok(Output::Record(vec![(
+        "entries_dropped",
+        json!(response.entries_dropped),
+    )]))
+}
+
+/// Every cached entry, fetched a page at a time.
+pub async fn list_cache(client: &RobotsClient) -> Result<Output, ClientError> {
+    let mut rows = Vec::new();
+    let mut page_token = String::new();
+    loop {
+        let response = client.list_cached_entries(0, &page_token).await?;
+        rows.extend(response.entries.into_iter().map(|entry| {
+            let access_result = AccessResult::try_from(entry.access_result)
+                .map_or("UNKNOWN", |result| result.as_str_name());
+            vec![
+                json!(entry.robots_txt_url),
+                json!(access_result),
+                json!(entry.http_status_code),
+                json!(entry.age_seconds),
+                json!(entry.truncated),
+            ]
+        }));
+        if response.next_page_token.is_empty() {
+            break;
+        }
+        page_token = response.next_page_token;
+    }
+    Ok(Output::Rows {
+        columns: vec![
+            "robots_txt_url",
+            "access_result",
+            "http_status_code",
+            "age_seconds",
+            "truncated",
+        ],
+        rows,
+    })
+}
+
+pub async fn stats(client: &RobotsClient) -> Result<Output, ClientError> {
+    Ok(stats_output(client.get_server_info().await?))
+}
+
+pub async fn prefetch(client: &RobotsClient, urls: &[String]) -> Result<Output, ClientError> {
+    let response = client.prefetch_hosts(urls).await?;
+    Ok(Output::Record(vec![
+        ("job_id", json!(response.job_id)),
+        ("accepted", json!(response.accepted)),
+        ("rejected", json!(response.rejected)),
+        ("skipped", json!(response.skipped)),
+    ]))
+}
+
+pub async fn prefetch_status(client: &RobotsClient, job_id: u64) -> Result<Output, ClientError> {
+    let response = client.get_prefetch_status(job_id).await?;
+    Ok(Output::Record(vec![
+        ("job_id", json!(response.job_id)),
+        ("done", json!(response.done)),
+        ("failed", json!(response.failed)),
+        ("remaining", json!(response.remaining)),
+    ]))
+}
+
+fn stats_output(info: GetServerInfoResponse) -> Output {
+    let limits = info.limits.unwrap_or_default();
+    let cache = info.cache.unwrap_or_default();
+    Output::Record(vec![
+        ("version", json!(info.version)),
+        ("git_hash", json!(info.git_hash)),
+        ("uptime_seconds", json!(info.uptime_seconds)),
+        ("features", json!(info.features)),
+        ("cache_entries", json!(cache.entries)),
+        ("cache_hits", json!(cache.hits)),
+        ("cache_misses", json!(cache.misses)),
+        ("cache_hit_rate", json!(cache.hit_rate)),
+        ("in_flight_fetches", json!(info.in_flight_fetches)),
+        ("fetches_total", json!(info.fetches_total)),
+        ("fetch_timeout_seconds", json!(limits.fetch_timeout_seconds)),
+        ("cache_ttl_seconds", json!(limits.cache_ttl_seconds)),
+        (
+            "header_cache_ttl_seconds",
+            json!(limits.header_cache_ttl_seconds),
+        ),
+        ("max_robots_txt_bytes", json!(limits.max_robots_txt_bytes)),
+        ("host_rate_per_second", json!(limits.host_rate_per_second)),
+        ("host_rate_burst", json!(limits.host_rate_burst)),
+    ])
+}
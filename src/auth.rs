@@ -0,0 +1,50 @@
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Status, service::Interceptor};
+use tracing::debug;
+
+use crate::config::ConfigHandle;
+
+/// Checks the `x-api-key` (or `authorization: Bearer`) metadata against the
+/// keys in the live configuration, `admin_api_keys` included. Every request
+/// is allowed when no keys are configured.
+#[derive(Clone)]
+pub struct ApiKeyAuth {
+    config: ConfigHandle,
+}
+
+impl ApiKeyAuth {
+    pub fn new(config: ConfigHandle) -> Self {
+        Self { config }
+    }
+}
+
+impl Interceptor for ApiKeyAuth {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let config = self.config.get();
+        if !config.requires_api_key() {
+            return Ok(request);
+        }
+        match api_key(request.metadata()) {
+            Some(key) if config.accepts_api_key(key) => Ok(request),
+            Some(_) => {
+                debug!("Rejected request with unknown API key");
+                Err(Status::unauthenticated("Invalid API key"))
+            }
+            None => Err(Status::unauthenticated("Missing API key")),
+        }
+    }
+}
+
+/// The API key a call carries as `x-api-key` or `authorization: Bearer`
+/// metadata.
+pub fn api_key(metadata: &MetadataMap) -> Option<&str> {
+    metadata
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            metadata
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+        })
+}
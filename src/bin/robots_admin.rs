@@ -0,0 +1,29 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use robots_server::admin_cli::{AdminCli, run};
+use robots_server::cli::EXIT_ERROR;
+use robots_server::client::ClientError;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = AdminCli::parse();
+    let output = match run(&args).await {
+        Ok(output) => output,
+        Err(ClientError::Status(status)) => {
+            eprintln!("error: {:?}: {}", status.code(), status.message());
+            return ExitCode::from(EXIT_ERROR);
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    match output.write(args.output, &mut std::io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use robots_server::client::ClientError;
+use robots_server::client_cli::{ClientCli, run};
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = ClientCli::parse();
+    match run(&args).await {
+        Ok(value) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&value).expect("JSON values always serialize")
+            );
+            ExitCode::SUCCESS
+        }
+        Err(ClientError::Status(status)) => {
+            eprintln!("error: {:?}: {}", status.code(), status.message());
+            if !status.details().is_empty() {
+                eprintln!("details: {}", String::from_utf8_lossy(status.details()));
+            }
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,11 +1,23 @@
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
-use std::time::Duration;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use moka::Expiry;
 use moka::future::Cache as MokaCacheImpl;
+use moka::notification::RemovalCause;
 use thiserror::Error;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
+
+use crate::cached_robots::CachedRobots;
+use crate::clock::{self, Clock};
+use crate::config::{ConfigHandle, ConfiguredTtl, RuntimeConfig};
+use crate::log_sampling::LogSampler;
+use crate::robots_data::RobotsData;
 
 #[async_trait]
 pub trait Cache<
@@ -16,77 +28,860 @@ pub trait Cache<
     async fn get(&self, key: &K) -> CacheResult<Option<V>>;
     async fn set(&self, key: K, value: V) -> CacheResult<()>;
     async fn delete(&self, key: &K) -> CacheResult<bool>;
+
+    /// [`get`](Self::get), without counting toward [`stats`](Self::stats),
+    /// for lookups that are not of robots.txt, such as of supplemental rules
+    /// or a readiness probe. Backends that keep no statistics look up as
+    /// `get` does.
+    async fn get_uncounted(&self, key: &K) -> CacheResult<Option<V>> {
+        self.get(key).await
+    }
+
+    /// [`get`](Self::get), with the time the entry has left before it
+    /// expires: `None` for one that never does, or when the backend cannot
+    /// tell.
+    async fn get_with_ttl(&self, key: &K) -> CacheResult<Option<(V, Option<Duration>)>> {
+        Ok(self.get(key).await?.map(|value| (value, None)))
+    }
+
+    /// Store `value` to expire after `ttl` rather than the cache's own TTL.
+    /// Backends that cannot expire entries one at a time store it as
+    /// [`set`](Self::set) does.
+    async fn set_with_ttl(&self, key: K, value: V, ttl: Duration) -> CacheResult<()> {
+        let _ = ttl;
+        self.set(key, value).await
+    }
+
+    /// Store `value` to be kept until it is deleted or replaced: it does not
+    /// expire, is not evicted to make room, and survives
+    /// [`clear`](Self::clear).
+    async fn pin(&self, key: K, value: V) -> CacheResult<()> {
+        let _ = (key, value);
+        Err(CacheError::Unsupported("pin entries"))
+    }
+
+    /// Approximate number of entries, when the backend can count them.
+    fn entry_count(&self) -> Option<u64> {
+        None
+    }
+
+    /// Drop every entry but [pinned](Self::pin) ones, returning how many
+    /// were dropped when the backend can count them.
+    async fn clear(&self) -> CacheResult<Option<u64>> {
+        Err(CacheError::Unsupported("clear"))
+    }
+
+    /// Every entry with the time left before it expires, `None` for one
+    /// that never does, when the backend can list what it holds.
+    async fn entries(&self) -> CacheResult<Vec<(K, V, Option<Duration>)>> {
+        Err(CacheError::Unsupported("list entries"))
+    }
+
+    /// How often lookups have hit since the cache was created, and what it
+    /// holds now, when the backend keeps count.
+    async fn stats(&self) -> Option<CacheStats> {
+        None
+    }
+
+    /// Flush buffered writes and release connections before the process
+    /// exits. Called once, after the server has stopped serving.
+    async fn close(&self) -> CacheResult<()> {
+        Ok(())
+    }
 }
 
 pub type CacheResult<T> = Result<T, CacheError>;
 
+/// Counts of a cache's operations since it was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups that found an entry.
+    pub hits: u64,
+    /// Lookups that found none.
+    pub misses: u64,
+    /// Values stored, including those replacing an entry.
+    pub insertions: u64,
+    /// Entries dropped because they expired or to make room, but not those
+    /// deleted or replaced.
+    pub evictions: u64,
+    pub entry_count: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    insertions: AtomicU64,
+    evictions: AtomicU64,
+}
+
 #[derive(Debug, Error)]
 pub enum CacheError {
     #[error("Cache backend unavailable")]
     Unavailable,
     #[error("Cache write failed: {0}")]
     WriteFailed(String),
+    #[error("Cache backend cannot {0}")]
+    Unsupported(&'static str),
+}
+
+/// Values a [`MokaCache`] can hold within a byte budget, set with
+/// [`MokaCacheBuilder::max_bytes`].
+pub trait EstimatedSize {
+    /// Rough bytes the value holds, when it is stored.
+    fn estimated_size(&self) -> usize;
+}
+
+impl EstimatedSize for String {
+    fn estimated_size(&self) -> usize {
+        size_of::<Self>() + self.len()
+    }
+}
+
+impl EstimatedSize for RobotsData {
+    fn estimated_size(&self) -> usize {
+        RobotsData::estimated_size(self)
+    }
+}
+
+impl EstimatedSize for CachedRobots {
+    fn estimated_size(&self) -> usize {
+        CachedRobots::estimated_size(self)
+    }
 }
 
 pub struct MokaCache<
     K: Hash + Eq + Clone + Debug + Send + Sync + 'static,
     V: Clone + Send + Sync + 'static,
 > {
-    cache: MokaCacheImpl<K, V>,
+    cache: MokaCacheImpl<K, Entry<V>>,
+    counters: Arc<Counters>,
+    /// Shared with the cache's expiry, to tell how long entries have left.
+    default_ttl: Arc<DefaultTtl>,
+    /// Tells entries' age, for lookups to ignore those past their TTL.
+    clock: Arc<dyn Clock>,
+    /// Entries stored with [`Cache::pin`], kept apart so that moka neither
+    /// expires nor evicts them.
+    pinned: Arc<RwLock<HashMap<K, V>>>,
+}
+
+/// A cached value, the TTL it was stored with if it has its own, and when
+/// it was stored.
+#[derive(Clone)]
+struct Entry<V> {
+    value: V,
+    ttl: Option<Duration>,
+    written_at: Instant,
+}
+
+/// How much a [`MokaCache`] holds before it evicts.
+enum Capacity<V> {
+    Entries(u64),
+    /// A byte budget, with the size of each value.
+    Bytes(u64, fn(&V) -> usize),
+}
+
+/// The TTL of entries stored without their own.
+enum DefaultTtl {
+    Fixed(Duration),
+    Configured(ConfiguredTtl),
+}
+
+impl DefaultTtl {
+    fn ttl<K, V>(&self, key: &K, value: &V, written_at: Instant) -> Option<Duration> {
+        match self {
+            Self::Fixed(ttl) => Some(*ttl),
+            Self::Configured(ttl) => {
+                Expiry::<K, V>::expire_after_create(ttl, key, value, written_at)
+            }
+        }
+    }
+}
+
+/// Expires entries stored with their own TTL after it, and the rest after
+/// `default`.
+struct EntryExpiry {
+    default: Arc<DefaultTtl>,
+}
+
+impl<K, V> Expiry<K, Entry<V>> for EntryExpiry {
+    fn expire_after_create(
+        &self,
+        key: &K,
+        entry: &Entry<V>,
+        created_at: Instant,
+    ) -> Option<Duration> {
+        entry
+            .ttl
+            .or_else(|| self.default.ttl(key, &entry.value, created_at))
+    }
+
+    fn expire_after_update(
+        &self,
+        key: &K,
+        entry: &Entry<V>,
+        updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        entry
+            .ttl
+            .or_else(|| self.default.ttl(key, &entry.value, updated_at))
+    }
 }
 
 impl<K: Hash + Eq + Clone + Debug + Send + Sync + 'static, V: Clone + Send + Sync + 'static>
     MokaCache<K, V>
 {
+    /// An unbounded cache whose entries expire 24 hours after they are
+    /// written.
     pub fn new() -> Self {
         debug!("Creating new Moka cache with 24h TTL");
-        Self {
-            cache: MokaCacheImpl::builder()
-                .time_to_live(Duration::from_hours(24))
-                .build(),
+        Self::builder().build()
+    }
+
+    /// A cache whose entries expire `ttl` after they are written.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self::builder().time_to_live(ttl).build()
+    }
+
+    /// A cache whose TTL follows `cache_ttl_secs` in the live configuration.
+    pub fn with_config(config: ConfigHandle) -> Self {
+        debug!("Creating new Moka cache with configured TTL");
+        Self::builder().config(config).build()
+    }
+
+    /// Start a cache with the settings of [`MokaCache::new`].
+    pub fn builder() -> MokaCacheBuilder<K, V> {
+        MokaCacheBuilder {
+            ttl: DefaultTtl::Fixed(Duration::from_hours(24)),
+            capacity: None,
+            initial_capacity: None,
+            clock: clock::system(),
+            _entries: PhantomData,
+        }
+    }
+
+    /// Carry out the evictions and expirations moka otherwise applies in
+    /// batches, so [`Cache::entry_count`] is exact.
+    pub async fn run_pending_tasks(&self) {
+        self.cache.run_pending_tasks().await;
+    }
+
+    /// Estimated bytes held, for a cache built with
+    /// [`max_bytes`](MokaCacheBuilder::max_bytes), or otherwise the number of
+    /// entries.
+    pub fn weighted_size(&self) -> u64 {
+        self.cache.weighted_size()
+    }
+
+    /// How long `entry` has left before it expires, `None` if it never does.
+    fn remaining(&self, key: &K, entry: &Entry<V>) -> Option<Duration> {
+        let ttl = entry
+            .ttl
+            .or_else(|| self.default_ttl.ttl(key, &entry.value, entry.written_at));
+        let age = self
+            .clock
+            .instant()
+            .saturating_duration_since(entry.written_at);
+        ttl.map(|ttl| ttl.saturating_sub(age))
+    }
+
+    /// The value under `key` and the time it has left, unless it has
+    /// expired by the clock. moka drops expired entries by real time, so
+    /// one may linger until then.
+    async fn live(&self, key: &K) -> Option<(V, Option<Duration>)> {
+        if let Some(value) = self.pinned().get(key) {
+            return Some((value.clone(), None));
+        }
+        let entry = self.cache.get(key).await?;
+        let remaining = self.remaining(key, &entry);
+        if remaining.is_some_and(|remaining| remaining.is_zero()) {
+            return None;
+        }
+        Some((entry.value, remaining))
+    }
+
+    fn pinned(&self) -> std::sync::RwLockReadGuard<'_, HashMap<K, V>> {
+        self.pinned.read().expect("pinned entries lock poisoned")
+    }
+
+    fn pinned_mut(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<K, V>> {
+        self.pinned.write().expect("pinned entries lock poisoned")
+    }
+
+    fn entry(&self, value: V, ttl: Option<Duration>) -> Entry<V> {
+        Entry {
+            value,
+            ttl,
+            written_at: self.clock.instant(),
+        }
+    }
+}
+
+/// Settings for a [`MokaCache`]. Start one with [`MokaCache::builder`].
+pub struct MokaCacheBuilder<K, V> {
+    ttl: DefaultTtl,
+    capacity: Option<Capacity<V>>,
+    initial_capacity: Option<usize>,
+    clock: Arc<dyn Clock>,
+    _entries: PhantomData<fn(K, V)>,
+}
+
+impl<K: Hash + Eq + Clone + Debug + Send + Sync + 'static, V: Clone + Send + Sync + 'static>
+    MokaCacheBuilder<K, V>
+{
+    /// Expire entries `ttl` after they are written, unless stored with a TTL
+    /// of their own. Replaces any earlier [`config`](Self::config).
+    pub fn time_to_live(mut self, ttl: Duration) -> Self {
+        self.ttl = DefaultTtl::Fixed(ttl);
+        self
+    }
+
+    /// Expire entries after `cache_ttl_secs` in the live configuration, as
+    /// it is when each is written. Replaces any earlier
+    /// [`time_to_live`](Self::time_to_live).
+    pub fn config(mut self, config: ConfigHandle) -> Self {
+        self.ttl = DefaultTtl::Configured(ConfiguredTtl::new(config, RuntimeConfig::cache_ttl));
+        self
+    }
+
+    /// Hold at most `entries` entries, evicting the least useful when full.
+    /// Unbounded without one. Replaces any earlier
+    /// [`max_bytes`](Self::max_bytes).
+    pub fn max_capacity(mut self, entries: u64) -> Self {
+        self.capacity = Some(Capacity::Entries(entries));
+        self
+    }
+
+    /// Room for `entries` entries before the cache has to grow.
+    pub fn initial_capacity(mut self, entries: usize) -> Self {
+        self.initial_capacity = Some(entries);
+        self
+    }
+
+    /// Age entries by `clock` rather than the system's, so that tests can
+    /// expire them by moving it forward.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn build(self) -> MokaCache<K, V> {
+        let default_ttl = Arc::new(self.ttl);
+        let mut builder = MokaCacheImpl::builder().expire_after(EntryExpiry {
+            default: default_ttl.clone(),
+        });
+        match self.capacity {
+            Some(Capacity::Entries(entries)) => builder = builder.max_capacity(entries),
+            Some(Capacity::Bytes(bytes, size)) => {
+                builder = builder
+                    .max_capacity(bytes)
+                    .weigher(move |_, entry: &Entry<V>| {
+                        u32::try_from(size(&entry.value)).unwrap_or(u32::MAX)
+                    });
+            }
+            None => {}
+        }
+        let counters = Arc::new(Counters::default());
+        let evictions = counters.clone();
+        builder = builder.eviction_listener(move |_, _, cause: RemovalCause| {
+            if cause.was_evicted() {
+                evictions.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+        if let Some(entries) = self.initial_capacity {
+            builder = builder.initial_capacity(entries);
+        }
+        MokaCache {
+            cache: builder.build(),
+            counters,
+            default_ttl,
+            clock: self.clock,
+            pinned: Arc::default(),
         }
     }
 }
 
+impl<
+    K: Hash + Eq + Clone + Debug + Send + Sync + 'static,
+    V: EstimatedSize + Clone + Send + Sync + 'static,
+> MokaCacheBuilder<K, V>
+{
+    /// Hold entries of about `bytes` in total, by their
+    /// [`EstimatedSize`] when stored, evicting the least useful when full.
+    /// A value larger than the whole budget is not kept. Replaces any earlier
+    /// [`max_capacity`](Self::max_capacity).
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.capacity = Some(Capacity::Bytes(bytes, V::estimated_size));
+        self
+    }
+}
+
+// Cache operations run several times per request, so their debug lines are
+// sampled
+static CACHE_GET: LogSampler = LogSampler::new("cache.get");
+static CACHE_SET: LogSampler = LogSampler::new("cache.set");
+static CACHE_DELETE: LogSampler = LogSampler::new("cache.delete");
+
 #[async_trait]
 impl<K: Hash + Eq + Clone + Debug + Send + Sync + 'static, V: Clone + Send + Sync + 'static>
     Cache<K, V> for MokaCache<K, V>
 {
     #[instrument(skip(self, key), fields(key = ?key))]
     async fn get(&self, key: &K) -> CacheResult<Option<V>> {
-        debug!("Checking cache for key");
-        Ok(match self.cache.get(key).await {
-            Some(value) => {
-                debug!("Cache hit");
-                Some(value)
-            }
-            None => {
-                debug!("Cache miss");
-                None
-            }
-        })
+        let value = self.live(key).await.map(|(value, _)| value);
+        let counter = match value {
+            Some(_) => &self.counters.hits,
+            None => &self.counters.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if CACHE_GET.sample() {
+            debug!(hit = value.is_some(), "Cache lookup");
+        }
+        Ok(value)
+    }
+
+    #[instrument(skip(self, key), fields(key = ?key))]
+    async fn get_with_ttl(&self, key: &K) -> CacheResult<Option<(V, Option<Duration>)>> {
+        let entry = self.live(key).await;
+        let counter = match entry {
+            Some(_) => &self.counters.hits,
+            None => &self.counters.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if CACHE_GET.sample() {
+            debug!(hit = entry.is_some(), "Cache lookup");
+        }
+        Ok(entry)
+    }
+
+    async fn get_uncounted(&self, key: &K) -> CacheResult<Option<V>> {
+        Ok(self.live(key).await.map(|(value, _)| value))
     }
 
     #[instrument(skip(self, key, value), fields(key = ?key))]
     async fn set(&self, key: K, value: V) -> CacheResult<()> {
-        debug!("Inserting value into cache");
-        self.cache.insert(key, value).await;
-        debug!("Value inserted");
+        self.pinned_mut().remove(&key);
+        self.cache.insert(key, self.entry(value, None)).await;
+        self.counters.insertions.fetch_add(1, Ordering::Relaxed);
+        if CACHE_SET.sample() {
+            debug!("Value inserted");
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self, key, value), fields(key = ?key))]
+    async fn set_with_ttl(&self, key: K, value: V, ttl: Duration) -> CacheResult<()> {
+        self.pinned_mut().remove(&key);
+        self.cache.insert(key, self.entry(value, Some(ttl))).await;
+        self.counters.insertions.fetch_add(1, Ordering::Relaxed);
+        if CACHE_SET.sample() {
+            debug!(?ttl, "Value inserted");
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self, key, value), fields(key = ?key))]
+    async fn pin(&self, key: K, value: V) -> CacheResult<()> {
+        self.cache.remove(&key).await;
+        self.pinned_mut().insert(key, value);
+        self.counters.insertions.fetch_add(1, Ordering::Relaxed);
+        if CACHE_SET.sample() {
+            debug!("Value pinned");
+        }
         Ok(())
     }
 
     #[instrument(skip(self, key), fields(key = ?key))]
     async fn delete(&self, key: &K) -> CacheResult<bool> {
-        debug!("Removing key from cache");
-        Ok(match self.cache.remove(key).await {
-            Some(_) => {
-                debug!("Removed existing key");
-                true
-            }
-            None => {
-                debug!("Key not in cache");
-                false
+        let pinned = self.pinned_mut().remove(key).is_some();
+        let existed = self.cache.remove(key).await.is_some() || pinned;
+        if CACHE_DELETE.sample() {
+            debug!(existed, "Removed key from cache");
+        }
+        Ok(existed)
+    }
+
+    #[instrument(skip(self))]
+    async fn clear(&self) -> CacheResult<Option<u64>> {
+        self.cache.run_pending_tasks().await;
+        let dropped = self.cache.entry_count();
+        self.cache.invalidate_all();
+        self.cache.run_pending_tasks().await;
+        debug!(dropped, "Cleared cache");
+        Ok(Some(dropped))
+    }
+
+    fn entry_count(&self) -> Option<u64> {
+        Some(self.cache.entry_count() + self.pinned().len() as u64)
+    }
+
+    async fn entries(&self) -> CacheResult<Vec<(K, V, Option<Duration>)>> {
+        let mut entries = self
+            .pinned()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone(), None))
+            .collect::<Vec<_>>();
+        for (key, entry) in self.cache.iter() {
+            let remaining = self.remaining(&key, &entry);
+            if remaining.is_some_and(|remaining| remaining.is_zero()) {
+                continue;
             }
+            entries.push(((*key).clone(), entry.value, remaining));
+        }
+        Ok(entries)
+    }
+
+    /// Runs pending evictions and expirations first, so `entry_count` and
+    /// `evictions` are exact.
+    async fn stats(&self) -> Option<CacheStats> {
+        self.cache.run_pending_tasks().await;
+        let counters = &self.counters;
+        Some(CacheStats {
+            hits: counters.hits.load(Ordering::Relaxed),
+            misses: counters.misses.load(Ordering::Relaxed),
+            insertions: counters.insertions.load(Ordering::Relaxed),
+            evictions: counters.evictions.load(Ordering::Relaxed),
+            entry_count: self.cache.entry_count() + self.pinned().len() as u64,
         })
     }
 }
+
+/// A fast local cache (`l1`) in front of a shared one (`l2`), such as a
+/// [`MokaCache`] in front of Redis. Lookups try `l1` first and copy `l2`
+/// hits into it; writes and deletes go to both. When `l2` fails, the call
+/// goes on with `l1` alone and the failure is logged, so a shared store
+/// outage slows replicas down rather than failing their requests. Entries
+/// copied into `l1` keep the time they had left in `l2`, or take `l1`'s TTL
+/// when `l2` cannot tell it, so `l1`'s should be short enough for a change
+/// in `l2` to reach every replica in good time.
+pub struct TieredCache<L1, L2> {
+    l1: L1,
+    l2: L2,
+}
+
+impl<L1, L2> TieredCache<L1, L2> {
+    pub fn new(l1: L1, l2: L2) -> Self {
+        Self { l1, l2 }
+    }
+}
+
+/// `result` of an `l2` call, or `fallback` with a warning if it failed.
+fn degraded<T>(result: CacheResult<T>, fallback: T, operation: &str) -> T {
+    result.unwrap_or_else(|e| {
+        warn!(error = %e, operation, "Shared cache failed, using the local cache alone");
+        fallback
+    })
+}
+
+#[async_trait]
+impl<K, V, L1, L2> Cache<K, V> for TieredCache<L1, L2>
+where
+    K: Eq + Hash + Clone + Debug + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+    L1: Cache<K, V>,
+    L2: Cache<K, V>,
+{
+    async fn get(&self, key: &K) -> CacheResult<Option<V>> {
+        if let Some(value) = self.l1.get(key).await? {
+            return Ok(Some(value));
+        }
+        let Some((value, remaining)) = degraded(self.l2.get_with_ttl(key).await, None, "get")
+        else {
+            return Ok(None);
+        };
+        match remaining {
+            Some(ttl) => {
+                self.l1
+                    .set_with_ttl(key.clone(), value.clone(), ttl)
+                    .await?
+            }
+            None => self.l1.set(key.clone(), value.clone()).await?,
+        }
+        Ok(Some(value))
+    }
+
+    /// Tries `l1`, then `l2` without copying what it finds into `l1`.
+    async fn get_uncounted(&self, key: &K) -> CacheResult<Option<V>> {
+        if let Some(value) = self.l1.get_uncounted(key).await? {
+            return Ok(Some(value));
+        }
+        Ok(degraded(self.l2.get_uncounted(key).await, None, "get"))
+    }
+
+    async fn set(&self, key: K, value: V) -> CacheResult<()> {
+        self.l1.set(key.clone(), value.clone()).await?;
+        degraded(self.l2.set(key, value).await, (), "set");
+        Ok(())
+    }
+
+    async fn set_with_ttl(&self, key: K, value: V, ttl: Duration) -> CacheResult<()> {
+        self.l1
+            .set_with_ttl(key.clone(), value.clone(), ttl)
+            .await?;
+        degraded(self.l2.set_with_ttl(key, value, ttl).await, (), "set");
+        Ok(())
+    }
+
+    async fn pin(&self, key: K, value: V) -> CacheResult<()> {
+        self.l1.pin(key.clone(), value.clone()).await?;
+        degraded(self.l2.pin(key, value).await, (), "pin");
+        Ok(())
+    }
+
+    async fn delete(&self, key: &K) -> CacheResult<bool> {
+        let in_l1 = self.l1.delete(key).await?;
+        let in_l2 = degraded(self.l2.delete(key).await, false, "delete");
+        Ok(in_l1 || in_l2)
+    }
+
+    /// Counts the entries `l2` dropped, or `l1`'s when `l2` cannot count
+    /// them or failed.
+    async fn clear(&self) -> CacheResult<Option<u64>> {
+        let l1 = self.l1.clear().await?;
+        let l2 = degraded(self.l2.clear().await, None, "clear");
+        Ok(l2.or(l1))
+    }
+
+    /// `l2`'s count when it keeps one, as the entries all replicas share.
+    fn entry_count(&self) -> Option<u64> {
+        self.l2.entry_count().or_else(|| self.l1.entry_count())
+    }
+
+    /// `l1`'s entries: what this replica holds locally.
+    async fn entries(&self) -> CacheResult<Vec<(K, V, Option<Duration>)>> {
+        self.l1.entries().await
+    }
+
+    /// `l1`'s statistics: how often this replica found an entry locally.
+    async fn stats(&self) -> Option<CacheStats> {
+        self.l1.stats().await
+    }
+
+    async fn close(&self) -> CacheResult<()> {
+        let l1 = self.l1.close().await;
+        self.l2.close().await?;
+        l1
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use redis_cache::RedisCache;
+
+#[cfg(feature = "redis")]
+mod redis_cache {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use redis::{AsyncCommands, Client, aio::ConnectionManager};
+    use tracing::{debug, instrument, warn};
+
+    use super::{CACHE_DELETE, CACHE_GET, CACHE_SET, Cache, CacheError, CacheResult};
+    use crate::cached_robots::CachedRobots;
+    use crate::config::ConfigHandle;
+    use crate::stored_robots::StoredEntry;
+
+    const KEY_PREFIX: &str = "robots-server:robots:";
+    /// Keys asked for per SCAN while clearing.
+    const CLEAR_BATCH: usize = 1000;
+
+    /// robots.txt cached in Redis, so several server replicas fetch each
+    /// one once between them and an invalidation reaches all of them.
+    /// Entries are stored as JSON, parsed or, when not parsed yet, as the
+    /// fetched text, and expire `cache_ttl_secs` from the live configuration
+    /// after they are written unless given their own TTL. A failed Redis
+    /// call is [`CacheError::Unavailable`].
+    pub struct RedisCache {
+        connection: ConnectionManager,
+        config: ConfigHandle,
+    }
+
+    impl RedisCache {
+        /// Connect to the Redis server at `url`. The connection is shared
+        /// by every call and re-established when it drops.
+        pub async fn connect(url: &str, config: ConfigHandle) -> redis::RedisResult<Self> {
+            let client = Client::open(url)?;
+            let connection = client.get_connection_manager().await?;
+            Ok(Self { connection, config })
+        }
+
+        /// Store `value` under `key` to expire after `ttl`, or never
+        /// without one.
+        async fn store(
+            &self,
+            key: String,
+            value: CachedRobots,
+            ttl: Option<Duration>,
+        ) -> CacheResult<()> {
+            let json = serde_json::to_string(&StoredEntry::from(&value))
+                .map_err(|e| CacheError::WriteFailed(e.to_string()))?;
+            let key = format!("{KEY_PREFIX}{key}");
+            let mut connection = self.connection.clone();
+            match ttl {
+                // Redis rejects an expiry of zero
+                Some(ttl) => {
+                    connection
+                        .set_ex::<_, _, ()>(key, json, ttl.as_secs().max(1))
+                        .await
+                }
+                None => connection.set::<_, _, ()>(key, json).await,
+            }
+            .map_err(unavailable)
+        }
+    }
+
+    /// The entry stored as `json`. One written by an incompatible version is
+    /// a miss.
+    fn decode(json: String) -> Option<CachedRobots> {
+        match serde_json::from_str::<StoredEntry>(&json) {
+            Ok(stored) => Some(CachedRobots::from(stored)),
+            Err(e) => {
+                warn!(error = %e, "Ignoring unreadable Redis cache entry");
+                None
+            }
+        }
+    }
+
+    fn unavailable(e: redis::RedisError) -> CacheError {
+        warn!(error = %e, "Redis cache call failed");
+        CacheError::Unavailable
+    }
+
+    #[async_trait]
+    impl Cache<String, CachedRobots> for RedisCache {
+        #[instrument(skip(self, key), fields(key = ?key))]
+        async fn get(&self, key: &String) -> CacheResult<Option<CachedRobots>> {
+            let json: Option<String> = self
+                .connection
+                .clone()
+                .get(format!("{KEY_PREFIX}{key}"))
+                .await
+                .map_err(unavailable)?;
+            let value = json.and_then(decode);
+            if CACHE_GET.sample() {
+                debug!(hit = value.is_some(), "Cache lookup");
+            }
+            Ok(value)
+        }
+
+        /// Reads the entry and its PTTL together, so the time left belongs
+        /// to the value returned.
+        #[instrument(skip(self, key), fields(key = ?key))]
+        async fn get_with_ttl(
+            &self,
+            key: &String,
+        ) -> CacheResult<Option<(CachedRobots, Option<Duration>)>> {
+            let key = format!("{KEY_PREFIX}{key}");
+            let (json, pttl): (Option<String>, i64) = redis::pipe()
+                .atomic()
+                .get(&key)
+                .pttl(&key)
+                .query_async(&mut self.connection.clone())
+                .await
+                .map_err(unavailable)?;
+            let value = json.and_then(decode);
+            if CACHE_GET.sample() {
+                debug!(hit = value.is_some(), "Cache lookup");
+            }
+            // A negative PTTL means no expiry, or that the key has just gone
+            let remaining = u64::try_from(pttl).ok().map(Duration::from_millis);
+            Ok(value.map(|value| (value, remaining)))
+        }
+
+        #[instrument(skip(self, key, value), fields(key = ?key))]
+        async fn set(&self, key: String, value: CachedRobots) -> CacheResult<()> {
+            let ttl = self.config.get().cache_ttl();
+            self.store(key, value, Some(ttl)).await?;
+            if CACHE_SET.sample() {
+                debug!("Value inserted");
+            }
+            Ok(())
+        }
+
+        #[instrument(skip(self, key, value), fields(key = ?key))]
+        async fn set_with_ttl(
+            &self,
+            key: String,
+            value: CachedRobots,
+            ttl: Duration,
+        ) -> CacheResult<()> {
+            self.store(key, value, Some(ttl)).await?;
+            if CACHE_SET.sample() {
+                debug!(?ttl, "Value inserted");
+            }
+            Ok(())
+        }
+
+        /// Stores the entry without an expiry, which [`clear`](Self::clear)
+        /// leaves alone. Whether Redis evicts it under memory pressure is up
+        /// to its `maxmemory-policy`: a `volatile-*` one never does.
+        #[instrument(skip(self, key, value), fields(key = ?key))]
+        async fn pin(&self, key: String, value: CachedRobots) -> CacheResult<()> {
+            self.store(key, value, None).await?;
+            if CACHE_SET.sample() {
+                debug!("Value pinned");
+            }
+            Ok(())
+        }
+
+        #[instrument(skip(self, key), fields(key = ?key))]
+        async fn delete(&self, key: &String) -> CacheResult<bool> {
+            let removed: u64 = self
+                .connection
+                .clone()
+                .del(format!("{KEY_PREFIX}{key}"))
+                .await
+                .map_err(unavailable)?;
+            if CACHE_DELETE.sample() {
+                debug!(existed = removed > 0, "Removed key from cache");
+            }
+            Ok(removed > 0)
+        }
+
+        /// Deletes this server's keys that expire a batch at a time, so
+        /// entries written while it runs may survive.
+        #[instrument(skip(self))]
+        async fn clear(&self) -> CacheResult<Option<u64>> {
+            let mut connection = self.connection.clone();
+            let mut cursor = 0_u64;
+            let mut dropped = 0;
+            loop {
+                let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(format!("{KEY_PREFIX}*"))
+                    .arg("COUNT")
+                    .arg(CLEAR_BATCH)
+                    .query_async(&mut connection)
+                    .await
+                    .map_err(unavailable)?;
+                if !keys.is_empty() {
+                    let mut pttls = redis::pipe();
+                    for key in &keys {
+                        pttls.pttl(key);
+                    }
+                    let pttls: Vec<i64> = pttls
+                        .query_async(&mut connection)
+                        .await
+                        .map_err(unavailable)?;
+                    // A PTTL of -1 is a pinned key, kept
+                    let expiring = keys
+                        .into_iter()
+                        .zip(pttls)
+                        .filter_map(|(key, pttl)| (pttl != -1).then_some(key))
+                        .collect::<Vec<_>>();
+                    if !expiring.is_empty() {
+                        let removed: u64 = connection.del(&expiring).await.map_err(unavailable)?;
+                        dropped += removed;
+                    }
+                }
+                if next == 0 {
+                    break;
+                }
+                cursor = next;
+            }
+            debug!(dropped, "Cleared cache");
+            Ok(Some(dropped))
+        }
+    }
+}
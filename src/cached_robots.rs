@@ -0,0 +1,191 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use tracing::debug;
+
+use crate::fetcher::RobotsText;
+use crate::robots_data::RobotsData;
+
+/// Raw bodies at least this long are compressed when the `compression`
+/// feature is enabled.
+pub const COMPRESSION_THRESHOLD: usize = 4 * 1024;
+
+/// A robots.txt as held in the cache: either parsed when it was stored, or
+/// kept as the fetched text and parsed the first time it is needed. Cloning
+/// shares the entry, so a raw entry is parsed at most once however many
+/// requests use it.
+#[derive(Clone, Debug)]
+pub enum CachedRobots {
+    Parsed(Arc<RobotsData>),
+    Raw(Arc<RawRobots>),
+}
+
+impl CachedRobots {
+    /// Keep `text`, fetched for `target_url`, unparsed until first use. With
+    /// the `compression` feature a long body is held compressed.
+    pub fn raw(mut text: RobotsText, target_url: impl Into<String>) -> Self {
+        let body = Body::new(std::mem::take(&mut text.body));
+        Self::Raw(Arc::new(RawRobots {
+            target_url: target_url.into(),
+            text: Mutex::new(Some((text, body))),
+            parsed: OnceLock::new(),
+        }))
+    }
+
+    /// The parsed data, parsing a raw entry if this is its first use.
+    pub fn data(&self) -> Arc<RobotsData> {
+        match self {
+            Self::Parsed(data) => data.clone(),
+            Self::Raw(raw) => raw.data(),
+        }
+    }
+
+    /// Whether the rules have been parsed yet.
+    pub fn is_parsed(&self) -> bool {
+        match self {
+            Self::Parsed(_) => true,
+            Self::Raw(raw) => raw.parsed.get().is_some(),
+        }
+    }
+
+    /// The fetched text of an entry that has not been parsed yet.
+    pub fn raw_text(&self) -> Option<RobotsText> {
+        let Self::Raw(raw) = self else {
+            return None;
+        };
+        let text = raw.text.lock().unwrap_or_else(|e| e.into_inner());
+        text.as_ref().map(|(text, body)| RobotsText {
+            body: body.decode(),
+            ..text.clone()
+        })
+    }
+
+    /// The target URL a raw entry is parsed for, whether or not it has been
+    /// yet.
+    pub fn raw_target_url(&self) -> Option<&str> {
+        match self {
+            Self::Parsed(_) => None,
+            Self::Raw(raw) => Some(&raw.target_url),
+        }
+    }
+
+    /// Bytes held for the body of an entry that has not been parsed yet,
+    /// after any compression.
+    pub fn raw_size_bytes(&self) -> Option<usize> {
+        let Self::Raw(raw) = self else {
+            return None;
+        };
+        let text = raw.text.lock().unwrap_or_else(|e| e.into_inner());
+        text.as_ref().map(|(_, body)| body.len())
+    }
+
+    /// Rough bytes held by the entry as it is now: the parsed data, or for
+    /// an entry not yet parsed, its text.
+    pub fn estimated_size(&self) -> usize {
+        match self {
+            Self::Parsed(data) => data.estimated_size(),
+            Self::Raw(raw) => {
+                let text = raw.text.lock().unwrap_or_else(|e| e.into_inner());
+                let text = text.as_ref().map_or(0, |(text, body)| {
+                    let headers: usize = text
+                        .upstream_headers
+                        .iter()
+                        .map(|(name, value)| name.len() + value.len())
+                        .sum();
+                    text.robots_txt_url.len() + text.http_version.len() + headers + body.len()
+                });
+                let parsed = raw.parsed.get().map_or(0, |data| data.estimated_size());
+                size_of::<RawRobots>() + raw.target_url.len() + text + parsed
+            }
+        }
+    }
+}
+
+impl From<Arc<RobotsData>> for CachedRobots {
+    fn from(value: Arc<RobotsData>) -> Self {
+        Self::Parsed(value)
+    }
+}
+
+impl From<RobotsData> for CachedRobots {
+    fn from(value: RobotsData) -> Self {
+        Self::Parsed(Arc::new(value))
+    }
+}
+
+/// A fetched robots.txt waiting to be parsed. The text is dropped once it
+/// has been parsed, so a used entry holds only the parsed form.
+#[derive(Debug)]
+pub struct RawRobots {
+    target_url: String,
+    /// The text with its body held apart, in `Body`.
+    text: Mutex<Option<(RobotsText, Body)>>,
+    parsed: OnceLock<Arc<RobotsData>>,
+}
+
+impl RawRobots {
+    fn data(&self) -> Arc<RobotsData> {
+        self.parsed
+            .get_or_init(|| {
+                // OnceLock runs this once; concurrent callers wait for it
+                let (mut text, body) = self
+                    .text
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .take()
+                    .expect("raw robots.txt is parsed only once");
+                text.body = body.decode();
+                debug!(
+                    body_len = text.body.len(),
+                    "Parsing cached robots.txt on first use"
+                );
+                Arc::new(text.parse(&self.target_url))
+            })
+            .clone()
+    }
+}
+
+/// A raw entry's body, compressed when it is long enough to be worth it.
+/// The checksum is taken when the body is parsed, so it always covers the
+/// uncompressed bytes.
+#[derive(Debug)]
+enum Body {
+    Plain(String),
+    #[cfg(feature = "compression")]
+    Lz4(Vec<u8>),
+}
+
+impl Body {
+    fn new(body: String) -> Self {
+        #[cfg(feature = "compression")]
+        if body.len() >= COMPRESSION_THRESHOLD {
+            let compressed = lz4_flex::compress_prepend_size(body.as_bytes());
+            debug!(
+                body_len = body.len(),
+                compressed_len = compressed.len(),
+                "Compressed cached robots.txt"
+            );
+            return Self::Lz4(compressed);
+        }
+        Self::Plain(body)
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Plain(body) => body.len(),
+            #[cfg(feature = "compression")]
+            Self::Lz4(compressed) => compressed.len(),
+        }
+    }
+
+    fn decode(&self) -> String {
+        match self {
+            Self::Plain(body) => body.clone(),
+            #[cfg(feature = "compression")]
+            Self::Lz4(compressed) => {
+                let bytes = lz4_flex::decompress_size_prepended(compressed)
+                    .expect("compressed by Body::new");
+                String::from_utf8(bytes).expect("compressed from a String")
+            }
+        }
+    }
+}
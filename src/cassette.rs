@@ -0,0 +1,253 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, instrument, warn};
+
+use crate::clock::{self, Clock};
+use crate::fetcher::{FetchError, FetchStats, Fetcher, InFlight, RobotsFetcher, RobotsText};
+use crate::redact;
+use crate::robots_data::RobotsData;
+use crate::target::NormalizedTarget;
+use crate::x_robots_tag::XRobotsTagHeaders;
+
+/// The cassette format written by this version. Cassettes of any other
+/// version are rejected rather than replayed wrongly.
+pub const CASSETTE_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum CassetteError {
+    #[error("Failed to read cassette {}: {message}", path.display())]
+    Read { path: PathBuf, message: String },
+    #[error("Invalid cassette {}: {message}", path.display())]
+    Invalid { path: PathBuf, message: String },
+    #[error("Cassette {} is version {found}, expected {CASSETTE_VERSION}", path.display())]
+    Version { path: PathBuf, found: u32 },
+}
+
+/// The fetches recorded in a cassette file, in the order they were made.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Cassette {
+    version: u32,
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Interaction {
+    /// The robots.txt URL, or the page URL for `X-Robots-Tag` checks.
+    url: String,
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    RobotsTxt(Result<RobotsText, FetchError>),
+    XRobotsTag(Result<XRobotsTagHeaders, FetchError>),
+}
+
+impl Outcome {
+    fn kind(&self) -> Kind {
+        match self {
+            Outcome::RobotsTxt(_) => Kind::RobotsTxt,
+            Outcome::XRobotsTag(_) => Kind::XRobotsTag,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Kind {
+    RobotsTxt,
+    XRobotsTag,
+}
+
+#[derive(Clone)]
+enum Mode<F> {
+    /// Fetch with the inner fetcher and write each result to the cassette.
+    Record {
+        inner: F,
+        cassette: Arc<tokio::sync::Mutex<Cassette>>,
+    },
+    /// Answer from the recorded results, each URL's in turn, repeating the
+    /// last once they run out.
+    Replay(Arc<Mutex<HashMap<(Kind, String), VecDeque<Outcome>>>>),
+}
+
+/// A [`Fetcher`] that records the robots.txt and `X-Robots-Tag` fetches
+/// of another to a JSON cassette file, or replays them from one without
+/// network access, so tests can run against real-world responses.
+/// Redirects are followed while recording, and replay serves where they
+/// ended up.
+#[derive(Clone)]
+pub struct CassetteFetcher<F: Fetcher = RobotsFetcher> {
+    path: PathBuf,
+    mode: Mode<F>,
+    stats: Arc<FetchStats>,
+    clock: Arc<dyn Clock>,
+}
+
+impl CassetteFetcher {
+    /// Replay the cassette at `path`. URLs it has no recording for fail as
+    /// unreachable.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, CassetteError> {
+        let path = path.into();
+        let contents = std::fs::read_to_string(&path).map_err(|e| CassetteError::Read {
+            path: path.clone(),
+            message: e.to_string(),
+        })?;
+        let cassette: Cassette =
+            serde_json::from_str(&contents).map_err(|e| CassetteError::Invalid {
+                path: path.clone(),
+                message: e.to_string(),
+            })?;
+        if cassette.version != CASSETTE_VERSION {
+            return Err(CassetteError::Version {
+                path,
+                found: cassette.version,
+            });
+        }
+        let mut recorded: HashMap<_, VecDeque<_>> = HashMap::new();
+        for interaction in cassette.interactions {
+            recorded
+                .entry((interaction.outcome.kind(), interaction.url))
+                .or_default()
+                .push_back(interaction.outcome);
+        }
+        Ok(Self {
+            path,
+            mode: Mode::Replay(Arc::new(Mutex::new(recorded))),
+            stats: Arc::default(),
+            clock: clock::system(),
+        })
+    }
+}
+
+impl<F: Fetcher> CassetteFetcher<F> {
+    /// Fetch with `inner`, writing every result to a new cassette at
+    /// `path`, replacing any already there.
+    pub fn record(path: impl Into<PathBuf>, inner: F) -> Self {
+        let clock = inner.clock().clone();
+        Self {
+            path: path.into(),
+            mode: Mode::Record {
+                inner,
+                cassette: Arc::new(tokio::sync::Mutex::new(Cassette {
+                    version: CASSETTE_VERSION,
+                    interactions: Vec::new(),
+                })),
+            },
+            stats: Arc::default(),
+            clock,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Add `outcome` to the cassette and write it out. A cassette that
+    /// cannot be written is logged rather than failing the fetch.
+    async fn write(&self, cassette: &tokio::sync::Mutex<Cassette>, url: String, outcome: Outcome) {
+        let mut cassette = cassette.lock().await;
+        cassette.interactions.push(Interaction { url, outcome });
+        let result = match serde_json::to_string_pretty(&*cassette) {
+            Ok(json) => tokio::fs::write(&self.path, json)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = result {
+            warn!(path = %self.path.display(), error = %e, "Failed to write cassette");
+        }
+    }
+
+    /// The next recorded outcome for `url`.
+    fn replayed(
+        recorded: &Mutex<HashMap<(Kind, String), VecDeque<Outcome>>>,
+        kind: Kind,
+        url: &str,
+    ) -> Result<Outcome, FetchError> {
+        let mut recorded = recorded.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(outcomes) = recorded.get_mut(&(kind, url.to_string())) else {
+            debug!(url = %redact::url(url), "No recording in cassette");
+            return Err(FetchError::Unreachable((
+                format!("No recording in cassette for {url}"),
+                None,
+            )));
+        };
+        Ok(match outcomes.len() {
+            1 => outcomes[0].clone(),
+            _ => outcomes.pop_front().expect("recordings are never empty"),
+        })
+    }
+}
+
+#[async_trait]
+impl<F: Fetcher> Fetcher for CassetteFetcher<F> {
+    async fn fetch(&self, target_url: &str) -> Result<RobotsData, FetchError> {
+        self.fetch_text(target_url)
+            .await
+            .map(|text| text.parse(target_url))
+    }
+
+    #[instrument(skip(self), fields(target_url = %redact::url(target_url)))]
+    async fn fetch_text(&self, target_url: &str) -> Result<RobotsText, FetchError> {
+        let _in_flight = InFlight::start(&self.stats);
+        let url = NormalizedTarget::parse(target_url)?.robots_key;
+        match &self.mode {
+            Mode::Record { inner, cassette } => {
+                let result = inner.fetch_text(target_url).await;
+                self.write(cassette, url, Outcome::RobotsTxt(result.clone()))
+                    .await;
+                result
+            }
+            Mode::Replay(recorded) => match Self::replayed(recorded, Kind::RobotsTxt, &url)? {
+                Outcome::RobotsTxt(result) => result,
+                Outcome::XRobotsTag(_) => unreachable!("recordings are keyed by kind"),
+            },
+        }
+    }
+
+    #[instrument(skip(self), fields(target_url = %redact::url(target_url)))]
+    async fn fetch_x_robots_tag(&self, target_url: &str) -> Result<XRobotsTagHeaders, FetchError> {
+        let url = NormalizedTarget::parse(target_url)?.target_url;
+        match &self.mode {
+            Mode::Record { inner, cassette } => {
+                let result = inner.fetch_x_robots_tag(target_url).await;
+                self.write(cassette, url, Outcome::XRobotsTag(result.clone()))
+                    .await;
+                result
+            }
+            Mode::Replay(recorded) => match Self::replayed(recorded, Kind::XRobotsTag, &url)? {
+                Outcome::XRobotsTag(result) => result,
+                Outcome::RobotsTxt(_) => unreachable!("recordings are keyed by kind"),
+            },
+        }
+    }
+
+    fn stats(&self) -> &FetchStats {
+        &self.stats
+    }
+
+    fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Replayed robots.txt keep the time they were recorded at, so only
+    /// fetches while recording use `clock`.
+    fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.mode = match self.mode {
+            Mode::Record { inner, cassette } => Mode::Record {
+                inner: inner.with_clock(clock.clone()),
+                cassette,
+            },
+            replay => replay,
+        };
+        self.clock = clock;
+        self
+    }
+}
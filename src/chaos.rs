@@ -0,0 +1,243 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+use crate::cache::{Cache, CacheError, CacheResult, CacheStats};
+use crate::clock::Clock;
+use crate::config::ChaosConfig;
+use crate::fetcher::{FetchError, FetchStats, Fetcher, RobotsText};
+use crate::metrics;
+use crate::robots_data::RobotsData;
+use crate::x_robots_tag::XRobotsTagHeaders;
+
+/// A kind of injected fault, as labelled in the metrics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    FetchTimeout,
+    FetchServerError,
+    FetchLatency,
+    CacheGetError,
+    CacheSetError,
+}
+
+impl Fault {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Fault::FetchTimeout => "fetch_timeout",
+            Fault::FetchServerError => "fetch_server_error",
+            Fault::FetchLatency => "fetch_latency",
+            Fault::CacheGetError => "cache_get_error",
+            Fault::CacheSetError => "cache_set_error",
+        }
+    }
+}
+
+/// Decides which calls get a fault. Clones share one random sequence, so
+/// wrappers built from the same `Chaos` fail the same calls on every run
+/// that makes the same calls in the same order.
+#[derive(Clone, Debug)]
+pub struct Chaos {
+    config: ChaosConfig,
+    state: Arc<Mutex<u64>>,
+}
+
+impl Chaos {
+    pub fn new(config: ChaosConfig) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(config.seed)),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &ChaosConfig {
+        &self.config
+    }
+
+    /// Whether to inject `fault`, which happens with `probability`.
+    fn inject(&self, fault: Fault, probability: f64) -> bool {
+        if probability <= 0.0 || self.next() >= probability {
+            return false;
+        }
+        debug!(fault = fault.as_str(), "Injecting fault");
+        metrics::CHAOS_FAULTS
+            .with_label_values(&[fault.as_str()])
+            .inc();
+        true
+    }
+
+    /// The next number in `[0, 1)` from a SplitMix64 sequence.
+    fn next(&self) -> f64 {
+        let mut state = self.state.lock().expect("chaos lock poisoned");
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A [`Fetcher`] that delays or fails some fetches before they reach the
+/// one it wraps.
+#[derive(Clone)]
+pub struct ChaosFetcher<F: Fetcher> {
+    inner: F,
+    chaos: Chaos,
+}
+
+impl<F: Fetcher> ChaosFetcher<F> {
+    pub fn new(inner: F, chaos: Chaos) -> Self {
+        warn!(config = ?chaos.config(), "Injecting faults into fetches");
+        Self { inner, chaos }
+    }
+
+    /// Apply the fetch faults: maybe wait, then maybe fail.
+    async fn disrupt(&self) -> Result<(), FetchError> {
+        let config = self.chaos.config();
+        if self.chaos.inject(Fault::FetchLatency, config.fetch_latency) {
+            tokio::time::sleep(config.fetch_latency()).await;
+        }
+        if self.chaos.inject(Fault::FetchTimeout, config.fetch_timeout) {
+            return Err(FetchError::Timeout);
+        }
+        if self
+            .chaos
+            .inject(Fault::FetchServerError, config.fetch_server_error)
+        {
+            return Err(FetchError::Unreachable((
+                "Server error: 503 Service Unavailable (injected)".to_string(),
+                Some(503),
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<F: Fetcher> Fetcher for ChaosFetcher<F> {
+    async fn fetch(&self, target_url: &str) -> Result<RobotsData, FetchError> {
+        self.disrupt().await?;
+        self.inner.fetch(target_url).await
+    }
+
+    async fn fetch_text(&self, target_url: &str) -> Result<RobotsText, FetchError> {
+        self.disrupt().await?;
+        self.inner.fetch_text(target_url).await
+    }
+
+    async fn fetch_x_robots_tag(&self, target_url: &str) -> Result<XRobotsTagHeaders, FetchError> {
+        self.disrupt().await?;
+        self.inner.fetch_x_robots_tag(target_url).await
+    }
+
+    fn stats(&self) -> &FetchStats {
+        self.inner.stats()
+    }
+
+    fn clock(&self) -> &Arc<dyn Clock> {
+        self.inner.clock()
+    }
+
+    fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.inner = self.inner.with_clock(clock);
+        self
+    }
+}
+
+/// A [`Cache`] whose lookups and writes sometimes fail before reaching the
+/// one it wraps.
+pub struct ChaosCache<C> {
+    inner: C,
+    chaos: Chaos,
+}
+
+impl<C> ChaosCache<C> {
+    pub fn new(inner: C, chaos: Chaos) -> Self {
+        warn!(config = ?chaos.config(), "Injecting faults into the cache");
+        Self { inner, chaos }
+    }
+}
+
+#[async_trait]
+impl<K, V, C> Cache<K, V> for ChaosCache<C>
+where
+    K: Eq + Hash + Clone + Debug + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+    C: Cache<K, V>,
+{
+    async fn get(&self, key: &K) -> CacheResult<Option<V>> {
+        let probability = self.chaos.config().cache_get_error;
+        if self.chaos.inject(Fault::CacheGetError, probability) {
+            return Err(CacheError::Unavailable);
+        }
+        self.inner.get(key).await
+    }
+
+    async fn get_uncounted(&self, key: &K) -> CacheResult<Option<V>> {
+        let probability = self.chaos.config().cache_get_error;
+        if self.chaos.inject(Fault::CacheGetError, probability) {
+            return Err(CacheError::Unavailable);
+        }
+        self.inner.get_uncounted(key).await
+    }
+
+    async fn get_with_ttl(&self, key: &K) -> CacheResult<Option<(V, Option<Duration>)>> {
+        let probability = self.chaos.config().cache_get_error;
+        if self.chaos.inject(Fault::CacheGetError, probability) {
+            return Err(CacheError::Unavailable);
+        }
+        self.inner.get_with_ttl(key).await
+    }
+
+    async fn set(&self, key: K, value: V) -> CacheResult<()> {
+        let probability = self.chaos.config().cache_set_error;
+        if self.chaos.inject(Fault::CacheSetError, probability) {
+            return Err(CacheError::WriteFailed("injected fault".to_string()));
+        }
+        self.inner.set(key, value).await
+    }
+
+    async fn set_with_ttl(&self, key: K, value: V, ttl: Duration) -> CacheResult<()> {
+        let probability = self.chaos.config().cache_set_error;
+        if self.chaos.inject(Fault::CacheSetError, probability) {
+            return Err(CacheError::WriteFailed("injected fault".to_string()));
+        }
+        self.inner.set_with_ttl(key, value, ttl).await
+    }
+
+    async fn pin(&self, key: K, value: V) -> CacheResult<()> {
+        let probability = self.chaos.config().cache_set_error;
+        if self.chaos.inject(Fault::CacheSetError, probability) {
+            return Err(CacheError::WriteFailed("injected fault".to_string()));
+        }
+        self.inner.pin(key, value).await
+    }
+
+    async fn delete(&self, key: &K) -> CacheResult<bool> {
+        self.inner.delete(key).await
+    }
+
+    async fn clear(&self) -> CacheResult<Option<u64>> {
+        self.inner.clear().await
+    }
+
+    async fn close(&self) -> CacheResult<()> {
+        self.inner.close().await
+    }
+
+    fn entry_count(&self) -> Option<u64> {
+        self.inner.entry_count()
+    }
+
+    async fn entries(&self) -> CacheResult<Vec<(K, V, Option<Duration>)>> {
+        self.inner.entries().await
+    }
+
+    async fn stats(&self) -> Option<CacheStats> {
+        self.inner.stats().await
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use crate::cache::{Cache, MokaCache};
+use crate::cached_robots::CachedRobots;
+use crate::fetcher::{FetchError, Fetcher, RobotsFetcher};
+use crate::robots_data::{AccessResult, RobotsData, Rule};
+use crate::target::{NormalizedTarget, TargetError};
+
+#[derive(Debug, Error)]
+pub enum CheckError {
+    #[error(transparent)]
+    InvalidUrl(#[from] TargetError),
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+}
+
+/// Whether robots.txt lets a user-agent crawl a URL, and why.
+#[derive(Clone, Debug)]
+pub struct Decision {
+    pub allowed: bool,
+    /// The same reason IsAllowed gives, such as `Disallow: /private`.
+    pub reason: String,
+    /// The longest matching rule, or `None` when no rule matched.
+    pub rule: Option<Rule>,
+    pub access_result: AccessResult,
+}
+
+/// Fetch the robots.txt for `url` and decide whether `user_agent` may crawl
+/// it, the way IsAllowed does with the default settings, but without a
+/// server or cache.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), robots_server::CheckError> {
+/// let decision = robots_server::check_url("https://example.com/x", "MyBot").await?;
+/// println!("{}: {}", decision.allowed, decision.reason);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn check_url(url: &str, user_agent: &str) -> Result<Decision, CheckError> {
+    check_url_with(
+        &RobotsFetcher::new(),
+        None::<&MokaCache<String, CachedRobots>>,
+        url,
+        user_agent,
+    )
+    .await
+}
+
+/// [`check_url`] with a prebuilt `fetcher`, reusing robots.txt from `cache`
+/// when given one. Cache failures are logged and the check goes on uncached.
+pub async fn check_url_with<F: Fetcher, C: Cache<String, CachedRobots>>(
+    fetcher: &F,
+    cache: Option<&C>,
+    url: &str,
+    user_agent: &str,
+) -> Result<Decision, CheckError> {
+    let target = NormalizedTarget::parse(url)?;
+    let cached = match cache {
+        Some(cache) => cache.get(&target.robots_key).await.unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to read cached robots.txt");
+            None
+        }),
+        None => None,
+    };
+    let data = match cached {
+        Some(entry) => {
+            debug!("Using cached robots.txt");
+            entry.data()
+        }
+        None => {
+            let data = Arc::new(fetch(fetcher, &target).await?);
+            if let Some(cache) = cache
+                && let Err(e) = cache
+                    .set(target.robots_key.clone(), data.clone().into())
+                    .await
+            {
+                warn!(error = %e, "Failed to cache robots.txt data");
+            }
+            data
+        }
+    };
+    Ok(decide(&data, &target, user_agent))
+}
+
+/// The robots.txt data for `target`, with failures IsAllowed can still
+/// decide from reported the way the service caches them.
+async fn fetch<F: Fetcher>(
+    fetcher: &F,
+    target: &NormalizedTarget,
+) -> Result<RobotsData, FetchError> {
+    let mut failure_reason = None;
+    let (access_result, http_status_code) = match fetcher.fetch(&target.target_url).await {
+        Ok(data) => return Ok(data),
+        Err(FetchError::TooManyRedirects) => {
+            failure_reason = Some("TOO_MANY_REDIRECTS");
+            (AccessResult::TooManyRedirects, 0)
+        }
+        Err(FetchError::RedirectLoop) => {
+            failure_reason = Some("REDIRECT_LOOP");
+            (AccessResult::TooManyRedirects, 0)
+        }
+        Err(FetchError::Unavailable(status)) => (AccessResult::Unavailable, status as u32),
+        Err(FetchError::RateLimited(_)) => (AccessResult::Unavailable, 429),
+        Err(FetchError::Unreachable((_, status))) => {
+            (AccessResult::Unreachable, status.unwrap_or(0) as u32)
+        }
+        Err(FetchError::Timeout) => (AccessResult::Unreachable, 0),
+        Err(e) => return Err(e),
+    };
+    Ok(RobotsData {
+        target_url: target.target_url.clone(),
+        robots_txt_url: target.robots_key.clone(),
+        access_result,
+        http_status_code,
+        failure_reason: failure_reason.map(str::to_string),
+        fetched_at: Some(fetcher.clock().now()),
+        ..Default::default()
+    })
+}
+
+fn decide(data: &RobotsData, target: &NormalizedTarget, user_agent: &str) -> Decision {
+    let (allowed, reason, rule) = match data.access_result {
+        AccessResult::Unreachable => (false, "robots.txt unreachable".to_string(), None),
+        AccessResult::Unavailable => (true, "robots.txt unavailable".to_string(), None),
+        AccessResult::TooManyRedirects => {
+            let reason = data
+                .failure_reason
+                .as_deref()
+                .unwrap_or("TOO_MANY_REDIRECTS");
+            (true, format!("robots.txt unavailable: {reason}"), None)
+        }
+        AccessResult::Unusable => (true, "robots.txt unusable: allow-all".to_string(), None),
+        _ => {
+            let explanation = data.explain(user_agent, &target.path_and_query);
+            let reason = match &explanation.rule {
+                Some(rule) => format!("{}: {}", rule.rule_type, rule.path_pattern),
+                None => "no matching rule".to_string(),
+            };
+            (explanation.allowed, reason, explanation.rule)
+        }
+    };
+    Decision {
+        allowed,
+        reason,
+        rule,
+        access_result: data.access_result,
+    }
+}
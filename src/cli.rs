@@ -0,0 +1,597 @@
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use thiserror::Error;
+use toml::Table;
+use tracing::{debug, instrument};
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+use crate::config::{Config, ConfigError, ConfigHandle, RuntimeConfig};
+use crate::fetcher::{FetchError, RobotsFetcher, extract_robots_url};
+use crate::log_format::LogFormat;
+use crate::parser;
+use crate::redact;
+use crate::robots_data::{AccessResult, RobotsData, RuleType};
+
+/// Crate version and the commit it was built from, for `--version`.
+pub const VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("ROBOTS_GIT_HASH"),
+    ")"
+);
+
+#[derive(Debug, Parser)]
+#[command(name = "robots-server", version = VERSION, about = "RFC 9309 robots.txt service")]
+pub struct Cli {
+    /// Path to a TOML config file
+    #[arg(long, global = true, env = "ROBOTS_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Format of log lines written to stderr
+    #[arg(
+        long,
+        global = true,
+        env = "ROBOTS_LOG_FORMAT",
+        value_enum,
+        default_value_t = LogFormat::Text
+    )]
+    pub log_format: LogFormat,
+    /// Which log lines to write, as `RUST_LOG` directives such as
+    /// `robots_server=debug`. Replaces `RUST_LOG` when given
+    #[arg(long, global = true, value_name = "FILTER", value_parser = parse_log_filter)]
+    pub log_filter: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the gRPC server (the default)
+    Serve(ServeArgs),
+    /// Fetch robots.txt for a URL and report whether a user-agent may crawl it
+    Check(CheckArgs),
+    /// Print the parsed robots.txt for a URL or local file
+    Dump(DumpArgs),
+}
+
+/// Server settings given on the command line. They are defaults: a value
+/// in the config file takes precedence over the same flag.
+#[derive(Debug, Default, Args)]
+pub struct ServeArgs {
+    /// Address to listen on, e.g. `0.0.0.0:50051`. Repeat it to listen on
+    /// several, such as `0.0.0.0:50051` and `[::]:50051`
+    #[arg(long, visible_aliases = ["listen-addr", "listen"], value_name = "ADDR")]
+    pub bind: Vec<SocketAddr>,
+    /// Port to listen on, replacing the port of every `--bind` or of the
+    /// default address
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Whether an address that cannot be bound stops the server starting
+    /// (`error`) or is skipped with a warning (`warn`)
+    #[arg(long, value_name = "POLICY", value_parser = ["error", "warn"])]
+    pub bind_failure: Option<String>,
+    /// Address to serve `/healthz` and `/readyz` on over plain HTTP, e.g.
+    /// `0.0.0.0:8080`
+    #[arg(long, value_name = "ADDR")]
+    pub health_addr: Option<SocketAddr>,
+    /// How long fetched robots.txt is cached, e.g. `6h`
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub cache_ttl: Option<Duration>,
+    /// How long X-Robots-Tag lookups are cached, e.g. `5m`
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub header_cache_ttl: Option<Duration>,
+    /// How long 5xx responses and failed fetches are cached, e.g. `30m`
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub negative_cache_ttl: Option<Duration>,
+    /// How long expired robots.txt is still answered from while it is
+    /// refetched in the background, e.g. `1h`
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub stale_while_revalidate: Option<Duration>,
+    /// Timeout for fetching from origins, e.g. `10s`
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub fetch_timeout: Option<Duration>,
+    /// Bytes of robots.txt read before the rest is ignored, e.g. `1MiB`
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_robots_size: Option<u64>,
+    /// Calls served at once before further ones are rejected with
+    /// `RESOURCE_EXHAUSTED`; 0 for no limit
+    #[arg(long, value_name = "N")]
+    pub max_concurrent_requests: Option<usize>,
+    /// Most robots.txt entries to keep in the in-memory cache; unbounded by
+    /// default
+    #[arg(long, value_name = "N")]
+    pub cache_max_entries: Option<u64>,
+    /// Estimated bytes of robots.txt to keep in the in-memory cache, in
+    /// place of `--cache-max-entries`, which it cannot be given with, e.g.
+    /// `512MiB`
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_cache_bytes: Option<u64>,
+    /// Save the in-memory cache to this file on shutdown, and restore it
+    /// from there at startup
+    #[arg(long, value_name = "PATH")]
+    pub cache_snapshot: Option<PathBuf>,
+    /// Threads running async tasks; one per CPU by default
+    #[arg(long, value_name = "N")]
+    pub worker_threads: Option<usize>,
+    /// Threads for blocking work such as file access; 512 by default
+    #[arg(long, value_name = "N")]
+    pub max_blocking_threads: Option<usize>,
+    /// Check the cache backend and egress before serving, warning about
+    /// failures
+    #[arg(long)]
+    pub self_check: bool,
+    /// Run the self-check and exit with an error if any part of it fails
+    #[arg(long)]
+    pub strict_startup: bool,
+    /// Run the self-check, fetching the robots.txt of this URL to check
+    /// egress, in place of `self_check_probe_url`
+    #[arg(long, value_name = "URL")]
+    pub startup_probe_url: Option<String>,
+    /// Once serving, fetch robots.txt into the cache for each URL in this
+    /// file, one per line
+    #[arg(long, value_name = "PATH")]
+    pub warm_from: Option<PathBuf>,
+    /// Load and validate the configuration, print it with secrets redacted,
+    /// and exit without serving
+    #[arg(long)]
+    pub validate_config: bool,
+    /// PEM certificate chain to serve over TLS with, in place of the
+    /// `[tls]` table. Needs `--tls-key`
+    #[arg(long, value_name = "PATH", requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key for `--tls-cert`
+    #[arg(long, value_name = "PATH", requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+    /// PEM CA certificates that clients must present a certificate signed
+    /// by. Needs `--tls-cert`
+    #[arg(long, value_name = "PATH", requires = "tls_cert")]
+    pub tls_client_ca: Option<PathBuf>,
+    /// Unix domain socket to serve on, in place of the `[uds]` table. TCP is
+    /// served too unless `--uds-only` is given
+    #[arg(long, value_name = "PATH")]
+    pub uds: Option<PathBuf>,
+    /// Serve only on `--uds`, not over TCP
+    #[arg(long, requires = "uds")]
+    pub uds_only: bool,
+    /// Inject the faults configured in the `[chaos]` table. Never use this
+    /// in production
+    #[arg(long)]
+    pub enable_chaos: bool,
+}
+
+impl ServeArgs {
+    /// The flags as config-file settings, for [`Config::load_with_defaults`].
+    pub fn config_defaults(&self) -> Table {
+        let mut table = Table::new();
+        if let Some((addr, additional)) = self.listen_addrs().split_first() {
+            table.insert("listen_addr".into(), addr.to_string().into());
+            if !additional.is_empty() {
+                let additional = additional.iter().map(|addr| addr.to_string().into());
+                table.insert(
+                    "additional_listen_addrs".into(),
+                    toml::Value::Array(additional.collect()),
+                );
+            }
+        }
+        if let Some(policy) = &self.bind_failure {
+            table.insert("bind_failure".into(), policy.clone().into());
+        }
+        if let Some(addr) = self.health_addr {
+            table.insert("health_addr".into(), addr.to_string().into());
+        }
+        if let Some(url) = &self.startup_probe_url {
+            table.insert("self_check_probe_url".into(), url.clone().into());
+        }
+        if let Some(ttl) = self.cache_ttl {
+            table.insert("cache_ttl_secs".into(), secs(ttl).into());
+        }
+        if let Some(ttl) = self.header_cache_ttl {
+            table.insert("header_cache_ttl_secs".into(), secs(ttl).into());
+        }
+        if let Some(ttl) = self.negative_cache_ttl {
+            table.insert("negative_cache_ttl_secs".into(), secs(ttl).into());
+        }
+        if let Some(window) = self.stale_while_revalidate {
+            table.insert("stale_while_revalidate_secs".into(), secs(window).into());
+        }
+        if let Some(timeout) = self.fetch_timeout {
+            table.insert("fetch_timeout_secs".into(), timeout.as_secs_f64().into());
+        }
+        if let Some(size) = self.max_robots_size {
+            let size = i64::try_from(size).unwrap_or(i64::MAX);
+            table.insert("max_robots_txt_bytes".into(), size.into());
+        }
+        if let Some(max) = self.max_concurrent_requests {
+            let max = i64::try_from(max).unwrap_or(i64::MAX);
+            table.insert("max_concurrent_requests".into(), max.into());
+        }
+        if let Some(entries) = self.cache_max_entries {
+            let entries = i64::try_from(entries).unwrap_or(i64::MAX);
+            table.insert("cache_max_entries".into(), entries.into());
+        }
+        if let Some(bytes) = self.max_cache_bytes {
+            let bytes = i64::try_from(bytes).unwrap_or(i64::MAX);
+            table.insert("max_cache_bytes".into(), bytes.into());
+        }
+        if let Some(path) = &self.cache_snapshot {
+            table.insert("cache_snapshot".into(), path.display().to_string().into());
+        }
+        if let Some(threads) = self.worker_threads {
+            let threads = i64::try_from(threads).unwrap_or(i64::MAX);
+            table.insert("worker_threads".into(), threads.into());
+        }
+        if let Some(threads) = self.max_blocking_threads {
+            let threads = i64::try_from(threads).unwrap_or(i64::MAX);
+            table.insert("max_blocking_threads".into(), threads.into());
+        }
+        if let (Some(cert), Some(key)) = (&self.tls_cert, &self.tls_key) {
+            let mut tls = Table::new();
+            tls.insert("cert".into(), cert.display().to_string().into());
+            tls.insert("key".into(), key.display().to_string().into());
+            if let Some(ca) = &self.tls_client_ca {
+                tls.insert("client_ca".into(), ca.display().to_string().into());
+            }
+            table.insert("tls".into(), tls.into());
+        }
+        if let Some(path) = &self.uds {
+            let mut uds = Table::new();
+            uds.insert("path".into(), path.display().to_string().into());
+            uds.insert("tcp".into(), (!self.uds_only).into());
+            table.insert("uds".into(), uds.into());
+        }
+        table
+    }
+
+    /// The addresses from `--bind` and `--port`, if either was given.
+    fn listen_addrs(&self) -> Vec<SocketAddr> {
+        let mut addrs = match (self.bind.is_empty(), self.port) {
+            (true, None) => return Vec::new(),
+            (true, Some(_)) => vec![Config::default().listen_addr],
+            (false, _) => self.bind.clone(),
+        };
+        if let Some(port) = self.port {
+            for addr in &mut addrs {
+                addr.set_port(port);
+            }
+        }
+        addrs
+    }
+}
+
+/// Whole seconds, saturating at the largest value TOML can hold.
+fn secs(duration: Duration) -> i64 {
+    i64::try_from(duration.as_secs()).unwrap_or(i64::MAX)
+}
+
+/// Check that `value` is a valid log filter, so a typo fails before the
+/// server starts rather than silently logging nothing.
+pub fn parse_log_filter(value: &str) -> Result<String, String> {
+    EnvFilter::try_new(value)
+        .map(|_| value.to_string())
+        .map_err(|e| format!("invalid log filter '{value}': {e}"))
+}
+
+/// Parse a byte size such as `512`, `64KB`, or `1MiB`. `KB`/`MB`/`GB` are
+/// powers of 1000 and `KiB`/`MiB`/`GiB` powers of 1024.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{value}'"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "kib" => 1 << 10,
+        "mb" => 1000 * 1000,
+        "mib" => 1 << 20,
+        "gb" => 1000 * 1000 * 1000,
+        "gib" => 1 << 30,
+        _ => return Err(format!("unknown size unit in '{value}'")),
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size '{value}' is too large"))
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// URL to evaluate
+    pub url: String,
+    /// User-agent to evaluate the URL for
+    #[arg(long, short = 'u', default_value = "*")]
+    pub user_agent: String,
+    /// Print the decision as JSON
+    #[arg(long)]
+    pub json: bool,
+    #[command(flatten)]
+    pub fetch: FetchArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct DumpArgs {
+    /// URL whose robots.txt is dumped
+    #[arg(required_unless_present = "from_file", conflicts_with = "from_file")]
+    pub url: Option<String>,
+    /// Print the parsed structure as JSON
+    #[arg(long, conflicts_with = "raw")]
+    pub json: bool,
+    /// Print the robots.txt exactly as served instead of the parsed form
+    #[arg(long)]
+    pub raw: bool,
+    /// Parse a local file instead of fetching
+    #[arg(long)]
+    pub from_file: Option<PathBuf>,
+    #[command(flatten)]
+    pub fetch: FetchArgs,
+}
+
+/// Fetch settings that override the config file for one-off commands.
+#[derive(Debug, Default, Args)]
+pub struct FetchArgs {
+    /// Fetch timeout in seconds
+    #[arg(long)]
+    pub timeout: Option<f64>,
+    /// User-Agent header sent when fetching robots.txt
+    #[arg(long)]
+    pub fetch_user_agent: Option<String>,
+}
+
+impl FetchArgs {
+    pub fn apply(&self, config: &mut RuntimeConfig) {
+        if let Some(timeout) = self.timeout {
+            config.fetch_timeout_secs = timeout;
+        }
+        if let Some(user_agent) = &self.fetch_user_agent {
+            config.fetch_user_agent = Some(user_agent.clone());
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+    #[error("Failed to write output: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to encode JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Failed to encode TOML: {0}")]
+    Toml(#[from] toml::ser::Error),
+}
+
+/// Outcome of `check`, mapped to the process exit code for scripting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decision {
+    Allowed,
+    Disallowed,
+}
+
+impl Decision {
+    pub fn exit_code(self) -> ExitCode {
+        match self {
+            Decision::Allowed => ExitCode::SUCCESS,
+            Decision::Disallowed => ExitCode::from(2),
+        }
+    }
+}
+
+/// Exit code for commands that failed before reaching a decision.
+pub const EXIT_ERROR: u8 = 1;
+
+#[derive(Debug, Serialize)]
+struct CheckReport {
+    url: String,
+    robots_txt_url: String,
+    http_status_code: u32,
+    user_agent: String,
+    allowed: bool,
+    reason: String,
+    matched_user_agents: Vec<String>,
+    matched_rule: Option<MatchedRule>,
+}
+
+#[derive(Debug, Serialize)]
+struct MatchedRule {
+    directive: &'static str,
+    path_pattern: String,
+}
+
+/// Load the runtime configuration for a one-off command, applying `fetch`
+/// on top of the config file if one was given.
+pub fn runtime_config(path: Option<&Path>, fetch: &FetchArgs) -> Result<RuntimeConfig, CliError> {
+    let mut runtime = match path {
+        Some(path) => Config::load(path)?.runtime,
+        None => RuntimeConfig::default(),
+    };
+    fetch.apply(&mut runtime);
+    runtime.validate()?;
+    Ok(runtime)
+}
+
+/// Fetch and evaluate robots.txt for `args.url` and write the decision to `out`.
+#[instrument(skip(args, config, out), fields(url = %redact::url(&args.url), user_agent = %args.user_agent))]
+pub async fn check(
+    args: &CheckArgs,
+    config: RuntimeConfig,
+    out: &mut impl Write,
+) -> Result<Decision, CliError> {
+    let robots_url = extract_robots_url(&args.url)?;
+    let fetcher = RobotsFetcher::with_config(ConfigHandle::new(config));
+    let data = fetch_robots_data(&fetcher, &args.url).await?;
+
+    let report = evaluate(args, robots_url, &data);
+    if args.json {
+        serde_json::to_writer_pretty(&mut *out, &report)?;
+        writeln!(out)?;
+    } else {
+        write_report(&report, out)?;
+    }
+
+    Ok(if report.allowed {
+        Decision::Allowed
+    } else {
+        Decision::Disallowed
+    })
+}
+
+/// Write the effective configuration to `out` as TOML, with secrets
+/// redacted and every default filled in.
+pub fn write_config(config: &Config, out: &mut impl Write) -> Result<(), CliError> {
+    out.write_all(toml::to_string_pretty(&config.redacted())?.as_bytes())?;
+    Ok(())
+}
+
+/// Write the parsed (or, with `--raw`, the original) robots.txt to `out`.
+/// Output is deterministic so dumps can be diffed.
+#[instrument(skip(args, config, out), fields(url = ?args.url.as_deref().map(redact::url), from_file = ?args.from_file))]
+pub async fn dump(
+    args: &DumpArgs,
+    config: RuntimeConfig,
+    out: &mut impl Write,
+) -> Result<(), CliError> {
+    if let Some(path) = &args.from_file {
+        let body = std::fs::read_to_string(path)?;
+        if args.raw {
+            out.write_all(body.as_bytes())?;
+            return Ok(());
+        }
+        let mut data = parser::parse(&body);
+        data.robots_txt_url = path.display().to_string();
+        data.content_length_bytes = body.len() as u64;
+        return write_data(&data, args.json, out);
+    }
+
+    let url = args.url.as_deref().unwrap_or_default();
+    let fetcher = RobotsFetcher::with_config(ConfigHandle::new(config));
+    if args.raw {
+        let text = fetcher.fetch_text(url).await?;
+        out.write_all(text.body.as_bytes())?;
+        return Ok(());
+    }
+    let data = fetch_robots_data(&fetcher, url).await?;
+    write_data(&data, args.json, out)
+}
+
+fn write_data(data: &RobotsData, json: bool, out: &mut impl Write) -> Result<(), CliError> {
+    if json {
+        serde_json::to_writer_pretty(&mut *out, data)?;
+        writeln!(out)?;
+    } else {
+        writeln!(out, "{}", String::from(data))?;
+    }
+    Ok(())
+}
+
+/// Fetch robots.txt, turning unavailable and unreachable responses into
+/// data the way the server caches them.
+async fn fetch_robots_data(fetcher: &RobotsFetcher, url: &str) -> Result<RobotsData, CliError> {
+    let robots_txt_url = extract_robots_url(url)?;
+    let (access_result, status) = match fetcher.fetch(url).await {
+        Ok(data) => return Ok(data),
+        Err(FetchError::Unavailable(status)) => (AccessResult::Unavailable, status),
+        Err(FetchError::RateLimited(_)) => (AccessResult::Unavailable, 429),
+        Err(FetchError::Unreachable((e, status))) => {
+            debug!(error = %e, "robots.txt unreachable");
+            (AccessResult::Unreachable, status.unwrap_or(0))
+        }
+        Err(FetchError::Timeout) => (AccessResult::Unreachable, 0),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(RobotsData {
+        target_url: url.to_string(),
+        robots_txt_url,
+        access_result,
+        http_status_code: status as u32,
+        ..Default::default()
+    })
+}
+
+fn evaluate(args: &CheckArgs, robots_txt_url: String, data: &RobotsData) -> CheckReport {
+    let mut report = CheckReport {
+        url: args.url.clone(),
+        robots_txt_url,
+        http_status_code: data.http_status_code,
+        user_agent: args.user_agent.clone(),
+        allowed: true,
+        reason: String::new(),
+        matched_user_agents: Vec::new(),
+        matched_rule: None,
+    };
+    match data.access_result {
+        AccessResult::Unavailable => {
+            report.reason = "robots.txt unavailable, all paths allowed".to_string();
+        }
+        AccessResult::Unreachable => {
+            report.allowed = false;
+            report.reason = "robots.txt unreachable, all paths disallowed".to_string();
+        }
+        _ => {
+            let path = path_of(&args.url);
+            let explanation = data.explain(&args.user_agent, &path);
+            report.allowed = explanation.allowed;
+            report.matched_user_agents = explanation.user_agents;
+            report.reason = match &explanation.rule {
+                Some(_) => "matched rule".to_string(),
+                None => "no rule matched".to_string(),
+            };
+            report.matched_rule = explanation.rule.map(|rule| MatchedRule {
+                directive: match rule.rule_type {
+                    RuleType::Allow => "Allow",
+                    RuleType::Disallow => "Disallow",
+                },
+                path_pattern: rule.path_pattern.to_string(),
+            });
+        }
+    }
+    report
+}
+
+fn write_report(report: &CheckReport, out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "URL:        {}", report.url)?;
+    writeln!(
+        out,
+        "robots.txt: {} (HTTP {})",
+        report.robots_txt_url, report.http_status_code
+    )?;
+    writeln!(out, "User-agent: {}", report.user_agent)?;
+    let decision = if report.allowed {
+        "ALLOWED"
+    } else {
+        "DISALLOWED"
+    };
+    writeln!(out, "Decision:   {decision} ({})", report.reason)?;
+    if !report.matched_user_agents.is_empty() {
+        writeln!(
+            out,
+            "Group:      User-agent: {}",
+            report.matched_user_agents.join(", ")
+        )?;
+    }
+    if let Some(rule) = &report.matched_rule {
+        writeln!(out, "Rule:       {}: {}", rule.directive, rule.path_pattern)?;
+    }
+    Ok(())
+}
+
+/// The path and query used for matching, as the service does.
+fn path_of(url: &str) -> String {
+    let Ok(parsed) = Url::parse(url) else {
+        return "/".to_string();
+    };
+    match parsed.query() {
+        Some(query) => format!("{}?{query}", parsed.path()),
+        None => parsed.path().to_string(),
+    }
+}
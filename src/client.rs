@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use thiserror::Error;
+use tonic::metadata::{AsciiMetadataValue, errors::InvalidMetadataValue};
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status};
+use tracing::{debug, instrument};
+
+use crate::fetch_queue::Priority;
+use crate::redact;
+use crate::service::robots::v1::{
+    ClearCacheRequest, ClearCacheResponse, GetCanonicalRobotsTextResponse,
+    GetPrefetchStatusRequest, GetPrefetchStatusResponse, GetRobotsRequest, GetRobotsResponse,
+    GetServerInfoRequest, GetServerInfoResponse, InvalidateUrlRequest, InvalidateUrlResponse,
+    IsAllowedRequest, IsAllowedResponse, ListCachedEntriesRequest, ListCachedEntriesResponse,
+    PrefetchHostsRequest, PrefetchHostsResponse, RefreshRobotsRequest, RefreshRobotsResponse,
+    robots_service_client::RobotsServiceClient,
+};
+
+const DEFAULT_DEADLINE: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("Transport error: {0}")]
+    Transport(#[from] tonic::transport::Error),
+    #[error("gRPC status {:?}: {}", .0.code(), .0.message())]
+    Status(#[from] Status),
+    #[error("Invalid API key: {0}")]
+    InvalidApiKey(#[from] InvalidMetadataValue),
+}
+
+/// Thin wrapper over the generated client that applies a per-call deadline
+/// and the API key to every request.
+#[derive(Clone)]
+pub struct RobotsClient {
+    inner: RobotsServiceClient<Channel>,
+    deadline: Duration,
+    api_key: Option<AsciiMetadataValue>,
+    priority: Option<Priority>,
+}
+
+impl RobotsClient {
+    pub async fn connect(endpoint: impl Into<String>) -> Result<Self, ClientError> {
+        let endpoint = endpoint.into();
+        debug!(%endpoint, "Connecting to robots-server");
+        let channel = Endpoint::from_shared(endpoint)?.connect().await?;
+        Ok(Self::new(channel))
+    }
+
+    pub fn new(channel: Channel) -> Self {
+        Self {
+            inner: RobotsServiceClient::new(channel),
+            deadline: DEFAULT_DEADLINE,
+            api_key: None,
+            priority: None,
+        }
+    }
+
+    /// Set the deadline sent with each call.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Send `api_key` as `x-api-key` metadata on each call.
+    pub fn with_api_key(mut self, api_key: &str) -> Result<Self, ClientError> {
+        self.api_key = Some(api_key.parse()?);
+        Ok(self)
+    }
+
+    /// Ask for the fetches made for each call to be queued at `priority`,
+    /// as bulk jobs should so they do not hold up interactive callers.
+    pub fn with_fetch_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    fn request<T>(&self, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.set_timeout(self.deadline);
+        if let Some(api_key) = &self.api_key {
+            request.metadata_mut().insert("x-api-key", api_key.clone());
+        }
+        if let Some(priority) = self.priority {
+            request.metadata_mut().insert(
+                "x-fetch-priority",
+                AsciiMetadataValue::from_static(priority.as_str()),
+            );
+        }
+        request
+    }
+
+    #[instrument(skip(self, url), fields(url = %redact::url(url)))]
+    pub async fn get_robots_txt(&self, url: &str) -> Result<GetRobotsResponse, ClientError> {
+        let request = self.request(GetRobotsRequest {
+            url: url.to_string(),
+            ..Default::default()
+        });
+        let response = self.inner.clone().get_robots_txt(request).await?;
+        Ok(response.into_inner())
+    }
+
+    /// robots.txt for `url`'s host as the server interprets it.
+    #[instrument(skip(self, url), fields(url = %redact::url(url)))]
+    pub async fn get_canonical_robots_text(
+        &self,
+        url: &str,
+    ) -> Result<GetCanonicalRobotsTextResponse, ClientError> {
+        let request = self.request(GetRobotsRequest {
+            url: url.to_string(),
+            ..Default::default()
+        });
+        let response = self
+            .inner
+            .clone()
+            .get_canonical_robots_text(request)
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    #[instrument(skip(self, target_url), fields(target_url = %redact::url(target_url)))]
+    pub async fn is_allowed(
+        &self,
+        target_url: &str,
+        user_agent: &str,
+    ) -> Result<IsAllowedResponse, ClientError> {
+        let request = self.request(IsAllowedRequest {
+            target_url: target_url.to_string(),
+            user_agent: user_agent.to_string(),
+            ..Default::default()
+        });
+        let response = self.inner.clone().is_allowed(request).await?;
+        Ok(response.into_inner())
+    }
+
+    #[instrument(skip(self, url), fields(url = %redact::url(url)))]
+    pub async fn invalidate_url(&self, url: &str) -> Result<InvalidateUrlResponse, ClientError> {
+        let request = self.request(InvalidateUrlRequest {
+            url: url.to_string(),
+        });
+        let response = self.inner.clone().invalidate_url(request).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Refetch robots.txt for `url`'s host. With `force`, the server fetches
+    /// even if it did so within its minimum refresh interval, provided the
+    /// client's API key is one of its admin keys.
+    #[instrument(skip(self, url), fields(url = %redact::url(url)))]
+    pub async fn refresh_robots(
+        &self,
+        url: &str,
+        force: bool,
+    ) -> Result<RefreshRobotsResponse, ClientError> {
+        let request = self.request(RefreshRobotsRequest {
+            url: url.to_string(),
+            force,
+        });
+        let response = self.inner.clone().refresh_robots(request).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Drop every robots.txt the server has cached. Refused unless the
+    /// server sets `allow_clear_cache`.
+    #[instrument(skip(self))]
+    pub async fn clear_cache(&self) -> Result<ClearCacheResponse, ClientError> {
+        let request = self.request(ClearCacheRequest {});
+        let response = self.inner.clone().clear_cache(request).await?;
+        Ok(response.into_inner())
+    }
+
+    /// One page of the robots.txt entries the server has cached. Pass the
+    /// previous page's `next_page_token`, or an empty one for the first.
+    #[instrument(skip(self, page_token))]
+    pub async fn list_cached_entries(
+        &self,
+        page_size: u32,
+        page_token: &str,
+    ) -> Result<ListCachedEntriesResponse, ClientError> {
+        let request = self.request(ListCachedEntriesRequest {
+            page_size,
+            page_token: page_token.to_string(),
+        });
+        let response = self.inner.clone().list_cached_entries(request).await?;
+        Ok(response.into_inner())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_server_info(&self) -> Result<GetServerInfoResponse, ClientError> {
+        let request = self.request(GetServerInfoRequest {});
+        let response = self.inner.clone().get_server_info(request).await?;
+        Ok(response.into_inner())
+    }
+
+    #[instrument(skip(self, urls), fields(urls = urls.len()))]
+    pub async fn prefetch_hosts(
+        &self,
+        urls: &[String],
+    ) -> Result<PrefetchHostsResponse, ClientError> {
+        let request = self.request(PrefetchHostsRequest {
+            urls: urls.to_vec(),
+        });
+        let response = self.inner.clone().prefetch_hosts(request).await?;
+        Ok(response.into_inner())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_prefetch_status(
+        &self,
+        job_id: u64,
+    ) -> Result<GetPrefetchStatusResponse, ClientError> {
+        let request = self.request(GetPrefetchStatusRequest { job_id });
+        let response = self.inner.clone().get_prefetch_status(request).await?;
+        Ok(response.into_inner())
+    }
+}
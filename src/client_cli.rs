@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use serde_json::{Value, json};
+
+use crate::client::{ClientError, RobotsClient};
+use crate::service::robots::v1::{
+    AccessResult, GetRobotsResponse, InvalidateUrlResponse, IsAllowedResponse, rule::RuleType,
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "robots-client",
+    version,
+    about = "Example client for robots-server"
+)]
+pub struct ClientCli {
+    /// Server endpoint
+    #[arg(long, env = "ROBOTS_ENDPOINT", default_value = "http://[::1]:50051")]
+    pub endpoint: String,
+    /// Deadline for each call in seconds
+    #[arg(long, default_value_t = 10.0)]
+    pub timeout: f64,
+    /// API key sent as x-api-key metadata
+    #[arg(long, env = "ROBOTS_API_KEY")]
+    pub api_key: Option<String>,
+    #[command(subcommand)]
+    pub command: ClientCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ClientCommand {
+    /// Fetch the parsed robots.txt for a URL
+    GetRobots { url: String },
+    /// Check whether a user-agent may crawl a URL
+    IsAllowed {
+        url: String,
+        #[arg(long, short = 'u', default_value = "*")]
+        user_agent: String,
+    },
+    /// Evict the cached robots.txt for a URL's host
+    Invalidate { url: String },
+}
+
+/// Connect to the server, run one command, and return the response as JSON.
+pub async fn run(args: &ClientCli) -> Result<Value, ClientError> {
+    let mut client = RobotsClient::connect(args.endpoint.clone())
+        .await?
+        .with_deadline(Duration::from_secs_f64(args.timeout));
+    if let Some(api_key) = &args.api_key {
+        client = client.with_api_key(api_key)?;
+    }
+
+    Ok(match &args.command {
+        ClientCommand::GetRobots { url } => get_robots_json(client.get_robots_txt(url).await?),
+        ClientCommand::IsAllowed { url, user_agent } => {
+            is_allowed_json(client.is_allowed(url, user_agent).await?)
+        }
+        ClientCommand::Invalidate { url } => invalidate_json(client.invalidate_url(url).await?),
+    })
+}
+
+fn get_robots_json(response: GetRobotsResponse) -> Value {
+    let access_result = AccessResult::try_from(response.access_result)
+        .map(|r| r.as_str_name())
+        .unwrap_or("ACCESS_RESULT_UNSPECIFIED");
+    let groups = response
+        .groups
+        .into_iter()
+        .map(|group| {
+            let rules = group
+                .rules
+                .into_iter()
+                .map(|rule| {
+                    let rule_type = RuleType::try_from(rule.rule_type)
+                        .map(|r| r.as_str_name())
+                        .unwrap_or("RULE_TYPE_UNSPECIFIED");
+                    json!({
+                        "rule_type": rule_type,
+                        "path_pattern": rule.path_pattern,
+                        "line_number": rule.line_number,
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "user_agents": group.user_agents,
+                "rules": rules,
+                "crawl_delay_seconds": group.crawl_delay_seconds,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "target_url": response.target_url,
+        "robots_txt_url": response.robots_txt_url,
+        "access_result": access_result,
+        "http_status_code": response.http_status_code,
+        "groups": groups,
+        "sitemaps": response.sitemaps,
+        "sitemap_line_numbers": response.sitemap_line_numbers,
+        "sitemaps_truncated": response.sitemaps_truncated,
+        "content_length_bytes": response.content_length_bytes,
+        "truncated": response.truncated,
+        "fetched_at": response.fetched_at.map(|t| t.to_string()),
+        "expires_at": response.expires_at.map(|t| t.to_string()),
+        "data_source": response.data_source().as_str_name(),
+        "cache_key": response.cache_key,
+        "upstream_headers": response.upstream_headers,
+        "resolved_address": response.resolved_address,
+        "address_family": response.address_family().as_str_name(),
+        "failure_reason": response.failure_reason,
+        "next_page_token": response.next_page_token,
+    })
+}
+
+fn is_allowed_json(response: IsAllowedResponse) -> Value {
+    json!({
+        "allowed": response.allowed,
+        "suggested_wait_ms": response.suggested_wait_ms,
+        "reason": response.reason,
+        "stale": response.stale,
+        "data_source": response.data_source().as_str_name(),
+    })
+}
+
+fn invalidate_json(response: InvalidateUrlResponse) -> Value {
+    json!({
+        "robots_txt_url": response.robots_txt_url,
+        "existed": response.existed,
+    })
+}
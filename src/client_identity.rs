@@ -0,0 +1,88 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[cfg(unix)]
+use tonic::transport::server::UdsConnectInfo;
+use tonic::transport::server::{TcpConnectInfo, TlsConnectInfo};
+use tower::{Layer, Service};
+use tracing::{Instrument, Span, info_span};
+use x509_parser::prelude::{FromDer, GeneralName, X509Certificate};
+
+/// Tower layer that runs each call in a `client` span whose
+/// `client_identity` field names the holder of the verified client
+/// certificate, so every event the call logs says which client asked.
+/// Calls without a client certificate run as they are.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClientIdentityLayer;
+
+impl<S> Layer<S> for ClientIdentityLayer {
+    type Service = ClientIdentity<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientIdentity { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct ClientIdentity<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for ClientIdentity<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let span = match request_identity(&request) {
+            Some(identity) => info_span!("client", client_identity = %identity),
+            None => Span::none(),
+        };
+        let future = span.in_scope(|| self.inner.call(request));
+        Box::pin(future.instrument(span))
+    }
+}
+
+fn request_identity<B>(request: &http::Request<B>) -> Option<String> {
+    let extensions = request.extensions();
+    let certs = extensions
+        .get::<TlsConnectInfo<TcpConnectInfo>>()
+        .and_then(TlsConnectInfo::peer_certs);
+    #[cfg(unix)]
+    let certs = certs.or_else(|| {
+        extensions
+            .get::<TlsConnectInfo<UdsConnectInfo>>()
+            .and_then(TlsConnectInfo::peer_certs)
+    });
+    client_identity(certs?.first()?)
+}
+
+/// The name a client certificate identifies its holder by: the subject's
+/// common name, or failing that its first DNS, URI, or email subject
+/// alternative name. `None` if `der` is not a certificate or has neither.
+pub fn client_identity(der: &[u8]) -> Option<String> {
+    let (_, cert) = X509Certificate::from_der(der).ok()?;
+    if let Some(cn) = cert
+        .subject()
+        .iter_common_name()
+        .find_map(|cn| cn.as_str().ok())
+    {
+        return Some(cn.to_string());
+    }
+    let san = cert.subject_alternative_name().ok()??;
+    san.value.general_names.iter().find_map(|name| match name {
+        GeneralName::DNSName(name) | GeneralName::URI(name) | GeneralName::RFC822Name(name) => {
+            Some(name.to_string())
+        }
+        _ => None,
+    })
+}
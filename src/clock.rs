@@ -0,0 +1,74 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Where time-dependent components read the current time from, so tests
+/// can move it forward instead of sleeping. A `MokaCache` given one ages
+/// its entries by it; timeouts still run on real time.
+pub trait Clock: Send + Sync + 'static {
+    /// Monotonic time, for spacing and measuring intervals.
+    fn instant(&self) -> Instant;
+    /// Wall-clock time, for timestamps that are stored or reported.
+    fn now(&self) -> SystemTime;
+}
+
+/// The system's clocks.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// The clock used when none is given.
+pub fn system() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// A clock that starts at the time it was created and only moves when
+/// advanced.
+#[derive(Debug)]
+pub struct ManualClock {
+    instant: Instant,
+    system: SystemTime,
+    elapsed: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            instant: Instant::now(),
+            system: SystemTime::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().expect("clock lock poisoned") += by;
+    }
+
+    fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().expect("clock lock poisoned")
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn instant(&self) -> Instant {
+        self.instant + self.elapsed()
+    }
+
+    fn now(&self) -> SystemTime {
+        self.system + self.elapsed()
+    }
+}
@@ -0,0 +1,1285 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwap;
+use ipnet::IpNet;
+use moka::Expiry;
+use reqwest::header::HeaderName;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use toml::Table;
+use tracing::{error, info, instrument, warn};
+use url::Url;
+
+use crate::fetcher::MAX_ROBOTS_TXT_SIZE;
+#[cfg(feature = "server")]
+use crate::log_format::{self, LogFormat};
+use crate::log_sampling::{self, LogSampling};
+use crate::overrides::{PolicyRules, RobotsOverride};
+use crate::politeness::RateLimit;
+use crate::redact::{self, UrlRedaction};
+use crate::robots_data::{AccessResult, RobotsData};
+use crate::target::NormalizedTarget;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse config file: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Invalid config: {0}")]
+    Invalid(String),
+    #[error("No config file to reload")]
+    NoFile,
+}
+
+/// Server configuration as read from the TOML config file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Address the gRPC server listens on. Only read at startup.
+    pub listen_addr: SocketAddr,
+    /// More addresses served alongside `listen_addr`, such as an IPv4 one
+    /// next to an IPv6 one. Only read at startup.
+    pub additional_listen_addrs: Vec<SocketAddr>,
+    /// Whether an address that cannot be bound stops the server starting.
+    /// Only read at startup.
+    pub bind_failure: BindFailure,
+    /// Address of the plain HTTP listener for `/healthz` and `/readyz`
+    /// probes. Only read at startup.
+    pub health_addr: Option<SocketAddr>,
+    /// File for access logs. Only read at startup.
+    pub access_log: Option<LogSinkConfig>,
+    /// Format of log lines written to stderr, in place of `--log-format`.
+    /// Only read at startup.
+    #[cfg(feature = "server")]
+    pub log_format: Option<LogFormat>,
+    /// URL whose robots.txt the startup self-check fetches. Only read at
+    /// startup.
+    pub self_check_probe_url: Option<String>,
+    /// How long requests in flight may run on after SIGTERM or SIGINT
+    /// before they are cancelled. Only read at startup.
+    pub shutdown_grace_secs: f64,
+    /// Calls served at once before further ones are rejected with
+    /// `RESOURCE_EXHAUSTED`, or 0 for no limit. Only read at startup.
+    pub max_concurrent_requests: usize,
+    /// Most robots.txt entries the in-memory cache holds before evicting
+    /// the least used, or unbounded when unset. Only read at startup.
+    pub cache_max_entries: Option<u64>,
+    /// Estimated bytes of robots.txt the in-memory cache holds before
+    /// evicting the least used, in place of `cache_max_entries`; setting
+    /// both is rejected. Only read at startup.
+    pub max_cache_bytes: Option<u64>,
+    /// File the in-memory cache is saved to on shutdown and restored from at
+    /// startup. Only read at startup.
+    pub cache_snapshot: Option<PathBuf>,
+    /// Threads running async tasks, or one per CPU when unset. Only read at
+    /// startup.
+    pub worker_threads: Option<usize>,
+    /// Threads for blocking work, such as file access, or tokio's default
+    /// of 512 when unset. Only read at startup.
+    pub max_blocking_threads: Option<usize>,
+    /// Faults to inject, from the `[chaos]` table. Only read at startup.
+    pub chaos: Option<ChaosConfig>,
+    /// robots.txt files served in place of fetching, from the
+    /// `[static_robots]` table. Only read at startup.
+    pub static_robots: Option<StaticRobotsConfig>,
+    /// Certificate and key to serve gRPC over TLS with, from the `[tls]`
+    /// table. Only read at startup.
+    pub tls: Option<TlsConfig>,
+    /// Unix domain socket to serve on, from the `[uds]` table. Only read at
+    /// startup.
+    pub uds: Option<UdsConfig>,
+    /// HTTP/2 and TCP settings for client connections, from the
+    /// `[transport]` table. Only read at startup.
+    pub transport: TransportConfig,
+    #[serde(flatten)]
+    pub runtime: RuntimeConfig,
+    /// Problems that did not stop the configuration loading, such as
+    /// unknown keys, for logging once logging has started.
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            listen_addr: "[::1]:50051".parse().expect("valid default address"),
+            additional_listen_addrs: Vec::new(),
+            bind_failure: BindFailure::default(),
+            health_addr: None,
+            access_log: None,
+            #[cfg(feature = "server")]
+            log_format: None,
+            self_check_probe_url: None,
+            shutdown_grace_secs: 10.0,
+            max_concurrent_requests: 0,
+            cache_max_entries: None,
+            max_cache_bytes: None,
+            cache_snapshot: None,
+            worker_threads: None,
+            max_blocking_threads: None,
+            chaos: None,
+            static_robots: None,
+            tls: None,
+            uds: None,
+            transport: TransportConfig::default(),
+            runtime: RuntimeConfig::default(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Read and validate a config file.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        Self::load_with_defaults(Some(path), &Table::new())
+    }
+
+    /// Read and validate a config file, taking any top-level setting it
+    /// leaves out from `defaults` (such as command-line flags), and letting
+    /// the [`env_settings`] and then `ROBOTS_SERVER_*` environment variables
+    /// override it. With no file, or a file that does not exist, `defaults`
+    /// are applied to the built-in configuration.
+    pub fn load_with_defaults(path: Option<&Path>, defaults: &Table) -> Result<Self, ConfigError> {
+        let vars: Vec<_> = std::env::vars().collect();
+        let (mut overrides, warnings) = env_settings(vars.iter().cloned());
+        overrides.extend(env_overrides(vars));
+        let mut config = Self::load_layered(path, &overrides, defaults)?;
+        config.warnings.extend(warnings);
+        Ok(config)
+    }
+
+    /// [`Config::load_with_defaults`] with the settings from the environment
+    /// given as `overrides`.
+    pub fn load_layered(
+        path: Option<&Path>,
+        overrides: &Table,
+        defaults: &Table,
+    ) -> Result<Self, ConfigError> {
+        let mut warnings = Vec::new();
+        let mut table = match path {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(contents) => contents.parse::<Table>()?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    warnings.push(format!(
+                        "Config file {} not found, using defaults",
+                        path.display()
+                    ));
+                    Table::new()
+                }
+                Err(e) => return Err(e.into()),
+            },
+            None => Table::new(),
+        };
+        table.extend(overrides.clone());
+        for (key, value) in defaults {
+            table.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        let mut config: Self = table.clone().try_into()?;
+        config.validate()?;
+        // Keys serde ignored are the ones missing once the config is written back
+        if let Ok(toml::Value::Table(known)) = toml::Value::try_from(&config) {
+            unknown_keys(&table, &known, "", &mut warnings);
+        }
+        config.warnings = warnings;
+        Ok(config)
+    }
+
+    /// Check the settings that parsing alone does not. Override files are
+    /// read and CIDRs parsed while the file is, so those are already known
+    /// to be good.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(access_log) = &self.access_log
+            && access_log.rotation == Rotation::MaxBytes(0)
+        {
+            return Err(ConfigError::Invalid(
+                "access_log max_bytes must be positive".to_string(),
+            ));
+        }
+        if let Some(url) = &self.self_check_probe_url {
+            NormalizedTarget::parse(url).map_err(|e| {
+                ConfigError::Invalid(format!("self_check_probe_url is not a valid URL: {e}"))
+            })?;
+        }
+        let grace = self.shutdown_grace_secs;
+        if !grace.is_finite() || grace < 0.0 {
+            return Err(ConfigError::Invalid(
+                "shutdown_grace_secs must be finite and non-negative".to_string(),
+            ));
+        }
+        if self.cache_max_entries == Some(0) {
+            return Err(ConfigError::Invalid(
+                "cache_max_entries must be positive".to_string(),
+            ));
+        }
+        if self.max_cache_bytes == Some(0) {
+            return Err(ConfigError::Invalid(
+                "max_cache_bytes must be positive".to_string(),
+            ));
+        }
+        if self.cache_max_entries.is_some() && self.max_cache_bytes.is_some() {
+            return Err(ConfigError::Invalid(
+                "cache_max_entries and max_cache_bytes cannot both be set".to_string(),
+            ));
+        }
+        if self.worker_threads == Some(0) || self.max_blocking_threads == Some(0) {
+            return Err(ConfigError::Invalid(
+                "worker_threads and max_blocking_threads must be positive".to_string(),
+            ));
+        }
+        if let Some(chaos) = &self.chaos {
+            chaos.validate()?;
+        }
+        self.transport.validate()?;
+        if let Some(static_robots) = &self.static_robots
+            && !static_robots.dir.is_dir()
+        {
+            return Err(ConfigError::Invalid(format!(
+                "static_robots dir {} is not a directory",
+                static_robots.dir.display()
+            )));
+        }
+        self.runtime.validate()
+    }
+
+    /// The settings only read at startup that differ in `other`, which need
+    /// a restart to take effect.
+    pub fn restart_required(&self, other: &Config) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        let mut compare = |name, differs| {
+            if differs {
+                changed.push(name);
+            }
+        };
+        compare("listen_addr", self.listen_addr != other.listen_addr);
+        compare(
+            "additional_listen_addrs",
+            self.additional_listen_addrs != other.additional_listen_addrs,
+        );
+        compare("bind_failure", self.bind_failure != other.bind_failure);
+        compare("health_addr", self.health_addr != other.health_addr);
+        compare("access_log", self.access_log != other.access_log);
+        #[cfg(feature = "server")]
+        compare("log_format", self.log_format != other.log_format);
+        compare(
+            "self_check_probe_url",
+            self.self_check_probe_url != other.self_check_probe_url,
+        );
+        compare(
+            "shutdown_grace_secs",
+            self.shutdown_grace_secs != other.shutdown_grace_secs,
+        );
+        compare(
+            "max_concurrent_requests",
+            self.max_concurrent_requests != other.max_concurrent_requests,
+        );
+        compare(
+            "cache_max_entries",
+            self.cache_max_entries != other.cache_max_entries,
+        );
+        compare(
+            "max_cache_bytes",
+            self.max_cache_bytes != other.max_cache_bytes,
+        );
+        compare(
+            "cache_snapshot",
+            self.cache_snapshot != other.cache_snapshot,
+        );
+        compare(
+            "worker_threads",
+            self.worker_threads != other.worker_threads,
+        );
+        compare(
+            "max_blocking_threads",
+            self.max_blocking_threads != other.max_blocking_threads,
+        );
+        compare("chaos", self.chaos != other.chaos);
+        compare("static_robots", self.static_robots != other.static_robots);
+        compare("tls", self.tls != other.tls);
+        compare("uds", self.uds != other.uds);
+        compare("transport", self.transport != other.transport);
+        changed
+    }
+
+    /// `listen_addr` followed by `additional_listen_addrs`.
+    pub fn listen_addrs(&self) -> Vec<SocketAddr> {
+        let mut addrs = vec![self.listen_addr];
+        for addr in &self.additional_listen_addrs {
+            if !addrs.contains(addr) {
+                addrs.push(*addr);
+            }
+        }
+        addrs
+    }
+
+    pub fn shutdown_grace(&self) -> Duration {
+        Duration::from_secs_f64(self.shutdown_grace_secs)
+    }
+
+    /// The configuration with API keys replaced, for printing.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        let runtime = &mut config.runtime;
+        for key in runtime
+            .api_keys
+            .iter_mut()
+            .chain(&mut runtime.admin_api_keys)
+        {
+            *key = redact::REDACTED.to_string();
+        }
+        config
+    }
+}
+
+/// The prefix of environment variables that override config file settings.
+pub const ENV_PREFIX: &str = "ROBOTS_SERVER_";
+
+/// The top-level settings given as `ROBOTS_SERVER_<NAME>` variables in
+/// `vars`, keyed by the lowercased `<NAME>`. Values are read as TOML, so
+/// `60`, `true` and `["a", "b"]` have their TOML types, and as a plain
+/// string when they are not valid TOML.
+pub fn env_overrides(vars: impl IntoIterator<Item = (String, String)>) -> Table {
+    vars.into_iter()
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix(ENV_PREFIX)?.to_ascii_lowercase();
+            let value = format!("value = {value}")
+                .parse::<Table>()
+                .ok()
+                .and_then(|mut table| table.remove("value"))
+                .unwrap_or(toml::Value::String(value));
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// The settings given as `ROBOTS_CACHE_TTL_SECS`, `ROBOTS_FETCH_TIMEOUT_SECS`
+/// or `ROBOTS_MAX_BODY_BYTES` variables in `vars`. A value that cannot be
+/// read is left out with a warning, so the setting keeps the value it would
+/// have had without the variable.
+pub fn env_settings(vars: impl IntoIterator<Item = (String, String)>) -> (Table, Vec<String>) {
+    let mut settings = Table::new();
+    let mut warnings = Vec::new();
+    for (name, value) in vars {
+        let (key, parse): (_, fn(&str) -> Option<toml::Value>) = match name.as_str() {
+            "ROBOTS_CACHE_TTL_SECS" => ("cache_ttl_secs", positive_integer),
+            "ROBOTS_FETCH_TIMEOUT_SECS" => ("fetch_timeout_secs", positive_number),
+            "ROBOTS_MAX_BODY_BYTES" => ("max_robots_txt_bytes", positive_integer),
+            _ => continue,
+        };
+        match parse(value.trim()) {
+            Some(parsed) => {
+                settings.insert(key.to_string(), parsed);
+            }
+            None => warnings.push(format!(
+                "Ignoring {name}={value:?}, expected a positive number"
+            )),
+        }
+    }
+    (settings, warnings)
+}
+
+fn positive_integer(value: &str) -> Option<toml::Value> {
+    value
+        .parse::<i64>()
+        .ok()
+        .filter(|n| *n > 0)
+        .map(toml::Value::from)
+}
+
+fn positive_number(value: &str) -> Option<toml::Value> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite() && *n > 0.0)
+        .map(toml::Value::from)
+}
+
+/// Add a warning for each key of `given` that is not in `known`, looking
+/// into tables both have.
+fn unknown_keys(given: &Table, known: &Table, prefix: &str, warnings: &mut Vec<String>) {
+    for (key, value) in given {
+        match (value, known.get(key)) {
+            (_, None) => warnings.push(format!("Ignoring unknown config key '{prefix}{key}'")),
+            (toml::Value::Table(given), Some(toml::Value::Table(known))) => {
+                unknown_keys(given, known, &format!("{prefix}{key}."), warnings);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// What to do when one of the addresses to serve on cannot be bound.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BindFailure {
+    /// Refuse to start.
+    #[default]
+    Error,
+    /// Log a warning and serve on the others, if any could be bound.
+    Warn,
+}
+
+/// When the active log file is moved aside and a new one started.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Rotation {
+    /// At the first write after midnight UTC.
+    Daily,
+    /// Before a write that would take the file past this many bytes.
+    MaxBytes(u64),
+}
+
+/// A log file that tracing events for some targets are written to, as
+/// configured in the `[access_log]` table.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct LogSinkConfig {
+    pub path: PathBuf,
+    pub rotation: Rotation,
+    /// Rotated files kept next to the active one, as `<path>.1` (newest)
+    /// through `<path>.<retain>`.
+    #[serde(default = "default_retain")]
+    pub retain: usize,
+    /// Targets routed to the file. Each also matches its child targets.
+    #[serde(default = "default_targets")]
+    pub targets: Vec<String>,
+}
+
+fn default_retain() -> usize {
+    7
+}
+
+fn default_targets() -> Vec<String> {
+    vec!["access".to_string()]
+}
+
+/// A corpus of saved robots.txt files, `<host>.robots.txt` in `dir`, served
+/// instead of fetching, as configured in the `[static_robots]` table.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct StaticRobotsConfig {
+    pub dir: PathBuf,
+    /// Fetch hosts without a file over the network rather than treating
+    /// them as 404.
+    #[serde(default)]
+    pub fetch_missing: bool,
+}
+
+/// PEM files for serving over TLS, as configured in the `[tls]` table.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct TlsConfig {
+    /// The certificate chain, leaf first.
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    /// CA certificates that client certificates must chain to. When set,
+    /// connections without a valid client certificate are refused.
+    #[serde(default)]
+    pub client_ca: Option<PathBuf>,
+}
+
+/// A Unix domain socket to serve on, as configured in the `[uds]` table.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct UdsConfig {
+    pub path: PathBuf,
+    /// Permission bits of the socket file.
+    #[serde(default = "default_uds_mode")]
+    pub mode: u32,
+    /// Serve on `listen_addr` over TCP as well.
+    #[serde(default = "default_uds_tcp")]
+    pub tcp: bool,
+}
+
+fn default_uds_mode() -> u32 {
+    0o660
+}
+
+fn default_uds_tcp() -> bool {
+    true
+}
+
+/// HTTP/2 and TCP settings for client connections, as configured in the
+/// `[transport]` table. Those left unset keep tonic's defaults.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct TransportConfig {
+    /// How often idle connections are sent an HTTP/2 PING, so load
+    /// balancers see traffic and dead peers are noticed.
+    pub http2_keepalive_interval_secs: Option<f64>,
+    /// How long a PING may go unanswered before the connection is closed.
+    pub http2_keepalive_timeout_secs: Option<f64>,
+    /// Idle time before TCP keepalive probes are sent.
+    pub tcp_keepalive_secs: Option<f64>,
+    /// HTTP/2 streams, and so calls, each connection may have open at once.
+    pub max_concurrent_streams: Option<u32>,
+    /// How long a call may run before it fails.
+    pub timeout_secs: Option<f64>,
+}
+
+impl TransportConfig {
+    pub fn http2_keepalive_interval(&self) -> Option<Duration> {
+        self.http2_keepalive_interval_secs
+            .map(Duration::from_secs_f64)
+    }
+
+    pub fn http2_keepalive_timeout(&self) -> Option<Duration> {
+        self.http2_keepalive_timeout_secs
+            .map(Duration::from_secs_f64)
+    }
+
+    pub fn tcp_keepalive(&self) -> Option<Duration> {
+        self.tcp_keepalive_secs.map(Duration::from_secs_f64)
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_secs.map(Duration::from_secs_f64)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let durations = [
+            self.http2_keepalive_interval_secs,
+            self.http2_keepalive_timeout_secs,
+            self.tcp_keepalive_secs,
+            self.timeout_secs,
+        ];
+        if durations
+            .into_iter()
+            .flatten()
+            .any(|secs| !secs.is_finite() || secs <= 0.0)
+        {
+            return Err(ConfigError::Invalid(
+                "transport durations must be positive".to_string(),
+            ));
+        }
+        if self.max_concurrent_streams == Some(0) {
+            return Err(ConfigError::Invalid(
+                "transport max_concurrent_streams must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Faults injected into fetches and cache calls, for rehearsing incidents
+/// in staging. Ignored unless the server is built with the `chaos` feature
+/// or started with `--enable-chaos`. Probabilities run from 0 to 1.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct ChaosConfig {
+    /// Seed for choosing which calls fail, so a run can be repeated.
+    pub seed: u64,
+    /// Chance a fetch fails as timed out.
+    pub fetch_timeout: f64,
+    /// Chance a fetch fails as if the origin answered 503.
+    pub fetch_server_error: f64,
+    /// Chance a fetch is held back by `fetch_latency_ms` first.
+    pub fetch_latency: f64,
+    pub fetch_latency_ms: u64,
+    /// Chance a cache lookup fails as if the backend were unavailable.
+    pub cache_get_error: f64,
+    /// Chance a cache write fails.
+    pub cache_set_error: f64,
+}
+
+impl ChaosConfig {
+    pub fn fetch_latency(&self) -> Duration {
+        Duration::from_millis(self.fetch_latency_ms)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let probabilities = [
+            self.fetch_timeout,
+            self.fetch_server_error,
+            self.fetch_latency,
+            self.cache_get_error,
+            self.cache_set_error,
+        ];
+        if probabilities.iter().any(|p| !(0.0..=1.0).contains(p)) {
+            return Err(ConfigError::Invalid(
+                "chaos probabilities must be between 0 and 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How IsAllowed decides for a robots.txt that was fetched but could not be
+/// understood.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnusablePolicy {
+    /// Treat it as an empty robots.txt, as RFC 9309 does.
+    #[default]
+    AllowAll,
+    /// Treat it as disallowing every path.
+    DenyAll,
+    /// Disallow every path until the host has served unusable content for
+    /// this many hours, then allow them.
+    DenyForHours(f64),
+}
+
+/// How IsAllowed answers for URLs whose scheme is not http or https.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsupportedSchemePolicy {
+    /// Fail with INVALID_ARGUMENT, as every RPC does.
+    #[default]
+    Reject,
+    /// Answer allowed, with the reason `UNSUPPORTED_SCHEME`.
+    Allow,
+    /// Answer disallowed, with the reason `UNSUPPORTED_SCHEME`.
+    Deny,
+}
+
+/// HTTP version spoken to origins.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpProtocol {
+    /// HTTP/2 when the origin offers it during the TLS handshake, HTTP/1.1
+    /// otherwise.
+    #[default]
+    Auto,
+    /// Always HTTP/1.1.
+    Http1Only,
+    /// HTTP/2 without negotiation, over plain HTTP too.
+    Http2PriorKnowledge,
+}
+
+/// Whether robots.txt is fetched over HTTP/3.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Http3Mode {
+    #[default]
+    Never,
+    /// HTTP/3 first for https origins, with HTTP/2 or HTTP/1.1 tried
+    /// alongside once it fails or has not answered after
+    /// `http3_fallback_delay_secs`. Needs the `http3` feature.
+    WithFallback,
+}
+
+/// How long a fetched robots.txt is cached, by how the fetch turned out, as
+/// configured in the `[ttl_policy]` table. Each class left unset is cached
+/// for `cache_ttl_secs`, or `negative_cache_ttl_secs` for 5xx responses and
+/// failed fetches, as every entry is without the table.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TtlPolicy {
+    /// 2xx responses, including unusable ones.
+    pub success_secs: Option<u64>,
+    /// Longest a 2xx response's `Cache-Control` max-age is followed for,
+    /// in place of `success_secs`. Unset ignores max-age.
+    pub success_max_secs: Option<u64>,
+    /// 404 and 410.
+    pub not_found_secs: Option<u64>,
+    /// 401 and 403.
+    pub forbidden_secs: Option<u64>,
+    /// 429.
+    pub rate_limited_secs: Option<u64>,
+    /// Longest a 429's `Retry-After` is followed for, in place of
+    /// `rate_limited_secs`. Unset ignores Retry-After.
+    pub rate_limited_max_secs: Option<u64>,
+    /// Other 4xx responses.
+    pub client_error_secs: Option<u64>,
+    /// 5xx responses.
+    pub server_error_secs: Option<u64>,
+    /// Timeouts, failed connections and unexpected statuses.
+    pub unreachable_secs: Option<u64>,
+}
+
+impl TtlPolicy {
+    /// How long to cache `data`, where `default` is the TTL of unset classes
+    /// and `negative` that of unset 5xx and unreachable classes.
+    pub fn ttl(&self, data: &RobotsData, default: Duration, negative: Duration) -> Duration {
+        let configured = |secs: Option<u64>| secs.map_or(default, Duration::from_secs);
+        let negative = |secs: Option<u64>| secs.map_or(negative, Duration::from_secs);
+        // What the origin asked for, when a cap makes it followed
+        let requested =
+            |max_secs: Option<u64>| Some(data.max_age?.min(Duration::from_secs(max_secs?)));
+        match (data.access_result, data.http_status_code) {
+            (AccessResult::Unavailable, 404 | 410) => configured(self.not_found_secs),
+            (AccessResult::Unavailable, 401 | 403) => configured(self.forbidden_secs),
+            (AccessResult::Unavailable, 429) => requested(self.rate_limited_max_secs)
+                .unwrap_or_else(|| configured(self.rate_limited_secs)),
+            (AccessResult::Unavailable | AccessResult::TooManyRedirects, _) => {
+                configured(self.client_error_secs)
+            }
+            (AccessResult::Unreachable, 500..=599) => negative(self.server_error_secs),
+            (AccessResult::Unreachable, _) => negative(self.unreachable_secs),
+            _ => requested(self.success_max_secs).unwrap_or_else(|| configured(self.success_secs)),
+        }
+    }
+
+    fn classes(&self) -> [Option<u64>; 9] {
+        [
+            self.success_secs,
+            self.success_max_secs,
+            self.not_found_secs,
+            self.forbidden_secs,
+            self.rate_limited_secs,
+            self.rate_limited_max_secs,
+            self.client_error_secs,
+            self.server_error_secs,
+            self.unreachable_secs,
+        ]
+    }
+}
+
+/// The part of the configuration that can change while the server runs.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    pub fetch_timeout_secs: f64,
+    /// Head start given to a host's preferred address family before its
+    /// other family is tried as well.
+    pub address_fallback_delay_secs: f64,
+    pub http_protocol: HttpProtocol,
+    /// HTTP version used instead of `http_protocol`, by host.
+    pub http_protocol_overrides: HashMap<String, HttpProtocol>,
+    pub http3: Http3Mode,
+    /// Head start given to HTTP/3 before the other HTTP versions are tried
+    /// as well.
+    pub http3_fallback_delay_secs: f64,
+    /// `User-Agent` header sent with outgoing requests.
+    pub fetch_user_agent: Option<String>,
+    /// Response headers kept with a fetched robots.txt, for diagnostics.
+    pub upstream_headers: Vec<String>,
+    /// Send the W3C trace context of the call that caused a robots.txt
+    /// fetch with it, to hosts in `trace_context_hosts`.
+    pub propagate_trace_context: bool,
+    /// Hosts (and their subdomains) sent the trace context of calls.
+    pub trace_context_hosts: Vec<String>,
+    pub cache_ttl_secs: u64,
+    pub header_cache_ttl_secs: u64,
+    /// How long 5xx responses, timeouts and failed connections are cached,
+    /// so that a host is not held up for long by a passing failure.
+    pub negative_cache_ttl_secs: u64,
+    /// Cache TTLs that differ from `cache_ttl_secs` by fetch outcome.
+    pub ttl_policy: TtlPolicy,
+    /// How long after a robots.txt expires it is still answered from while
+    /// it is refetched in the background. Zero waits for the refetch.
+    pub stale_while_revalidate_secs: u64,
+    /// Bytes of robots.txt read before the rest is ignored.
+    pub max_robots_txt_bytes: usize,
+    /// Sitemaps listed in a GetRobotsTxt response before the rest are left
+    /// out.
+    pub max_sitemaps: usize,
+    /// robots.txt fetches run at once, across all hosts.
+    pub max_concurrent_fetches: usize,
+    /// Fetches that may wait for a turn before more are refused.
+    pub fetch_queue_depth: usize,
+    /// Hosts each PrefetchHosts job fetches at once.
+    pub prefetch_concurrency: usize,
+    /// Hosts that may wait to be prefetched, across all jobs.
+    pub prefetch_queue_capacity: usize,
+    /// Items one batch call may carry, such as URLs given to PrefetchHosts.
+    pub max_batch_items: usize,
+    /// Shortest time between fetches of one robots.txt that RefreshRobots
+    /// will cause, unless forced. Zero disables the limit.
+    pub min_refresh_interval_secs: u64,
+    /// Cache fetched robots.txt as text and parse it when first needed,
+    /// rather than when it is fetched.
+    pub lazy_parse: bool,
+    pub host_rate: RateLimit,
+    /// Accepted API keys. Authentication is disabled when empty.
+    pub api_keys: Vec<String>,
+    /// API keys that may also make admin overrides, such as a forced
+    /// RefreshRobots. They are accepted wherever `api_keys` are, and turn
+    /// authentication on as they do.
+    pub admin_api_keys: Vec<String>,
+    /// Let ClearCache drop every cached robots.txt. Best paired with
+    /// `api_keys`, as any client that may call the service can use it.
+    pub allow_clear_cache: bool,
+    /// When non-empty, only these hosts (and their subdomains) may be fetched.
+    pub host_allowlist: Vec<String>,
+    /// Hosts (and their subdomains) that are never fetched.
+    pub host_blocklist: Vec<String>,
+    /// When non-empty, only clients connecting from these CIDR ranges may
+    /// call the service.
+    pub client_allowlist: Vec<IpNet>,
+    /// Whether clients on Unix domain sockets bypass `client_allowlist`.
+    pub trust_unix_sockets: bool,
+    /// Which events are logged to stderr, in place of `--log-filter` and
+    /// `RUST_LOG`, e.g. `robots_server=debug`.
+    pub log_filter: Option<String>,
+    /// Rate of hot-path debug events such as per-chunk and cache lines.
+    pub log_sampling: LogSampling,
+    /// How URLs are rewritten before they are logged.
+    pub url_redaction: UrlRedaction,
+    /// robots.txt to use instead of fetching, by host.
+    pub robots_overrides: HashMap<String, RobotsOverride>,
+    /// Rules checked before a host's robots.txt, by host.
+    pub policy_rules: HashMap<String, PolicyRules>,
+    /// Host whose cached robots.txt is used, by alias host.
+    pub host_aliases: HashMap<String, String>,
+    /// Treat `www.<host>` as an alias of `<host>`.
+    pub strip_www: bool,
+    /// Fetch an aliased host's robots.txt from its canonical host rather
+    /// than from the host asked about.
+    pub fetch_canonical_host: bool,
+    /// Decision for a robots.txt whose body could not be understood.
+    pub unusable_robots_txt: UnusablePolicy,
+    /// IsAllowed's answer for URLs that are not http(s).
+    pub unsupported_schemes: UnsupportedSchemePolicy,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            fetch_timeout_secs: 30.0,
+            address_fallback_delay_secs: 0.3,
+            http_protocol: HttpProtocol::default(),
+            http_protocol_overrides: HashMap::new(),
+            http3: Http3Mode::default(),
+            http3_fallback_delay_secs: 0.3,
+            fetch_user_agent: None,
+            upstream_headers: ["last-modified", "etag", "server", "age", "cache-control"]
+                .map(String::from)
+                .to_vec(),
+            propagate_trace_context: false,
+            trace_context_hosts: Vec::new(),
+            cache_ttl_secs: 24 * 60 * 60,
+            header_cache_ttl_secs: 5 * 60,
+            negative_cache_ttl_secs: 30 * 60,
+            ttl_policy: TtlPolicy::default(),
+            stale_while_revalidate_secs: 0,
+            max_robots_txt_bytes: MAX_ROBOTS_TXT_SIZE,
+            max_sitemaps: 1000,
+            max_concurrent_fetches: 256,
+            fetch_queue_depth: 4096,
+            prefetch_concurrency: 16,
+            prefetch_queue_capacity: 10_000,
+            max_batch_items: 10_000,
+            min_refresh_interval_secs: 60,
+            lazy_parse: false,
+            host_rate: RateLimit::default(),
+            api_keys: Vec::new(),
+            admin_api_keys: Vec::new(),
+            allow_clear_cache: false,
+            host_allowlist: Vec::new(),
+            host_blocklist: Vec::new(),
+            client_allowlist: Vec::new(),
+            trust_unix_sockets: true,
+            log_filter: None,
+            log_sampling: LogSampling::default(),
+            url_redaction: UrlRedaction::default(),
+            robots_overrides: HashMap::new(),
+            policy_rules: HashMap::new(),
+            host_aliases: HashMap::new(),
+            strip_www: false,
+            fetch_canonical_host: false,
+            unusable_robots_txt: UnusablePolicy::default(),
+            unsupported_schemes: UnsupportedSchemePolicy::default(),
+        }
+    }
+}
+
+impl RuntimeConfig {
+    pub fn fetch_timeout(&self) -> Duration {
+        Duration::from_secs_f64(self.fetch_timeout_secs)
+    }
+
+    pub fn address_fallback_delay(&self) -> Duration {
+        Duration::from_secs_f64(self.address_fallback_delay_secs)
+    }
+
+    pub fn http3_fallback_delay(&self) -> Duration {
+        Duration::from_secs_f64(self.http3_fallback_delay_secs)
+    }
+
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache_ttl_secs)
+    }
+
+    pub fn header_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.header_cache_ttl_secs)
+    }
+
+    pub fn negative_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.negative_cache_ttl_secs)
+    }
+
+    pub fn stale_while_revalidate(&self) -> Duration {
+        Duration::from_secs(self.stale_while_revalidate_secs)
+    }
+
+    pub fn min_refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.min_refresh_interval_secs)
+    }
+
+    /// How long to cache `data` under `ttl_policy`.
+    pub fn entry_ttl(&self, data: &RobotsData) -> Duration {
+        self.ttl_policy
+            .ttl(data, self.cache_ttl(), self.negative_cache_ttl())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !self.fetch_timeout_secs.is_finite() || self.fetch_timeout_secs <= 0.0 {
+            return Err(ConfigError::Invalid(
+                "fetch_timeout_secs must be positive".to_string(),
+            ));
+        }
+        #[cfg(feature = "server")]
+        if let Some(filter) = &self.log_filter {
+            tracing_subscriber::EnvFilter::try_new(filter)
+                .map_err(|e| ConfigError::Invalid(format!("log_filter is invalid: {e}")))?;
+        }
+        let delay = self.address_fallback_delay_secs;
+        if !delay.is_finite() || delay < 0.0 {
+            return Err(ConfigError::Invalid(
+                "address_fallback_delay_secs must be finite and non-negative".to_string(),
+            ));
+        }
+        let delay = self.http3_fallback_delay_secs;
+        if !delay.is_finite() || delay < 0.0 {
+            return Err(ConfigError::Invalid(
+                "http3_fallback_delay_secs must be finite and non-negative".to_string(),
+            ));
+        }
+        if self.http3 != Http3Mode::Never && !cfg!(feature = "http3") {
+            return Err(ConfigError::Invalid(
+                "http3 needs the server built with the http3 feature".to_string(),
+            ));
+        }
+        if self.cache_ttl_secs == 0
+            || self.header_cache_ttl_secs == 0
+            || self.negative_cache_ttl_secs == 0
+        {
+            return Err(ConfigError::Invalid(
+                "cache TTLs must be positive".to_string(),
+            ));
+        }
+        if self.ttl_policy.classes().contains(&Some(0)) {
+            return Err(ConfigError::Invalid(
+                "ttl_policy TTLs must be positive".to_string(),
+            ));
+        }
+        if self.max_robots_txt_bytes == 0 {
+            return Err(ConfigError::Invalid(
+                "max_robots_txt_bytes must be positive".to_string(),
+            ));
+        }
+        if self.max_sitemaps == 0 {
+            return Err(ConfigError::Invalid(
+                "max_sitemaps must be positive".to_string(),
+            ));
+        }
+        if self.max_concurrent_fetches == 0 || self.fetch_queue_depth == 0 {
+            return Err(ConfigError::Invalid(
+                "max_concurrent_fetches and fetch_queue_depth must be positive".to_string(),
+            ));
+        }
+        if self.prefetch_concurrency == 0 || self.prefetch_queue_capacity == 0 {
+            return Err(ConfigError::Invalid(
+                "prefetch_concurrency and prefetch_queue_capacity must be positive".to_string(),
+            ));
+        }
+        if self.max_batch_items == 0 {
+            return Err(ConfigError::Invalid(
+                "max_batch_items must be positive".to_string(),
+            ));
+        }
+        let rate = self.host_rate;
+        if !rate.per_second.is_finite() || rate.per_second < 0.0 || !rate.burst.is_finite() {
+            return Err(ConfigError::Invalid(
+                "host_rate must be finite and non-negative".to_string(),
+            ));
+        }
+        let interval = self.log_sampling.interval_secs;
+        if !interval.is_finite() || interval < 0.0 {
+            return Err(ConfigError::Invalid(
+                "log_sampling interval_secs must be finite and non-negative".to_string(),
+            ));
+        }
+        if let UnusablePolicy::DenyForHours(hours) = self.unusable_robots_txt
+            && (!hours.is_finite() || hours < 0.0)
+        {
+            return Err(ConfigError::Invalid(
+                "unusable_robots_txt deny_for_hours must be finite and non-negative".to_string(),
+            ));
+        }
+        if self
+            .upstream_headers
+            .iter()
+            .any(|name| HeaderName::from_bytes(name.as_bytes()).is_err())
+        {
+            return Err(ConfigError::Invalid(
+                "upstream_headers must be valid header names".to_string(),
+            ));
+        }
+        if self.api_keys.iter().any(|k| k.trim().is_empty()) {
+            return Err(ConfigError::Invalid(
+                "api_keys must not be empty".to_string(),
+            ));
+        }
+        if self.admin_api_keys.iter().any(|k| k.trim().is_empty()) {
+            return Err(ConfigError::Invalid(
+                "admin_api_keys must not be empty".to_string(),
+            ));
+        }
+        if self
+            .host_allowlist
+            .iter()
+            .chain(&self.host_blocklist)
+            .any(|h| h.trim().is_empty())
+        {
+            return Err(ConfigError::Invalid(
+                "host list entries must not be empty".to_string(),
+            ));
+        }
+        if self
+            .robots_overrides
+            .keys()
+            .chain(self.policy_rules.keys())
+            .any(|h| h.trim().is_empty())
+        {
+            return Err(ConfigError::Invalid(
+                "robots_overrides and policy_rules hosts must not be empty".to_string(),
+            ));
+        }
+        if self
+            .host_aliases
+            .iter()
+            .any(|(alias, host)| alias.trim().is_empty() || host.trim().is_empty())
+        {
+            return Err(ConfigError::Invalid(
+                "host_aliases hosts must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Apply the logging settings, which are process-wide rather than read
+    /// through a handle. Done on load and on every reload.
+    pub fn apply_to_logging(&self) {
+        log_sampling::configure(&self.log_sampling);
+        redact::configure(&self.url_redaction);
+        #[cfg(feature = "server")]
+        log_format::set_filter(self.log_filter.as_deref());
+    }
+
+    /// Whether calls must carry an API key.
+    pub fn requires_api_key(&self) -> bool {
+        !self.api_keys.is_empty() || !self.admin_api_keys.is_empty()
+    }
+
+    pub fn accepts_api_key(&self, key: &str) -> bool {
+        self.api_keys.iter().any(|k| k == key) || self.accepts_admin_key(key)
+    }
+
+    /// Whether `key` is one of `admin_api_keys`.
+    pub fn accepts_admin_key(&self, key: &str) -> bool {
+        self.admin_api_keys.iter().any(|k| k == key)
+    }
+
+    /// Whether `host` passes the allowlist and blocklist. Entries match the
+    /// host itself and any of its subdomains.
+    pub fn host_permitted(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+        let matches = |entry: &String| {
+            let entry = entry.to_lowercase();
+            host == entry || host.ends_with(&format!(".{entry}"))
+        };
+        if self.host_blocklist.iter().any(matches) {
+            return false;
+        }
+        self.host_allowlist.is_empty() || self.host_allowlist.iter().any(matches)
+    }
+
+    /// Whether fetches from `host` carry the trace context of the call that
+    /// caused them. Entries match the host itself and any of its subdomains.
+    pub fn propagates_trace_context(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+        self.propagate_trace_context
+            && self.trace_context_hosts.iter().any(|entry| {
+                let entry = entry.to_lowercase();
+                host == entry || host.ends_with(&format!(".{entry}"))
+            })
+    }
+
+    /// The robots.txt override for exactly `host`, if one is configured.
+    pub fn robots_override(&self, host: &str) -> Option<&RobotsOverride> {
+        self.robots_overrides
+            .iter()
+            .find(|(h, _)| h.eq_ignore_ascii_case(host))
+            .map(|(_, robots_override)| robots_override)
+    }
+
+    /// The HTTP version to use with exactly `host`.
+    pub fn http_protocol(&self, host: &str) -> HttpProtocol {
+        self.http_protocol_overrides
+            .iter()
+            .find(|(h, _)| h.eq_ignore_ascii_case(host))
+            .map_or(self.http_protocol, |(_, protocol)| *protocol)
+    }
+
+    /// Whether a fetch of `url` tries HTTP/3 first: only for https, and
+    /// only where `http_protocol` for the host leaves the version to
+    /// negotiation.
+    pub fn tries_http3(&self, url: &Url) -> bool {
+        self.http3 == Http3Mode::WithFallback
+            && url.scheme() == "https"
+            && self.http_protocol(url.host_str().unwrap_or_default()) == HttpProtocol::Auto
+    }
+
+    /// The supplemental rules configured for exactly `host`, if any.
+    pub fn policy_rules(&self, host: &str) -> Option<&PolicyRules> {
+        self.policy_rules
+            .iter()
+            .find(|(h, _)| h.eq_ignore_ascii_case(host))
+            .map(|(_, rules)| rules)
+    }
+
+    /// The host whose robots.txt cache entry `host` shares, when it is an
+    /// alias of another: its entry in `host_aliases`, compared exactly, or
+    /// with `strip_www` the host without a leading `www.`.
+    pub fn canonical_host(&self, host: &str) -> Option<String> {
+        let canonical = self
+            .host_aliases
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(host))
+            .map(|(_, canonical)| canonical.to_lowercase())
+            .or_else(|| {
+                host.strip_prefix("www.")
+                    .filter(|_| self.strip_www)
+                    .map(str::to_string)
+            })?;
+        (!canonical.eq_ignore_ascii_case(host)).then_some(canonical)
+    }
+
+    /// Whether a client connecting from `addr` passes `client_allowlist`.
+    pub fn client_permitted(&self, addr: IpAddr) -> bool {
+        // IPv4 clients of a dual-stack listener show up as IPv4-mapped IPv6
+        let addr = addr.to_canonical();
+        self.client_allowlist.is_empty() || self.client_allowlist.iter().any(|n| n.contains(&addr))
+    }
+}
+
+/// Shared, hot-swappable handle to the runtime configuration. Components
+/// keep a clone and read through it on every use.
+#[derive(Clone)]
+pub struct ConfigHandle {
+    path: Option<PathBuf>,
+    defaults: Arc<Table>,
+    current: Arc<ArcSwap<RuntimeConfig>>,
+    /// The configuration loaded at startup, to tell which changed settings
+    /// are not applied by a reload.
+    started_with: Arc<Config>,
+}
+
+impl ConfigHandle {
+    /// A handle with fixed settings and no file to reload from.
+    pub fn new(runtime: RuntimeConfig) -> Self {
+        Self {
+            path: None,
+            defaults: Arc::default(),
+            current: Arc::new(ArcSwap::from_pointee(runtime)),
+            started_with: Arc::default(),
+        }
+    }
+
+    /// Load `path` and keep it as the source for later reloads.
+    pub fn from_file(path: impl Into<PathBuf>) -> Result<(Config, Self), ConfigError> {
+        Self::load(Some(path.into()), Table::new())
+    }
+
+    /// Load `path`, if any, over `defaults`. Both are kept so a reload
+    /// applies the same defaults to the re-read file.
+    pub fn load(path: Option<PathBuf>, defaults: Table) -> Result<(Config, Self), ConfigError> {
+        let config = Config::load_with_defaults(path.as_deref(), &defaults)?;
+        config.runtime.apply_to_logging();
+        let handle = Self {
+            path,
+            defaults: Arc::new(defaults),
+            current: Arc::new(ArcSwap::from_pointee(config.runtime.clone())),
+            started_with: Arc::new(config.clone()),
+        };
+        Ok((config, handle))
+    }
+
+    pub fn get(&self) -> Arc<RuntimeConfig> {
+        self.current.load_full()
+    }
+
+    /// Re-read the config file. An invalid file is rejected as a whole and
+    /// the previous configuration stays in effect.
+    #[instrument(skip(self), fields(path = ?self.path))]
+    pub fn reload(&self) -> Result<(), ConfigError> {
+        let path = self.path.as_ref().ok_or(ConfigError::NoFile)?;
+        // A file moved away keeps the running settings rather than resetting them
+        let loaded = match path.try_exists() {
+            Ok(true) => Config::load_with_defaults(Some(path), &self.defaults),
+            Ok(false) => Err(std::io::Error::from(std::io::ErrorKind::NotFound).into()),
+            Err(e) => Err(e.into()),
+        };
+        match loaded {
+            Ok(config) => {
+                for warning in &config.warnings {
+                    warn!("{warning}");
+                }
+                for setting in self.started_with.restart_required(&config) {
+                    warn!(setting, "Setting changed, but only takes effect on restart");
+                }
+                config.runtime.apply_to_logging();
+                self.current.store(Arc::new(config.runtime));
+                info!("Reloaded configuration");
+                Ok(())
+            }
+            Err(e) => {
+                error!(error = %e, "Rejected new configuration, keeping previous");
+                Err(e)
+            }
+        }
+    }
+
+    /// Reload the configuration every time the process receives SIGHUP.
+    #[cfg(unix)]
+    pub fn reload_on_sighup(&self) -> std::io::Result<tokio::task::JoinHandle<()>> {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut hangup = signal(SignalKind::hangup())?;
+        let handle = self.clone();
+        Ok(tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                info!("Received SIGHUP, reloading configuration");
+                // Failures are logged by reload and the old config is kept
+                let _ = handle.reload();
+            }
+        }))
+    }
+}
+
+impl Default for ConfigHandle {
+    fn default() -> Self {
+        Self::new(RuntimeConfig::default())
+    }
+}
+
+/// Moka expiry policy that reads its TTL from the live configuration, so a
+/// reload applies to every entry inserted afterwards.
+pub struct ConfiguredTtl {
+    config: ConfigHandle,
+    ttl: fn(&RuntimeConfig) -> Duration,
+}
+
+impl ConfiguredTtl {
+    pub fn new(config: ConfigHandle, ttl: fn(&RuntimeConfig) -> Duration) -> Self {
+        Self { config, ttl }
+    }
+}
+
+impl<K, V> Expiry<K, V> for ConfiguredTtl {
+    fn expire_after_create(&self, _key: &K, _value: &V, _created_at: Instant) -> Option<Duration> {
+        Some((self.ttl)(&self.config.get()))
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &K,
+        _value: &V,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        Some((self.ttl)(&self.config.get()))
+    }
+}
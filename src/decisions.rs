@@ -0,0 +1,97 @@
+use std::pin::Pin;
+
+use futures_util::{Stream, stream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tonic::Status;
+use tracing::debug;
+
+use crate::service::robots::v1::{DecisionEvent, StreamDecisionsRequest};
+
+/// Events buffered per subscriber before the oldest are dropped.
+pub const DEFAULT_DECISION_CAPACITY: usize = 1024;
+
+pub type DecisionStream = Pin<Box<dyn Stream<Item = Result<DecisionEvent, Status>> + Send>>;
+
+/// Fan-out of IsAllowed decisions to StreamDecisions subscribers.
+///
+/// Publishing never waits on subscribers: one that falls more than the
+/// channel capacity behind loses the oldest events and is told how many on
+/// the next event it receives.
+#[derive(Clone)]
+pub struct DecisionFeed {
+    sender: broadcast::Sender<DecisionEvent>,
+}
+
+impl DecisionFeed {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: DecisionEvent) {
+        // An error only means nobody is subscribed
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self, filter: StreamDecisionsRequest) -> DecisionStream {
+        let filter = DecisionFilter::from(filter);
+        let receiver = self.sender.subscribe();
+        Box::pin(stream::unfold(
+            (receiver, filter),
+            |(mut receiver, filter)| async move {
+                let mut dropped = 0;
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) if filter.matches(&event) => {
+                            let event = DecisionEvent { dropped, ..event };
+                            return Some((Ok(event), (receiver, filter)));
+                        }
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(count)) => {
+                            debug!(count, "Decision subscriber lagged, events dropped");
+                            dropped += count;
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        ))
+    }
+}
+
+impl Default for DecisionFeed {
+    fn default() -> Self {
+        Self::new(DEFAULT_DECISION_CAPACITY)
+    }
+}
+
+struct DecisionFilter {
+    host_suffix: String,
+    allowed: Option<bool>,
+}
+
+impl DecisionFilter {
+    fn matches(&self, event: &DecisionEvent) -> bool {
+        if self.allowed.is_some_and(|allowed| allowed != event.allowed) {
+            return false;
+        }
+        self.host_suffix.is_empty()
+            || event.host == self.host_suffix
+            || event
+                .host
+                .strip_suffix(&self.host_suffix)
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    }
+}
+
+impl From<StreamDecisionsRequest> for DecisionFilter {
+    fn from(value: StreamDecisionsRequest) -> Self {
+        Self {
+            host_suffix: value
+                .host_suffix
+                .trim_start_matches('.')
+                .to_ascii_lowercase(),
+            allowed: value.allowed,
+        }
+    }
+}
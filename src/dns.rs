@@ -0,0 +1,83 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+/// The IP version of an origin address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressFamily {
+    V4,
+    V6,
+}
+
+impl AddressFamily {
+    pub fn of(addr: &SocketAddr) -> Self {
+        match addr {
+            SocketAddr::V4(_) => Self::V4,
+            SocketAddr::V6(_) => Self::V6,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V4 => "ipv4",
+            Self::V6 => "ipv6",
+        }
+    }
+}
+
+impl fmt::Display for AddressFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Resolves names with the operating system's resolver, in the order it
+/// prefers them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl Resolve for SystemResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0)).await?;
+            Ok(Box::new(addrs) as Addrs)
+        })
+    }
+}
+
+/// Passes on what `inner` resolves, keeping only the addresses of `family`
+/// when one is given.
+pub(crate) struct FamilyResolver {
+    pub(crate) inner: Arc<dyn Resolve>,
+    pub(crate) family: Option<AddressFamily>,
+}
+
+impl Resolve for FamilyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolving = self.inner.resolve(name);
+        let Some(family) = self.family else {
+            return resolving;
+        };
+        Box::pin(async move {
+            let addrs = resolving.await?;
+            Ok(Box::new(addrs.filter(move |addr| AddressFamily::of(addr) == family)) as Addrs)
+        })
+    }
+}
+
+/// The address families `name` resolves to, in order of preference.
+pub(crate) async fn families(resolver: &dyn Resolve, name: Name) -> Vec<AddressFamily> {
+    let Ok(addrs) = resolver.resolve(name).await else {
+        return Vec::new();
+    };
+    let mut families = Vec::with_capacity(2);
+    for addr in addrs {
+        let family = AddressFamily::of(&addr);
+        if !families.contains(&family) {
+            families.push(family);
+        }
+    }
+    families
+}
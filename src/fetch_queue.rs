@@ -0,0 +1,187 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use thiserror::Error;
+use tokio::sync::oneshot;
+use tracing::debug;
+
+use crate::config::ConfigHandle;
+use crate::metrics;
+
+/// How urgently a fetch is needed. Waiting fetches start in this order, and
+/// in the order they arrived within a class.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// A caller is waiting on the answer.
+    #[default]
+    Interactive,
+    /// Part of a bulk job, which can wait behind interactive calls.
+    Batch,
+    /// Nobody is waiting, as when warming the cache.
+    Background,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [Priority::Interactive, Priority::Batch, Priority::Background];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Priority::Interactive => "interactive",
+            Priority::Batch => "batch",
+            Priority::Background => "background",
+        }
+    }
+
+    /// The priority named `name`, as sent in `x-fetch-priority` metadata.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|priority| priority.as_str().eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("Fetch queue is full ({depth} fetches waiting)")]
+pub struct QueueFull {
+    pub depth: usize,
+}
+
+/// Admits up to `max_concurrent_fetches` fetches at a time. Beyond that,
+/// up to `fetch_queue_depth` fetches wait for a turn and the rest are
+/// turned away. Clones share one queue.
+#[derive(Clone)]
+pub struct FetchQueue {
+    config: ConfigHandle,
+    state: Arc<Mutex<QueueState>>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    running: usize,
+    /// Fetches waiting for a turn, by priority.
+    waiting: [VecDeque<oneshot::Sender<FetchPermit>>; 3],
+}
+
+impl QueueState {
+    fn depth(&self) -> usize {
+        self.waiting.iter().map(VecDeque::len).sum()
+    }
+
+    fn record_depth(&self) {
+        for (priority, waiting) in Priority::ALL.iter().zip(&self.waiting) {
+            metrics::FETCH_QUEUE_DEPTH
+                .with_label_values(&[priority.as_str()])
+                .set(waiting.len() as i64);
+        }
+    }
+}
+
+impl FetchQueue {
+    pub fn new(config: ConfigHandle) -> Self {
+        Self {
+            config,
+            state: Arc::default(),
+        }
+    }
+
+    /// Fetches holding a turn.
+    pub fn running(&self) -> usize {
+        self.state().running
+    }
+
+    /// Fetches waiting for a turn, not counting any whose caller gave up.
+    pub fn waiting(&self) -> usize {
+        let state = self.state();
+        state
+            .waiting
+            .iter()
+            .flatten()
+            .filter(|sender| !sender.is_closed())
+            .count()
+    }
+
+    /// Wait for a turn to fetch. The turn lasts until the permit is dropped,
+    /// when it passes to the longest-waiting fetch of the highest priority.
+    pub async fn acquire(&self, priority: Priority) -> Result<FetchPermit, QueueFull> {
+        let started = Instant::now();
+        let receiver = {
+            let config = self.config.get();
+            let mut state = self.state();
+            if state.running < config.max_concurrent_fetches {
+                state.running += 1;
+                None
+            } else {
+                // Callers that gave up no longer hold a place
+                for waiting in &mut state.waiting {
+                    waiting.retain(|sender| !sender.is_closed());
+                }
+                let depth = state.depth();
+                if depth >= config.fetch_queue_depth {
+                    debug!(depth, priority = priority.as_str(), "Fetch queue full");
+                    metrics::FETCH_QUEUE_REJECTED
+                        .with_label_values(&[priority.as_str()])
+                        .inc();
+                    return Err(QueueFull { depth });
+                }
+                let (sender, receiver) = oneshot::channel();
+                state.waiting[priority as usize].push_back(sender);
+                state.record_depth();
+                Some(receiver)
+            }
+        };
+        let permit = match receiver {
+            Some(receiver) => receiver
+                .await
+                .expect("waiting fetches are only dropped after their caller"),
+            None => FetchPermit {
+                queue: Some(self.clone()),
+            },
+        };
+        metrics::FETCH_QUEUE_WAIT
+            .with_label_values(&[priority.as_str()])
+            .observe(started.elapsed().as_secs_f64());
+        Ok(permit)
+    }
+
+    /// Pass a finished fetch's turn on, or give it up when nothing is
+    /// waiting or the limit has been lowered since.
+    fn release(&self) {
+        let max_concurrent = self.config.get().max_concurrent_fetches;
+        let mut state = self.state();
+        if state.running <= max_concurrent {
+            while let Some(sender) = state.waiting.iter_mut().find_map(VecDeque::pop_front) {
+                let permit = FetchPermit {
+                    queue: Some(self.clone()),
+                };
+                match sender.send(permit) {
+                    Ok(()) => {
+                        state.record_depth();
+                        return;
+                    }
+                    // The caller gave up; the turn is still this one's to pass
+                    Err(mut permit) => permit.queue = None,
+                }
+            }
+            state.record_depth();
+        }
+        state.running -= 1;
+    }
+
+    fn state(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A turn to fetch, given back to the queue when dropped.
+pub struct FetchPermit {
+    queue: Option<FetchQueue>,
+}
+
+impl Drop for FetchPermit {
+    fn drop(&mut self) {
+        if let Some(queue) = self.queue.take() {
+            queue.release();
+        }
+    }
+}
@@ -1,21 +1,73 @@
-use crate::robots_data::RobotsData;
-use crate::service::robots::AccessResult;
+use crate::clock::{self, Clock};
+use crate::config::{ConfigHandle, HttpProtocol, RuntimeConfig};
+use crate::dns::{self, AddressFamily, FamilyResolver, SystemResolver};
+use crate::log_sampling::LogSampler;
+use crate::metrics;
+use crate::parser;
+use crate::redact;
+use crate::robots_data::{AccessResult, RobotsData};
+use crate::target::{NormalizedTarget, TargetError};
+use crate::trace_context::TraceContext;
+use crate::x_robots_tag::XRobotsTagHeaders;
+use async_trait::async_trait;
 use futures_util::StreamExt;
-use reqwest::{Client, redirect::Policy};
-use robotstxt_rs::RobotsTxt;
-use std::time::Duration;
+#[cfg(feature = "http3")]
+use reqwest::Version;
+use reqwest::{
+    Client, RequestBuilder, Response, StatusCode,
+    dns::{Name, Resolve},
+    header::{AGE, CACHE_CONTROL, HeaderMap, RANGE, RETRY_AFTER, USER_AGENT},
+    redirect::Policy,
+};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
 use thiserror::Error;
 use tracing::{debug, info, instrument};
 use url::Url;
 
-const MAX_ROBOTS_TXT_SIZE: usize = 550 * 1024;
+// Logged once per body chunk, so sampled
+static STREAMED_CHUNK: LogSampler = LogSampler::new("fetcher.chunk");
 
-#[derive(Error, Debug, PartialEq, Eq)]
+/// Redirects followed before a fetch fails.
+const MAX_REDIRECTS: usize = 5;
+
+tokio::task_local! {
+    /// Redirects followed by the request being sent from this task.
+    static REDIRECTS: Cell<usize>;
+    /// The origin address the request being sent from this task connected
+    /// to, once it has a response.
+    static REMOTE_ADDRESS: Cell<Option<SocketAddr>>;
+}
+
+/// Default for the bytes of robots.txt read before the rest is ignored.
+pub const MAX_ROBOTS_TXT_SIZE: usize = 550 * 1024;
+
+/// Bytes of an upstream header value kept before the rest is cut.
+pub const MAX_UPSTREAM_HEADER_VALUE: usize = 256;
+
+/// Bytes of names and values kept across all of a response's upstream
+/// headers. Headers past this are left out.
+pub const MAX_UPSTREAM_HEADERS_BYTES: usize = 2048;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FetchError {
     #[error("Too many redirects")]
     TooManyRedirects,
+    /// A redirect led back to a URL already visited.
+    #[error("Redirect loop")]
+    RedirectLoop,
     #[error("Robots.txt unavailable: HTTP {0}")]
     Unavailable(u16),
+    /// A 429, with how long its `Retry-After` asked to wait.
+    #[error("Robots.txt unavailable: HTTP 429")]
+    RateLimited(Option<Duration>),
     #[error("Server unreachable: {}", 0.0)]
     Unreachable((String, Option<u16>)),
     #[error("Request timeout")]
@@ -26,36 +78,483 @@ pub enum FetchError {
     InvalidUrl(String),
 }
 
+impl From<TargetError> for FetchError {
+    fn from(value: TargetError) -> Self {
+        FetchError::InvalidUrl(value.to_string())
+    }
+}
+
+/// A robots.txt body as served, before parsing.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RobotsText {
+    pub robots_txt_url: String,
+    pub http_status_code: u32,
+    pub content_length_bytes: u64,
+    pub body: String,
+    pub truncated: bool,
+    pub fetched_at: SystemTime,
+    /// The HTTP version the origin answered with, such as `HTTP/1.1`.
+    pub http_version: String,
+    /// The response headers named in `upstream_headers`.
+    pub upstream_headers: BTreeMap<String, String>,
+    /// The response's `Cache-Control` max-age, less its `Age`.
+    pub max_age: Option<Duration>,
+    /// The origin address the response came from, when fetched over a
+    /// connection.
+    pub resolved_address: Option<SocketAddr>,
+}
+
+impl RobotsText {
+    /// Parse the body into rules, recording where it came from and how it
+    /// was served.
+    pub fn parse(&self, target_url: &str) -> RobotsData {
+        let mut data = parser::parse(&self.body);
+        data.content_length_bytes = self.content_length_bytes;
+        data.robots_txt_url = self.robots_txt_url.clone();
+        data.target_url = target_url.to_string();
+        data.http_status_code = self.http_status_code;
+        // Lines were there but none was understood, as with binary or HTML
+        // served as text/plain, so the empty rule set means nothing
+        let understood = !data.groups.is_empty() || !data.sitemaps.is_empty();
+        data.access_result = if !understood && !data.warnings.is_empty() {
+            AccessResult::Unusable
+        } else {
+            AccessResult::Success
+        };
+        data.truncated = self.truncated;
+        data.fetched_at = Some(self.fetched_at);
+        data.http_version = Some(self.http_version.clone());
+        data.upstream_headers = self.upstream_headers.clone();
+        data.max_age = self.max_age;
+        data.resolved_address = self.resolved_address;
+        data
+    }
+}
+
+/// Run `fetch`, along with the origin address it connected to when it got
+/// as far as a response, so failed fetches can report it too. Only
+/// [`RobotsFetcher`] reports addresses.
+pub async fn with_remote_address<T>(fetch: impl Future<Output = T>) -> (T, Option<SocketAddr>) {
+    REMOTE_ADDRESS
+        .scope(Cell::new(None), async {
+            let result = fetch.await;
+            (result, REMOTE_ADDRESS.with(Cell::get))
+        })
+        .await
+}
+
+/// Where the server gets robots.txt and `X-Robots-Tag` headers from.
+/// [`RobotsFetcher`] fetches them over HTTP; other implementations stand in
+/// for origins in tests.
+#[async_trait]
+pub trait Fetcher: Clone + Send + Sync + 'static {
+    /// Fetch and parse robots.txt for `target_url`.
+    async fn fetch(&self, target_url: &str) -> Result<RobotsData, FetchError>;
+
+    /// Fetch the robots.txt body for `target_url` without parsing it.
+    async fn fetch_text(&self, target_url: &str) -> Result<RobotsText, FetchError>;
+
+    /// Fetch the `X-Robots-Tag` headers `target_url` is served with.
+    async fn fetch_x_robots_tag(&self, target_url: &str) -> Result<XRobotsTagHeaders, FetchError>;
+
+    fn stats(&self) -> &FetchStats;
+
+    /// The clock fetches are timestamped with.
+    fn clock(&self) -> &Arc<dyn Clock>;
+
+    /// Timestamp fetches with `clock`.
+    fn with_clock(self, clock: Arc<dyn Clock>) -> Self;
+}
+
+/// Collects a streamed robots.txt body, keeping at most `limit` bytes and
+/// cutting at the last complete line when the limit is hit. A body with no
+/// newline before the limit is kept up to exactly `limit` bytes. Bytes are
+/// decoded once, when finished, so characters split across chunks survive.
+#[derive(Debug)]
+pub struct CappedBody {
+    body: Vec<u8>,
+    limit: usize,
+    truncated: bool,
+}
+
+impl CappedBody {
+    /// A body capped at [`MAX_ROBOTS_TXT_SIZE`].
+    pub fn new() -> Self {
+        Self::with_limit(MAX_ROBOTS_TXT_SIZE)
+    }
+
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            body: Vec::new(),
+            limit,
+            truncated: false,
+        }
+    }
+
+    /// Reserve room for a body of `content_length` bytes, as the response
+    /// announced, up to the limit.
+    pub fn with_content_length(mut self, content_length: u64) -> Self {
+        let capacity =
+            usize::try_from(content_length).map_or(self.limit, |len| len.min(self.limit));
+        self.body.reserve_exact(capacity);
+        self
+    }
+
+    /// Append the next chunk. Returns `false` once the limit is reached and
+    /// the rest of the body should be ignored.
+    pub fn push(&mut self, chunk: &[u8]) -> bool {
+        if self.truncated {
+            return false;
+        }
+        let remaining = self.limit - self.body.len();
+        if chunk.len() <= remaining {
+            self.body.extend_from_slice(chunk);
+            return true;
+        }
+        self.truncated = true;
+        self.body.extend_from_slice(&chunk[..remaining]);
+        // Searched once, here, rather than in every chunk
+        if let Some(last_nl) = self.body.iter().rposition(|&b| b == b'\n') {
+            self.body.truncate(last_nl + 1);
+        }
+        false
+    }
+
+    /// The body read so far and whether it was truncated.
+    pub fn finish(self) -> (String, bool) {
+        let body = String::from_utf8(self.body)
+            .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+        (body, self.truncated)
+    }
+}
+
+impl Default for CappedBody {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone)]
 pub struct RobotsFetcher {
-    client: reqwest::Client,
+    clients: Clients,
+    http1_clients: Clients,
+    http2_clients: Clients,
+    #[cfg(feature = "http3")]
+    http3_client: Client,
+    resolver: Arc<dyn Resolve>,
+    config: ConfigHandle,
+    stats: Arc<FetchStats>,
+    clock: Arc<dyn Clock>,
+}
+
+/// HTTP clients speaking one protocol, for any address and for each family.
+#[derive(Clone)]
+struct Clients {
+    any: Client,
+    ipv4: Client,
+    ipv6: Client,
+}
+
+impl Clients {
+    fn build(resolver: &Arc<dyn Resolve>, protocol: HttpProtocol) -> Self {
+        let client = |family| {
+            let resolver = FamilyResolver {
+                inner: resolver.clone(),
+                family,
+            };
+            let builder = Client::builder()
+                .redirect(redirect_policy())
+                .dns_resolver(Arc::new(resolver));
+            let builder = match protocol {
+                HttpProtocol::Auto => builder,
+                HttpProtocol::Http1Only => builder.http1_only(),
+                HttpProtocol::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+            };
+            builder.build().expect("Failed to build HTTP client")
+        };
+        Self {
+            any: client(None),
+            ipv4: client(Some(AddressFamily::V4)),
+            ipv6: client(Some(AddressFamily::V6)),
+        }
+    }
+
+    fn family(&self, family: AddressFamily) -> &Client {
+        match family {
+            AddressFamily::V4 => &self.ipv4,
+            AddressFamily::V6 => &self.ipv6,
+        }
+    }
+}
+
+/// A client speaking only HTTP/3, for requests sent with that version.
+#[cfg(feature = "http3")]
+fn http3_client(resolver: &Arc<dyn Resolve>) -> Client {
+    let resolver = FamilyResolver {
+        inner: resolver.clone(),
+        family: None,
+    };
+    Client::builder()
+        .redirect(redirect_policy())
+        .dns_resolver(Arc::new(resolver))
+        .http3_prior_knowledge()
+        .build()
+        .expect("Failed to build HTTP client")
+}
+
+/// Send `preferred`, and once it fails or has not answered after `delay`,
+/// `fallback` alongside it. The first response wins, along with whether it
+/// came from the fallback; when both fail, the later failure is returned.
+async fn race<P, F>(
+    preferred: P,
+    fallback: impl FnOnce() -> F,
+    delay: Duration,
+) -> Result<(Response, bool), reqwest::Error>
+where
+    P: Future<Output = Result<Response, reqwest::Error>>,
+    F: Future<Output = Result<Response, reqwest::Error>>,
+{
+    tokio::pin!(preferred);
+    let preferred_failed = tokio::select! {
+        result = &mut preferred => match result {
+            Ok(response) => return Ok((response, false)),
+            Err(e) => {
+                debug!(error = %e.without_url(), "Preferred attempt failed");
+                true
+            }
+        },
+        _ = tokio::time::sleep(delay) => false,
+    };
+
+    let fallback = fallback();
+    if preferred_failed {
+        return fallback.await.map(|response| (response, true));
+    }
+    tokio::pin!(fallback);
+    tokio::select! {
+        result = &mut preferred => match result {
+            Ok(response) => Ok((response, false)),
+            Err(_) => fallback.await.map(|response| (response, true)),
+        },
+        result = &mut fallback => match result {
+            Ok(response) => Ok((response, true)),
+            Err(_) => preferred.await.map(|response| (response, false)),
+        },
+    }
+}
+
+/// Why [`redirect_policy`] stopped following redirects.
+#[derive(Debug, Error)]
+enum RedirectError {
+    #[error("too many redirects")]
+    TooMany,
+    #[error("redirect loop")]
+    Loop,
+}
+
+/// Follows up to `MAX_REDIRECTS` redirects, noting how many were followed
+/// for the metrics, and stops at one back to a URL already visited.
+/// reqwest follows them while the request is polled, so within the task
+/// that sent it.
+fn redirect_policy() -> Policy {
+    Policy::custom(|attempt| {
+        let followed = attempt.previous().len();
+        if attempt.previous().contains(attempt.url()) {
+            return attempt.error(RedirectError::Loop);
+        }
+        if followed > MAX_REDIRECTS {
+            return attempt.error(RedirectError::TooMany);
+        }
+        let _ = REDIRECTS.try_with(|redirects| redirects.set(followed));
+        attempt.follow()
+    })
+}
+
+/// The [`FetchError`] for a request [`redirect_policy`] stopped.
+fn redirect_failure(error: &reqwest::Error) -> FetchError {
+    let mut source = std::error::Error::source(error);
+    while let Some(e) = source {
+        if let Some(RedirectError::Loop) = e.downcast_ref::<RedirectError>() {
+            return FetchError::RedirectLoop;
+        }
+        source = e.source();
+    }
+    FetchError::TooManyRedirects
+}
+
+/// Adds a body's bytes to the egress metrics when dropped, so bodies cut
+/// short by a failed or cancelled download are counted too.
+struct Downloaded {
+    status_class: &'static str,
+    bytes: u64,
+    truncated: bool,
+}
+
+impl Drop for Downloaded {
+    fn drop(&mut self) {
+        let truncated = if self.truncated { "true" } else { "false" };
+        metrics::FETCH_BODY_BYTES
+            .with_label_values(&[self.status_class, truncated])
+            .inc_by(self.bytes);
+    }
+}
+
+/// Counters maintained by the fetch path.
+#[derive(Debug, Default)]
+pub struct FetchStats {
+    in_flight: AtomicU64,
+    total: AtomicU64,
+    family_fallbacks: AtomicU64,
+    http3_fallbacks: AtomicU64,
+}
+
+impl FetchStats {
+    /// robots.txt fetches currently waiting on an origin.
+    pub fn in_flight(&self) -> u64 {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// robots.txt fetches started since the fetcher was created.
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Fetches from dual-stack hosts answered over the host's less
+    /// preferred address family.
+    pub fn family_fallbacks(&self) -> u64 {
+        self.family_fallbacks.load(Ordering::Relaxed)
+    }
+
+    /// Fetches that tried HTTP/3 first and were answered over HTTP/2 or
+    /// HTTP/1.1 instead.
+    pub fn http3_fallbacks(&self) -> u64 {
+        self.http3_fallbacks.load(Ordering::Relaxed)
+    }
+}
+
+/// Counts one fetch as in flight until dropped, so cancelled fetches are
+/// released too.
+pub(crate) struct InFlight<'a>(&'a FetchStats);
+
+impl<'a> InFlight<'a> {
+    pub(crate) fn start(stats: &'a FetchStats) -> Self {
+        stats.total.fetch_add(1, Ordering::Relaxed);
+        stats.in_flight.fetch_add(1, Ordering::Relaxed);
+        Self(stats)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 impl RobotsFetcher {
     pub fn new() -> Self {
-        info!("Creating fetcher with 30s timeout");
+        Self::with_config(ConfigHandle::default())
+    }
+
+    /// A fetcher that gives up on a request after `timeout`.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_config(ConfigHandle::new(RuntimeConfig {
+            fetch_timeout_secs: timeout.as_secs_f64(),
+            ..RuntimeConfig::default()
+        }))
+    }
+
+    /// A fetcher whose request timeout follows `fetch_timeout_secs` in the
+    /// live configuration.
+    pub fn with_config(config: ConfigHandle) -> Self {
+        Self::with_resolver(config, Arc::new(SystemResolver))
+    }
+
+    /// A fetcher that looks up origin addresses with `resolver`.
+    pub fn with_resolver(config: ConfigHandle, resolver: Arc<dyn Resolve>) -> Self {
+        info!(
+            timeout_secs = config.get().fetch_timeout_secs,
+            "Creating fetcher"
+        );
         Self {
-            client: Client::builder()
-                .timeout(Duration::from_secs(30))
-                .redirect(Policy::limited(5))
-                .build()
-                .expect("Failed to build HTTP client"),
+            clients: Clients::build(&resolver, HttpProtocol::Auto),
+            http1_clients: Clients::build(&resolver, HttpProtocol::Http1Only),
+            http2_clients: Clients::build(&resolver, HttpProtocol::Http2PriorKnowledge),
+            #[cfg(feature = "http3")]
+            http3_client: http3_client(&resolver),
+            resolver,
+            config,
+            stats: Arc::default(),
+            clock: clock::system(),
         }
     }
 
-    #[instrument(skip(self), fields(target_url = %target_url))]
+    /// Timestamp fetches with `clock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    pub fn stats(&self) -> &FetchStats {
+        &self.stats
+    }
+
+    #[instrument(skip(self), fields(target_url = %redact::url(target_url)))]
     pub async fn fetch(&self, target_url: &str) -> Result<RobotsData, FetchError> {
+        let text = self.fetch_text(target_url).await?;
+        debug!(body_len = text.body.len(), "Parsing robots.txt content");
+
+        let data = text.parse(target_url);
+        debug!("Successfully parsed robots.txt");
+
+        info!(
+            groups_count = data.groups.len(),
+            sitemaps_count = data.sitemaps.len(),
+            warnings_count = data.warnings.len(),
+            truncated = data.truncated,
+            "Parsed robots.txt"
+        );
+
+        Ok(data)
+    }
+
+    /// Fetch the robots.txt body for `target_url` without parsing it.
+    #[instrument(skip(self), fields(target_url = %redact::url(target_url)))]
+    pub async fn fetch_text(&self, target_url: &str) -> Result<RobotsText, FetchError> {
         let robots_url = extract_robots_url(target_url)?;
         debug!(%robots_url, "Extracted robots.txt url");
-        let response = match self.client.get(&robots_url).send().await {
-            Ok(r) => {
-                debug!(status = %r.status(), "Received HTTP response");
+        let _in_flight = InFlight::start(&self.stats);
+        let sent = REDIRECTS.scope(Cell::new(0), async {
+            let result = self.get(&robots_url).await;
+            (result, REDIRECTS.with(Cell::get))
+        });
+        let response = match sent.await {
+            (Ok(r), redirects) => {
+                debug!(
+                    status = %r.status(),
+                    version = ?r.version(),
+                    redirects,
+                    "Received HTTP response"
+                );
+                metrics::FETCH_REDIRECTS.observe(redirects as f64);
+                metrics::FETCH_HTTP_VERSIONS
+                    .with_label_values(&[format!("{:?}", r.version()).as_str()])
+                    .inc();
+                let _ = REMOTE_ADDRESS.try_with(|address| address.set(r.remote_addr()));
                 r
             }
-            Err(e) if e.is_timeout() => {
+            (Err(e), _) if e.is_timeout() => {
                 debug!("Request timed out");
                 return Err(FetchError::Timeout);
             }
-            Err(e) => {
+            (Err(e), _) if e.is_redirect() => {
+                debug!(error = %e, "Stopped following redirects");
+                return Err(redirect_failure(&e));
+            }
+            (Err(e), _) => {
                 debug!(error = %e, "robots.txt unreachable");
                 return Err(FetchError::Unreachable((e.to_string(), None)));
             }
@@ -63,10 +562,15 @@ impl RobotsFetcher {
 
         let status = response.status();
         let content_length = response.content_length().unwrap_or(0);
-        debug!(%status, content_length, "Response details");
+        let resolved_address = response.remote_addr();
+        debug!(%status, content_length, ?resolved_address, "Response details");
 
         match status.as_u16() {
             200..=299 => {
+                let http_version = format!("{:?}", response.version());
+                let upstream_headers =
+                    upstream_headers(response.headers(), &self.config.get().upstream_headers);
+                let max_age = max_age(response.headers());
                 let content_type = response
                     .headers()
                     .get("content-type")
@@ -79,61 +583,51 @@ impl RobotsFetcher {
                     )));
                 }
 
-                let mut body = String::new();
+                let mut reader = CappedBody::with_limit(self.config.get().max_robots_txt_bytes)
+                    .with_content_length(content_length);
+                let mut downloaded = Downloaded {
+                    status_class: metrics::status_class(status.as_u16()),
+                    bytes: 0,
+                    truncated: false,
+                };
                 let mut stream = response.bytes_stream();
-                let mut total_bytes = 0;
-                let mut last_newline = 0;
-                let mut truncated = false;
-
                 while let Some(chunk) = stream.next().await {
                     let chunk = chunk.map_err(|e| {
                         debug!(error = %e, "invalid chunk in robots.txt");
                         FetchError::Unreachable((e.to_string(), Some(status.as_u16())))
                     })?;
-                    debug!("Streaming repsonse");
-                    if total_bytes + chunk.len() > MAX_ROBOTS_TXT_SIZE {
-                        truncated = true;
-                        let remaining = MAX_ROBOTS_TXT_SIZE - total_bytes;
-                        let partial = &chunk[..remaining];
-                        if let Some(last_nl) = partial.iter().rposition(|&b| b == b'\n') {
-                            body.push_str(&String::from_utf8_lossy(&partial[..=last_nl]));
-                        } else if last_newline > 0 {
-                            body.truncate(last_newline);
-                        } else {
-                            body.push_str(&String::from_utf8_lossy(partial));
-                        }
-                        break;
+                    downloaded.bytes += chunk.len() as u64;
+                    if STREAMED_CHUNK.sample() {
+                        debug!(chunk_len = chunk.len(), "Streaming response");
                     }
-
-                    if let Some(pos) = chunk.iter().rposition(|&b| b == b'\n') {
-                        last_newline = body.len() + pos;
+                    if !reader.push(&chunk) {
+                        break;
                     }
-
-                    body.push_str(&String::from_utf8_lossy(&chunk));
-                    total_bytes += chunk.len();
                 }
+                let (body, truncated) = reader.finish();
+                downloaded.truncated = truncated;
+                if truncated {
+                    metrics::FETCH_TRUNCATIONS.inc();
+                }
+                metrics::FETCH_BODY_SIZE.observe(downloaded.bytes as f64);
 
-                debug!(body_len = body.len(), "Parsing robots.txt content");
-
-                let robots = RobotsTxt::parse(&body);
-
-                debug!("Successfully parsed robots.txt");
-                let mut data: RobotsData = robots.into();
-                data.content_length_bytes = content_length;
-                data.robots_txt_url = robots_url.clone();
-                data.target_url = target_url.to_string();
-                data.http_status_code = status.as_u16() as u32;
-                data.access_result = AccessResult::Success;
-                data.truncated = truncated;
-
-                info!(
-                    groups_count = data.groups.len(),
-                    sitemaps_count = data.sitemaps.len(),
-                    truncated = data.truncated,
-                    "Parsed robots.txt"
-                );
-
-                Ok(data)
+                Ok(RobotsText {
+                    robots_txt_url: robots_url,
+                    http_status_code: status.as_u16() as u32,
+                    content_length_bytes: content_length,
+                    body,
+                    truncated,
+                    fetched_at: self.clock.now(),
+                    http_version,
+                    upstream_headers,
+                    max_age,
+                    resolved_address,
+                })
+            }
+            429 => {
+                let retry_after = retry_after(response.headers(), self.clock.now());
+                debug!(?retry_after, "Rate limited");
+                Err(FetchError::RateLimited(retry_after))
             }
             400..=499 => {
                 debug!(status_code = status.as_u16(), "Client error response");
@@ -155,37 +649,290 @@ impl RobotsFetcher {
             }
         }
     }
+
+    /// Fetch the `X-Robots-Tag` headers for `target_url` with a HEAD request,
+    /// falling back to a single-byte ranged GET when the origin rejects HEAD.
+    #[instrument(skip(self), fields(target_url = %redact::url(target_url)))]
+    pub async fn fetch_x_robots_tag(
+        &self,
+        target_url: &str,
+    ) -> Result<XRobotsTagHeaders, FetchError> {
+        let url = parse_http_url(target_url)?;
+        let client = &self.clients(url.host_str().unwrap_or_default()).any;
+        let response = match self.configured(client.head(url.clone())).send().await {
+            Ok(r)
+                if r.status() != StatusCode::METHOD_NOT_ALLOWED
+                    && r.status() != StatusCode::NOT_IMPLEMENTED =>
+            {
+                debug!(status = %r.status(), "Received HEAD response");
+                r
+            }
+            Ok(r) => {
+                debug!(status = %r.status(), "HEAD rejected, falling back to ranged GET");
+                self.ranged_get(url).await?
+            }
+            Err(e) if e.is_timeout() => {
+                debug!("HEAD request timed out");
+                return Err(FetchError::Timeout);
+            }
+            Err(e) => {
+                // reqwest errors include the request URL, which here is the
+                // target with its query
+                debug!(error = %e.without_url(), "HEAD request failed, falling back to ranged GET");
+                self.ranged_get(url).await?
+            }
+        };
+
+        let values = response
+            .headers()
+            .get_all("x-robots-tag")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        debug!(
+            headers_count = values.len(),
+            "Collected X-Robots-Tag headers"
+        );
+
+        Ok(XRobotsTagHeaders {
+            http_status_code: response.status().as_u16() as u32,
+            values,
+        })
+    }
+
+    /// Apply the configured timeout and user-agent to an outgoing request.
+    fn configured(&self, request: RequestBuilder) -> RequestBuilder {
+        let config = self.config.get();
+        let request = request.timeout(config.fetch_timeout());
+        match &config.fetch_user_agent {
+            Some(user_agent) => request.header(USER_AGENT, user_agent),
+            None => request,
+        }
+    }
+
+    /// Add the trace context of the call being served to `request` when
+    /// `host` is configured to take part in tracing.
+    fn traced(&self, request: RequestBuilder, host: &str) -> RequestBuilder {
+        let Some(context) = TraceContext::current() else {
+            return request;
+        };
+        if !self.config.get().propagates_trace_context(host) {
+            return request;
+        }
+        debug!(%host, "Propagating trace context");
+        let request = request.header("traceparent", context.traceparent());
+        match context.tracestate() {
+            Some(tracestate) => request.header("tracestate", tracestate),
+            None => request,
+        }
+    }
+
+    /// GET `url`, over HTTP/3 first when `http3` is configured for it, with
+    /// [`dual_stack_get`](Self::dual_stack_get) as the fallback.
+    async fn get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        #[cfg(feature = "http3")]
+        if let Ok(parsed) = Url::parse(url)
+            && self.config.get().tries_http3(&parsed)
+        {
+            let host = parsed.host_str().unwrap_or_default();
+            let request = self.http3_client.get(url).version(Version::HTTP_3);
+            let http3 = self.traced(self.configured(request), host).send();
+            let fallback_get = || {
+                debug!("Trying HTTP/2 and HTTP/1.1");
+                self.dual_stack_get(url)
+            };
+            let delay = self.config.get().http3_fallback_delay();
+            let (response, fell_back) = race(http3, fallback_get, delay).await?;
+            if fell_back {
+                self.stats.http3_fallbacks.fetch_add(1, Ordering::Relaxed);
+            }
+            return Ok(response);
+        }
+        self.dual_stack_get(url).await
+    }
+
+    /// GET `url`, and when its host has addresses of both families, give
+    /// the preferred family a head start of `address_fallback_delay_secs`
+    /// before trying the other one alongside it. A failure of the preferred
+    /// family ends its head start early. The first response wins.
+    async fn dual_stack_get(&self, url: &str) -> Result<Response, reqwest::Error> {
+        let parsed = Url::parse(url).ok();
+        let host = parsed.as_ref().and_then(Url::host_str).unwrap_or_default();
+        let clients = self.clients(host);
+        let families = match parsed.as_ref().and_then(Url::host) {
+            Some(url::Host::Domain(host)) => match Name::from_str(host) {
+                Ok(name) => dns::families(self.resolver.as_ref(), name).await,
+                Err(_) => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        let get = |client: &Client| self.traced(self.configured(client.get(url)), host).send();
+        let [preferred, fallback] = families[..] else {
+            return get(&clients.any).await;
+        };
+
+        let delay = self.config.get().address_fallback_delay();
+        let fallback_get = || {
+            debug!(family = %fallback, "Trying the other address family");
+            get(clients.family(fallback))
+        };
+        let (response, fell_back) =
+            race(get(clients.family(preferred)), fallback_get, delay).await?;
+        let family = if fell_back { fallback } else { preferred };
+        Ok(self.answered(response, preferred, family))
+    }
+
+    /// The clients speaking the HTTP version configured for `host`.
+    fn clients(&self, host: &str) -> &Clients {
+        match self.config.get().http_protocol(host) {
+            HttpProtocol::Auto => &self.clients,
+            HttpProtocol::Http1Only => &self.http1_clients,
+            HttpProtocol::Http2PriorKnowledge => &self.http2_clients,
+        }
+    }
+
+    /// Record which family a dual-stack host answered over.
+    fn answered(
+        &self,
+        response: Response,
+        preferred: AddressFamily,
+        family: AddressFamily,
+    ) -> Response {
+        if family != preferred {
+            self.stats.family_fallbacks.fetch_add(1, Ordering::Relaxed);
+        }
+        debug!(%family, fallback = family != preferred, "Dual-stack host answered");
+        response
+    }
+
+    async fn ranged_get(&self, url: Url) -> Result<Response, FetchError> {
+        let client = &self.clients(url.host_str().unwrap_or_default()).any;
+        let request = self.configured(client.get(url).header(RANGE, "bytes=0-0"));
+        match request.send().await {
+            Ok(r) => {
+                debug!(status = %r.status(), "Received ranged GET response");
+                Ok(r)
+            }
+            Err(e) if e.is_timeout() => {
+                debug!("Ranged GET timed out");
+                Err(FetchError::Timeout)
+            }
+            Err(e) => {
+                let e = e.without_url();
+                debug!(error = %e, "Target unreachable");
+                Err(FetchError::Unreachable((e.to_string(), None)))
+            }
+        }
+    }
 }
 
-#[instrument]
+#[async_trait]
+impl Fetcher for RobotsFetcher {
+    async fn fetch(&self, target_url: &str) -> Result<RobotsData, FetchError> {
+        RobotsFetcher::fetch(self, target_url).await
+    }
+
+    async fn fetch_text(&self, target_url: &str) -> Result<RobotsText, FetchError> {
+        RobotsFetcher::fetch_text(self, target_url).await
+    }
+
+    async fn fetch_x_robots_tag(&self, target_url: &str) -> Result<XRobotsTagHeaders, FetchError> {
+        RobotsFetcher::fetch_x_robots_tag(self, target_url).await
+    }
+
+    fn stats(&self) -> &FetchStats {
+        RobotsFetcher::stats(self)
+    }
+
+    fn clock(&self) -> &Arc<dyn Clock> {
+        RobotsFetcher::clock(self)
+    }
+
+    fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        RobotsFetcher::with_clock(self, clock)
+    }
+}
+
+#[instrument(fields(target_url = %redact::url(target_url)))]
 pub fn extract_robots_url(target_url: &str) -> Result<String, FetchError> {
-    debug!("Parsing target url");
-    let parsed = Url::parse(target_url).map_err(|e| {
-        debug!(error = %e, "Invalid url");
-        FetchError::InvalidUrl(format!("Failed to parse URL: {e}"))
-    })?;
-    let scheme = parsed.scheme();
-    if scheme != "http" && scheme != "https" {
-        debug!(scheme = %scheme, "Unsupported scheme");
-        return Err(FetchError::InvalidUrl(format!(
-            "Unsupported scheme: {scheme}"
-        )));
-    }
-    let host = parsed.host_str().ok_or_else(|| {
-        debug!("URL has no nost component");
-        FetchError::InvalidUrl("URL has no host".to_string())
-    })?;
-    let port = parsed.port();
-    let robots_url = match port {
-        Some(p) if (scheme == "http" && p != 80) || (scheme == "https" && p != 443) => {
-            debug!(port = p, "Using non-standard port");
-            format!("{scheme}://{host}:{p}/robots.txt")
-        }
-        _ => {
-            debug!("Using standard port");
-            format!("{scheme}://{host}/robots.txt")
-        }
-    };
+    let robots_url = NormalizedTarget::parse(target_url)?.robots_key;
     debug!(%robots_url, "Constructed robots.txt URL");
     Ok(robots_url)
 }
+
+fn parse_http_url(target_url: &str) -> Result<Url, FetchError> {
+    debug!("Parsing target url");
+    let target = NormalizedTarget::parse(target_url)?;
+    Url::parse(&target.target_url).map_err(|e| FetchError::InvalidUrl(e.to_string()))
+}
+
+/// The headers in `headers` named in `names`, by lowercased name. Repeated
+/// headers are joined with `, `. Values are cut at
+/// [`MAX_UPSTREAM_HEADER_VALUE`], and once [`MAX_UPSTREAM_HEADERS_BYTES`]
+/// would be exceeded the remaining headers are left out.
+fn upstream_headers(headers: &HeaderMap, names: &[String]) -> BTreeMap<String, String> {
+    let mut captured = BTreeMap::new();
+    let mut budget = MAX_UPSTREAM_HEADERS_BYTES;
+    for name in names {
+        let name = name.to_ascii_lowercase();
+        let values = headers
+            .get_all(name.as_str())
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect::<Vec<_>>();
+        if values.is_empty() || captured.contains_key(&name) {
+            continue;
+        }
+        let mut value = values.join(", ");
+        // `to_str` only accepts visible ASCII, so any length is a char boundary
+        value.truncate(MAX_UPSTREAM_HEADER_VALUE);
+        let Some(remaining) = budget.checked_sub(name.len() + value.len()) else {
+            debug!(header = %name, "Upstream headers over the size limit");
+            break;
+        };
+        budget = remaining;
+        captured.insert(name, value);
+    }
+    captured
+}
+
+/// How much longer a response stays fresh: the max-age directive of its
+/// `Cache-Control` header, less its `Age` when a cache in between has held
+/// it for a while.
+fn max_age(headers: &HeaderMap) -> Option<Duration> {
+    let age = headers
+        .get(AGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+        .map_or(Duration::ZERO, Duration::from_secs);
+    let max_age = headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .find_map(|directive| {
+            let (name, value) = directive.split_once('=')?;
+            if !name.trim().eq_ignore_ascii_case("max-age") {
+                return None;
+            }
+            let secs = value.trim().trim_matches('"').parse().ok()?;
+            Some(Duration::from_secs(secs))
+        })?;
+    Some(max_age.saturating_sub(age))
+}
+
+/// How long a `Retry-After` header asks to wait from `now`, given either in
+/// seconds or as an HTTP date.
+fn retry_after(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        SystemTime::from(date)
+            .duration_since(now)
+            .unwrap_or_default(),
+    )
+}
@@ -1,137 +1,4 @@
 // This file is @generated by prost-build.
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct GetRobotsRequest {
-    #[prost(string, tag = "1")]
-    pub url: ::prost::alloc::string::String,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct GetRobotsResponse {
-    #[prost(string, tag = "1")]
-    pub target_url: ::prost::alloc::string::String,
-    #[prost(string, tag = "2")]
-    pub robots_txt_url: ::prost::alloc::string::String,
-    #[prost(enumeration = "AccessResult", tag = "3")]
-    pub access_result: i32,
-    #[prost(uint32, tag = "4")]
-    pub http_status_code: u32,
-    #[prost(message, repeated, tag = "5")]
-    pub groups: ::prost::alloc::vec::Vec<Group>,
-    #[prost(string, repeated, tag = "6")]
-    pub sitemaps: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
-    #[prost(uint64, tag = "7")]
-    pub content_length_bytes: u64,
-    #[prost(bool, tag = "8")]
-    pub truncated: bool,
-}
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Group {
-    #[prost(string, repeated, tag = "1")]
-    pub user_agents: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
-    #[prost(message, repeated, tag = "2")]
-    pub rules: ::prost::alloc::vec::Vec<Rule>,
-}
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct Rule {
-    #[prost(enumeration = "rule::RuleType", tag = "1")]
-    pub rule_type: i32,
-    #[prost(string, tag = "2")]
-    pub path_pattern: ::prost::alloc::string::String,
-}
-/// Nested message and enum types in `Rule`.
-pub mod rule {
-    #[derive(
-        Clone,
-        Copy,
-        Debug,
-        PartialEq,
-        Eq,
-        Hash,
-        PartialOrd,
-        Ord,
-        ::prost::Enumeration
-    )]
-    #[repr(i32)]
-    pub enum RuleType {
-        Unspecified = 0,
-        Allow = 1,
-        Disallow = 2,
-    }
-    impl RuleType {
-        /// String value of the enum field names used in the ProtoBuf definition.
-        ///
-        /// The values are not transformed in any way and thus are considered stable
-        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
-        pub fn as_str_name(&self) -> &'static str {
-            match self {
-                Self::Unspecified => "RULE_TYPE_UNSPECIFIED",
-                Self::Allow => "RULE_TYPE_ALLOW",
-                Self::Disallow => "RULE_TYPE_DISALLOW",
-            }
-        }
-        /// Creates an enum from field names used in the ProtoBuf definition.
-        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
-            match value {
-                "RULE_TYPE_UNSPECIFIED" => Some(Self::Unspecified),
-                "RULE_TYPE_ALLOW" => Some(Self::Allow),
-                "RULE_TYPE_DISALLOW" => Some(Self::Disallow),
-                _ => None,
-            }
-        }
-    }
-}
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct IsAllowedRequest {
-    #[prost(string, tag = "1")]
-    pub target_url: ::prost::alloc::string::String,
-    #[prost(string, tag = "2")]
-    pub user_agent: ::prost::alloc::string::String,
-}
-#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct IsAllowedResponse {
-    #[prost(bool, tag = "1")]
-    pub allowed: bool,
-}
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
-pub enum AccessResult {
-    Unspecified = 0,
-    Success = 1,
-    RedirectSuccess = 2,
-    TooManyRedirects = 3,
-    Unavailable = 4,
-    Unreachable = 5,
-    Cached = 6,
-}
-impl AccessResult {
-    /// String value of the enum field names used in the ProtoBuf definition.
-    ///
-    /// The values are not transformed in any way and thus are considered stable
-    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
-    pub fn as_str_name(&self) -> &'static str {
-        match self {
-            Self::Unspecified => "ACCESS_RESULT_UNSPECIFIED",
-            Self::Success => "ACCESS_RESULT_SUCCESS",
-            Self::RedirectSuccess => "ACCESS_RESULT_REDIRECT_SUCCESS",
-            Self::TooManyRedirects => "ACCESS_RESULT_TOO_MANY_REDIRECTS",
-            Self::Unavailable => "ACCESS_RESULT_UNAVAILABLE",
-            Self::Unreachable => "ACCESS_RESULT_UNREACHABLE",
-            Self::Cached => "ACCESS_RESULT_CACHED",
-        }
-    }
-    /// Creates an enum from field names used in the ProtoBuf definition.
-    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
-        match value {
-            "ACCESS_RESULT_UNSPECIFIED" => Some(Self::Unspecified),
-            "ACCESS_RESULT_SUCCESS" => Some(Self::Success),
-            "ACCESS_RESULT_REDIRECT_SUCCESS" => Some(Self::RedirectSuccess),
-            "ACCESS_RESULT_TOO_MANY_REDIRECTS" => Some(Self::TooManyRedirects),
-            "ACCESS_RESULT_UNAVAILABLE" => Some(Self::Unavailable),
-            "ACCESS_RESULT_UNREACHABLE" => Some(Self::Unreachable),
-            "ACCESS_RESULT_CACHED" => Some(Self::Cached),
-            _ => None,
-        }
-    }
-}
 /// Generated client implementations.
 pub mod robots_service_client {
     #![allow(
@@ -143,6 +10,9 @@ pub mod robots_service_client {
     )]
     use tonic::codegen::*;
     use tonic::codegen::http::Uri;
+    /// Deprecated: use robots.v1.RobotsService. This is the pre-versioning path,
+    /// kept so existing clients keep working. Messages are shared with robots.v1
+    /// and are identical on the wire.
     #[derive(Debug, Clone)]
     pub struct RobotsServiceClient<T> {
         inner: tonic::client::Grpc<T>,
@@ -225,9 +95,9 @@ pub mod robots_service_client {
         }
         pub async fn get_robots_txt(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetRobotsRequest>,
+            request: impl tonic::IntoRequest<super::v1::GetRobotsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetRobotsResponse>,
+            tonic::Response<super::v1::GetRobotsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -249,9 +119,9 @@ pub mod robots_service_client {
         }
         pub async fn is_allowed(
             &mut self,
-            request: impl tonic::IntoRequest<super::IsAllowedRequest>,
+            request: impl tonic::IntoRequest<super::v1::IsAllowedRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::IsAllowedResponse>,
+            tonic::Response<super::v1::IsAllowedResponse>,
             tonic::Status,
         > {
             self.inner
@@ -271,127 +141,539 @@ pub mod robots_service_client {
                 .insert(GrpcMethod::new("robots.RobotsService", "IsAllowed"));
             self.inner.unary(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod robots_service_server {
-    #![allow(
-        unused_variables,
-        dead_code,
-        missing_docs,
-        clippy::wildcard_imports,
-        clippy::let_unit_value,
-    )]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with RobotsServiceServer.
-    #[async_trait]
-    pub trait RobotsService: std::marker::Send + std::marker::Sync + 'static {
-        async fn get_robots_txt(
-            &self,
-            request: tonic::Request<super::GetRobotsRequest>,
+        pub async fn check_robots_headers(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::CheckRobotsHeadersRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetRobotsResponse>,
+            tonic::Response<super::v1::CheckRobotsHeadersResponse>,
             tonic::Status,
-        >;
-        async fn is_allowed(
-            &self,
-            request: tonic::Request<super::IsAllowedRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/CheckRobotsHeaders",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "CheckRobotsHeaders"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn can_crawl(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::CanCrawlRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::IsAllowedResponse>,
+            tonic::Response<super::v1::CanCrawlResponse>,
             tonic::Status,
-        >;
-    }
-    #[derive(Debug)]
-    pub struct RobotsServiceServer<T> {
-        inner: Arc<T>,
-        accept_compression_encodings: EnabledCompressionEncodings,
-        send_compression_encodings: EnabledCompressionEncodings,
-        max_decoding_message_size: Option<usize>,
-        max_encoding_message_size: Option<usize>,
-    }
-    impl<T> RobotsServiceServer<T> {
-        pub fn new(inner: T) -> Self {
-            Self::from_arc(Arc::new(inner))
-        }
-        pub fn from_arc(inner: Arc<T>) -> Self {
-            Self {
-                inner,
-                accept_compression_encodings: Default::default(),
-                send_compression_encodings: Default::default(),
-                max_decoding_message_size: None,
-                max_encoding_message_size: None,
-            }
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/CanCrawl",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "CanCrawl"));
+            self.inner.unary(req, path, codec).await
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
-        where
-            F: tonic::service::Interceptor,
-        {
-            InterceptedService::new(Self::new(inner), interceptor)
+        pub async fn next_allowed_fetch(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::NextAllowedFetchRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::NextAllowedFetchResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/NextAllowedFetch",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "NextAllowedFetch"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Enable decompressing requests with the given encoding.
-        #[must_use]
-        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.accept_compression_encodings.enable(encoding);
-            self
+        pub async fn invalidate_url(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::InvalidateUrlRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::InvalidateUrlResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/InvalidateUrl",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "InvalidateUrl"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Compress responses with the given encoding, if the client supports it.
-        #[must_use]
-        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.send_compression_encodings.enable(encoding);
-            self
+        pub async fn stream_decisions(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::StreamDecisionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::v1::DecisionEvent>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/StreamDecisions",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "StreamDecisions"));
+            self.inner.server_streaming(req, path, codec).await
         }
-        /// Limits the maximum size of a decoded message.
-        ///
-        /// Default: `4MB`
-        #[must_use]
-        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
-            self.max_decoding_message_size = Some(limit);
-            self
+        pub async fn get_server_info(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::GetServerInfoRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::GetServerInfoResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/GetServerInfo",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "GetServerInfo"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Limits the maximum size of an encoded message.
-        ///
-        /// Default: `usize::MAX`
-        #[must_use]
-        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
-            self.max_encoding_message_size = Some(limit);
-            self
+        pub async fn set_policy_rules(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::SetPolicyRulesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::SetPolicyRulesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/SetPolicyRules",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "SetPolicyRules"));
+            self.inner.unary(req, path, codec).await
         }
-    }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for RobotsServiceServer<T>
-    where
-        T: RobotsService,
-        B: Body + std::marker::Send + 'static,
-        B::Error: Into<StdError> + std::marker::Send + 'static,
-    {
-        type Response = http::Response<tonic::body::Body>;
-        type Error = std::convert::Infallible;
-        type Future = BoxFuture<Self::Response, Self::Error>;
-        fn poll_ready(
+        pub async fn prefetch_hosts(
             &mut self,
-            _cx: &mut Context<'_>,
-        ) -> Poll<std::result::Result<(), Self::Error>> {
-            Poll::Ready(Ok(()))
+            request: impl tonic::IntoRequest<super::v1::PrefetchHostsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::PrefetchHostsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/PrefetchHosts",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "PrefetchHosts"));
+            self.inner.unary(req, path, codec).await
         }
-        fn call(&mut self, req: http::Request<B>) -> Self::Future {
-            match req.uri().path() {
-                "/robots.RobotsService/GetRobotsTxt" => {
-                    #[allow(non_camel_case_types)]
-                    struct GetRobotsTxtSvc<T: RobotsService>(pub Arc<T>);
-                    impl<
-                        T: RobotsService,
-                    > tonic::server::UnaryService<super::GetRobotsRequest>
-                    for GetRobotsTxtSvc<T> {
-                        type Response = super::GetRobotsResponse;
+        pub async fn get_prefetch_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::GetPrefetchStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::GetPrefetchStatusResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/GetPrefetchStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "GetPrefetchStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn refresh_robots(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::RefreshRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::RefreshRobotsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/RefreshRobots",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "RefreshRobots"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_canonical_robots_text(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::GetRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::GetCanonicalRobotsTextResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/GetCanonicalRobotsText",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "GetCanonicalRobotsText"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn clear_cache(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::ClearCacheRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::ClearCacheResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/ClearCache",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "ClearCache"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_cached_entries(
+            &mut self,
+            request: impl tonic::IntoRequest<super::v1::ListCachedEntriesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::ListCachedEntriesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.RobotsService/ListCachedEntries",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.RobotsService", "ListCachedEntries"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod robots_service_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with RobotsServiceServer.
+    #[async_trait]
+    pub trait RobotsService: std::marker::Send + std::marker::Sync + 'static {
+        async fn get_robots_txt(
+            &self,
+            request: tonic::Request<super::v1::GetRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::GetRobotsResponse>,
+            tonic::Status,
+        >;
+        async fn is_allowed(
+            &self,
+            request: tonic::Request<super::v1::IsAllowedRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::IsAllowedResponse>,
+            tonic::Status,
+        >;
+        async fn check_robots_headers(
+            &self,
+            request: tonic::Request<super::v1::CheckRobotsHeadersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::CheckRobotsHeadersResponse>,
+            tonic::Status,
+        >;
+        async fn can_crawl(
+            &self,
+            request: tonic::Request<super::v1::CanCrawlRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::CanCrawlResponse>,
+            tonic::Status,
+        >;
+        async fn next_allowed_fetch(
+            &self,
+            request: tonic::Request<super::v1::NextAllowedFetchRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::NextAllowedFetchResponse>,
+            tonic::Status,
+        >;
+        async fn invalidate_url(
+            &self,
+            request: tonic::Request<super::v1::InvalidateUrlRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::InvalidateUrlResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the StreamDecisions method.
+        type StreamDecisionsStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::v1::DecisionEvent, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        async fn stream_decisions(
+            &self,
+            request: tonic::Request<super::v1::StreamDecisionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamDecisionsStream>,
+            tonic::Status,
+        >;
+        async fn get_server_info(
+            &self,
+            request: tonic::Request<super::v1::GetServerInfoRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::GetServerInfoResponse>,
+            tonic::Status,
+        >;
+        async fn set_policy_rules(
+            &self,
+            request: tonic::Request<super::v1::SetPolicyRulesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::SetPolicyRulesResponse>,
+            tonic::Status,
+        >;
+        async fn prefetch_hosts(
+            &self,
+            request: tonic::Request<super::v1::PrefetchHostsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::PrefetchHostsResponse>,
+            tonic::Status,
+        >;
+        async fn get_prefetch_status(
+            &self,
+            request: tonic::Request<super::v1::GetPrefetchStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::GetPrefetchStatusResponse>,
+            tonic::Status,
+        >;
+        async fn refresh_robots(
+            &self,
+            request: tonic::Request<super::v1::RefreshRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::RefreshRobotsResponse>,
+            tonic::Status,
+        >;
+        async fn get_canonical_robots_text(
+            &self,
+            request: tonic::Request<super::v1::GetRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::GetCanonicalRobotsTextResponse>,
+            tonic::Status,
+        >;
+        async fn clear_cache(
+            &self,
+            request: tonic::Request<super::v1::ClearCacheRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::ClearCacheResponse>,
+            tonic::Status,
+        >;
+        async fn list_cached_entries(
+            &self,
+            request: tonic::Request<super::v1::ListCachedEntriesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::v1::ListCachedEntriesResponse>,
+            tonic::Status,
+        >;
+    }
+    /// Deprecated: use robots.v1.RobotsService. This is the pre-versioning path,
+    /// kept so existing clients keep working. Messages are shared with robots.v1
+    /// and are identical on the wire.
+    #[derive(Debug)]
+    pub struct RobotsServiceServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> RobotsServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for RobotsServiceServer<T>
+    where
+        T: RobotsService,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::Body>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/robots.RobotsService/GetRobotsTxt" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetRobotsTxtSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::GetRobotsRequest>
+                    for GetRobotsTxtSvc<T> {
+                        type Response = super::v1::GetRobotsResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetRobotsRequest>,
+                            request: tonic::Request<super::v1::GetRobotsRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
@@ -427,16 +709,16 @@ pub mod robots_service_server {
                     struct IsAllowedSvc<T: RobotsService>(pub Arc<T>);
                     impl<
                         T: RobotsService,
-                    > tonic::server::UnaryService<super::IsAllowedRequest>
+                    > tonic::server::UnaryService<super::v1::IsAllowedRequest>
                     for IsAllowedSvc<T> {
-                        type Response = super::IsAllowedResponse;
+                        type Response = super::v1::IsAllowedResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::IsAllowedRequest>,
+                            request: tonic::Request<super::v1::IsAllowedRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
@@ -467,6 +749,592 @@ pub mod robots_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/robots.RobotsService/CheckRobotsHeaders" => {
+                    #[allow(non_camel_case_types)]
+                    struct CheckRobotsHeadersSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::CheckRobotsHeadersRequest>
+                    for CheckRobotsHeadersSvc<T> {
+                        type Response = super::v1::CheckRobotsHeadersResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::CheckRobotsHeadersRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::check_robots_headers(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CheckRobotsHeadersSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/CanCrawl" => {
+                    #[allow(non_camel_case_types)]
+                    struct CanCrawlSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::CanCrawlRequest>
+                    for CanCrawlSvc<T> {
+                        type Response = super::v1::CanCrawlResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::CanCrawlRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::can_crawl(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CanCrawlSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/NextAllowedFetch" => {
+                    #[allow(non_camel_case_types)]
+                    struct NextAllowedFetchSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::NextAllowedFetchRequest>
+                    for NextAllowedFetchSvc<T> {
+                        type Response = super::v1::NextAllowedFetchResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::NextAllowedFetchRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::next_allowed_fetch(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = NextAllowedFetchSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/InvalidateUrl" => {
+                    #[allow(non_camel_case_types)]
+                    struct InvalidateUrlSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::InvalidateUrlRequest>
+                    for InvalidateUrlSvc<T> {
+                        type Response = super::v1::InvalidateUrlResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::InvalidateUrlRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::invalidate_url(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = InvalidateUrlSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/StreamDecisions" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamDecisionsSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::ServerStreamingService<super::v1::StreamDecisionsRequest>
+                    for StreamDecisionsSvc<T> {
+                        type Response = super::v1::DecisionEvent;
+                        type ResponseStream = T::StreamDecisionsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::StreamDecisionsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::stream_decisions(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = StreamDecisionsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/GetServerInfo" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetServerInfoSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::GetServerInfoRequest>
+                    for GetServerInfoSvc<T> {
+                        type Response = super::v1::GetServerInfoResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::GetServerInfoRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::get_server_info(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetServerInfoSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/SetPolicyRules" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetPolicyRulesSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::SetPolicyRulesRequest>
+                    for SetPolicyRulesSvc<T> {
+                        type Response = super::v1::SetPolicyRulesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::SetPolicyRulesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::set_policy_rules(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = SetPolicyRulesSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/PrefetchHosts" => {
+                    #[allow(non_camel_case_types)]
+                    struct PrefetchHostsSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::PrefetchHostsRequest>
+                    for PrefetchHostsSvc<T> {
+                        type Response = super::v1::PrefetchHostsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::PrefetchHostsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::prefetch_hosts(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = PrefetchHostsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/GetPrefetchStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetPrefetchStatusSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::GetPrefetchStatusRequest>
+                    for GetPrefetchStatusSvc<T> {
+                        type Response = super::v1::GetPrefetchStatusResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::GetPrefetchStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::get_prefetch_status(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetPrefetchStatusSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/RefreshRobots" => {
+                    #[allow(non_camel_case_types)]
+                    struct RefreshRobotsSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::RefreshRobotsRequest>
+                    for RefreshRobotsSvc<T> {
+                        type Response = super::v1::RefreshRobotsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::RefreshRobotsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::refresh_robots(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RefreshRobotsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/GetCanonicalRobotsText" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetCanonicalRobotsTextSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::GetRobotsRequest>
+                    for GetCanonicalRobotsTextSvc<T> {
+                        type Response = super::v1::GetCanonicalRobotsTextResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::GetRobotsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::get_canonical_robots_text(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetCanonicalRobotsTextSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/ClearCache" => {
+                    #[allow(non_camel_case_types)]
+                    struct ClearCacheSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::ClearCacheRequest>
+                    for ClearCacheSvc<T> {
+                        type Response = super::v1::ClearCacheResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::ClearCacheRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::clear_cache(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ClearCacheSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.RobotsService/ListCachedEntries" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListCachedEntriesSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::v1::ListCachedEntriesRequest>
+                    for ListCachedEntriesSvc<T> {
+                        type Response = super::v1::ListCachedEntriesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::v1::ListCachedEntriesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::list_cached_entries(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListCachedEntriesSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         let mut response = http::Response::new(
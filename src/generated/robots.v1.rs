@@ -0,0 +1,2081 @@
+// This file is @generated by prost-build.
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetRobotsRequest {
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    /// GetRobotsResponse fields to return, by name; unset or empty for all of
+    /// them. Fields left out are sent as their defaults.
+    #[prost(message, optional, tag = "2")]
+    pub read_mask: ::core::option::Option<::prost_types::FieldMask>,
+    /// Only return the groups that apply to this user-agent, as IsAllowed
+    /// would choose them.
+    #[prost(string, optional, tag = "3")]
+    pub user_agent: ::core::option::Option<::prost::alloc::string::String>,
+    /// The checksum of the copy the caller already has. When it matches, the
+    /// response only carries not_modified, the checksum, and the fetch times.
+    #[prost(string, tag = "4")]
+    pub known_sha256: ::prost::alloc::string::String,
+    /// Return at most this many groups, with next_page_token set when more
+    /// remain. Zero returns them all. Rules within a group are never split.
+    #[prost(uint32, tag = "5")]
+    pub page_size: u32,
+    /// The next_page_token of the previous page. ABORTED when robots.txt has
+    /// changed since, and paging must start again.
+    #[prost(string, tag = "6")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetRobotsResponse {
+    #[prost(string, tag = "1")]
+    pub target_url: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub robots_txt_url: ::prost::alloc::string::String,
+    #[prost(enumeration = "AccessResult", tag = "3")]
+    pub access_result: i32,
+    #[prost(uint32, tag = "4")]
+    pub http_status_code: u32,
+    #[prost(message, repeated, tag = "5")]
+    pub groups: ::prost::alloc::vec::Vec<Group>,
+    #[prost(string, repeated, tag = "6")]
+    pub sitemaps: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint64, tag = "7")]
+    pub content_length_bytes: u64,
+    #[prost(bool, tag = "8")]
+    pub truncated: bool,
+    /// The user-agent line that selected the groups when the request set
+    /// user_agent: a specific token, or "*" for the fallback group. Empty when
+    /// no group applies.
+    #[prost(string, tag = "9")]
+    pub effective_user_agent_match: ::prost::alloc::string::String,
+    /// When robots.txt was fetched, or the failed fetch was attempted. Unset
+    /// for data that did not come from an origin.
+    #[prost(message, optional, tag = "10")]
+    pub fetched_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// When the cached entry is due to be refetched.
+    #[prost(message, optional, tag = "11")]
+    pub expires_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// The request's known_sha256 matches, so groups and sitemaps were left out.
+    #[prost(bool, tag = "12")]
+    pub not_modified: bool,
+    /// Hex-encoded SHA-256 of the robots.txt body, empty when none was fetched.
+    #[prost(string, tag = "13")]
+    pub checksum: ::prost::alloc::string::String,
+    /// Supplemental rules for the host, from the config file and SetPolicyRules.
+    /// They are checked before the robots.txt rules.
+    #[prost(message, repeated, tag = "14")]
+    pub policy_rules: ::prost::alloc::vec::Vec<Rule>,
+    #[prost(enumeration = "DataSource", tag = "15")]
+    pub data_source: i32,
+    /// The host the request named.
+    #[prost(string, tag = "16")]
+    pub requested_host: ::prost::alloc::string::String,
+    /// The robots.txt URL the data is cached under. Differs from the requested
+    /// host's when that host is configured as an alias of another.
+    #[prost(string, tag = "17")]
+    pub cache_key: ::prost::alloc::string::String,
+    /// Response headers from the origin named in the server's upstream_headers
+    /// setting, by lowercased name. Only kept for robots.txt fetched
+    /// successfully; long values are cut.
+    #[prost(map = "string, string", tag = "18")]
+    pub upstream_headers: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// The origin address robots.txt was fetched from, as ip:port. Set for
+    /// failed fetches too when the origin answered. Empty when nothing was
+    /// fetched over a connection.
+    #[prost(string, tag = "19")]
+    pub resolved_address: ::prost::alloc::string::String,
+    #[prost(enumeration = "AddressFamily", tag = "20")]
+    pub address_family: i32,
+    /// More distinct sitemaps were listed than the server's max_sitemaps, and
+    /// only the first ones are in sitemaps.
+    #[prost(bool, tag = "21")]
+    pub sitemaps_truncated: bool,
+    /// Pass as page_token for the next page of groups; empty on the last page.
+    #[prost(string, tag = "22")]
+    pub next_page_token: ::prost::alloc::string::String,
+    /// 1-based line of robots.txt each entry of sitemaps was read from, in the
+    /// same order, or zero when not known.
+    #[prost(uint32, repeated, tag = "23")]
+    pub sitemap_line_numbers: ::prost::alloc::vec::Vec<u32>,
+    /// Why robots.txt could not be fetched, when the failure has a name of its
+    /// own: REDIRECT_LOOP or TOO_MANY_REDIRECTS, with access_result
+    /// ACCESS_RESULT_TOO_MANY_REDIRECTS.
+    #[prost(string, tag = "24")]
+    pub failure_reason: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Group {
+    #[prost(string, repeated, tag = "1")]
+    pub user_agents: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, repeated, tag = "2")]
+    pub rules: ::prost::alloc::vec::Vec<Rule>,
+    /// Deprecated: use crawl_delay. Kept for existing clients.
+    #[prost(double, optional, tag = "3")]
+    pub crawl_delay_seconds: ::core::option::Option<f64>,
+    #[prost(message, optional, tag = "4")]
+    pub crawl_delay: ::core::option::Option<::prost_types::Duration>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Rule {
+    #[prost(enumeration = "rule::RuleType", tag = "1")]
+    pub rule_type: i32,
+    #[prost(string, tag = "2")]
+    pub path_pattern: ::prost::alloc::string::String,
+    /// 1-based line of robots.txt the rule was read from, or zero when not
+    /// known, as for configured rules.
+    #[prost(uint32, tag = "3")]
+    pub line_number: u32,
+}
+/// Nested message and enum types in `Rule`.
+pub mod rule {
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum RuleType {
+        Unspecified = 0,
+        Allow = 1,
+        Disallow = 2,
+    }
+    impl RuleType {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Self::Unspecified => "RULE_TYPE_UNSPECIFIED",
+                Self::Allow => "RULE_TYPE_ALLOW",
+                Self::Disallow => "RULE_TYPE_DISALLOW",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "RULE_TYPE_UNSPECIFIED" => Some(Self::Unspecified),
+                "RULE_TYPE_ALLOW" => Some(Self::Allow),
+                "RULE_TYPE_DISALLOW" => Some(Self::Disallow),
+                _ => None,
+            }
+        }
+    }
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct IsAllowedRequest {
+    #[prost(string, tag = "1")]
+    pub target_url: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub user_agent: ::prost::alloc::string::String,
+    /// When robots.txt has to be refetched and the refetch would outlast the
+    /// call's deadline, answer from the last data held for the host instead.
+    #[prost(bool, tag = "3")]
+    pub allow_stale: bool,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct IsAllowedResponse {
+    #[prost(bool, tag = "1")]
+    pub allowed: bool,
+    /// Milliseconds to wait before fetching to respect the per-host rate limit
+    #[prost(uint64, tag = "2")]
+    pub suggested_wait_ms: u64,
+    /// The rule that decided, e.g. "Disallow: /admin", or why none was used.
+    /// Starts with "POLICY: " when a supplemental rule decided, and with
+    /// "STALE: " when the answer came from outdated data.
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
+    /// The decision used robots.txt data that is due to be refetched.
+    #[prost(bool, tag = "4")]
+    pub stale: bool,
+    #[prost(enumeration = "DataSource", tag = "5")]
+    pub data_source: i32,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CheckRobotsHeadersRequest {
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub user_agent: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CheckRobotsHeadersResponse {
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "2")]
+    pub http_status_code: u32,
+    #[prost(bool, tag = "3")]
+    pub header_present: bool,
+    #[prost(bool, tag = "4")]
+    pub noindex: bool,
+    #[prost(bool, tag = "5")]
+    pub nofollow: bool,
+    #[prost(bool, tag = "6")]
+    pub none: bool,
+    /// Seconds since the Unix epoch, unset when absent or unparseable.
+    #[prost(int64, optional, tag = "7")]
+    pub unavailable_after: ::core::option::Option<i64>,
+    #[prost(string, repeated, tag = "8")]
+    pub directives: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CanCrawlRequest {
+    #[prost(string, tag = "1")]
+    pub target_url: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub user_agent: ::prost::alloc::string::String,
+    /// Skip the X-Robots-Tag request and decide on robots.txt alone.
+    #[prost(bool, tag = "3")]
+    pub skip_header_check: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CanCrawlResponse {
+    #[prost(bool, tag = "1")]
+    pub allowed: bool,
+    #[prost(bool, tag = "2")]
+    pub robots_allowed: bool,
+    /// Unset when robots.txt denied the crawl or the header check was skipped.
+    #[prost(message, optional, tag = "3")]
+    pub headers: ::core::option::Option<CheckRobotsHeadersResponse>,
+    #[prost(enumeration = "DeniedBy", tag = "4")]
+    pub denied_by: i32,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct NextAllowedFetchRequest {
+    /// Any URL on the host; only the scheme, host, and port are used.
+    #[prost(string, tag = "1")]
+    pub host_url: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub user_agent: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct NextAllowedFetchResponse {
+    /// True when the caller may fetch now; the slot has been reserved for it.
+    #[prost(bool, tag = "1")]
+    pub granted: bool,
+    /// How long to wait before asking again when not granted.
+    #[prost(uint64, tag = "2")]
+    pub wait_ms: u64,
+    /// The delay enforced between grants for this host and user-agent.
+    #[prost(uint64, tag = "3")]
+    pub crawl_delay_ms: u64,
+    /// Whether the delay came from robots.txt rather than the server default.
+    #[prost(bool, tag = "4")]
+    pub crawl_delay_from_robots: bool,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct InvalidateUrlRequest {
+    /// Any URL on the host; it is normalized to its robots.txt URL.
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct InvalidateUrlResponse {
+    #[prost(string, tag = "1")]
+    pub robots_txt_url: ::prost::alloc::string::String,
+    /// Whether a cached entry existed and was removed.
+    #[prost(bool, tag = "2")]
+    pub existed: bool,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct StreamDecisionsRequest {
+    /// Only hosts equal to or under this domain; empty for all hosts.
+    #[prost(string, tag = "1")]
+    pub host_suffix: ::prost::alloc::string::String,
+    /// Only allowed (true) or denied (false) decisions; unset for both.
+    #[prost(bool, optional, tag = "2")]
+    pub allowed: ::core::option::Option<bool>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct DecisionEvent {
+    /// Milliseconds since the Unix epoch.
+    #[prost(int64, tag = "1")]
+    pub timestamp_ms: i64,
+    #[prost(string, tag = "2")]
+    pub host: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub user_agent: ::prost::alloc::string::String,
+    #[prost(bool, tag = "4")]
+    pub allowed: bool,
+    #[prost(string, tag = "5")]
+    pub reason: ::prost::alloc::string::String,
+    /// Whether robots.txt came from the cache.
+    #[prost(bool, tag = "6")]
+    pub cache_hit: bool,
+    /// Events dropped for this subscriber since the previous one, because it
+    /// fell behind. Dropped events are counted before filtering.
+    #[prost(uint64, tag = "7")]
+    pub dropped: u64,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetServerInfoRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetServerInfoResponse {
+    #[prost(string, tag = "1")]
+    pub version: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub git_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub uptime_seconds: u64,
+    #[prost(message, optional, tag = "4")]
+    pub limits: ::core::option::Option<ServerLimits>,
+    #[prost(message, optional, tag = "5")]
+    pub cache: ::core::option::Option<CacheSummary>,
+    /// robots.txt fetches currently waiting on an origin.
+    #[prost(uint64, tag = "6")]
+    pub in_flight_fetches: u64,
+    #[prost(uint64, tag = "7")]
+    pub fetches_total: u64,
+    /// Cargo features the server was built with.
+    #[prost(string, repeated, tag = "8")]
+    pub features: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ServerLimits {
+    #[prost(double, tag = "1")]
+    pub fetch_timeout_seconds: f64,
+    #[prost(uint64, tag = "2")]
+    pub cache_ttl_seconds: u64,
+    #[prost(uint64, tag = "3")]
+    pub header_cache_ttl_seconds: u64,
+    #[prost(uint64, tag = "4")]
+    pub max_robots_txt_bytes: u64,
+    #[prost(double, tag = "5")]
+    pub host_rate_per_second: f64,
+    #[prost(double, tag = "6")]
+    pub host_rate_burst: f64,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct CacheSummary {
+    /// Unset when the cache backend cannot count its entries.
+    #[prost(uint64, optional, tag = "1")]
+    pub entries: ::core::option::Option<u64>,
+    /// robots.txt lookups since the cache was created, as the backend counts
+    /// them; zero when it keeps no count.
+    #[prost(uint64, tag = "2")]
+    pub hits: u64,
+    #[prost(uint64, tag = "3")]
+    pub misses: u64,
+    #[prost(double, tag = "4")]
+    pub hit_rate: f64,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct SetPolicyRulesRequest {
+    /// Any URL on the host; the rules apply to the whole host.
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    /// Replaces the rules stored for the host; empty removes them.
+    #[prost(message, repeated, tag = "2")]
+    pub rules: ::prost::alloc::vec::Vec<Rule>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct SetPolicyRulesResponse {
+    #[prost(string, tag = "1")]
+    pub host: ::prost::alloc::string::String,
+    /// Every rule now applied to the host, configured ones first.
+    #[prost(message, repeated, tag = "2")]
+    pub rules: ::prost::alloc::vec::Vec<Rule>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct PrefetchHostsRequest {
+    /// Any URL on each host; only the scheme, host, and port are used.
+    #[prost(string, repeated, tag = "1")]
+    pub urls: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct PrefetchHostsResponse {
+    #[prost(uint64, tag = "1")]
+    pub job_id: u64,
+    /// Hosts queued to be fetched.
+    #[prost(uint64, tag = "2")]
+    pub accepted: u64,
+    /// Invalid or excluded URLs, and hosts that did not fit in the queue.
+    #[prost(uint64, tag = "3")]
+    pub rejected: u64,
+    /// Hosts already cached, listed more than once, or with a configured
+    /// override.
+    #[prost(uint64, tag = "4")]
+    pub skipped: u64,
+    /// Why each rejected URL was rejected.
+    #[prost(message, repeated, tag = "5")]
+    pub errors: ::prost::alloc::vec::Vec<ItemError>,
+}
+/// An item of a batch call that failed on its own, while the rest went
+/// ahead.
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ItemError {
+    /// The item's position in the request.
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    /// The google.rpc.Code the item would have failed with on its own.
+    #[prost(int32, tag = "2")]
+    pub code: i32,
+    #[prost(string, tag = "3")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetPrefetchStatusRequest {
+    #[prost(uint64, tag = "1")]
+    pub job_id: u64,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetPrefetchStatusResponse {
+    #[prost(uint64, tag = "1")]
+    pub job_id: u64,
+    /// Hosts whose robots.txt was fetched and cached, whatever the origin
+    /// answered.
+    #[prost(uint64, tag = "2")]
+    pub done: u64,
+    /// Hosts whose fetch could not complete, as when the fetch queue was full.
+    #[prost(uint64, tag = "3")]
+    pub failed: u64,
+    /// Hosts not yet fetched.
+    #[prost(uint64, tag = "4")]
+    pub remaining: u64,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RefreshRobotsRequest {
+    /// Any URL on the host; it is normalized to its robots.txt URL.
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    /// Admin: fetch even when the host's robots.txt was fetched within
+    /// min_refresh_interval_secs. Refused unless the call carries one of
+    /// admin_api_keys.
+    #[prost(bool, tag = "2")]
+    pub force: bool,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RefreshRobotsResponse {
+    #[prost(string, tag = "1")]
+    pub robots_txt_url: ::prost::alloc::string::String,
+    #[prost(enumeration = "AccessResult", tag = "2")]
+    pub access_result: i32,
+    #[prost(uint32, tag = "3")]
+    pub http_status_code: u32,
+    #[prost(message, optional, tag = "4")]
+    pub fetched_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// SHA-256 of the robots.txt body, hex-encoded.
+    #[prost(string, tag = "5")]
+    pub checksum: ::prost::alloc::string::String,
+    /// robots.txt was fetched too recently to fetch again, so this is the data
+    /// from that fetch, or what is cached when that is gone, or empty when
+    /// nothing is.
+    #[prost(bool, tag = "6")]
+    pub throttled: bool,
+    /// When a throttled refresh would next fetch; unset otherwise.
+    #[prost(message, optional, tag = "7")]
+    pub next_refresh_at: ::core::option::Option<::prost_types::Timestamp>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetCanonicalRobotsTextResponse {
+    /// The groups and sitemaps that are enforced, written out as robots.txt.
+    #[prost(string, tag = "1")]
+    pub text: ::prost::alloc::string::String,
+    /// SHA-256 of text, hex-encoded.
+    #[prost(string, tag = "2")]
+    pub checksum: ::prost::alloc::string::String,
+    /// SHA-256 of the fetched robots.txt body, as GetRobotsResponse.checksum.
+    #[prost(string, tag = "3")]
+    pub based_on_sha256: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ClearCacheRequest {}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ClearCacheResponse {
+    /// Entries dropped; unset when the cache backend cannot count them.
+    #[prost(uint64, optional, tag = "1")]
+    pub entries_dropped: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ListCachedEntriesRequest {
+    /// Entries per page; 0 for 100. Larger values are capped at 1000.
+    #[prost(uint32, tag = "1")]
+    pub page_size: u32,
+    /// The next_page_token of the previous page; empty for the first.
+    #[prost(string, tag = "2")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CachedEntry {
+    /// The robots.txt URL the entry is cached under.
+    #[prost(string, tag = "1")]
+    pub robots_txt_url: ::prost::alloc::string::String,
+    #[prost(enumeration = "AccessResult", tag = "2")]
+    pub access_result: i32,
+    #[prost(uint32, tag = "3")]
+    pub http_status_code: u32,
+    /// Seconds since robots.txt was fetched; 0 for data that did not come from
+    /// an origin.
+    #[prost(uint64, tag = "4")]
+    pub age_seconds: u64,
+    #[prost(bool, tag = "5")]
+    pub truncated: bool,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ListCachedEntriesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub entries: ::prost::alloc::vec::Vec<CachedEntry>,
+    /// Pass as page_token for the next page; empty on the last page.
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AccessResult {
+    Unspecified = 0,
+    Success = 1,
+    RedirectSuccess = 2,
+    TooManyRedirects = 3,
+    Unavailable = 4,
+    Unreachable = 5,
+    Cached = 6,
+    /// An operator-configured robots.txt was used; nothing was fetched.
+    Override = 7,
+    /// Fetched with a 2xx status, but nothing in the body could be understood,
+    /// as with binary or HTML content.
+    Unusable = 8,
+}
+impl AccessResult {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "ACCESS_RESULT_UNSPECIFIED",
+            Self::Success => "ACCESS_RESULT_SUCCESS",
+            Self::RedirectSuccess => "ACCESS_RESULT_REDIRECT_SUCCESS",
+            Self::TooManyRedirects => "ACCESS_RESULT_TOO_MANY_REDIRECTS",
+            Self::Unavailable => "ACCESS_RESULT_UNAVAILABLE",
+            Self::Unreachable => "ACCESS_RESULT_UNREACHABLE",
+            Self::Cached => "ACCESS_RESULT_CACHED",
+            Self::Override => "ACCESS_RESULT_OVERRIDE",
+            Self::Unusable => "ACCESS_RESULT_UNUSABLE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ACCESS_RESULT_UNSPECIFIED" => Some(Self::Unspecified),
+            "ACCESS_RESULT_SUCCESS" => Some(Self::Success),
+            "ACCESS_RESULT_REDIRECT_SUCCESS" => Some(Self::RedirectSuccess),
+            "ACCESS_RESULT_TOO_MANY_REDIRECTS" => Some(Self::TooManyRedirects),
+            "ACCESS_RESULT_UNAVAILABLE" => Some(Self::Unavailable),
+            "ACCESS_RESULT_UNREACHABLE" => Some(Self::Unreachable),
+            "ACCESS_RESULT_CACHED" => Some(Self::Cached),
+            "ACCESS_RESULT_OVERRIDE" => Some(Self::Override),
+            "ACCESS_RESULT_UNUSABLE" => Some(Self::Unusable),
+            _ => None,
+        }
+    }
+}
+/// Where the data behind a response came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DataSource {
+    Unspecified = 0,
+    /// robots.txt was fetched for this request.
+    FreshFetch = 1,
+    Cache = 2,
+    /// Earlier data, used because a refetch could not finish in time.
+    StaleCache = 3,
+    /// An operator-configured robots.txt; nothing was fetched.
+    Override = 4,
+    /// A supplemental rule decided before robots.txt was consulted.
+    SupplementalPolicy = 5,
+}
+impl DataSource {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "DATA_SOURCE_UNSPECIFIED",
+            Self::FreshFetch => "DATA_SOURCE_FRESH_FETCH",
+            Self::Cache => "DATA_SOURCE_CACHE",
+            Self::StaleCache => "DATA_SOURCE_STALE_CACHE",
+            Self::Override => "DATA_SOURCE_OVERRIDE",
+            Self::SupplementalPolicy => "DATA_SOURCE_SUPPLEMENTAL_POLICY",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "DATA_SOURCE_UNSPECIFIED" => Some(Self::Unspecified),
+            "DATA_SOURCE_FRESH_FETCH" => Some(Self::FreshFetch),
+            "DATA_SOURCE_CACHE" => Some(Self::Cache),
+            "DATA_SOURCE_STALE_CACHE" => Some(Self::StaleCache),
+            "DATA_SOURCE_OVERRIDE" => Some(Self::Override),
+            "DATA_SOURCE_SUPPLEMENTAL_POLICY" => Some(Self::SupplementalPolicy),
+            _ => None,
+        }
+    }
+}
+/// The address family of an origin address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AddressFamily {
+    Unspecified = 0,
+    Ipv4 = 1,
+    Ipv6 = 2,
+}
+impl AddressFamily {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "ADDRESS_FAMILY_UNSPECIFIED",
+            Self::Ipv4 => "ADDRESS_FAMILY_IPV4",
+            Self::Ipv6 => "ADDRESS_FAMILY_IPV6",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ADDRESS_FAMILY_UNSPECIFIED" => Some(Self::Unspecified),
+            "ADDRESS_FAMILY_IPV4" => Some(Self::Ipv4),
+            "ADDRESS_FAMILY_IPV6" => Some(Self::Ipv6),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DeniedBy {
+    None = 0,
+    RobotsTxt = 1,
+    XRobotsTag = 2,
+}
+impl DeniedBy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::None => "DENIED_BY_NONE",
+            Self::RobotsTxt => "DENIED_BY_ROBOTS_TXT",
+            Self::XRobotsTag => "DENIED_BY_X_ROBOTS_TAG",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "DENIED_BY_NONE" => Some(Self::None),
+            "DENIED_BY_ROBOTS_TXT" => Some(Self::RobotsTxt),
+            "DENIED_BY_X_ROBOTS_TAG" => Some(Self::XRobotsTag),
+            _ => None,
+        }
+    }
+}
+/// Generated client implementations.
+pub mod robots_service_client {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    #[derive(Debug, Clone)]
+    pub struct RobotsServiceClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl RobotsServiceClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> RobotsServiceClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::Body>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> RobotsServiceClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::Body>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::Body>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::Body>,
+            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+        {
+            RobotsServiceClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        pub async fn get_robots_txt(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetRobotsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/GetRobotsTxt",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "GetRobotsTxt"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn is_allowed(
+            &mut self,
+            request: impl tonic::IntoRequest<super::IsAllowedRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IsAllowedResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/IsAllowed",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "IsAllowed"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn check_robots_headers(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CheckRobotsHeadersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CheckRobotsHeadersResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/CheckRobotsHeaders",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "CheckRobotsHeaders"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn can_crawl(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CanCrawlRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CanCrawlResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/CanCrawl",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "CanCrawl"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn next_allowed_fetch(
+            &mut self,
+            request: impl tonic::IntoRequest<super::NextAllowedFetchRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::NextAllowedFetchResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/NextAllowedFetch",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "NextAllowedFetch"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn invalidate_url(
+            &mut self,
+            request: impl tonic::IntoRequest<super::InvalidateUrlRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::InvalidateUrlResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/InvalidateUrl",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "InvalidateUrl"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Admin: live feed of IsAllowed decisions.
+        pub async fn stream_decisions(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamDecisionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::DecisionEvent>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/StreamDecisions",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "StreamDecisions"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Admin: build, configuration, and runtime summary for debugging.
+        pub async fn get_server_info(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetServerInfoRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetServerInfoResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/GetServerInfo",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "GetServerInfo"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Admin: replace the supplemental rules checked before a host's robots.txt.
+        pub async fn set_policy_rules(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetPolicyRulesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SetPolicyRulesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/SetPolicyRules",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "SetPolicyRules"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Admin: fetch robots.txt for a list of hosts in the background, to warm
+        /// the cache. Returns at once with a job to follow.
+        pub async fn prefetch_hosts(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PrefetchHostsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PrefetchHostsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/PrefetchHosts",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "PrefetchHosts"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Admin: progress of a PrefetchHosts job.
+        pub async fn get_prefetch_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetPrefetchStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetPrefetchStatusResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/GetPrefetchStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "GetPrefetchStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Fetch a host's robots.txt again, bypassing the cache, at most once per
+        /// min_refresh_interval_secs.
+        pub async fn refresh_robots(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RefreshRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RefreshRobotsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/RefreshRobots",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "RefreshRobots"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// robots.txt as the server interprets it, from the same cached data as
+        /// GetRobotsTxt. Only url is read from the request.
+        pub async fn get_canonical_robots_text(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetCanonicalRobotsTextResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/GetCanonicalRobotsText",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "GetCanonicalRobotsText"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Admin: drop every cached robots.txt. Refused unless allow_clear_cache is
+        /// set.
+        pub async fn clear_cache(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ClearCacheRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ClearCacheResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/ClearCache",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "ClearCache"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Admin: the cached robots.txt entries, a page at a time, in order of
+        /// robots.txt URL. UNIMPLEMENTED with the Redis cache, which cannot list
+        /// its entries.
+        pub async fn list_cached_entries(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListCachedEntriesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListCachedEntriesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/robots.v1.RobotsService/ListCachedEntries",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("robots.v1.RobotsService", "ListCachedEntries"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod robots_service_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with RobotsServiceServer.
+    #[async_trait]
+    pub trait RobotsService: std::marker::Send + std::marker::Sync + 'static {
+        async fn get_robots_txt(
+            &self,
+            request: tonic::Request<super::GetRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetRobotsResponse>,
+            tonic::Status,
+        >;
+        async fn is_allowed(
+            &self,
+            request: tonic::Request<super::IsAllowedRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IsAllowedResponse>,
+            tonic::Status,
+        >;
+        async fn check_robots_headers(
+            &self,
+            request: tonic::Request<super::CheckRobotsHeadersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CheckRobotsHeadersResponse>,
+            tonic::Status,
+        >;
+        async fn can_crawl(
+            &self,
+            request: tonic::Request<super::CanCrawlRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CanCrawlResponse>,
+            tonic::Status,
+        >;
+        async fn next_allowed_fetch(
+            &self,
+            request: tonic::Request<super::NextAllowedFetchRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::NextAllowedFetchResponse>,
+            tonic::Status,
+        >;
+        async fn invalidate_url(
+            &self,
+            request: tonic::Request<super::InvalidateUrlRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::InvalidateUrlResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the StreamDecisions method.
+        type StreamDecisionsStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::DecisionEvent, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        /// Admin: live feed of IsAllowed decisions.
+        async fn stream_decisions(
+            &self,
+            request: tonic::Request<super::StreamDecisionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamDecisionsStream>,
+            tonic::Status,
+        >;
+        /// Admin: build, configuration, and runtime summary for debugging.
+        async fn get_server_info(
+            &self,
+            request: tonic::Request<super::GetServerInfoRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetServerInfoResponse>,
+            tonic::Status,
+        >;
+        /// Admin: replace the supplemental rules checked before a host's robots.txt.
+        async fn set_policy_rules(
+            &self,
+            request: tonic::Request<super::SetPolicyRulesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SetPolicyRulesResponse>,
+            tonic::Status,
+        >;
+        /// Admin: fetch robots.txt for a list of hosts in the background, to warm
+        /// the cache. Returns at once with a job to follow.
+        async fn prefetch_hosts(
+            &self,
+            request: tonic::Request<super::PrefetchHostsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PrefetchHostsResponse>,
+            tonic::Status,
+        >;
+        /// Admin: progress of a PrefetchHosts job.
+        async fn get_prefetch_status(
+            &self,
+            request: tonic::Request<super::GetPrefetchStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetPrefetchStatusResponse>,
+            tonic::Status,
+        >;
+        /// Fetch a host's robots.txt again, bypassing the cache, at most once per
+        /// min_refresh_interval_secs.
+        async fn refresh_robots(
+            &self,
+            request: tonic::Request<super::RefreshRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RefreshRobotsResponse>,
+            tonic::Status,
+        >;
+        /// robots.txt as the server interprets it, from the same cached data as
+        /// GetRobotsTxt. Only url is read from the request.
+        async fn get_canonical_robots_text(
+            &self,
+            request: tonic::Request<super::GetRobotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetCanonicalRobotsTextResponse>,
+            tonic::Status,
+        >;
+        /// Admin: drop every cached robots.txt. Refused unless allow_clear_cache is
+        /// set.
+        async fn clear_cache(
+            &self,
+            request: tonic::Request<super::ClearCacheRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ClearCacheResponse>,
+            tonic::Status,
+        >;
+        /// Admin: the cached robots.txt entries, a page at a time, in order of
+        /// robots.txt URL. UNIMPLEMENTED with the Redis cache, which cannot list
+        /// its entries.
+        async fn list_cached_entries(
+            &self,
+            request: tonic::Request<super::ListCachedEntriesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListCachedEntriesResponse>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct RobotsServiceServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> RobotsServiceServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for RobotsServiceServer<T>
+    where
+        T: RobotsService,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::Body>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/robots.v1.RobotsService/GetRobotsTxt" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetRobotsTxtSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::GetRobotsRequest>
+                    for GetRobotsTxtSvc<T> {
+                        type Response = super::GetRobotsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetRobotsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::get_robots_txt(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetRobotsTxtSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/IsAllowed" => {
+                    #[allow(non_camel_case_types)]
+                    struct IsAllowedSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::IsAllowedRequest>
+                    for IsAllowedSvc<T> {
+                        type Response = super::IsAllowedResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::IsAllowedRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::is_allowed(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = IsAllowedSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/CheckRobotsHeaders" => {
+                    #[allow(non_camel_case_types)]
+                    struct CheckRobotsHeadersSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::CheckRobotsHeadersRequest>
+                    for CheckRobotsHeadersSvc<T> {
+                        type Response = super::CheckRobotsHeadersResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CheckRobotsHeadersRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::check_robots_headers(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CheckRobotsHeadersSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/CanCrawl" => {
+                    #[allow(non_camel_case_types)]
+                    struct CanCrawlSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::CanCrawlRequest>
+                    for CanCrawlSvc<T> {
+                        type Response = super::CanCrawlResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CanCrawlRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::can_crawl(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CanCrawlSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/NextAllowedFetch" => {
+                    #[allow(non_camel_case_types)]
+                    struct NextAllowedFetchSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::NextAllowedFetchRequest>
+                    for NextAllowedFetchSvc<T> {
+                        type Response = super::NextAllowedFetchResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::NextAllowedFetchRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::next_allowed_fetch(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = NextAllowedFetchSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/InvalidateUrl" => {
+                    #[allow(non_camel_case_types)]
+                    struct InvalidateUrlSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::InvalidateUrlRequest>
+                    for InvalidateUrlSvc<T> {
+                        type Response = super::InvalidateUrlResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::InvalidateUrlRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::invalidate_url(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = InvalidateUrlSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/StreamDecisions" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamDecisionsSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::ServerStreamingService<super::StreamDecisionsRequest>
+                    for StreamDecisionsSvc<T> {
+                        type Response = super::DecisionEvent;
+                        type ResponseStream = T::StreamDecisionsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamDecisionsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::stream_decisions(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = StreamDecisionsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/GetServerInfo" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetServerInfoSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::GetServerInfoRequest>
+                    for GetServerInfoSvc<T> {
+                        type Response = super::GetServerInfoResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetServerInfoRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::get_server_info(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetServerInfoSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/SetPolicyRules" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetPolicyRulesSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::SetPolicyRulesRequest>
+                    for SetPolicyRulesSvc<T> {
+                        type Response = super::SetPolicyRulesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetPolicyRulesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::set_policy_rules(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = SetPolicyRulesSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/PrefetchHosts" => {
+                    #[allow(non_camel_case_types)]
+                    struct PrefetchHostsSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::PrefetchHostsRequest>
+                    for PrefetchHostsSvc<T> {
+                        type Response = super::PrefetchHostsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PrefetchHostsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::prefetch_hosts(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = PrefetchHostsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/GetPrefetchStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetPrefetchStatusSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::GetPrefetchStatusRequest>
+                    for GetPrefetchStatusSvc<T> {
+                        type Response = super::GetPrefetchStatusResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetPrefetchStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::get_prefetch_status(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetPrefetchStatusSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/RefreshRobots" => {
+                    #[allow(non_camel_case_types)]
+                    struct RefreshRobotsSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::RefreshRobotsRequest>
+                    for RefreshRobotsSvc<T> {
+                        type Response = super::RefreshRobotsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RefreshRobotsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::refresh_robots(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RefreshRobotsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/GetCanonicalRobotsText" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetCanonicalRobotsTextSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::GetRobotsRequest>
+                    for GetCanonicalRobotsTextSvc<T> {
+                        type Response = super::GetCanonicalRobotsTextResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetRobotsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::get_canonical_robots_text(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetCanonicalRobotsTextSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/ClearCache" => {
+                    #[allow(non_camel_case_types)]
+                    struct ClearCacheSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::ClearCacheRequest>
+                    for ClearCacheSvc<T> {
+                        type Response = super::ClearCacheResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ClearCacheRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::clear_cache(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ClearCacheSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/robots.v1.RobotsService/ListCachedEntries" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListCachedEntriesSvc<T: RobotsService>(pub Arc<T>);
+                    impl<
+                        T: RobotsService,
+                    > tonic::server::UnaryService<super::ListCachedEntriesRequest>
+                    for ListCachedEntriesSvc<T> {
+                        type Response = super::ListCachedEntriesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListCachedEntriesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RobotsService>::list_cached_entries(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListCachedEntriesSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        let mut response = http::Response::new(
+                            tonic::body::Body::default(),
+                        );
+                        let headers = response.headers_mut();
+                        headers
+                            .insert(
+                                tonic::Status::GRPC_STATUS,
+                                (tonic::Code::Unimplemented as i32).into(),
+                            );
+                        headers
+                            .insert(
+                                http::header::CONTENT_TYPE,
+                                tonic::metadata::GRPC_CONTENT_TYPE,
+                            );
+                        Ok(response)
+                    })
+                }
+            }
+        }
+    }
+    impl<T> Clone for RobotsServiceServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+                max_decoding_message_size: self.max_decoding_message_size,
+                max_encoding_message_size: self.max_encoding_message_size,
+            }
+        }
+    }
+    /// Generated gRPC service name
+    pub const SERVICE_NAME: &str = "robots.v1.RobotsService";
+    impl<T> tonic::server::NamedService for RobotsServiceServer<T> {
+        const NAME: &'static str = SERVICE_NAME;
+    }
+}
@@ -0,0 +1,79 @@
+use std::future::Future;
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use axum::Router;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use futures_util::future::BoxFuture;
+use tokio::net::TcpListener;
+use tracing::info;
+
+type Check = Arc<dyn Fn() -> BoxFuture<'static, bool> + Send + Sync>;
+
+/// Whether `/readyz` reports the server ready: once it is serving, for as
+/// long as `check` passes, and no longer once shutdown has begun.
+#[derive(Clone)]
+pub struct Readiness {
+    serving: Arc<AtomicBool>,
+    check: Check,
+}
+
+impl Readiness {
+    /// Not ready until [`set_serving`](Self::set_serving). `check` runs on
+    /// every `/readyz`, to confirm the cache backend still answers.
+    pub fn new(check: impl Fn() -> BoxFuture<'static, bool> + Send + Sync + 'static) -> Self {
+        Self {
+            serving: Arc::default(),
+            check: Arc::new(check),
+        }
+    }
+
+    pub fn set_serving(&self, serving: bool) {
+        self.serving.store(serving, Ordering::Relaxed);
+    }
+
+    /// `signal`, marking the server not ready as soon as it resolves so load
+    /// balancers stop sending it requests while those in flight drain.
+    pub fn draining_after(self, signal: impl Future<Output = ()>) -> impl Future<Output = ()> {
+        async move {
+            signal.await;
+            self.set_serving(false);
+            info!("Reporting not ready while draining");
+        }
+    }
+
+    pub async fn ready(&self) -> bool {
+        self.serving.load(Ordering::Relaxed) && (self.check)().await
+    }
+}
+
+/// `/healthz`, which answers 200 while the process runs, and `/readyz`,
+/// which answers 200 when `readiness` says so and 503 otherwise.
+pub fn router(readiness: Readiness) -> Router {
+    Router::new()
+        .route("/healthz", get(|| async { "ok\n" }))
+        .route("/readyz", get(readyz))
+        .with_state(readiness)
+}
+
+async fn readyz(State(readiness): State<Readiness>) -> (StatusCode, &'static str) {
+    if readiness.ready().await {
+        (StatusCode::OK, "ready\n")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not ready\n")
+    }
+}
+
+/// Serve [`router`] over HTTP/1 on `listener` until `shutdown` resolves.
+pub async fn serve(
+    listener: TcpListener,
+    readiness: Readiness,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> io::Result<()> {
+    axum::serve(listener, router(readiness))
+        .with_graceful_shutdown(shutdown)
+        .await
+}
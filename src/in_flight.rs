@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use tokio::task::AbortHandle;
+use tonic::Status;
+use tracing::{Instrument, debug, warn};
+
+use crate::robots_data::RobotsData;
+
+/// A fetch that any number of callers can wait on for the same result.
+pub type SharedFetch = Shared<BoxFuture<'static, Result<Arc<RobotsData>, Status>>>;
+
+/// The robots.txt fetch running for each cache key, so that callers that
+/// need one while it runs join it rather than fetching again. Clones share
+/// the fetches.
+#[derive(Clone, Default)]
+pub struct InFlightFetches {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    fetches: HashMap<String, (SharedFetch, AbortHandle)>,
+    /// Set by [`InFlightFetches::cancel_all`]; no fetch starts after it.
+    cancelled: bool,
+}
+
+impl InFlightFetches {
+    /// The fetch running for `cache_key`, or else `refresh` started as it.
+    /// Every caller gets the result, failures included. The fetch is
+    /// spawned, so it finishes and makes way for the next one even when
+    /// every caller waiting on it has gone.
+    pub fn join(
+        &self,
+        cache_key: String,
+        refresh: impl Future<Output = Result<Arc<RobotsData>, Status>> + Send + 'static,
+    ) -> SharedFetch {
+        let mut state = self.state();
+        if let Some((fetch, _)) = state.fetches.get(&cache_key) {
+            debug!("Joining robots.txt fetch already in flight");
+            return fetch.clone();
+        }
+        if state.cancelled {
+            debug!("Refusing fetch after shutdown");
+            return async { Err(Status::unavailable("Server is shutting down")) }
+                .boxed()
+                .shared();
+        }
+        // Held by the task, which cannot remove the fetch before it is
+        // added, as that needs the lock held here
+        let guard = Guard {
+            fetches: self.clone(),
+            cache_key: cache_key.clone(),
+        };
+        let handle = tokio::spawn(
+            async move {
+                let _guard = guard;
+                refresh.await
+            }
+            .in_current_span(),
+        );
+        let abort = handle.abort_handle();
+        let fetch = async move {
+            handle.await.unwrap_or_else(|e| {
+                if e.is_cancelled() {
+                    return Err(Status::unavailable("Server is shutting down"));
+                }
+                warn!(error = %e, "robots.txt refresh failed");
+                Err(Status::internal(e.to_string()))
+            })
+        }
+        .boxed()
+        .shared();
+        state.fetches.insert(cache_key, (fetch.clone(), abort));
+        fetch
+    }
+
+    /// Abort every fetch still running, so that none writes to the cache
+    /// after this, and refuse any more with `UNAVAILABLE`. Callers waiting
+    /// on them get the same. Returns how many were aborted.
+    pub fn cancel_all(&self) -> usize {
+        let aborts = {
+            let mut state = self.state();
+            state.cancelled = true;
+            state
+                .fetches
+                .values()
+                .map(|(_, abort)| abort.clone())
+                .collect::<Vec<_>>()
+        };
+        // Aborted outside the lock, which each task takes as it ends
+        for abort in &aborts {
+            abort.abort();
+        }
+        aborts.len()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Removes a fetch from the map when it ends, however it ends.
+struct Guard {
+    fetches: InFlightFetches,
+    cache_key: String,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.fetches.state().fetches.remove(&self.cache_key);
+    }
+}
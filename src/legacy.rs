@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::{
+    cache::Cache,
+    cached_robots::CachedRobots,
+    decisions::DecisionStream,
+    fetcher::{Fetcher, RobotsFetcher},
+    service::{
+        RobotsServer,
+        robots::{
+            robots_service_server::RobotsService as LegacyRobotsService,
+            v1::{
+                CanCrawlRequest, CanCrawlResponse, CheckRobotsHeadersRequest,
+                CheckRobotsHeadersResponse, ClearCacheRequest, ClearCacheResponse,
+                GetCanonicalRobotsTextResponse, GetPrefetchStatusRequest,
+                GetPrefetchStatusResponse, GetRobotsRequest, GetRobotsResponse,
+                GetServerInfoRequest, GetServerInfoResponse, InvalidateUrlRequest,
+                InvalidateUrlResponse, IsAllowedRequest, IsAllowedResponse,
+                ListCachedEntriesRequest, ListCachedEntriesResponse, NextAllowedFetchRequest,
+                NextAllowedFetchResponse, PrefetchHostsRequest, PrefetchHostsResponse,
+                RefreshRobotsRequest, RefreshRobotsResponse, SetPolicyRulesRequest,
+                SetPolicyRulesResponse, StreamDecisionsRequest,
+                robots_service_server::RobotsService,
+            },
+        },
+    },
+};
+
+/// The unversioned `robots.RobotsService`, kept for clients built before the
+/// package was versioned. Every call forwards to a shared [`RobotsServer`],
+/// so both paths see the same cache and counters; the message types are the
+/// `robots.v1` ones, so nothing is converted.
+pub struct LegacyRobotsServer<T: Cache<String, CachedRobots>, F: Fetcher = RobotsFetcher> {
+    inner: Arc<RobotsServer<T, F>>,
+}
+
+impl<T: Cache<String, CachedRobots>, F: Fetcher> LegacyRobotsServer<T, F> {
+    pub fn new(inner: Arc<RobotsServer<T, F>>) -> Self {
+        Self { inner }
+    }
+}
+
+#[tonic::async_trait]
+impl<T: Cache<String, CachedRobots>, F: Fetcher> LegacyRobotsService for LegacyRobotsServer<T, F> {
+    async fn get_robots_txt(
+        &self,
+        request: Request<GetRobotsRequest>,
+    ) -> Result<Response<GetRobotsResponse>, Status> {
+        self.inner.get_robots_txt(request).await
+    }
+
+    async fn is_allowed(
+        &self,
+        request: Request<IsAllowedRequest>,
+    ) -> Result<Response<IsAllowedResponse>, Status> {
+        self.inner.is_allowed(request).await
+    }
+
+    async fn check_robots_headers(
+        &self,
+        request: Request<CheckRobotsHeadersRequest>,
+    ) -> Result<Response<CheckRobotsHeadersResponse>, Status> {
+        self.inner.check_robots_headers(request).await
+    }
+
+    async fn can_crawl(
+        &self,
+        request: Request<CanCrawlRequest>,
+    ) -> Result<Response<CanCrawlResponse>, Status> {
+        self.inner.can_crawl(request).await
+    }
+
+    async fn next_allowed_fetch(
+        &self,
+        request: Request<NextAllowedFetchRequest>,
+    ) -> Result<Response<NextAllowedFetchResponse>, Status> {
+        self.inner.next_allowed_fetch(request).await
+    }
+
+    async fn invalidate_url(
+        &self,
+        request: Request<InvalidateUrlRequest>,
+    ) -> Result<Response<InvalidateUrlResponse>, Status> {
+        self.inner.invalidate_url(request).await
+    }
+
+    type StreamDecisionsStream = DecisionStream;
+
+    async fn stream_decisions(
+        &self,
+        request: Request<StreamDecisionsRequest>,
+    ) -> Result<Response<Self::StreamDecisionsStream>, Status> {
+        self.inner.stream_decisions(request).await
+    }
+
+    async fn get_server_info(
+        &self,
+        request: Request<GetServerInfoRequest>,
+    ) -> Result<Response<GetServerInfoResponse>, Status> {
+        self.inner.get_server_info(request).await
+    }
+
+    async fn set_policy_rules(
+        &self,
+        request: Request<SetPolicyRulesRequest>,
+    ) -> Result<Response<SetPolicyRulesResponse>, Status> {
+        self.inner.set_policy_rules(request).await
+    }
+
+    async fn prefetch_hosts(
+        &self,
+        request: Request<PrefetchHostsRequest>,
+    ) -> Result<Response<PrefetchHostsResponse>, Status> {
+        self.inner.prefetch_hosts(request).await
+    }
+
+    async fn get_prefetch_status(
+        &self,
+        request: Request<GetPrefetchStatusRequest>,
+    ) -> Result<Response<GetPrefetchStatusResponse>, Status> {
+        self.inner.get_prefetch_status(request).await
+    }
+
+    async fn refresh_robots(
+        &self,
+        request: Request<RefreshRobotsRequest>,
+    ) -> Result<Response<RefreshRobotsResponse>, Status> {
+        self.inner.refresh_robots(request).await
+    }
+
+    async fn get_canonical_robots_text(
+        &self,
+        request: Request<GetRobotsRequest>,
+    ) -> Result<Response<GetCanonicalRobotsTextResponse>, Status> {
+        self.inner.get_canonical_robots_text(request).await
+    }
+
+    async fn clear_cache(
+        &self,
+        request: Request<ClearCacheRequest>,
+    ) -> Result<Response<ClearCacheResponse>, Status> {
+        self.inner.clear_cache(request).await
+    }
+
+    async fn list_cached_entries(
+        &self,
+        request: Request<ListCachedEntriesRequest>,
+    ) -> Result<Response<ListCachedEntriesResponse>, Status> {
+        self.inner.list_cached_entries(request).await
+    }
+}
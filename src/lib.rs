@@ -1,4 +1,87 @@
+#[cfg(feature = "server")]
+pub mod access_log;
+#[cfg(feature = "server")]
+pub mod admin_cli;
+#[cfg(feature = "server")]
+pub mod auth;
 pub mod cache;
+pub mod cached_robots;
+pub mod cassette;
+pub mod chaos;
+pub mod check;
+#[cfg(feature = "server")]
+pub mod cli;
+#[cfg(feature = "server")]
+pub mod client;
+#[cfg(feature = "server")]
+pub mod client_cli;
+#[cfg(feature = "tls")]
+pub mod client_identity;
+pub mod clock;
+pub mod config;
+#[cfg(feature = "server")]
+pub mod decisions;
+pub mod dns;
+pub mod fetch_queue;
 pub mod fetcher;
+#[cfg(feature = "server")]
+pub mod health;
+#[cfg(feature = "server")]
+pub mod in_flight;
+#[cfg(feature = "server")]
+pub mod legacy;
+#[cfg(feature = "server")]
+pub mod listen;
+#[cfg(feature = "server")]
+pub mod load_shed;
+#[cfg(feature = "server")]
+pub mod log_format;
+pub mod log_sampling;
+#[cfg(feature = "server")]
+pub mod log_sink;
+pub mod metrics;
+#[cfg(feature = "middleware")]
+pub mod middleware;
+pub mod overrides;
+pub mod pagination;
+#[cfg(feature = "server")]
+pub mod panic_guard;
+pub mod parser;
+#[cfg(feature = "server")]
+pub mod peer_filter;
+pub mod politeness;
+pub mod prefetch;
+#[cfg(feature = "server")]
+pub mod read_mask;
+pub mod redact;
+#[cfg(feature = "server")]
+pub mod request_id;
 pub mod robots_data;
+#[cfg(feature = "server")]
+pub mod runtime;
+#[cfg(feature = "server")]
+pub mod self_check;
+#[cfg(feature = "server")]
+pub mod server_builder;
+#[cfg(feature = "server")]
 pub mod service;
+#[cfg(feature = "server")]
+pub mod shutdown;
+pub mod snapshot;
+pub mod static_fetcher;
+pub mod stored_robots;
+#[cfg(all(unix, feature = "systemd"))]
+pub mod systemd;
+pub mod target;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod trace_context;
+#[cfg(unix)]
+pub mod uds;
+#[cfg(feature = "server")]
+pub mod warm;
+pub mod x_robots_tag;
+
+pub use check::{CheckError, Decision, check_url, check_url_with};
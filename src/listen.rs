@@ -0,0 +1,92 @@
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures_util::Stream;
+use socket2::{Domain, Protocol, SockRef, Socket, TcpKeepalive, Type};
+use tokio::net::{TcpListener, TcpStream};
+use tonic::transport::Server;
+use tracing::warn;
+
+use crate::config::{BindFailure, TransportConfig};
+
+/// Connections waiting to be accepted before new ones are refused.
+const BACKLOG: i32 = 1024;
+
+/// Listen on each of `addrs`. When there is more than one, IPv6 addresses
+/// accept only IPv6, so `0.0.0.0` and `[::]` on the same port can be served
+/// side by side. With [`BindFailure::Warn`], an address that cannot be bound
+/// is logged and left out, as long as one of the others can be bound.
+pub fn bind_all(addrs: &[SocketAddr], on_failure: BindFailure) -> io::Result<Vec<TcpListener>> {
+    let only_v6 = addrs.len() > 1;
+    let mut listeners = Vec::new();
+    let mut failed = None;
+    for &addr in addrs {
+        match bind(addr, only_v6) {
+            Ok(listener) => listeners.push(listener),
+            Err(e) => {
+                let e = io::Error::new(e.kind(), format!("Failed to bind {addr}: {e}"));
+                if on_failure == BindFailure::Error {
+                    return Err(e);
+                }
+                warn!(%addr, error = %e, "Not serving on an address that could not be bound");
+                failed = Some(e);
+            }
+        }
+    }
+    match failed {
+        Some(e) if listeners.is_empty() => Err(e),
+        _ => Ok(listeners),
+    }
+}
+
+/// Listen on `addr`. Must be called from within a tokio runtime.
+fn bind(addr: SocketAddr, only_v6: bool) -> io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(only_v6)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(BACKLOG)?;
+    TcpListener::from_std(socket.into())
+}
+
+/// `server` with the HTTP/2 settings in `transport`. Its TCP keepalive is
+/// applied by [`incoming`] instead, as tonic only sets it on connections it
+/// accepts itself.
+pub fn configure(server: Server, transport: &TransportConfig) -> Server {
+    let mut server = server
+        .http2_keepalive_interval(transport.http2_keepalive_interval())
+        .max_concurrent_streams(transport.max_concurrent_streams);
+    if let Some(timeout) = transport.http2_keepalive_timeout() {
+        server = server.http2_keepalive_timeout(timeout);
+    }
+    if let Some(timeout) = transport.timeout() {
+        server = server.timeout(timeout);
+    }
+    server
+}
+
+/// The connections accepted on `listener`, for `serve_with_incoming`, with
+/// TCP keepalive probes after `tcp_keepalive` of idleness if given.
+pub fn incoming(
+    listener: TcpListener,
+    tcp_keepalive: Option<Duration>,
+) -> impl Stream<Item = io::Result<TcpStream>> {
+    Box::pin(futures_util::stream::unfold(
+        listener,
+        move |listener| async move {
+            let accepted = listener.accept().await.and_then(|(stream, _)| {
+                stream.set_nodelay(true)?;
+                if let Some(idle) = tcp_keepalive {
+                    SockRef::from(&stream)
+                        .set_tcp_keepalive(&TcpKeepalive::new().with_time(idle))?;
+                }
+                Ok(stream)
+            });
+            Some((accepted, listener))
+        },
+    ))
+}
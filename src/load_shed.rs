@@ -0,0 +1,93 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+
+use tokio::sync::Semaphore;
+use tonic::Status;
+use tower::{Layer, Service};
+use tracing::{info, warn};
+
+use crate::metrics;
+
+/// Tower layer that lets at most `max` calls run at once, across every
+/// connection and every service built from it. Calls over the limit get
+/// `RESOURCE_EXHAUSTED` at once instead of waiting for a turn. A streaming
+/// call counts until its response starts.
+#[derive(Clone)]
+pub struct LoadShedLayer {
+    max: usize,
+    permits: Option<Arc<Semaphore>>,
+    shedding: Arc<AtomicBool>,
+}
+
+impl LoadShedLayer {
+    /// A `max` of 0 lets every call through.
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            permits: (max > 0).then(|| Arc::new(Semaphore::new(max))),
+            shedding: Arc::default(),
+        }
+    }
+}
+
+impl<S> Layer<S> for LoadShedLayer {
+    type Service = LoadShed<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LoadShed {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LoadShed<S> {
+    inner: S,
+    layer: LoadShedLayer,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for LoadShed<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let Some(permits) = &self.layer.permits else {
+            return Box::pin(self.inner.call(request));
+        };
+        let Ok(permit) = permits.clone().try_acquire_owned() else {
+            metrics::REQUESTS_SHED.inc();
+            if !self.layer.shedding.swap(true, Ordering::Relaxed) {
+                warn!(
+                    max_concurrent_requests = self.layer.max,
+                    "Concurrency limit reached, shedding requests"
+                );
+            }
+            let response = Status::resource_exhausted("Server is at its concurrency limit");
+            let response = response.into_http();
+            return Box::pin(async move { Ok(response) });
+        };
+        if self.layer.shedding.swap(false, Ordering::Relaxed) {
+            info!("Below the concurrency limit again, no longer shedding requests");
+        }
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await;
+            drop(permit);
+            response
+        })
+    }
+}
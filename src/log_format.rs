@@ -0,0 +1,156 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use chrono::{SecondsFormat, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber, warn};
+use tracing_subscriber::fmt::format::{JsonFields, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormattedFields, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::reload;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// How log lines are written to stderr.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line, for log pipelines.
+    Json,
+}
+
+/// Replaces the filter of the stderr layer, with the directives that layer
+/// started with.
+struct StderrFilter {
+    initial: Option<String>,
+    reload: Box<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>,
+}
+
+static STDERR_FILTER: OnceLock<StderrFilter> = OnceLock::new();
+
+/// Stderr logging in `format`, filtered by `filter`, or by `RUST_LOG`
+/// without one. The filter can be changed later with [`set_filter`].
+pub fn stderr_layer<S>(format: LogFormat, filter: Option<&str>) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + 'static,
+{
+    let layer = match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .boxed(),
+        LogFormat::Json => json_layer(std::io::stderr).boxed(),
+    };
+    let (reloadable, handle) = reload::Layer::new(env_filter(filter));
+    let _ = STDERR_FILTER.set(StderrFilter {
+        initial: filter.map(str::to_string),
+        reload: Box::new(move |filter| handle.reload(filter)),
+    });
+    layer.with_filter(reloadable).boxed()
+}
+
+/// Filter stderr logging by `filter`, or go back to the filter it started
+/// with when given `None`. Does nothing before [`stderr_layer`] is called.
+/// `filter` must already be known to be valid.
+pub fn set_filter(filter: Option<&str>) {
+    let Some(stderr) = STDERR_FILTER.get() else {
+        return;
+    };
+    let filter = env_filter(filter.or(stderr.initial.as_deref()));
+    if let Err(e) = (stderr.reload)(filter) {
+        warn!(error = %e, "Failed to change the log filter");
+    }
+}
+
+fn env_filter(filter: Option<&str>) -> EnvFilter {
+    filter.map_or_else(EnvFilter::from_default_env, EnvFilter::new)
+}
+
+/// A layer writing [`JsonLines`] to `writer`.
+pub fn json_layer<S, W>(writer: W) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt::layer()
+        .fmt_fields(JsonFields::new())
+        .event_format(JsonLines)
+        .with_writer(writer)
+}
+
+/// Formats each event as a single JSON object with `timestamp` (RFC 3339,
+/// UTC), `level`, `target`, `span`, and the fields of the event and of every
+/// span it is in, all at the top level. Fields recorded on a span after it
+/// was created are included. When names collide, the event wins over spans
+/// and inner spans win over outer ones.
+pub struct JsonLines;
+
+impl<S> FormatEvent<S, JsonFields> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, JsonFields>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".to_string(),
+            Utc::now()
+                .to_rfc3339_opts(SecondsFormat::Micros, true)
+                .into(),
+        );
+        line.insert("level".to_string(), metadata.level().as_str().into());
+        line.insert("target".to_string(), metadata.target().into());
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                line.insert("span".to_string(), span.name().into());
+                let extensions = span.extensions();
+                // JsonFields stores each span's fields as a JSON object
+                if let Some(fields) = extensions.get::<FormattedFields<JsonFields>>()
+                    && let Ok(Value::Object(fields)) = serde_json::from_str(&fields.fields)
+                {
+                    line.extend(fields);
+                }
+            }
+        }
+        event.record(&mut JsonVisitor(&mut line));
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}").into());
+    }
+}
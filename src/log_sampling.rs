@@ -0,0 +1,97 @@
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// How often hot-path debug events are let through, as configured in the
+/// `[log_sampling]` table.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct LogSampling {
+    /// Occurrences of each event logged per interval.
+    pub burst: u64,
+    /// Length of the interval. 0 turns sampling off and logs every event.
+    pub interval_secs: f64,
+}
+
+impl Default for LogSampling {
+    fn default() -> Self {
+        Self {
+            burst: 10,
+            interval_secs: 10.0,
+        }
+    }
+}
+
+static BURST: AtomicU64 = AtomicU64::new(10);
+static INTERVAL_MS: AtomicU64 = AtomicU64::new(10_000);
+
+/// Apply `sampling` to every [`LogSampler`] in the process.
+pub fn configure(sampling: &LogSampling) {
+    BURST.store(sampling.burst, Ordering::Relaxed);
+    let interval = Duration::from_secs_f64(sampling.interval_secs);
+    INTERVAL_MS.store(interval.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// Milliseconds since the first sampled event, so windows fit in an atomic.
+fn now_ms() -> u64 {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed().as_millis() as u64
+}
+
+/// Limits one frequent debug event to the first few occurrences in each
+/// interval. When a new interval starts, the number suppressed in the last
+/// one is logged under the sampler's key. Declare one `static` per call
+/// site and log only when [`LogSampler::sample`] returns true.
+#[derive(Debug)]
+pub struct LogSampler {
+    key: &'static str,
+    window_start: AtomicU64,
+    seen: AtomicU64,
+    suppressed: AtomicU64,
+}
+
+impl LogSampler {
+    pub const fn new(key: &'static str) -> Self {
+        Self {
+            key,
+            window_start: AtomicU64::new(0),
+            seen: AtomicU64::new(0),
+            suppressed: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether this occurrence should be logged.
+    pub fn sample(&self) -> bool {
+        let interval = INTERVAL_MS.load(Ordering::Relaxed);
+        if interval == 0 {
+            return true;
+        }
+        let now = now_ms();
+        let start = self.window_start.load(Ordering::Relaxed);
+        // Only the caller that moves the window reports and resets it
+        if now.saturating_sub(start) >= interval
+            && self
+                .window_start
+                .compare_exchange(start, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            self.seen.store(0, Ordering::Relaxed);
+            let suppressed = self.suppressed.swap(0, Ordering::Relaxed);
+            if suppressed > 0 {
+                debug!(
+                    key = self.key,
+                    suppressed, "Suppressed {suppressed} similar events"
+                );
+            }
+        }
+        if self.seen.fetch_add(1, Ordering::Relaxed) < BURST.load(Ordering::Relaxed) {
+            true
+        } else {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+    }
+}
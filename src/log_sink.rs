@@ -0,0 +1,146 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, Utc};
+use tracing::Subscriber;
+use tracing_appender::non_blocking::{ErrorCounter, NonBlocking, NonBlockingBuilder, WorkerGuard};
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::{LogSinkConfig, Rotation};
+
+/// Lines buffered for the writer thread before new ones are dropped.
+const BUFFERED_LINES: usize = 128_000;
+
+/// File writer that rotates by size or day and keeps a fixed number of
+/// old files.
+pub struct RotatingFile {
+    path: PathBuf,
+    rotation: Rotation,
+    retain: usize,
+    file: File,
+    written: u64,
+    opened_on: NaiveDate,
+}
+
+impl RotatingFile {
+    pub fn open(path: impl Into<PathBuf>, rotation: Rotation, retain: usize) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            rotation,
+            retain,
+            file,
+            written,
+            opened_on: Utc::now().date_naive(),
+        })
+    }
+
+    fn should_rotate(&self, incoming: usize) -> bool {
+        match self.rotation {
+            Rotation::Daily => Utc::now().date_naive() != self.opened_on,
+            Rotation::MaxBytes(max) => self.written > 0 && self.written + incoming as u64 > max,
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.retain == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let oldest = rotated_path(&self.path, self.retain);
+            if oldest.exists() {
+                fs::remove_file(&oldest)?;
+            }
+            for n in (1..self.retain).rev() {
+                let from = rotated_path(&self.path, n);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.written = 0;
+        self.opened_on = Utc::now().date_naive();
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.should_rotate(buf.len()) {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// A configured log file behind a non-blocking writer. Keep it alive for
+/// as long as events should be written; dropping it flushes what is queued.
+pub struct LogSink {
+    writer: NonBlocking,
+    targets: Targets,
+    _guard: WorkerGuard,
+}
+
+impl LogSink {
+    pub fn open(config: &LogSinkConfig) -> io::Result<Self> {
+        let file = RotatingFile::open(&config.path, config.rotation, config.retain)?;
+        // Lossy so a slow disk never blocks request handling; drops are
+        // counted instead
+        let (writer, guard) = NonBlockingBuilder::default()
+            .lossy(true)
+            .buffered_lines_limit(BUFFERED_LINES)
+            .finish(file);
+        let targets = config
+            .targets
+            .iter()
+            .fold(Targets::new(), |targets, target| {
+                targets.with_target(target.clone(), LevelFilter::TRACE)
+            });
+        Ok(Self {
+            writer,
+            targets,
+            _guard: guard,
+        })
+    }
+
+    /// A tracing layer writing the configured targets to the file.
+    pub fn layer<S>(&self) -> impl Layer<S> + use<S>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer(self.writer.clone())
+            .with_filter(self.targets.clone())
+    }
+
+    /// Counter of lines dropped because the writer fell behind.
+    pub fn dropped_lines(&self) -> ErrorCounter {
+        self.writer.error_counter()
+    }
+}
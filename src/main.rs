@@ -1,29 +1,551 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Parser;
+use futures_util::future::BoxFuture;
+#[cfg(feature = "redis")]
+use robots_server::cache::RedisCache;
+#[cfg(feature = "tls")]
+use robots_server::client_identity::ClientIdentityLayer;
+#[cfg(all(unix, feature = "systemd"))]
+use robots_server::systemd;
+#[cfg(unix)]
+use robots_server::uds;
 use robots_server::{
-    cache::MokaCache,
-    fetcher::RobotsFetcher,
-    service::{RobotsServer, robots::robots_service_server::RobotsServiceServer},
+    access_log::AccessLogLayer,
+    auth::ApiKeyAuth,
+    cache::{Cache, MokaCache},
+    cached_robots::CachedRobots,
+    chaos::{Chaos, ChaosCache, ChaosFetcher},
+    cli::{self, CheckArgs, Cli, Command, DumpArgs, EXIT_ERROR, ServeArgs},
+    config::{BindFailure, ChaosConfig, Config, ConfigHandle, TlsConfig},
+    fetcher::{Fetcher, RobotsFetcher},
+    health::{self, Readiness},
+    legacy::LegacyRobotsServer,
+    listen,
+    load_shed::LoadShedLayer,
+    log_format::{self, LogFormat},
+    log_sink::LogSink,
+    panic_guard::{self, CatchPanicLayer},
+    peer_filter::PeerAllowlistLayer,
+    politeness::{HostRateLimiter, MokaBucketStore},
+    request_id::RequestIdLayer,
+    runtime, self_check,
+    server_builder::RobotsServerBuilder,
+    service::{
+        FILE_DESCRIPTOR_SET, RobotsServer,
+        robots::{
+            robots_service_server::RobotsServiceServer as LegacyRobotsServiceServer,
+            v1::robots_service_server::RobotsServiceServer,
+        },
+    },
+    shutdown, snapshot,
+    static_fetcher::StaticFetcher,
+    trace_context::TraceContextLayer,
+    warm,
 };
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+use tokio::sync::oneshot;
+use tonic::service::interceptor::InterceptedService;
 use tonic::transport::Server;
-use tracing::info;
-use tracing_subscriber::EnvFilter;
+use tracing::{info, warn};
+use tracing_appender::non_blocking::ErrorCounter;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+fn main() -> ExitCode {
+    panic_guard::install_panic_hook();
+    let args = Cli::parse();
+    let config_path = args.config.as_deref();
+    let result = match &args.command {
+        None => serve(&args, &ServeArgs::default()).map(|()| ExitCode::SUCCESS),
+        Some(Command::Serve(serve_args)) => serve(&args, serve_args).map(|()| ExitCode::SUCCESS),
+        Some(Command::Check(check)) => {
+            init_tracing(&args, args.log_format, None);
+            runtime::build(&Config::default())
+                .map_err(Into::into)
+                .and_then(|runtime| runtime.block_on(run_check(config_path, check)))
+        }
+        Some(Command::Dump(dump)) => {
+            init_tracing(&args, args.log_format, None);
+            runtime::build(&Config::default())
+                .map_err(Into::into)
+                .and_then(|runtime| runtime.block_on(run_dump(config_path, dump)))
+                .map(|()| ExitCode::SUCCESS)
+        }
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("error: {e}");
+        ExitCode::from(EXIT_ERROR)
+    })
+}
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
+/// Log to stderr in `format`, filtered as `args` say, and to `access_log`
+/// if given.
+fn init_tracing(args: &Cli, format: LogFormat, access_log: Option<&LogSink>) {
+    tracing_subscriber::registry()
+        .with(log_format::stderr_layer(format, args.log_filter.as_deref()))
+        .with(access_log.map(LogSink::layer))
         .init();
-    let addr = "[::1]:50051".parse()?;
-    info!(%addr, "Starting robots-server");
-    let cache = MokaCache::new();
-    let fetcher = RobotsFetcher::new();
-    let service = RobotsServer::new(cache, fetcher);
+}
 
-    Server::builder()
-        .add_service(RobotsServiceServer::new(service))
-        .serve(addr)
-        .await?;
+fn serve(cli: &Cli, args: &ServeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config_path = cli.config.as_deref();
+    // The config file is read before tracing starts because it says where
+    // the access log goes, and before the runtime because it sizes it.
+    // Flags only fill in what the file leaves out
+    let (config, config_handle) =
+        ConfigHandle::load(config_path.map(Path::to_path_buf), args.config_defaults())?;
+    if args.validate_config {
+        for warning in &config.warnings {
+            eprintln!("warning: {warning}");
+        }
+        cli::write_config(&config, &mut std::io::stdout().lock())?;
+        return Ok(());
+    }
+    runtime::build(&config)?.block_on(run_server(cli, args, config, config_handle))
+}
+
+async fn run_server(
+    cli: &Cli,
+    args: &ServeArgs,
+    config: Config,
+    config_handle: ConfigHandle,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config_path = cli.config.as_deref();
+    let access_log = config.access_log.as_ref().map(LogSink::open).transpose()?;
+    init_tracing(
+        cli,
+        config.log_format.unwrap_or(cli.log_format),
+        access_log.as_ref(),
+    );
+    log_format::set_filter(config.runtime.log_filter.as_deref());
+    for warning in &config.warnings {
+        warn!("{warning}");
+    }
+    if let Some(path) = config_path {
+        info!(path = %path.display(), "Loaded configuration");
+        #[cfg(unix)]
+        config_handle.reload_on_sighup()?;
+    }
+    if let Some(sink) = &access_log {
+        tokio::spawn(report_dropped_lines(sink.dropped_lines()));
+    }
+    #[cfg(not(unix))]
+    if config.uds.is_some() {
+        return Err("Unix domain sockets are not supported on this platform".into());
+    }
+    let tcp_addrs = match &config.uds {
+        Some(uds) if !uds.tcp => Vec::new(),
+        _ => config.listen_addrs(),
+    };
+    let health = match config.health_addr {
+        Some(addr) => Some(listen::bind_all(&[addr], BindFailure::Error)?.remove(0)),
+        None => None,
+    };
+    let listener = Listener {
+        server: listen::configure(server(config.tls.as_ref())?, &config.transport),
+        tcp: tcp_listeners(&tcp_addrs, config.bind_failure)?,
+        tcp_keepalive: config.transport.tcp_keepalive(),
+        #[cfg(unix)]
+        uds: config.uds.as_ref().map(uds::bind).transpose()?,
+        health,
+        grace: config.shutdown_grace(),
+        load_shed: LoadShedLayer::new(config.max_concurrent_requests),
+    };
+    let addrs = listener
+        .tcp
+        .iter()
+        .filter_map(|listener| listener.local_addr().ok())
+        .collect::<Vec<_>>();
+    info!(
+        ?addrs,
+        uds = ?config.uds.as_ref().map(|uds| &uds.path),
+        health_addr = ?config.health_addr,
+        tls = config.tls.is_some(),
+        client_auth = config.tls.as_ref().is_some_and(|tls| tls.client_ca.is_some()),
+        worker_threads = tokio::runtime::Handle::current().metrics().num_workers(),
+        max_blocking_threads = config
+            .max_blocking_threads
+            .unwrap_or(runtime::DEFAULT_MAX_BLOCKING_THREADS),
+        "Starting robots-server"
+    );
+    run_with_cache_backend(args, config, config_handle, listener).await?;
 
     info!("Shutting down");
+    if let Some(sink) = &access_log {
+        let dropped = sink.dropped_lines().dropped_lines();
+        if dropped > 0 {
+            warn!(dropped, "Access log lines were dropped");
+        }
+    }
+
+    Ok(())
+}
+
+/// Where and how to serve.
+struct Listener {
+    server: Server,
+    /// Empty when serving only on a Unix domain socket.
+    tcp: Vec<TcpListener>,
+    tcp_keepalive: Option<Duration>,
+    #[cfg(unix)]
+    uds: Option<UnixListener>,
+    /// For `/healthz` and `/readyz`.
+    health: Option<TcpListener>,
+    /// How long requests in flight get to finish after SIGTERM or SIGINT.
+    grace: Duration,
+    /// Shared by every listener, so the limit covers all of them together.
+    load_shed: LoadShedLayer,
+}
+
+/// How the cache is warmed at startup and kept across restarts.
+struct Warmup {
+    /// URLs whose robots.txt is fetched once serving, from `--warm-from`.
+    seeds: Vec<String>,
+    /// Where the cache is saved on shutdown, from `cache_snapshot`.
+    snapshot: Option<PathBuf>,
+}
+
+/// A server using the certificate and key in `tls`, if given.
+fn server(tls: Option<&TlsConfig>) -> Result<Server, Box<dyn std::error::Error>> {
+    match tls {
+        None => Ok(Server::builder()),
+        #[cfg(feature = "tls")]
+        Some(tls) => {
+            let tls = robots_server::tls::server_tls_config(tls)?;
+            Ok(Server::builder().tls_config(tls)?)
+        }
+        #[cfg(not(feature = "tls"))]
+        Some(_) => Err("TLS is configured but the server was built without the tls feature".into()),
+    }
+}
+
+/// The TCP sockets systemd passed, when socket-activated, or else `addrs`
+/// bound as `on_failure` says.
+fn tcp_listeners(
+    addrs: &[SocketAddr],
+    on_failure: BindFailure,
+) -> std::io::Result<Vec<TcpListener>> {
+    #[cfg(all(unix, feature = "systemd"))]
+    {
+        let fds = systemd::listen_fds();
+        if !fds.is_empty() {
+            info!(sockets = fds.len(), "Serving on sockets passed by systemd");
+            return systemd::tcp_listeners(fds);
+        }
+    }
+    listen::bind_all(addrs, on_failure)
+}
 
+/// Serve with robots.txt cached in Redis when `REDIS_CACHE_URL` is set, and
+/// in memory otherwise.
+async fn run_with_cache_backend(
+    args: &ServeArgs,
+    config: Config,
+    config_handle: ConfigHandle,
+    listener: Listener,
+) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "redis")]
+    if let Ok(url) = std::env::var("REDIS_CACHE_URL") {
+        info!("Sharing the robots.txt cache through Redis");
+        let cache = RedisCache::connect(&url, config_handle.clone()).await?;
+        return run_with_cache(cache, args, config, config_handle, listener).await;
+    }
+    let mut cache = MokaCache::builder().config(config_handle.clone());
+    // Config::validate rejects both limits together, so at most one is set
+    if let Some(bytes) = config.max_cache_bytes {
+        cache = cache.max_bytes(bytes);
+    } else if let Some(entries) = config.cache_max_entries {
+        cache = cache.max_capacity(entries);
+    }
+    run_with_cache(cache.build(), args, config, config_handle, listener).await
+}
+
+/// Check `cache` and egress if asked to, restore its snapshot, then serve it
+/// with the fetcher and faults `config` describes.
+async fn run_with_cache<C: Cache<String, CachedRobots>>(
+    cache: C,
+    args: &ServeArgs,
+    config: Config,
+    config_handle: ConfigHandle,
+    listener: Listener,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let fetcher = RobotsFetcher::with_config(config_handle.clone());
+    if args.self_check || args.strict_startup || args.startup_probe_url.is_some() {
+        let report = self_check::run(
+            &config_handle.get(),
+            &cache,
+            &fetcher,
+            config.self_check_probe_url.as_deref(),
+        )
+        .await;
+        report.log();
+        if args.strict_startup && !report.passed() {
+            return Err("startup self-check failed".into());
+        }
+    }
+    let seeds = match &args.warm_from {
+        Some(path) => warm::read_seed_list(path).await?,
+        None => Vec::new(),
+    };
+    if let Some(path) = &config.cache_snapshot
+        && let Err(e) = snapshot::restore(&cache, path).await
+    {
+        warn!(error = %e, "Failed to restore the cache snapshot");
+    }
+    let warmup = Warmup {
+        seeds,
+        snapshot: config.cache_snapshot.clone(),
+    };
+    let host_limiter = host_rate_limiter(config_handle.clone()).await?;
+    let chaos = config.chaos.filter(|_| {
+        let enabled = cfg!(feature = "chaos") || args.enable_chaos;
+        if !enabled {
+            warn!("Ignoring [chaos] settings, chaos is not enabled");
+        }
+        enabled
+    });
+    match config.static_robots {
+        Some(corpus) => {
+            info!(
+                dir = %corpus.dir.display(),
+                fetch_missing = corpus.fetch_missing,
+                "Serving robots.txt from a static corpus"
+            );
+            let static_fetcher = StaticFetcher::new(corpus.dir, config_handle.clone());
+            let static_fetcher = if corpus.fetch_missing {
+                static_fetcher.with_fallback(fetcher)
+            } else {
+                static_fetcher
+            };
+            run_with_chaos(
+                cache,
+                static_fetcher,
+                chaos,
+                config_handle,
+                host_limiter,
+                listener,
+                warmup,
+            )
+            .await
+        }
+        None => {
+            run_with_chaos(
+                cache,
+                fetcher,
+                chaos,
+                config_handle,
+                host_limiter,
+                listener,
+                warmup,
+            )
+            .await
+        }
+    }
+}
+
+/// Serve `cache` and `fetcher`, with `chaos` faults injected into them when
+/// given.
+async fn run_with_chaos<C: Cache<String, CachedRobots>, F: Fetcher>(
+    cache: C,
+    fetcher: F,
+    chaos: Option<ChaosConfig>,
+    config_handle: ConfigHandle,
+    host_limiter: HostRateLimiter,
+    listener: Listener,
+    warmup: Warmup,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match chaos {
+        Some(chaos) => {
+            let chaos = Chaos::new(chaos);
+            let service = RobotsServer::builder()
+                .cache(ChaosCache::new(cache, chaos.clone()))
+                .fetcher(ChaosFetcher::new(fetcher, chaos));
+            run_service(service, config_handle, host_limiter, listener, warmup).await
+        }
+        None => {
+            let service = RobotsServer::builder().cache(cache).fetcher(fetcher);
+            run_service(service, config_handle, host_limiter, listener, warmup).await
+        }
+    }
+}
+
+/// Finish building the service and serve it with `listener` until SIGTERM
+/// or SIGINT, then give requests in flight up to its grace period to finish
+/// and close the cache. The cache is warmed in the background once serving,
+/// and saved before it is closed, as `warmup` says.
+async fn run_service<T: Cache<String, CachedRobots>, F: Fetcher>(
+    service: RobotsServerBuilder<T, F>,
+    config_handle: ConfigHandle,
+    host_limiter: HostRateLimiter,
+    listener: Listener,
+    warmup: Warmup,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let service = Arc::new(
+        service
+            .config(config_handle.clone())
+            .host_rate_limiter(host_limiter)
+            .build()?,
+    );
+    let auth = ApiKeyAuth::new(config_handle.clone());
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .build_v1()?;
+
+    let Listener {
+        server,
+        tcp,
+        tcp_keepalive,
+        #[cfg(unix)]
+        uds,
+        health,
+        grace,
+        load_shed,
+    } = listener;
+    let router = |server: Server| {
+        let server = server.layer(RequestIdLayer);
+        #[cfg(feature = "tls")]
+        let server = server.layer(ClientIdentityLayer);
+        server
+            .layer(CatchPanicLayer::new())
+            .layer(AccessLogLayer)
+            .layer(PeerAllowlistLayer::new(config_handle.clone()))
+            .layer(load_shed.clone())
+            .layer(TraceContextLayer)
+            .add_service(InterceptedService::new(
+                RobotsServiceServer::from_arc(service.clone()),
+                auth.clone(),
+            ))
+            .add_service(InterceptedService::new(
+                LegacyRobotsServiceServer::new(LegacyRobotsServer::new(service.clone())),
+                auth.clone(),
+            ))
+            .add_service(reflection.clone())
+    };
+    let readiness = Readiness::new({
+        let service = service.clone();
+        move || {
+            let service = service.clone();
+            Box::pin(async move { service.cache_reachable().await })
+        }
+    });
+    let (stop_health, health_stopping) = oneshot::channel::<()>();
+    let health = health.map(|listener| {
+        tokio::spawn(health::serve(listener, readiness.clone(), async {
+            health_stopping.await.ok();
+        }))
+    });
+    readiness.set_serving(true);
+    #[cfg(all(unix, feature = "systemd"))]
+    systemd::notify_ready();
+    let Warmup { seeds, snapshot } = warmup;
+    if !seeds.is_empty() {
+        info!(urls = seeds.len(), "Warming the cache");
+        let service = service.clone();
+        tokio::spawn(async move { service.warm(&seeds).await });
+    }
+    let served = shutdown::serve_all_with_grace(
+        |signal| {
+            let mut servers: Vec<BoxFuture<'static, Result<(), tonic::transport::Error>>> =
+                Vec::new();
+            for listener in tcp {
+                let incoming = listen::incoming(listener, tcp_keepalive);
+                let router = router(server.clone());
+                servers.push(Box::pin(
+                    router.serve_with_incoming_shutdown(incoming, signal.clone()),
+                ));
+            }
+            #[cfg(unix)]
+            if let Some(listener) = uds {
+                let incoming = uds::incoming(listener);
+                let router = router(server);
+                servers.push(Box::pin(
+                    router.serve_with_incoming_shutdown(incoming, signal),
+                ));
+            }
+            servers
+        },
+        readiness.clone().draining_after(shutdown::termination()),
+        grace,
+    )
+    .await;
+    readiness.set_serving(false);
+    if let Some(health) = health {
+        let _ = stop_health.send(());
+        if let Ok(Err(e)) = health.await {
+            warn!(error = %e, "Health listener failed");
+        }
+    }
+    // Fetches outlive the requests that started them, so stop them before
+    // the cache is saved and closed
+    let cancelled = service.cancel_fetches();
+    if cancelled > 0 {
+        info!(cancelled, "Cancelled robots.txt fetches in flight");
+    }
+    if let Some(path) = snapshot
+        && let Err(e) = service.save_snapshot(&path).await
+    {
+        warn!(error = %e, "Failed to save the cache snapshot");
+    }
+    match service.close().await {
+        Ok(()) => info!("Cache closed"),
+        Err(e) => warn!(error = %e, "Failed to close the cache"),
+    }
+    served?;
     Ok(())
 }
+
+async fn run_check(
+    config_path: Option<&Path>,
+    args: &CheckArgs,
+) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let config = cli::runtime_config(config_path, &args.fetch)?;
+    let decision = cli::check(args, config, &mut std::io::stdout().lock()).await?;
+    Ok(decision.exit_code())
+}
+
+async fn run_dump(
+    config_path: Option<&Path>,
+    args: &DumpArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = cli::runtime_config(config_path, &args.fetch)?;
+    cli::dump(args, config, &mut std::io::stdout().lock()).await?;
+    Ok(())
+}
+
+/// Build the per-host rate limiter, backed by Redis when `REDIS_URL` is set.
+async fn host_rate_limiter(
+    config: ConfigHandle,
+) -> Result<HostRateLimiter, Box<dyn std::error::Error>> {
+    #[cfg(feature = "redis")]
+    if let Ok(url) = std::env::var("REDIS_URL") {
+        info!("Sharing host rate limits through Redis");
+        let store = robots_server::politeness::RedisBucketStore::connect(&url).await?;
+        return Ok(HostRateLimiter::with_config(store, config));
+    }
+
+    Ok(HostRateLimiter::with_config(MokaBucketStore::new(), config))
+}
+
+/// Warn whenever access log lines were dropped since the last check.
+async fn report_dropped_lines(counter: ErrorCounter) {
+    let mut reported = 0;
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+    loop {
+        interval.tick().await;
+        let dropped = counter.dropped_lines();
+        if dropped > reported {
+            warn!(
+                dropped = dropped - reported,
+                total = dropped,
+                "Access log writer fell behind, lines dropped"
+            );
+            reported = dropped;
+        }
+    }
+}
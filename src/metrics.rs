@@ -0,0 +1,200 @@
+use std::sync::LazyLock;
+
+use prometheus::core::Collector;
+use prometheus::{
+    Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder, exponential_buckets, linear_buckets,
+};
+
+static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+/// robots.txt body bytes read from origins, by status class and whether the
+/// body was cut at `max_robots_txt_bytes`. Bytes of bodies whose download
+/// failed part way are counted too.
+pub(crate) static FETCH_BODY_BYTES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "robots_fetch_body_bytes_total",
+                "robots.txt body bytes downloaded",
+            ),
+            &["status_class", "truncated"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Bytes downloaded for each robots.txt read to its end or to the limit.
+pub(crate) static FETCH_BODY_SIZE: LazyLock<Histogram> = LazyLock::new(|| {
+    register(
+        Histogram::with_opts(
+            HistogramOpts::new(
+                "robots_fetch_body_size_bytes",
+                "Size of downloaded robots.txt bodies",
+            )
+            .buckets(exponential_buckets(256.0, 4.0, 7).expect("valid buckets")),
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Redirects followed by each robots.txt request that got a response.
+pub(crate) static FETCH_REDIRECTS: LazyLock<Histogram> = LazyLock::new(|| {
+    register(
+        Histogram::with_opts(
+            HistogramOpts::new(
+                "robots_fetch_redirects",
+                "Redirects followed per robots.txt fetch",
+            )
+            .buckets(linear_buckets(0.0, 1.0, 6).expect("valid buckets")),
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// robots.txt responses by the HTTP version the origin answered with.
+pub(crate) static FETCH_HTTP_VERSIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "robots_fetch_http_versions_total",
+                "robots.txt responses by HTTP version",
+            ),
+            &["version"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+pub(crate) static FETCH_TRUNCATIONS: LazyLock<IntCounter> = LazyLock::new(|| {
+    register(
+        IntCounter::new(
+            "robots_fetch_truncations_total",
+            "robots.txt bodies cut at max_robots_txt_bytes",
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Fetches waiting for a turn in the fetch queue, by priority.
+pub(crate) static FETCH_QUEUE_DEPTH: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new("robots_fetch_queue_depth", "Fetches waiting for a turn"),
+            &["priority"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// How long fetches waited for a turn, by priority. Fetches that started at
+/// once are counted with no wait.
+pub(crate) static FETCH_QUEUE_WAIT: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "robots_fetch_queue_wait_seconds",
+                "Time fetches waited in the fetch queue",
+            )
+            .buckets(exponential_buckets(0.001, 4.0, 9).expect("valid buckets")),
+            &["priority"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Fetches turned away because the fetch queue was full, by priority.
+pub(crate) static FETCH_QUEUE_REJECTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "robots_fetch_queue_rejected_total",
+                "Fetches rejected by a full fetch queue",
+            ),
+            &["priority"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Hosts given to PrefetchHosts, by what became of them: `accepted`,
+/// `rejected` or `skipped` when submitted, then `done` or `failed`.
+pub(crate) static PREFETCH_HOSTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "robots_prefetch_hosts_total",
+                "Hosts given to PrefetchHosts",
+            ),
+            &["outcome"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Calls answered with `RESOURCE_EXHAUSTED` because
+/// `max_concurrent_requests` were already running.
+pub(crate) static REQUESTS_SHED: LazyLock<IntCounter> = LazyLock::new(|| {
+    register(
+        IntCounter::new(
+            "robots_requests_shed_total",
+            "Calls rejected at the concurrency limit",
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// RefreshRobots calls answered from earlier data because the host's
+/// robots.txt was fetched within `min_refresh_interval_secs`.
+pub(crate) static REFRESH_THROTTLED: LazyLock<IntCounter> = LazyLock::new(|| {
+    register(
+        IntCounter::new(
+            "robots_refresh_throttled_total",
+            "Refreshes answered without fetching",
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Faults injected by the chaos wrappers, by kind.
+pub(crate) static CHAOS_FAULTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new("robots_chaos_faults_total", "Faults injected for testing"),
+            &["fault"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+fn register<M: Collector + Clone + 'static>(metric: M) -> M {
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("metric registered twice");
+    metric
+}
+
+/// The registry the server's metrics are kept in. Metrics appear once they
+/// are first recorded.
+pub fn registry() -> &'static Registry {
+    &REGISTRY
+}
+
+/// The registry's metrics in the Prometheus text format.
+pub fn gather_text() -> String {
+    TextEncoder::new()
+        .encode_to_string(&REGISTRY.gather())
+        .expect("metrics encode as text")
+}
+
+/// `2xx` and so on, for labelling by HTTP status.
+pub(crate) fn status_class(status: u16) -> &'static str {
+    match status {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        500..=599 => "5xx",
+        _ => "other",
+    }
+}
@@ -0,0 +1,178 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use moka::future::Cache as MokaCacheImpl;
+use thiserror::Error;
+use tower::{Layer, Service};
+use tracing::{debug, instrument, warn};
+
+use crate::client::RobotsClient;
+use crate::redact;
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+const DEFAULT_DECISION_TTL: Duration = Duration::from_secs(60);
+const DEFAULT_DECISION_CAPACITY: u64 = 10_000;
+
+/// Returned in place of the inner service's response when robots.txt
+/// disallows the request.
+#[derive(Debug, Error)]
+#[error("robots.txt disallows {uri} for user-agent {user_agent}")]
+pub struct DisallowedError {
+    pub uri: String,
+    pub user_agent: String,
+}
+
+/// What to do with a request when the robots server cannot be asked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Let the request through.
+    #[default]
+    Open,
+    /// Fail the request with the client error.
+    Closed,
+}
+
+struct Checker {
+    client: RobotsClient,
+    user_agent: String,
+    policy: FailurePolicy,
+    decisions: MokaCacheImpl<String, bool>,
+}
+
+impl Checker {
+    #[instrument(skip(self, uri), fields(uri = %redact::url(&uri), user_agent = %self.user_agent))]
+    async fn check(&self, uri: String) -> Result<(), BoxError> {
+        let allowed = match self.decisions.get(&uri).await {
+            Some(allowed) => {
+                debug!(allowed, "Decision cache hit");
+                allowed
+            }
+            None => match self.client.is_allowed(&uri, &self.user_agent).await {
+                Ok(response) => {
+                    self.decisions.insert(uri.clone(), response.allowed).await;
+                    response.allowed
+                }
+                Err(e) if self.policy == FailurePolicy::Open => {
+                    warn!(error = %e, "robots check failed, allowing request");
+                    true
+                }
+                Err(e) => return Err(e.into()),
+            },
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(DisallowedError {
+                uri,
+                user_agent: self.user_agent.clone(),
+            }
+            .into())
+        }
+    }
+}
+
+/// Tower layer that asks the robots server whether each outbound request
+/// may be made before passing it to the inner service.
+#[derive(Clone)]
+pub struct RobotsCheckLayer {
+    client: RobotsClient,
+    user_agent: String,
+    policy: FailurePolicy,
+    decision_ttl: Duration,
+    decision_capacity: u64,
+}
+
+impl RobotsCheckLayer {
+    pub fn new(client: RobotsClient, user_agent: impl Into<String>) -> Self {
+        Self {
+            client,
+            user_agent: user_agent.into(),
+            policy: FailurePolicy::default(),
+            decision_ttl: DEFAULT_DECISION_TTL,
+            decision_capacity: DEFAULT_DECISION_CAPACITY,
+        }
+    }
+
+    /// Set how requests are handled when the robots server returns an error.
+    pub fn with_failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set how long decisions are cached locally. Zero disables the cache.
+    pub fn with_decision_ttl(mut self, ttl: Duration) -> Self {
+        self.decision_ttl = ttl;
+        self
+    }
+
+    /// Set the maximum number of cached decisions.
+    pub fn with_decision_capacity(mut self, capacity: u64) -> Self {
+        self.decision_capacity = capacity;
+        self
+    }
+}
+
+impl<S> Layer<S> for RobotsCheckLayer {
+    type Service = RobotsCheck<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let capacity = if self.decision_ttl.is_zero() {
+            0
+        } else {
+            self.decision_capacity
+        };
+        RobotsCheck {
+            inner,
+            checker: Arc::new(Checker {
+                client: self.client.clone(),
+                user_agent: self.user_agent.clone(),
+                policy: self.policy,
+                decisions: MokaCacheImpl::builder()
+                    .max_capacity(capacity)
+                    .time_to_live(self.decision_ttl)
+                    .build(),
+            }),
+        }
+    }
+}
+
+/// Service produced by [`RobotsCheckLayer`]. Errors are boxed; a disallowed
+/// request fails with a [`DisallowedError`] and, under
+/// [`FailurePolicy::Closed`], a server failure with a
+/// [`ClientError`](crate::client::ClientError).
+#[derive(Clone)]
+pub struct RobotsCheck<S> {
+    inner: S,
+    checker: Arc<Checker>,
+}
+
+impl<S, B> Service<http::Request<B>> for RobotsCheck<S>
+where
+    S: Service<http::Request<B>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        // Take the service that was polled ready and leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let checker = self.checker.clone();
+        Box::pin(async move {
+            checker.check(request.uri().to_string()).await?;
+            inner.call(request).await.map_err(Into::into)
+        })
+    }
+}
@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser;
+use crate::robots_data::{AccessResult, Group, RobotsData, Rule, RuleType};
+
+/// Where an override's robots.txt comes from.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverrideSource {
+    /// robots.txt written inline in the config file.
+    Content(String),
+    /// A file holding the robots.txt.
+    Path(PathBuf),
+}
+
+/// Operator-supplied robots.txt served for a host instead of the one it
+/// publishes, as configured in the `[robots_overrides]` table. The file is
+/// read and the rules parsed when the configuration is loaded, so a reload
+/// picks up changes to either.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "OverrideSource", into = "OverrideSource")]
+pub struct RobotsOverride {
+    pub source: OverrideSource,
+    data: Arc<RobotsData>,
+}
+
+impl RobotsOverride {
+    /// The override as served for `target_url`.
+    pub fn data_for(&self, target_url: &str, robots_txt_url: &str) -> RobotsData {
+        RobotsData {
+            target_url: target_url.to_string(),
+            robots_txt_url: robots_txt_url.to_string(),
+            ..(*self.data).clone()
+        }
+    }
+}
+
+impl TryFrom<OverrideSource> for RobotsOverride {
+    type Error = String;
+
+    fn try_from(source: OverrideSource) -> Result<Self, Self::Error> {
+        let body = match &source {
+            OverrideSource::Content(content) => content.clone(),
+            OverrideSource::Path(path) => std::fs::read_to_string(path).map_err(|e| {
+                format!("Failed to read robots.txt override {}: {e}", path.display())
+            })?,
+        };
+        let mut data = parser::parse(&body);
+        data.access_result = AccessResult::Override;
+        data.content_length_bytes = body.len() as u64;
+        Ok(Self {
+            source,
+            data: Arc::new(data),
+        })
+    }
+}
+
+impl From<RobotsOverride> for OverrideSource {
+    fn from(value: RobotsOverride) -> Self {
+        value.source
+    }
+}
+
+impl PartialEq for RobotsOverride {
+    // The rules follow from the body, so equal checksums mean equal rules
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.data.checksum == other.data.checksum
+    }
+}
+
+/// Supplemental path rules for a host, as configured in the `[policy_rules]`
+/// table. They are checked before the host's robots.txt, and whichever of
+/// them matches the path decides, with the usual longest-match precedence.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyRules {
+    /// Patterns that are always allowed.
+    pub allow: Vec<String>,
+    /// Patterns that are never allowed.
+    pub disallow: Vec<String>,
+}
+
+impl PolicyRules {
+    pub fn rules(&self) -> impl Iterator<Item = Rule> + '_ {
+        let allow = self
+            .allow
+            .iter()
+            .map(|p| Rule::new(RuleType::Allow, p.as_str()));
+        let disallow = self
+            .disallow
+            .iter()
+            .map(|p| Rule::new(RuleType::Disallow, p.as_str()));
+        allow.chain(disallow)
+    }
+}
+
+/// Cache key for the rules set on `host` through SetPolicyRules. Stored
+/// next to the robots.txt entries, so they are shared by every replica
+/// using the same backend and outlive a refresh of the host's robots.txt.
+pub fn policy_key(host: &str) -> String {
+    format!("policy:{}", host.to_lowercase())
+}
+
+/// `rules` as a robots.txt with a single group for every user-agent, which
+/// is how they are cached and evaluated.
+pub fn policy_data(rules: Vec<Rule>) -> RobotsData {
+    RobotsData {
+        groups: vec![Group::new(vec!["*".to_string()], rules, None)],
+        ..Default::default()
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum PageTokenError {
+    #[error("Invalid page_token")]
+    Malformed,
+    /// The cached robots.txt was replaced since the token was issued.
+    #[error("robots.txt changed since page_token was issued; start again from the first page")]
+    Stale,
+}
+
+/// Where the next page of GetRobotsTxt groups starts, and the checksum of
+/// the robots.txt the earlier pages came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageToken {
+    offset: usize,
+    checksum: String,
+}
+
+impl PageToken {
+    /// The groups of the current page of `groups`: those from the token's
+    /// offset, or from the start without a token, up to `page_size` of them,
+    /// or all that remain when it is zero. Also gives the token for the next
+    /// page when groups remain.
+    pub fn page<G>(
+        token: Option<&Self>,
+        page_size: usize,
+        groups: &mut Vec<G>,
+        checksum: &str,
+    ) -> Result<Option<Self>, PageTokenError> {
+        let offset = match token {
+            Some(token) if token.checksum != checksum => return Err(PageTokenError::Stale),
+            Some(token) => token.offset.min(groups.len()),
+            None => 0,
+        };
+        groups.drain(..offset);
+        if page_size == 0 || groups.len() <= page_size {
+            return Ok(None);
+        }
+        groups.truncate(page_size);
+        Ok(Some(Self {
+            offset: offset + page_size,
+            checksum: checksum.to_string(),
+        }))
+    }
+}
+
+impl fmt::Display for PageToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.offset, self.checksum)
+    }
+}
+
+impl FromStr for PageToken {
+    type Err = PageTokenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (offset, checksum) = s.split_once('.').ok_or(PageTokenError::Malformed)?;
+        let offset = offset.parse().map_err(|_| PageTokenError::Malformed)?;
+        Ok(Self {
+            offset,
+            checksum: checksum.to_string(),
+        })
+    }
+}
+
+/// Where the next page of ListCachedEntries starts: the last robots.txt URL
+/// of the previous page. Entries are listed in URL order, so entries added
+/// or evicted between pages do not shift the ones after them. Written as
+/// hex so callers treat it as opaque.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachePageToken {
+    after: String,
+}
+
+impl CachePageToken {
+    /// Whether `key` is yet to be listed, sorting after the token's.
+    pub fn is_pending(&self, key: &str) -> bool {
+        key > self.after.as_str()
+    }
+
+    /// Cut `entries`, those left after the token's key, down to the first
+    /// `page_size` by key, in order. Only the entries kept are sorted. Also
+    /// gives the token for the next page when entries remain.
+    pub fn page<E>(
+        page_size: usize,
+        entries: &mut Vec<E>,
+        key: impl Fn(&E) -> &str,
+    ) -> Option<Self> {
+        let more = page_size > 0 && entries.len() > page_size;
+        if more {
+            entries.select_nth_unstable_by(page_size, |a, b| key(a).cmp(key(b)));
+            entries.truncate(page_size);
+        }
+        entries.sort_unstable_by(|a, b| key(a).cmp(key(b)));
+        if !more {
+            return None;
+        }
+        entries.last().map(|e| Self {
+            after: key(e).to_string(),
+        })
+    }
+}
+
+impl fmt::Display for CachePageToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.after.bytes().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
+
+impl FromStr for CachePageToken {
+    type Err = PageTokenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() % 2 != 0 || !s.is_ascii() {
+            return Err(PageTokenError::Malformed);
+        }
+        let bytes = (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| PageTokenError::Malformed)?;
+        let after = String::from_utf8(bytes).map_err(|_| PageTokenError::Malformed)?;
+        Ok(Self { after })
+    }
+}
@@ -0,0 +1,121 @@
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+use futures_util::FutureExt;
+use tonic::Status;
+use tower::{Layer, Service};
+use tracing::error;
+
+/// Log panics through tracing with a backtrace. Hooks run on the panicking
+/// thread, so the event carries the fields of the handler's span.
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        error!(
+            panic = panic_message(info.payload()),
+            %location,
+            backtrace = %Backtrace::force_capture(),
+            "Panic"
+        );
+    }));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload")
+}
+
+/// Tower layer that turns a panic while handling a request into an
+/// `INTERNAL` response instead of dropping the connection.
+#[derive(Clone, Default)]
+pub struct CatchPanicLayer {
+    panics: Arc<AtomicU64>,
+}
+
+impl CatchPanicLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of panics caught so far by services built from this layer.
+    pub fn panics(&self) -> u64 {
+        self.panics.load(Ordering::Relaxed)
+    }
+}
+
+impl<S> Layer<S> for CatchPanicLayer {
+    type Service = CatchPanic<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CatchPanic {
+            inner,
+            panics: self.panics.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CatchPanic<S> {
+    inner: S,
+    panics: Arc<AtomicU64>,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for CatchPanic<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let path = request.uri().path().to_string();
+        let panics = self.panics.clone();
+        // The panic may come from building the future as well as polling it
+        let future = match panic::catch_unwind(AssertUnwindSafe(|| self.inner.call(request))) {
+            Ok(future) => future,
+            Err(payload) => {
+                return Box::pin(async move { Ok(internal(&panics, &path, payload)) });
+            }
+        };
+        Box::pin(async move {
+            match AssertUnwindSafe(future).catch_unwind().await {
+                Ok(result) => result,
+                Err(payload) => Ok(internal(&panics, &path, payload)),
+            }
+        })
+    }
+}
+
+fn internal<B: Default>(
+    panics: &AtomicU64,
+    path: &str,
+    payload: Box<dyn Any + Send>,
+) -> http::Response<B> {
+    panics.fetch_add(1, Ordering::Relaxed);
+    error!(
+        path,
+        panic = panic_message(payload.as_ref()),
+        "Handler panicked"
+    );
+    Status::internal("internal error").into_http()
+}
@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use tracing::debug;
+use url::Url;
+
+use crate::robots_data::{AccessResult, Group, Interner, ParseWarning, RobotsData, Rule, RuleType};
+
+/// Parse robots.txt content into groups in source order per RFC 9309.
+///
+/// Consecutive `user-agent` lines share one group; a `user-agent` line after
+/// any rule line starts a new group. `crawl-delay` is kept per group, and
+/// `sitemap` lines are collected regardless of where they appear, once each
+/// in the order first seen, as [`sitemap_key`] tells them apart. Lines that
+/// are skipped are reported as warnings. Patterns repeated across groups
+/// share one copy. Rules and sitemaps record the line they were read from.
+pub fn parse(body: &str) -> RobotsData {
+    let checksum = format!("{:x}", Sha256::digest(body.as_bytes()));
+    let body = body.strip_prefix('\u{feff}').unwrap_or(body);
+    let mut groups: Vec<PendingGroup> = Vec::new();
+    let mut patterns = Interner::default();
+    let mut sitemaps = Vec::new();
+    let mut sitemap_line_numbers = Vec::new();
+    let mut seen_sitemaps = HashSet::new();
+    let mut warnings = Vec::new();
+    // Whether the current group has seen a rule line, so the next
+    // user-agent line starts a new group
+    let mut in_rules = true;
+
+    for (index, line) in body.lines().enumerate() {
+        let line_number = index + 1;
+        let mut warn = |message: String| {
+            debug!(line = line_number, %message, "robots.txt parse warning");
+            warnings.push(ParseWarning {
+                line: line_number,
+                message,
+            });
+        };
+        let line = match line.find('#') {
+            Some(pos) => &line[..pos],
+            None => line,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            warn(format!("Missing ':' separator in '{}'", line.trim()));
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+
+        match key.as_str() {
+            "user-agent" => {
+                if in_rules {
+                    groups.push(PendingGroup::default());
+                    in_rules = false;
+                }
+                if value.is_empty() {
+                    warn("Empty user-agent".to_string());
+                } else if let Some(group) = groups.last_mut() {
+                    group.user_agents.push(value.to_string());
+                }
+            }
+            "allow" | "disallow" => {
+                in_rules = true;
+                let Some(group) = groups.last_mut() else {
+                    warn(format!("'{key}' rule before any user-agent"));
+                    continue;
+                };
+                // RFC 9309: an empty pattern matches nothing
+                if value.is_empty() {
+                    continue;
+                }
+                let rule_type = if key == "allow" {
+                    RuleType::Allow
+                } else {
+                    RuleType::Disallow
+                };
+                group.rules.push(
+                    Rule::new(rule_type, patterns.intern(value))
+                        .with_line_number(line_number as u32),
+                );
+            }
+            "crawl-delay" => {
+                in_rules = true;
+                let Some(group) = groups.last_mut() else {
+                    warn("'crawl-delay' before any user-agent".to_string());
+                    continue;
+                };
+                match value.parse::<f64>() {
+                    Ok(secs) if secs.is_finite() && secs >= 0.0 => {
+                        group.crawl_delay = Some(Duration::from_secs_f64(secs));
+                    }
+                    _ => warn(format!("Invalid crawl-delay '{value}'")),
+                }
+            }
+            "sitemap" => {
+                if value.is_empty() {
+                    warn("Empty sitemap".to_string());
+                } else if seen_sitemaps.insert(sitemap_key(value)) {
+                    sitemaps.push(value.to_string());
+                    sitemap_line_numbers.push(line_number as u32);
+                } else {
+                    debug!(line = line_number, "Skipping repeated sitemap");
+                }
+            }
+            _ => warn(format!("Unknown directive '{key}'")),
+        }
+    }
+
+    RobotsData {
+        access_result: AccessResult::Unspecified,
+        groups: groups.into_iter().map(PendingGroup::finish).collect(),
+        sitemaps,
+        sitemap_line_numbers,
+        warnings,
+        checksum,
+        ..Default::default()
+    }
+}
+
+/// The form sitemap URLs are compared in: scheme and host lowercased and
+/// default ports dropped, as [`Url`] normalizes them. Values that are not
+/// URLs are compared as written.
+pub fn sitemap_key(value: &str) -> String {
+    Url::parse(value).map_or_else(|_| value.to_string(), String::from)
+}
+
+/// A group still being read. Groups are only built once complete, since a
+/// built group's lists are fixed-size.
+#[derive(Default)]
+struct PendingGroup {
+    user_agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl PendingGroup {
+    fn finish(self) -> Group {
+        Group::new(self.user_agents, self.rules, self.crawl_delay)
+    }
+}
@@ -0,0 +1,106 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+use tonic::Status;
+use tonic::transport::server::TcpConnectInfo;
+#[cfg(unix)]
+use tonic::transport::server::UdsConnectInfo;
+use tower::{Layer, Service};
+use tracing::debug;
+
+use crate::config::ConfigHandle;
+
+/// Tower layer that admits only clients whose address is in the configured
+/// `client_allowlist`. Other clients get `PERMISSION_DENIED` before any
+/// handler runs.
+#[derive(Clone)]
+pub struct PeerAllowlistLayer {
+    config: ConfigHandle,
+    rejected: Arc<AtomicU64>,
+}
+
+impl PeerAllowlistLayer {
+    pub fn new(config: ConfigHandle) -> Self {
+        Self {
+            config,
+            rejected: Arc::default(),
+        }
+    }
+
+    /// Number of requests rejected so far by services built from this layer.
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
+
+impl<S> Layer<S> for PeerAllowlistLayer {
+    type Service = PeerAllowlist<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PeerAllowlist {
+            inner,
+            config: self.config.clone(),
+            rejected: self.rejected.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PeerAllowlist<S> {
+    inner: S,
+    config: ConfigHandle,
+    rejected: Arc<AtomicU64>,
+}
+
+impl<S> PeerAllowlist<S> {
+    fn permitted<B>(&self, request: &http::Request<B>) -> bool {
+        let config = self.config.get();
+        if config.client_allowlist.is_empty() {
+            return true;
+        }
+        let extensions = request.extensions();
+        if let Some(addr) = extensions
+            .get::<TcpConnectInfo>()
+            .and_then(TcpConnectInfo::remote_addr)
+        {
+            let permitted = config.client_permitted(addr.ip());
+            if !permitted {
+                debug!(%addr, "Client address not in allowlist");
+            }
+            return permitted;
+        }
+        #[cfg(unix)]
+        if extensions.get::<UdsConnectInfo>().is_some() {
+            return config.trust_unix_sockets;
+        }
+        debug!("Client address unknown, rejecting");
+        false
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for PeerAllowlist<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        if !self.permitted(&request) {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            let response = Status::permission_denied("Client address not permitted").into_http();
+            return Box::pin(async move { Ok(response) });
+        }
+        Box::pin(self.inner.call(request))
+    }
+}
@@ -0,0 +1,398 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use moka::future::Cache as MokaCacheImpl;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+
+use crate::cache::CacheResult;
+use crate::clock::{self, Clock};
+use crate::config::{ConfigHandle, RuntimeConfig};
+
+pub const DEFAULT_CRAWL_DELAY: Duration = Duration::from_secs(1);
+const MAX_TRACKED_SLOTS: u64 = 100_000;
+const SLOT_IDLE_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Outcome of asking for the next fetch slot on a host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FetchSlot {
+    /// The caller may fetch now; the slot has been reserved.
+    Granted,
+    /// The caller must wait this long before asking again.
+    Wait(Duration),
+}
+
+/// Tracks the last granted fetch per (host, user-agent) so grants are spaced
+/// by the crawl-delay. State is bounded and idle entries are evicted.
+pub struct PolitenessTracker {
+    last_granted: MokaCacheImpl<(String, String), Arc<Mutex<Option<Instant>>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl PolitenessTracker {
+    pub fn new() -> Self {
+        Self::with_capacity(MAX_TRACKED_SLOTS)
+    }
+
+    pub fn with_capacity(max_slots: u64) -> Self {
+        Self {
+            last_granted: MokaCacheImpl::builder()
+                .max_capacity(max_slots)
+                .time_to_idle(SLOT_IDLE_TIMEOUT)
+                .build(),
+            clock: clock::system(),
+        }
+    }
+
+    /// Space grants by `clock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// How long until the next slot opens for this host and user-agent,
+    /// without reserving it. `None` means a slot is available now.
+    pub async fn pending_wait(
+        &self,
+        host: &str,
+        user_agent: &str,
+        delay: Duration,
+    ) -> Option<Duration> {
+        let key = (host.to_string(), user_agent.to_lowercase());
+        let slot = self.last_granted.get(&key).await?;
+        let last = slot.lock().expect("politeness slot lock poisoned");
+        let next = (*last)? + delay;
+        next.checked_duration_since(self.clock.instant())
+            .filter(|wait| !wait.is_zero())
+    }
+
+    /// Grant a slot if `delay` has elapsed since the previous grant for this
+    /// host and user-agent. Concurrent callers for the same key are
+    /// serialized, so a slot is never handed out twice.
+    #[instrument(skip(self))]
+    pub async fn try_acquire(&self, host: &str, user_agent: &str, delay: Duration) -> FetchSlot {
+        let key = (host.to_string(), user_agent.to_lowercase());
+        let slot = self
+            .last_granted
+            .get_with(key, async { Arc::new(Mutex::new(None)) })
+            .await;
+        let mut last = slot.lock().expect("politeness slot lock poisoned");
+        let now = self.clock.instant();
+        match *last {
+            Some(previous) if now < previous + delay => {
+                let wait = previous + delay - now;
+                debug!(wait_ms = wait.as_millis() as u64, "Slot not yet available");
+                FetchSlot::Wait(wait)
+            }
+            _ => {
+                *last = Some(now);
+                debug!("Slot granted");
+                FetchSlot::Granted
+            }
+        }
+    }
+}
+
+impl Default for PolitenessTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// When each robots.txt URL was last fetched from its origin, so forced
+/// refreshes can be spaced out. State is bounded and entries idle for an
+/// hour are evicted. Clones share the history.
+#[derive(Clone)]
+pub struct FetchHistory {
+    last_fetched: MokaCacheImpl<String, Instant>,
+    clock: Arc<dyn Clock>,
+}
+
+impl FetchHistory {
+    pub fn new() -> Self {
+        Self::with_capacity(MAX_TRACKED_SLOTS)
+    }
+
+    pub fn with_capacity(max_hosts: u64) -> Self {
+        Self {
+            last_fetched: MokaCacheImpl::builder()
+                .max_capacity(max_hosts)
+                .time_to_idle(SLOT_IDLE_TIMEOUT)
+                .build(),
+            clock: clock::system(),
+        }
+    }
+
+    /// Measure intervals by `clock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Note that `robots_url` is being fetched now.
+    pub async fn record(&self, robots_url: &str) {
+        self.last_fetched
+            .insert(robots_url.to_string(), self.clock.instant())
+            .await;
+    }
+
+    /// How long until `robots_url` has gone `interval` without a fetch.
+    /// `None` means it may be fetched now.
+    pub async fn pending_wait(&self, robots_url: &str, interval: Duration) -> Option<Duration> {
+        let last = self.last_fetched.get(robots_url).await?;
+        (last + interval)
+            .checked_duration_since(self.clock.instant())
+            .filter(|wait| !wait.is_zero())
+    }
+}
+
+impl Default for FetchHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Aggregate request rate allowed per host across all clients.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RateLimit {
+    pub per_second: f64,
+    pub burst: f64,
+}
+
+impl RateLimit {
+    /// A limit that never makes callers wait.
+    pub const UNLIMITED: Self = Self {
+        per_second: 0.0,
+        burst: 0.0,
+    };
+
+    pub fn is_unlimited(&self) -> bool {
+        self.per_second <= 0.0
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            per_second: 1.0,
+            burst: 1.0,
+        }
+    }
+}
+
+/// Storage for per-host token buckets.
+#[async_trait]
+pub trait HostBucketStore: Send + Sync + 'static {
+    /// Take one token from the bucket for `host`, or report how long until
+    /// one is available.
+    async fn take(&self, host: &str, limit: RateLimit) -> CacheResult<FetchSlot>;
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// In-process token buckets, bounded and evicted when idle.
+pub struct MokaBucketStore {
+    buckets: MokaCacheImpl<String, Arc<Mutex<Bucket>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl MokaBucketStore {
+    pub fn new() -> Self {
+        Self::with_capacity(MAX_TRACKED_SLOTS)
+    }
+
+    pub fn with_capacity(max_hosts: u64) -> Self {
+        Self {
+            buckets: MokaCacheImpl::builder()
+                .max_capacity(max_hosts)
+                .time_to_idle(SLOT_IDLE_TIMEOUT)
+                .build(),
+            clock: clock::system(),
+        }
+    }
+
+    /// Refill buckets by `clock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+impl Default for MokaBucketStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl HostBucketStore for MokaBucketStore {
+    async fn take(&self, host: &str, limit: RateLimit) -> CacheResult<FetchSlot> {
+        let bucket = self
+            .buckets
+            .get_with(host.to_string(), async {
+                Arc::new(Mutex::new(Bucket {
+                    tokens: limit.burst.max(1.0),
+                    updated: self.clock.instant(),
+                }))
+            })
+            .await;
+        let mut bucket = bucket.lock().expect("token bucket lock poisoned");
+        let now = self.clock.instant();
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * limit.per_second;
+        bucket.tokens = (bucket.tokens + refill).min(limit.burst.max(1.0));
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(FetchSlot::Granted)
+        } else {
+            let wait = (1.0 - bucket.tokens) / limit.per_second;
+            Ok(FetchSlot::Wait(Duration::from_secs_f64(wait)))
+        }
+    }
+}
+
+/// Enforces an aggregate request rate per host, shared by every client of
+/// the server.
+pub struct HostRateLimiter {
+    store: Box<dyn HostBucketStore>,
+    config: ConfigHandle,
+}
+
+impl HostRateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self::with_store(MokaBucketStore::new(), limit)
+    }
+
+    pub fn with_store(store: impl HostBucketStore, limit: RateLimit) -> Self {
+        let config = ConfigHandle::new(RuntimeConfig {
+            host_rate: limit,
+            ..Default::default()
+        });
+        Self::with_config(store, config)
+    }
+
+    /// A limiter that follows `host_rate` in the live configuration.
+    pub fn with_config(store: impl HostBucketStore, config: ConfigHandle) -> Self {
+        Self {
+            store: Box::new(store),
+            config,
+        }
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.config.get().host_rate
+    }
+
+    /// Take a token for `host`. A failing bucket store is logged and treated
+    /// as granted so politeness state never blocks crawling outright.
+    #[instrument(skip(self))]
+    pub async fn acquire(&self, host: &str) -> FetchSlot {
+        let limit = self.limit();
+        if limit.is_unlimited() {
+            return FetchSlot::Granted;
+        }
+        match self.store.take(host, limit).await {
+            Ok(slot) => {
+                debug!(?slot, "Host token bucket consulted");
+                slot
+            }
+            Err(e) => {
+                warn!(error = %e, "Token bucket store failed, allowing request");
+                FetchSlot::Granted
+            }
+        }
+    }
+}
+
+impl Default for HostRateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimit::default())
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use redis_store::RedisBucketStore;
+
+#[cfg(feature = "redis")]
+mod redis_store {
+    use std::sync::LazyLock;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use redis::{Client, Script, aio::ConnectionManager};
+    use tracing::warn;
+
+    use super::{FetchSlot, HostBucketStore, RateLimit, SLOT_IDLE_TIMEOUT};
+    use crate::cache::{CacheError, CacheResult};
+
+    const KEY_PREFIX: &str = "robots-server:bucket:";
+
+    // Refill and take atomically using the Redis clock so replicas agree.
+    // Returns 0 when a token was taken, otherwise the wait in milliseconds.
+    static TAKE_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+        Script::new(
+            r"
+local rate = tonumber(ARGV[1])
+local burst = tonumber(ARGV[2])
+local ttl = tonumber(ARGV[3])
+local t = redis.call('TIME')
+local now = tonumber(t[1]) * 1000 + tonumber(t[2]) / 1000
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(state[1]) or burst
+local updated = tonumber(state[2]) or now
+tokens = math.min(burst, tokens + math.max(0, now - updated) / 1000 * rate)
+local wait = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+else
+  wait = math.ceil((1 - tokens) / rate * 1000)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated', tostring(now))
+redis.call('PEXPIRE', KEYS[1], ttl)
+return wait
+",
+        )
+    });
+
+    /// Token buckets kept in Redis so several server replicas share one
+    /// rate per host.
+    pub struct RedisBucketStore {
+        connection: ConnectionManager,
+    }
+
+    impl RedisBucketStore {
+        pub async fn connect(url: &str) -> redis::RedisResult<Self> {
+            let client = Client::open(url)?;
+            let connection = client.get_connection_manager().await?;
+            Ok(Self { connection })
+        }
+    }
+
+    #[async_trait]
+    impl HostBucketStore for RedisBucketStore {
+        async fn take(&self, host: &str, limit: RateLimit) -> CacheResult<FetchSlot> {
+            let mut connection = self.connection.clone();
+            let wait_ms: u64 = TAKE_SCRIPT
+                .key(format!("{KEY_PREFIX}{host}"))
+                .arg(limit.per_second)
+                .arg(limit.burst.max(1.0))
+                .arg(SLOT_IDLE_TIMEOUT.as_millis() as u64)
+                .invoke_async(&mut connection)
+                .await
+                .map_err(|e| {
+                    warn!(error = %e, "Redis token bucket script failed");
+                    CacheError::Unavailable
+                })?;
+            Ok(if wait_ms == 0 {
+                FetchSlot::Granted
+            } else {
+                FetchSlot::Wait(Duration::from_millis(wait_ms))
+            })
+        }
+    }
+}
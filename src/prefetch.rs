@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures_util::StreamExt;
+use futures_util::stream;
+use tracing::{debug, info};
+
+use crate::metrics;
+
+/// Finished jobs kept for GetPrefetchStatus before the oldest are forgotten.
+const MAX_FINISHED_JOBS: usize = 1000;
+
+/// Progress of one PrefetchHosts job.
+#[derive(Debug)]
+pub struct PrefetchJob {
+    id: u64,
+    total: u64,
+    done: AtomicU64,
+    failed: AtomicU64,
+    /// Hosts still queued across every job, shared with [`PrefetchJobs`].
+    queued: Arc<AtomicUsize>,
+}
+
+impl PrefetchJob {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Hosts the job was given to fetch.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Hosts whose robots.txt was fetched and cached, whatever the origin
+    /// answered.
+    pub fn done(&self) -> u64 {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// Hosts whose fetch could not complete.
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.total - self.done() - self.failed()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Run `fetches`, at most `concurrency` at a time, counting each as done
+    /// when it yields `true` and failed otherwise.
+    pub async fn run<I>(self: Arc<Self>, fetches: I, concurrency: usize)
+    where
+        I: IntoIterator,
+        I::Item: Future<Output = bool>,
+    {
+        stream::iter(fetches)
+            .for_each_concurrent(concurrency, |fetch| {
+                let job = self.clone();
+                async move { job.record(fetch.await) }
+            })
+            .await;
+        info!(
+            job_id = self.id,
+            done = self.done(),
+            failed = self.failed(),
+            "Prefetch job finished"
+        );
+    }
+
+    fn record(&self, fetched: bool) {
+        let (count, outcome) = if fetched {
+            (&self.done, "done")
+        } else {
+            (&self.failed, "failed")
+        };
+        count.fetch_add(1, Ordering::Relaxed);
+        metrics::PREFETCH_HOSTS.with_label_values(&[outcome]).inc();
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The PrefetchHosts jobs started so far. Hosts waiting to be fetched are
+/// bounded across all jobs, and the oldest finished jobs are forgotten.
+/// Clones share the jobs.
+#[derive(Clone, Debug, Default)]
+pub struct PrefetchJobs {
+    jobs: Arc<Mutex<Jobs>>,
+    queued: Arc<AtomicUsize>,
+}
+
+#[derive(Debug, Default)]
+struct Jobs {
+    next_id: u64,
+    by_id: BTreeMap<u64, Arc<PrefetchJob>>,
+}
+
+impl PrefetchJobs {
+    /// Start a job for up to `hosts` hosts, as many as fit while at most
+    /// `capacity` are queued. The job's total is how many were taken.
+    pub fn start(&self, hosts: usize, capacity: usize) -> Arc<PrefetchJob> {
+        let taken = match self
+            .queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |queued| {
+                Some(queued + hosts.min(capacity.saturating_sub(queued)))
+            }) {
+            Ok(queued) | Err(queued) => hosts.min(capacity.saturating_sub(queued)),
+        };
+        let mut jobs = self.jobs();
+        jobs.next_id += 1;
+        let job = Arc::new(PrefetchJob {
+            id: jobs.next_id,
+            total: taken as u64,
+            done: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            queued: self.queued.clone(),
+        });
+        jobs.by_id.insert(job.id, job.clone());
+        let finished = jobs.by_id.values().filter(|job| job.is_finished()).count();
+        let forget = finished.saturating_sub(MAX_FINISHED_JOBS);
+        if forget > 0 {
+            debug!(forget, "Forgetting finished prefetch jobs");
+            let ids = jobs
+                .by_id
+                .values()
+                .filter(|job| job.is_finished())
+                .take(forget)
+                .map(|job| job.id)
+                .collect::<Vec<_>>();
+            for id in ids {
+                jobs.by_id.remove(&id);
+            }
+        }
+        job
+    }
+
+    pub fn get(&self, id: u64) -> Option<Arc<PrefetchJob>> {
+        self.jobs().by_id.get(&id).cloned()
+    }
+
+    /// Hosts waiting to be fetched, across every job.
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    fn jobs(&self) -> MutexGuard<'_, Jobs> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
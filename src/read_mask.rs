@@ -0,0 +1,164 @@
+use prost_types::FieldMask;
+use thiserror::Error;
+
+use crate::dns::AddressFamily;
+use crate::robots_data::RobotsData;
+use crate::service::robots::v1::{
+    AccessResult as ProtoBufAccessResult, AddressFamily as ProtoBufAddressFamily, GetRobotsResponse,
+};
+use crate::service::to_timestamp;
+
+/// GetRobotsResponse fields a `read_mask` may name, in tag order.
+pub const FIELDS: [&str; 24] = [
+    "target_url",
+    "robots_txt_url",
+    "access_result",
+    "http_status_code",
+    "groups",
+    "sitemaps",
+    "content_length_bytes",
+    "truncated",
+    "effective_user_agent_match",
+    "fetched_at",
+    "expires_at",
+    "not_modified",
+    "checksum",
+    "policy_rules",
+    "data_source",
+    "requested_host",
+    "cache_key",
+    "upstream_headers",
+    "resolved_address",
+    "address_family",
+    "sitemaps_truncated",
+    "next_page_token",
+    "sitemap_line_numbers",
+    "failure_reason",
+];
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ReadMaskError {
+    #[error("Invalid read_mask path: {}", .0.join(", "))]
+    UnknownPaths(Vec<String>),
+}
+
+/// The GetRobotsResponse fields a caller asked for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadMask(u32);
+
+impl ReadMask {
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Only top-level fields can be named. No mask, or one without paths,
+    /// selects every field.
+    pub fn parse(mask: Option<&FieldMask>) -> Result<Self, ReadMaskError> {
+        let Some(mask) = mask.filter(|mask| !mask.paths.is_empty()) else {
+            return Ok(Self::ALL);
+        };
+        let mut bits = 0;
+        let mut unknown = Vec::new();
+        for path in &mask.paths {
+            match FIELDS.iter().position(|field| field == path) {
+                Some(index) => bits |= 1 << index,
+                None => unknown.push(path.clone()),
+            }
+        }
+        if unknown.is_empty() {
+            Ok(Self(bits))
+        } else {
+            Err(ReadMaskError::UnknownPaths(unknown))
+        }
+    }
+
+    pub fn includes(&self, field: &str) -> bool {
+        FIELDS
+            .iter()
+            .position(|f| *f == field)
+            .is_some_and(|index| self.0 & (1 << index) != 0)
+    }
+
+    /// Build the response with only the selected fields. Groups are the
+    /// expensive part of a large robots.txt, and are not converted at all
+    /// unless selected. With a `user_agent`, only the groups that apply to
+    /// it are included. `expires_at`, `policy_rules`, `data_source`,
+    /// `requested_host`, `cache_key`, `sitemaps_truncated` and
+    /// `next_page_token` are not part of the robots.txt and are left for the
+    /// caller.
+    pub fn response(&self, data: &RobotsData, user_agent: Option<&str>) -> GetRobotsResponse {
+        if *self == Self::ALL && user_agent.is_none() {
+            return data.into();
+        }
+        let mut response = GetRobotsResponse::default();
+        if self.includes("target_url") {
+            response.target_url = data.target_url.clone();
+        }
+        if self.includes("robots_txt_url") {
+            response.robots_txt_url = data.robots_txt_url.clone();
+        }
+        if self.includes("access_result") {
+            response.access_result = ProtoBufAccessResult::from(data.access_result).into();
+        }
+        if self.includes("http_status_code") {
+            response.http_status_code = data.http_status_code;
+        }
+        match user_agent {
+            Some(user_agent) => {
+                let (groups, token) = data.groups_for(user_agent);
+                if self.includes("groups") {
+                    response.groups = groups.into_iter().map(Into::into).collect();
+                }
+                if self.includes("effective_user_agent_match") {
+                    response.effective_user_agent_match = token.unwrap_or_default().to_string();
+                }
+            }
+            None if self.includes("groups") => {
+                response.groups = data.groups.iter().map(Into::into).collect();
+            }
+            None => {}
+        }
+        if self.includes("sitemaps") {
+            response.sitemaps = data.sitemaps.clone();
+        }
+        if self.includes("sitemap_line_numbers") {
+            response.sitemap_line_numbers = (0..data.sitemaps.len())
+                .map(|i| data.sitemap_line_numbers.get(i).copied().unwrap_or(0))
+                .collect();
+        }
+        if self.includes("content_length_bytes") {
+            response.content_length_bytes = data.content_length_bytes;
+        }
+        if self.includes("truncated") {
+            response.truncated = data.truncated;
+        }
+        if self.includes("fetched_at") {
+            response.fetched_at = data.fetched_at.map(to_timestamp);
+        }
+        if self.includes("checksum") {
+            response.checksum = data.checksum.clone();
+        }
+        if self.includes("upstream_headers") {
+            response.upstream_headers = data
+                .upstream_headers
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+        }
+        if self.includes("failure_reason") {
+            response.failure_reason = data.failure_reason.clone().unwrap_or_default();
+        }
+        if self.includes("resolved_address") {
+            response.resolved_address = data
+                .resolved_address
+                .map(|address| address.to_string())
+                .unwrap_or_default();
+        }
+        if self.includes("address_family") {
+            response.address_family = data
+                .resolved_address
+                .map(|address| ProtoBufAddressFamily::from(AddressFamily::of(&address)))
+                .unwrap_or_default()
+                .into();
+        }
+        response
+    }
+}
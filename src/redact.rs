@@ -0,0 +1,102 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+/// Replacement for query values hidden by [`UrlRedaction::Allowlist`].
+pub const REDACTED: &str = "REDACTED";
+
+/// How URLs are rewritten before they are logged, as configured in the
+/// `[url_redaction]` table. Credentials and fragments are dropped by every
+/// mode except `Keep`. Only logs are affected: cache keys and rule matching
+/// always use the URL as sent.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum UrlRedaction {
+    /// Log URLs unchanged.
+    Keep,
+    /// Drop the query string.
+    #[default]
+    StripQuery,
+    /// Replace the query string with a short SHA-256 of it, so requests for
+    /// the same URL can still be correlated.
+    HashQuery,
+    /// Keep the values of the listed query parameters and replace the rest.
+    Allowlist { params: Vec<String> },
+}
+
+impl UrlRedaction {
+    /// `url` as it should appear in logs.
+    pub fn apply<'a>(&self, url: &'a str) -> Cow<'a, str> {
+        if *self == Self::Keep {
+            return Cow::Borrowed(url);
+        }
+        let Ok(mut parsed) = Url::parse(url) else {
+            // Nothing reliable to keep past the path of a malformed URL
+            let end = url.find(['?', '#']).unwrap_or(url.len());
+            return Cow::Borrowed(&url[..end]);
+        };
+        // Both only fail for URLs that cannot have credentials
+        let _ = parsed.set_username("");
+        let _ = parsed.set_password(None);
+        parsed.set_fragment(None);
+        let query = parsed.query().map(str::to_string);
+        match (self, query) {
+            (_, None) => {}
+            (Self::HashQuery, Some(query)) => {
+                let digest = format!("{:x}", Sha256::digest(query.as_bytes()));
+                parsed.set_query(Some(&format!("sha256:{}", &digest[..16])));
+            }
+            (Self::Allowlist { params }, Some(_)) => {
+                let pairs: Vec<(String, String)> = parsed
+                    .query_pairs()
+                    .map(|(name, value)| {
+                        let value = if params.iter().any(|p| *p == name) {
+                            value.into_owned()
+                        } else {
+                            REDACTED.to_string()
+                        };
+                        (name.into_owned(), value)
+                    })
+                    .collect();
+                parsed.query_pairs_mut().clear().extend_pairs(pairs);
+            }
+            _ => parsed.set_query(None),
+        }
+        Cow::Owned(parsed.into())
+    }
+}
+
+static POLICY: RwLock<UrlRedaction> = RwLock::new(UrlRedaction::StripQuery);
+
+/// Apply `policy` to every URL logged by the process.
+pub fn configure(policy: &UrlRedaction) {
+    *POLICY.write().unwrap_or_else(|e| e.into_inner()) = policy.clone();
+}
+
+/// `url` for a log line or span field, redacted by the configured policy
+/// when it is formatted.
+pub fn url(url: &str) -> RedactedUrl<'_> {
+    RedactedUrl(url)
+}
+
+/// A URL that is redacted when displayed. See [`url`].
+#[derive(Clone, Copy)]
+pub struct RedactedUrl<'a>(&'a str);
+
+impl fmt::Display for RedactedUrl<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let policy = POLICY.read().unwrap_or_else(|e| e.into_inner());
+        f.write_str(&policy.apply(self.0))
+    }
+}
+
+impl fmt::Debug for RedactedUrl<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let policy = POLICY.read().unwrap_or_else(|e| e.into_inner());
+        fmt::Debug::fmt(&policy.apply(self.0), f)
+    }
+}
@@ -0,0 +1,81 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use http::HeaderValue;
+use tower::{Layer, Service};
+use tracing::{Instrument, info_span};
+use uuid::Uuid;
+
+/// Metadata key carrying the ID of a call, on both the request and the
+/// response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied ID kept; longer ones are replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Tower layer that runs each call in a `request` span whose `request_id`
+/// field is the call's `x-request-id` metadata, or a new UUID when it has
+/// none, so every event the call logs, down to its fetches and cache
+/// lookups, carries the ID. The ID is echoed in the response metadata.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestIdLayer;
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestId<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestId { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestId<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for RequestId<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<ReqBody>) -> Self::Future {
+        let id = match request.headers().get(REQUEST_ID_HEADER) {
+            Some(id) if valid(id) => id.clone(),
+            _ => {
+                let id = HeaderValue::from_str(&Uuid::new_v4().to_string())
+                    .expect("a UUID is a valid header value");
+                request.headers_mut().insert(REQUEST_ID_HEADER, id.clone());
+                id
+            }
+        };
+        let span = info_span!(
+            "request",
+            request_id = id.to_str().expect("checked to be visible ASCII")
+        );
+        let future = span.in_scope(|| self.inner.call(request));
+        Box::pin(
+            async move {
+                let mut response = future.await?;
+                response.headers_mut().insert(REQUEST_ID_HEADER, id);
+                Ok(response)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+/// Whether a client-supplied ID is short, non-empty visible ASCII, so it is
+/// safe to log and echo as it is.
+fn valid(id: &HeaderValue) -> bool {
+    let id = id.as_bytes();
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.iter().all(u8::is_ascii_graphic)
+}
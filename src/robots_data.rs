@@ -1,10 +1,58 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::net::SocketAddr;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, SecondsFormat, Utc};
 use robotstxt_rs::RobotsTxt;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Outcome of fetching robots.txt, mirroring the wire `AccessResult`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AccessResult {
+    #[default]
+    #[serde(rename = "ACCESS_RESULT_UNSPECIFIED")]
+    Unspecified,
+    #[serde(rename = "ACCESS_RESULT_SUCCESS")]
+    Success,
+    #[serde(rename = "ACCESS_RESULT_REDIRECT_SUCCESS")]
+    RedirectSuccess,
+    #[serde(rename = "ACCESS_RESULT_TOO_MANY_REDIRECTS")]
+    TooManyRedirects,
+    #[serde(rename = "ACCESS_RESULT_UNAVAILABLE")]
+    Unavailable,
+    #[serde(rename = "ACCESS_RESULT_UNREACHABLE")]
+    Unreachable,
+    #[serde(rename = "ACCESS_RESULT_CACHED")]
+    Cached,
+    /// Served from a configured override rather than fetched.
+    #[serde(rename = "ACCESS_RESULT_OVERRIDE")]
+    Override,
+    /// Fetched successfully, but no line of the body could be understood.
+    #[serde(rename = "ACCESS_RESULT_UNUSABLE")]
+    Unusable,
+}
 
-use crate::service::robots::{
-    AccessResult, GetRobotsResponse, Group as ProtoBufGroup, Rule as ProtoBufRule, rule::RuleType,
-};
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RuleType {
+    #[serde(rename = "RULE_TYPE_ALLOW")]
+    Allow,
+    #[serde(rename = "RULE_TYPE_DISALLOW")]
+    Disallow,
+}
 
-#[derive(Clone, Debug, Default)]
+impl fmt::Display for RuleType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RuleType::Allow => "Allow",
+            RuleType::Disallow => "Disallow",
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct RobotsData {
     pub target_url: String,
     pub robots_txt_url: String,
@@ -12,129 +60,366 @@ pub struct RobotsData {
     pub http_status_code: u32,
     pub groups: Vec<Group>,
     pub sitemaps: Vec<String>,
+    /// The 1-based line each of `sitemaps` was read from. Empty when not
+    /// known, as for data that was not parsed from a robots.txt.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sitemap_line_numbers: Vec<u32>,
     pub content_length_bytes: u64,
     pub truncated: bool,
+    /// Problems found while parsing, in source order.
+    pub warnings: Vec<ParseWarning>,
+    /// Hex-encoded SHA-256 of the robots.txt body that was parsed.
+    pub checksum: String,
+    /// When robots.txt was fetched, or the failed fetch was attempted. `None`
+    /// for data that did not come from an origin.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_fetched_at"
+    )]
+    pub fetched_at: Option<SystemTime>,
+    /// The HTTP version robots.txt was served over, for diagnostics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<String>,
+    /// The origin's response headers named in `upstream_headers`, by
+    /// lowercased name, for diagnostics.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub upstream_headers: BTreeMap<String, String>,
+    /// The origin address robots.txt was fetched from, when the origin
+    /// answered, for diagnostics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_address: Option<SocketAddr>,
+    /// Why robots.txt could not be fetched, when that has a name of its
+    /// own: `REDIRECT_LOOP` or `TOO_MANY_REDIRECTS`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+    /// How long the origin asked for the response to be cached: the
+    /// `Cache-Control` max-age of a 2xx response less its `Age`, or a 429's
+    /// `Retry-After`.
+    #[serde(skip)]
+    pub max_age: Option<Duration>,
 }
 
 impl RobotsData {
     pub fn is_allowed(&self, user_agent: &str, path: &str) -> bool {
+        self.explain(user_agent, path).allowed
+    }
+
+    /// Evaluate `path` for `user_agent` and report the rule that decided it.
+    pub fn explain(&self, user_agent: &str, path: &str) -> Explanation {
+        let groups_to_check = self.matching_groups(user_agent);
+        let user_agents = groups_to_check
+            .iter()
+            .flat_map(|g| g.user_agents.iter().map(|ua| ua.to_string()))
+            .collect();
+        // Combine all rules from matching groups per RFC 9309
+        let mut decisive: Option<&Rule> = None;
+        for group in &groups_to_check {
+            for rule in &group.rules {
+                if !rule.matches(path) {
+                    continue;
+                }
+                decisive = match decisive {
+                    None => Some(rule),
+                    // Longest match wins (most octets per RFC 9309)
+                    Some(best) if rule.path_pattern.len() > best.path_pattern.len() => Some(rule),
+                    // RFC 9309: If allow and disallow are equivalent, allow wins
+                    Some(best)
+                        if rule.path_pattern.len() == best.path_pattern.len()
+                            && best.rule_type == RuleType::Disallow
+                            && rule.rule_type == RuleType::Allow =>
+                    {
+                        Some(rule)
+                    }
+                    keep => keep,
+                };
+            }
+        }
+        // RFC 9309 Section 2.2.2: If no match, URI is allowed
+        let allowed = decisive.is_none_or(|rule| rule.rule_type == RuleType::Allow);
+
+        Explanation {
+            allowed,
+            user_agents,
+            rule: decisive.cloned(),
+        }
+    }
+
+    /// The crawl-delay that applies to `user_agent`, taking the longest
+    /// delay when several groups match.
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<Duration> {
+        self.matching_groups(user_agent)
+            .iter()
+            .filter_map(|g| g.crawl_delay)
+            .max()
+    }
+
+    /// The groups consulted for `user_agent`, with the user-agent line that
+    /// selected them: the first matching token of a specific group, or `*`
+    /// for the fallback group. `None` when no group applies.
+    pub fn groups_for(&self, user_agent: &str) -> (Vec<&Group>, Option<&str>) {
+        let groups = self.matching_groups(user_agent);
+        let user_agent_lower = user_agent.to_lowercase();
+        let token = groups
+            .first()
+            .map(|group| group.matched_user_agent(&user_agent_lower).unwrap_or("*"));
+        (groups, token)
+    }
+
+    /// Select the groups that apply to `user_agent`, falling back to `*`.
+    fn matching_groups(&self, user_agent: &str) -> Vec<&Group> {
         // RFC 9309 Section 2.2.1: Case-insensitive matching
         let user_agent_lower = user_agent.to_lowercase();
         // Find all matching groups per RFC 9309
         let matching_groups: Vec<&Group> = self
             .groups
             .iter()
-            .filter(|group| {
-                group.user_agents.iter().any(|ua| {
-                    let ua_lower = ua.to_lowercase();
-                    // Exact match or substring match (product token is substring of UA)
-                    user_agent_lower == ua_lower || user_agent_lower.contains(&ua_lower)
-                })
-            })
+            .filter(|group| group.applies_to(&user_agent_lower))
             .collect();
         // RFC 9309: If no matching group, check for wildcard
-        let groups_to_check = if matching_groups.is_empty() {
+        if matching_groups.is_empty() {
             self.groups
                 .iter()
-                .filter(|g| g.user_agents.iter().any(|ua| ua == "*"))
+                .filter(|g| g.user_agent_tokens.iter().any(|ua| &**ua == "*"))
                 .collect::<Vec<_>>()
         } else {
             matching_groups
-        };
-        // If still no groups, no rules apply (allowed)
-        if groups_to_check.is_empty() {
-            return true;
         }
-        // Combine all rules from matching groups per RFC 9309
-        let mut all_rules = Vec::new();
-        for group in &groups_to_check {
+    }
+
+    /// Rough bytes held by the data, counting its strings, groups, rules and
+    /// sitemaps but not allocator overhead. Patterns shared between rules are
+    /// counted once per rule.
+    pub fn estimated_size(&self) -> usize {
+        let string = |s: &String| size_of::<String>() + s.len();
+        let optional = |s: &Option<String>| s.as_ref().map_or(0, String::len);
+        size_of::<Self>()
+            + self.target_url.len()
+            + self.robots_txt_url.len()
+            + self.groups.iter().map(Group::estimated_size).sum::<usize>()
+            + self.sitemaps.iter().map(string).sum::<usize>()
+            + self.sitemap_line_numbers.len() * size_of::<u32>()
+            + self
+                .warnings
+                .iter()
+                .map(|w| size_of::<ParseWarning>() + w.message.len())
+                .sum::<usize>()
+            + self.checksum.len()
+            + optional(&self.http_version)
+            + self
+                .upstream_headers
+                .iter()
+                .map(|(name, value)| string(name) + string(value))
+                .sum::<usize>()
+            + optional(&self.failure_reason)
+    }
+
+    /// robots.txt as the server interprets it: the groups and sitemaps it
+    /// enforces, in a fixed layout and without comments, so it can be
+    /// diffed against the original. Parsing it again gives the same rules.
+    pub fn canonical_text(&self) -> String {
+        let mut text = self.render(false);
+        text.truncate(text.trim_end().len());
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The groups and sitemaps written out as robots.txt, with the source
+    /// line of each rule and sitemap as a comment when `line_numbers` is set.
+    fn render(&self, line_numbers: bool) -> String {
+        let line_number = |n| if line_numbers { n } else { 0 };
+        let mut lines = Vec::new();
+
+        // A group without user-agents applies to no one
+        for group in self.groups.iter().filter(|g| !g.user_agents.is_empty()) {
+            for ua in &group.user_agents {
+                lines.push(format!("User-agent: {ua}"));
+            }
+
             for rule in &group.rules {
-                if let Ok(rule_type) = RuleType::try_from(rule.rule_type) {
-                    if rule_type == RuleType::Allow || rule_type == RuleType::Disallow {
-                        all_rules.push(rule);
-                    }
-                }
+                lines.push(with_line_number(
+                    format!("{}: {}", rule.rule_type, rule.path_pattern),
+                    line_number(rule.line_number),
+                ));
+            }
+
+            if let Some(delay) = group.crawl_delay {
+                lines.push(format!("Crawl-delay: {}", delay.as_secs_f64()));
+            } else if group.rules.is_empty() {
+                // Keeps the group from merging with the next when read back
+                lines.push("Disallow:".to_string());
             }
+
+            lines.push(String::new());
         }
-        // Find matching rules for this path
-        let matching_rules: Vec<&Rule> = all_rules
-            .iter()
-            .filter(|rule| Self::path_matches_rfc9309(path, &rule.path_pattern))
-            .copied()
-            .collect();
-        // RFC 9309 Section 2.2.2: If no match, URI is allowed
-        if matching_rules.is_empty() {
-            return true;
+
+        for (i, sitemap) in self.sitemaps.iter().enumerate() {
+            let n = self.sitemap_line_numbers.get(i).copied().unwrap_or(0);
+            lines.push(with_line_number(
+                format!("Sitemap: {sitemap}"),
+                line_number(n),
+            ));
         }
-        // Find the longest match (most octets per RFC 9309)
-        let max_len = matching_rules
-            .iter()
-            .map(|r| r.path_pattern.len())
-            .max()
-            .unwrap();
-        // Get all rules with the longest pattern
-        let longest_rules: Vec<_> = matching_rules
+
+        lines.join("\n")
+    }
+}
+
+impl From<&RobotsData> for String {
+    fn from(value: &RobotsData) -> Self {
+        value.render(true)
+    }
+}
+
+/// `line` with a comment naming the source line it came from, when known.
+fn with_line_number(line: String, line_number: u32) -> String {
+    match line_number {
+        0 => line,
+        n => format!("{line}  # line {n}"),
+    }
+}
+
+/// The outcome of evaluating a path, with the rule that decided it.
+#[derive(Clone, Debug)]
+pub struct Explanation {
+    pub allowed: bool,
+    /// User-agents of the groups that were consulted.
+    pub user_agents: Vec<String>,
+    /// The longest matching rule, or `None` when no rule matched.
+    pub rule: Option<Rule>,
+}
+
+/// A group of rules and the user-agents they apply to. Build it with
+/// [`Group::new`] and add user-agents with [`Group::push_user_agent`] so the
+/// lowercased copies used for matching stay in step. Lists are boxed slices
+/// rather than `Vec`s since cached groups never grow.
+#[derive(Clone, Debug, Serialize)]
+pub struct Group {
+    pub user_agents: Box<[Box<str>]>,
+    pub rules: Box<[Rule]>,
+    #[serde(
+        rename = "crawl_delay_seconds",
+        serialize_with = "serialize_crawl_delay"
+    )]
+    pub crawl_delay: Option<Duration>,
+    /// `user_agents` lowercased once, rather than on every lookup.
+    #[serde(skip)]
+    user_agent_tokens: Box<[Box<str>]>,
+}
+
+impl Group {
+    pub fn new(user_agents: Vec<String>, rules: Vec<Rule>, crawl_delay: Option<Duration>) -> Self {
+        let user_agent_tokens = user_agents
             .iter()
-            .filter(|r| r.path_pattern.len() == max_len)
+            .map(|ua| ua.to_lowercase().into_boxed_str())
             .collect();
-        // RFC 9309: If allow and disallow are equivalent, allow wins
-        let has_allow = longest_rules
+        Self {
+            user_agents: user_agents
+                .into_iter()
+                .map(String::into_boxed_str)
+                .collect(),
+            rules: rules.into_boxed_slice(),
+            crawl_delay,
+            user_agent_tokens,
+        }
+    }
+
+    /// Add a user-agent. This reallocates the list, which is fine for the
+    /// handful of user-agents a group has.
+    pub fn push_user_agent(&mut self, user_agent: String) {
+        let mut tokens = std::mem::take(&mut self.user_agent_tokens).into_vec();
+        tokens.push(user_agent.to_lowercase().into_boxed_str());
+        self.user_agent_tokens = tokens.into_boxed_slice();
+        let mut user_agents = std::mem::take(&mut self.user_agents).into_vec();
+        user_agents.push(user_agent.into_boxed_str());
+        self.user_agents = user_agents.into_boxed_slice();
+    }
+
+    fn estimated_size(&self) -> usize {
+        let user_agents: usize = self
+            .user_agents
             .iter()
-            .any(|r| RuleType::try_from(r.rule_type).ok() == Some(RuleType::Allow));
-        let has_disallow = longest_rules
+            .chain(&self.user_agent_tokens)
+            .map(|ua| size_of::<Box<str>>() + ua.len())
+            .sum();
+        size_of::<Self>() + user_agents + self.rules.iter().map(Rule::estimated_size).sum::<usize>()
+    }
+
+    /// Whether one of this group's user-agents is a product token of
+    /// `user_agent_lower`.
+    fn applies_to(&self, user_agent_lower: &str) -> bool {
+        self.matched_user_agent(user_agent_lower).is_some()
+    }
+
+    /// The first of this group's user-agents, as written, that is a product
+    /// token of `user_agent_lower`.
+    fn matched_user_agent(&self, user_agent_lower: &str) -> Option<&str> {
+        self.user_agent_tokens
             .iter()
-            .any(|r| RuleType::try_from(r.rule_type).ok() == Some(RuleType::Disallow));
-        // Allow wins on tie (RFC 9309 Section 2.2.2)
-        if has_allow {
-            return true;
+            .position(|ua| user_agent_lower == &**ua || user_agent_lower.contains(&**ua))
+            .map(|index| &*self.user_agents[index])
+    }
+}
+
+/// An allow or disallow line. Build it with [`Rule::new`], which works out
+/// once how the pattern has to be matched. The pattern is shared, so rules
+/// built from an [`Interner`] hold one copy of each distinct pattern.
+#[derive(Clone, Debug, Serialize)]
+pub struct Rule {
+    pub rule_type: RuleType,
+    pub path_pattern: Arc<str>,
+    /// The 1-based line the rule was read from, or zero when not known.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub line_number: u32,
+    #[serde(skip)]
+    pattern: Pattern,
+}
+
+impl Rule {
+    pub fn new(rule_type: RuleType, path_pattern: impl Into<Arc<str>>) -> Self {
+        let path_pattern = path_pattern.into();
+        let pattern = Pattern::compile(&path_pattern);
+        Self {
+            rule_type,
+            path_pattern,
+            line_number: 0,
+            pattern,
         }
-        // Otherwise follow disallow
-        !has_disallow
+    }
+
+    /// Record the 1-based line of robots.txt the rule was read from.
+    pub fn with_line_number(mut self, line_number: u32) -> Self {
+        self.line_number = line_number;
+        self
+    }
+
+    fn estimated_size(&self) -> usize {
+        size_of::<Self>()
+            + self.path_pattern.len()
+            + self.pattern.segments.len() * size_of::<Range<u32>>()
     }
 
     /// RFC 9309 Section 2.2.2: Path matching with wildcards and special characters
-    fn path_matches_rfc9309(path: &str, pattern: &str) -> bool {
-        if pattern.is_empty() {
+    fn matches(&self, path: &str) -> bool {
+        if self.path_pattern.is_empty() {
             return false;
         }
-        // Handle end-of-path anchor $ (RFC 9309 Section 2.2.3)
-        if pattern.ends_with('$') {
-            let prefix = &pattern[..pattern.len() - 1];
-            return Self::match_pattern(path, prefix, true);
-        }
-        // Regular prefix match
-        Self::match_pattern(path, pattern, false)
-    }
-    /// Match pattern against path with wildcard support
-    fn match_pattern(path: &str, pattern: &str, exact: bool) -> bool {
-        // Handle wildcards (* matches any sequence per RFC 9309 Section 2.2.3)
-        if pattern.contains('*') {
-            return Self::wildcard_match(path, pattern, exact);
-        }
+        let pattern = &self.pattern;
+        let body = &self.path_pattern[..pattern.body_len as usize];
         // RFC 9309: Match MUST start with first octet of path (prefix match)
-        if exact {
-            path == pattern
-        } else {
-            path.starts_with(pattern)
-        }
-    }
-    /// RFC 9309 wildcard matching (* matches any characters)
-    fn wildcard_match(path: &str, pattern: &str, exact: bool) -> bool {
-        let parts: Vec<&str> = pattern.split('*').collect();
-        if parts.is_empty() {
-            return true;
-        }
-        if parts.len() == 1 {
-            // No wildcards after split (should not happen due to earlier check)
-            return if exact {
-                path == pattern
+        if !pattern.has_wildcard {
+            return if pattern.end_anchored {
+                path == body
             } else {
-                path.starts_with(pattern)
+                path.starts_with(body)
             };
         }
-        // Multi-part wildcard matching
+        // Multi-part wildcard matching (* matches any sequence per RFC 9309 Section 2.2.3)
+        let last = pattern.segments.len() - 1;
         let mut pos = 0;
-        for (i, part) in parts.iter().enumerate() {
+        for (i, range) in pattern.segments.iter().enumerate() {
+            let part = &self.path_pattern[range.start as usize..range.end as usize];
             if part.is_empty() {
                 continue;
             }
@@ -144,97 +429,224 @@ impl RobotsData {
                     return false;
                 }
                 pos = part.len();
-            } else if i == parts.len() - 1 && exact {
+            } else if i == last && pattern.end_anchored {
                 // Last part with exact match must be at end
                 if !path.ends_with(part) {
                     return false;
                 }
-            } else {
+            } else if let Some(found) = path[pos..].find(part) {
                 // Middle parts can be anywhere after current position
-                if let Some(found) = path[pos..].find(part) {
-                    pos += found + part.len();
-                } else {
-                    return false;
-                }
+                pos += found + part.len();
+            } else {
+                return false;
             }
         }
         true
     }
 }
 
-impl From<&RobotsData> for String {
-    fn from(value: &RobotsData) -> Self {
-        let mut lines = Vec::new();
+/// How a path pattern is matched, worked out when the rule is built.
+/// Offsets are `u32` to keep rules small; robots.txt is capped far below
+/// 4 GiB.
+#[derive(Clone, Debug)]
+struct Pattern {
+    /// The pattern ends in `$` (RFC 9309 Section 2.2.3), so the match must
+    /// reach the end of the path.
+    end_anchored: bool,
+    has_wildcard: bool,
+    /// Length of the pattern without the trailing `$`.
+    body_len: u32,
+    /// Byte ranges of the pieces between `*`s, empty ones included. Empty,
+    /// and so not allocated, for patterns without a wildcard.
+    segments: Box<[Range<u32>]>,
+}
 
-        for group in &value.groups {
-            for ua in &group.user_agents {
-                lines.push(format!("User-agent: {ua}"));
+impl Pattern {
+    fn compile(path_pattern: &str) -> Self {
+        let end_anchored = path_pattern.ends_with('$');
+        let body = if end_anchored {
+            &path_pattern[..path_pattern.len() - 1]
+        } else {
+            path_pattern
+        };
+        let has_wildcard = body.contains('*');
+        let mut segments = Vec::new();
+        if has_wildcard {
+            let mut start = 0;
+            for part in body.split('*') {
+                segments.push(start as u32..(start + part.len()) as u32);
+                start += part.len() + 1;
             }
+        }
+        Self {
+            end_anchored,
+            has_wildcard,
+            body_len: body.len() as u32,
+            segments: segments.into_boxed_slice(),
+        }
+    }
+}
 
-            for rule in &group.rules {
-                let Ok(rule_type) = RuleType::try_from(rule.rule_type) else {
-                    continue;
-                };
-                let directive = match rule_type {
-                    RuleType::Allow => "Allow",
-                    RuleType::Disallow => "Disallow",
-                    _ => continue,
-                };
-                lines.push(format!("{directive}: {}", rule.path_pattern));
-            }
+/// Hands out one shared copy of each distinct string. Parsing one robots.txt
+/// through an interner lets rules repeated across groups, which is common
+/// when a site lists the same paths for several crawlers, share storage.
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+}
 
-            lines.push(String::new());
+impl Interner {
+    pub fn intern(&mut self, value: &str) -> Arc<str> {
+        if let Some(existing) = self.strings.get(value) {
+            return existing.clone();
         }
+        let value: Arc<str> = value.into();
+        self.strings.insert(value.clone());
+        value
+    }
+}
 
-        for sitemap in &value.sitemaps {
-            lines.push(format!("Sitemap: {sitemap}"));
-        }
+/// A line of robots.txt that was ignored or only partly understood.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
 
-        lines.join("\n")
-    }
+fn is_zero(value: &u32) -> bool {
+    *value == 0
 }
 
-#[derive(Clone, Debug)]
-pub struct Group {
-    pub user_agents: Vec<String>,
-    pub rules: Vec<Rule>,
+fn serialize_crawl_delay<S: Serializer>(
+    value: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(delay) => serializer.serialize_some(&delay.as_secs_f64()),
+        None => serializer.serialize_none(),
+    }
 }
 
-#[derive(Clone, Debug)]
-pub struct Rule {
-    pub rule_type: i32,
-    pub path_pattern: String,
+fn serialize_fetched_at<S: Serializer>(
+    value: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(time) => serializer.serialize_some(
+            &DateTime::<Utc>::from(*time).to_rfc3339_opts(SecondsFormat::Micros, true),
+        ),
+        None => serializer.serialize_none(),
+    }
 }
 
-impl From<Rule> for ProtoBufRule {
-    fn from(value: Rule) -> Self {
-        Self {
-            rule_type: value.rule_type,
-            path_pattern: value.path_pattern,
+/// Conversions to the generated protobuf types used on the wire.
+#[cfg(feature = "server")]
+mod wire {
+    use super::{AccessResult, Group, RobotsData, Rule, RuleType};
+    use crate::dns::AddressFamily;
+    use crate::service::robots::v1::{
+        AccessResult as ProtoBufAccessResult, AddressFamily as ProtoBufAddressFamily,
+        GetRobotsResponse, Group as ProtoBufGroup, Rule as ProtoBufRule,
+        rule::RuleType as ProtoBufRuleType,
+    };
+    use crate::service::{to_duration, to_timestamp};
+
+    impl From<AddressFamily> for ProtoBufAddressFamily {
+        fn from(value: AddressFamily) -> Self {
+            match value {
+                AddressFamily::V4 => Self::Ipv4,
+                AddressFamily::V6 => Self::Ipv6,
+            }
         }
     }
-}
 
-impl From<Group> for ProtoBufGroup {
-    fn from(value: Group) -> Self {
-        Self {
-            user_agents: value.user_agents,
-            rules: value.rules.into_iter().map(Into::into).collect(),
+    impl From<AccessResult> for ProtoBufAccessResult {
+        fn from(value: AccessResult) -> Self {
+            match value {
+                AccessResult::Unspecified => Self::Unspecified,
+                AccessResult::Success => Self::Success,
+                AccessResult::RedirectSuccess => Self::RedirectSuccess,
+                AccessResult::TooManyRedirects => Self::TooManyRedirects,
+                AccessResult::Unavailable => Self::Unavailable,
+                AccessResult::Unreachable => Self::Unreachable,
+                AccessResult::Cached => Self::Cached,
+                AccessResult::Override => Self::Override,
+                AccessResult::Unusable => Self::Unusable,
+            }
         }
     }
-}
 
-impl From<RobotsData> for GetRobotsResponse {
-    fn from(value: RobotsData) -> Self {
-        Self {
-            target_url: value.target_url,
-            robots_txt_url: value.robots_txt_url,
-            access_result: value.access_result.into(),
-            http_status_code: value.http_status_code,
-            groups: value.groups.into_iter().map(Into::into).collect(),
-            sitemaps: value.sitemaps,
-            content_length_bytes: value.content_length_bytes,
-            truncated: value.truncated,
+    impl From<RuleType> for ProtoBufRuleType {
+        fn from(value: RuleType) -> Self {
+            match value {
+                RuleType::Allow => Self::Allow,
+                RuleType::Disallow => Self::Disallow,
+            }
+        }
+    }
+
+    impl From<&Rule> for ProtoBufRule {
+        fn from(value: &Rule) -> Self {
+            Self {
+                rule_type: ProtoBufRuleType::from(value.rule_type).into(),
+                path_pattern: value.path_pattern.to_string(),
+                line_number: value.line_number,
+            }
+        }
+    }
+
+    impl From<&Group> for ProtoBufGroup {
+        fn from(value: &Group) -> Self {
+            Self {
+                user_agents: value.user_agents.iter().map(|ua| ua.to_string()).collect(),
+                rules: value.rules.iter().map(Into::into).collect(),
+                crawl_delay_seconds: value.crawl_delay.map(|d| d.as_secs_f64()),
+                crawl_delay: value.crawl_delay.map(to_duration),
+            }
+        }
+    }
+
+    /// Built by reference so cached data can be served without copying it
+    /// first.
+    impl From<&RobotsData> for GetRobotsResponse {
+        fn from(value: &RobotsData) -> Self {
+            Self {
+                target_url: value.target_url.clone(),
+                robots_txt_url: value.robots_txt_url.clone(),
+                access_result: ProtoBufAccessResult::from(value.access_result).into(),
+                http_status_code: value.http_status_code,
+                groups: value.groups.iter().map(Into::into).collect(),
+                sitemaps: value.sitemaps.clone(),
+                content_length_bytes: value.content_length_bytes,
+                truncated: value.truncated,
+                effective_user_agent_match: String::new(),
+                fetched_at: value.fetched_at.map(to_timestamp),
+                // Depends on the cache TTL, so filled in by the service
+                expires_at: None,
+                not_modified: false,
+                checksum: value.checksum.clone(),
+                // Kept apart from the robots.txt, so filled in by the service
+                policy_rules: Vec::new(),
+                // Known only to the service
+                data_source: 0,
+                requested_host: String::new(),
+                cache_key: String::new(),
+                upstream_headers: value
+                    .upstream_headers
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect(),
+                resolved_address: value
+                    .resolved_address
+                    .map(|address| address.to_string())
+                    .unwrap_or_default(),
+                address_family: value
+                    .resolved_address
+                    .map(|address| ProtoBufAddressFamily::from(AddressFamily::of(&address)))
+                    .unwrap_or_default()
+                    .into(),
+            }
         }
     }
 }
@@ -242,25 +654,17 @@ impl From<RobotsData> for GetRobotsResponse {
 impl From<RobotsTxt> for RobotsData {
     fn from(value: RobotsTxt) -> Self {
         let mut groups = Vec::new();
+        let mut patterns = Interner::default();
         for (user_agent, rule) in value.get_rules() {
             let mut rules = Vec::new();
             for path in &rule.allowed {
-                rules.push(Rule {
-                    rule_type: 1,
-                    path_pattern: path.clone(),
-                });
+                rules.push(Rule::new(RuleType::Allow, patterns.intern(path)));
             }
             for path in &rule.disallowed {
-                rules.push(Rule {
-                    rule_type: 2,
-                    path_pattern: path.clone(),
-                });
+                rules.push(Rule::new(RuleType::Disallow, patterns.intern(path)));
             }
 
-            groups.push(Group {
-                user_agents: vec![user_agent.clone()],
-                rules,
-            });
+            groups.push(Group::new(vec![user_agent.clone()], rules, None));
         }
 
         let sitemaps = value
@@ -278,6 +682,13 @@ impl From<RobotsTxt> for RobotsData {
             sitemaps,
             content_length_bytes: 0,
             truncated: false,
+            warnings: Vec::new(),
+            checksum: String::new(),
+            fetched_at: None,
+            http_version: None,
+            upstream_headers: BTreeMap::new(),
+            max_age: None,
+            resolved_address: None,
         }
     }
 }
@@ -0,0 +1,23 @@
+use std::io;
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::config::Config;
+
+/// tokio's limit on blocking threads when `max_blocking_threads` is unset.
+pub const DEFAULT_MAX_BLOCKING_THREADS: usize = 512;
+
+/// The multi-threaded runtime to serve on, with the thread counts `config`
+/// sets and tokio's defaults for the rest.
+pub fn build(config: &Config) -> io::Result<Runtime> {
+    let mut builder = Builder::new_multi_thread();
+    builder.enable_all().max_blocking_threads(
+        config
+            .max_blocking_threads
+            .unwrap_or(DEFAULT_MAX_BLOCKING_THREADS),
+    );
+    if let Some(threads) = config.worker_threads {
+        builder.worker_threads(threads);
+    }
+    builder.build()
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+
+use tracing::{info, instrument, warn};
+
+use crate::cache::Cache;
+use crate::cached_robots::CachedRobots;
+use crate::config::RuntimeConfig;
+use crate::fetcher::{FetchError, RobotsFetcher};
+use crate::redact;
+use crate::robots_data::RobotsData;
+use crate::target::NormalizedTarget;
+
+/// Cache key written and removed again by the cache check.
+const CACHE_PROBE_KEY: &str = "self-check:probe";
+
+/// The outcome of one part of the startup self-check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: true,
+            detail: detail.into(),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = if self.passed { "PASS" } else { "FAIL" };
+        write!(f, "{outcome} {}: {}", self.name, self.detail)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelfCheckReport {
+    pub results: Vec<CheckResult>,
+}
+
+impl SelfCheckReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.passed)
+    }
+
+    /// Log each result, failures as warnings, and a summary line.
+    pub fn log(&self) {
+        for result in &self.results {
+            if result.passed {
+                info!(check = result.name, "{result}");
+            } else {
+                warn!(check = result.name, "{result}");
+            }
+        }
+        let failed = self.results.iter().filter(|r| !r.passed).count();
+        if failed == 0 {
+            info!(checks = self.results.len(), "Self-check PASS");
+        } else {
+            warn!(checks = self.results.len(), failed, "Self-check FAIL");
+        }
+    }
+}
+
+/// Check that the deployment can do its job before it takes requests: that
+/// the cache backend answers, and when `probe_url` is given, that its
+/// robots.txt can be fetched. The probe is subject to the host allow and
+/// blocklists like any request.
+#[instrument(skip(config, cache, fetcher), fields(probe_url = ?probe_url.map(redact::url)))]
+pub async fn run<T: Cache<String, CachedRobots>>(
+    config: &RuntimeConfig,
+    cache: &T,
+    fetcher: &RobotsFetcher,
+    probe_url: Option<&str>,
+) -> SelfCheckReport {
+    let mut results = vec![check_cache(cache).await];
+    if let Some(probe_url) = probe_url {
+        results.push(check_egress(config, fetcher, probe_url).await);
+    }
+    SelfCheckReport { results }
+}
+
+async fn check_cache<T: Cache<String, CachedRobots>>(cache: &T) -> CheckResult {
+    const NAME: &str = "cache";
+    let key = CACHE_PROBE_KEY.to_string();
+    if let Err(e) = cache.set(key.clone(), RobotsData::default().into()).await {
+        return CheckResult::fail(NAME, format!("write failed: {e}"));
+    }
+    match cache.get_uncounted(&key).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return CheckResult::fail(NAME, "written entry was not read back"),
+        Err(e) => return CheckResult::fail(NAME, format!("read failed: {e}")),
+    }
+    match cache.delete(&key).await {
+        Ok(_) => CheckResult::pass(NAME, "read and write succeeded"),
+        Err(e) => CheckResult::fail(NAME, format!("delete failed: {e}")),
+    }
+}
+
+async fn check_egress(
+    config: &RuntimeConfig,
+    fetcher: &RobotsFetcher,
+    probe_url: &str,
+) -> CheckResult {
+    const NAME: &str = "egress";
+    let target = match NormalizedTarget::parse(probe_url) {
+        Ok(target) => target,
+        Err(e) => return CheckResult::fail(NAME, format!("invalid probe URL: {e}")),
+    };
+    if !config.host_permitted(&target.host) {
+        return CheckResult::fail(NAME, format!("host {} is not permitted", target.host));
+    }
+    match fetcher.fetch_text(probe_url).await {
+        Ok(text) => CheckResult::pass(
+            NAME,
+            format!(
+                "fetched {} (HTTP {})",
+                text.robots_txt_url, text.http_status_code
+            ),
+        ),
+        // The origin answered, which is all this checks
+        Err(FetchError::Unavailable(status)) => CheckResult::pass(
+            NAME,
+            format!("reached {} (HTTP {status})", target.robots_key),
+        ),
+        Err(FetchError::RateLimited(_)) => {
+            CheckResult::pass(NAME, format!("reached {} (HTTP 429)", target.robots_key))
+        }
+        Err(FetchError::ParseError(e)) => {
+            CheckResult::pass(NAME, format!("reached {} ({e})", target.robots_key))
+        }
+        Err(e) => CheckResult::fail(NAME, format!("{}: {e}", target.robots_key)),
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::cache::Cache;
+use crate::cached_robots::CachedRobots;
+use crate::clock::Clock;
+use crate::config::{ConfigError, ConfigHandle};
+use crate::decisions::DEFAULT_DECISION_CAPACITY;
+use crate::fetcher::{Fetcher, RobotsFetcher};
+use crate::politeness::{DEFAULT_CRAWL_DELAY, HostRateLimiter, MokaBucketStore};
+use crate::service::RobotsServer;
+
+#[derive(Debug, Error)]
+pub enum BuildError {
+    #[error("A cache is required")]
+    MissingCache,
+    #[error("A fetcher is required")]
+    MissingFetcher,
+    #[error("Decision capacity must be positive")]
+    ZeroDecisionCapacity,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+/// Collects a [`RobotsServer`]'s parts and settings, checking them together
+/// when the server is built. Start one with [`RobotsServer::builder`].
+pub struct RobotsServerBuilder<T: Cache<String, CachedRobots>, F: Fetcher = RobotsFetcher> {
+    cache: Option<T>,
+    fetcher: Option<F>,
+    config: Option<ConfigHandle>,
+    default_crawl_delay: Duration,
+    host_limiter: Option<HostRateLimiter>,
+    decision_capacity: usize,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl<T: Cache<String, CachedRobots>, F: Fetcher> RobotsServerBuilder<T, F> {
+    pub(crate) fn new() -> Self {
+        Self {
+            cache: None,
+            fetcher: None,
+            config: None,
+            default_crawl_delay: DEFAULT_CRAWL_DELAY,
+            host_limiter: None,
+            decision_capacity: DEFAULT_DECISION_CAPACITY,
+            clock: None,
+        }
+    }
+
+    pub fn cache(mut self, cache: T) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn fetcher(mut self, fetcher: F) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    /// Read the runtime settings through `config`. Without one the
+    /// defaults are used.
+    pub fn config(mut self, config: ConfigHandle) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// The delay enforced by NextAllowedFetch when robots.txt has no
+    /// crawl-delay.
+    pub fn default_crawl_delay(mut self, delay: Duration) -> Self {
+        self.default_crawl_delay = delay;
+        self
+    }
+
+    /// The aggregate per-host rate limiter. Without one, a limiter kept in
+    /// memory that follows `host_rate` in the configuration is used.
+    pub fn host_rate_limiter(mut self, limiter: HostRateLimiter) -> Self {
+        self.host_limiter = Some(limiter);
+        self
+    }
+
+    /// How many decision events each StreamDecisions subscriber may fall
+    /// behind before the oldest are dropped.
+    pub fn decision_capacity(mut self, capacity: usize) -> Self {
+        self.decision_capacity = capacity;
+        self
+    }
+
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn build(self) -> Result<RobotsServer<T, F>, BuildError> {
+        let cache = self.cache.ok_or(BuildError::MissingCache)?;
+        let fetcher = self.fetcher.ok_or(BuildError::MissingFetcher)?;
+        if self.decision_capacity == 0 {
+            return Err(BuildError::ZeroDecisionCapacity);
+        }
+        let config = self.config.unwrap_or_default();
+        config.get().validate()?;
+
+        let host_limiter = self.host_limiter.unwrap_or_else(|| {
+            let store = match &self.clock {
+                Some(clock) => MokaBucketStore::new().with_clock(clock.clone()),
+                None => MokaBucketStore::new(),
+            };
+            HostRateLimiter::with_config(store, config.clone())
+        });
+        let server = RobotsServer::new(cache, fetcher)
+            .with_config(config)
+            .with_default_crawl_delay(self.default_crawl_delay)
+            .with_host_rate_limiter(host_limiter)
+            .with_decision_capacity(self.decision_capacity);
+        Ok(match self.clock {
+            Some(clock) => server.with_clock(clock),
+            None => server,
+        })
+    }
+}
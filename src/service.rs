@@ -1,151 +1,764 @@
-use tonic::{Request, Response, Status};
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use robots::{
-    AccessResult, GetRobotsRequest, GetRobotsResponse, robots_service_server::RobotsService,
-};
-use tracing::{Span, debug, info, instrument, warn};
-use url::Url;
+use futures_util::StreamExt;
+use moka::future::Cache as MokaCacheImpl;
+use sha2::{Digest, Sha256};
+
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::{Code, Request, Response, Status};
+
+use robots::v1::{GetRobotsRequest, GetRobotsResponse, robots_service_server::RobotsService};
+use tracing::{Instrument, Span, debug, info, instrument, warn};
 
 use crate::{
-    cache::Cache,
-    fetcher::{FetchError, RobotsFetcher, extract_robots_url},
-    robots_data::RobotsData,
-    service::robots::{IsAllowedRequest, IsAllowedResponse},
+    auth,
+    cache::{Cache, CacheError, CacheResult},
+    cached_robots::CachedRobots,
+    clock::{self, Clock},
+    config::{ConfigHandle, ConfiguredTtl, RuntimeConfig, UnsupportedSchemePolicy, UnusablePolicy},
+    decisions::{DecisionFeed, DecisionStream},
+    fetch_queue::{FetchQueue, Priority},
+    fetcher::{FetchError, Fetcher, RobotsFetcher, with_remote_address},
+    in_flight::{InFlightFetches, SharedFetch},
+    log_sampling::LogSampler,
+    metrics,
+    overrides::{policy_data, policy_key},
+    pagination::{CachePageToken, PageToken},
+    politeness::{
+        DEFAULT_CRAWL_DELAY, FetchHistory, FetchSlot, HostRateLimiter, PolitenessTracker,
+    },
+    prefetch::PrefetchJobs,
+    read_mask::ReadMask,
+    redact,
+    robots_data::{AccessResult, RobotsData, Rule, RuleType},
+    server_builder::RobotsServerBuilder,
+    service::robots::v1::{
+        AccessResult as ProtoBufAccessResult, CacheSummary, CachedEntry, CanCrawlRequest,
+        CanCrawlResponse, CheckRobotsHeadersRequest, CheckRobotsHeadersResponse, ClearCacheRequest,
+        ClearCacheResponse, DataSource, DecisionEvent, DeniedBy, GetCanonicalRobotsTextResponse,
+        GetPrefetchStatusRequest, GetPrefetchStatusResponse, GetServerInfoRequest,
+        GetServerInfoResponse, InvalidateUrlRequest, InvalidateUrlResponse, IsAllowedRequest,
+        IsAllowedResponse, ItemError, ListCachedEntriesRequest, ListCachedEntriesResponse,
+        NextAllowedFetchRequest, NextAllowedFetchResponse, PrefetchHostsRequest,
+        PrefetchHostsResponse, RefreshRobotsRequest, RefreshRobotsResponse, ServerLimits,
+        SetPolicyRulesRequest, SetPolicyRulesResponse, StreamDecisionsRequest,
+        rule::RuleType as ProtoBufRuleType,
+    },
+    snapshot::{self, SnapshotError},
+    target::{NormalizedTarget, TargetError},
+    trace_context::TraceContext,
+    warm::WarmReport,
+    x_robots_tag::{XRobotsTagHeaders, parse_x_robots_tag},
 };
 
+/// Generated gRPC types. `robots::v1` is the canonical package; the
+/// top-level `robots` module only holds the deprecated unversioned service,
+/// served by [`crate::legacy::LegacyRobotsServer`].
 pub mod robots {
     include!("generated/robots.rs");
+
+    pub mod v1 {
+        include!("generated/robots.v1.rs");
+    }
 }
 
-pub struct RobotsServer<T: Cache<String, RobotsData>> {
-    cache: T,
-    fetcher: RobotsFetcher,
+/// Encoded descriptors for both packages, for the reflection service.
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/robots_descriptor.bin"));
+
+pub struct RobotsServer<T: Cache<String, CachedRobots>, F: Fetcher = RobotsFetcher> {
+    cache: Arc<T>,
+    fetcher: F,
+    header_cache: MokaCacheImpl<String, XRobotsTagHeaders>,
+    /// When each robots.txt URL was first seen serving unusable content,
+    /// for `UnusablePolicy::DenyForHours`.
+    unusable_since: MokaCacheImpl<String, SystemTime>,
+    /// The last robots.txt fetched for each robots.txt URL, kept past its
+    /// cache expiry for callers that accept stale data.
+    last_known: MokaCacheImpl<String, CachedRobots>,
+    /// The fetch running for each robots.txt URL, joined by everything
+    /// that needs it fetched meanwhile.
+    in_flight: InFlightFetches,
+    fetch_queue: FetchQueue,
+    prefetch_jobs: PrefetchJobs,
+    config: ConfigHandle,
+    politeness: PolitenessTracker,
+    /// When each robots.txt URL was last fetched, to space out RefreshRobots.
+    fetch_history: FetchHistory,
+    host_limiter: HostRateLimiter,
+    default_crawl_delay: Duration,
+    decisions: DecisionFeed,
+    started_at: Instant,
+    clock: Arc<dyn Clock>,
+    groups_converted: AtomicU64,
+}
+
+/// The outcome of evaluating robots.txt for one target.
+struct Verdict {
+    allowed: bool,
+    reason: String,
+    source: DataSource,
 }
 
-impl<T: Cache<String, RobotsData>> RobotsServer<T> {
-    pub fn new(cache: T, fetcher: RobotsFetcher) -> Self {
-        Self { cache, fetcher }
+/// robots.txt data found for one target, and where it came from.
+struct Lookup {
+    data: Arc<RobotsData>,
+    source: DataSource,
+    /// The robots.txt URL the data is cached under.
+    cache_key: String,
+}
+
+impl<T: Cache<String, CachedRobots>, F: Fetcher> RobotsServer<T, F> {
+    /// Build a server from its parts and settings, checked together.
+    pub fn builder() -> RobotsServerBuilder<T, F> {
+        RobotsServerBuilder::new()
+    }
+
+    pub fn new(cache: T, fetcher: F) -> Self {
+        let config = ConfigHandle::default();
+        Self {
+            cache: Arc::new(cache),
+            fetcher,
+            header_cache: header_cache(config.clone()),
+            unusable_since: MokaCacheImpl::new(MAX_UNUSABLE_HOSTS),
+            last_known: MokaCacheImpl::new(MAX_LAST_KNOWN),
+            in_flight: InFlightFetches::default(),
+            fetch_queue: FetchQueue::new(config.clone()),
+            prefetch_jobs: PrefetchJobs::default(),
+            config,
+            politeness: PolitenessTracker::new(),
+            fetch_history: FetchHistory::new(),
+            host_limiter: HostRateLimiter::default(),
+            default_crawl_delay: DEFAULT_CRAWL_DELAY,
+            decisions: DecisionFeed::default(),
+            started_at: Instant::now(),
+            clock: clock::system(),
+            groups_converted: AtomicU64::new(0),
+        }
+    }
+
+    /// Set the delay enforced by NextAllowedFetch when robots.txt has no crawl-delay.
+    pub fn with_default_crawl_delay(mut self, delay: Duration) -> Self {
+        self.default_crawl_delay = delay;
+        self
+    }
+
+    /// Read the header cache TTL, fetch limits and host allow/blocklists
+    /// through `config`.
+    pub fn with_config(mut self, config: ConfigHandle) -> Self {
+        self.header_cache = header_cache(config.clone());
+        self.fetch_queue = FetchQueue::new(config.clone());
+        self.config = config;
+        self
+    }
+
+    /// Read the time from `clock` for fetch timestamps, crawl-delay and
+    /// refresh spacing, and the unusable robots.txt policy.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.fetcher = self.fetcher.with_clock(clock.clone());
+        self.politeness = PolitenessTracker::new().with_clock(clock.clone());
+        self.fetch_history = FetchHistory::new().with_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    /// How many GetRobots responses have included groups. Converting them
+    /// is most of the cost of a large response, and a `read_mask` without
+    /// `groups` skips it.
+    pub fn groups_converted(&self) -> u64 {
+        self.groups_converted.load(Ordering::Relaxed)
+    }
+
+    /// Close the cache once serving has stopped, so it can flush and
+    /// release what it holds. Call once, after the last request.
+    pub async fn close(&self) -> CacheResult<()> {
+        self.cache.close().await
+    }
+
+    /// Fetch robots.txt into the cache for each host in `urls` that is not
+    /// already cached, `prefetch_concurrency` at a time as background work,
+    /// as PrefetchHosts does but waiting for the fetches to finish. URLs
+    /// that cannot be warmed are logged and skipped.
+    #[instrument(skip_all, fields(urls = urls.len()))]
+    pub async fn warm(&self, urls: &[String]) -> WarmReport {
+        let config = self.config.get();
+        let mut report = WarmReport::default();
+        let mut seen = HashSet::new();
+        let mut fetches = Vec::new();
+        for url in urls {
+            let aliased = normalize(url).and_then(|target| {
+                if config.robots_override(&target.host).is_some() {
+                    return Ok(None);
+                }
+                self.check_host(&target)?;
+                self.aliased(&target).map(Some)
+            });
+            let (cache_key, target) = match aliased {
+                Ok(Some(aliased)) => aliased,
+                Ok(None) => {
+                    report.skipped += 1;
+                    continue;
+                }
+                Err(status) => {
+                    warn!(
+                        url = %redact::url(url),
+                        error = %status.message(),
+                        "Not warming the cache for URL"
+                    );
+                    report.failed += 1;
+                    continue;
+                }
+            };
+            if !seen.insert(cache_key.clone())
+                || matches!(self.cache.get_uncounted(&cache_key).await, Ok(Some(_)))
+            {
+                report.skipped += 1;
+                continue;
+            }
+            let refresh = self.deferred_refresh(Priority::Background, cache_key, target);
+            let url = redact::url(url);
+            fetches.push(async move {
+                let fetched = refresh.await;
+                if let Err(status) = &fetched {
+                    warn!(%url, error = %status.message(), "Failed to warm the cache for URL");
+                }
+                fetched.is_ok()
+            });
+        }
+        let results = futures_util::stream::iter(fetches)
+            .buffer_unordered(config.prefetch_concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+        for fetched in results {
+            if fetched {
+                report.warmed += 1;
+            } else {
+                report.failed += 1;
+            }
+        }
+        info!(
+            warmed = report.warmed,
+            skipped = report.skipped,
+            failed = report.failed,
+            "Finished warming the cache"
+        );
+        report
+    }
+
+    /// Save every cached robots.txt to `path`, to be restored with
+    /// [`snapshot::restore`] after a restart. Call once serving has stopped
+    /// and before [`close`](Self::close).
+    pub async fn save_snapshot(&self, path: &Path) -> Result<usize, SnapshotError> {
+        snapshot::save(&*self.cache, path).await
+    }
+
+    /// Abort the robots.txt fetches still running and refuse any more, so
+    /// that nothing is written to the cache after it is saved or closed.
+    /// Call once serving has stopped. Returns how many were aborted.
+    pub fn cancel_fetches(&self) -> usize {
+        self.in_flight.cancel_all()
+    }
+
+    /// Whether the cache backend answers a lookup, for readiness probes.
+    pub async fn cache_reachable(&self) -> bool {
+        self.cache
+            .get_uncounted(&"readiness:probe".to_string())
+            .await
+            .is_ok()
+    }
+
+    /// Reject targets whose host is excluded by the configured allow/blocklists.
+    fn check_host(&self, target: &NormalizedTarget) -> Result<(), Status> {
+        let host = target.host.as_str();
+        if self.config.get().host_permitted(host) {
+            Ok(())
+        } else {
+            debug!(host, "Host excluded by configuration");
+            Err(Status::permission_denied(format!(
+                "Host {host} is not permitted"
+            )))
+        }
     }
 
+    /// The cache key for `target` and the target whose robots.txt is
+    /// fetched for it. A host configured as an alias of another shares that
+    /// host's entry, and is still fetched itself unless `fetch_canonical_host`
+    /// is set.
+    fn aliased(&self, target: &NormalizedTarget) -> Result<(String, NormalizedTarget), Status> {
+        let config = self.config.get();
+        let Some(host) = config.canonical_host(&target.host) else {
+            return Ok((target.robots_key.clone(), target.clone()));
+        };
+        let canonical = target.on_host(&host).map_err(|e| {
+            warn!(error = %e, canonical_host = %host, "Invalid host alias");
+            Status::internal(format!("Invalid host alias {host}: {e}"))
+        })?;
+        debug!(canonical_host = %host, "Host is an alias");
+        if !config.fetch_canonical_host {
+            return Ok((canonical.robots_key, target.clone()));
+        }
+        self.check_host(&canonical)?;
+        Ok((canonical.robots_key.clone(), canonical))
+    }
+
+    /// Whether the call carries one of `admin_api_keys`, and so may make
+    /// admin overrides.
+    fn is_admin(&self, metadata: &MetadataMap) -> bool {
+        let config = self.config.get();
+        auth::api_key(metadata).is_some_and(|key| config.accepts_admin_key(key))
+    }
+
+    /// Replace the aggregate per-host rate limiter shared by all clients.
+    pub fn with_host_rate_limiter(mut self, limiter: HostRateLimiter) -> Self {
+        self.host_limiter = limiter;
+        self
+    }
+
+    /// Set how many decision events each StreamDecisions subscriber may fall
+    /// behind before the oldest are dropped.
+    pub fn with_decision_capacity(mut self, capacity: usize) -> Self {
+        self.decisions = DecisionFeed::new(capacity);
+        self
+    }
+
+    /// Look up robots.txt for `target`, fetching it on a cache miss. Also
+    /// returns where the data came from. The data is shared with
+    /// the cache rather than copied out of it. With `lazy_parse` a fetched
+    /// body is cached as text and parsed by the first cache hit that needs
+    /// it. A host with a configured override is never fetched or cached.
+    /// Fetches wait for a turn in the fetch queue at `priority`, and
+    /// concurrent misses on the same robots.txt share one fetch. Within
+    /// `stale_while_revalidate_secs` of an entry expiring, the expired data
+    /// is returned and refetched in the background instead.
     async fn get_robots_data(
         &self,
-        robots_url: String,
-        target_url: String,
-    ) -> Result<RobotsData, Status> {
-        match self.cache.get(&robots_url).await {
-            Ok(Some(data)) => {
+        target: &NormalizedTarget,
+        priority: Priority,
+    ) -> Result<(Arc<RobotsData>, DataSource), Status> {
+        let lookup = self.lookup(target, priority, None).await?;
+        Ok((lookup.data, lookup.source))
+    }
+
+    /// [`get_robots_data`](Self::get_robots_data), but when a fetch is still
+    /// running at `answer_by` and earlier data for the host is known, answer
+    /// from that instead and leave the fetch to finish in the background.
+    async fn lookup(
+        &self,
+        target: &NormalizedTarget,
+        priority: Priority,
+        answer_by: Option<Instant>,
+    ) -> Result<Lookup, Status> {
+        self.check_host(target)?;
+        if let Some(robots_override) = self.config.get().robots_override(&target.host) {
+            debug!("Using configured robots.txt override");
+            let data = robots_override.data_for(&target.target_url, &target.robots_key);
+            return Ok(Lookup {
+                data: Arc::new(data),
+                source: DataSource::Override,
+                cache_key: target.robots_key.clone(),
+            });
+        }
+        let (cache_key, fetch_target) = self.aliased(target)?;
+        match self.cache.get(&cache_key).await {
+            Ok(Some(entry)) => {
                 debug!("Cache hit for request");
-                Ok(data)
+                return Ok(Lookup {
+                    data: entry.data(),
+                    source: DataSource::Cache,
+                    cache_key,
+                });
             }
             Ok(None) => {
                 debug!("Cache miss for request, fetching from origin");
-                match self.fetcher.fetch(&target_url).await {
-                    Ok(data) => {
-                        info!(
-                            status_code = data.http_status_code,
-                            content_length = data.content_length_bytes,
-                            "Successfully fetched robots.txt"
-                        );
-                        if let Err(e) = self
-                            .cache
-                            .set(data.robots_txt_url.clone(), data.clone())
-                            .await
-                        {
-                            warn!(error = %e, "Failed to cache robots.txt data");
-                        }
-                        Ok(data)
-                    }
-                    Err(FetchError::Unavailable(s)) => {
-                        info!(status_code = s, "robots.txt unavailable");
-                        let data = RobotsData {
-                            target_url,
-                            robots_txt_url: robots_url,
-                            access_result: AccessResult::Unavailable,
-                            http_status_code: s as u32,
-                            ..Default::default()
-                        };
-
-                        if let Err(e) = self
-                            .cache
-                            .set(data.robots_txt_url.clone(), data.clone())
-                            .await
-                        {
-                            warn!(error = %e, "Failed to cache robots.txt data");
-                        }
-                        Ok(data)
-                    }
-                    Err(FetchError::Unreachable(e)) => {
-                        info!(error = %e.0, status = e.1, "robots.txt unreachable");
-                        let s = e.1.unwrap_or(0);
-                        let data = RobotsData {
-                            target_url,
-                            robots_txt_url: robots_url,
-                            access_result: AccessResult::Unreachable,
-                            http_status_code: s as u32,
-                            ..Default::default()
-                        };
-                        if let Err(e) = self
-                            .cache
-                            .set(data.robots_txt_url.clone(), data.clone())
-                            .await
-                        {
-                            warn!(error = %e, "Failed to cache robots.txt data");
-                        }
-                        Ok(data)
-                    }
-                    Err(FetchError::Timeout) => {
-                        info!("Request timeout");
-                        let data = RobotsData {
-                            target_url,
-                            robots_txt_url: robots_url,
-                            access_result: AccessResult::Unreachable,
-                            ..Default::default()
-                        };
-                        if let Err(e) = self
-                            .cache
-                            .set(data.robots_txt_url.clone(), data.clone())
-                            .await
-                        {
-                            warn!(error = %e, "Failed to cache robots.txt data");
-                        }
-                        Ok(data)
-                    }
-                    Err(e) => {
-                        warn!(error = %e, "Failed to fetch robots.txt");
-                        Err(Status::internal(e.to_string()))
-                    }
-                }
+            }
+            // A shared cache going down costs fetches, not answers
+            Err(CacheError::Unavailable) => {
+                warn!("Cache unavailable, fetching from origin");
             }
             Err(e) => {
                 warn!(error = %e, "Cache error");
-                Err(Status::internal(e.to_string()))
+                return Err(Status::internal(e.to_string()));
+            }
+        }
+        if let Some(data) = self.revalidate(&cache_key, &fetch_target).await {
+            return Ok(Lookup {
+                data,
+                source: DataSource::StaleCache,
+                cache_key,
+            });
+        }
+        let refresh = self.coalesced_refresh(priority, cache_key.clone(), fetch_target);
+        let previous = match answer_by {
+            Some(answer_by) => self
+                .last_known
+                .get(&cache_key)
+                .await
+                .map(|previous| (answer_by, previous)),
+            None => None,
+        };
+        let Some((answer_by, previous)) = previous else {
+            return Ok(Lookup {
+                data: refresh.await?,
+                source: DataSource::FreshFetch,
+                cache_key,
+            });
+        };
+        let budget = answer_by.saturating_duration_since(Instant::now());
+        match tokio::time::timeout(budget, refresh).await {
+            Ok(fetched) => Ok(Lookup {
+                data: fetched?,
+                source: DataSource::FreshFetch,
+                cache_key,
+            }),
+            Err(_) => {
+                info!("robots.txt refresh outlasts the deadline, answering from stale data");
+                Ok(Lookup {
+                    data: previous.data(),
+                    source: DataSource::StaleCache,
+                    cache_key,
+                })
             }
         }
     }
+
+    /// The last robots.txt fetched under `cache_key`, when it expired no
+    /// more than `stale_while_revalidate_secs` ago, after starting a
+    /// background refetch of it unless one is already running.
+    async fn revalidate(
+        &self,
+        cache_key: &str,
+        target: &NormalizedTarget,
+    ) -> Option<Arc<RobotsData>> {
+        let config = self.config.get();
+        let window = config.stale_while_revalidate();
+        if window.is_zero() {
+            return None;
+        }
+        let data = self.last_known.get(cache_key).await?.data();
+        let expired_at = data.fetched_at? + config.entry_ttl(&data);
+        let now = self.clock.now();
+        // An entry dropped before it expired, as by InvalidateUrl, is
+        // fetched again as usual
+        if now < expired_at || now > expired_at + window {
+            return None;
+        }
+        info!("Answering from expired robots.txt while it is refetched");
+        // Joins the fetch already running, if there is one; either way it
+        // finishes without being waited on
+        let _ = self.coalesced_refresh(Priority::Background, cache_key.to_string(), target.clone());
+        Some(data)
+    }
+
+    /// [`queued_refresh`](Self::queued_refresh), or the one already running
+    /// for `cache_key`, so that however many calls need a robots.txt at
+    /// once, it is fetched once and all of them get its result, failures
+    /// included.
+    fn coalesced_refresh(
+        &self,
+        priority: Priority,
+        cache_key: String,
+        target: NormalizedTarget,
+    ) -> SharedFetch {
+        let refresh = self.queued_refresh(priority, cache_key.clone(), target);
+        self.in_flight.join(cache_key, refresh)
+    }
+
+    /// [`coalesced_refresh`](Self::coalesced_refresh), started or joined
+    /// only once polled, for fetches run a few at a time.
+    fn deferred_refresh(
+        &self,
+        priority: Priority,
+        cache_key: String,
+        target: NormalizedTarget,
+    ) -> impl Future<Output = Result<Arc<RobotsData>, Status>> + Send + 'static + use<T, F> {
+        let refresh = self.queued_refresh(priority, cache_key.clone(), target);
+        let in_flight = self.in_flight.clone();
+        async move { in_flight.join(cache_key, refresh).await }
+    }
+
+    /// Refetch robots.txt for `target` into the cache under `cache_key` once
+    /// the fetch queue gives it a turn at `priority`. Owns what it needs, so
+    /// it can finish after the call that started it has returned.
+    fn queued_refresh(
+        &self,
+        priority: Priority,
+        cache_key: String,
+        target: NormalizedTarget,
+    ) -> impl Future<Output = Result<Arc<RobotsData>, Status>> + Send + 'static + use<T, F> {
+        let fetch = refresh(
+            self.cache.clone(),
+            self.fetcher.clone(),
+            self.last_known.clone(),
+            self.fetch_history.clone(),
+            self.config.get(),
+            cache_key,
+            target,
+        );
+        // Taken now, as the fetch may be spawned away from the call's task
+        let fetch = TraceContext::scope(TraceContext::current(), fetch);
+        let fetch_queue = self.fetch_queue.clone();
+        async move {
+            // Held until the fetch is done, even when it finishes in the
+            // background
+            let _permit = fetch_queue.acquire(priority).await.map_err(|e| {
+                warn!(error = %e, "Refusing fetch");
+                Status::unavailable(e.to_string())
+            })?;
+            fetch.await
+        }
+    }
+
+    /// When the cached `data` is due to be refetched under the current TTL
+    /// for its fetch outcome.
+    fn expires_at(&self, data: &RobotsData) -> Option<prost_types::Timestamp> {
+        let ttl = self.config.get().entry_ttl(data);
+        data.fetched_at.map(|t| to_timestamp(t + ttl))
+    }
+
+    /// The supplemental rules for `host`: the configured ones, then any set
+    /// through SetPolicyRules.
+    async fn policy_rules(&self, host: &str) -> Result<Vec<Rule>, Status> {
+        let mut rules: Vec<Rule> = self
+            .config
+            .get()
+            .policy_rules(host)
+            .map(|rules| rules.rules().collect())
+            .unwrap_or_default();
+        match self.cache.get_uncounted(&policy_key(host)).await {
+            Ok(Some(entry)) => {
+                let data = entry.data();
+                rules.extend(data.groups.iter().flat_map(|g| g.rules.iter().cloned()));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!(error = %e, "Cache error");
+                return Err(Status::internal(e.to_string()));
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Evaluate the host's supplemental rules and then robots.txt for
+    /// `target`, treating an unreachable robots.txt as a full disallow. A
+    /// matching supplemental rule decides without robots.txt being looked up.
+    /// With `answer_by`, a refetch still running then is answered from stale
+    /// data when there is any.
+    async fn robots_allows(
+        &self,
+        target: &NormalizedTarget,
+        user_agent: &str,
+        priority: Priority,
+        answer_by: Option<Instant>,
+    ) -> Result<Verdict, Status> {
+        Span::current().record("robots_url", &target.robots_key);
+        self.check_host(target)?;
+        let policy = self.policy_rules(&target.host).await?;
+        if !policy.is_empty() {
+            let explanation = policy_data(policy).explain(user_agent, &target.path_and_query);
+            if let Some(rule) = explanation.rule {
+                debug!("Supplemental rule decided");
+                return Ok(Verdict {
+                    allowed: explanation.allowed,
+                    reason: format!("POLICY: {}: {}", rule.rule_type, rule.path_pattern),
+                    source: DataSource::SupplementalPolicy,
+                });
+            }
+        }
+        let Lookup { data, source, .. } = self.lookup(target, priority, answer_by).await?;
+        if source == DataSource::FreshFetch && data.access_result != AccessResult::Unusable {
+            // A fresh fetch that could be used restarts the clock
+            self.unusable_since.invalidate(&target.robots_key).await;
+        }
+        let mut verdict = match data.access_result {
+            AccessResult::Unreachable => Verdict {
+                allowed: false,
+                reason: "robots.txt unreachable".to_string(),
+                source,
+            },
+            AccessResult::Unavailable => Verdict {
+                allowed: true,
+                reason: "robots.txt unavailable".to_string(),
+                source,
+            },
+            AccessResult::TooManyRedirects => Verdict {
+                allowed: true,
+                reason: format!(
+                    "robots.txt unavailable: {}",
+                    data.failure_reason
+                        .as_deref()
+                        .unwrap_or("TOO_MANY_REDIRECTS")
+                ),
+                source,
+            },
+            AccessResult::Unusable => self.unusable_verdict(target, &data, source).await,
+            _ => {
+                let explanation = data.explain(user_agent, &target.path_and_query);
+                Verdict {
+                    allowed: explanation.allowed,
+                    reason: match explanation.rule {
+                        Some(rule) => format!("{}: {}", rule.rule_type, rule.path_pattern),
+                        None => "no matching rule".to_string(),
+                    },
+                    source,
+                }
+            }
+        };
+        if source == DataSource::StaleCache {
+            verdict.reason = format!("STALE: {}", verdict.reason);
+        }
+
+        Ok(verdict)
+    }
+
+    /// IsAllowed's answer for `url` when its scheme is not http(s) and
+    /// `unsupported_schemes` says to answer rather than reject it.
+    fn unsupported_scheme_answer(&self, url: &str) -> Option<IsAllowedResponse> {
+        let allowed = match self.config.get().unsupported_schemes {
+            UnsupportedSchemePolicy::Reject => return None,
+            UnsupportedSchemePolicy::Allow => true,
+            UnsupportedSchemePolicy::Deny => false,
+        };
+        let Err(TargetError::UnsupportedScheme(scheme)) = NormalizedTarget::parse(url) else {
+            return None;
+        };
+        debug!(%scheme, allowed, "Answering for an unsupported scheme");
+        Some(IsAllowedResponse {
+            allowed,
+            reason: "UNSUPPORTED_SCHEME".to_string(),
+            ..Default::default()
+        })
+    }
+
+    /// Decide for a robots.txt that was fetched but could not be understood,
+    /// following `unusable_robots_txt`.
+    async fn unusable_verdict(
+        &self,
+        target: &NormalizedTarget,
+        data: &RobotsData,
+        source: DataSource,
+    ) -> Verdict {
+        let (allowed, reason) = match self.config.get().unusable_robots_txt {
+            UnusablePolicy::AllowAll => (true, "robots.txt unusable: allow-all".to_string()),
+            UnusablePolicy::DenyAll => (false, "robots.txt unusable: deny-all".to_string()),
+            UnusablePolicy::DenyForHours(hours) => {
+                let since = self
+                    .unusable_since
+                    .get_with(target.robots_key.clone(), async {
+                        data.fetched_at.unwrap_or_else(|| self.clock.now())
+                    })
+                    .await;
+                let until = since + Duration::from_secs_f64(hours * 60.0 * 60.0);
+                if self.clock.now() < until {
+                    (false, format!("robots.txt unusable: deny for {hours}h"))
+                } else {
+                    (true, format!("robots.txt unusable: allow after {hours}h"))
+                }
+            }
+        };
+        debug!(allowed, "robots.txt unusable");
+        Verdict {
+            allowed,
+            reason,
+            source,
+        }
+    }
 }
 
 #[tonic::async_trait]
-impl<T: Cache<String, RobotsData>> RobotsService for RobotsServer<T> {
-    #[instrument(skip(self, request), fields(url = %request.get_ref().url, robots_url = tracing::field::Empty))]
+impl<T: Cache<String, CachedRobots>, F: Fetcher> RobotsService for RobotsServer<T, F> {
+    #[instrument(skip(self, request), fields(url = %redact::url(&request.get_ref().url), robots_url = tracing::field::Empty))]
     async fn get_robots_txt(
         &self,
         request: Request<GetRobotsRequest>,
     ) -> Result<Response<GetRobotsResponse>, Status> {
+        let priority = fetch_priority(request.metadata());
         let req = request.into_inner();
-        let robots_url =
-            extract_robots_url(&req.url).map_err(|e| Status::invalid_argument(e.to_string()))?;
-        let target_url = req.url;
-
-        Span::current().record("robots_url", &robots_url);
+        let target = normalize(&req.url)?;
+        let mask = ReadMask::parse(req.read_mask.as_ref()).map_err(|e| {
+            debug!(error = %e, "Rejected read mask");
+            Status::invalid_argument(e.to_string())
+        })?;
+        let page_token = match req.page_token.as_str() {
+            "" => None,
+            token => Some(
+                token
+                    .parse::<PageToken>()
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?,
+            ),
+        };
+        Span::current().record("robots_url", &target.robots_key);
         info!("Processing robots.txt request");
-        let data = self.get_robots_data(robots_url, target_url).await?;
-        Ok(Response::new(data.into()))
+        let Lookup {
+            data,
+            source,
+            cache_key,
+        } = self.lookup(&target, priority, None).await?;
+        if !req.known_sha256.is_empty() && req.known_sha256.eq_ignore_ascii_case(&data.checksum) {
+            debug!("Caller already has this robots.txt");
+            return Ok(Response::new(GetRobotsResponse {
+                not_modified: true,
+                checksum: data.checksum.clone(),
+                fetched_at: data.fetched_at.map(to_timestamp),
+                expires_at: self.expires_at(&data),
+                data_source: source.into(),
+                ..Default::default()
+            }));
+        }
+        if mask.includes("groups") {
+            self.groups_converted.fetch_add(1, Ordering::Relaxed);
+        }
+        let mut response = mask.response(&data, req.user_agent.as_deref());
+        let max_sitemaps = self.config.get().max_sitemaps;
+        if response.sitemaps.len() > max_sitemaps {
+            debug!(
+                sitemaps = response.sitemaps.len(),
+                "Leaving out sitemaps over the limit"
+            );
+            response.sitemaps.truncate(max_sitemaps);
+            response.sitemap_line_numbers.truncate(max_sitemaps);
+            response.sitemaps_truncated = mask.includes("sitemaps_truncated");
+        }
+        let next_page = PageToken::page(
+            page_token.as_ref(),
+            req.page_size as usize,
+            &mut response.groups,
+            &data.checksum,
+        )
+        .map_err(|e| {
+            debug!(error = %e, "Rejected page token");
+            Status::aborted(e.to_string())
+        })?;
+        if mask.includes("next_page_token") {
+            response.next_page_token = next_page.map(|t| t.to_string()).unwrap_or_default();
+        }
+        if mask.includes("expires_at") {
+            response.expires_at = self.expires_at(&data);
+        }
+        if mask.includes("policy_rules") {
+            let rules = self.policy_rules(&target.host).await?;
+            response.policy_rules = rules.iter().map(Into::into).collect();
+        }
+        if mask.includes("data_source") {
+            response.data_source = source.into();
+        }
+        if mask.includes("requested_host") {
+            response.requested_host = target.host;
+        }
+        if mask.includes("cache_key") {
+            response.cache_key = cache_key;
+        }
+        Ok(Response::new(response))
     }
 
     #[instrument(
         skip(self, request), 
         fields(
-            target_url = %request.get_ref().target_url, 
+            target_url = %redact::url(&request.get_ref().target_url), 
             user_agent = %request.get_ref().user_agent, 
             robots_url = tracing::field::Empty, 
             allowed = tracing::field::Empty))
@@ -154,34 +767,942 @@ impl<T: Cache<String, RobotsData>> RobotsService for RobotsServer<T> {
         &self,
         request: Request<IsAllowedRequest>,
     ) -> Result<Response<IsAllowedResponse>, Status> {
+        // Leave time for the response to reach the caller before its deadline
+        let answer_by = grpc_timeout(request.metadata())
+            .map(|timeout| Instant::now() + timeout.saturating_sub(STALE_ANSWER_MARGIN));
+        let priority = fetch_priority(request.metadata());
+        let req = request.into_inner();
+        if let Some(response) = self.unsupported_scheme_answer(&req.target_url) {
+            Span::current().record("allowed", response.allowed);
+            return Ok(Response::new(response));
+        }
+        let target = normalize(&req.target_url)?;
+        let answer_by = answer_by.filter(|_| req.allow_stale);
+        let verdict = self
+            .robots_allows(&target, &req.user_agent, priority, answer_by)
+            .await?;
+        let allowed = verdict.allowed;
+        Span::current().record("allowed", allowed);
+        if IS_ALLOWED_DECISION.sample() {
+            debug!(reason = %verdict.reason, "Decided robots.txt access");
+        }
+        self.decisions.publish(DecisionEvent {
+            timestamp_ms: self
+                .clock
+                .now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or_default(),
+            host: target.host.clone(),
+            user_agent: req.user_agent.clone(),
+            allowed,
+            reason: verdict.reason.clone(),
+            cache_hit: verdict.source == DataSource::Cache,
+            dropped: 0,
+        });
+        // An allowed URL is about to be fetched, so it counts against the host rate
+        let suggested_wait = if allowed {
+            match self.host_limiter.acquire(&target.robots_key).await {
+                FetchSlot::Granted => Duration::ZERO,
+                FetchSlot::Wait(wait) => wait,
+            }
+        } else {
+            Duration::ZERO
+        };
+
+        Ok(Response::new(IsAllowedResponse {
+            allowed,
+            suggested_wait_ms: suggested_wait.as_micros().div_ceil(1000) as u64,
+            reason: verdict.reason,
+            stale: verdict.source == DataSource::StaleCache,
+            data_source: verdict.source.into(),
+        }))
+    }
+
+    #[instrument(
+        skip(self, request),
+        fields(
+            url = %redact::url(&request.get_ref().url),
+            user_agent = %request.get_ref().user_agent,
+            noindex = tracing::field::Empty))
+    ]
+    async fn check_robots_headers(
+        &self,
+        request: Request<CheckRobotsHeadersRequest>,
+    ) -> Result<Response<CheckRobotsHeadersResponse>, Status> {
         let req = request.into_inner();
+        let target = normalize(&req.url)?;
+        self.check_host(&target)?;
+        let headers = self
+            .fetcher
+            .fetch_x_robots_tag(&target.target_url)
+            .await
+            .map_err(header_fetch_status)?;
+        let response = headers_response(target.target_url, headers, &req.user_agent);
+        Span::current().record("noindex", response.noindex);
+        info!(
+            header_present = response.header_present,
+            "Checked X-Robots-Tag headers"
+        );
+
+        Ok(Response::new(response))
+    }
 
-        let target_url = req.target_url;
-        let user_agent = &req.user_agent;
-        let robots_url =
-            extract_robots_url(&target_url).map_err(|e| Status::invalid_argument(e.to_string()))?;
-        let data = self.get_robots_data(robots_url, target_url.clone()).await?;
-        match data.access_result {
-            AccessResult::Unreachable => {
-                return Ok(Response::new(IsAllowedResponse { allowed: false }));
+    #[instrument(
+        skip(self, request),
+        fields(
+            target_url = %redact::url(&request.get_ref().target_url),
+            user_agent = %request.get_ref().user_agent,
+            robots_url = tracing::field::Empty,
+            allowed = tracing::field::Empty))
+    ]
+    async fn can_crawl(
+        &self,
+        request: Request<CanCrawlRequest>,
+    ) -> Result<Response<CanCrawlResponse>, Status> {
+        let priority = fetch_priority(request.metadata());
+        let req = request.into_inner();
+        let target = normalize(&req.target_url)?;
+        let robots_allowed = self
+            .robots_allows(&target, &req.user_agent, priority, None)
+            .await?
+            .allowed;
+        if !robots_allowed {
+            debug!("robots.txt denies crawl, skipping header check");
+            Span::current().record("allowed", false);
+            return Ok(Response::new(CanCrawlResponse {
+                allowed: false,
+                robots_allowed,
+                headers: None,
+                denied_by: DeniedBy::RobotsTxt.into(),
+            }));
+        }
+        if req.skip_header_check {
+            debug!("Header check skipped by request");
+            Span::current().record("allowed", true);
+            return Ok(Response::new(CanCrawlResponse {
+                allowed: true,
+                robots_allowed,
+                headers: None,
+                denied_by: DeniedBy::None.into(),
+            }));
+        }
+
+        let headers = match self.header_cache.get(&target.target_url).await {
+            Some(headers) => {
+                debug!("Header cache hit");
+                headers
+            }
+            None => {
+                let headers = self
+                    .fetcher
+                    .fetch_x_robots_tag(&target.target_url)
+                    .await
+                    .map_err(header_fetch_status)?;
+                self.header_cache
+                    .insert(target.target_url.clone(), headers.clone())
+                    .await;
+                headers
+            }
+        };
+        let headers = headers_response(target.target_url, headers, &req.user_agent);
+        let allowed = !headers.noindex && !headers.none;
+        Span::current().record("allowed", allowed);
+        info!(allowed, "Evaluated crawl permission");
+
+        Ok(Response::new(CanCrawlResponse {
+            allowed,
+            robots_allowed,
+            headers: Some(headers),
+            denied_by: if allowed {
+                DeniedBy::None.into()
+            } else {
+                DeniedBy::XRobotsTag.into()
+            },
+        }))
+    }
+
+    #[instrument(
+        skip(self, request),
+        fields(
+            host_url = %redact::url(&request.get_ref().host_url),
+            user_agent = %request.get_ref().user_agent,
+            robots_url = tracing::field::Empty,
+            granted = tracing::field::Empty))
+    ]
+    async fn next_allowed_fetch(
+        &self,
+        request: Request<NextAllowedFetchRequest>,
+    ) -> Result<Response<NextAllowedFetchResponse>, Status> {
+        let priority = fetch_priority(request.metadata());
+        let req = request.into_inner();
+        let target = normalize(&req.host_url)?;
+        let robots_url = target.robots_key.clone();
+        Span::current().record("robots_url", &robots_url);
+        let (data, _) = self.get_robots_data(&target, priority).await?;
+        let robots_delay = data.crawl_delay(&req.user_agent);
+        let delay = robots_delay.unwrap_or(self.default_crawl_delay);
+
+        // Check the crawl-delay first so a host token is only spent on a
+        // request that can actually be granted
+        let slot = match self
+            .politeness
+            .pending_wait(&robots_url, &req.user_agent, delay)
+            .await
+        {
+            Some(wait) => FetchSlot::Wait(wait),
+            None => match self.host_limiter.acquire(&robots_url).await {
+                FetchSlot::Granted => {
+                    self.politeness
+                        .try_acquire(&robots_url, &req.user_agent, delay)
+                        .await
+                }
+                wait => wait,
+            },
+        };
+        let (granted, wait) = match slot {
+            FetchSlot::Granted => (true, Duration::ZERO),
+            FetchSlot::Wait(wait) => (false, wait),
+        };
+        Span::current().record("granted", granted);
+        info!(
+            granted,
+            delay_ms = delay.as_millis() as u64,
+            "Evaluated fetch slot"
+        );
+
+        Ok(Response::new(NextAllowedFetchResponse {
+            granted,
+            // Round up so a caller sleeping for wait_ms never asks too early
+            wait_ms: wait.as_micros().div_ceil(1000) as u64,
+            crawl_delay_ms: delay.as_millis() as u64,
+            crawl_delay_from_robots: robots_delay.is_some(),
+        }))
+    }
+
+    #[instrument(skip(self, request), fields(url = %redact::url(&request.get_ref().url), robots_url = tracing::field::Empty))]
+    async fn invalidate_url(
+        &self,
+        request: Request<InvalidateUrlRequest>,
+    ) -> Result<Response<InvalidateUrlResponse>, Status> {
+        let target = normalize(&request.get_ref().url)?;
+        let (robots_url, _) = self.aliased(&target)?;
+        Span::current().record("robots_url", &robots_url);
+        let existed = self.cache.delete(&robots_url).await.map_err(|e| {
+            warn!(error = %e, "Cache error");
+            Status::internal(e.to_string())
+        })?;
+        // The stale copy goes too, so neither a failed refetch nor a
+        // throttled refresh answers with the invalidated robots.txt, and a
+        // robots.txt found unusable starts its grace period over
+        self.last_known.invalidate(&robots_url).await;
+        self.unusable_since.invalidate(&target.robots_key).await;
+        info!(existed, "Invalidated cached robots.txt");
+
+        Ok(Response::new(InvalidateUrlResponse {
+            robots_txt_url: robots_url,
+            existed,
+        }))
+    }
+
+    type StreamDecisionsStream = DecisionStream;
+
+    #[instrument(skip(self, request), fields(host_suffix = %request.get_ref().host_suffix, allowed = ?request.get_ref().allowed))]
+    async fn stream_decisions(
+        &self,
+        request: Request<StreamDecisionsRequest>,
+    ) -> Result<Response<Self::StreamDecisionsStream>, Status> {
+        info!("Subscribing to decision events");
+        let stream = self.decisions.subscribe(request.into_inner());
+        Ok(Response::new(stream))
+    }
+
+    #[instrument(skip(self, _request))]
+    async fn get_server_info(
+        &self,
+        _request: Request<GetServerInfoRequest>,
+    ) -> Result<Response<GetServerInfoResponse>, Status> {
+        let config = self.config.get();
+        let cache_stats = self.cache.stats().await;
+        let (hits, misses) = cache_stats.map_or((0, 0), |s| (s.hits, s.misses));
+        let lookups = hits + misses;
+        let stats = self.fetcher.stats();
+
+        Ok(Response::new(GetServerInfoResponse {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: env!("ROBOTS_GIT_HASH").to_string(),
+            uptime_seconds: self.started_at.elapsed().as_secs(),
+            limits: Some(ServerLimits {
+                fetch_timeout_seconds: config.fetch_timeout_secs,
+                cache_ttl_seconds: config.cache_ttl_secs,
+                header_cache_ttl_seconds: config.header_cache_ttl_secs,
+                max_robots_txt_bytes: config.max_robots_txt_bytes as u64,
+                host_rate_per_second: config.host_rate.per_second,
+                host_rate_burst: config.host_rate.burst,
+            }),
+            cache: Some(CacheSummary {
+                entries: cache_stats
+                    .map(|s| s.entry_count)
+                    .or_else(|| self.cache.entry_count()),
+                hits,
+                misses,
+                hit_rate: if lookups == 0 {
+                    0.0
+                } else {
+                    hits as f64 / lookups as f64
+                },
+            }),
+            in_flight_fetches: stats.in_flight(),
+            fetches_total: stats.total(),
+            features: enabled_features(),
+        }))
+    }
+
+    #[instrument(skip(self, request), fields(url = %redact::url(&request.get_ref().url), host = tracing::field::Empty))]
+    async fn set_policy_rules(
+        &self,
+        request: Request<SetPolicyRulesRequest>,
+    ) -> Result<Response<SetPolicyRulesResponse>, Status> {
+        let req = request.into_inner();
+        let host = normalize(&req.url)?.host;
+        Span::current().record("host", &host);
+        let rules = req
+            .rules
+            .iter()
+            .map(|rule| {
+                let rule_type = match rule.rule_type() {
+                    ProtoBufRuleType::Allow => RuleType::Allow,
+                    ProtoBufRuleType::Disallow => RuleType::Disallow,
+                    ProtoBufRuleType::Unspecified => {
+                        return Err(Status::invalid_argument("Rule type is required"));
+                    }
+                };
+                if rule.path_pattern.is_empty() {
+                    return Err(Status::invalid_argument("Rule path pattern is required"));
+                }
+                Ok(Rule::new(rule_type, rule.path_pattern.as_str()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let key = policy_key(&host);
+        let stored = if rules.is_empty() {
+            self.cache.delete(&key).await.map(|_| ())
+        } else {
+            // Pinned, so the rules stay until replaced rather than expiring,
+            // being evicted or going with ClearCache
+            self.cache.pin(key, policy_data(rules).into()).await
+        };
+        stored.map_err(|e| {
+            warn!(error = %e, "Cache error");
+            match e {
+                CacheError::Unsupported(_) => Status::unimplemented(e.to_string()),
+                _ => Status::internal(e.to_string()),
+            }
+        })?;
+        let rules = self.policy_rules(&host).await?;
+        info!(rules = rules.len(), "Set supplemental rules");
+
+        Ok(Response::new(SetPolicyRulesResponse {
+            host,
+            rules: rules.iter().map(Into::into).collect(),
+        }))
+    }
+
+    #[instrument(skip(self, request), fields(urls = request.get_ref().urls.len(), job_id = tracing::field::Empty))]
+    async fn prefetch_hosts(
+        &self,
+        request: Request<PrefetchHostsRequest>,
+    ) -> Result<Response<PrefetchHostsResponse>, Status> {
+        let config = self.config.get();
+        let urls = &request.get_ref().urls;
+        check_batch_size(urls.len(), &config)?;
+        let (mut errors, mut skipped) = (Vec::new(), 0);
+        let mut seen = HashSet::new();
+        let mut targets = Vec::new();
+        for (index, url) in urls.iter().enumerate() {
+            let target = match normalize(url) {
+                Ok(target) => target,
+                Err(status) => {
+                    errors.push(item_error(index, &status));
+                    continue;
+                }
+            };
+            if config.robots_override(&target.host).is_some() {
+                skipped += 1;
+                continue;
+            }
+            let (cache_key, fetch_target) = match self
+                .check_host(&target)
+                .and_then(|()| self.aliased(&target))
+            {
+                Ok(aliased) => aliased,
+                Err(status) => {
+                    errors.push(item_error(index, &status));
+                    continue;
+                }
+            };
+            if !seen.insert(cache_key.clone())
+                || matches!(self.cache.get_uncounted(&cache_key).await, Ok(Some(_)))
+            {
+                skipped += 1;
+                continue;
+            }
+            targets.push((index, cache_key, fetch_target));
+        }
+        let job = self
+            .prefetch_jobs
+            .start(targets.len(), config.prefetch_queue_capacity);
+        Span::current().record("job_id", job.id());
+        let full = Status::resource_exhausted("Prefetch queue is full");
+        for (index, ..) in targets.drain(job.total() as usize..) {
+            errors.push(item_error(index, &full));
+        }
+        errors.sort_by_key(|error| error.index);
+        let rejected = errors.len() as u64;
+        for (outcome, count) in [
+            ("accepted", job.total()),
+            ("rejected", rejected),
+            ("skipped", skipped),
+        ] {
+            metrics::PREFETCH_HOSTS
+                .with_label_values(&[outcome])
+                .inc_by(count);
+        }
+        let fetches = targets
+            .into_iter()
+            .map(|(_, cache_key, target)| {
+                let refresh = self.deferred_refresh(Priority::Background, cache_key, target);
+                async move { refresh.await.is_ok() }
+            })
+            .collect::<Vec<_>>();
+        tokio::spawn(
+            job.clone()
+                .run(fetches, config.prefetch_concurrency)
+                .in_current_span(),
+        );
+        info!(
+            accepted = job.total(),
+            rejected, skipped, "Started prefetch job"
+        );
+
+        Ok(Response::new(PrefetchHostsResponse {
+            job_id: job.id(),
+            accepted: job.total(),
+            rejected,
+            skipped,
+            errors,
+        }))
+    }
+
+    #[instrument(skip(self, request), fields(job_id = request.get_ref().job_id))]
+    async fn get_prefetch_status(
+        &self,
+        request: Request<GetPrefetchStatusRequest>,
+    ) -> Result<Response<GetPrefetchStatusResponse>, Status> {
+        let job_id = request.get_ref().job_id;
+        let job = self.prefetch_jobs.get(job_id).ok_or_else(|| {
+            debug!("Unknown prefetch job");
+            Status::not_found(format!("No prefetch job {job_id}"))
+        })?;
+
+        Ok(Response::new(GetPrefetchStatusResponse {
+            job_id,
+            done: job.done(),
+            failed: job.failed(),
+            remaining: job.remaining(),
+        }))
+    }
+
+    #[instrument(
+        skip(self, request),
+        fields(
+            url = %redact::url(&request.get_ref().url),
+            force = request.get_ref().force,
+            robots_url = tracing::field::Empty,
+            throttled = tracing::field::Empty))
+    ]
+    async fn refresh_robots(
+        &self,
+        request: Request<RefreshRobotsRequest>,
+    ) -> Result<Response<RefreshRobotsResponse>, Status> {
+        let priority = fetch_priority(request.metadata());
+        if request.get_ref().force && !self.is_admin(request.metadata()) {
+            debug!("Refusing a forced refresh without an admin API key");
+            return Err(Status::permission_denied(
+                "force needs an API key from admin_api_keys; refresh without it instead",
+            ));
+        }
+        let req = request.into_inner();
+        let target = normalize(&req.url)?;
+        self.check_host(&target)?;
+        if self.config.get().robots_override(&target.host).is_some() {
+            debug!("Refusing to refresh an overridden host");
+            return Err(Status::failed_precondition(format!(
+                "Host {} has a configured robots.txt override",
+                target.host
+            )));
+        }
+        let (cache_key, fetch_target) = self.aliased(&target)?;
+        Span::current().record("robots_url", &cache_key);
+        let wait = if req.force {
+            None
+        } else {
+            let interval = self.config.get().min_refresh_interval();
+            self.fetch_history
+                .pending_wait(&fetch_target.robots_key, interval)
+                .await
+        };
+        let (data, next_refresh_at) = match wait {
+            Some(wait) => {
+                info!(
+                    wait_ms = wait.as_millis() as u64,
+                    "robots.txt fetched too recently, answering from the last fetch"
+                );
+                metrics::REFRESH_THROTTLED.inc();
+                // The last fetch may have been dropped since, as by
+                // InvalidateUrl, leaving what is cached, if anything
+                let data = match self.last_known.get(&cache_key).await {
+                    Some(previous) => Some(previous.data()),
+                    None => self
+                        .cache
+                        .get(&cache_key)
+                        .await
+                        .map_err(|e| {
+                            warn!(error = %e, "Cache error");
+                            Status::internal(e.to_string())
+                        })?
+                        .map(|entry| entry.data()),
+                };
+                (data, Some(self.clock.now() + wait))
             }
-            _ => {}
+            None => {
+                let data = self
+                    .coalesced_refresh(priority, cache_key.clone(), fetch_target)
+                    .await?;
+                info!("Refreshed robots.txt");
+                (Some(data), None)
+            }
+        };
+        Span::current().record("throttled", next_refresh_at.is_some());
+
+        let mut response = RefreshRobotsResponse {
+            robots_txt_url: cache_key,
+            throttled: next_refresh_at.is_some(),
+            next_refresh_at: next_refresh_at.map(to_timestamp),
+            ..Default::default()
+        };
+        if let Some(data) = data {
+            response.access_result = ProtoBufAccessResult::from(data.access_result).into();
+            response.http_status_code = data.http_status_code;
+            response.fetched_at = data.fetched_at.map(to_timestamp);
+            response.checksum = data.checksum.clone();
         }
-        let path = extract_path_from_url(&target_url)?;
+        Ok(Response::new(response))
+    }
 
-        let allowed = data.is_allowed(&user_agent, &path);
+    #[instrument(skip(self, request), fields(url = %redact::url(&request.get_ref().url), robots_url = tracing::field::Empty))]
+    async fn get_canonical_robots_text(
+        &self,
+        request: Request<GetRobotsRequest>,
+    ) -> Result<Response<GetCanonicalRobotsTextResponse>, Status> {
+        let priority = fetch_priority(request.metadata());
+        let target = normalize(&request.get_ref().url)?;
+        Span::current().record("robots_url", &target.robots_key);
+        let Lookup { data, .. } = self.lookup(&target, priority, None).await?;
+        let text = data.canonical_text();
+        debug!(bytes = text.len(), "Rendered canonical robots.txt");
+        Ok(Response::new(GetCanonicalRobotsTextResponse {
+            checksum: format!("{:x}", Sha256::digest(text.as_bytes())),
+            text,
+            based_on_sha256: data.checksum.clone(),
+        }))
+    }
 
-        Ok(Response::new(IsAllowedResponse { allowed }))
+    #[instrument(skip(self, _request))]
+    async fn clear_cache(
+        &self,
+        _request: Request<ClearCacheRequest>,
+    ) -> Result<Response<ClearCacheResponse>, Status> {
+        if !self.config.get().allow_clear_cache {
+            debug!("Refusing ClearCache with allow_clear_cache off");
+            return Err(Status::permission_denied(
+                "ClearCache is disabled; set allow_clear_cache to enable it",
+            ));
+        }
+        let entries_dropped = self.cache.clear().await.map_err(|e| {
+            warn!(error = %e, "Cache error");
+            match e {
+                CacheError::Unsupported(_) => Status::unimplemented(e.to_string()),
+                _ => Status::internal(e.to_string()),
+            }
+        })?;
+        // Stale copies and X-Robots-Tag lookups go too, so nothing cached
+        // before the clear is served after it
+        self.last_known.invalidate_all();
+        self.header_cache.invalidate_all();
+        self.unusable_since.invalidate_all();
+        warn!(?entries_dropped, "Cleared the robots.txt cache");
+
+        Ok(Response::new(ClearCacheResponse { entries_dropped }))
     }
+
+    #[instrument(skip(self, request), fields(page_size = request.get_ref().page_size))]
+    async fn list_cached_entries(
+        &self,
+        request: Request<ListCachedEntriesRequest>,
+    ) -> Result<Response<ListCachedEntriesResponse>, Status> {
+        let req = request.into_inner();
+        let page_token = match req.page_token.as_str() {
+            "" => None,
+            token => Some(
+                token
+                    .parse::<CachePageToken>()
+                    .map_err(|e| Status::invalid_argument(e.to_string()))?,
+            ),
+        };
+        let page_size = match req.page_size {
+            0 => DEFAULT_CACHE_PAGE_SIZE,
+            size => (size as usize).min(MAX_CACHE_PAGE_SIZE),
+        };
+        let policy_prefix = policy_key("");
+        let mut entries = self
+            .cache
+            .entries()
+            .await
+            .map_err(|e| {
+                warn!(error = %e, "Cache error");
+                match e {
+                    CacheError::Unsupported(_) => Status::unimplemented(e.to_string()),
+                    _ => Status::internal(e.to_string()),
+                }
+            })?
+            .into_iter()
+            // Rules set through SetPolicyRules share the cache but are not
+            // fetched robots.txt
+            .filter_map(|(key, value, _)| {
+                let listed = page_token.as_ref().is_none_or(|t| t.is_pending(&key));
+                (listed && !key.starts_with(&policy_prefix)).then_some((key, value))
+            })
+            .collect::<Vec<_>>();
+        let next_page = CachePageToken::page(page_size, &mut entries, |(key, _)| key);
+        let now = self.clock.now();
+        // Only the entries on the page are parsed, so listing leaves the
+        // rest of a lazily parsed cache as it is
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| {
+                let data = value.data();
+                CachedEntry {
+                    robots_txt_url: key,
+                    access_result: ProtoBufAccessResult::from(data.access_result).into(),
+                    http_status_code: data.http_status_code,
+                    age_seconds: data
+                        .fetched_at
+                        .and_then(|t| now.duration_since(t).ok())
+                        .map_or(0, |age| age.as_secs()),
+                    truncated: data.truncated,
+                }
+            })
+            .collect::<Vec<_>>();
+        debug!(entries = entries.len(), "Listed cached entries");
+
+        Ok(Response::new(ListCachedEntriesResponse {
+            entries,
+            next_page_token: next_page.map(|t| t.to_string()).unwrap_or_default(),
+        }))
+    }
+}
+
+/// Validate and normalize a URL taken from a request. Every RPC goes through
+/// this so malformed input gets the same status, message, and
+/// `x-error-reason` metadata whichever RPC it was sent to.
+fn normalize(url: &str) -> Result<NormalizedTarget, Status> {
+    NormalizedTarget::parse(url).map_err(|e| {
+        debug!(error = %e, "Rejected URL");
+        let mut metadata = MetadataMap::new();
+        metadata.insert("x-error-reason", MetadataValue::from_static(e.reason()));
+        Status::with_metadata(Code::InvalidArgument, format!("Invalid URL: {e}"), metadata)
+    })
+}
+
+/// Reject a batch call carrying more than `max_batch_items` items,
+/// echoing the limit in `x-max-batch-items` metadata.
+fn check_batch_size(items: usize, config: &RuntimeConfig) -> Result<(), Status> {
+    if items <= config.max_batch_items {
+        return Ok(());
+    }
+    debug!(items, "Rejected oversized batch");
+    let mut metadata = MetadataMap::new();
+    metadata.insert(
+        "x-error-reason",
+        MetadataValue::from_static("BATCH_TOO_LARGE"),
+    );
+    metadata.insert("x-max-batch-items", config.max_batch_items.into());
+    Err(Status::with_metadata(
+        Code::OutOfRange,
+        format!(
+            "Batch of {items} items exceeds max_batch_items ({})",
+            config.max_batch_items
+        ),
+        metadata,
+    ))
 }
 
-fn extract_path_from_url(url: &str) -> Result<String, Status> {
-    let parsed = Url::parse(url).map_err(|e| Status::invalid_argument(e.to_string()))?;
-    let mut path = parsed.path().to_string();
-    if let Some(query) = parsed.query() {
-        path.push('?');
-        path.push_str(query);
+/// The per-item result for the item at `index` of a batch, which failed
+/// with `status`.
+fn item_error(index: usize, status: &Status) -> ItemError {
+    ItemError {
+        index: index as u32,
+        code: status.code() as i32,
+        message: status.message().to_string(),
     }
+}
+
+/// `time` as a protobuf Timestamp.
+pub fn to_timestamp(time: SystemTime) -> prost_types::Timestamp {
+    time.into()
+}
 
-    Ok(path)
+/// `duration` as a protobuf Duration, saturating at the longest one the
+/// wire type can hold.
+pub fn to_duration(duration: Duration) -> prost_types::Duration {
+    prost_types::Duration::try_from(duration).unwrap_or(prost_types::Duration {
+        seconds: i64::MAX,
+        nanos: 999_999_999,
+    })
+}
+
+/// Every optional Cargo feature the server was built with.
+fn enabled_features() -> Vec<String> {
+    [
+        ("server", cfg!(feature = "server")),
+        ("redis", cfg!(feature = "redis")),
+        ("chaos", cfg!(feature = "chaos")),
+        ("compression", cfg!(feature = "compression")),
+        ("http3", cfg!(feature = "http3")),
+        ("middleware", cfg!(feature = "middleware")),
+        ("test-support", cfg!(feature = "test-support")),
+        ("tls", cfg!(feature = "tls")),
+        ("systemd", cfg!(feature = "systemd")),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(name, _)| name.to_string())
+    .collect()
+}
+
+/// Hosts whose first unusable robots.txt is remembered at once.
+const MAX_UNUSABLE_HOSTS: u64 = 100_000;
+
+/// robots.txt URLs whose last fetch is kept for stale answers.
+const MAX_LAST_KNOWN: u64 = 10_000;
+
+/// URLs whose X-Robots-Tag lookup is cached.
+const MAX_HEADER_LOOKUPS: u64 = 100_000;
+
+/// How long before the caller's deadline a stale answer is given.
+const STALE_ANSWER_MARGIN: Duration = Duration::from_millis(100);
+
+/// Entries per ListCachedEntries page when the caller gives no page_size.
+const DEFAULT_CACHE_PAGE_SIZE: usize = 100;
+
+/// The most entries a ListCachedEntries page holds, whatever page_size asks.
+const MAX_CACHE_PAGE_SIZE: usize = 1000;
+
+// IsAllowed runs for every URL a crawler considers, so its decision line is
+// sampled
+static IS_ALLOWED_DECISION: LogSampler = LogSampler::new("service.is_allowed");
+
+/// Fetch robots.txt for `target` and store it in the cache under
+/// `cache_key`, for as long as `ttl_policy` gives its outcome. Takes what it
+/// needs by value, so it can be spawned to finish after its caller gives up.
+async fn refresh<T: Cache<String, CachedRobots>, F: Fetcher>(
+    cache: Arc<T>,
+    fetcher: F,
+    last_known: MokaCacheImpl<String, CachedRobots>,
+    history: FetchHistory,
+    config: Arc<RuntimeConfig>,
+    cache_key: String,
+    target: NormalizedTarget,
+) -> Result<Arc<RobotsData>, Status> {
+    let robots_url = target.robots_key;
+    let target_url = target.target_url;
+    history.record(&robots_url).await;
+    let mut parsed_now = None;
+    let (fetched, resolved_address) = if config.lazy_parse {
+        let (fetched, resolved_address) =
+            with_remote_address(fetcher.fetch_text(&target_url)).await;
+        let fetched = fetched.map(|text| {
+            info!(
+                status_code = text.http_status_code,
+                content_length = text.content_length_bytes,
+                "Fetched robots.txt, caching it unparsed"
+            );
+            // This request decides from its own copy, so hosts that are
+            // never asked about again only ever hold the text
+            parsed_now = Some(Arc::new(text.parse(&target_url)));
+            CachedRobots::raw(text, target_url.clone())
+        });
+        (fetched, resolved_address)
+    } else {
+        let (fetched, resolved_address) = with_remote_address(fetcher.fetch(&target_url)).await;
+        let fetched = fetched.map(|data| {
+            info!(
+                status_code = data.http_status_code,
+                content_length = data.content_length_bytes,
+                "Successfully fetched robots.txt"
+            );
+            data.into()
+        });
+        (fetched, resolved_address)
+    };
+    let entry = match fetched {
+        Ok(entry) => entry,
+        Err(FetchError::Unavailable(s)) => {
+            info!(status_code = s, "robots.txt unavailable");
+            RobotsData {
+                target_url,
+                robots_txt_url: robots_url.clone(),
+                fetched_at: Some(fetcher.clock().now()),
+                resolved_address,
+                access_result: AccessResult::Unavailable,
+                http_status_code: s as u32,
+                ..Default::default()
+            }
+            .into()
+        }
+        Err(FetchError::RateLimited(retry_after)) => {
+            info!(?retry_after, "robots.txt rate limited");
+            RobotsData {
+                target_url,
+                robots_txt_url: robots_url.clone(),
+                fetched_at: Some(fetcher.clock().now()),
+                resolved_address,
+                access_result: AccessResult::Unavailable,
+                http_status_code: 429,
+                max_age: retry_after,
+                ..Default::default()
+            }
+            .into()
+        }
+        Err(e @ (FetchError::TooManyRedirects | FetchError::RedirectLoop)) => {
+            info!(error = %e, "robots.txt redirects could not be followed");
+            let reason = match e {
+                FetchError::RedirectLoop => "REDIRECT_LOOP",
+                _ => "TOO_MANY_REDIRECTS",
+            };
+            // RFC 9309 Section 2.3.1.2: treated as unavailable
+            RobotsData {
+                target_url,
+                robots_txt_url: robots_url.clone(),
+                fetched_at: Some(fetcher.clock().now()),
+                resolved_address,
+                access_result: AccessResult::TooManyRedirects,
+                failure_reason: Some(reason.to_string()),
+                ..Default::default()
+            }
+            .into()
+        }
+        Err(FetchError::Unreachable(e)) => {
+            info!(error = %e.0, status = e.1, "robots.txt unreachable");
+            let s = e.1.unwrap_or(0);
+            RobotsData {
+                target_url,
+                robots_txt_url: robots_url.clone(),
+                fetched_at: Some(fetcher.clock().now()),
+                resolved_address,
+                access_result: AccessResult::Unreachable,
+                http_status_code: s as u32,
+                ..Default::default()
+            }
+            .into()
+        }
+        Err(FetchError::Timeout) => {
+            info!("Request timeout");
+            RobotsData {
+                target_url,
+                robots_txt_url: robots_url.clone(),
+                fetched_at: Some(fetcher.clock().now()),
+                resolved_address,
+                access_result: AccessResult::Unreachable,
+                ..Default::default()
+            }
+            .into()
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to fetch robots.txt");
+            return Err(Status::internal(e.to_string()));
+        }
+    };
+    let data = parsed_now.unwrap_or_else(|| entry.data());
+    let ttl = config.entry_ttl(&data);
+    debug!(?ttl, "Caching robots.txt");
+    last_known.insert(cache_key.clone(), entry.clone()).await;
+    if let Err(e) = cache.set_with_ttl(cache_key, entry, ttl).await {
+        warn!(error = %e, "Failed to cache robots.txt data");
+    }
+    Ok(data)
+}
+
+/// The priority a caller asked its fetches to be queued at with
+/// `x-fetch-priority` metadata. Calls without it, or naming no known
+/// priority, are interactive.
+fn fetch_priority(metadata: &MetadataMap) -> Priority {
+    metadata
+        .get("x-fetch-priority")
+        .and_then(|value| value.to_str().ok())
+        .and_then(Priority::from_name)
+        .unwrap_or_default()
+}
+
+/// The caller's deadline, from the `grpc-timeout` header tonic sends for
+/// `Request::set_timeout`.
+fn grpc_timeout(metadata: &MetadataMap) -> Option<Duration> {
+    let value = metadata.get("grpc-timeout")?.to_str().ok()?;
+    let (amount, unit) = value.split_at(value.len().checked_sub(1)?);
+    let amount: u64 = amount.parse().ok()?;
+    Some(match unit {
+        "H" => Duration::from_secs(amount.saturating_mul(60 * 60)),
+        "M" => Duration::from_secs(amount.saturating_mul(60)),
+        "S" => Duration::from_secs(amount),
+        "m" => Duration::from_millis(amount),
+        "u" => Duration::from_micros(amount),
+        "n" => Duration::from_nanos(amount),
+        _ => return None,
+    })
+}
+
+fn header_cache(config: ConfigHandle) -> MokaCacheImpl<String, XRobotsTagHeaders> {
+    MokaCacheImpl::builder()
+        .max_capacity(MAX_HEADER_LOOKUPS)
+        .expire_after(ConfiguredTtl::new(config, RuntimeConfig::header_cache_ttl))
+        .build()
+}
+
+fn header_fetch_status(e: FetchError) -> Status {
+    match e {
+        FetchError::InvalidUrl(_) => Status::invalid_argument(e.to_string()),
+        FetchError::Timeout => Status::deadline_exceeded(e.to_string()),
+        _ => Status::unavailable(e.to_string()),
+    }
+}
+
+fn headers_response(
+    url: String,
+    headers: XRobotsTagHeaders,
+    user_agent: &str,
+) -> CheckRobotsHeadersResponse {
+    let directives = parse_x_robots_tag(headers.values.iter().map(String::as_str), user_agent);
+    CheckRobotsHeadersResponse {
+        url,
+        http_status_code: headers.http_status_code,
+        header_present: directives.header_present,
+        noindex: directives.noindex,
+        nofollow: directives.nofollow,
+        none: directives.none,
+        unavailable_after: directives
+            .unavailable_after
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64),
+        directives: directives.directives,
+    }
 }
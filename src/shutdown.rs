@@ -0,0 +1,135 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use futures_util::stream::FuturesUnordered;
+use tokio::sync::{oneshot, watch};
+use tracing::{error, info, warn};
+
+/// Resolves when the process receives SIGTERM or SIGINT.
+pub async fn termination() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = terminate.recv() => info!("Received SIGTERM"),
+                    () = interrupt() => {}
+                }
+                return;
+            }
+            Err(e) => warn!(error = %e, "Failed to listen for SIGTERM"),
+        }
+    }
+    interrupt().await;
+}
+
+/// Resolves on SIGINT (Ctrl-C), or never if it cannot be listened for.
+async fn interrupt() {
+    match tokio::signal::ctrl_c().await {
+        Ok(()) => info!("Received SIGINT"),
+        Err(e) => {
+            warn!(error = %e, "Failed to listen for SIGINT");
+            std::future::pending().await
+        }
+    }
+}
+
+/// Run the server `serve` builds until `signal` resolves, then let the
+/// requests in flight finish for up to `grace`. `serve` is given the future
+/// to pass to `serve_with_shutdown`, which stops accepting connections when
+/// it resolves. Requests still running when `grace` is up are dropped. The
+/// robots.txt fetches they were waiting on run on their own, so stop them
+/// with [`RobotsServer::cancel_fetches`](crate::service::RobotsServer::cancel_fetches)
+/// before saving or closing the cache.
+pub async fn serve_with_grace<F, E>(
+    serve: impl FnOnce(BoxFuture<'static, ()>) -> F,
+    signal: impl Future<Output = ()> + Send + 'static,
+    grace: Duration,
+) -> Result<(), E>
+where
+    F: Future<Output = Result<(), E>>,
+{
+    let (stop, stopping) = watch::channel(false);
+    let mut server_stopping = stopping.clone();
+    tokio::spawn(async move {
+        signal.await;
+        info!(
+            grace_ms = grace.as_millis() as u64,
+            "Shutting down, draining requests in flight"
+        );
+        let _ = stop.send(true);
+    });
+    let server = serve(Box::pin(async move {
+        let _ = server_stopping.wait_for(|stopping| *stopping).await;
+    }));
+    let deadline = async move {
+        let mut stopping = stopping;
+        if stopping.wait_for(|stopping| *stopping).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+        tokio::time::sleep(grace).await;
+    };
+    tokio::select! {
+        result = server => result,
+        () = deadline => {
+            warn!("Shutdown grace period over, dropping requests in flight");
+            Ok(())
+        }
+    }
+}
+
+/// Run the servers `serve` builds, one per listener, as [`serve_with_grace`]
+/// does, passing each a clone of the shutdown future. When one of them
+/// fails, the others shut down as if `signal` had resolved, and its error is
+/// returned once they have.
+pub async fn serve_all_with_grace<F, E>(
+    serve: impl FnOnce(Shared<BoxFuture<'static, ()>>) -> Vec<F>,
+    signal: impl Future<Output = ()> + Send + 'static,
+    grace: Duration,
+) -> Result<(), E>
+where
+    F: Future<Output = Result<(), E>>,
+    E: Display,
+{
+    let (failed, on_failure) = oneshot::channel::<()>();
+    let signal = async move {
+        tokio::select! {
+            () = signal => {}
+            Ok(()) = on_failure => {}
+        }
+    };
+    let mut first_error = None;
+    serve_with_grace(
+        |stop| {
+            let mut servers = serve(stop.shared())
+                .into_iter()
+                .collect::<FuturesUnordered<_>>();
+            let first_error = &mut first_error;
+            let mut failed = Some(failed);
+            async move {
+                while let Some(result) = servers.next().await {
+                    if let Err(e) = result {
+                        error!(error = %e, "Listener failed, shutting down the others");
+                        if let Some(failed) = failed.take() {
+                            let _ = failed.send(());
+                        }
+                        first_error.get_or_insert(e);
+                    }
+                }
+                Ok::<(), E>(())
+            }
+        },
+        signal,
+        grace,
+    )
+    .await?;
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,142 @@
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, instrument, warn};
+
+use crate::cache::{Cache, CacheError};
+use crate::cached_robots::CachedRobots;
+use crate::stored_robots::StoredEntry;
+
+/// Version of the snapshot format. Snapshots of any other version are
+/// discarded when restoring.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Snapshot I/O failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("Snapshot could not be encoded: {0}")]
+    Encode(#[from] serde_json::Error),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+}
+
+/// The cached robots.txt entries, as written to disk.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    /// When the snapshot was taken, which the entries' TTLs count from.
+    saved_at: SystemTime,
+    entries: Vec<SnapshotEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+    key: String,
+    /// Time the entry had left when the snapshot was taken, `None` if it
+    /// never expires.
+    ttl: Option<Duration>,
+    robots: StoredEntry,
+}
+
+/// Just the version, read before the rest so that a snapshot in another
+/// format is recognised as such rather than failing to decode.
+#[derive(Deserialize)]
+struct SnapshotVersion {
+    version: u32,
+}
+
+/// Write every entry in `cache`, with the time it has left, to `path`. The
+/// file is replaced at once, so an interrupted save leaves the previous
+/// snapshot in place. Returns how many entries were saved.
+#[instrument(skip(cache), fields(path = %path.display()))]
+pub async fn save<T: Cache<String, CachedRobots>>(
+    cache: &T,
+    path: &Path,
+) -> Result<usize, SnapshotError> {
+    let entries = cache
+        .entries()
+        .await?
+        .into_iter()
+        .map(|(key, value, ttl)| SnapshotEntry {
+            key,
+            ttl,
+            robots: StoredEntry::from(&value),
+        })
+        .collect::<Vec<_>>();
+    let saved = entries.len();
+    let snapshot = Snapshot {
+        version: SNAPSHOT_VERSION,
+        saved_at: SystemTime::now(),
+        entries,
+    };
+    let json = serde_json::to_vec(&snapshot)?;
+    let partial = path.with_extension("partial");
+    tokio::fs::write(&partial, json).await?;
+    tokio::fs::rename(&partial, path).await?;
+    info!(saved, "Saved cache snapshot");
+    Ok(saved)
+}
+
+/// Store the entries of the snapshot at `path` in `cache`, each for the
+/// time it had left less the time since the snapshot was taken, or
+/// [pinned](Cache::pin) if it had no expiry. Entries that have run out are
+/// skipped. A missing snapshot restores nothing, and one that cannot be read
+/// as the current version is discarded with a warning. Returns how many
+/// entries were restored.
+#[instrument(skip(cache), fields(path = %path.display()))]
+pub async fn restore<T: Cache<String, CachedRobots>>(
+    cache: &T,
+    path: &Path,
+) -> Result<usize, SnapshotError> {
+    let json = match tokio::fs::read(path).await {
+        Ok(json) => json,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            info!("No cache snapshot to restore");
+            return Ok(0);
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let snapshot = match serde_json::from_slice::<SnapshotVersion>(&json) {
+        Ok(SnapshotVersion {
+            version: SNAPSHOT_VERSION,
+        }) => serde_json::from_slice::<Snapshot>(&json),
+        Ok(SnapshotVersion { version }) => {
+            warn!(
+                version,
+                expected = SNAPSHOT_VERSION,
+                "Discarding cache snapshot of another version"
+            );
+            return Ok(0);
+        }
+        Err(e) => Err(e),
+    };
+    let snapshot = match snapshot {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            warn!(error = %e, "Discarding unreadable cache snapshot");
+            return Ok(0);
+        }
+    };
+    let age = snapshot.saved_at.elapsed().unwrap_or_default();
+    let (mut restored, mut expired) = (0, 0);
+    for entry in snapshot.entries {
+        let value = CachedRobots::from(entry.robots);
+        match entry.ttl {
+            Some(ttl) if ttl <= age => expired += 1,
+            Some(ttl) => {
+                cache.set_with_ttl(entry.key, value, ttl - age).await?;
+                restored += 1;
+            }
+            None => {
+                cache.pin(entry.key, value).await?;
+                restored += 1;
+            }
+        }
+    }
+    info!(restored, expired, "Restored cache snapshot");
+    Ok(restored)
+}
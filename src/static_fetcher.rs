@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::{debug, instrument};
+
+use crate::clock::{self, Clock};
+use crate::config::ConfigHandle;
+use crate::fetcher::{
+    CappedBody, FetchError, FetchStats, Fetcher, InFlight, RobotsFetcher, RobotsText,
+};
+use crate::redact;
+use crate::robots_data::RobotsData;
+use crate::target::NormalizedTarget;
+use crate::x_robots_tag::XRobotsTagHeaders;
+
+/// A [`Fetcher`] that serves robots.txt from files in a corpus directory,
+/// `<host>.robots.txt` for each host, whatever the scheme and port. Bodies
+/// are cut at `max_robots_txt_bytes` as fetched ones are. Hosts without a
+/// file are 404, or fetched by the fallback fetcher when there is one.
+#[derive(Clone)]
+pub struct StaticFetcher<F: Fetcher = RobotsFetcher> {
+    corpus: PathBuf,
+    fallback: Option<F>,
+    config: ConfigHandle,
+    stats: Arc<FetchStats>,
+    clock: Arc<dyn Clock>,
+}
+
+impl StaticFetcher {
+    /// Serve only what is in `corpus`, without any network access.
+    pub fn new(corpus: impl Into<PathBuf>, config: ConfigHandle) -> Self {
+        Self {
+            corpus: corpus.into(),
+            fallback: None,
+            config,
+            stats: Arc::default(),
+            clock: clock::system(),
+        }
+    }
+
+    /// Fetch hosts without a file in the corpus with `fallback`, along with
+    /// every `X-Robots-Tag` check.
+    pub fn with_fallback<G: Fetcher>(self, fallback: G) -> StaticFetcher<G> {
+        StaticFetcher {
+            corpus: self.corpus,
+            fallback: Some(fallback.with_clock(self.clock.clone())),
+            config: self.config,
+            stats: self.stats,
+            clock: self.clock,
+        }
+    }
+}
+
+impl<F: Fetcher> StaticFetcher<F> {
+    pub fn corpus(&self) -> &Path {
+        &self.corpus
+    }
+
+    /// The file `target`'s robots.txt is read from.
+    fn path(&self, target: &NormalizedTarget) -> PathBuf {
+        self.corpus.join(format!("{}.robots.txt", target.host))
+    }
+
+    /// The corpus file for `target_url`, or `None` when it has none.
+    async fn read(&self, target_url: &str) -> Result<Option<RobotsText>, FetchError> {
+        let target = NormalizedTarget::parse(target_url)?;
+        let path = self.path(&target);
+        let bytes = match tokio::fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                debug!(path = %path.display(), "No robots.txt in corpus");
+                return Ok(None);
+            }
+            Err(e) => {
+                debug!(path = %path.display(), error = %e, "Failed to read robots.txt from corpus");
+                return Err(FetchError::Unreachable((e.to_string(), None)));
+            }
+        };
+        let mut reader = CappedBody::with_limit(self.config.get().max_robots_txt_bytes)
+            .with_content_length(bytes.len() as u64);
+        reader.push(&bytes);
+        let (body, truncated) = reader.finish();
+        debug!(path = %path.display(), truncated, "Read robots.txt from corpus");
+        Ok(Some(RobotsText {
+            robots_txt_url: target.robots_key,
+            http_status_code: 200,
+            content_length_bytes: bytes.len() as u64,
+            body,
+            truncated,
+            fetched_at: self.clock.now(),
+            http_version: "static".to_string(),
+            upstream_headers: BTreeMap::new(),
+            max_age: None,
+            resolved_address: None,
+        }))
+    }
+}
+
+#[async_trait]
+impl<F: Fetcher> Fetcher for StaticFetcher<F> {
+    #[instrument(skip(self), fields(target_url = %redact::url(target_url)))]
+    async fn fetch(&self, target_url: &str) -> Result<RobotsData, FetchError> {
+        let _in_flight = InFlight::start(&self.stats);
+        match self.read(target_url).await? {
+            Some(text) => Ok(text.parse(target_url)),
+            None => match &self.fallback {
+                Some(fallback) => fallback.fetch(target_url).await,
+                None => Err(FetchError::Unavailable(404)),
+            },
+        }
+    }
+
+    #[instrument(skip(self), fields(target_url = %redact::url(target_url)))]
+    async fn fetch_text(&self, target_url: &str) -> Result<RobotsText, FetchError> {
+        let _in_flight = InFlight::start(&self.stats);
+        match self.read(target_url).await? {
+            Some(text) => Ok(text),
+            None => match &self.fallback {
+                Some(fallback) => fallback.fetch_text(target_url).await,
+                None => Err(FetchError::Unavailable(404)),
+            },
+        }
+    }
+
+    /// Pages are not in the corpus, so without a fallback they are served
+    /// with no `X-Robots-Tag` headers.
+    async fn fetch_x_robots_tag(&self, target_url: &str) -> Result<XRobotsTagHeaders, FetchError> {
+        match &self.fallback {
+            Some(fallback) => fallback.fetch_x_robots_tag(target_url).await,
+            None => {
+                NormalizedTarget::parse(target_url)?;
+                Ok(XRobotsTagHeaders {
+                    http_status_code: 200,
+                    values: Vec::new(),
+                })
+            }
+        }
+    }
+
+    fn stats(&self) -> &FetchStats {
+        &self.stats
+    }
+
+    fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.fallback = self
+            .fallback
+            .map(|fallback| fallback.with_clock(clock.clone()));
+        self.clock = clock;
+        self
+    }
+}
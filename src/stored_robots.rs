@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cached_robots::CachedRobots;
+use crate::fetcher::RobotsText;
+use crate::robots_data::{AccessResult, Group, ParseWarning, RobotsData, Rule, RuleType};
+
+/// A cache entry as stored outside the process: parsed, or for one not
+/// parsed yet, its fetched text, so that storing it does not parse it.
+/// Entries stored before raw ones could be read as parsed.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StoredEntry {
+    Raw { target_url: String, raw: RobotsText },
+    Parsed(StoredRobots),
+}
+
+impl From<&CachedRobots> for StoredEntry {
+    fn from(value: &CachedRobots) -> Self {
+        match (value.raw_target_url(), value.raw_text()) {
+            (Some(target_url), Some(raw)) => Self::Raw {
+                target_url: target_url.to_string(),
+                raw,
+            },
+            // Parsed already, so data() costs nothing
+            _ => Self::Parsed(StoredRobots::from(&*value.data())),
+        }
+    }
+}
+
+impl From<StoredEntry> for CachedRobots {
+    fn from(stored: StoredEntry) -> Self {
+        match stored {
+            StoredEntry::Raw { target_url, raw } => CachedRobots::raw(raw, target_url),
+            StoredEntry::Parsed(stored) => CachedRobots::from(RobotsData::from(stored)),
+        }
+    }
+}
+
+/// [`RobotsData`] as stored outside the process, in Redis or a cache
+/// snapshot. Rules are kept as written and compiled again when read.
+#[derive(Serialize, Deserialize)]
+pub struct StoredRobots {
+    target_url: String,
+    robots_txt_url: String,
+    access_result: AccessResult,
+    http_status_code: u32,
+    groups: Vec<StoredGroup>,
+    sitemaps: Vec<String>,
+    sitemap_line_numbers: Vec<u32>,
+    content_length_bytes: u64,
+    truncated: bool,
+    warnings: Vec<ParseWarning>,
+    checksum: String,
+    fetched_at: Option<SystemTime>,
+    http_version: Option<String>,
+    upstream_headers: BTreeMap<String, String>,
+    resolved_address: Option<SocketAddr>,
+    failure_reason: Option<String>,
+    max_age: Option<Duration>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredGroup {
+    user_agents: Vec<String>,
+    rules: Vec<StoredRule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredRule {
+    rule_type: RuleType,
+    path_pattern: String,
+    line_number: u32,
+}
+
+impl From<&RobotsData> for StoredRobots {
+    fn from(data: &RobotsData) -> Self {
+        // Destructured so a new field cannot be left out unnoticed
+        let RobotsData {
+            target_url,
+            robots_txt_url,
+            access_result,
+            http_status_code,
+            groups,
+            sitemaps,
+            sitemap_line_numbers,
+            content_length_bytes,
+            truncated,
+            warnings,
+            checksum,
+            fetched_at,
+            http_version,
+            upstream_headers,
+            resolved_address,
+            failure_reason,
+            max_age,
+        } = data;
+        let groups = groups
+            .iter()
+            .map(|group| StoredGroup {
+                user_agents: group.user_agents.iter().map(|ua| ua.to_string()).collect(),
+                rules: group
+                    .rules
+                    .iter()
+                    .map(|rule| StoredRule {
+                        rule_type: rule.rule_type,
+                        path_pattern: rule.path_pattern.to_string(),
+                        line_number: rule.line_number,
+                    })
+                    .collect(),
+                crawl_delay: group.crawl_delay,
+            })
+            .collect();
+        Self {
+            target_url: target_url.clone(),
+            robots_txt_url: robots_txt_url.clone(),
+            access_result: *access_result,
+            http_status_code: *http_status_code,
+            groups,
+            sitemaps: sitemaps.clone(),
+            sitemap_line_numbers: sitemap_line_numbers.clone(),
+            content_length_bytes: *content_length_bytes,
+            truncated: *truncated,
+            warnings: warnings.clone(),
+            checksum: checksum.clone(),
+            fetched_at: *fetched_at,
+            http_version: http_version.clone(),
+            upstream_headers: upstream_headers.clone(),
+            resolved_address: *resolved_address,
+            failure_reason: failure_reason.clone(),
+            max_age: *max_age,
+        }
+    }
+}
+
+impl From<StoredRobots> for RobotsData {
+    fn from(stored: StoredRobots) -> Self {
+        let groups = stored
+            .groups
+            .into_iter()
+            .map(|group| {
+                let rules = group
+                    .rules
+                    .into_iter()
+                    .map(|rule| {
+                        Rule::new(rule.rule_type, rule.path_pattern)
+                            .with_line_number(rule.line_number)
+                    })
+                    .collect();
+                Group::new(group.user_agents, rules, group.crawl_delay)
+            })
+            .collect();
+        Self {
+            target_url: stored.target_url,
+            robots_txt_url: stored.robots_txt_url,
+            access_result: stored.access_result,
+            http_status_code: stored.http_status_code,
+            groups,
+            sitemaps: stored.sitemaps,
+            sitemap_line_numbers: stored.sitemap_line_numbers,
+            content_length_bytes: stored.content_length_bytes,
+            truncated: stored.truncated,
+            warnings: stored.warnings,
+            checksum: stored.checksum,
+            fetched_at: stored.fetched_at,
+            http_version: stored.http_version,
+            upstream_headers: stored.upstream_headers,
+            resolved_address: stored.resolved_address,
+            failure_reason: stored.failure_reason,
+            max_age: stored.max_age,
+        }
+    }
+}
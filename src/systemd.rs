@@ -0,0 +1,66 @@
+use std::io;
+use std::ops::Range;
+use std::os::fd::{FromRawFd, OwnedFd, RawFd};
+
+use sd_notify::NotifyState;
+use tokio::net::TcpListener;
+use tracing::warn;
+
+/// The first descriptor systemd passes, after stdin, stdout, and stderr.
+const LISTEN_FDS_START: RawFd = 3;
+
+/// The sockets systemd passed to this process for socket activation, as
+/// described by `LISTEN_PID` and `LISTEN_FDS`. Empty when the process was
+/// not socket-activated. Call at most once, as the descriptors are taken.
+pub fn listen_fds() -> Vec<OwnedFd> {
+    let listen_pid = std::env::var("LISTEN_PID").ok();
+    let listen_fds = std::env::var("LISTEN_FDS").ok();
+    let fds = passed_fds(
+        listen_pid.as_deref(),
+        listen_fds.as_deref(),
+        std::process::id(),
+    );
+    // SAFETY: systemd passed these descriptors open for this process to own,
+    // and nothing else in it has taken them
+    fds.map(|fd| unsafe { OwnedFd::from_raw_fd(fd) }).collect()
+}
+
+/// The descriptors `LISTEN_FDS` counts, if `LISTEN_PID` is `pid`. The
+/// variables are inherited by child processes, which must not take
+/// descriptors meant for their parent.
+pub fn passed_fds(listen_pid: Option<&str>, listen_fds: Option<&str>, pid: u32) -> Range<RawFd> {
+    let count = match (listen_pid, listen_fds) {
+        (Some(listen_pid), Some(listen_fds)) if listen_pid.parse() == Ok(pid) => {
+            listen_fds.parse().unwrap_or(0)
+        }
+        _ => 0,
+    };
+    LISTEN_FDS_START..LISTEN_FDS_START + count
+}
+
+/// Listeners for the TCP sockets in `fds`, for `serve_with_incoming`. A
+/// descriptor that is not a TCP socket is an error. Must be called from
+/// within a tokio runtime.
+pub fn tcp_listeners(fds: Vec<OwnedFd>) -> io::Result<Vec<TcpListener>> {
+    fds.into_iter()
+        .map(|fd| {
+            let listener = std::net::TcpListener::from(fd);
+            listener.local_addr().map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("Socket passed by systemd is not a TCP socket: {e}"),
+                )
+            })?;
+            listener.set_nonblocking(true)?;
+            TcpListener::from_std(listener)
+        })
+        .collect()
+}
+
+/// Tell systemd the server is ready, for `Type=notify` units. Does nothing
+/// when not run by systemd.
+pub fn notify_ready() {
+    if let Err(e) = sd_notify::notify(false, &[NotifyState::Ready]) {
+        warn!(error = %e, "Failed to notify systemd of readiness");
+    }
+}
@@ -0,0 +1,111 @@
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+/// Longest URL accepted, in bytes, before any parsing is attempted.
+pub const MAX_URL_LENGTH: usize = 2048;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum TargetError {
+    #[error("URL is empty")]
+    Empty,
+    #[error("URL is {length} bytes, longer than the {max} byte limit")]
+    TooLong { length: usize, max: usize },
+    #[error("Failed to parse URL: {0}")]
+    Malformed(String),
+    #[error("Unsupported scheme: {0}")]
+    UnsupportedScheme(String),
+    #[error("URL has no host")]
+    MissingHost,
+}
+
+impl TargetError {
+    /// Stable machine-readable name for the failure, sent to clients
+    /// alongside the message.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            TargetError::Empty => "URL_EMPTY",
+            TargetError::TooLong { .. } => "URL_TOO_LONG",
+            TargetError::Malformed(_) => "URL_MALFORMED",
+            TargetError::UnsupportedScheme(_) => "URL_UNSUPPORTED_SCHEME",
+            TargetError::MissingHost => "URL_MISSING_HOST",
+        }
+    }
+}
+
+/// A validated http(s) URL and the pieces robots.txt evaluation needs.
+///
+/// Hosts are lowercased and IDNA-encoded, default ports are dropped, and
+/// userinfo and fragments are removed, so equivalent URLs share one
+/// `robots_key`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NormalizedTarget {
+    /// The robots.txt URL for the target's origin, used as the cache key.
+    pub robots_key: String,
+    /// The normalized target URL.
+    pub target_url: String,
+    /// Path and query matched against robots.txt rules.
+    pub path_and_query: String,
+    /// The normalized host, without port.
+    pub host: String,
+}
+
+impl NormalizedTarget {
+    pub fn parse(url: &str) -> Result<Self, TargetError> {
+        let url = url.trim();
+        if url.is_empty() {
+            return Err(TargetError::Empty);
+        }
+        if url.len() > MAX_URL_LENGTH {
+            return Err(TargetError::TooLong {
+                length: url.len(),
+                max: MAX_URL_LENGTH,
+            });
+        }
+        let mut parsed = Url::parse(url).map_err(|e| {
+            debug!(error = %e, "Invalid url");
+            TargetError::Malformed(e.to_string())
+        })?;
+        let scheme = parsed.scheme().to_string();
+        if scheme != "http" && scheme != "https" {
+            debug!(%scheme, "Unsupported scheme");
+            return Err(TargetError::UnsupportedScheme(scheme));
+        }
+        let host = parsed
+            .host_str()
+            .filter(|host| !host.is_empty())
+            .ok_or(TargetError::MissingHost)?
+            .to_string();
+        // Both only fail for URLs that cannot have credentials, which
+        // http(s) URLs with a host always can
+        let _ = parsed.set_username("");
+        let _ = parsed.set_password(None);
+        parsed.set_fragment(None);
+
+        // `Url` already omits the port when it is the scheme default
+        let robots_key = match parsed.port() {
+            Some(port) => format!("{scheme}://{host}:{port}/robots.txt"),
+            None => format!("{scheme}://{host}/robots.txt"),
+        };
+        let path_and_query = match parsed.query() {
+            Some(query) => format!("{}?{query}", parsed.path()),
+            None => parsed.path().to_string(),
+        };
+
+        Ok(Self {
+            robots_key,
+            target_url: parsed.into(),
+            path_and_query,
+            host,
+        })
+    }
+
+    /// The same URL on `host`, for a host that shares another's robots.txt.
+    pub fn on_host(&self, host: &str) -> Result<Self, TargetError> {
+        let mut url =
+            Url::parse(&self.target_url).map_err(|e| TargetError::Malformed(e.to_string()))?;
+        url.set_host(Some(host))
+            .map_err(|e| TargetError::Malformed(e.to_string()))?;
+        Self::parse(url.as_str())
+    }
+}
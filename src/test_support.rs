@@ -0,0 +1,586 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::{Channel, Endpoint, Server};
+
+use crate::cache::{Cache, CacheResult, CacheStats};
+use crate::cached_robots::CachedRobots;
+use crate::client::RobotsClient;
+use crate::clock::{self, Clock};
+use crate::fetcher::{FetchError, FetchStats, Fetcher, InFlight, RobotsText};
+use crate::robots_data::{AccessResult, Group, RobotsData, Rule, RuleType};
+use crate::service::RobotsServer;
+use crate::service::robots::v1::robots_service_client::RobotsServiceClient;
+use crate::service::robots::v1::robots_service_server::RobotsServiceServer;
+use crate::target::NormalizedTarget;
+use crate::trace_context::TraceContextLayer;
+use crate::x_robots_tag::XRobotsTagHeaders;
+
+/// An allow rule for `path_pattern`.
+pub fn allow(path_pattern: &str) -> Rule {
+    Rule::new(RuleType::Allow, path_pattern)
+}
+
+/// A disallow rule for `path_pattern`.
+pub fn disallow(path_pattern: &str) -> Rule {
+    Rule::new(RuleType::Disallow, path_pattern)
+}
+
+/// Builds a [`Group`] one line at a time.
+#[derive(Clone, Debug, Default)]
+pub struct GroupBuilder {
+    user_agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl GroupBuilder {
+    /// A group for `user_agent`.
+    pub fn new(user_agent: &str) -> Self {
+        Self {
+            user_agents: vec![user_agent.to_string()],
+            ..Default::default()
+        }
+    }
+
+    /// Also apply the group to `user_agent`.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agents.push(user_agent.to_string());
+        self
+    }
+
+    pub fn allow(mut self, path_pattern: &str) -> Self {
+        self.rules.push(allow(path_pattern));
+        self
+    }
+
+    pub fn disallow(mut self, path_pattern: &str) -> Self {
+        self.rules.push(disallow(path_pattern));
+        self
+    }
+
+    pub fn crawl_delay(mut self, delay: Duration) -> Self {
+        self.crawl_delay = Some(delay);
+        self
+    }
+
+    pub fn build(self) -> Group {
+        Group::new(self.user_agents, self.rules, self.crawl_delay)
+    }
+}
+
+/// Builds [`RobotsData`] as if a robots.txt with the given lines had been
+/// fetched successfully. Rule and delay lines apply to the group most
+/// recently started with [`group`](Self::group), or to a `*` group when none
+/// has been.
+#[derive(Clone, Debug)]
+pub struct RobotsDataBuilder {
+    groups: Vec<GroupBuilder>,
+    sitemaps: Vec<String>,
+    access_result: AccessResult,
+    http_status_code: u32,
+}
+
+impl RobotsDataBuilder {
+    pub fn new() -> Self {
+        Self {
+            groups: Vec::new(),
+            sitemaps: Vec::new(),
+            access_result: AccessResult::Success,
+            http_status_code: 200,
+        }
+    }
+
+    /// Start a group for `user_agent`.
+    pub fn group(mut self, user_agent: &str) -> Self {
+        self.groups.push(GroupBuilder::new(user_agent));
+        self
+    }
+
+    /// Also apply the current group to `user_agent`.
+    pub fn user_agent(self, user_agent: &str) -> Self {
+        self.with_group(|group| group.user_agent(user_agent))
+    }
+
+    pub fn allow(self, path_pattern: &str) -> Self {
+        self.with_group(|group| group.allow(path_pattern))
+    }
+
+    pub fn disallow(self, path_pattern: &str) -> Self {
+        self.with_group(|group| group.disallow(path_pattern))
+    }
+
+    pub fn crawl_delay(self, delay: Duration) -> Self {
+        self.with_group(|group| group.crawl_delay(delay))
+    }
+
+    pub fn sitemap(mut self, url: &str) -> Self {
+        self.sitemaps.push(url.to_string());
+        self
+    }
+
+    /// Record the fetch as having ended with `access_result` and
+    /// `http_status_code` rather than a 200.
+    pub fn outcome(mut self, access_result: AccessResult, http_status_code: u32) -> Self {
+        self.access_result = access_result;
+        self.http_status_code = http_status_code;
+        self
+    }
+
+    pub fn build(self) -> RobotsData {
+        let mut data = RobotsData {
+            access_result: self.access_result,
+            http_status_code: self.http_status_code,
+            groups: self.groups.into_iter().map(GroupBuilder::build).collect(),
+            sitemaps: self.sitemaps,
+            ..Default::default()
+        };
+        let body = String::from(&data);
+        data.content_length_bytes = body.len() as u64;
+        data.checksum = format!("{:x}", Sha256::digest(body.as_bytes()));
+        data
+    }
+
+    fn with_group(mut self, f: impl FnOnce(GroupBuilder) -> GroupBuilder) -> Self {
+        let group = self.groups.pop().unwrap_or_else(|| GroupBuilder::new("*"));
+        self.groups.push(f(group));
+        self
+    }
+}
+
+impl Default for RobotsDataBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`Fetcher`] that answers from programmed responses instead of origins.
+/// Responses are keyed by host, so every URL on a host gets the same
+/// robots.txt. Hosts without one answer as if robots.txt were missing, with
+/// a 404. Clones share the responses and the record of what was fetched, so
+/// a test can keep one and hand another to the server.
+#[derive(Clone)]
+pub struct FakeFetcher {
+    state: Arc<Mutex<FakeState>>,
+    stats: Arc<FetchStats>,
+    clock: Arc<dyn Clock>,
+}
+
+#[derive(Default)]
+struct FakeState {
+    robots: HashMap<String, Result<RobotsData, FetchError>>,
+    headers: HashMap<String, XRobotsTagHeaders>,
+    fetched: Vec<String>,
+}
+
+impl FakeFetcher {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::default(),
+            stats: Arc::default(),
+            clock: clock::system(),
+        }
+    }
+
+    /// Serve `data` as the robots.txt of `host`.
+    pub fn serve(&self, host: &str, data: RobotsData) {
+        self.state().robots.insert(host.to_lowercase(), Ok(data));
+    }
+
+    /// Fail fetches of robots.txt from `host` with `error`.
+    pub fn fail(&self, host: &str, error: FetchError) {
+        self.state().robots.insert(host.to_lowercase(), Err(error));
+    }
+
+    /// Serve `values` as the `X-Robots-Tag` headers of every URL on `host`.
+    pub fn serve_headers(&self, host: &str, values: &[&str]) {
+        let headers = XRobotsTagHeaders {
+            http_status_code: 200,
+            values: values.iter().map(|value| value.to_string()).collect(),
+        };
+        self.state().headers.insert(host.to_lowercase(), headers);
+    }
+
+    /// The robots.txt URLs fetched so far, in order.
+    pub fn fetched(&self) -> Vec<String> {
+        self.state().fetched.clone()
+    }
+
+    /// How many times robots.txt was fetched from `host`.
+    pub fn fetch_count(&self, host: &str) -> usize {
+        let host = host.to_lowercase();
+        self.state()
+            .fetched
+            .iter()
+            .filter(|url| NormalizedTarget::parse(url).is_ok_and(|target| target.host == host))
+            .count()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, FakeState> {
+        self.state.lock().expect("fake fetcher lock poisoned")
+    }
+}
+
+impl Default for FakeFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Fetcher for FakeFetcher {
+    async fn fetch(&self, target_url: &str) -> Result<RobotsData, FetchError> {
+        let target = NormalizedTarget::parse(target_url)?;
+        let _in_flight = InFlight::start(&self.stats);
+        let mut state = self.state();
+        state.fetched.push(target.robots_key.clone());
+        let mut data = match state.robots.get(&target.host) {
+            Some(response) => response.clone()?,
+            None => return Err(FetchError::Unavailable(404)),
+        };
+        data.target_url = target_url.to_string();
+        data.robots_txt_url = target.robots_key;
+        data.fetched_at = Some(self.clock.now());
+        Ok(data)
+    }
+
+    /// The programmed robots.txt written back out as text.
+    async fn fetch_text(&self, target_url: &str) -> Result<RobotsText, FetchError> {
+        let data = self.fetch(target_url).await?;
+        let body = String::from(&data);
+        Ok(RobotsText {
+            robots_txt_url: data.robots_txt_url,
+            http_status_code: data.http_status_code,
+            content_length_bytes: body.len() as u64,
+            body,
+            truncated: data.truncated,
+            fetched_at: self.clock.now(),
+            http_version: "HTTP/1.1".to_string(),
+            upstream_headers: data.upstream_headers,
+            max_age: data.max_age,
+            resolved_address: data.resolved_address,
+        })
+    }
+
+    async fn fetch_x_robots_tag(&self, target_url: &str) -> Result<XRobotsTagHeaders, FetchError> {
+        let target = NormalizedTarget::parse(target_url)?;
+        Ok(self
+            .state()
+            .headers
+            .get(&target.host)
+            .cloned()
+            .unwrap_or(XRobotsTagHeaders {
+                http_status_code: 200,
+                values: Vec::new(),
+            }))
+    }
+
+    fn stats(&self) -> &FetchStats {
+        &self.stats
+    }
+
+    fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+/// A cache held in a map, with no expiry, that can be inspected directly.
+/// TTLs given to [`Cache::set_with_ttl`] are recorded but not enforced, and
+/// entries stored with [`Cache::pin`] are kept by [`Cache::clear`].
+/// Clones share the entries, so a test can keep one and hand another to the
+/// server.
+pub struct InMemoryCache<K, V> {
+    entries: Arc<Mutex<HashMap<K, V>>>,
+    ttls: Arc<Mutex<HashMap<K, Duration>>>,
+    pinned: Arc<Mutex<HashSet<K>>>,
+    gets: Arc<AtomicU64>,
+    hits: Arc<AtomicU64>,
+    sets: Arc<AtomicU64>,
+}
+
+impl<K: Hash + Eq + Clone + Debug + Send + Sync + 'static, V: Clone + Send + Sync + 'static>
+    InMemoryCache<K, V>
+{
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::default(),
+            ttls: Arc::default(),
+            pinned: Arc::default(),
+            gets: Arc::default(),
+            hits: Arc::default(),
+            sets: Arc::default(),
+        }
+    }
+
+    /// The entry for `key`, without counting as a lookup.
+    pub fn peek(&self, key: &K) -> Option<V> {
+        self.entries().get(key).cloned()
+    }
+
+    /// Store `value` without counting as a write.
+    pub fn insert(&self, key: K, value: V) {
+        self.entries().insert(key, value);
+    }
+
+    /// The TTL the entry for `key` was stored with, if it was given one.
+    pub fn ttl(&self, key: &K) -> Option<Duration> {
+        self.ttls().get(key).copied()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.entries().contains_key(key)
+    }
+
+    pub fn keys(&self) -> Vec<K> {
+        self.entries().keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries().is_empty()
+    }
+
+    /// Lookups made through [`Cache::get`] or [`Cache::get_with_ttl`].
+    pub fn gets(&self) -> u64 {
+        self.gets.load(Ordering::Relaxed)
+    }
+
+    /// The value under `key`, counted as a lookup.
+    fn lookup(&self, key: &K) -> Option<V> {
+        self.gets.fetch_add(1, Ordering::Relaxed);
+        let value = self.peek(key);
+        if value.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        value
+    }
+
+    /// Whether the entry for `key` was stored with [`Cache::pin`].
+    pub fn is_pinned(&self, key: &K) -> bool {
+        self.pinned().contains(key)
+    }
+
+    /// Writes made through [`Cache::set`], [`Cache::set_with_ttl`] or
+    /// [`Cache::pin`].
+    pub fn sets(&self) -> u64 {
+        self.sets.load(Ordering::Relaxed)
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<K, V>> {
+        self.entries.lock().expect("cache lock poisoned")
+    }
+
+    fn ttls(&self) -> std::sync::MutexGuard<'_, HashMap<K, Duration>> {
+        self.ttls.lock().expect("cache lock poisoned")
+    }
+
+    fn pinned(&self) -> std::sync::MutexGuard<'_, HashSet<K>> {
+        self.pinned.lock().expect("cache lock poisoned")
+    }
+}
+
+impl InMemoryCache<String, CachedRobots> {
+    /// The parsed robots.txt cached under `robots_url`.
+    pub fn robots(&self, robots_url: &str) -> Option<Arc<RobotsData>> {
+        self.peek(&robots_url.to_string()).map(|entry| entry.data())
+    }
+}
+
+impl<K, V> Clone for InMemoryCache<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            ttls: self.ttls.clone(),
+            pinned: self.pinned.clone(),
+            gets: self.gets.clone(),
+            hits: self.hits.clone(),
+            sets: self.sets.clone(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone + Debug + Send + Sync + 'static, V: Clone + Send + Sync + 'static> Default
+    for InMemoryCache<K, V>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<K: Hash + Eq + Clone + Debug + Send + Sync + 'static, V: Clone + Send + Sync + 'static>
+    Cache<K, V> for InMemoryCache<K, V>
+{
+    async fn get(&self, key: &K) -> CacheResult<Option<V>> {
+        Ok(self.lookup(key))
+    }
+
+    async fn get_uncounted(&self, key: &K) -> CacheResult<Option<V>> {
+        Ok(self.peek(key))
+    }
+
+    /// Gives the TTL the entry was stored with, as none expire.
+    async fn get_with_ttl(&self, key: &K) -> CacheResult<Option<(V, Option<Duration>)>> {
+        Ok(self.lookup(key).map(|value| (value, self.ttl(key))))
+    }
+
+    async fn set(&self, key: K, value: V) -> CacheResult<()> {
+        self.sets.fetch_add(1, Ordering::Relaxed);
+        self.ttls().remove(&key);
+        self.pinned().remove(&key);
+        self.insert(key, value);
+        Ok(())
+    }
+
+    async fn set_with_ttl(&self, key: K, value: V, ttl: Duration) -> CacheResult<()> {
+        self.sets.fetch_add(1, Ordering::Relaxed);
+        self.ttls().insert(key.clone(), ttl);
+        self.pinned().remove(&key);
+        self.insert(key, value);
+        Ok(())
+    }
+
+    async fn pin(&self, key: K, value: V) -> CacheResult<()> {
+        self.sets.fetch_add(1, Ordering::Relaxed);
+        self.ttls().remove(&key);
+        self.pinned().insert(key.clone());
+        self.insert(key, value);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &K) -> CacheResult<bool> {
+        self.ttls().remove(key);
+        self.pinned().remove(key);
+        Ok(self.entries().remove(key).is_some())
+    }
+
+    async fn clear(&self) -> CacheResult<Option<u64>> {
+        self.ttls().clear();
+        let pinned = self.pinned();
+        let mut entries = self.entries();
+        let before = entries.len();
+        entries.retain(|key, _| pinned.contains(key));
+        Ok(Some((before - entries.len()) as u64))
+    }
+
+    fn entry_count(&self) -> Option<u64> {
+        Some(self.len() as u64)
+    }
+
+    /// Gives each entry the TTL it was stored with, as none expire.
+    async fn entries(&self) -> CacheResult<Vec<(K, V, Option<Duration>)>> {
+        let ttls = self.ttls().clone();
+        let entries = self
+            .entries()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone(), ttls.get(key).copied()))
+            .collect();
+        Ok(entries)
+    }
+
+    /// Counts lookups and writes; nothing is ever evicted.
+    async fn stats(&self) -> Option<CacheStats> {
+        let gets = self.gets();
+        let hits = self.hits.load(Ordering::Relaxed);
+        Some(CacheStats {
+            hits,
+            misses: gets - hits,
+            insertions: self.sets(),
+            evictions: 0,
+            entry_count: self.len() as u64,
+        })
+    }
+}
+
+/// A [`RobotsServer`] serving on an ephemeral local port, and a client
+/// connected to it. The server stops when this is dropped.
+pub struct TestServer {
+    pub addr: SocketAddr,
+    pub client: RobotsClient,
+    channel: Channel,
+    shutdown: Option<oneshot::Sender<()>>,
+    handle: JoinHandle<Result<(), tonic::transport::Error>>,
+}
+
+impl TestServer {
+    /// The URL clients connect to.
+    pub fn endpoint(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A generated client on the same connection, for RPCs the wrapper
+    /// does not cover.
+    pub fn grpc_client(&self) -> RobotsServiceClient<Channel> {
+        RobotsServiceClient::new(self.channel.clone())
+    }
+
+    /// Stop the server and wait for it to finish.
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        (&mut self.handle)
+            .await
+            .expect("server task panicked")
+            .expect("server failed");
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+/// Serve `service` on an ephemeral port on the loopback interface and
+/// connect a client to it.
+pub async fn spawn_server<T: Cache<String, CachedRobots>, F: Fetcher>(
+    service: RobotsServer<T, F>,
+) -> TestServer {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind ephemeral port");
+    let addr = listener.local_addr().expect("listener address");
+    let (shutdown, signal) = oneshot::channel::<()>();
+    let handle = tokio::spawn(
+        Server::builder()
+            .layer(TraceContextLayer)
+            .add_service(RobotsServiceServer::new(service))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async {
+                signal.await.ok();
+            }),
+    );
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .expect("valid endpoint")
+        .connect()
+        .await
+        .expect("connect to test server");
+    TestServer {
+        addr,
+        client: RobotsClient::new(channel.clone()),
+        channel,
+        shutdown: Some(shutdown),
+        handle,
+    }
+}
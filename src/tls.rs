@@ -0,0 +1,52 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+use crate::config::TlsConfig;
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("Failed to read {}: {message}", path.display())]
+    Read { path: PathBuf, message: String },
+    #[error("{} has no PEM {label}", path.display())]
+    NoPem { path: PathBuf, label: &'static str },
+}
+
+/// The TLS settings for serving with the certificate and key `config` names,
+/// requiring client certificates signed by its `client_ca` if it has one.
+/// Files that cannot be read or hold no PEM of the right kind are rejected
+/// here; tonic parses the PEM itself when the server is built.
+pub fn server_tls_config(config: &TlsConfig) -> Result<ServerTlsConfig, TlsError> {
+    let cert = read_pem(&config.cert, "CERTIFICATE")?;
+    let key = read_pem(&config.key, "PRIVATE KEY")?;
+    let tls = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+    match &config.client_ca {
+        Some(path) => {
+            let ca = read_pem(path, "CERTIFICATE")?;
+            Ok(tls.client_ca_root(Certificate::from_pem(ca)))
+        }
+        None => Ok(tls),
+    }
+}
+
+/// The contents of `path`, which must hold at least one PEM block whose
+/// label ends with `label`.
+fn read_pem(path: &Path, label: &'static str) -> Result<String, TlsError> {
+    let pem = std::fs::read_to_string(path).map_err(|e| TlsError::Read {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })?;
+    let has_block = pem.lines().any(|line| {
+        line.strip_prefix("-----BEGIN ")
+            .and_then(|rest| rest.strip_suffix("-----"))
+            .is_some_and(|found| found.ends_with(label))
+    });
+    if !has_block {
+        return Err(TlsError::NoPem {
+            path: path.to_path_buf(),
+            label,
+        });
+    }
+    Ok(pem)
+}
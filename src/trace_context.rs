@@ -0,0 +1,129 @@
+use std::future::Future;
+
+tokio::task_local! {
+    /// The trace context of the call being served by this task.
+    static CURRENT: Option<TraceContext>;
+}
+
+/// Bytes of `tracestate` passed on before it is dropped, per the W3C limit.
+const MAX_TRACESTATE_BYTES: usize = 512;
+
+/// The W3C trace context an incoming call carried, to pass on with the
+/// fetches it causes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceContext {
+    traceparent: String,
+    tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// The context in `traceparent` and `tracestate` header values, when
+    /// `traceparent` is a well-formed version 00 header. A `tracestate` that
+    /// is empty or too long is left out.
+    pub fn parse(traceparent: &str, tracestate: Option<&str>) -> Option<Self> {
+        let traceparent = traceparent.trim();
+        let [version, trace_id, parent_id, flags]: [&str; 4] =
+            traceparent.split('-').collect::<Vec<_>>().try_into().ok()?;
+        let hex = |field: &str, len: usize| {
+            field.len() == len
+                && field
+                    .bytes()
+                    .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        };
+        let zero = |field: &str| field.bytes().all(|b| b == b'0');
+        if version != "00"
+            || !hex(trace_id, 32)
+            || !hex(parent_id, 16)
+            || !hex(flags, 2)
+            || zero(trace_id)
+            || zero(parent_id)
+        {
+            return None;
+        }
+        let tracestate = tracestate
+            .map(str::trim)
+            .filter(|state| !state.is_empty() && state.len() <= MAX_TRACESTATE_BYTES)
+            .map(str::to_string);
+        Some(Self {
+            traceparent: traceparent.to_string(),
+            tracestate,
+        })
+    }
+
+    pub fn traceparent(&self) -> &str {
+        &self.traceparent
+    }
+
+    pub fn tracestate(&self) -> Option<&str> {
+        self.tracestate.as_deref()
+    }
+
+    /// The context of the call being served by this task, if it carried one.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok().flatten()
+    }
+
+    /// Run `future` with `context` as the current context.
+    pub async fn scope<T>(context: Option<Self>, future: impl Future<Output = T>) -> T {
+        CURRENT.scope(context, future).await
+    }
+}
+
+#[cfg(feature = "server")]
+mod layer {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use tower::{Layer, Service};
+
+    use super::{CURRENT, TraceContext};
+
+    /// Tower layer that makes the W3C trace context sent with each call, in
+    /// `traceparent` and `tracestate` metadata, current while it is served.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct TraceContextLayer;
+
+    impl<S> Layer<S> for TraceContextLayer {
+        type Service = PropagateTraceContext<S>;
+
+        fn layer(&self, inner: S) -> Self::Service {
+            PropagateTraceContext { inner }
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct PropagateTraceContext<S> {
+        inner: S,
+    }
+
+    impl<S, ReqBody> Service<http::Request<ReqBody>> for PropagateTraceContext<S>
+    where
+        S: Service<http::Request<ReqBody>>,
+        S::Future: Send + 'static,
+    {
+        type Response = S::Response;
+        type Error = S::Error;
+        type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.inner.poll_ready(cx)
+        }
+
+        fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+            let header = |name: &str| {
+                request
+                    .headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+            };
+            let context = header("traceparent")
+                .and_then(|traceparent| TraceContext::parse(traceparent, header("tracestate")));
+            let future = CURRENT.sync_scope(context.clone(), || self.inner.call(request));
+            Box::pin(CURRENT.scope(context, future))
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+pub use layer::{PropagateTraceContext, TraceContextLayer};
@@ -0,0 +1,52 @@
+use std::io;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::Path;
+
+use futures_util::Stream;
+use tokio::net::{UnixListener, UnixStream};
+use tracing::info;
+
+use crate::config::UdsConfig;
+
+/// Listen on the socket `config` names, with its permissions. A socket file
+/// left behind by a server that is no longer running is removed first; one
+/// a server still answers on, or a file that is not a socket, is an error.
+pub fn bind(config: &UdsConfig) -> io::Result<UnixListener> {
+    remove_stale(&config.path)?;
+    let listener = UnixListener::bind(&config.path)?;
+    std::fs::set_permissions(&config.path, std::fs::Permissions::from_mode(config.mode))?;
+    Ok(listener)
+}
+
+fn remove_stale(path: &Path) -> io::Result<()> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} is in use by a running server", path.display()),
+        ));
+    }
+    info!(path = %path.display(), "Removing stale socket");
+    std::fs::remove_file(path)
+}
+
+/// The connections accepted on `listener`, for `serve_with_incoming`.
+pub fn incoming(listener: UnixListener) -> impl Stream<Item = io::Result<UnixStream>> {
+    Box::pin(futures_util::stream::unfold(
+        listener,
+        |listener| async move {
+            let accepted = listener.accept().await.map(|(stream, _)| stream);
+            Some((accepted, listener))
+        },
+    ))
+}
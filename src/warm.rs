@@ -0,0 +1,37 @@
+use std::io;
+use std::path::Path;
+
+/// How warming the cache from a seed list went.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WarmReport {
+    /// Hosts whose robots.txt was fetched and cached, whatever the origin
+    /// answered.
+    pub warmed: u64,
+    /// URLs whose robots.txt was already cached, repeated in the list, or
+    /// served from `robots_overrides`.
+    pub skipped: u64,
+    /// URLs that were invalid or excluded by the host lists, or whose fetch
+    /// could not complete.
+    pub failed: u64,
+}
+
+/// The URLs in a seed list, one per line. Blank lines and lines starting
+/// with `#` are left out.
+pub fn parse_seed_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Read the seed list at `path`.
+pub async fn read_seed_list(path: &Path) -> io::Result<Vec<String>> {
+    let text = tokio::fs::read_to_string(path).await.map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("Failed to read seed list {}: {e}", path.display()),
+        )
+    })?;
+    Ok(parse_seed_list(&text))
+}
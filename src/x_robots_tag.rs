@@ -0,0 +1,139 @@
+use std::time::SystemTime;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Directive names that may legitimately be followed by a `:` and a value,
+/// so a leading `name:` with one of these is not a user-agent scope.
+const VALUED_DIRECTIVES: [&str; 4] = [
+    "unavailable_after",
+    "max-snippet",
+    "max-image-preview",
+    "max-video-preview",
+];
+
+/// Raw `X-Robots-Tag` header values returned by the origin for a target URL.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct XRobotsTagHeaders {
+    pub http_status_code: u32,
+    pub values: Vec<String>,
+}
+
+/// The effective directive set for one user-agent after applying
+/// unscoped and user-agent-scoped `X-Robots-Tag` values.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RobotsHeaderDirectives {
+    pub header_present: bool,
+    pub noindex: bool,
+    pub nofollow: bool,
+    pub none: bool,
+    pub unavailable_after: Option<SystemTime>,
+    pub directives: Vec<String>,
+}
+
+/// Parse the `X-Robots-Tag` header values that apply to `user_agent`.
+///
+/// Values may be scoped with a leading user-agent token (`googlebot: noindex`);
+/// scoped values only apply when the token matches the user-agent using the
+/// same case-insensitive substring rule as robots.txt group selection.
+pub fn parse_x_robots_tag<'a>(
+    values: impl IntoIterator<Item = &'a str>,
+    user_agent: &str,
+) -> RobotsHeaderDirectives {
+    let user_agent_lower = user_agent.to_lowercase();
+    let mut result = RobotsHeaderDirectives::default();
+
+    for value in values {
+        result.header_present = true;
+        let (scope, directives) = split_scope(value);
+        if let Some(scope) = scope
+            && !user_agent_lower.contains(&scope)
+        {
+            debug!(%scope, "Skipping X-Robots-Tag scoped to another user-agent");
+            continue;
+        }
+
+        let mut parts = directives.split(',');
+        while let Some(part) = parts.next() {
+            let directive = part.trim();
+            if directive.is_empty() {
+                continue;
+            }
+            if let Some(rest) = strip_prefix_ignore_case(directive, "unavailable_after") {
+                // RFC 850 dates contain a comma, so the date swallows the rest of the value
+                let mut raw_date = rest.trim_start().trim_start_matches(':').to_string();
+                for remaining in parts.by_ref() {
+                    raw_date.push(',');
+                    raw_date.push_str(remaining);
+                }
+                let raw_date = raw_date.trim();
+                match parse_unavailable_after(raw_date) {
+                    Some(time) => result.unavailable_after = Some(time),
+                    None => debug!(date = %raw_date, "Unparseable unavailable_after date"),
+                }
+                result
+                    .directives
+                    .push(format!("unavailable_after: {raw_date}"));
+                continue;
+            }
+            let lower = directive.to_lowercase();
+            match lower.as_str() {
+                "noindex" => result.noindex = true,
+                "nofollow" => result.nofollow = true,
+                "none" => {
+                    result.none = true;
+                    result.noindex = true;
+                    result.nofollow = true;
+                }
+                _ => {}
+            }
+            result.directives.push(lower);
+        }
+    }
+
+    result
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = value.get(..prefix.len())?;
+    if head.eq_ignore_ascii_case(prefix) {
+        Some(&value[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Split an optional user-agent scope (lowercased) from the directive list.
+fn split_scope(value: &str) -> (Option<String>, &str) {
+    let Some((prefix, rest)) = value.split_once(':') else {
+        return (None, value);
+    };
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.contains(',') || VALUED_DIRECTIVES.contains(&prefix.as_str()) {
+        return (None, value);
+    }
+    (Some(prefix), rest)
+}
+
+/// Parse the date formats accepted for `unavailable_after`: RFC 822/2822,
+/// RFC 850, and ISO 8601 date or date-time. Dates without a zone are UTC.
+fn parse_unavailable_after(raw: &str) -> Option<SystemTime> {
+    if let Ok(dt) = DateTime::parse_from_rfc2822(raw) {
+        return Some(dt.into());
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Some(dt.into());
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(raw, "%A, %d-%b-%y %H:%M:%S GMT") {
+        return Some(dt.and_utc().into());
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S") {
+        return Some(dt.and_utc().into());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        let dt: DateTime<Utc> = date.and_hms_opt(0, 0, 0)?.and_utc();
+        return Some(dt.into());
+    }
+    None
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "test-support")]
+
+use robots_server::admin_cli::{self, AdminCli, AdminCommand, Output, OutputFormat};
+use robots_server::service::RobotsServer;
+use robots_server::test_support::{FakeFetcher, InMemoryCache, RobotsDataBuilder, spawn_server};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_admin_commands_against_server() {
+    let fetcher = FakeFetcher::new();
+    fetcher.serve(
+        "example.com",
+        RobotsDataBuilder::new()
+            .group("*")
+            .disallow("/admin")
+            .build(),
+    );
+    let cache = InMemoryCache::new();
+    let server = spawn_server(RobotsServer::new(cache.clone(), fetcher)).await;
+    server
+        .client
+        .is_allowed("http://example.com/page", "MyBot")
+        .await
+        .unwrap();
+
+    let stats = admin_cli::stats(&server.client).await.unwrap();
+    let stats = stats.to_json();
+    assert_eq!(stats["cache_entries"], 1);
+    assert_eq!(stats["cache_misses"], 1);
+    assert_eq!(stats["fetches_total"], 1);
+
+    let urls = vec![
+        "http://example.com/page".to_string(),
+        "http://other.example/".to_string(),
+    ];
+    let invalidated = admin_cli::invalidate(&server.client, &urls).await.unwrap();
+    assert_eq!(
+        invalidated.to_json(),
+        json!([
+            { "robots_txt_url": "http://example.com/robots.txt", "existed": true },
+            { "robots_txt_url": "http://other.example/robots.txt", "existed": false },
+        ])
+    );
+    assert!(cache.is_empty());
+
+    let args = AdminCli {
+        endpoint: server.endpoint(),
+        timeout: 5.0,
+        api_key: None,
+        output: OutputFormat::Json,
+        command: AdminCommand::Invalidate {
+            urls: vec!["not a url".to_string()],
+        },
+    };
+    assert!(admin_cli::run(&args).await.is_err());
+
+    server.shutdown().await;
+}
+
+#[test]
+fn test_output_formats() {
+    let output = Output::Rows {
+        columns: vec!["robots_txt_url", "existed"],
+        rows: vec![
+            vec![json!("http://example.com/robots.txt"), json!(true)],
+            vec![json!("http://a.example/robots.txt"), json!(false)],
+        ],
+    };
+
+    let mut table = Vec::new();
+    output.write(OutputFormat::Table, &mut table).unwrap();
+    assert_eq!(
+        String::from_utf8(table).unwrap(),
+        "ROBOTS_TXT_URL                 EXISTED\n\
+         http://example.com/robots.txt  true\n\
+         http://a.example/robots.txt    false\n"
+    );
+
+    let record = Output::Record(vec![("version", json!("0.1.0")), ("entries", json!(null))]);
+    assert_eq!(record.to_table(), "version  0.1.0\nentries  -\n");
+    let mut out = Vec::new();
+    record.write(OutputFormat::Json, &mut out).unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(parsed, json!({ "version": "0.1.0", "entries": null }));
+}
@@ -0,0 +1,348 @@
+#![cfg(feature = "server")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use robots_server::cache::{Cache, MokaCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::fetcher::{RobotsFetcher, RobotsText};
+use robots_server::parser::parse;
+use robots_server::politeness::{HostRateLimiter, RateLimit};
+use robots_server::robots_data::{AccessResult, Group, RobotsData, Rule, RuleType};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{GetRobotsRequest, GetRobotsResponse, IsAllowedRequest};
+use tonic::Request;
+
+/// Counts bytes allocated on the current thread, and bytes still live.
+/// Tests run on a current-thread runtime, so this covers everything a
+/// handler does.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATED.try_with(|n| n.set(n.get() + layout.size()));
+        let _ = LIVE.try_with(|n| n.set(n.get() + layout.size() as isize));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _ = LIVE.try_with(|n| n.set(n.get() - layout.size() as isize));
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocated() -> usize {
+    ALLOCATED.with(Cell::get)
+}
+
+fn live() -> isize {
+    LIVE.with(Cell::get)
+}
+
+const ROBOTS_URL: &str = "http://example.com/robots.txt";
+
+fn large_robots_data() -> RobotsData {
+    let rules = (0..50_000)
+        .map(|i| {
+            let rule_type = if i % 2 == 0 {
+                RuleType::Disallow
+            } else {
+                RuleType::Allow
+            };
+            Rule::new(rule_type, format!("/section-{i}/private/"))
+        })
+        .collect();
+    RobotsData {
+        target_url: "http://example.com/".to_string(),
+        robots_txt_url: ROBOTS_URL.to_string(),
+        access_result: AccessResult::Success,
+        http_status_code: 200,
+        groups: vec![Group::new(vec!["*".to_string()], rules, None)],
+        ..Default::default()
+    }
+}
+
+async fn cached_service(data: Arc<RobotsData>) -> RobotsServer<MokaCache<String, CachedRobots>> {
+    let cache = MokaCache::new();
+    cache
+        .set(ROBOTS_URL.to_string(), data.into())
+        .await
+        .unwrap();
+    RobotsServer::new(cache, RobotsFetcher::new())
+        .with_host_rate_limiter(HostRateLimiter::new(RateLimit::UNLIMITED))
+}
+
+#[tokio::test]
+async fn test_is_allowed_does_not_copy_cached_data() {
+    let data = Arc::new(large_robots_data());
+    let before = allocated();
+    let copy = RobotsData::clone(&data);
+    let copy_bytes = allocated() - before;
+    drop(copy);
+
+    let service = cached_service(data).await;
+    let request = || {
+        Request::new(IsAllowedRequest {
+            target_url: "http://example.com/section-10/private/page".to_string(),
+            user_agent: "MyBot".to_string(),
+            ..Default::default()
+        })
+    };
+    // Warm up anything allocated once per service
+    service.is_allowed(request()).await.unwrap();
+
+    let before = allocated();
+    let response = service.is_allowed(request()).await.unwrap().into_inner();
+    let call_bytes = allocated() - before;
+
+    assert!(!response.allowed);
+    assert!(
+        call_bytes * 20 < copy_bytes,
+        "is_allowed allocated {call_bytes} bytes; a copy of the data is {copy_bytes}"
+    );
+}
+
+#[tokio::test]
+async fn test_get_robots_txt_builds_response_from_cached_data() {
+    let data = Arc::new(large_robots_data());
+    let before = allocated();
+    let copy = RobotsData::clone(&data);
+    let copy_bytes = allocated() - before;
+    drop(copy);
+    let before = allocated();
+    let response = GetRobotsResponse::from(data.as_ref());
+    let conversion_bytes = allocated() - before;
+    drop(response);
+
+    let service = cached_service(data).await;
+    let before = allocated();
+    let response = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: "http://example.com/".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    let call_bytes = allocated() - before;
+
+    assert_eq!(response.groups[0].rules.len(), 50_000);
+    // Only the response itself is built; cloning the data first would add
+    // another `copy_bytes`
+    assert!(
+        call_bytes < conversion_bytes + copy_bytes / 2,
+        "get_robots_txt allocated {call_bytes} bytes; conversion alone is \
+         {conversion_bytes} and a copy of the data is {copy_bytes}"
+    );
+}
+
+/// Rule storage as it was before patterns were shared and lists boxed,
+/// rebuilt from parsed data to measure the saving against.
+#[allow(dead_code)]
+mod reference {
+    use std::ops::Range;
+    use std::time::Duration;
+
+    use robots_server::robots_data::{RobotsData, RuleType};
+
+    pub struct Rule {
+        rule_type: RuleType,
+        path_pattern: String,
+        end_anchored: bool,
+        has_wildcard: bool,
+        body_len: usize,
+        segments: Vec<Range<usize>>,
+    }
+
+    pub struct Group {
+        user_agents: Vec<String>,
+        rules: Vec<Rule>,
+        crawl_delay: Option<Duration>,
+        user_agent_tokens: Vec<String>,
+    }
+
+    /// `data`'s groups built line by line, as the old parser did.
+    pub fn groups(data: &RobotsData) -> Vec<Group> {
+        let mut groups = Vec::new();
+        for group in &data.groups {
+            let mut old = Group {
+                user_agents: Vec::new(),
+                rules: Vec::new(),
+                crawl_delay: group.crawl_delay,
+                user_agent_tokens: Vec::new(),
+            };
+            for ua in &group.user_agents {
+                old.user_agent_tokens.push(ua.to_lowercase());
+                old.user_agents.push(ua.to_string());
+            }
+            for rule in &group.rules {
+                let pattern = rule.path_pattern.to_string();
+                let body = pattern.strip_suffix('$').unwrap_or(&pattern);
+                let mut segments = Vec::new();
+                let mut start = 0;
+                if body.contains('*') {
+                    for part in body.split('*') {
+                        segments.push(start..start + part.len());
+                        start += part.len() + 1;
+                    }
+                }
+                old.rules.push(Rule {
+                    rule_type: rule.rule_type,
+                    end_anchored: pattern.ends_with('$'),
+                    has_wildcard: body.contains('*'),
+                    body_len: body.len(),
+                    segments,
+                    path_pattern: pattern,
+                });
+            }
+            groups.push(old);
+        }
+        groups
+    }
+}
+
+/// Live bytes held by `parse(body)` and by the old representation of the
+/// same groups.
+fn retained_bytes(body: &str) -> (isize, isize) {
+    let before = live();
+    let data = parse(body);
+    let compact = live() - before;
+    let before = live();
+    let old = reference::groups(&data);
+    let previous = live() - before;
+    drop(old);
+    (compact, previous)
+}
+
+#[test]
+fn test_shared_patterns_cut_retained_memory() {
+    // Sites commonly repeat one list of paths for many crawlers
+    let mut body = String::new();
+    for crawler in 0..40 {
+        body.push_str(&format!(
+            "User-agent: Crawler{crawler}\nUser-agent: Crawler{crawler}-News\n"
+        ));
+        for i in 0..500 {
+            if i % 10 == 0 {
+                body.push_str(&format!("Disallow: /*/section-{i}/*.pdf$\n"));
+            } else {
+                body.push_str(&format!("Disallow: /section-{i}/private/\n"));
+            }
+        }
+        for i in 0..20 {
+            body.push_str(&format!("Allow: /crawler-{crawler}/feed-{i}.xml\n"));
+        }
+        body.push('\n');
+    }
+
+    let (compact, previous) = retained_bytes(&body);
+    assert!(
+        compact * 10 <= previous * 7,
+        "parsed data holds {compact} bytes; the old layout held {previous}"
+    );
+}
+
+#[test]
+fn test_unique_patterns_do_not_grow_retained_memory() {
+    let mut body = String::from("User-agent: *\n");
+    for i in 0..20_000 {
+        body.push_str(&format!("Disallow: /section-{i}/page-{}/\n", i % 97));
+    }
+
+    let (compact, previous) = retained_bytes(&body);
+    assert!(
+        compact < previous,
+        "parsed data holds {compact} bytes; the old layout held {previous}"
+    );
+}
+
+fn robots_text(body: &str) -> RobotsText {
+    RobotsText {
+        robots_txt_url: ROBOTS_URL.to_string(),
+        http_status_code: 200,
+        content_length_bytes: body.len() as u64,
+        body: body.to_string(),
+        truncated: false,
+        fetched_at: SystemTime::now(),
+        http_version: "HTTP/1.1".to_string(),
+        upstream_headers: BTreeMap::new(),
+        max_age: None,
+        resolved_address: None,
+    }
+}
+
+#[test]
+fn test_raw_entry_holds_less_until_first_use() {
+    let mut body = String::from("User-agent: *\n");
+    for i in 0..20_000 {
+        body.push_str(&format!("Disallow: /section-{i}/page-{}/\n", i % 97));
+    }
+
+    let before = live();
+    let parsed = CachedRobots::from(robots_text(&body).parse("http://example.com/"));
+    let parsed_bytes = live() - before;
+    let before = live();
+    let raw = CachedRobots::raw(robots_text(&body), "http://example.com/");
+    let raw_bytes = live() - before;
+    assert!(
+        raw_bytes * 2 < parsed_bytes,
+        "raw entry holds {raw_bytes} bytes; parsed holds {parsed_bytes}"
+    );
+
+    // Parsing on first use releases the text
+    let before = live();
+    assert_eq!(
+        raw.data().groups[0].rules.len(),
+        parsed.data().groups[0].rules.len()
+    );
+    let growth = live() - before;
+    assert!(
+        growth < parsed_bytes - raw_bytes / 2,
+        "first use grew the entry by {growth} bytes; parsed holds {parsed_bytes}"
+    );
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn test_compressed_raw_entry_holds_less() {
+    use robots_server::cached_robots::COMPRESSION_THRESHOLD;
+
+    // About 500KB of the repetitive lists large sites publish
+    let mut body = String::new();
+    for crawler in 0..40 {
+        body.push_str(&format!("User-agent: Crawler{crawler}\n"));
+        for i in 0..1_000 {
+            body.push_str(&format!("Disallow: /section-{i}/private/\n"));
+        }
+        body.push('\n');
+    }
+    assert!(body.len() > 500 * 1024);
+
+    let before = live();
+    let raw = CachedRobots::raw(robots_text(&body), "http://example.com/");
+    let raw_bytes = live() - before;
+    assert!(
+        raw_bytes * 10 < body.len() as isize,
+        "raw entry holds {raw_bytes} bytes for a {} byte body",
+        body.len()
+    );
+    assert!(raw.raw_size_bytes().unwrap() * 10 < body.len());
+    assert_eq!(raw.raw_text().unwrap().body, body);
+
+    let short = "User-agent: *\nDisallow: /private/\n";
+    assert!(short.len() < COMPRESSION_THRESHOLD);
+    let raw = CachedRobots::raw(robots_text(short), "http://example.com/");
+    assert_eq!(raw.raw_size_bytes(), Some(short.len()));
+}
@@ -1,4 +1,10 @@
-use robots_server::cache::{Cache, MokaCache};
+use std::sync::Arc;
+use std::time::Duration;
+
+use robots_server::cache::{Cache, CacheStats, MokaCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::clock::ManualClock;
+use robots_server::robots_data::{Group, RobotsData, Rule, RuleType};
 
 #[tokio::test]
 async fn test_cache_get_set() {
@@ -94,3 +100,264 @@ async fn test_cache_clone_behavior() {
 
     assert_eq!(data, vec![1, 2, 3]);
 }
+#[tokio::test]
+async fn test_cache_entry_ttl() {
+    let cache: MokaCache<String, String> = MokaCache::new();
+
+    cache
+        .set_with_ttl(
+            "short".to_string(),
+            "value".to_string(),
+            Duration::from_millis(50),
+        )
+        .await
+        .unwrap();
+    cache
+        .set("long".to_string(), "value".to_string())
+        .await
+        .unwrap();
+    assert!(cache.get(&"short".to_string()).await.unwrap().is_some());
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Only the entry stored with its own TTL has expired
+    assert!(cache.get(&"short".to_string()).await.unwrap().is_none());
+    assert!(cache.get(&"long".to_string()).await.unwrap().is_some());
+}
+#[tokio::test]
+async fn test_cache_with_ttl() {
+    let cache: MokaCache<String, String> = MokaCache::with_ttl(Duration::from_secs(1));
+
+    cache
+        .set("key".to_string(), "value".to_string())
+        .await
+        .unwrap();
+    assert!(cache.get(&"key".to_string()).await.unwrap().is_some());
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    assert!(cache.get(&"key".to_string()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_builder_time_to_live() {
+    let cache: MokaCache<String, String> = MokaCache::builder()
+        .time_to_live(Duration::from_millis(200))
+        .initial_capacity(16)
+        .build();
+
+    cache
+        .set("key".to_string(), "value".to_string())
+        .await
+        .unwrap();
+    assert!(cache.get(&"key".to_string()).await.unwrap().is_some());
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    assert!(cache.get(&"key".to_string()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_builder_clock_ages_entries() {
+    let clock = Arc::new(ManualClock::new());
+    let cache: MokaCache<String, String> = MokaCache::builder()
+        .time_to_live(Duration::from_secs(60))
+        .clock(clock.clone())
+        .build();
+    let key = "key".to_string();
+    cache.set(key.clone(), "value".to_string()).await.unwrap();
+
+    clock.advance(Duration::from_secs(45));
+    let (_, remaining) = cache.get_with_ttl(&key).await.unwrap().unwrap();
+    assert_eq!(remaining, Some(Duration::from_secs(15)));
+
+    clock.advance(Duration::from_secs(15));
+    assert!(cache.get(&key).await.unwrap().is_none());
+    assert!(cache.entries().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_builder_max_capacity_evicts() {
+    let cache: MokaCache<String, String> = MokaCache::builder().max_capacity(10).build();
+
+    for i in 0..100 {
+        cache
+            .set(format!("key{i}"), "value".to_string())
+            .await
+            .unwrap();
+    }
+    cache.run_pending_tasks().await;
+
+    assert!(cache.entry_count().unwrap() <= 10);
+    let mut kept = 0;
+    for i in 0..100 {
+        if cache.get(&format!("key{i}")).await.unwrap().is_some() {
+            kept += 1;
+        }
+    }
+    assert!((1..=10).contains(&kept), "{kept} entries kept");
+}
+
+/// A parsed robots.txt with `rules` disallow lines of about 100 bytes each.
+fn large_robots(rules: usize) -> CachedRobots {
+    let rules = (0..rules)
+        .map(|i| Rule::new(RuleType::Disallow, format!("/{i:099}")))
+        .collect();
+    RobotsData {
+        groups: vec![Group::new(vec!["*".to_string()], rules, None)],
+        ..Default::default()
+    }
+    .into()
+}
+
+#[tokio::test]
+async fn test_builder_max_bytes_evicts() {
+    let entry_size = large_robots(1000).estimated_size() as u64;
+    assert!(entry_size > 100_000, "{entry_size} bytes");
+    let budget = entry_size * 5;
+    let cache: MokaCache<String, CachedRobots> = MokaCache::builder().max_bytes(budget).build();
+
+    for i in 0..50 {
+        cache
+            .set(format!("key{i}"), large_robots(1000))
+            .await
+            .unwrap();
+        cache.run_pending_tasks().await;
+        assert!(cache.weighted_size() <= budget);
+    }
+
+    let mut kept = 0;
+    for i in 0..50 {
+        if cache.get(&format!("key{i}")).await.unwrap().is_some() {
+            kept += 1;
+        }
+    }
+    assert!((1..=5).contains(&kept), "{kept} entries kept");
+    assert_eq!(cache.weighted_size(), kept * entry_size);
+    // An entry larger than the whole budget is not kept
+    cache
+        .set("huge".to_string(), large_robots(10_000))
+        .await
+        .unwrap();
+    cache.run_pending_tasks().await;
+    assert!(cache.get(&"huge".to_string()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_stats_count_hits_misses_and_evictions() {
+    let cache: MokaCache<String, String> = MokaCache::builder()
+        .time_to_live(Duration::from_millis(200))
+        .build();
+    let key = |name: &str| name.to_string();
+
+    assert!(cache.get(&key("a")).await.unwrap().is_none());
+    cache.set(key("a"), "1".to_string()).await.unwrap();
+    cache.set(key("b"), "2".to_string()).await.unwrap();
+    cache
+        .set_with_ttl(key("c"), "3".to_string(), Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert!(cache.get(&key("a")).await.unwrap().is_some());
+    assert!(cache.get(&key("a")).await.unwrap().is_some());
+    // Deleted entries are not evictions
+    assert!(cache.delete(&key("b")).await.unwrap());
+    assert_eq!(
+        cache.stats().await,
+        Some(CacheStats {
+            hits: 2,
+            misses: 1,
+            insertions: 3,
+            evictions: 0,
+            entry_count: 2,
+        })
+    );
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(cache.get(&key("a")).await.unwrap().is_none());
+    assert_eq!(
+        cache.stats().await,
+        Some(CacheStats {
+            hits: 2,
+            misses: 2,
+            insertions: 3,
+            evictions: 1,
+            entry_count: 1,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_clear_drops_every_entry() {
+    let cache: MokaCache<String, String> = MokaCache::new();
+    for i in 0..3 {
+        cache
+            .set(format!("key{i}"), "value".to_string())
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(cache.clear().await.unwrap(), Some(3));
+    for i in 0..3 {
+        assert!(cache.get(&format!("key{i}")).await.unwrap().is_none());
+    }
+    assert_eq!(cache.clear().await.unwrap(), Some(0));
+}
+
+#[tokio::test]
+async fn test_pinned_entries_outlive_ttl_capacity_and_clear() {
+    let clock = Arc::new(ManualClock::new());
+    let cache: MokaCache<String, String> = MokaCache::builder()
+        .time_to_live(Duration::from_secs(60))
+        .max_capacity(10)
+        .clock(clock.clone())
+        .build();
+    let pinned = "pinned".to_string();
+    cache.pin(pinned.clone(), "kept".to_string()).await.unwrap();
+    for i in 0..100 {
+        cache
+            .set(format!("key{i}"), "value".to_string())
+            .await
+            .unwrap();
+    }
+    cache.run_pending_tasks().await;
+
+    clock.advance(Duration::from_secs(120));
+    assert_eq!(
+        cache.get_with_ttl(&pinned).await.unwrap(),
+        Some(("kept".to_string(), None))
+    );
+    cache.clear().await.unwrap();
+    assert_eq!(cache.get(&pinned).await.unwrap(), Some("kept".to_string()));
+    assert_eq!(cache.entry_count(), Some(1));
+
+    // Writing it again unpins it
+    cache
+        .set(pinned.clone(), "value".to_string())
+        .await
+        .unwrap();
+    cache.clear().await.unwrap();
+    assert!(cache.get(&pinned).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_uncounted_lookups_leave_stats_alone() {
+    let cache: MokaCache<String, String> = MokaCache::new();
+    cache
+        .set("key".to_string(), "value".to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        cache.get_uncounted(&"key".to_string()).await.unwrap(),
+        Some("value".to_string())
+    );
+    assert!(
+        cache
+            .get_uncounted(&"other".to_string())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let stats = cache.stats().await.unwrap();
+    assert_eq!((stats.hits, stats.misses), (0, 0));
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "server")]
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{CanCrawlRequest, DeniedBy};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn mount_robots(mock_server: &MockServer, body: &str) {
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_can_crawl_robots_denies_without_header_request() {
+    let mock_server = MockServer::start().await;
+    mount_robots(&mock_server, "User-agent: *\nDisallow: /private").await;
+    Mock::given(method("HEAD"))
+        .and(path("/private/page.html"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let response = service
+        .can_crawl(Request::new(CanCrawlRequest {
+            target_url: format!("http://{}/private/page.html", mock_server.address()),
+            user_agent: "MyBot".to_string(),
+            skip_header_check: false,
+        }))
+        .await
+        .unwrap();
+    let response = response.get_ref();
+
+    assert!(!response.allowed);
+    assert!(!response.robots_allowed);
+    assert!(response.headers.is_none());
+    assert_eq!(response.denied_by, DeniedBy::RobotsTxt as i32);
+}
+#[tokio::test]
+async fn test_can_crawl_noindex_header_denies() {
+    let mock_server = MockServer::start().await;
+    mount_robots(&mock_server, "User-agent: *\nAllow: /").await;
+    Mock::given(method("HEAD"))
+        .and(path("/page.html"))
+        .respond_with(ResponseTemplate::new(200).insert_header("x-robots-tag", "noindex"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let response = service
+        .can_crawl(Request::new(CanCrawlRequest {
+            target_url: format!("http://{}/page.html", mock_server.address()),
+            user_agent: "MyBot".to_string(),
+            skip_header_check: false,
+        }))
+        .await
+        .unwrap();
+    let response = response.get_ref();
+
+    assert!(!response.allowed);
+    assert!(response.robots_allowed);
+    assert!(response.headers.as_ref().unwrap().noindex);
+    assert_eq!(response.denied_by, DeniedBy::XRobotsTag as i32);
+}
+#[tokio::test]
+async fn test_can_crawl_both_allow() {
+    let mock_server = MockServer::start().await;
+    mount_robots(&mock_server, "User-agent: *\nDisallow: /private").await;
+    Mock::given(method("HEAD"))
+        .and(path("/page.html"))
+        .respond_with(ResponseTemplate::new(200).insert_header("x-robots-tag", "nofollow"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let url = format!("http://{}/page.html", mock_server.address());
+    for _ in 0..2 {
+        // The second call is answered from the header cache
+        let response = service
+            .can_crawl(Request::new(CanCrawlRequest {
+                target_url: url.clone(),
+                user_agent: "MyBot".to_string(),
+                skip_header_check: false,
+            }))
+            .await
+            .unwrap();
+        let response = response.get_ref();
+
+        assert!(response.allowed);
+        assert!(response.robots_allowed);
+        assert!(response.headers.as_ref().unwrap().nofollow);
+        assert_eq!(response.denied_by, DeniedBy::None as i32);
+    }
+}
+#[tokio::test]
+async fn test_can_crawl_skip_header_check() {
+    let mock_server = MockServer::start().await;
+    mount_robots(&mock_server, "User-agent: *\nAllow: /").await;
+    Mock::given(method("HEAD"))
+        .and(path("/page.html"))
+        .respond_with(ResponseTemplate::new(200).insert_header("x-robots-tag", "noindex"))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let response = service
+        .can_crawl(Request::new(CanCrawlRequest {
+            target_url: format!("http://{}/page.html", mock_server.address()),
+            user_agent: "MyBot".to_string(),
+            skip_header_check: true,
+        }))
+        .await
+        .unwrap();
+    let response = response.get_ref();
+
+    assert!(response.allowed);
+    assert!(response.headers.is_none());
+    assert_eq!(response.denied_by, DeniedBy::None as i32);
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "server")]
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::parser::parse;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::GetRobotsRequest;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use sha2::{Digest, Sha256};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const MESSY_ROBOTS_TXT: &str = include_str!("fixtures/messy_robots.txt");
+
+const CANONICAL: &str = "\
+User-agent: BadBot
+User-agent: OtherBot
+Disallow: /
+
+User-agent: *
+Allow: /public
+Disallow: /private
+Disallow: /private
+Crawl-delay: 2.5
+
+User-agent: EmptyBot
+Disallow:
+
+User-agent: GoodBot
+Allow: /
+
+Sitemap: https://example.com/sitemap.xml
+Sitemap: https://example.com/news.xml
+";
+
+#[tokio::test]
+async fn test_canonical_text_of_messy_robots_txt() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(MESSY_ROBOTS_TXT))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let url = format!("http://{}/", mock_server.address());
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+
+    let robots = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: url.clone(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    let canonical = service
+        .get_canonical_robots_text(Request::new(GetRobotsRequest {
+            url,
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert_eq!(canonical.text, CANONICAL);
+    assert_eq!(
+        canonical.checksum,
+        format!("{:x}", Sha256::digest(CANONICAL.as_bytes()))
+    );
+    assert_eq!(canonical.based_on_sha256, robots.checksum);
+    assert_eq!(
+        canonical.based_on_sha256,
+        format!("{:x}", Sha256::digest(MESSY_ROBOTS_TXT.as_bytes()))
+    );
+}
+
+#[test]
+fn test_canonical_text_reads_back_the_same() {
+    let canonical = parse(MESSY_ROBOTS_TXT).canonical_text();
+    let reparsed = parse(&canonical);
+    assert_eq!(reparsed.canonical_text(), canonical);
+    assert!(reparsed.warnings.is_empty());
+    assert_eq!(reparsed.groups.len(), 4);
+}
+
+#[test]
+fn test_canonical_text_of_empty_robots_txt() {
+    assert_eq!(parse("").canonical_text(), "");
+    assert_eq!(parse("# nothing here\n").canonical_text(), "");
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use robots_server::cassette::{CassetteError, CassetteFetcher};
+use robots_server::fetcher::{FetchError, Fetcher, RobotsFetcher};
+use robots_server::robots_data::AccessResult;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use common::origin;
+
+fn cassette_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "robots-server-{}-{name}.cassette.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// An origin whose robots.txt redirects to a file with a `Cache-Control`
+/// header, and a page served with an `X-Robots-Tag`.
+async fn redirecting_origin() -> MockServer {
+    let mock_server = origin(301, "", Duration::ZERO, &[("location", "/real-robots.txt")]).await;
+    Mock::given(method("GET"))
+        .and(path("/real-robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("cache-control", "max-age=600")
+                .set_body_string("User-agent: *\nDisallow: /private\nSitemap: /sitemap.xml\n"),
+        )
+        .mount(&mock_server)
+        .await;
+    Mock::given(path("/page"))
+        .respond_with(ResponseTemplate::new(200).insert_header("x-robots-tag", "noindex"))
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+#[tokio::test]
+async fn test_replay_matches_recording() {
+    let cassette = cassette_path("replay");
+    let mock_server = redirecting_origin().await;
+    let url = format!("http://{}/page", mock_server.address());
+    let recorder = CassetteFetcher::record(&cassette, RobotsFetcher::new());
+    let recorded = recorder.fetch(&url).await.unwrap();
+    let recorded_headers = recorder.fetch_x_robots_tag(&url).await.unwrap();
+    assert_eq!(recorded.access_result, AccessResult::Success);
+    assert!(!recorded.is_allowed("MyBot", "/private"));
+    drop(mock_server);
+
+    let player = CassetteFetcher::replay(&cassette).unwrap();
+    let replayed = player.fetch(&url).await.unwrap();
+    assert_eq!(
+        serde_json::to_value(&replayed).unwrap(),
+        serde_json::to_value(&recorded).unwrap()
+    );
+    assert_eq!(replayed.max_age, recorded.max_age);
+    let replayed_headers = player.fetch_x_robots_tag(&url).await.unwrap();
+    assert_eq!(replayed_headers.values, recorded_headers.values);
+    assert_eq!(replayed_headers.values, ["noindex"]);
+    // Served again from the same recording
+    assert_eq!(
+        player.fetch_text(&url).await.unwrap().body,
+        "User-agent: *\nDisallow: /private\nSitemap: /sitemap.xml\n"
+    );
+}
+
+#[tokio::test]
+async fn test_replay_rejects_unrecorded_hosts() {
+    let cassette = cassette_path("unrecorded");
+    let mock_server = origin(404, "", Duration::ZERO, &[]).await;
+    let url = format!("http://{}/", mock_server.address());
+    let recorder = CassetteFetcher::record(&cassette, RobotsFetcher::new());
+    assert_eq!(
+        recorder.fetch(&url).await.unwrap_err(),
+        FetchError::Unavailable(404)
+    );
+    drop(mock_server);
+
+    let player = CassetteFetcher::replay(&cassette).unwrap();
+    assert_eq!(
+        player.fetch(&url).await.unwrap_err(),
+        FetchError::Unavailable(404)
+    );
+    assert!(matches!(
+        player.fetch("https://unrecorded.example.com/").await,
+        Err(FetchError::Unreachable(_))
+    ));
+
+    let contents = std::fs::read_to_string(&cassette).unwrap();
+    std::fs::write(
+        &cassette,
+        contents.replacen("\"version\": 1", "\"version\": 99", 1),
+    )
+    .unwrap();
+    assert!(matches!(
+        CassetteFetcher::replay(&cassette),
+        Err(CassetteError::Version { found: 99, .. })
+    ));
+}
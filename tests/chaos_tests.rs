@@ -0,0 +1,137 @@
+#![cfg(feature = "test-support")]
+
+use std::time::{Duration, Instant};
+
+use robots_server::cached_robots::CachedRobots;
+use robots_server::chaos::{Chaos, ChaosCache, ChaosFetcher};
+use robots_server::config::{ChaosConfig, Config};
+use robots_server::metrics;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::AccessResult;
+use robots_server::test_support::{
+    FakeFetcher, InMemoryCache, RobotsDataBuilder, TestServer, spawn_server,
+};
+
+const URL: &str = "http://example.com/page";
+
+fn scrape(series: &str) -> f64 {
+    metrics::gather_text()
+        .lines()
+        .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+        .map_or(0.0, |value| value.parse().unwrap())
+}
+
+fn injected(fault: &str) -> f64 {
+    scrape(&format!(r#"robots_chaos_faults_total{{fault="{fault}"}}"#))
+}
+
+/// A server whose origin allows everything, behind faults from `config`.
+async fn server(config: ChaosConfig) -> (TestServer, InMemoryCache<String, CachedRobots>) {
+    let fetcher = FakeFetcher::new();
+    fetcher.serve(
+        "example.com",
+        RobotsDataBuilder::new().group("*").allow("/").build(),
+    );
+    let cache = InMemoryCache::new();
+    let chaos = Chaos::new(config);
+    let service = RobotsServer::new(
+        ChaosCache::new(cache.clone(), chaos.clone()),
+        ChaosFetcher::new(fetcher, chaos),
+    );
+    (spawn_server(service).await, cache)
+}
+
+#[tokio::test]
+async fn test_injected_fetch_timeout() {
+    let before = injected("fetch_timeout");
+    let (server, _) = server(ChaosConfig {
+        fetch_timeout: 1.0,
+        ..Default::default()
+    })
+    .await;
+
+    let response = server.client.is_allowed(URL, "MyBot").await.unwrap();
+
+    assert!(!response.allowed);
+    assert_eq!(response.reason, "robots.txt unreachable");
+    assert!(injected("fetch_timeout") - before >= 1.0);
+}
+
+#[tokio::test]
+async fn test_injected_server_error() {
+    let before = injected("fetch_server_error");
+    let (server, _) = server(ChaosConfig {
+        fetch_server_error: 1.0,
+        ..Default::default()
+    })
+    .await;
+
+    let response = server.client.get_robots_txt(URL).await.unwrap();
+
+    assert_eq!(response.access_result, AccessResult::Unreachable as i32);
+    assert_eq!(response.http_status_code, 503);
+    assert!(injected("fetch_server_error") - before >= 1.0);
+}
+
+#[tokio::test]
+async fn test_injected_latency() {
+    let (server, _) = server(ChaosConfig {
+        fetch_latency: 1.0,
+        fetch_latency_ms: 200,
+        ..Default::default()
+    })
+    .await;
+
+    let start = Instant::now();
+    let response = server.client.is_allowed(URL, "MyBot").await.unwrap();
+
+    assert!(response.allowed);
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn test_injected_cache_get_error() {
+    let before = injected("cache_get_error");
+    let (server, _) = server(ChaosConfig {
+        cache_get_error: 1.0,
+        ..Default::default()
+    })
+    .await;
+
+    // An unavailable cache is passed over and the origin asked instead
+    let response = server.client.is_allowed(URL, "MyBot").await.unwrap();
+
+    assert!(response.allowed);
+    assert!(injected("cache_get_error") - before >= 1.0);
+}
+
+#[tokio::test]
+async fn test_injected_cache_set_error() {
+    let (server, cache) = server(ChaosConfig {
+        cache_set_error: 1.0,
+        ..Default::default()
+    })
+    .await;
+
+    // Failed writes are logged and the fetched data is still used
+    let response = server.client.is_allowed(URL, "MyBot").await.unwrap();
+
+    assert!(response.allowed);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_chaos_probabilities_validated() {
+    let config = Config {
+        chaos: Some(ChaosConfig {
+            fetch_timeout: 1.5,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        config.validate().unwrap_err().to_string(),
+        "Invalid config: chaos probabilities must be between 0 and 1"
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::time::Duration;
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::fetcher::{Fetcher, RobotsFetcher};
+use robots_server::robots_data::{AccessResult, RuleType};
+use robots_server::{CheckError, check_url, check_url_with};
+
+use common::{fetches, origin, origin_ok};
+
+#[tokio::test]
+async fn test_check_url_one_shot() {
+    let mock_server = origin_ok("User-agent: *\nDisallow: /private\nAllow: /private/ok").await;
+    let base = format!("http://{}", mock_server.address());
+
+    let decision = check_url(&format!("{base}/private/page"), "MyBot")
+        .await
+        .unwrap();
+    assert!(!decision.allowed);
+    assert_eq!(decision.reason, "Disallow: /private");
+    assert_eq!(decision.rule.unwrap().rule_type, RuleType::Disallow);
+    assert_eq!(decision.access_result, AccessResult::Success);
+
+    let decision = check_url(&format!("{base}/private/ok"), "MyBot")
+        .await
+        .unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.reason, "Allow: /private/ok");
+
+    let decision = check_url(&format!("{base}/public"), "MyBot").await.unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.reason, "no matching rule");
+    assert!(decision.rule.is_none());
+}
+
+#[tokio::test]
+async fn test_check_url_failed_fetches() {
+    let missing = origin(404, "", Duration::ZERO, &[]).await;
+    let decision = check_url(&format!("http://{}/page", missing.address()), "MyBot")
+        .await
+        .unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.access_result, AccessResult::Unavailable);
+
+    let failing = origin(503, "", Duration::ZERO, &[]).await;
+    let decision = check_url(&format!("http://{}/page", failing.address()), "MyBot")
+        .await
+        .unwrap();
+    assert!(!decision.allowed);
+    assert_eq!(decision.access_result, AccessResult::Unreachable);
+
+    assert!(matches!(
+        check_url("ftp://example.com/", "MyBot").await,
+        Err(CheckError::InvalidUrl(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_check_url_with_cache() {
+    let mock_server = origin_ok("User-agent: *\nDisallow: /a").await;
+    let fetcher = RobotsFetcher::new();
+    let cache = MokaCache::<String, CachedRobots>::new();
+
+    for page in ["a", "b", "a/c"] {
+        let url = format!("http://{}/{page}", mock_server.address());
+        let decision = check_url_with(&fetcher, Some(&cache), &url, "MyBot")
+            .await
+            .unwrap();
+        assert_eq!(decision.allowed, page == "b", "{page}");
+    }
+    assert_eq!(fetcher.stats().total(), 1);
+    assert_eq!(fetches(&mock_server).await, 1);
+}
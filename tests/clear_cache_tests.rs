@@ -0,0 +1,81 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::ClearCacheRequest;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use tonic::{Code, Request};
+
+use common::{ROBOTS_TXT, fetches, get_robots, origin_ok};
+
+fn server(allow_clear_cache: bool) -> RobotsServer<MokaCache<String, CachedRobots>, RobotsFetcher> {
+    RobotsServer::builder()
+        .cache(MokaCache::new())
+        .fetcher(RobotsFetcher::new())
+        .config(ConfigHandle::new(RuntimeConfig {
+            allow_clear_cache,
+            ..Default::default()
+        }))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_clear_cache_refetches() {
+    let origins = [origin_ok(ROBOTS_TXT).await, origin_ok(ROBOTS_TXT).await];
+    let service = server(true);
+    for origin in &origins {
+        service
+            .get_robots_txt(Request::new(get_robots(origin)))
+            .await
+            .unwrap();
+        service
+            .get_robots_txt(Request::new(get_robots(origin)))
+            .await
+            .unwrap();
+        assert_eq!(fetches(origin).await, 1);
+    }
+
+    let response = service
+        .clear_cache(Request::new(ClearCacheRequest {}))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.entries_dropped, Some(2));
+
+    for origin in &origins {
+        service
+            .get_robots_txt(Request::new(get_robots(origin)))
+            .await
+            .unwrap();
+        assert_eq!(fetches(origin).await, 2);
+    }
+}
+
+#[tokio::test]
+async fn test_clear_cache_disabled_by_default() {
+    let origin = origin_ok(ROBOTS_TXT).await;
+    let service = server(false);
+    service
+        .get_robots_txt(Request::new(get_robots(&origin)))
+        .await
+        .unwrap();
+
+    let status = service
+        .clear_cache(Request::new(ClearCacheRequest {}))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+
+    service
+        .get_robots_txt(Request::new(get_robots(&origin)))
+        .await
+        .unwrap();
+    assert_eq!(fetches(&origin).await, 1);
+    assert!(!RuntimeConfig::default().allow_clear_cache);
+}
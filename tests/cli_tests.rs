@@ -0,0 +1,520 @@
+#![cfg(feature = "server")]
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+use robots_server::cli::{
+    CheckArgs, Cli, Command, Decision, DumpArgs, FetchArgs, ServeArgs, check, dump, parse_size,
+};
+use robots_server::config::{BindFailure, Config, RuntimeConfig, TlsConfig};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn check_args(url: String, user_agent: &str, json: bool) -> CheckArgs {
+    CheckArgs {
+        url,
+        user_agent: user_agent.to_string(),
+        json,
+        fetch: FetchArgs::default(),
+    }
+}
+
+fn dump_args(url: Option<String>, json: bool, raw: bool) -> DumpArgs {
+    DumpArgs {
+        url,
+        json,
+        raw,
+        from_file: None,
+        fetch: FetchArgs::default(),
+    }
+}
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/robots.txt");
+
+async fn mount_robots(mock_server: &MockServer, body: &str) {
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_check_disallowed_reports_rule() {
+    let mock_server = MockServer::start().await;
+    mount_robots(
+        &mock_server,
+        "User-agent: MyBot\nAllow: /private/ok\nDisallow: /private\n",
+    )
+    .await;
+
+    let url = format!("http://{}/private/page", mock_server.address());
+    let mut out = Vec::new();
+    let decision = check(
+        &check_args(url, "MyBot/1.0", false),
+        RuntimeConfig::default(),
+        &mut out,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(decision, Decision::Disallowed);
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("DISALLOWED"));
+    assert!(out.contains("Group:      User-agent: MyBot"));
+    assert!(out.contains("Rule:       Disallow: /private"));
+}
+#[tokio::test]
+async fn test_check_json_output() {
+    let mock_server = MockServer::start().await;
+    mount_robots(
+        &mock_server,
+        "User-agent: *\nDisallow: /private\nAllow: /private/ok\n",
+    )
+    .await;
+
+    let url = format!("http://{}/private/ok?x=1", mock_server.address());
+    let mut out = Vec::new();
+    let decision = check(
+        &check_args(url.clone(), "MyBot", true),
+        RuntimeConfig::default(),
+        &mut out,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(decision, Decision::Allowed);
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["url"], url);
+    assert_eq!(json["allowed"], true);
+    assert_eq!(json["http_status_code"], 200);
+    assert_eq!(json["matched_user_agents"], serde_json::json!(["*"]));
+    assert_eq!(json["matched_rule"]["directive"], "Allow");
+    assert_eq!(json["matched_rule"]["path_pattern"], "/private/ok");
+}
+#[tokio::test]
+async fn test_check_no_matching_rule() {
+    let mock_server = MockServer::start().await;
+    mount_robots(&mock_server, "User-agent: *\nDisallow: /private\n").await;
+
+    let url = format!("http://{}/public", mock_server.address());
+    let mut out = Vec::new();
+    let decision = check(
+        &check_args(url, "MyBot", true),
+        RuntimeConfig::default(),
+        &mut out,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(decision, Decision::Allowed);
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert!(json["matched_rule"].is_null());
+}
+#[tokio::test]
+async fn test_check_unreachable_is_disallowed() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&mock_server)
+        .await;
+
+    let url = format!("http://{}/page", mock_server.address());
+    let mut out = Vec::new();
+    let decision = check(
+        &check_args(url, "MyBot", false),
+        RuntimeConfig::default(),
+        &mut out,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(decision, Decision::Disallowed);
+    assert!(String::from_utf8(out).unwrap().contains("unreachable"));
+}
+#[tokio::test]
+async fn test_check_sends_fetch_user_agent() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .and(header("user-agent", "MyCrawler/2.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut args = check_args(
+        format!("http://{}/page", mock_server.address()),
+        "MyBot",
+        false,
+    );
+    args.fetch.fetch_user_agent = Some("MyCrawler/2.0".to_string());
+    let mut config = RuntimeConfig::default();
+    args.fetch.apply(&mut config);
+
+    let decision = check(&args, config, &mut Vec::new()).await.unwrap();
+    assert_eq!(decision, Decision::Disallowed);
+}
+#[tokio::test]
+async fn test_check_invalid_url_errors() {
+    let result = check(
+        &check_args("ftp://example.com/".to_string(), "MyBot", false),
+        RuntimeConfig::default(),
+        &mut Vec::new(),
+    )
+    .await;
+    assert!(result.is_err());
+}
+#[tokio::test]
+async fn test_dump_fixture_json_snapshot() {
+    let mut args = dump_args(None, true, false);
+    args.from_file = Some(PathBuf::from(FIXTURE));
+    let mut out = Vec::new();
+    dump(&args, RuntimeConfig::default(), &mut out)
+        .await
+        .unwrap();
+
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "target_url": "",
+            "robots_txt_url": FIXTURE,
+            "access_result": "ACCESS_RESULT_UNSPECIFIED",
+            "http_status_code": 0,
+            "groups": [
+                {
+                    "user_agents": ["Googlebot", "Bingbot"],
+                    "rules": [
+                        {"rule_type": "RULE_TYPE_DISALLOW", "path_pattern": "/private/", "line_number": 4},
+                        {"rule_type": "RULE_TYPE_ALLOW", "path_pattern": "/private/public$", "line_number": 5}
+                    ],
+                    "crawl_delay_seconds": 2.0
+                },
+                {
+                    "user_agents": ["*"],
+                    "rules": [
+                        {"rule_type": "RULE_TYPE_DISALLOW", "path_pattern": "/tmp", "line_number": 9}
+                    ],
+                    "crawl_delay_seconds": null
+                }
+            ],
+            "sitemaps": ["https://example.com/sitemap.xml"],
+            "sitemap_line_numbers": [13],
+            "content_length_bytes": 214,
+            "truncated": false,
+            "warnings": [
+                {"line": 10, "message": "Missing ':' separator in 'Nonsense line'"},
+                {"line": 11, "message": "Unknown directive 'foo'"}
+            ],
+            "checksum": "512b0aeb9964fa646709049370a27bedcac02e452537b9bf26f776422ec680ea"
+        })
+    );
+}
+#[tokio::test]
+async fn test_dump_fixture_raw() {
+    let mut args = dump_args(None, false, true);
+    args.from_file = Some(PathBuf::from(FIXTURE));
+    let mut out = Vec::new();
+    dump(&args, RuntimeConfig::default(), &mut out)
+        .await
+        .unwrap();
+
+    assert_eq!(out, std::fs::read(FIXTURE).unwrap());
+}
+#[tokio::test]
+async fn test_dump_url_json_is_deterministic() {
+    let mock_server = MockServer::start().await;
+    let body = std::fs::read_to_string(FIXTURE).unwrap();
+    mount_robots(&mock_server, &body).await;
+
+    let url = format!("http://{}/", mock_server.address());
+    let args = dump_args(Some(url.clone()), true, false);
+    let mut first = Vec::new();
+    dump(&args, RuntimeConfig::default(), &mut first)
+        .await
+        .unwrap();
+    let mut second = Vec::new();
+    dump(&args, RuntimeConfig::default(), &mut second)
+        .await
+        .unwrap();
+    assert_eq!(first, second);
+
+    let json: serde_json::Value = serde_json::from_slice(&first).unwrap();
+    assert_eq!(json["target_url"], url);
+    assert_eq!(json["access_result"], "ACCESS_RESULT_SUCCESS");
+    assert_eq!(json["http_status_code"], 200);
+    assert_eq!(json["groups"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        json["checksum"],
+        "512b0aeb9964fa646709049370a27bedcac02e452537b9bf26f776422ec680ea"
+    );
+}
+#[tokio::test]
+async fn test_dump_url_raw() {
+    let mock_server = MockServer::start().await;
+    mount_robots(&mock_server, "User-agent: *\nDisallow: /x # note\n").await;
+
+    let args = dump_args(
+        Some(format!("http://{}/", mock_server.address())),
+        false,
+        true,
+    );
+    let mut out = Vec::new();
+    dump(&args, RuntimeConfig::default(), &mut out)
+        .await
+        .unwrap();
+    assert_eq!(out, b"User-agent: *\nDisallow: /x # note\n");
+}
+#[tokio::test]
+async fn test_dump_unavailable() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+
+    let args = dump_args(
+        Some(format!("http://{}/", mock_server.address())),
+        true,
+        false,
+    );
+    let mut out = Vec::new();
+    dump(&args, RuntimeConfig::default(), &mut out)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["access_result"], "ACCESS_RESULT_UNAVAILABLE");
+    assert_eq!(json["http_status_code"], 404);
+}
+
+fn serve_args(args: &[&str]) -> ServeArgs {
+    let cli = Cli::try_parse_from([&["robots-server", "serve"], args].concat()).unwrap();
+    match cli.command {
+        Some(Command::Serve(args)) => args,
+        other => panic!("expected serve, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_serve_flags_parse() {
+    let args = serve_args(&[
+        "--bind",
+        "0.0.0.0:50051",
+        "--cache-ttl",
+        "6h",
+        "--header-cache-ttl",
+        "90s",
+        "--negative-cache-ttl",
+        "10m",
+        "--stale-while-revalidate",
+        "1h",
+        "--warm-from",
+        "seeds.txt",
+        "--cache-snapshot",
+        "cache.json",
+        "--fetch-timeout",
+        "1500ms",
+        "--max-robots-size",
+        "1MiB",
+        "--max-concurrent-requests",
+        "64",
+        "--cache-max-entries",
+        "5000",
+        "--max-cache-bytes",
+        "64MiB",
+        "--worker-threads",
+        "2",
+        "--max-blocking-threads",
+        "16",
+    ]);
+    assert_eq!(args.bind, vec!["0.0.0.0:50051".parse().unwrap()]);
+    assert_eq!(args.cache_ttl, Some(Duration::from_secs(6 * 60 * 60)));
+    assert_eq!(args.header_cache_ttl, Some(Duration::from_secs(90)));
+    assert_eq!(args.negative_cache_ttl, Some(Duration::from_secs(10 * 60)));
+    assert_eq!(
+        args.stale_while_revalidate,
+        Some(Duration::from_secs(60 * 60))
+    );
+    assert_eq!(args.fetch_timeout, Some(Duration::from_millis(1500)));
+    assert_eq!(args.max_robots_size, Some(1024 * 1024));
+    assert_eq!(args.max_cache_bytes, Some(64 << 20));
+    assert_eq!(args.warm_from, Some(PathBuf::from("seeds.txt")));
+    assert_eq!(args.cache_snapshot, Some(PathBuf::from("cache.json")));
+    // Both capacity flags together are rejected when the config is loaded
+    assert!(Config::load_with_defaults(None, &args.config_defaults()).is_err());
+    let args = ServeArgs {
+        max_cache_bytes: None,
+        ..args
+    };
+    let config = Config::load_with_defaults(None, &args.config_defaults()).unwrap();
+    assert_eq!(config.max_concurrent_requests, 64);
+    assert_eq!(config.cache_max_entries, Some(5000));
+    assert_eq!(config.worker_threads, Some(2));
+    assert_eq!(config.max_blocking_threads, Some(16));
+    assert_eq!(config.cache_snapshot, Some(PathBuf::from("cache.json")));
+
+    assert!(Cli::try_parse_from(["robots-server", "serve", "--cache-ttl", "soon"]).is_err());
+    assert!(Cli::try_parse_from(["robots-server", "serve", "--bind", "localhost"]).is_err());
+}
+
+#[test]
+fn test_listen_flags() {
+    let config = |args: &[&str]| {
+        Config::load_with_defaults(None, &serve_args(args).config_defaults())
+            .unwrap()
+            .listen_addr
+    };
+    assert_eq!(config(&[]), "[::1]:50051".parse().unwrap());
+    assert_eq!(
+        config(&["--listen-addr", "0.0.0.0:50051"]),
+        "0.0.0.0:50051".parse().unwrap()
+    );
+    assert_eq!(config(&["--port", "50052"]), "[::1]:50052".parse().unwrap());
+    assert_eq!(
+        config(&["--bind", "0.0.0.0:50051", "--port", "8080"]),
+        "0.0.0.0:8080".parse().unwrap()
+    );
+
+    let config = Config::load_with_defaults(
+        None,
+        &serve_args(&[
+            "--listen",
+            "0.0.0.0:50051",
+            "--listen",
+            "[::]:50051",
+            "--port",
+            "8080",
+        ])
+        .config_defaults(),
+    )
+    .unwrap();
+    assert_eq!(
+        config.listen_addrs(),
+        [
+            "0.0.0.0:8080".parse().unwrap(),
+            "[::]:8080".parse().unwrap()
+        ]
+    );
+    assert_eq!(config.bind_failure, BindFailure::Error);
+    let config = Config::load_with_defaults(
+        None,
+        &serve_args(&["--bind-failure", "warn"]).config_defaults(),
+    )
+    .unwrap();
+    assert_eq!(config.bind_failure, BindFailure::Warn);
+
+    assert!(Cli::try_parse_from(["robots-server", "serve", "--port", "70000"]).is_err());
+    assert!(Cli::try_parse_from(["robots-server", "serve", "--listen-addr", "::1"]).is_err());
+}
+
+#[test]
+fn test_tls_flags() {
+    let config = Config::load_with_defaults(
+        None,
+        &serve_args(&["--tls-cert", "cert.pem", "--tls-key", "key.pem"]).config_defaults(),
+    )
+    .unwrap();
+    assert_eq!(
+        config.tls,
+        Some(TlsConfig {
+            cert: "cert.pem".into(),
+            key: "key.pem".into(),
+            client_ca: None,
+        })
+    );
+    let config = Config::load_with_defaults(
+        None,
+        &serve_args(&[
+            "--tls-cert",
+            "cert.pem",
+            "--tls-key",
+            "key.pem",
+            "--tls-client-ca",
+            "ca.pem",
+        ])
+        .config_defaults(),
+    )
+    .unwrap();
+    assert_eq!(config.tls.unwrap().client_ca, Some("ca.pem".into()));
+
+    assert!(Cli::try_parse_from(["robots-server", "serve", "--tls-cert", "cert.pem"]).is_err());
+    assert!(Cli::try_parse_from(["robots-server", "serve", "--tls-key", "key.pem"]).is_err());
+    assert!(Cli::try_parse_from(["robots-server", "serve", "--tls-client-ca", "ca.pem"]).is_err());
+}
+
+#[test]
+fn test_log_filter_flag() {
+    let cli =
+        Cli::try_parse_from(["robots-server", "--log-filter", "robots_server=debug"]).unwrap();
+    assert_eq!(cli.log_filter.as_deref(), Some("robots_server=debug"));
+    let cli = Cli::try_parse_from(["robots-server", "serve", "--log-filter", "warn"]).unwrap();
+    assert_eq!(cli.log_filter.as_deref(), Some("warn"));
+    assert!(Cli::try_parse_from(["robots-server", "--log-filter", "robots_server=loud"]).is_err());
+}
+
+#[test]
+fn test_parse_size_units() {
+    assert_eq!(parse_size("512"), Ok(512));
+    assert_eq!(parse_size("64KB"), Ok(64_000));
+    assert_eq!(parse_size("64kib"), Ok(64 * 1024));
+    assert_eq!(parse_size("2 MB"), Ok(2_000_000));
+    assert_eq!(parse_size("1GiB"), Ok(1 << 30));
+    assert!(parse_size("MiB").is_err());
+    assert!(parse_size("10 parsecs").is_err());
+    assert!(parse_size("99999999999999GiB").is_err());
+}
+
+#[test]
+fn test_serve_flags_merge_below_config_file() {
+    let args = serve_args(&[
+        "--bind",
+        "0.0.0.0:7000",
+        "--cache-ttl",
+        "1h",
+        "--fetch-timeout",
+        "5s",
+    ]);
+
+    // With no file the flags apply over the built-in defaults
+    let config = Config::load_with_defaults(None, &args.config_defaults()).unwrap();
+    assert_eq!(config.listen_addr, "0.0.0.0:7000".parse().unwrap());
+    assert_eq!(config.runtime.cache_ttl_secs, 3600);
+    assert_eq!(config.runtime.fetch_timeout_secs, 5.0);
+    assert_eq!(
+        config.runtime.header_cache_ttl_secs,
+        RuntimeConfig::default().header_cache_ttl_secs
+    );
+
+    // A setting in the file wins over the same flag
+    let path = std::env::temp_dir().join(format!(
+        "robots-server-{}-serve-flags.toml",
+        std::process::id()
+    ));
+    std::fs::write(&path, "cache_ttl_secs = 60\nmax_robots_txt_bytes = 4096\n").unwrap();
+    let config = Config::load_with_defaults(Some(&path), &args.config_defaults()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.listen_addr, "0.0.0.0:7000".parse().unwrap());
+    assert_eq!(config.runtime.cache_ttl_secs, 60);
+    assert_eq!(config.runtime.fetch_timeout_secs, 5.0);
+    assert_eq!(config.runtime.max_robots_txt_bytes, 4096);
+}
+
+#[test]
+fn test_serve_flags_are_validated() {
+    let args = serve_args(&["--cache-ttl", "500ms"]);
+    assert!(Config::load_with_defaults(None, &args.config_defaults()).is_err());
+    let args = serve_args(&["--max-robots-size", "0"]);
+    assert!(Config::load_with_defaults(None, &args.config_defaults()).is_err());
+    let args = serve_args(&["--startup-probe-url", "ftp://example.com/"]);
+    assert!(Config::load_with_defaults(None, &args.config_defaults()).is_err());
+    let args = serve_args(&["--startup-probe-url", "https://example.com/"]);
+    let config = Config::load_with_defaults(None, &args.config_defaults()).unwrap();
+    assert_eq!(
+        config.self_check_probe_url.as_deref(),
+        Some("https://example.com/")
+    );
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "test-support")]
+
+use robots_server::client::ClientError;
+use robots_server::client_cli::{ClientCli, ClientCommand, run};
+use robots_server::service::RobotsServer;
+use robots_server::test_support::{FakeFetcher, InMemoryCache, RobotsDataBuilder, spawn_server};
+use tonic::Code;
+
+fn cli(endpoint: &str, command: ClientCommand) -> ClientCli {
+    ClientCli {
+        endpoint: endpoint.to_string(),
+        timeout: 5.0,
+        api_key: None,
+        command,
+    }
+}
+
+#[tokio::test]
+async fn test_client_commands_against_server() {
+    let fetcher = FakeFetcher::new();
+    fetcher.serve(
+        "example.com",
+        RobotsDataBuilder::new()
+            .group("*")
+            .disallow("/admin")
+            .build(),
+    );
+    let server = spawn_server(RobotsServer::new(InMemoryCache::new(), fetcher)).await;
+    let endpoint = server.endpoint();
+    let cli = |command| cli(&endpoint, command);
+
+    let url = "http://example.com/".to_string();
+
+    let robots = run(&cli(ClientCommand::GetRobots { url: url.clone() }))
+        .await
+        .unwrap();
+    assert_eq!(robots["http_status_code"], 200);
+    assert_eq!(robots["access_result"], "ACCESS_RESULT_SUCCESS");
+    assert_eq!(robots["data_source"], "DATA_SOURCE_FRESH_FETCH");
+    assert_eq!(robots["groups"][0]["rules"][0]["path_pattern"], "/admin");
+
+    let allowed = run(&cli(ClientCommand::IsAllowed {
+        url: format!("{url}admin/page"),
+        user_agent: "MyBot".to_string(),
+    }))
+    .await
+    .unwrap();
+    assert_eq!(allowed["allowed"], false);
+    assert_eq!(allowed["data_source"], "DATA_SOURCE_CACHE");
+
+    let invalidated = run(&cli(ClientCommand::Invalidate { url: url.clone() }))
+        .await
+        .unwrap();
+    assert_eq!(invalidated["existed"], true);
+    assert_eq!(
+        invalidated["robots_txt_url"],
+        "http://example.com/robots.txt"
+    );
+    let invalidated = run(&cli(ClientCommand::Invalidate { url })).await.unwrap();
+    assert_eq!(invalidated["existed"], false);
+
+    let error = run(&cli(ClientCommand::GetRobots {
+        url: "not a url".to_string(),
+    }))
+    .await
+    .unwrap_err();
+    match error {
+        ClientError::Status(status) => assert_eq!(status.code(), Code::InvalidArgument),
+        other => panic!("expected a gRPC status, got {other}"),
+    }
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_client_connection_error() {
+    let args = cli(
+        "http://[::1]:1",
+        ClientCommand::GetRobots {
+            url: "http://example.com/".to_string(),
+        },
+    );
+    let error = run(&args).await.unwrap_err();
+    assert!(matches!(error, ClientError::Transport(_)));
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "test-support")]
+
+mod common;
+
+use std::time::Duration;
+
+use futures_util::future::join_all;
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::{FetchError, RobotsFetcher};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{AccessResult, GetRobotsRequest};
+use robots_server::test_support::{FakeFetcher, InMemoryCache};
+use tonic::{Code, Request};
+
+use common::{ROBOTS_TXT, fetches, get_robots, origin};
+
+const CALLERS: usize = 50;
+const ORIGIN_DELAY: Duration = Duration::from_millis(200);
+
+#[tokio::test]
+async fn test_concurrent_misses_fetch_once() {
+    let origin = origin(200, ROBOTS_TXT, ORIGIN_DELAY, &[]).await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+
+    let responses =
+        join_all((0..CALLERS).map(|_| service.get_robots_txt(Request::new(get_robots(&origin)))))
+            .await;
+    for response in responses {
+        let response = response.unwrap().into_inner();
+        assert_eq!(response.http_status_code, 200);
+        assert_eq!(response.access_result(), AccessResult::Success);
+    }
+    assert_eq!(fetches(&origin).await, 1);
+}
+
+#[tokio::test]
+async fn test_concurrent_misses_share_negative_result() {
+    let origin = origin(404, "", ORIGIN_DELAY, &[]).await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+
+    let responses =
+        join_all((0..CALLERS).map(|_| service.get_robots_txt(Request::new(get_robots(&origin)))))
+            .await;
+    for response in responses {
+        let response = response.unwrap().into_inner();
+        assert_eq!(response.http_status_code, 404);
+        assert_eq!(response.access_result(), AccessResult::Unavailable);
+    }
+    assert_eq!(fetches(&origin).await, 1);
+}
+
+#[tokio::test]
+async fn test_failed_fetch_is_not_wedged() {
+    let fetcher = FakeFetcher::new();
+    fetcher.fail(
+        "broken.example",
+        FetchError::ParseError("unreadable".to_string()),
+    );
+    let service = RobotsServer::new(MokaCache::new(), fetcher.clone());
+
+    for _ in 0..2 {
+        let status = service
+            .get_robots_txt(Request::new(GetRobotsRequest {
+                url: "https://broken.example/".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Internal);
+    }
+    // Each failure ends its fetch, so the next miss starts another
+    assert_eq!(fetcher.fetch_count("broken.example"), 2);
+}
+
+#[tokio::test]
+async fn test_cancelled_fetches_write_nothing() {
+    let origin = origin(200, ROBOTS_TXT, Duration::from_secs(1), &[]).await;
+    let cache = InMemoryCache::new();
+    let service = RobotsServer::new(cache.clone(), RobotsFetcher::new());
+    let get = || service.get_robots_txt(Request::new(get_robots(&origin)));
+
+    let (response, cancelled) = tokio::join!(get(), async {
+        tokio::time::sleep(ORIGIN_DELAY).await;
+        service.cancel_fetches()
+    });
+    assert_eq!(cancelled, 1);
+    assert_eq!(response.unwrap_err().code(), Code::Unavailable);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(cache.is_empty());
+
+    // Nothing is fetched once fetches are cancelled
+    assert_eq!(get().await.unwrap_err().code(), Code::Unavailable);
+    assert_eq!(fetches(&origin).await, 1);
+}
@@ -0,0 +1,69 @@
+//! Origins and requests shared by the integration tests. Each test binary
+//! uses only some of them.
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+#[cfg(feature = "server")]
+use robots_server::service::robots::v1::GetRobotsRequest;
+#[cfg(feature = "server")]
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// The robots.txt most origins serve.
+pub const ROBOTS_TXT: &str = "User-agent: *\nDisallow: /private\n";
+
+/// The key tests list in `admin_api_keys`.
+pub const ADMIN_KEY: &str = "admin-key";
+
+/// An origin answering `GET /robots.txt` with `status`, `body` and
+/// `headers`, `delay` after each request.
+pub async fn origin(
+    status: u16,
+    body: &str,
+    delay: Duration,
+    headers: &[(&str, &str)],
+) -> MockServer {
+    let mut response = ResponseTemplate::new(status)
+        .set_body_string(body)
+        .set_delay(delay);
+    for (name, value) in headers {
+        response = response.insert_header(*name, *value);
+    }
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(response)
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+/// An origin answering `GET /robots.txt` at once with 200 and `body`.
+pub async fn origin_ok(body: &str) -> MockServer {
+    origin(200, body, Duration::ZERO, &[]).await
+}
+
+/// How many requests `origin` has received.
+pub async fn fetches(origin: &MockServer) -> usize {
+    origin.received_requests().await.unwrap().len()
+}
+
+/// A request for `origin`'s robots.txt.
+#[cfg(feature = "server")]
+pub fn get_robots(origin: &MockServer) -> GetRobotsRequest {
+    GetRobotsRequest {
+        url: format!("http://{}/", origin.address()),
+        ..Default::default()
+    }
+}
+
+/// `request`, carrying [`ADMIN_KEY`].
+#[cfg(feature = "server")]
+pub fn as_admin<T>(mut request: Request<T>) -> Request<T> {
+    request
+        .metadata_mut()
+        .insert("x-api-key", ADMIN_KEY.parse().unwrap());
+    request
+}
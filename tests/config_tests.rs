@@ -0,0 +1,545 @@
+#![cfg(feature = "server")]
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use robots_server::auth::ApiKeyAuth;
+use robots_server::cache::{Cache, MokaCache};
+use robots_server::cli::write_config;
+use robots_server::config::{
+    Config, ConfigHandle, RuntimeConfig, TransportConfig, env_overrides, env_settings,
+};
+use robots_server::fetcher::{FetchError, RobotsFetcher};
+use robots_server::log_format::LogFormat;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::GetRobotsRequest;
+use robots_server::service::robots::v1::robots_service_client::RobotsServiceClient;
+use robots_server::service::robots::v1::robots_service_server::{
+    RobotsService, RobotsServiceServer,
+};
+use toml::Table;
+use tonic::transport::{Channel, Server};
+use tonic::{Code, Request};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn config_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("robots-server-{}-{name}.toml", std::process::id()))
+}
+
+async fn mount_robots(mock_server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /admin"))
+        .mount(mock_server)
+        .await;
+}
+
+fn with_key(url: &str, key: &str) -> Request<GetRobotsRequest> {
+    let mut request = Request::new(GetRobotsRequest {
+        url: url.to_string(),
+        ..Default::default()
+    });
+    request
+        .metadata_mut()
+        .insert("x-api-key", key.parse().unwrap());
+    request
+}
+
+#[test]
+fn test_load_config_file() {
+    let path = config_path("load");
+    std::fs::write(
+        &path,
+        r#"
+listen_addr = "127.0.0.1:6000"
+fetch_timeout_secs = 5
+cache_ttl_secs = 60
+api_keys = ["secret"]
+host_blocklist = ["example.com"]
+
+[host_rate]
+per_second = 2.5
+burst = 3
+"#,
+    )
+    .unwrap();
+
+    let (config, handle) = ConfigHandle::from_file(&path).unwrap();
+    assert_eq!(config.listen_addr, "127.0.0.1:6000".parse().unwrap());
+    let runtime = handle.get();
+    assert_eq!(runtime.fetch_timeout(), Duration::from_secs(5));
+    assert_eq!(runtime.cache_ttl(), Duration::from_secs(60));
+    assert_eq!(
+        runtime.header_cache_ttl(),
+        RuntimeConfig::default().header_cache_ttl()
+    );
+    assert_eq!(runtime.host_rate.per_second, 2.5);
+    assert!(runtime.accepts_api_key("secret"));
+    assert!(!runtime.host_permitted("www.Example.com"));
+    assert!(runtime.host_permitted("example.org"));
+
+    std::fs::remove_file(path).unwrap();
+}
+#[test]
+fn test_unknown_keys_warned() {
+    let path = config_path("unknown-keys");
+    std::fs::write(
+        &path,
+        r#"
+listen_addr = "127.0.0.1:6000"
+log_format = "json"
+cache_ttl_secs = 60
+cache_ttl = "1h"
+
+[host_rate]
+per_second = 2.5
+burts = 3
+
+[access_log]
+path = "access.log"
+rotation = "daily"
+"#,
+    )
+    .unwrap();
+
+    let config = Config::load_layered(Some(&path), &Table::new(), &Table::new()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(config.log_format, Some(LogFormat::Json));
+    assert_eq!(config.runtime.cache_ttl_secs, 60);
+    assert_eq!(
+        config.warnings,
+        [
+            "Ignoring unknown config key 'cache_ttl'",
+            "Ignoring unknown config key 'host_rate.burts'",
+        ]
+    );
+}
+#[test]
+fn test_missing_config_file_uses_defaults() {
+    let path = config_path("never-written");
+    let mut defaults = Table::new();
+    defaults.insert("cache_ttl_secs".into(), 120_i64.into());
+
+    let config = Config::load_layered(Some(&path), &Table::new(), &defaults).unwrap();
+    assert_eq!(config.listen_addr, Config::default().listen_addr);
+    assert_eq!(config.runtime.cache_ttl_secs, 120);
+    assert_eq!(config.warnings.len(), 1);
+    assert!(config.warnings[0].ends_with("not found, using defaults"));
+}
+#[test]
+fn test_environment_overrides_file() {
+    let path = config_path("env");
+    std::fs::write(
+        &path,
+        "cache_ttl_secs = 60
+fetch_timeout_secs = 5
+",
+    )
+    .unwrap();
+    let overrides = env_overrides(
+        [
+            ("ROBOTS_SERVER_CACHE_TTL_SECS", "3600"),
+            ("ROBOTS_SERVER_LISTEN_ADDR", "0.0.0.0:7000"),
+            ("ROBOTS_SERVER_HOST_BLOCKLIST", r#"["blocked.example"]"#),
+            ("ROBOTS_CONFIG", "elsewhere.toml"),
+            ("HOME", "/root"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string())),
+    );
+    assert_eq!(overrides.len(), 3);
+    let mut defaults = Table::new();
+    defaults.insert("listen_addr".into(), "127.0.0.1:6000".to_string().into());
+
+    let config = Config::load_layered(Some(&path), &overrides, &defaults).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(config.listen_addr, "0.0.0.0:7000".parse().unwrap());
+    assert_eq!(config.runtime.cache_ttl_secs, 3600);
+    assert_eq!(config.runtime.fetch_timeout_secs, 5.0);
+    assert_eq!(config.runtime.host_blocklist, ["blocked.example"]);
+    assert!(config.warnings.is_empty());
+}
+#[test]
+fn test_environment_settings() {
+    let (settings, warnings) = env_settings(
+        [
+            ("ROBOTS_CACHE_TTL_SECS", "3600"),
+            ("ROBOTS_FETCH_TIMEOUT_SECS", "2.5"),
+            ("ROBOTS_MAX_BODY_BYTES", "65536"),
+            ("HOME", "/root"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string())),
+    );
+    assert!(warnings.is_empty());
+    let config = Config::load_layered(None, &settings, &Table::new()).unwrap();
+    assert_eq!(config.runtime.cache_ttl_secs, 3600);
+    assert_eq!(config.runtime.fetch_timeout_secs, 2.5);
+    assert_eq!(config.runtime.max_robots_txt_bytes, 65536);
+
+    let (settings, warnings) = env_settings(
+        [
+            ("ROBOTS_CACHE_TTL_SECS", "1h"),
+            ("ROBOTS_FETCH_TIMEOUT_SECS", "-5"),
+            ("ROBOTS_MAX_BODY_BYTES", "0"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string())),
+    );
+    assert!(settings.is_empty());
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings[0].contains("ROBOTS_CACHE_TTL_SECS"));
+    let config = Config::load_layered(None, &settings, &Table::new()).unwrap();
+    assert_eq!(config.runtime, RuntimeConfig::default());
+}
+
+#[tokio::test]
+async fn test_serves_from_config_file() {
+    let mock_server = MockServer::start().await;
+    mount_robots(&mock_server).await;
+    let path = config_path("serve");
+    std::fs::write(
+        &path,
+        "listen_addr = \"[::1]:50072\"\ncache_ttl_secs = 60\nfetch_timeout_secs = 5\n",
+    )
+    .unwrap();
+    let (config, handle) = ConfigHandle::from_file(&path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    let service = RobotsServer::new(
+        MokaCache::with_config(handle.clone()),
+        RobotsFetcher::with_config(handle.clone()),
+    )
+    .with_config(handle);
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let server = Server::builder()
+        .add_service(RobotsServiceServer::new(service))
+        .serve_with_shutdown(config.listen_addr, async {
+            rx.await.ok();
+        });
+    let server_handle = tokio::spawn(server);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = RobotsServiceClient::connect("http://[::1]:50072")
+        .await
+        .unwrap();
+    let response = client
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: format!("http://{}/", mock_server.address()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.http_status_code, 200);
+    assert_eq!(response.groups.len(), 1);
+
+    tx.send(()).unwrap();
+    server_handle.await.unwrap().unwrap();
+}
+#[test]
+fn test_host_allowlist() {
+    let config = RuntimeConfig {
+        host_allowlist: vec!["example.com".to_string()],
+        ..Default::default()
+    };
+    assert!(config.host_permitted("example.com"));
+    assert!(config.host_permitted("shop.example.com"));
+    assert!(!config.host_permitted("notexample.com"));
+}
+#[test]
+fn test_client_allowlist() {
+    let config: RuntimeConfig =
+        toml::from_str(r#"client_allowlist = ["10.0.0.0/8", "2001:db8::/32"]"#).unwrap();
+    assert!(config.client_permitted("10.1.2.3".parse().unwrap()));
+    assert!(config.client_permitted("::ffff:10.1.2.3".parse().unwrap()));
+    assert!(config.client_permitted("2001:db8::1".parse().unwrap()));
+    assert!(!config.client_permitted("192.168.1.1".parse().unwrap()));
+    assert!(config.trust_unix_sockets);
+    assert!(RuntimeConfig::default().client_permitted("192.168.1.1".parse().unwrap()));
+}
+#[test]
+fn test_invalid_reload_keeps_previous_config() {
+    let path = config_path("invalid");
+    std::fs::write(&path, "api_keys = [\"old\"]\n").unwrap();
+    let (_, handle) = ConfigHandle::from_file(&path).unwrap();
+
+    std::fs::write(&path, "api_keys = [\"new\"]\nfetch_timeout_secs = 0\n").unwrap();
+    assert!(handle.reload().is_err());
+    std::fs::write(&path, "api_keys = [\"new\"\n").unwrap();
+    assert!(handle.reload().is_err());
+
+    assert!(handle.get().accepts_api_key("old"));
+    assert!(!handle.get().accepts_api_key("new"));
+
+    std::fs::remove_file(path).unwrap();
+}
+#[tokio::test]
+async fn test_reload_changes_fetch_timeout_and_keeps_cache() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("User-agent: *\nDisallow: /admin")
+                .set_delay(Duration::from_millis(500)),
+        )
+        .mount(&mock_server)
+        .await;
+    let path = config_path("timeout-reload");
+    std::fs::write(&path, "fetch_timeout_secs = 5\n").unwrap();
+    let (_, handle) = ConfigHandle::from_file(&path).unwrap();
+    let fetcher = RobotsFetcher::with_config(handle.clone());
+    let cache = MokaCache::with_config(handle.clone());
+    let url = format!("http://{}/", mock_server.address());
+
+    assert!(fetcher.fetch(&url).await.is_ok());
+    cache
+        .set("warm".to_string(), "entry".to_string())
+        .await
+        .unwrap();
+
+    std::fs::write(&path, "fetch_timeout_secs = 0.1\n").unwrap();
+    handle.reload().unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert!(matches!(
+        fetcher.fetch(&url).await,
+        Err(FetchError::Timeout)
+    ));
+    assert_eq!(
+        cache.get(&"warm".to_string()).await.unwrap().as_deref(),
+        Some("entry")
+    );
+}
+#[test]
+fn test_transport_settings() {
+    let config = Config::default();
+    assert_eq!(config.transport, TransportConfig::default());
+    assert_eq!(config.transport.http2_keepalive_interval(), None);
+
+    let config: Config = toml::from_str(
+        "[transport]\nhttp2_keepalive_interval_secs = 30\nhttp2_keepalive_timeout_secs = 2.5\n\
+         tcp_keepalive_secs = 60\nmax_concurrent_streams = 100\ntimeout_secs = 15\n",
+    )
+    .unwrap();
+    let transport = &config.transport;
+    assert_eq!(
+        transport.http2_keepalive_interval(),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(
+        transport.http2_keepalive_timeout(),
+        Some(Duration::from_millis(2500))
+    );
+    assert_eq!(transport.tcp_keepalive(), Some(Duration::from_secs(60)));
+    assert_eq!(transport.max_concurrent_streams, Some(100));
+    assert_eq!(transport.timeout(), Some(Duration::from_secs(15)));
+    assert_eq!(Config::default().restart_required(&config), ["transport"]);
+}
+
+#[test]
+fn test_restart_required_settings() {
+    let started: Config = toml::from_str("listen_addr = \"[::1]:50051\"\n").unwrap();
+    let reloaded: Config = toml::from_str(
+        "listen_addr = \"0.0.0.0:50051\"\nshutdown_grace_secs = 1\nfetch_timeout_secs = 5\n",
+    )
+    .unwrap();
+    assert_eq!(
+        started.restart_required(&reloaded),
+        ["listen_addr", "shutdown_grace_secs"]
+    );
+    assert!(started.restart_required(&started).is_empty());
+}
+
+#[test]
+fn test_validation_errors() {
+    let load_error = |name: &str, contents: &str| {
+        let path = config_path(name);
+        std::fs::write(&path, contents).unwrap();
+        let error = Config::load(&path).unwrap_err().to_string();
+        std::fs::remove_file(path).unwrap();
+        error
+    };
+
+    assert_eq!(
+        load_error("timeout", "fetch_timeout_secs = 0\n"),
+        "Invalid config: fetch_timeout_secs must be positive"
+    );
+    assert_eq!(
+        load_error(
+            "rotation",
+            "[access_log]\npath = \"access.log\"\nrotation = { max_bytes = 0 }\n"
+        ),
+        "Invalid config: access_log max_bytes must be positive"
+    );
+    assert_eq!(
+        load_error("probe", "self_check_probe_url = \"ftp://example.com/\"\n"),
+        "Invalid config: self_check_probe_url is not a valid URL: Unsupported scheme: ftp"
+    );
+    assert!(
+        load_error("cidr", "client_allowlist = [\"10.0.0.0/33\"]\n")
+            .starts_with("Failed to parse config file: ")
+    );
+    assert!(
+        load_error(
+            "override",
+            "[robots_overrides]\n\"example.com\" = { path = \"/nonexistent/robots.txt\" }\n"
+        )
+        .contains("Failed to read robots.txt override /nonexistent/robots.txt")
+    );
+    assert!(
+        load_error("log_filter", "log_filter = \"robots_server=loud\"\n")
+            .starts_with("Invalid config: log_filter is invalid: ")
+    );
+    assert_eq!(
+        load_error(
+            "keepalive",
+            "[transport]\nhttp2_keepalive_interval_secs = 0\n"
+        ),
+        "Invalid config: transport durations must be positive"
+    );
+    assert_eq!(
+        load_error("streams", "[transport]\nmax_concurrent_streams = 0\n"),
+        "Invalid config: transport max_concurrent_streams must be positive"
+    );
+    assert_eq!(
+        load_error(
+            "capacity",
+            "cache_max_entries = 1000\nmax_cache_bytes = 1000000\n"
+        ),
+        "Invalid config: cache_max_entries and max_cache_bytes cannot both be set"
+    );
+}
+#[test]
+fn test_write_config_redacts_api_keys() {
+    let config: Config = toml::from_str(
+        r#"
+listen_addr = "127.0.0.1:6000"
+api_keys = ["secret"]
+admin_api_keys = ["admin-secret"]
+"#,
+    )
+    .unwrap();
+
+    let mut out = Vec::new();
+    write_config(&config, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(!out.contains("secret"));
+    let printed: Config = toml::from_str(&out).unwrap();
+    assert_eq!(printed.listen_addr, config.listen_addr);
+    assert_eq!(printed.runtime.api_keys, ["REDACTED"]);
+    assert_eq!(printed.runtime.admin_api_keys, ["REDACTED"]);
+    assert_eq!(
+        printed.runtime.cache_ttl_secs,
+        RuntimeConfig::default().cache_ttl_secs
+    );
+}
+#[tokio::test]
+async fn test_reload_updates_host_blocklist() {
+    let mock_server = MockServer::start().await;
+    mount_robots(&mock_server).await;
+    let path = config_path("blocklist");
+    std::fs::write(&path, "host_blocklist = [\"127.0.0.1\"]\n").unwrap();
+    let (_, handle) = ConfigHandle::from_file(&path).unwrap();
+
+    let service =
+        RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(handle.clone());
+    let url = format!("http://{}/", mock_server.address());
+
+    let denied = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: url.clone(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(denied.code(), Code::PermissionDenied);
+
+    std::fs::write(&path, "host_blocklist = []\n").unwrap();
+    handle.reload().unwrap();
+    let response = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url,
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.get_ref().http_status_code, 200);
+
+    std::fs::remove_file(path).unwrap();
+}
+#[tokio::test]
+async fn test_reload_rotates_api_keys_without_dropping_requests() {
+    let mock_server = MockServer::start().await;
+    mount_robots(&mock_server).await;
+    let path = config_path("api-keys");
+    std::fs::write(&path, "api_keys = [\"old-key\", \"stable-key\"]\n").unwrap();
+    let (_, handle) = ConfigHandle::from_file(&path).unwrap();
+
+    let service = RobotsServer::new(
+        MokaCache::with_config(handle.clone()),
+        RobotsFetcher::with_config(handle.clone()),
+    )
+    .with_config(handle.clone());
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let server = Server::builder()
+        .add_service(RobotsServiceServer::with_interceptor(
+            service,
+            ApiKeyAuth::new(handle.clone()),
+        ))
+        .serve_with_shutdown("[::1]:50071".parse().unwrap(), async {
+            rx.await.ok();
+        });
+    let server_handle = tokio::spawn(server);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Channel::from_static("http://[::1]:50071")
+        .connect()
+        .await
+        .unwrap();
+    let url = format!("http://{}/", mock_server.address());
+
+    let mut client = RobotsServiceClient::new(channel.clone());
+    client
+        .get_robots_txt(with_key(&url, "old-key"))
+        .await
+        .unwrap();
+    let status = client
+        .get_robots_txt(with_key(&url, "new-key"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    // A client using a key present in both configs keeps working throughout
+    let background = {
+        let mut client = RobotsServiceClient::new(channel.clone());
+        let url = url.clone();
+        tokio::spawn(async move {
+            for _ in 0..50 {
+                client
+                    .get_robots_txt(with_key(&url, "stable-key"))
+                    .await
+                    .expect("request dropped during reload");
+                tokio::time::sleep(Duration::from_millis(2)).await;
+            }
+        })
+    };
+
+    std::fs::write(&path, "api_keys = [\"new-key\", \"stable-key\"]\n").unwrap();
+    handle.reload().unwrap();
+
+    client
+        .get_robots_txt(with_key(&url, "new-key"))
+        .await
+        .unwrap();
+    let status = client
+        .get_robots_txt(with_key(&url, "old-key"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    background.await.unwrap();
+    tx.send(()).unwrap();
+    server_handle.await.unwrap().unwrap();
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "server")]
+
+use futures_util::StreamExt;
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::politeness::{HostRateLimiter, RateLimit};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{IsAllowedRequest, StreamDecisionsRequest};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn robots_server() -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /admin"))
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+fn service() -> RobotsServer<MokaCache<String, CachedRobots>> {
+    RobotsServer::new(MokaCache::new(), RobotsFetcher::new())
+        .with_host_rate_limiter(HostRateLimiter::new(RateLimit::UNLIMITED))
+}
+
+async fn is_allowed(service: &RobotsServer<MokaCache<String, CachedRobots>>, url: String) {
+    service
+        .is_allowed(Request::new(IsAllowedRequest {
+            target_url: url,
+            user_agent: "MyBot".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_decisions_reports_is_allowed_traffic() {
+    let mock_server = robots_server().await;
+    let service = service();
+    let mut all = service
+        .stream_decisions(Request::new(StreamDecisionsRequest::default()))
+        .await
+        .unwrap()
+        .into_inner();
+    let mut denied = service
+        .stream_decisions(Request::new(StreamDecisionsRequest {
+            host_suffix: String::new(),
+            allowed: Some(false),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    let mut other_host = service
+        .stream_decisions(Request::new(StreamDecisionsRequest {
+            host_suffix: "example.com".to_string(),
+            allowed: None,
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let base = format!("http://{}", mock_server.address());
+    is_allowed(&service, format!("{base}/page")).await;
+    is_allowed(&service, format!("{base}/admin/users")).await;
+
+    let first = all.next().await.unwrap().unwrap();
+    assert_eq!(first.host, "127.0.0.1");
+    assert_eq!(first.user_agent, "MyBot");
+    assert!(first.allowed);
+    assert_eq!(first.reason, "no matching rule");
+    assert!(!first.cache_hit);
+    assert_eq!(first.dropped, 0);
+    assert!(first.timestamp_ms > 0);
+
+    let second = all.next().await.unwrap().unwrap();
+    assert!(!second.allowed);
+    assert_eq!(second.reason, "Disallow: /admin");
+    assert!(second.cache_hit);
+
+    let only_denied = denied.next().await.unwrap().unwrap();
+    assert!(!only_denied.allowed);
+    assert_eq!(only_denied.reason, "Disallow: /admin");
+
+    // Nothing matches the host filter, so the stream has nothing to yield
+    let pending =
+        tokio::time::timeout(std::time::Duration::from_millis(50), other_host.next()).await;
+    assert!(pending.is_err());
+}
+
+#[tokio::test]
+async fn test_stream_decisions_drops_for_slow_consumer() {
+    let mock_server = robots_server().await;
+    let service = service().with_decision_capacity(2);
+    let mut slow = service
+        .stream_decisions(Request::new(StreamDecisionsRequest::default()))
+        .await
+        .unwrap()
+        .into_inner();
+
+    // The serving path never waits on the subscriber
+    let base = format!("http://{}", mock_server.address());
+    for i in 0..5 {
+        is_allowed(&service, format!("{base}/page/{i}")).await;
+    }
+
+    let first = slow.next().await.unwrap().unwrap();
+    assert_eq!(first.dropped, 3);
+    let second = slow.next().await.unwrap().unwrap();
+    assert_eq!(second.dropped, 0);
+}
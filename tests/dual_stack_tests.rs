@@ -0,0 +1,79 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// In the discard-only IPv6 prefix, so nothing answers on it.
+const DEAD_IPV6: &str = "[100::1]:0";
+const LIVE_IPV4: &str = "127.0.0.1:0";
+
+/// Resolves every name to the same addresses, in the given order.
+struct StubResolver(Vec<SocketAddr>);
+
+impl Resolve for StubResolver {
+    fn resolve(&self, _name: Name) -> Resolving {
+        let addrs = self.0.clone();
+        Box::pin(async move { Ok(Box::new(addrs.into_iter()) as Addrs) })
+    }
+}
+
+async fn robots_server() -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+fn fetcher(addrs: &[&str]) -> RobotsFetcher {
+    let config = ConfigHandle::new(RuntimeConfig {
+        fetch_timeout_secs: 10.0,
+        ..Default::default()
+    });
+    let addrs = addrs.iter().map(|addr| addr.parse().unwrap()).collect();
+    RobotsFetcher::with_resolver(config, Arc::new(StubResolver(addrs)))
+}
+
+#[tokio::test]
+async fn test_falls_back_from_dead_preferred_family() {
+    let mock_server = robots_server().await;
+    let fetcher = fetcher(&[DEAD_IPV6, LIVE_IPV4]);
+    let url = format!("http://dual.test:{}/", mock_server.address().port());
+
+    let started = Instant::now();
+    let result = fetcher.fetch(&url).await.unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(result.http_status_code, 200);
+    assert_eq!(fetcher.stats().family_fallbacks(), 1);
+}
+
+#[tokio::test]
+async fn test_preferred_family_answers_first() {
+    let mock_server = robots_server().await;
+    let fetcher = fetcher(&[LIVE_IPV4, DEAD_IPV6]);
+    let url = format!("http://dual.test:{}/", mock_server.address().port());
+
+    let result = fetcher.fetch(&url).await.unwrap();
+
+    assert_eq!(result.http_status_code, 200);
+    assert_eq!(fetcher.stats().family_fallbacks(), 0);
+}
+
+#[test]
+fn test_fallback_delay_must_be_non_negative() {
+    let config = RuntimeConfig {
+        address_fallback_delay_secs: -0.1,
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+    assert!(RuntimeConfig::default().validate().is_ok());
+}
@@ -0,0 +1,204 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetch_queue::{FetchQueue, Priority, QueueFull};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::GetRobotsRequest;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use tonic::metadata::MetadataValue;
+use tonic::{Code, Request, Status};
+use wiremock::MockServer;
+
+use common::{ROBOTS_TXT, get_robots, origin};
+
+const ORIGIN_DELAY: Duration = Duration::from_millis(300);
+
+fn config(max_concurrent_fetches: usize, fetch_queue_depth: usize) -> ConfigHandle {
+    ConfigHandle::new(RuntimeConfig {
+        max_concurrent_fetches,
+        fetch_queue_depth,
+        ..Default::default()
+    })
+}
+
+/// Let spawned tasks run until `queue` has `waiting` fetches waiting.
+async fn until_waiting(queue: &FetchQueue, waiting: usize) {
+    while queue.waiting() != waiting {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn test_higher_priority_goes_first() {
+    let queue = FetchQueue::new(config(1, 10));
+    let running = queue.acquire(Priority::Interactive).await.unwrap();
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let mut handles = Vec::new();
+    for (i, priority) in [
+        Priority::Background,
+        Priority::Batch,
+        Priority::Batch,
+        Priority::Interactive,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let (queue, order) = (queue.clone(), order.clone());
+        handles.push(tokio::spawn(async move {
+            let _permit = queue.acquire(priority).await.unwrap();
+            order.lock().unwrap().push(i);
+        }));
+        until_waiting(&queue, i + 1).await;
+    }
+
+    drop(running);
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    assert_eq!(*order.lock().unwrap(), vec![3, 1, 2, 0]);
+    assert_eq!(queue.running(), 0);
+}
+
+#[tokio::test]
+async fn test_full_queue_refuses_at_once() {
+    let queue = FetchQueue::new(config(1, 1));
+    let running = queue.acquire(Priority::Interactive).await.unwrap();
+    let waiting = tokio::spawn({
+        let queue = queue.clone();
+        async move { queue.acquire(Priority::Batch).await.map(drop) }
+    });
+    until_waiting(&queue, 1).await;
+
+    let refused = queue.acquire(Priority::Interactive).await.err();
+    assert_eq!(refused, Some(QueueFull { depth: 1 }));
+
+    // A caller that gives up frees its place
+    waiting.abort();
+    assert!(waiting.await.unwrap_err().is_cancelled());
+    assert_eq!(queue.waiting(), 0);
+    let next = tokio::spawn({
+        let queue = queue.clone();
+        async move { queue.acquire(Priority::Batch).await.map(drop) }
+    });
+    until_waiting(&queue, 1).await;
+    drop(running);
+    assert_eq!(next.await.unwrap(), Ok(()));
+}
+
+#[tokio::test]
+async fn test_abandoned_turn_passes_on() {
+    let queue = FetchQueue::new(config(1, 10));
+    let running = queue.acquire(Priority::Interactive).await.unwrap();
+    let abandoned = tokio::spawn({
+        let queue = queue.clone();
+        async move { queue.acquire(Priority::Interactive).await.map(drop) }
+    });
+    until_waiting(&queue, 1).await;
+    abandoned.abort();
+    let _ = abandoned.await;
+    assert_eq!(queue.waiting(), 0);
+    let next = tokio::spawn({
+        let queue = queue.clone();
+        async move { queue.acquire(Priority::Batch).await.map(drop) }
+    });
+    until_waiting(&queue, 1).await;
+
+    drop(running);
+    next.await.unwrap().unwrap();
+    assert_eq!(queue.running(), 0);
+}
+
+fn request(origin: &MockServer, priority: Option<&'static str>) -> Request<GetRobotsRequest> {
+    let mut request = Request::new(get_robots(origin));
+    if let Some(priority) = priority {
+        request
+            .metadata_mut()
+            .insert("x-fetch-priority", MetadataValue::from_static(priority));
+    }
+    request
+}
+
+type Service = Arc<RobotsServer<MokaCache<String, CachedRobots>>>;
+
+fn spawn_get(
+    service: &Service,
+    origin: &MockServer,
+    priority: Option<&'static str>,
+    done: &Arc<Mutex<Vec<&'static str>>>,
+    name: &'static str,
+) -> tokio::task::JoinHandle<Result<(), Status>> {
+    let (service, done) = (service.clone(), done.clone());
+    let request = request(origin, priority);
+    tokio::spawn(async move {
+        service.get_robots_txt(request).await?;
+        done.lock().unwrap().push(name);
+        Ok(())
+    })
+}
+
+#[tokio::test]
+async fn test_interactive_requests_overtake_queued_batch_fetches() {
+    let origins = [
+        origin(200, ROBOTS_TXT, ORIGIN_DELAY, &[]).await,
+        origin(200, ROBOTS_TXT, ORIGIN_DELAY, &[]).await,
+        origin(200, ROBOTS_TXT, ORIGIN_DELAY, &[]).await,
+        origin(200, ROBOTS_TXT, ORIGIN_DELAY, &[]).await,
+    ];
+    let service: Service = Arc::new(
+        RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(config(1, 10)),
+    );
+    let done = Arc::new(Mutex::new(Vec::new()));
+
+    let mut handles = vec![spawn_get(&service, &origins[0], None, &done, "first")];
+    tokio::time::sleep(ORIGIN_DELAY / 4).await;
+    for (origin, name) in origins[1..3].iter().zip(["batch-1", "batch-2"]) {
+        handles.push(spawn_get(&service, origin, Some("batch"), &done, name));
+        tokio::time::sleep(ORIGIN_DELAY / 10).await;
+    }
+    handles.push(spawn_get(&service, &origins[3], None, &done, "interactive"));
+
+    for handle in handles {
+        handle.await.unwrap().unwrap();
+    }
+    assert_eq!(
+        *done.lock().unwrap(),
+        vec!["first", "interactive", "batch-1", "batch-2"]
+    );
+}
+
+#[tokio::test]
+async fn test_full_queue_is_unavailable() {
+    let origins = [
+        origin(200, ROBOTS_TXT, ORIGIN_DELAY, &[]).await,
+        origin(200, ROBOTS_TXT, ORIGIN_DELAY, &[]).await,
+        origin(200, ROBOTS_TXT, ORIGIN_DELAY, &[]).await,
+    ];
+    let service: Service = Arc::new(
+        RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(config(1, 1)),
+    );
+    let done = Arc::new(Mutex::new(Vec::new()));
+
+    let first = spawn_get(&service, &origins[0], None, &done, "first");
+    tokio::time::sleep(ORIGIN_DELAY / 4).await;
+    let queued = spawn_get(&service, &origins[1], Some("batch"), &done, "queued");
+    tokio::time::sleep(ORIGIN_DELAY / 10).await;
+
+    let status = service
+        .get_robots_txt(request(&origins[2], None))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert!(done.lock().unwrap().is_empty(), "refused without waiting");
+
+    first.await.unwrap().unwrap();
+    queued.await.unwrap().unwrap();
+}
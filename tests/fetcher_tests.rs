@@ -1,5 +1,10 @@
-use robots_server::fetcher::{FetchError, RobotsFetcher};
-use robots_server::service::robots::AccessResult;
+use std::time::Duration;
+
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::{
+    CappedBody, FetchError, MAX_ROBOTS_TXT_SIZE, MAX_UPSTREAM_HEADER_VALUE, RobotsFetcher,
+};
+use robots_server::robots_data::AccessResult;
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
@@ -243,6 +248,27 @@ async fn test_truncation_no_newlines() {
     assert!(result.truncated, "Should be marked as truncated");
 }
 
+#[tokio::test]
+async fn test_fetch_with_timeout() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("User-agent: *\nDisallow: /\n")
+                .set_delay(Duration::from_secs(2)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = RobotsFetcher::with_timeout(Duration::from_millis(200));
+    let result = fetcher
+        .fetch(&format!("http://{}/", mock_server.address()))
+        .await;
+    assert!(matches!(result, Err(FetchError::Timeout)));
+}
+
 #[tokio::test]
 async fn test_fetch_follows_redirect() {
     let redirect_server = MockServer::start().await;
@@ -298,5 +324,171 @@ async fn test_fetch_too_many_redirects() {
     let url = format!("http://{}/", servers[0].address());
     let result = fetcher.fetch(&url).await;
     // Should fail after 5 redirects (6th redirect exceeds limit)
-    assert!(result.is_err());
+    assert!(matches!(result, Err(FetchError::TooManyRedirects)));
+}
+
+#[tokio::test]
+async fn test_fetch_redirect_loop() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(301).insert_header("location", "/robots.txt"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = RobotsFetcher::new();
+    let url = format!("http://{}/", mock_server.address());
+    let result = fetcher.fetch(&url).await;
+    assert!(matches!(result, Err(FetchError::RedirectLoop)));
+}
+
+#[test]
+fn test_capped_body_cuts_at_last_line_across_chunks() {
+    let line = "Disallow: /a/fairly/long/path/to/fill/the/buffer\n";
+    let body = line.repeat(MAX_ROBOTS_TXT_SIZE / line.len() + 10);
+    let mut reader = CappedBody::new();
+    let mut chunks = 0;
+    for chunk in body.as_bytes().chunks(1000) {
+        chunks += 1;
+        if !reader.push(chunk) {
+            break;
+        }
+    }
+    let (read, truncated) = reader.finish();
+
+    assert!(truncated);
+    assert!(
+        chunks < body.len().div_ceil(1000),
+        "Should stop reading at the limit"
+    );
+    assert!(read.len() <= MAX_ROBOTS_TXT_SIZE);
+    assert!(read.ends_with('\n'));
+    assert_eq!(read.len() % line.len(), 0, "Should keep only whole lines");
+}
+
+#[test]
+fn test_capped_body_without_newlines_ends_at_limit() {
+    let body = "User-agent: bot".repeat(50);
+    for chunk_size in [1, 7, 64, body.len()] {
+        let mut reader = CappedBody::with_limit(100).with_content_length(body.len() as u64);
+        for chunk in body.as_bytes().chunks(chunk_size) {
+            if !reader.push(chunk) {
+                break;
+            }
+        }
+        let (read, truncated) = reader.finish();
+        assert!(truncated, "{chunk_size}");
+        assert_eq!(read, body[..100], "{chunk_size}");
+    }
+
+    // A line ending before the limit is still where the body is cut
+    let mut reader = CappedBody::with_limit(100);
+    reader.push(b"User-agent: *\n");
+    reader.push(body.as_bytes());
+    assert_eq!(reader.finish(), ("User-agent: *\n".to_string(), true));
+
+    // Characters split across chunks are kept whole
+    let mut reader = CappedBody::with_limit(100);
+    for chunk in "Disallow: /caf\u{e9}\n".as_bytes().chunks(1) {
+        assert!(reader.push(chunk));
+    }
+    assert_eq!(
+        reader.finish(),
+        ("Disallow: /caf\u{e9}\n".to_string(), false)
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_keeps_allowlisted_headers() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("User-agent: *\nDisallow: /private")
+                .insert_header("ETag", "\"abc123\"")
+                .insert_header("Last-Modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+                .insert_header("Cache-Control", "max-age=3600")
+                .insert_header("Server", "nginx")
+                .insert_header("X-Powered-By", "PHP/8.3"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = RobotsFetcher::new();
+    let url = format!("http://{}/", mock_server.address());
+    let headers = fetcher.fetch(&url).await.unwrap().upstream_headers;
+
+    assert_eq!(headers["etag"], "\"abc123\"");
+    assert_eq!(headers["last-modified"], "Wed, 21 Oct 2015 07:28:00 GMT");
+    assert_eq!(headers["cache-control"], "max-age=3600");
+    assert_eq!(headers["server"], "nginx");
+    assert!(!headers.contains_key("age"), "not sent");
+    assert!(!headers.contains_key("x-powered-by"), "not allowlisted");
+    assert!(!headers.contains_key("content-type"), "not allowlisted");
+}
+
+#[tokio::test]
+async fn test_fetch_cuts_long_header_values() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("User-agent: *\nDisallow: /private")
+                .insert_header("X-Served-By", "cache-".repeat(100).as_str()),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = RobotsFetcher::with_config(ConfigHandle::new(RuntimeConfig {
+        upstream_headers: vec!["X-Served-By".to_string()],
+        ..Default::default()
+    }));
+    let url = format!("http://{}/", mock_server.address());
+    let headers = fetcher.fetch(&url).await.unwrap().upstream_headers;
+
+    assert_eq!(headers.len(), 1);
+    assert_eq!(headers["x-served-by"].len(), MAX_UPSTREAM_HEADER_VALUE);
+    assert!(headers["x-served-by"].starts_with("cache-cache-"));
+}
+
+#[tokio::test]
+async fn test_fetch_reads_cache_control_max_age() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("User-agent: *\nDisallow: /private")
+                .insert_header("Cache-Control", "public, Max-Age=600"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = RobotsFetcher::new();
+    let url = format!("http://{}/", mock_server.address());
+    let data = fetcher.fetch(&url).await.unwrap();
+
+    assert_eq!(data.max_age, Some(Duration::from_secs(600)));
+}
+
+#[tokio::test]
+async fn test_fetch_429_reports_retry_after() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "120"))
+        .mount(&mock_server)
+        .await;
+
+    let fetcher = RobotsFetcher::new();
+    let url = format!("http://{}/", mock_server.address());
+    let result = fetcher.fetch(&url).await;
+
+    assert_eq!(
+        result.unwrap_err(),
+        FetchError::RateLimited(Some(Duration::from_secs(120)))
+    );
 }
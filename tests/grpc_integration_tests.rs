@@ -1,49 +1,28 @@
-use robots_server::cache::MokaCache;
-use robots_server::fetcher::RobotsFetcher;
-use robots_server::service::robots::{AccessResult, GetRobotsRequest};
-use robots_server::service::{RobotsServer, robots::robots_service_server::RobotsServiceServer};
-use tonic::transport::Server;
-use wiremock::matchers::{method, path};
-use wiremock::{Mock, MockServer, ResponseTemplate};
+#![cfg(feature = "test-support")]
+
+use robots_server::fetcher::FetchError;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::{AccessResult, GetRobotsRequest};
+use robots_server::test_support::{FakeFetcher, InMemoryCache, RobotsDataBuilder, spawn_server};
 
 #[tokio::test]
 async fn test_full_grpc_success() {
-    let mock_server = MockServer::start().await;
-    Mock::given(method("GET"))
-        .and(path("/robots.txt"))
-        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /admin"))
-        .mount(&mock_server)
-        .await;
-
-    let addr = "[::1]:50051".parse().unwrap();
-    let cache = MokaCache::new();
-    let fetcher = RobotsFetcher::new();
-    let service = RobotsServer::new(cache, fetcher);
-
-    let (tx, rx) = tokio::sync::oneshot::channel();
-
-    let server = Server::builder()
-        .add_service(RobotsServiceServer::new(service))
-        .serve_with_shutdown(addr, async {
-            rx.await.ok();
-        });
-
-    let server_handle = tokio::spawn(server);
-
-    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-
-    let channel = tonic::transport::Channel::from_static("http://[::1]:50051")
-        .connect()
-        .await
-        .unwrap();
-
-    let mut client =
-        robots_server::service::robots::robots_service_client::RobotsServiceClient::new(channel);
-
-    let url = format!("http://{}/", mock_server.address());
-    let request = tonic::Request::new(GetRobotsRequest { url });
+    let fetcher = FakeFetcher::new();
+    fetcher.serve(
+        "example.com",
+        RobotsDataBuilder::new()
+            .group("*")
+            .disallow("/admin")
+            .build(),
+    );
+    let cache = InMemoryCache::new();
+    let server = spawn_server(RobotsServer::new(cache.clone(), fetcher.clone())).await;
 
-    let response = client.get_robots_txt(request).await.unwrap();
+    let request = tonic::Request::new(GetRobotsRequest {
+        url: "http://example.com/".to_string(),
+        ..Default::default()
+    });
+    let response = server.grpc_client().get_robots_txt(request).await.unwrap();
 
     assert_eq!(response.get_ref().http_status_code, 200);
     assert_eq!(
@@ -51,7 +30,33 @@ async fn test_full_grpc_success() {
         AccessResult::Success as i32
     );
     assert_eq!(response.get_ref().groups.len(), 1);
+    assert_eq!(fetcher.fetched(), vec!["http://example.com/robots.txt"]);
+    assert_eq!(
+        cache
+            .robots("http://example.com/robots.txt")
+            .unwrap()
+            .groups
+            .len(),
+        1
+    );
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_full_grpc_fetch_failure() {
+    let fetcher = FakeFetcher::new();
+    fetcher.fail("down.example", FetchError::Timeout);
+    let server = spawn_server(RobotsServer::new(InMemoryCache::new(), fetcher.clone())).await;
+
+    let response = server
+        .client
+        .get_robots_txt("http://down.example/")
+        .await
+        .unwrap();
+
+    assert_eq!(response.access_result, AccessResult::Unreachable as i32);
+    assert_eq!(fetcher.fetch_count("down.example"), 1);
 
-    tx.send(()).unwrap();
-    server_handle.await.unwrap().unwrap();
+    server.shutdown().await;
 }
@@ -0,0 +1,114 @@
+#![cfg(feature = "server")]
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::health::{self, Readiness};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::GetRobotsRequest;
+use robots_server::service::robots::v1::robots_service_client::RobotsServiceClient;
+use robots_server::service::robots::v1::robots_service_server::RobotsServiceServer;
+use robots_server::shutdown::serve_with_grace;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tonic::transport::Server;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Serve the probe endpoints for `readiness` on an ephemeral port.
+async fn start_health(readiness: Readiness) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(health::serve(listener, readiness, std::future::pending()));
+    addr
+}
+
+async fn status(addr: SocketAddr, endpoint: &str) -> u16 {
+    reqwest::get(format!("http://{addr}{endpoint}"))
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+#[tokio::test]
+async fn test_readyz_follows_serving_and_check() {
+    let reachable = Arc::new(AtomicBool::new(true));
+    let readiness = Readiness::new({
+        let reachable = reachable.clone();
+        move || {
+            let reachable = reachable.load(Ordering::SeqCst);
+            Box::pin(async move { reachable })
+        }
+    });
+    let addr = start_health(readiness.clone()).await;
+
+    assert_eq!(status(addr, "/healthz").await, 200);
+    assert_eq!(status(addr, "/readyz").await, 503);
+    readiness.set_serving(true);
+    assert_eq!(status(addr, "/readyz").await, 200);
+    reachable.store(false, Ordering::SeqCst);
+    assert_eq!(status(addr, "/readyz").await, 503);
+    assert_eq!(status(addr, "/healthz").await, 200);
+    assert_eq!(status(addr, "/other").await, 404);
+}
+
+#[tokio::test]
+async fn test_readyz_unavailable_while_draining() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("User-agent: *\nAllow: /\n")
+                .set_delay(Duration::from_millis(500)),
+        )
+        .mount(&mock_server)
+        .await;
+    let service = Arc::new(RobotsServer::new(MokaCache::new(), RobotsFetcher::new()));
+    let readiness = Readiness::new({
+        let service = service.clone();
+        move || {
+            let service = service.clone();
+            Box::pin(async move { service.cache_reachable().await })
+        }
+    });
+    let health_addr = start_health(readiness.clone()).await;
+    let addr: SocketAddr = "[::1]:50084".parse().unwrap();
+    let router = Server::builder().add_service(RobotsServiceServer::from_arc(service));
+    let (stop, stopping) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_with_grace(
+        move |signal| router.serve_with_shutdown(addr, signal),
+        readiness.clone().draining_after(async {
+            stopping.await.ok();
+        }),
+        Duration::from_secs(10),
+    ));
+    readiness.set_serving(true);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(status(health_addr, "/healthz").await, 200);
+    assert_eq!(status(health_addr, "/readyz").await, 200);
+
+    let mut client = RobotsServiceClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    let request = GetRobotsRequest {
+        url: format!("http://{}/", mock_server.address()),
+        ..Default::default()
+    };
+    let in_flight = tokio::spawn(async move { client.get_robots_txt(request).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    stop.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // Still draining the request above
+    assert!(!server.is_finished());
+    assert_eq!(status(health_addr, "/readyz").await, 503);
+    assert_eq!(status(health_addr, "/healthz").await, 200);
+    assert!(in_flight.await.unwrap().is_ok());
+    server.await.unwrap().unwrap();
+}
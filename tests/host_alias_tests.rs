@@ -0,0 +1,153 @@
+#![cfg(feature = "test-support")]
+
+use std::collections::HashMap;
+
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    GetRobotsRequest, InvalidateUrlRequest, IsAllowedRequest,
+};
+use robots_server::test_support::{FakeFetcher, InMemoryCache, RobotsDataBuilder};
+use tonic::Request;
+
+type TestServer = RobotsServer<InMemoryCache<String, CachedRobots>, FakeFetcher>;
+
+fn server(fetcher: FakeFetcher, config: RuntimeConfig) -> TestServer {
+    RobotsServer::builder()
+        .cache(InMemoryCache::new())
+        .fetcher(fetcher)
+        .config(ConfigHandle::new(config))
+        .build()
+        .unwrap()
+}
+
+async fn allowed(service: &TestServer, url: &str) -> bool {
+    service
+        .is_allowed(Request::new(IsAllowedRequest {
+            target_url: url.to_string(),
+            user_agent: "MyBot".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .allowed
+}
+
+#[test]
+fn test_canonical_host() {
+    let config = RuntimeConfig {
+        host_aliases: HashMap::from([("Shop.Example.com".to_string(), "example.com".to_string())]),
+        strip_www: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        config.canonical_host("shop.example.com").as_deref(),
+        Some("example.com")
+    );
+    assert_eq!(
+        config.canonical_host("www.example.org").as_deref(),
+        Some("example.org")
+    );
+    assert_eq!(config.canonical_host("example.org"), None);
+    assert_eq!(config.canonical_host("sub.www.example.org"), None);
+
+    let config = RuntimeConfig::default();
+    assert_eq!(config.canonical_host("www.example.org"), None);
+}
+
+#[test]
+fn test_empty_alias_rejected() {
+    let config = RuntimeConfig {
+        host_aliases: HashMap::from([("www.example.com".to_string(), " ".to_string())]),
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_aliased_hosts_share_one_fetch() {
+    let fetcher = FakeFetcher::new();
+    fetcher.serve(
+        "www.example.com",
+        RobotsDataBuilder::new()
+            .group("*")
+            .disallow("/admin")
+            .build(),
+    );
+    let service = server(
+        fetcher.clone(),
+        RuntimeConfig {
+            strip_www: true,
+            ..Default::default()
+        },
+    );
+
+    assert!(!allowed(&service, "http://www.example.com/admin").await);
+    assert!(!allowed(&service, "http://example.com/admin").await);
+    assert!(allowed(&service, "http://example.com/public").await);
+    assert_eq!(fetcher.fetch_count("www.example.com"), 1);
+    assert_eq!(fetcher.fetch_count("example.com"), 0);
+
+    let response = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: "http://example.com/".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.requested_host, "example.com");
+    assert_eq!(response.cache_key, "http://example.com/robots.txt");
+    assert_eq!(response.groups.len(), 1);
+
+    let response = service
+        .invalidate_url(Request::new(InvalidateUrlRequest {
+            url: "http://www.example.com/".to_string(),
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(response.existed);
+    assert_eq!(response.robots_txt_url, "http://example.com/robots.txt");
+}
+
+#[tokio::test]
+async fn test_fetch_canonical_host() {
+    let fetcher = FakeFetcher::new();
+    fetcher.serve(
+        "example.com",
+        RobotsDataBuilder::new().group("*").disallow("/").build(),
+    );
+    let service = server(
+        fetcher.clone(),
+        RuntimeConfig {
+            host_aliases: HashMap::from([(
+                "shop.example.com".to_string(),
+                "example.com".to_string(),
+            )]),
+            fetch_canonical_host: true,
+            ..Default::default()
+        },
+    );
+
+    assert!(!allowed(&service, "http://shop.example.com/cart").await);
+    assert!(!allowed(&service, "http://example.com/cart").await);
+    assert_eq!(
+        fetcher.fetched(),
+        vec!["http://example.com/robots.txt".to_string()]
+    );
+
+    let response = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: "http://shop.example.com/".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.requested_host, "shop.example.com");
+    assert_eq!(response.cache_key, "http://example.com/robots.txt");
+}
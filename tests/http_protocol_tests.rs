@@ -0,0 +1,130 @@
+use robots_server::config::{ConfigHandle, Http3Mode, HttpProtocol, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use url::Url;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// wiremock serves HTTP/1.1, and HTTP/2 to clients that start with it.
+async fn robots_server() -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+async fn fetched_version(config: RuntimeConfig) -> Option<String> {
+    let mock_server = robots_server().await;
+    let fetcher = RobotsFetcher::with_config(ConfigHandle::new(config));
+    let url = format!("http://{}/", mock_server.address());
+    fetcher.fetch(&url).await.unwrap().http_version
+}
+
+#[tokio::test]
+async fn test_auto_uses_http1_without_tls() {
+    let version = fetched_version(RuntimeConfig::default()).await;
+    assert_eq!(version.as_deref(), Some("HTTP/1.1"));
+}
+
+#[tokio::test]
+async fn test_http2_prior_knowledge() {
+    let version = fetched_version(RuntimeConfig {
+        http_protocol: HttpProtocol::Http2PriorKnowledge,
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(version.as_deref(), Some("HTTP/2.0"));
+}
+
+#[tokio::test]
+async fn test_http1_only() {
+    let version = fetched_version(RuntimeConfig {
+        http_protocol: HttpProtocol::Http1Only,
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(version.as_deref(), Some("HTTP/1.1"));
+}
+
+#[tokio::test]
+async fn test_host_override_replaces_global_protocol() {
+    let mut config = RuntimeConfig {
+        http_protocol: HttpProtocol::Http2PriorKnowledge,
+        ..Default::default()
+    };
+    config
+        .http_protocol_overrides
+        .insert("127.0.0.1".to_string(), HttpProtocol::Http1Only);
+
+    let version = fetched_version(config).await;
+    assert_eq!(version.as_deref(), Some("HTTP/1.1"));
+}
+
+#[test]
+fn test_http_protocol_from_config() {
+    let config: RuntimeConfig = toml::from_str(
+        "http_protocol = \"http2_prior_knowledge\"\n\
+         [http_protocol_overrides]\n\
+         \"cdn.example.com\" = \"http1_only\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.http_protocol("example.com"),
+        HttpProtocol::Http2PriorKnowledge
+    );
+    assert_eq!(
+        config.http_protocol("CDN.example.com"),
+        HttpProtocol::Http1Only
+    );
+}
+
+#[test]
+fn test_http3_only_tried_for_negotiated_https() {
+    let config: RuntimeConfig = toml::from_str(
+        "http3 = \"with_fallback\"\n\
+         [http_protocol_overrides]\n\
+         \"legacy.example.com\" = \"http1_only\"\n",
+    )
+    .unwrap();
+    let url = |url: &str| Url::parse(url).unwrap();
+
+    assert!(config.tries_http3(&url("https://example.com/robots.txt")));
+    assert!(!config.tries_http3(&url("http://example.com/robots.txt")));
+    assert!(!config.tries_http3(&url("https://legacy.example.com/robots.txt")));
+
+    let never = RuntimeConfig {
+        http3: Http3Mode::Never,
+        ..config
+    };
+    assert!(!never.tries_http3(&url("https://example.com/robots.txt")));
+}
+
+#[cfg(not(feature = "http3"))]
+#[test]
+fn test_http3_needs_feature() {
+    let config = RuntimeConfig {
+        http3: Http3Mode::WithFallback,
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
+
+/// QUIC needs TLS, so plain HTTP origins are fetched as before.
+#[cfg(feature = "http3")]
+#[tokio::test]
+async fn test_http3_skipped_without_tls() {
+    let mock_server = robots_server().await;
+    let fetcher = RobotsFetcher::with_config(ConfigHandle::new(RuntimeConfig {
+        http3: Http3Mode::WithFallback,
+        ..Default::default()
+    }));
+    let url = format!("http://{}/", mock_server.address());
+
+    let data = fetcher.fetch(&url).await.unwrap();
+    assert_eq!(data.http_version.as_deref(), Some("HTTP/1.1"));
+    assert_eq!(fetcher.stats().http3_fallbacks(), 0);
+}
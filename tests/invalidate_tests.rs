@@ -0,0 +1,100 @@
+#![cfg(feature = "server")]
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    InvalidateUrlRequest, IsAllowedRequest, RefreshRobotsRequest,
+};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn serve_robots(mock_server: &MockServer, body: &str) {
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_invalidate_picks_up_fixed_robots_txt() {
+    let mock_server = MockServer::start().await;
+    serve_robots(&mock_server, "User-agent: *\nDisallow: /\n").await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let is_allowed = || {
+        service.is_allowed(Request::new(IsAllowedRequest {
+            target_url: format!("http://{}/page", mock_server.address()),
+            user_agent: "MyBot".to_string(),
+            ..Default::default()
+        }))
+    };
+    let invalidate =
+        |url: String| service.invalidate_url(Request::new(InvalidateUrlRequest { url }));
+
+    assert!(!is_allowed().await.unwrap().into_inner().allowed);
+
+    // The origin fixes its robots.txt, but the cached copy is still served
+    mock_server.reset().await;
+    serve_robots(&mock_server, "User-agent: *\nAllow: /\n").await;
+    assert!(!is_allowed().await.unwrap().into_inner().allowed);
+
+    // Any URL on the host names the same entry
+    let response = invalidate(format!("http://{}/some/page?q=1", mock_server.address()))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        response.robots_txt_url,
+        format!("http://{}/robots.txt", mock_server.address())
+    );
+    assert!(response.existed);
+    assert!(is_allowed().await.unwrap().into_inner().allowed);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+
+    let other = MockServer::start().await;
+    let response = invalidate(format!("http://{}/", other.address()))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!response.existed);
+}
+
+#[tokio::test]
+async fn test_invalidate_drops_the_copy_a_throttled_refresh_answers_with() {
+    let mock_server = MockServer::start().await;
+    serve_robots(&mock_server, "User-agent: *\nDisallow: /\n").await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let url = format!("http://{}/page", mock_server.address());
+    let refresh = || {
+        service.refresh_robots(Request::new(RefreshRobotsRequest {
+            url: url.clone(),
+            force: false,
+        }))
+    };
+    let poisoned = refresh().await.unwrap().into_inner();
+
+    mock_server.reset().await;
+    serve_robots(&mock_server, "User-agent: *\nAllow: /\n").await;
+    service
+        .invalidate_url(Request::new(InvalidateUrlRequest { url: url.clone() }))
+        .await
+        .unwrap();
+
+    // Within min_refresh_interval_secs of the first fetch, the refresh has
+    // no earlier copy left to answer with
+    let refreshed = refresh().await.unwrap().into_inner();
+    assert_ne!(refreshed.checksum, poisoned.checksum);
+    let response = service
+        .is_allowed(Request::new(IsAllowedRequest {
+            target_url: url.clone(),
+            user_agent: "MyBot".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(response.allowed);
+}
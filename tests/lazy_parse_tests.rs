@@ -0,0 +1,212 @@
+#![cfg(feature = "server")]
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use robots_server::cache::{Cache, CacheResult, MokaCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::{RobotsFetcher, RobotsText};
+use robots_server::politeness::{HostRateLimiter, RateLimit};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{GetRobotsRequest, IsAllowedRequest};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ROBOTS_TXT: &str = include_str!("fixtures/robots.txt");
+
+/// A cache the test can look into while the service uses it.
+#[derive(Clone)]
+struct SharedCache(Arc<MokaCache<String, CachedRobots>>);
+
+impl SharedCache {
+    fn new() -> Self {
+        Self(Arc::new(MokaCache::new()))
+    }
+}
+
+#[async_trait]
+impl Cache<String, CachedRobots> for SharedCache {
+    async fn get(&self, key: &String) -> CacheResult<Option<CachedRobots>> {
+        self.0.get(key).await
+    }
+
+    async fn set(&self, key: String, value: CachedRobots) -> CacheResult<()> {
+        self.0.set(key, value).await
+    }
+
+    async fn delete(&self, key: &String) -> CacheResult<bool> {
+        self.0.delete(key).await
+    }
+}
+
+async fn robots_server() -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(ROBOTS_TXT))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+fn service(cache: SharedCache, lazy_parse: bool) -> RobotsServer<SharedCache> {
+    RobotsServer::new(cache, RobotsFetcher::new())
+        .with_config(ConfigHandle::new(RuntimeConfig {
+            lazy_parse,
+            ..Default::default()
+        }))
+        .with_host_rate_limiter(HostRateLimiter::new(RateLimit::UNLIMITED))
+}
+
+async fn decide(
+    service: &RobotsServer<SharedCache>,
+    url: String,
+    user_agent: &str,
+) -> (bool, String) {
+    let response = service
+        .is_allowed(Request::new(IsAllowedRequest {
+            target_url: url,
+            user_agent: user_agent.to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    (response.allowed, response.reason)
+}
+
+#[tokio::test]
+async fn test_lazy_and_eager_decisions_match() {
+    let eager_origin = robots_server().await;
+    let lazy_origin = robots_server().await;
+    let eager = service(SharedCache::new(), false);
+    let lazy = service(SharedCache::new(), true);
+
+    for user_agent in ["Googlebot", "bingbot/2.0", "OtherBot"] {
+        for path in [
+            "/",
+            "/private/x",
+            "/private/public",
+            "/tmp/file",
+            "/page?q=1",
+        ] {
+            let eager_url = format!("http://{}{path}", eager_origin.address());
+            let lazy_url = format!("http://{}{path}", lazy_origin.address());
+            assert_eq!(
+                decide(&eager, eager_url, user_agent).await,
+                decide(&lazy, lazy_url, user_agent).await,
+                "{user_agent} {path}"
+            );
+        }
+    }
+
+    let robots = |service: &RobotsServer<SharedCache>, origin: &MockServer| {
+        let url = format!("http://{}/", origin.address());
+        async move {
+            service
+                .get_robots_txt(Request::new(GetRobotsRequest {
+                    url,
+                    ..Default::default()
+                }))
+                .await
+                .unwrap()
+                .into_inner()
+        }
+    };
+    let eager_response = robots(&eager, &eager_origin).await;
+    let lazy_response = robots(&lazy, &lazy_origin).await;
+    assert_eq!(eager_response.groups, lazy_response.groups);
+    assert_eq!(eager_response.sitemaps, lazy_response.sitemaps);
+    assert_eq!(eager_response.access_result, lazy_response.access_result);
+}
+
+#[tokio::test]
+async fn test_lazy_entry_is_parsed_on_first_hit() {
+    let origin = robots_server().await;
+    let cache = SharedCache::new();
+    let service = service(cache.clone(), true);
+    let url = format!("http://{}/private/x", origin.address());
+    let key = format!("http://{}/robots.txt", origin.address());
+
+    // The fetching request decides from its own parse
+    assert!(!decide(&service, url.clone(), "Googlebot").await.0);
+    let entry = cache.get(&key).await.unwrap().unwrap();
+    assert!(!entry.is_parsed());
+
+    assert!(!decide(&service, url, "Googlebot").await.0);
+    assert!(entry.is_parsed());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_raw_entry_is_parsed_once() {
+    let entry = CachedRobots::raw(
+        RobotsText {
+            robots_txt_url: "http://example.com/robots.txt".to_string(),
+            http_status_code: 200,
+            content_length_bytes: ROBOTS_TXT.len() as u64,
+            body: ROBOTS_TXT.to_string(),
+            truncated: false,
+            fetched_at: SystemTime::now(),
+            http_version: "HTTP/1.1".to_string(),
+            upstream_headers: BTreeMap::new(),
+            max_age: None,
+            resolved_address: None,
+        },
+        "http://example.com/",
+    );
+
+    let handles: Vec<_> = (0..16)
+        .map(|_| {
+            let entry = entry.clone();
+            tokio::spawn(async move { entry.data() })
+        })
+        .collect();
+    let mut parsed = Vec::new();
+    for handle in handles {
+        parsed.push(handle.await.unwrap());
+    }
+
+    assert!(parsed.iter().all(|data| Arc::ptr_eq(data, &parsed[0])));
+    assert!(Arc::ptr_eq(&entry.data(), &parsed[0]));
+    assert_eq!(parsed[0].groups.len(), 2);
+    assert_eq!(parsed[0].target_url, "http://example.com/");
+}
+
+fn text(body: String) -> RobotsText {
+    RobotsText {
+        robots_txt_url: "http://example.com/robots.txt".to_string(),
+        http_status_code: 200,
+        content_length_bytes: body.len() as u64,
+        body,
+        truncated: false,
+        fetched_at: SystemTime::now(),
+        http_version: "HTTP/1.1".to_string(),
+        upstream_headers: BTreeMap::new(),
+        max_age: None,
+        resolved_address: None,
+    }
+}
+
+/// About 500KB of robots.txt repeating the fixture's rules.
+fn large_body() -> String {
+    ROBOTS_TXT.repeat(500 * 1024 / ROBOTS_TXT.len())
+}
+
+#[test]
+fn test_raw_text_round_trips() {
+    let body = large_body();
+    let expected = text(body.clone()).parse("http://example.com/");
+    let entry = CachedRobots::raw(text(body.clone()), "http://example.com/");
+
+    assert_eq!(entry.raw_text().unwrap().body, body);
+    assert_eq!(entry.data().checksum, expected.checksum);
+    assert_eq!(entry.data().groups.len(), expected.groups.len());
+    assert!(entry.raw_text().is_none());
+    assert!(entry.raw_size_bytes().is_none());
+}
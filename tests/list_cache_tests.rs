@@ -0,0 +1,143 @@
+#![cfg(feature = "server")]
+
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, SystemTime};
+
+use robots_server::cache::{Cache, MokaCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::fetcher::{RobotsFetcher, RobotsText};
+use robots_server::overrides::policy_key;
+use robots_server::robots_data::{AccessResult, RobotsData};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    AccessResult as ProtoBufAccessResult, ListCachedEntriesRequest, ListCachedEntriesResponse,
+};
+use tonic::{Code, Request};
+
+/// A server with a dozen robots.txt cached, each fetched a minute ago, and
+/// one host's policy rules.
+async fn server() -> RobotsServer<MokaCache<String, CachedRobots>, RobotsFetcher> {
+    let cache = MokaCache::new();
+    for i in 0..12 {
+        let robots_txt_url = format!("https://host{i:02}.example/robots.txt");
+        let data = RobotsData {
+            robots_txt_url: robots_txt_url.clone(),
+            access_result: AccessResult::Unavailable,
+            http_status_code: 404,
+            fetched_at: Some(SystemTime::now() - Duration::from_secs(60)),
+            ..Default::default()
+        };
+        cache
+            .set(robots_txt_url, CachedRobots::from(data))
+            .await
+            .unwrap();
+    }
+    cache
+        .set(
+            policy_key("host00.example"),
+            CachedRobots::from(RobotsData::default()),
+        )
+        .await
+        .unwrap();
+    RobotsServer::new(cache, RobotsFetcher::new())
+}
+
+async fn list(
+    service: &RobotsServer<MokaCache<String, CachedRobots>, RobotsFetcher>,
+    page_size: u32,
+    page_token: &str,
+) -> ListCachedEntriesResponse {
+    service
+        .list_cached_entries(Request::new(ListCachedEntriesRequest {
+            page_size,
+            page_token: page_token.to_string(),
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+#[tokio::test]
+async fn test_list_cached_entries_walks_two_pages() {
+    let service = server().await;
+
+    let first = list(&service, 8, "").await;
+    assert_eq!(first.entries.len(), 8);
+    assert!(!first.next_page_token.is_empty());
+    let second = list(&service, 8, &first.next_page_token).await;
+    assert_eq!(second.entries.len(), 4);
+    assert!(second.next_page_token.is_empty());
+
+    let urls = first
+        .entries
+        .iter()
+        .chain(&second.entries)
+        .map(|entry| entry.robots_txt_url.clone())
+        .collect::<Vec<_>>();
+    let expected = (0..12)
+        .map(|i| format!("https://host{i:02}.example/robots.txt"))
+        .collect::<Vec<_>>();
+    assert_eq!(urls, expected);
+    assert_eq!(urls.iter().collect::<HashSet<_>>().len(), 12);
+
+    let entry = &second.entries[0];
+    assert_eq!(entry.access_result(), ProtoBufAccessResult::Unavailable);
+    assert_eq!(entry.http_status_code, 404);
+    assert!(entry.age_seconds >= 60 && entry.age_seconds < 120);
+    assert!(!entry.truncated);
+}
+
+#[tokio::test]
+async fn test_list_cached_entries_defaults_page_size() {
+    let service = server().await;
+    let page = list(&service, 0, "").await;
+    assert_eq!(page.entries.len(), 12);
+    assert!(page.next_page_token.is_empty());
+}
+
+#[tokio::test]
+async fn test_list_cached_entries_rejects_malformed_token() {
+    let service = server().await;
+    let status = service
+        .list_cached_entries(Request::new(ListCachedEntriesRequest {
+            page_size: 8,
+            page_token: "not a token".to_string(),
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_list_cached_entries_parses_only_the_page() {
+    let cache = MokaCache::new();
+    let mut entries = Vec::new();
+    for i in 0..4 {
+        let robots_txt_url = format!("https://host{i}.example/robots.txt");
+        let text = RobotsText {
+            robots_txt_url: robots_txt_url.clone(),
+            http_status_code: 200,
+            content_length_bytes: 14,
+            body: "User-agent: *\n".to_string(),
+            truncated: false,
+            fetched_at: SystemTime::now(),
+            http_version: "HTTP/1.1".to_string(),
+            upstream_headers: BTreeMap::new(),
+            max_age: None,
+            resolved_address: None,
+        };
+        let entry = CachedRobots::raw(text, format!("https://host{i}.example/"));
+        cache.set(robots_txt_url, entry.clone()).await.unwrap();
+        entries.push(entry);
+    }
+    let service = RobotsServer::new(cache, RobotsFetcher::new());
+
+    let page = list(&service, 1, "").await;
+    assert_eq!(page.entries[0].http_status_code, 200);
+    let parsed = entries
+        .iter()
+        .map(CachedRobots::is_parsed)
+        .collect::<Vec<_>>();
+    assert_eq!(parsed, [true, false, false, false]);
+}
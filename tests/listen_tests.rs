@@ -0,0 +1,176 @@
+#![cfg(feature = "server")]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use robots_server::cache::MokaCache;
+use robots_server::config::{BindFailure, TransportConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::listen::{bind_all, configure, incoming};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_client::RobotsServiceClient;
+use robots_server::service::robots::v1::robots_service_server::RobotsServiceServer;
+use robots_server::service::robots::v1::{GetRobotsRequest, IsAllowedRequest};
+use robots_server::shutdown::serve_all_with_grace;
+use tokio::sync::oneshot;
+use tonic::transport::{Channel, Server};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_serves_ipv4_and_ipv6_together() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private\n"),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let addrs: Vec<SocketAddr> = vec![
+        "127.0.0.1:50076".parse().unwrap(),
+        "[::1]:50076".parse().unwrap(),
+    ];
+    let listeners = bind_all(&addrs, BindFailure::Error).unwrap();
+    assert_eq!(listeners.len(), 2);
+    let service =
+        RobotsServiceServer::new(RobotsServer::new(MokaCache::new(), RobotsFetcher::new()));
+    for listener in listeners {
+        let router = Server::builder().add_service(service.clone());
+        tokio::spawn(router.serve_with_incoming(incoming(listener, None)));
+    }
+
+    for addr in addrs {
+        let mut client = RobotsServiceClient::connect(format!("http://{addr}"))
+            .await
+            .unwrap();
+        let response = client
+            .is_allowed(IsAllowedRequest {
+                target_url: format!("http://{}/private/page", mock_server.address()),
+                user_agent: "TestBot".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!response.allowed);
+    }
+}
+
+#[tokio::test]
+async fn test_listeners_share_one_cache() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nAllow: /\n"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let addrs: Vec<SocketAddr> = vec![
+        "127.0.0.1:50080".parse().unwrap(),
+        "127.0.0.1:50081".parse().unwrap(),
+    ];
+    let listeners = bind_all(&addrs, BindFailure::Error).unwrap();
+    let service =
+        RobotsServiceServer::new(RobotsServer::new(MokaCache::new(), RobotsFetcher::new()));
+    let (stop, stopping) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_all_with_grace(
+        move |signal| {
+            listeners
+                .into_iter()
+                .map(|listener| {
+                    Server::builder()
+                        .add_service(service.clone())
+                        .serve_with_incoming_shutdown(incoming(listener, None), signal.clone())
+                })
+                .collect::<Vec<_>>()
+        },
+        async {
+            stopping.await.ok();
+        },
+        Duration::from_secs(1),
+    ));
+
+    for addr in &addrs {
+        let mut client = RobotsServiceClient::connect(format!("http://{addr}"))
+            .await
+            .unwrap();
+        let response = client
+            .get_robots_txt(GetRobotsRequest {
+                url: format!("http://{}/", mock_server.address()),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.http_status_code, 200);
+    }
+    // The second address answered from the cache the first one filled
+    mock_server.verify().await;
+
+    stop.send(()).unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_bind_failure_policy() {
+    let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addrs = vec![taken.local_addr().unwrap(), "127.0.0.1:0".parse().unwrap()];
+
+    assert!(bind_all(&addrs, BindFailure::Error).is_err());
+    let listeners = bind_all(&addrs, BindFailure::Warn).unwrap();
+    assert_eq!(listeners.len(), 1);
+    assert!(bind_all(&addrs[..1], BindFailure::Warn).is_err());
+}
+
+#[tokio::test]
+async fn test_channel_survives_idle_with_keepalive() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nAllow: /\n"))
+        .mount(&mock_server)
+        .await;
+    let transport: TransportConfig = toml::from_str(
+        "http2_keepalive_interval_secs = 0.5\nhttp2_keepalive_timeout_secs = 1\n\
+         tcp_keepalive_secs = 30\nmax_concurrent_streams = 16\ntimeout_secs = 5\n",
+    )
+    .unwrap();
+    let addr: SocketAddr = "[::1]:50079".parse().unwrap();
+    let listener = bind_all(&[addr], BindFailure::Error).unwrap().remove(0);
+    let service =
+        RobotsServiceServer::new(RobotsServer::new(MokaCache::new(), RobotsFetcher::new()));
+    let router = configure(Server::builder(), &transport).add_service(service);
+    tokio::spawn(router.serve_with_incoming(incoming(listener, transport.tcp_keepalive())));
+
+    let channel = Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .http2_keep_alive_interval(Duration::from_millis(500))
+        .keep_alive_while_idle(true)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = RobotsServiceClient::new(channel);
+    let is_allowed = IsAllowedRequest {
+        target_url: format!("http://{}/page", mock_server.address()),
+        user_agent: "TestBot".to_string(),
+        ..Default::default()
+    };
+    assert!(
+        client
+            .is_allowed(is_allowed.clone())
+            .await
+            .unwrap()
+            .into_inner()
+            .allowed
+    );
+
+    // Several keepalive rounds go by without a call
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    let response = tokio::time::timeout(Duration::from_secs(1), client.is_allowed(is_allowed))
+        .await
+        .expect("call after idle did not hang")
+        .unwrap();
+    assert!(response.into_inner().allowed);
+}
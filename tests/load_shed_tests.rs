@@ -0,0 +1,64 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::load_shed::LoadShedLayer;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_client::RobotsServiceClient;
+use robots_server::service::robots::v1::robots_service_server::RobotsServiceServer;
+use tonic::Code;
+use tonic::transport::Server;
+
+use common::{ROBOTS_TXT, get_robots, origin};
+
+#[tokio::test]
+async fn test_sheds_calls_over_the_limit() {
+    let origins = [
+        origin(200, ROBOTS_TXT, Duration::from_secs(1), &[]).await,
+        origin(200, ROBOTS_TXT, Duration::from_secs(1), &[]).await,
+    ];
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    tokio::spawn(
+        Server::builder()
+            .layer(LoadShedLayer::new(2))
+            .add_service(RobotsServiceServer::new(service))
+            .serve("[::1]:50077".parse().unwrap()),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let client = RobotsServiceClient::connect("http://[::1]:50077")
+        .await
+        .unwrap();
+
+    let slow = origins.each_ref().map(|origin| {
+        let mut client = client.clone();
+        let request = get_robots(origin);
+        tokio::spawn(async move { client.get_robots_txt(request).await })
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let started = Instant::now();
+    let status = client
+        .clone()
+        .get_robots_txt(get_robots(&origins[0]))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    assert!(started.elapsed() < Duration::from_millis(500));
+
+    for call in slow {
+        let response = call.await.unwrap().unwrap().into_inner();
+        assert_eq!(response.http_status_code, 200);
+    }
+    // Served from the cache now that the slow calls have finished
+    let response = client
+        .clone()
+        .get_robots_txt(get_robots(&origins[0]))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.http_status_code, 200);
+}
@@ -0,0 +1,184 @@
+#![cfg(feature = "server")]
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use chrono::DateTime;
+use robots_server::cache::{Cache, MokaCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::log_format::json_layer;
+use robots_server::robots_data::{AccessResult, RobotsData};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{GetRobotsRequest, IsAllowedRequest};
+use serde_json::Value;
+use tonic::Request;
+use tracing::{Span, field, info, info_span};
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Collects everything written so the lines can be inspected.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Capture {
+    fn lines(&self) -> Vec<Value> {
+        let bytes = self.0.lock().unwrap();
+        String::from_utf8_lossy(&bytes)
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("each line is one JSON object"))
+            .collect()
+    }
+}
+
+fn subscriber(capture: &Capture) -> impl tracing::Subscriber + Send + Sync {
+    let capture = capture.clone();
+    tracing_subscriber::registry().with(json_layer(move || capture.clone()))
+}
+
+#[test]
+fn test_json_lines_flatten_span_fields() {
+    let capture = Capture::default();
+    tracing::subscriber::with_default(subscriber(&capture), || {
+        let span = info_span!(
+            "is_allowed",
+            target_url = "http://example.com/page",
+            user_agent = "MyBot",
+            robots_url = field::Empty,
+            allowed = field::Empty,
+        );
+        let _entered = span.enter();
+        Span::current().record("robots_url", "http://example.com/robots.txt");
+        Span::current().record("allowed", true);
+        info!(rules = 3, "Evaluated crawl permission");
+    });
+
+    let lines = capture.lines();
+    assert_eq!(lines.len(), 1);
+    let line = &lines[0];
+    assert_eq!(line["level"], "INFO");
+    assert_eq!(line["target"], module_path!());
+    assert_eq!(line["span"], "is_allowed");
+    assert_eq!(line["message"], "Evaluated crawl permission");
+    assert_eq!(line["rules"], 3);
+    assert_eq!(line["target_url"], "http://example.com/page");
+    assert_eq!(line["user_agent"], "MyBot");
+    assert_eq!(line["robots_url"], "http://example.com/robots.txt");
+    assert_eq!(line["allowed"], true);
+    let timestamp = line["timestamp"].as_str().unwrap();
+    assert!(
+        DateTime::parse_from_rfc3339(timestamp).is_ok(),
+        "{timestamp}"
+    );
+}
+
+#[test]
+fn test_inner_span_fields_win_over_outer() {
+    let capture = Capture::default();
+    tracing::subscriber::with_default(subscriber(&capture), || {
+        let outer = info_span!("outer", url = "http://outer.example/", request_id = 7);
+        let _outer = outer.enter();
+        let inner = info_span!("inner", url = "http://inner.example/");
+        let _inner = inner.enter();
+        info!("Nested");
+    });
+
+    let line = &capture.lines()[0];
+    assert_eq!(line["span"], "inner");
+    assert_eq!(line["url"], "http://inner.example/");
+    assert_eq!(line["request_id"], 7);
+}
+
+#[tokio::test]
+async fn test_service_events_carry_recorded_robots_url() {
+    let cache = MokaCache::new();
+    cache
+        .set(
+            "http://example.com/robots.txt".to_string(),
+            CachedRobots::from(RobotsData {
+                target_url: "http://example.com/".to_string(),
+                robots_txt_url: "http://example.com/robots.txt".to_string(),
+                access_result: AccessResult::Success,
+                http_status_code: 200,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+    let service = RobotsServer::new(cache, RobotsFetcher::new());
+
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(subscriber(&capture));
+    service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: "http://example.com/some/page".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+
+    let lines = capture.lines();
+    let line = lines
+        .iter()
+        .find(|line| line["message"] == "Processing robots.txt request")
+        .expect("request is logged");
+    assert_eq!(line["span"], "get_robots_txt");
+    assert_eq!(line["url"], "http://example.com/some/page");
+    assert_eq!(line["robots_url"], "http://example.com/robots.txt");
+}
+
+#[tokio::test]
+async fn test_is_allowed_lines_are_json_with_decision() {
+    let cache = MokaCache::new();
+    cache
+        .set(
+            "http://example.com/robots.txt".to_string(),
+            CachedRobots::from(RobotsData {
+                target_url: "http://example.com/".to_string(),
+                robots_txt_url: "http://example.com/robots.txt".to_string(),
+                access_result: AccessResult::Success,
+                http_status_code: 200,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+    let service = RobotsServer::new(cache, RobotsFetcher::new());
+
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(subscriber(&capture));
+    service
+        .is_allowed(Request::new(IsAllowedRequest {
+            target_url: "http://example.com/some/page".to_string(),
+            user_agent: "TestBot".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+
+    let lines = capture.lines();
+    assert!(!lines.is_empty());
+    for line in &lines {
+        let timestamp = line["timestamp"].as_str().unwrap();
+        assert!(DateTime::parse_from_rfc3339(timestamp).is_ok());
+    }
+    let line = lines
+        .iter()
+        .find(|line| line["message"] == "Decided robots.txt access")
+        .expect("decision is logged");
+    assert_eq!(line["span"], "is_allowed");
+    assert_eq!(line["target_url"], "http://example.com/some/page");
+    assert_eq!(line["robots_url"], "http://example.com/robots.txt");
+    assert_eq!(line["allowed"], true);
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "server")]
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use robots_server::cache::{Cache, MokaCache};
+use robots_server::log_format::json_layer;
+use robots_server::log_sampling::{self, LogSampler, LogSampling};
+use serde_json::Value;
+use tracing_subscriber::layer::SubscriberExt;
+
+// Sampling is process-wide, so every test applies the same settings
+const SAMPLING: LogSampling = LogSampling {
+    burst: 3,
+    interval_secs: 1.0,
+};
+
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Capture {
+    fn messages(&self) -> Vec<Value> {
+        let bytes = self.0.lock().unwrap();
+        String::from_utf8_lossy(&bytes)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn count(&self, message: &str) -> usize {
+        self.messages()
+            .iter()
+            .filter(|line| line["message"] == message)
+            .count()
+    }
+}
+
+fn subscriber(capture: &Capture) -> impl tracing::Subscriber + Send + Sync {
+    let capture = capture.clone();
+    tracing_subscriber::registry().with(json_layer(move || capture.clone()))
+}
+
+#[tokio::test]
+async fn test_cache_debug_lines_are_sampled() {
+    log_sampling::configure(&SAMPLING);
+    let cache = MokaCache::<String, u32>::new();
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(subscriber(&capture));
+
+    for _ in 0..20 {
+        cache
+            .get(&"http://example.com/robots.txt".to_string())
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(capture.count("Cache lookup"), 3);
+}
+
+#[test]
+fn test_suppressed_events_are_summarised() {
+    static SAMPLER: LogSampler = LogSampler::new("test.event");
+    log_sampling::configure(&SAMPLING);
+    let capture = Capture::default();
+    tracing::subscriber::with_default(subscriber(&capture), || {
+        let logged = (0..10).filter(|_| SAMPLER.sample()).count();
+        assert_eq!(logged, 3);
+
+        std::thread::sleep(Duration::from_millis(1_100));
+        assert!(SAMPLER.sample(), "a new interval logs again");
+    });
+
+    let lines = capture.messages();
+    let summary = lines
+        .iter()
+        .find(|line| line["key"] == "test.event")
+        .expect("summary is logged");
+    assert_eq!(summary["suppressed"], 7);
+    assert_eq!(summary["message"], "Suppressed 7 similar events");
+    assert_eq!(summary["level"], "DEBUG");
+}
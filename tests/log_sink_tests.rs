@@ -0,0 +1,109 @@
+#![cfg(feature = "server")]
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use robots_server::config::{Config, LogSinkConfig, Rotation};
+use robots_server::log_sink::{LogSink, RotatingFile};
+use tracing_subscriber::layer::SubscriberExt;
+
+fn log_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("robots-server-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn file_names(dir: &PathBuf) -> Vec<String> {
+    let mut names: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_rotating_file_rotates_by_size_and_keeps_retained_files() {
+    let dir = log_dir("rotate");
+    let path = dir.join("access.log");
+    let mut file = RotatingFile::open(&path, Rotation::MaxBytes(100), 2).unwrap();
+    // 30-byte lines, so each file holds three
+    for i in 0..20 {
+        let line = format!("line {i:02} {}\n", "x".repeat(21));
+        file.write_all(line.as_bytes()).unwrap();
+    }
+    file.flush().unwrap();
+
+    assert_eq!(
+        file_names(&dir),
+        vec!["access.log", "access.log.1", "access.log.2"]
+    );
+    let current = std::fs::read_to_string(&path).unwrap();
+    let newest = std::fs::read_to_string(dir.join("access.log.1")).unwrap();
+    let oldest = std::fs::read_to_string(dir.join("access.log.2")).unwrap();
+    assert!(current.starts_with("line 18"));
+    assert!(newest.starts_with("line 15"));
+    assert!(oldest.starts_with("line 12"));
+    for contents in [&current, &newest, &oldest] {
+        assert!(contents.len() <= 100);
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_log_sink_routes_only_configured_targets() {
+    let dir = log_dir("sink");
+    let config = LogSinkConfig {
+        path: dir.join("access.log"),
+        rotation: Rotation::MaxBytes(400),
+        retain: 1,
+        targets: vec!["access".to_string()],
+    };
+    let sink = LogSink::open(&config).unwrap();
+    let subscriber = tracing_subscriber::registry().with(sink.layer());
+    tracing::subscriber::with_default(subscriber, || {
+        for i in 0..20 {
+            tracing::info!(target: "access", i, "request");
+            tracing::info!(target: "robots_server::service", i, "not an access entry");
+        }
+    });
+    let dropped = sink.dropped_lines().dropped_lines();
+    // Dropping the sink flushes queued lines
+    drop(sink);
+
+    assert_eq!(dropped, 0);
+    assert_eq!(file_names(&dir), vec!["access.log", "access.log.1"]);
+    let current = std::fs::read_to_string(dir.join("access.log")).unwrap();
+    assert!(current.contains("request"));
+    assert!(!current.contains("not an access entry"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_access_log_config() {
+    let config: Config = toml::from_str(
+        r#"
+[access_log]
+path = "/var/log/robots/access.log"
+rotation = { max_bytes = 1048576 }
+retain = 3
+"#,
+    )
+    .unwrap();
+    let access_log = config.access_log.unwrap();
+    assert_eq!(access_log.rotation, Rotation::MaxBytes(1048576));
+    assert_eq!(access_log.retain, 3);
+    assert_eq!(access_log.targets, vec!["access"]);
+
+    let config: Config = toml::from_str(
+        r#"
+[access_log]
+path = "access.log"
+rotation = "daily"
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.access_log.unwrap().rotation, Rotation::Daily);
+}
@@ -0,0 +1,229 @@
+use robots_server::parser::parse;
+use robots_server::robots_data::{Group, RobotsData, Rule, RuleType};
+
+/// The matcher as it was before patterns were precompiled, kept as the
+/// reference the compiled matcher must agree with.
+mod reference {
+    use robots_server::robots_data::{Group, RobotsData, Rule, RuleType};
+
+    pub fn explain<'a>(data: &'a RobotsData, user_agent: &str, path: &str) -> Option<&'a Rule> {
+        let mut decisive: Option<&Rule> = None;
+        for group in matching_groups(data, user_agent) {
+            for rule in &group.rules {
+                if !path_matches_rfc9309(path, &rule.path_pattern) {
+                    continue;
+                }
+                decisive = match decisive {
+                    None => Some(rule),
+                    Some(best) if rule.path_pattern.len() > best.path_pattern.len() => Some(rule),
+                    Some(best)
+                        if rule.path_pattern.len() == best.path_pattern.len()
+                            && best.rule_type == RuleType::Disallow
+                            && rule.rule_type == RuleType::Allow =>
+                    {
+                        Some(rule)
+                    }
+                    keep => keep,
+                };
+            }
+        }
+        decisive
+    }
+
+    fn matching_groups<'a>(data: &'a RobotsData, user_agent: &str) -> Vec<&'a Group> {
+        let user_agent_lower = user_agent.to_lowercase();
+        let matching: Vec<&Group> = data
+            .groups
+            .iter()
+            .filter(|group| {
+                group.user_agents.iter().any(|ua| {
+                    let ua_lower = ua.to_lowercase();
+                    user_agent_lower == ua_lower || user_agent_lower.contains(&ua_lower)
+                })
+            })
+            .collect();
+        if matching.is_empty() {
+            data.groups
+                .iter()
+                .filter(|g| g.user_agents.iter().any(|ua| &**ua == "*"))
+                .collect()
+        } else {
+            matching
+        }
+    }
+
+    fn path_matches_rfc9309(path: &str, pattern: &str) -> bool {
+        if pattern.is_empty() {
+            return false;
+        }
+        if pattern.ends_with('$') {
+            let prefix = &pattern[..pattern.len() - 1];
+            return match_pattern(path, prefix, true);
+        }
+        match_pattern(path, pattern, false)
+    }
+
+    fn match_pattern(path: &str, pattern: &str, exact: bool) -> bool {
+        if pattern.contains('*') {
+            return wildcard_match(path, pattern, exact);
+        }
+        if exact {
+            path == pattern
+        } else {
+            path.starts_with(pattern)
+        }
+    }
+
+    fn wildcard_match(path: &str, pattern: &str, exact: bool) -> bool {
+        let parts: Vec<&str> = pattern.split('*').collect();
+        let mut pos = 0;
+        for (i, part) in parts.iter().enumerate() {
+            if part.is_empty() {
+                continue;
+            }
+            if i == 0 {
+                if !path.starts_with(part) {
+                    return false;
+                }
+                pos = part.len();
+            } else if i == parts.len() - 1 && exact {
+                if !path.ends_with(part) {
+                    return false;
+                }
+            } else if let Some(found) = path[pos..].find(part) {
+                pos += found + part.len();
+            } else {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+const PATTERNS: &[&str] = &[
+    "/",
+    "$",
+    "*",
+    "**",
+    "*$",
+    "/admin",
+    "/admin/",
+    "/admin$",
+    "/admin/$",
+    "/*.php",
+    "/*.php$",
+    "/fish*",
+    "/fish*.php",
+    "/*/private/*",
+    "/a*b*c",
+    "/a*b*c$",
+    "*/end$",
+    "/path$with$dollars",
+    "/path$with$dollars$",
+    "/café/*",
+    "/日本*語$",
+    "/q?id=*&x",
+    "/%2Fencoded",
+];
+
+const PATHS: &[&str] = &[
+    "",
+    "/",
+    "/admin",
+    "/admin/",
+    "/admin/panel",
+    "/administrator",
+    "/index.php",
+    "/index.php?x=1",
+    "/dir/index.php",
+    "/fish",
+    "/fishheads/yummy.php",
+    "/Fish.asp",
+    "/x/private/y",
+    "/a/b/c",
+    "/abcabc",
+    "/acb",
+    "/start/end",
+    "/end/end/",
+    "/path$with$dollars",
+    "/café/menu",
+    "/日本の語",
+    "/q?id=7&x=1",
+    "/%2Fencoded/more",
+];
+
+const USER_AGENTS: &[&str] = &[
+    "MyBot",
+    "mybot/2.1",
+    "Googlebot",
+    "GOOGLEBOT-Image",
+    "",
+    "*",
+];
+
+fn assert_equivalent(data: &RobotsData) {
+    for user_agent in USER_AGENTS {
+        for path in PATHS {
+            let expected = reference::explain(data, user_agent, path);
+            let explanation = data.explain(user_agent, path);
+            assert_eq!(
+                explanation.allowed,
+                expected.is_none_or(|rule| rule.rule_type == RuleType::Allow),
+                "allowed differs for {user_agent:?} {path:?}"
+            );
+            assert_eq!(
+                explanation.rule.map(|r| (r.rule_type, r.path_pattern)),
+                expected.map(|r| (r.rule_type, r.path_pattern.clone())),
+                "decisive rule differs for {user_agent:?} {path:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_compiled_patterns_match_reference_individually() {
+    for pattern in PATTERNS {
+        for rule_type in [RuleType::Allow, RuleType::Disallow] {
+            let data = RobotsData {
+                groups: vec![Group::new(
+                    vec!["*".to_string()],
+                    vec![Rule::new(rule_type, *pattern)],
+                    None,
+                )],
+                ..Default::default()
+            };
+            assert_equivalent(&data);
+        }
+    }
+}
+
+#[test]
+fn test_compiled_patterns_match_reference_combined() {
+    let mut body = String::new();
+    for (i, user_agent) in ["*", "MyBot", "googlebot"].iter().enumerate() {
+        body.push_str(&format!("User-agent: {user_agent}\n"));
+        for (j, pattern) in PATTERNS.iter().enumerate() {
+            let directive = if (i + j) % 2 == 0 {
+                "Allow"
+            } else {
+                "Disallow"
+            };
+            body.push_str(&format!("{directive}: {pattern}\n"));
+        }
+        body.push('\n');
+    }
+    assert_equivalent(&parse(&body));
+    assert_equivalent(&parse(include_str!("fixtures/robots.txt")));
+}
+
+#[test]
+fn test_push_user_agent_is_matched_case_insensitively() {
+    let mut group = Group::new(Vec::new(), vec![Rule::new(RuleType::Disallow, "/")], None);
+    group.push_user_agent("MyBot".to_string());
+    let data = RobotsData {
+        groups: vec![group],
+        ..Default::default()
+    };
+    assert!(!data.is_allowed("mybot/1.0", "/page"));
+    assert!(data.is_allowed("OtherBot", "/page"));
+}
@@ -0,0 +1,100 @@
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::metrics;
+use tokio::sync::Mutex;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// The metrics are process-wide, so tests measuring them take turns
+static TURN: Mutex<()> = Mutex::const_new(());
+
+/// The value of `series` in a scrape, or 0 before it is first recorded.
+fn scrape(series: &str) -> f64 {
+    metrics::gather_text()
+        .lines()
+        .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+        .map_or(0.0, |value| value.parse().unwrap())
+}
+
+fn body(len: usize) -> String {
+    "User-agent: *\nDisallow: /x\n"
+        .chars()
+        .cycle()
+        .take(len)
+        .collect()
+}
+
+async fn robots_server(body: String) -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+#[tokio::test]
+async fn test_body_bytes_counted() {
+    let _turn = TURN.lock().await;
+    let series = r#"robots_fetch_body_bytes_total{status_class="2xx",truncated="false"}"#;
+    let mock_server = robots_server(body(4000)).await;
+    let before = scrape(series);
+
+    RobotsFetcher::new()
+        .fetch(&format!("http://{}/", mock_server.address()))
+        .await
+        .unwrap();
+
+    assert_eq!(scrape(series) - before, 4000.0);
+    assert!(scrape("robots_fetch_body_size_bytes_count") >= 1.0);
+}
+
+#[tokio::test]
+async fn test_truncated_bytes_counted() {
+    let _turn = TURN.lock().await;
+    let series = r#"robots_fetch_body_bytes_total{status_class="2xx",truncated="true"}"#;
+    let mock_server = robots_server(body(4000)).await;
+    let fetcher = RobotsFetcher::with_config(ConfigHandle::new(RuntimeConfig {
+        max_robots_txt_bytes: 100,
+        ..Default::default()
+    }));
+    let bytes_before = scrape(series);
+    let truncations_before = scrape("robots_fetch_truncations_total");
+
+    let data = fetcher
+        .fetch(&format!("http://{}/", mock_server.address()))
+        .await
+        .unwrap();
+
+    assert!(data.truncated);
+    // Every byte streamed counts, not only the ones kept
+    assert!(scrape(series) - bytes_before >= 100.0);
+    assert_eq!(
+        scrape("robots_fetch_truncations_total") - truncations_before,
+        1.0
+    );
+}
+
+#[tokio::test]
+async fn test_redirects_observed() {
+    let _turn = TURN.lock().await;
+    let origin = robots_server(body(100)).await;
+    let redirector = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(301).insert_header(
+            "location",
+            format!("http://{}/robots.txt", origin.address()),
+        ))
+        .mount(&redirector)
+        .await;
+    let before = scrape("robots_fetch_redirects_sum");
+
+    RobotsFetcher::new()
+        .fetch(&format!("http://{}/", redirector.address()))
+        .await
+        .unwrap();
+
+    assert_eq!(scrape("robots_fetch_redirects_sum") - before, 1.0);
+}
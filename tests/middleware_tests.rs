@@ -0,0 +1,123 @@
+#![cfg(feature = "middleware")]
+
+use std::convert::Infallible;
+
+use robots_server::cache::MokaCache;
+use robots_server::client::{ClientError, RobotsClient};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::middleware::{DisallowedError, FailurePolicy, RobotsCheckLayer};
+use robots_server::service::{
+    RobotsServer, robots::v1::robots_service_server::RobotsServiceServer,
+};
+use tonic::transport::{Endpoint, Server};
+use tower::{Layer, ServiceExt, service_fn};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn echo(request: http::Request<()>) -> Result<String, Infallible> {
+    Ok(request.uri().to_string())
+}
+
+fn request(uri: &str) -> http::Request<()> {
+    http::Request::get(uri).body(()).unwrap()
+}
+
+fn unreachable_client() -> RobotsClient {
+    let channel = Endpoint::from_static("http://[::1]:1").connect_lazy();
+    RobotsClient::new(channel)
+}
+
+#[tokio::test]
+async fn test_layer_against_server() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /admin"))
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let server = Server::builder()
+        .add_service(RobotsServiceServer::new(service))
+        .serve_with_shutdown("[::1]:50091".parse().unwrap(), async {
+            rx.await.ok();
+        });
+    let server_handle = tokio::spawn(server);
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let client = RobotsClient::connect("http://[::1]:50091").await.unwrap();
+    let layer = RobotsCheckLayer::new(client.clone(), "TestBot");
+    let base = format!("http://{}", mock_server.address());
+
+    let allowed = layer
+        .layer(service_fn(echo))
+        .oneshot(request(&format!("{base}/page")))
+        .await
+        .unwrap();
+    assert_eq!(allowed, format!("{base}/page"));
+
+    let error = layer
+        .layer(service_fn(echo))
+        .oneshot(request(&format!("{base}/admin/secret")))
+        .await
+        .unwrap_err();
+    let disallowed = error.downcast_ref::<DisallowedError>().unwrap();
+    assert_eq!(disallowed.uri, format!("{base}/admin/secret"));
+    assert_eq!(disallowed.user_agent, "TestBot");
+
+    // Decisions are cached locally, so a changed robots.txt is not seen by
+    // an existing service until the entry expires
+    let cached = layer.layer(service_fn(echo));
+    cached
+        .clone()
+        .oneshot(request(&format!("{base}/page")))
+        .await
+        .unwrap();
+    mock_server.reset().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /"))
+        .mount(&mock_server)
+        .await;
+    client.invalidate_url(&base).await.unwrap();
+
+    cached
+        .oneshot(request(&format!("{base}/page")))
+        .await
+        .unwrap();
+    let error = layer
+        .layer(service_fn(echo))
+        .oneshot(request(&format!("{base}/page")))
+        .await
+        .unwrap_err();
+    assert!(error.is::<DisallowedError>());
+
+    drop(client);
+    drop(layer);
+    tx.send(()).unwrap();
+    server_handle.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_fail_open_allows_when_server_unreachable() {
+    let layer = RobotsCheckLayer::new(unreachable_client(), "TestBot");
+    let response = layer
+        .layer(service_fn(echo))
+        .oneshot(request("http://example.com/page"))
+        .await
+        .unwrap();
+    assert_eq!(response, "http://example.com/page");
+}
+
+#[tokio::test]
+async fn test_fail_closed_rejects_when_server_unreachable() {
+    let layer = RobotsCheckLayer::new(unreachable_client(), "TestBot")
+        .with_failure_policy(FailurePolicy::Closed);
+    let error = layer
+        .layer(service_fn(echo))
+        .oneshot(request("http://example.com/page"))
+        .await
+        .unwrap_err();
+    assert!(error.is::<ClientError>());
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "server")]
+
+use std::path::PathBuf;
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{Config, ConfigHandle};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::overrides::OverrideSource;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    AccessResult, DataSource, GetRobotsRequest, IsAllowedRequest,
+};
+use tonic::Request;
+use wiremock::matchers::any;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("robots-server-{}-{name}", std::process::id()))
+}
+
+/// An origin that must never be contacted.
+async fn untouched_origin() -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nAllow: /"))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+async fn allowed(
+    service: &RobotsServer<MokaCache<String, CachedRobots>>,
+    url: String,
+    user_agent: &str,
+) -> bool {
+    service
+        .is_allowed(Request::new(IsAllowedRequest {
+            target_url: url,
+            user_agent: user_agent.to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .allowed
+}
+
+#[test]
+fn test_override_sources_from_config() {
+    let robots_path = temp_path("override-robots.txt");
+    std::fs::write(&robots_path, "User-agent: *\nDisallow: /from-file\n").unwrap();
+    let config_path = temp_path("overrides.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[robots_overrides]\n\"partner.example\" = {{ content = \"User-agent: *\\nDisallow: /\" }}\n\"Other.Example\" = {{ path = {:?} }}\n",
+            robots_path.display().to_string()
+        ),
+    )
+    .unwrap();
+
+    let config = Config::load(&config_path).unwrap().runtime;
+
+    assert_eq!(
+        config.robots_override("partner.example").unwrap().source,
+        OverrideSource::Content("User-agent: *\nDisallow: /".to_string())
+    );
+    let data = config.robots_override("other.example").unwrap().data_for(
+        "https://other.example/x",
+        "https://other.example/robots.txt",
+    );
+    assert!(!data.is_allowed("MyBot", "/from-file"));
+    assert!(config.robots_override("www.partner.example").is_none());
+
+    std::fs::remove_file(&robots_path).unwrap();
+    assert!(Config::load(&config_path).is_err(), "missing override file");
+    std::fs::remove_file(config_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_overridden_host_is_never_fetched() {
+    let mock_server = untouched_origin().await;
+    let config_path = temp_path("override-service.toml");
+    std::fs::write(
+        &config_path,
+        "[robots_overrides]\n\"127.0.0.1\" = { content = \"User-agent: *\\nDisallow: /checkout\\n\\nUser-agent: PartnerBot\\nAllow: /\" }\n",
+    )
+    .unwrap();
+    let (_, handle) = ConfigHandle::from_file(&config_path).unwrap();
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(handle);
+    let base = format!("http://{}", mock_server.address());
+
+    assert!(!allowed(&service, format!("{base}/checkout/cart"), "MyBot").await);
+    assert!(allowed(&service, format!("{base}/products"), "MyBot").await);
+    assert!(allowed(&service, format!("{base}/checkout/cart"), "PartnerBot").await);
+
+    let response = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: format!("{base}/"),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.access_result(), AccessResult::Override);
+    assert_eq!(response.data_source(), DataSource::Override);
+    assert_eq!(response.robots_txt_url, format!("{base}/robots.txt"));
+    assert_eq!(response.groups.len(), 2);
+
+    std::fs::remove_file(config_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_overrides_follow_reload() {
+    let mock_server = untouched_origin().await;
+    let config_path = temp_path("override-reload.toml");
+    let write_override = |disallow: &str| {
+        std::fs::write(
+            &config_path,
+            format!(
+                "[robots_overrides]\n\"127.0.0.1\" = {{ content = \"User-agent: *\\nDisallow: {disallow}\" }}\n"
+            ),
+        )
+        .unwrap();
+    };
+    write_override("/old");
+    let (_, handle) = ConfigHandle::from_file(&config_path).unwrap();
+    let service =
+        RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(handle.clone());
+    let base = format!("http://{}", mock_server.address());
+
+    assert!(!allowed(&service, format!("{base}/old"), "MyBot").await);
+    assert!(allowed(&service, format!("{base}/new"), "MyBot").await);
+
+    write_override("/new");
+    handle.reload().unwrap();
+
+    assert!(allowed(&service, format!("{base}/old"), "MyBot").await);
+    assert!(!allowed(&service, format!("{base}/new"), "MyBot").await);
+
+    std::fs::remove_file(config_path).unwrap();
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::collections::HashSet;
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    GetRobotsRequest, GetRobotsResponse, RefreshRobotsRequest,
+};
+use tonic::{Code, Request, Status};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use common::{ADMIN_KEY, as_admin, origin_ok};
+
+/// A robots.txt with one group for each of `bot-0` to `bot-{count - 1}`.
+fn groups(count: usize) -> String {
+    (0..count)
+        .map(|i| format!("User-agent: bot-{i}\nDisallow: /p{i}\n\n"))
+        .collect()
+}
+
+async fn page(
+    service: &RobotsServer<MokaCache<String, CachedRobots>>,
+    url: &str,
+    page_size: u32,
+    page_token: String,
+) -> Result<GetRobotsResponse, Status> {
+    service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: url.to_string(),
+            page_size,
+            page_token,
+            ..Default::default()
+        }))
+        .await
+        .map(|response| response.into_inner())
+}
+
+#[tokio::test]
+async fn test_pages_through_all_groups() {
+    let mock_server = origin_ok(&groups(500)).await;
+    let url = format!("http://{}/", mock_server.address());
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+
+    let mut user_agents = Vec::new();
+    let mut page_token = String::new();
+    let mut pages = 0;
+    loop {
+        let response = page(&service, &url, 64, page_token).await.unwrap();
+        assert!(response.groups.len() <= 64);
+        assert_eq!(response.sitemaps, Vec::<String>::new());
+        user_agents.extend(
+            response
+                .groups
+                .into_iter()
+                .flat_map(|group| group.user_agents),
+        );
+        pages += 1;
+        if response.next_page_token.is_empty() {
+            break;
+        }
+        page_token = response.next_page_token;
+    }
+
+    assert_eq!(pages, 8);
+    assert_eq!(user_agents.len(), 500);
+    assert_eq!(user_agents.iter().collect::<HashSet<_>>().len(), 500);
+    let unpaged = page(&service, &url, 0, String::new()).await.unwrap();
+    assert!(unpaged.next_page_token.is_empty());
+    let all = unpaged
+        .groups
+        .into_iter()
+        .flat_map(|group| group.user_agents)
+        .collect::<Vec<_>>();
+    assert_eq!(user_agents, all);
+}
+
+#[tokio::test]
+async fn test_page_token_invalidated_by_refresh() {
+    let mock_server = origin_ok(&groups(10)).await;
+    let url = format!("http://{}/", mock_server.address());
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(
+        ConfigHandle::new(RuntimeConfig {
+            admin_api_keys: vec![ADMIN_KEY.to_string()],
+            ..Default::default()
+        }),
+    );
+
+    let first = page(&service, &url, 4, String::new()).await.unwrap();
+    assert_eq!(first.groups.len(), 4);
+    let token = first.next_page_token;
+    assert!(!token.is_empty());
+    let second = page(&service, &url, 4, token.clone()).await.unwrap();
+    assert_eq!(second.groups[0].user_agents, ["bot-4"]);
+
+    mock_server.reset().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(groups(12)))
+        .mount(&mock_server)
+        .await;
+    service
+        .refresh_robots(as_admin(Request::new(RefreshRobotsRequest {
+            url: url.clone(),
+            force: true,
+        })))
+        .await
+        .unwrap();
+
+    let status = page(&service, &url, 4, token).await.unwrap_err();
+    assert_eq!(status.code(), Code::Aborted);
+    let status = page(&service, &url, 4, "not-a-token".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "server")]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use robots_server::cache::{Cache, CacheResult, MokaCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::client::{ClientError, RobotsClient};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::panic_guard::CatchPanicLayer;
+use robots_server::service::{
+    RobotsServer, robots::v1::robots_service_server::RobotsServiceServer,
+};
+use tonic::Code;
+use tonic::transport::Server;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Cache that panics on its first lookup, standing in for a matcher or
+/// fetcher bug.
+struct PanicOnceCache {
+    inner: MokaCache<String, CachedRobots>,
+    panicked: AtomicBool,
+}
+
+#[async_trait]
+impl Cache<String, CachedRobots> for PanicOnceCache {
+    async fn get(&self, key: &String) -> CacheResult<Option<CachedRobots>> {
+        if !self.panicked.swap(true, Ordering::SeqCst) {
+            panic!("injected panic for {key}");
+        }
+        self.inner.get(key).await
+    }
+
+    async fn set(&self, key: String, value: CachedRobots) -> CacheResult<()> {
+        self.inner.set(key, value).await
+    }
+
+    async fn delete(&self, key: &String) -> CacheResult<bool> {
+        self.inner.delete(key).await
+    }
+}
+
+/// Cache that panics on every lookup for one host.
+struct PanicForHostCache {
+    inner: MokaCache<String, CachedRobots>,
+    host: String,
+}
+
+#[async_trait]
+impl Cache<String, CachedRobots> for PanicForHostCache {
+    async fn get(&self, key: &String) -> CacheResult<Option<CachedRobots>> {
+        if key.contains(&self.host) {
+            panic!("injected panic for {key}");
+        }
+        self.inner.get(key).await
+    }
+
+    async fn set(&self, key: String, value: CachedRobots) -> CacheResult<()> {
+        self.inner.set(key, value).await
+    }
+
+    async fn delete(&self, key: &String) -> CacheResult<bool> {
+        self.inner.delete(key).await
+    }
+}
+
+#[tokio::test]
+async fn test_handler_panic_becomes_internal_status() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /admin"))
+        .mount(&mock_server)
+        .await;
+
+    let cache = PanicOnceCache {
+        inner: MokaCache::new(),
+        panicked: AtomicBool::new(false),
+    };
+    let service = RobotsServer::new(cache, RobotsFetcher::new());
+    let layer = CatchPanicLayer::new();
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let server = Server::builder()
+        .layer(layer.clone())
+        .add_service(RobotsServiceServer::new(service))
+        .serve_with_shutdown("[::1]:50101".parse().unwrap(), async {
+            rx.await.ok();
+        });
+    let server_handle = tokio::spawn(server);
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let client = RobotsClient::connect("http://[::1]:50101").await.unwrap();
+    let url = format!("http://{}/admin", mock_server.address());
+
+    match client.is_allowed(&url, "MyBot").await.unwrap_err() {
+        ClientError::Status(status) => {
+            assert_eq!(status.code(), Code::Internal);
+            assert_eq!(status.message(), "internal error");
+        }
+        other => panic!("expected a gRPC status, got {other}"),
+    }
+    assert_eq!(layer.panics(), 1);
+
+    // The same key is served normally afterwards, over the same connection
+    let response = client.is_allowed(&url, "MyBot").await.unwrap();
+    assert!(!response.allowed);
+    assert_eq!(layer.panics(), 1);
+
+    drop(client);
+    tx.send(()).unwrap();
+    server_handle.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_panic_spares_calls_in_flight_on_the_connection() {
+    let slow_origin = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("User-agent: *\nDisallow: /admin")
+                .set_delay(Duration::from_millis(500)),
+        )
+        .mount(&slow_origin)
+        .await;
+    let cache = PanicForHostCache {
+        inner: MokaCache::new(),
+        host: "panics.example".to_string(),
+    };
+    let service = RobotsServer::new(cache, RobotsFetcher::new());
+    tokio::spawn(
+        Server::builder()
+            .layer(CatchPanicLayer::new())
+            .add_service(RobotsServiceServer::new(service))
+            .serve("[::1]:50086".parse().unwrap()),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let client = RobotsClient::connect("http://[::1]:50086").await.unwrap();
+
+    let in_flight = tokio::spawn({
+        let client = client.clone();
+        let url = format!("http://{}/admin", slow_origin.address());
+        async move { client.is_allowed(&url, "MyBot").await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    match client
+        .is_allowed("http://panics.example/", "MyBot")
+        .await
+        .unwrap_err()
+    {
+        ClientError::Status(status) => assert_eq!(status.code(), Code::Internal),
+        other => panic!("expected a gRPC status, got {other}"),
+    }
+    assert!(!in_flight.is_finished());
+
+    let response = in_flight.await.unwrap().unwrap();
+    assert!(!response.allowed);
+}
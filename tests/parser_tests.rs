@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use robots_server::parser::parse;
+use robots_server::robots_data::{Group, RobotsData, Rule, RuleType};
+
+fn user_agents(group: &Group) -> Vec<&str> {
+    group.user_agents.iter().map(AsRef::as_ref).collect()
+}
+
+#[test]
+fn test_parse_groups_in_source_order() {
+    let data = parse("User-agent: b\nDisallow: /b\n\nUser-agent: a\nDisallow: /a\n");
+    assert_eq!(data.groups.len(), 2);
+    assert_eq!(user_agents(&data.groups[0]), vec!["b"]);
+    assert_eq!(user_agents(&data.groups[1]), vec!["a"]);
+}
+#[test]
+fn test_parse_consecutive_user_agents_share_group() {
+    let data = parse("User-agent: BotOne\nUser-agent: BotTwo\nDisallow: /private/\n");
+    assert_eq!(data.groups.len(), 1);
+    assert_eq!(user_agents(&data.groups[0]), vec!["BotOne", "BotTwo"]);
+    assert_eq!(data.groups[0].rules.len(), 1);
+    assert_eq!(data.groups[0].rules[0].rule_type, RuleType::Disallow);
+}
+#[test]
+fn test_parse_comments_and_case() {
+    let data = parse("# header\nUSER-AGENT: * # everyone\nALLOW: /public # ok\ndisallow: /\n");
+    assert_eq!(data.groups.len(), 1);
+    assert_eq!(user_agents(&data.groups[0]), vec!["*"]);
+    assert_eq!(&*data.groups[0].rules[0].path_pattern, "/public");
+    assert_eq!(data.groups[0].rules[0].rule_type, RuleType::Allow);
+    assert_eq!(&*data.groups[0].rules[1].path_pattern, "/");
+}
+#[test]
+fn test_parse_empty_disallow_is_ignored() {
+    let data = parse("User-agent: *\nDisallow:\n");
+    assert_eq!(data.groups.len(), 1);
+    assert!(data.groups[0].rules.is_empty());
+}
+#[test]
+fn test_parse_crawl_delay() {
+    let data = parse("User-agent: slowbot\nCrawl-delay: 2.5\n\nUser-agent: *\nCrawl-delay: 1\n");
+    assert_eq!(
+        data.groups[0].crawl_delay,
+        Some(Duration::from_millis(2500))
+    );
+    assert_eq!(
+        data.crawl_delay("SlowBot/1.0"),
+        Some(Duration::from_millis(2500))
+    );
+    assert_eq!(data.crawl_delay("OtherBot"), Some(Duration::from_secs(1)));
+}
+#[test]
+fn test_parse_invalid_crawl_delay_ignored() {
+    let data = parse("User-agent: *\nCrawl-delay: soon\nCrawl-delay: -1\n");
+    assert_eq!(data.groups[0].crawl_delay, None);
+    assert_eq!(data.crawl_delay("MyBot"), None);
+}
+#[test]
+fn test_parse_sitemaps_anywhere() {
+    let data = parse(
+        "Sitemap: https://example.com/a.xml\nUser-agent: *\nDisallow: /\nSitemap: https://example.com/b.xml\n",
+    );
+    assert_eq!(
+        data.sitemaps,
+        vec!["https://example.com/a.xml", "https://example.com/b.xml"]
+    );
+}
+#[test]
+fn test_parse_rules_before_user_agent_ignored() {
+    let data = parse("Disallow: /nothing\nUser-agent: *\nDisallow: /private\n");
+    assert_eq!(data.groups.len(), 1);
+    assert_eq!(data.groups[0].rules.len(), 1);
+}
+#[test]
+fn test_parse_crlf_and_bom() {
+    let data = parse("\u{feff}User-agent: *\r\nDisallow: /admin\r\n");
+    assert_eq!(data.groups.len(), 1);
+    assert_eq!(&*data.groups[0].rules[0].path_pattern, "/admin");
+}
+#[test]
+fn test_parse_warnings() {
+    let data = parse("Disallow: /early\nUser-agent: *\nbogus\nCrawl-delay: soon\nNoindex: /x\n");
+    let lines: Vec<usize> = data.warnings.iter().map(|w| w.line).collect();
+    assert_eq!(lines, vec![1, 3, 4, 5]);
+    assert!(data.warnings[3].message.contains("noindex"));
+}
+#[test]
+fn test_parse_checksum() {
+    let a = parse("User-agent: *\nDisallow: /a\n");
+    let b = parse("User-agent: *\nDisallow: /b\n");
+    assert_eq!(a.checksum.len(), 64);
+    assert_ne!(a.checksum, b.checksum);
+    assert_eq!(a.checksum, parse("User-agent: *\nDisallow: /a\n").checksum);
+}
+#[test]
+fn test_parse_sitemaps_deduplicated_in_first_seen_order() {
+    let body = "Sitemap: https://example.com/b.xml\n\
+                Sitemap: https://example.com/a.xml\n\
+                User-agent: *\n\
+                Sitemap: HTTPS://Example.com/b.xml\n\
+                Sitemap: https://example.com:443/a.xml\n\
+                Sitemap: https://example.com/c.xml\n\
+                Sitemap: https://example.com/b.xml\n";
+    let data = parse(body);
+    assert_eq!(
+        data.sitemaps,
+        vec![
+            "https://example.com/b.xml",
+            "https://example.com/a.xml",
+            "https://example.com/c.xml",
+        ]
+    );
+    assert!(data.warnings.is_empty());
+    assert_eq!(parse(body).sitemaps, data.sitemaps);
+}
+
+#[test]
+fn test_parse_line_numbers() {
+    let body = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/robots.txt"
+    ))
+    .unwrap();
+    let data = parse(&body);
+    let lines = |group: &Group| {
+        group
+            .rules
+            .iter()
+            .map(|rule| (rule.path_pattern.to_string(), rule.line_number))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        lines(&data.groups[0]),
+        [
+            ("/private/".to_string(), 4),
+            ("/private/public$".to_string(), 5)
+        ]
+    );
+    assert_eq!(lines(&data.groups[1]), [("/tmp".to_string(), 9)]);
+    assert_eq!(data.sitemaps, ["https://example.com/sitemap.xml"]);
+    assert_eq!(data.sitemap_line_numbers, [13]);
+    assert_eq!(data.warnings[0].line, 10);
+
+    let rendered = String::from(&data);
+    assert!(rendered.contains("Disallow: /tmp  # line 9\n"));
+    assert!(rendered.ends_with("Sitemap: https://example.com/sitemap.xml  # line 13"));
+    // Rendered line references are comments, so they parse back the same
+    let reparsed = parse(&rendered);
+    assert_eq!(reparsed.groups[1].rules[0].path_pattern.as_ref(), "/tmp");
+
+    // Rules not read from a file have no line reference
+    let built = RobotsData {
+        groups: vec![Group::new(
+            vec!["*".to_string()],
+            vec![Rule::new(RuleType::Disallow, "/")],
+            None,
+        )],
+        ..Default::default()
+    };
+    assert_eq!(built.groups[0].rules[0].line_number, 0);
+    assert_eq!(String::from(&built), "User-agent: *\nDisallow: /\n");
+}
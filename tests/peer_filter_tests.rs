@@ -0,0 +1,74 @@
+#![cfg(feature = "server")]
+
+use robots_server::cache::MokaCache;
+use robots_server::client::{ClientError, RobotsClient};
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::peer_filter::PeerAllowlistLayer;
+use robots_server::service::{
+    RobotsServer, robots::v1::robots_service_server::RobotsServiceServer,
+};
+use tonic::Code;
+use tonic::transport::Server;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Serve on `addr` behind an allowlist of `networks` and make one call from
+/// 127.0.0.1, returning the call's result and the rejection count.
+async fn call_with_allowlist(addr: &str, networks: &[&str]) -> (Result<bool, ClientError>, u64) {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /admin"))
+        .mount(&mock_server)
+        .await;
+
+    let config = ConfigHandle::new(RuntimeConfig {
+        client_allowlist: networks.iter().map(|n| n.parse().unwrap()).collect(),
+        ..Default::default()
+    });
+    let layer = PeerAllowlistLayer::new(config.clone());
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(config);
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let server = Server::builder()
+        .layer(layer.clone())
+        .add_service(RobotsServiceServer::new(service))
+        .serve_with_shutdown(addr.parse().unwrap(), async {
+            rx.await.ok();
+        });
+    let server_handle = tokio::spawn(server);
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let client = RobotsClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    let url = format!("http://{}/page", mock_server.address());
+    let result = client
+        .is_allowed(&url, "MyBot")
+        .await
+        .map(|response| response.allowed);
+
+    drop(client);
+    tx.send(()).unwrap();
+    server_handle.await.unwrap().unwrap();
+    (result, layer.rejected())
+}
+
+#[tokio::test]
+async fn test_client_in_allowlist_accepted() {
+    let (result, rejected) =
+        call_with_allowlist("127.0.0.1:50111", &["10.0.0.0/8", "127.0.0.0/8"]).await;
+    assert!(result.unwrap());
+    assert_eq!(rejected, 0);
+}
+
+#[tokio::test]
+async fn test_client_outside_allowlist_rejected() {
+    let (result, rejected) =
+        call_with_allowlist("127.0.0.1:50112", &["10.0.0.0/8", "::1/128"]).await;
+    match result.unwrap_err() {
+        ClientError::Status(status) => assert_eq!(status.code(), Code::PermissionDenied),
+        other => panic!("expected a gRPC status, got {other}"),
+    }
+    assert_eq!(rejected, 1);
+}
@@ -0,0 +1,215 @@
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::clock::ManualClock;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::overrides::PolicyRules;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    ClearCacheRequest, DataSource, GetRobotsRequest, InvalidateUrlRequest, IsAllowedRequest,
+    IsAllowedResponse, Rule, SetPolicyRulesRequest, rule::RuleType,
+};
+use tonic::{Code, Request};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn robots_server(body: &str, fetches: u64) -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .expect(fetches)
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+async fn decide(
+    service: &RobotsServer<MokaCache<String, CachedRobots>>,
+    url: String,
+) -> IsAllowedResponse {
+    service
+        .is_allowed(Request::new(IsAllowedRequest {
+            target_url: url,
+            user_agent: "MyBot".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+fn rule(rule_type: RuleType, path_pattern: &str) -> Rule {
+    Rule {
+        rule_type: rule_type.into(),
+        path_pattern: path_pattern.to_string(),
+        line_number: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_configured_rules_override_robots_txt() {
+    let mock_server = robots_server("User-agent: *\nDisallow: /press/\n", 1).await;
+    let mut config = RuntimeConfig::default();
+    config.policy_rules.insert(
+        "127.0.0.1".to_string(),
+        PolicyRules {
+            allow: vec!["/press/".to_string()],
+            disallow: vec!["/checkout/".to_string()],
+        },
+    );
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new())
+        .with_config(ConfigHandle::new(config));
+    let base = format!("http://{}", mock_server.address());
+
+    let denied = decide(&service, format!("{base}/checkout/cart")).await;
+    assert!(!denied.allowed);
+    assert_eq!(denied.reason, "POLICY: Disallow: /checkout/");
+    assert_eq!(denied.data_source(), DataSource::SupplementalPolicy);
+
+    let allowed = decide(&service, format!("{base}/press/release")).await;
+    assert!(allowed.allowed);
+    assert_eq!(allowed.reason, "POLICY: Allow: /press/");
+
+    // Paths without a supplemental rule fall through to robots.txt
+    let fallthrough = decide(&service, format!("{base}/products")).await;
+    assert!(fallthrough.allowed);
+    assert!(!fallthrough.reason.starts_with("POLICY: "));
+    assert_eq!(fallthrough.data_source(), DataSource::FreshFetch);
+}
+
+#[tokio::test]
+async fn test_rules_set_over_rpc_survive_refetch() {
+    let mock_server = robots_server("User-agent: *\nDisallow: /\n", 2).await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let base = format!("http://{}", mock_server.address());
+    assert!(
+        !decide(&service, format!("{base}/partner/feed"))
+            .await
+            .allowed
+    );
+
+    let response = service
+        .set_policy_rules(Request::new(SetPolicyRulesRequest {
+            url: format!("{base}/"),
+            rules: vec![rule(RuleType::Allow, "/partner/")],
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.host, "127.0.0.1");
+    assert_eq!(response.rules, [rule(RuleType::Allow, "/partner/")]);
+
+    assert!(
+        decide(&service, format!("{base}/partner/feed"))
+            .await
+            .allowed
+    );
+    assert!(!decide(&service, format!("{base}/other")).await.allowed);
+
+    service
+        .invalidate_url(Request::new(InvalidateUrlRequest {
+            url: format!("{base}/"),
+        }))
+        .await
+        .unwrap();
+    assert!(!decide(&service, format!("{base}/other")).await.allowed);
+    assert!(
+        decide(&service, format!("{base}/partner/feed"))
+            .await
+            .allowed
+    );
+
+    let robots = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: format!("{base}/"),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(robots.policy_rules, [rule(RuleType::Allow, "/partner/")]);
+    assert_eq!(robots.groups.len(), 1);
+
+    // No rules removes them again
+    service
+        .set_policy_rules(Request::new(SetPolicyRulesRequest {
+            url: format!("{base}/"),
+            rules: Vec::new(),
+        }))
+        .await
+        .unwrap();
+    assert!(
+        !decide(&service, format!("{base}/partner/feed"))
+            .await
+            .allowed
+    );
+}
+
+#[tokio::test]
+async fn test_rules_set_over_rpc_outlive_cache_ttl_and_clear() {
+    let mock_server = robots_server("User-agent: *\nDisallow: /\n", 1).await;
+    let config = ConfigHandle::new(RuntimeConfig {
+        cache_ttl_secs: 60,
+        allow_clear_cache: true,
+        ..RuntimeConfig::default()
+    });
+    let clock = Arc::new(ManualClock::new());
+    let service = RobotsServer::builder()
+        .cache(
+            MokaCache::builder()
+                .config(config.clone())
+                .clock(clock.clone())
+                .build(),
+        )
+        .fetcher(RobotsFetcher::with_config(config.clone()))
+        .config(config)
+        .clock(clock.clone())
+        .build()
+        .unwrap();
+    let base = format!("http://{}", mock_server.address());
+    assert!(!decide(&service, format!("{base}/other")).await.allowed);
+    service
+        .set_policy_rules(Request::new(SetPolicyRulesRequest {
+            url: format!("{base}/"),
+            rules: vec![rule(RuleType::Allow, "/partner/")],
+        }))
+        .await
+        .unwrap();
+
+    clock.advance(Duration::from_secs(120));
+    let allowed = decide(&service, format!("{base}/partner/feed")).await;
+    assert!(allowed.allowed);
+    assert_eq!(allowed.reason, "POLICY: Allow: /partner/");
+
+    service
+        .clear_cache(Request::new(ClearCacheRequest {}))
+        .await
+        .unwrap();
+    assert!(
+        decide(&service, format!("{base}/partner/feed"))
+            .await
+            .allowed
+    );
+}
+
+#[tokio::test]
+async fn test_rule_type_is_required() {
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+
+    let status = service
+        .set_policy_rules(Request::new(SetPolicyRulesRequest {
+            url: "https://example.com/".to_string(),
+            rules: vec![rule(RuleType::Unspecified, "/x")],
+        }))
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
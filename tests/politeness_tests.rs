@@ -0,0 +1,264 @@
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use robots_server::cache::MokaCache;
+use robots_server::clock::ManualClock;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::politeness::{
+    FetchSlot, HostBucketStore, HostRateLimiter, MokaBucketStore, PolitenessTracker, RateLimit,
+};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{IsAllowedRequest, NextAllowedFetchRequest};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_tracker_spaces_grants() {
+    let clock = Arc::new(ManualClock::new());
+    let tracker = PolitenessTracker::new().with_clock(clock.clone());
+    let delay = Duration::from_millis(200);
+
+    assert_eq!(
+        tracker.try_acquire("http://a", "bot", delay).await,
+        FetchSlot::Granted
+    );
+    assert!(matches!(
+        tracker.try_acquire("http://a", "bot", delay).await,
+        FetchSlot::Wait(_)
+    ));
+    // Other hosts and user-agents have their own slots
+    assert_eq!(
+        tracker.try_acquire("http://b", "bot", delay).await,
+        FetchSlot::Granted
+    );
+    assert_eq!(
+        tracker.try_acquire("http://a", "otherbot", delay).await,
+        FetchSlot::Granted
+    );
+
+    clock.advance(delay);
+    assert_eq!(
+        tracker.try_acquire("http://a", "bot", delay).await,
+        FetchSlot::Granted
+    );
+}
+#[tokio::test]
+async fn test_tracker_concurrent_grants_not_double_booked() {
+    let tracker = Arc::new(PolitenessTracker::new());
+    let mut handles = Vec::new();
+    for _ in 0..20 {
+        let tracker = tracker.clone();
+        handles.push(tokio::spawn(async move {
+            tracker
+                .try_acquire("http://a", "bot", Duration::from_secs(10))
+                .await
+        }));
+    }
+    let mut granted = 0;
+    for handle in handles {
+        if handle.await.unwrap() == FetchSlot::Granted {
+            granted += 1;
+        }
+    }
+    assert_eq!(granted, 1);
+}
+
+#[tokio::test]
+async fn test_next_allowed_fetch_uses_crawl_delay() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nCrawl-delay: 1"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let host_url = format!("http://{}/", mock_server.address());
+    let request = || {
+        Request::new(NextAllowedFetchRequest {
+            host_url: host_url.clone(),
+            user_agent: "MyBot".to_string(),
+        })
+    };
+
+    let first = service.next_allowed_fetch(request()).await.unwrap();
+    assert!(first.get_ref().granted);
+    assert_eq!(first.get_ref().crawl_delay_ms, 1000);
+    assert!(first.get_ref().crawl_delay_from_robots);
+
+    let second = service.next_allowed_fetch(request()).await.unwrap();
+    assert!(!second.get_ref().granted);
+    assert!(second.get_ref().wait_ms > 0 && second.get_ref().wait_ms <= 1000);
+
+    tokio::time::sleep(Duration::from_millis(second.get_ref().wait_ms)).await;
+    let third = service.next_allowed_fetch(request()).await.unwrap();
+    assert!(third.get_ref().granted);
+}
+#[tokio::test]
+async fn test_next_allowed_fetch_default_delay() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /x"))
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new())
+        .with_default_crawl_delay(Duration::from_millis(250));
+    let host_url = format!("http://{}/", mock_server.address());
+
+    let mut grants = 0;
+    for _ in 0..5 {
+        let response = service
+            .next_allowed_fetch(Request::new(NextAllowedFetchRequest {
+                host_url: host_url.clone(),
+                user_agent: "MyBot".to_string(),
+            }))
+            .await
+            .unwrap();
+        assert_eq!(response.get_ref().crawl_delay_ms, 250);
+        assert!(!response.get_ref().crawl_delay_from_robots);
+        if response.get_ref().granted {
+            grants += 1;
+        }
+    }
+    assert_eq!(grants, 1);
+}
+
+#[tokio::test]
+async fn test_bucket_store_refills_at_rate() {
+    let clock = Arc::new(ManualClock::new());
+    let store = MokaBucketStore::new().with_clock(clock.clone());
+    let limit = RateLimit {
+        per_second: 10.0,
+        burst: 2.0,
+    };
+
+    assert_eq!(
+        store.take("http://a", limit).await.unwrap(),
+        FetchSlot::Granted
+    );
+    assert_eq!(
+        store.take("http://a", limit).await.unwrap(),
+        FetchSlot::Granted
+    );
+    let FetchSlot::Wait(wait) = store.take("http://a", limit).await.unwrap() else {
+        panic!("burst exhausted, expected a wait");
+    };
+    assert_eq!(wait, Duration::from_millis(100));
+    // Buckets are per host
+    assert_eq!(
+        store.take("http://b", limit).await.unwrap(),
+        FetchSlot::Granted
+    );
+
+    clock.advance(wait / 2);
+    assert!(matches!(
+        store.take("http://a", limit).await.unwrap(),
+        FetchSlot::Wait(_)
+    ));
+    clock.advance(wait / 2);
+    assert_eq!(
+        store.take("http://a", limit).await.unwrap(),
+        FetchSlot::Granted
+    );
+}
+#[tokio::test]
+async fn test_unlimited_host_rate_always_grants() {
+    let limiter = HostRateLimiter::new(RateLimit::UNLIMITED);
+    for _ in 0..10 {
+        assert_eq!(limiter.acquire("http://a").await, FetchSlot::Granted);
+    }
+}
+#[tokio::test]
+async fn test_host_rate_serializes_interleaved_clients() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /x"))
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new())
+        .with_default_crawl_delay(Duration::ZERO)
+        .with_host_rate_limiter(HostRateLimiter::new(RateLimit {
+            per_second: 5.0,
+            burst: 1.0,
+        }));
+    let host_url = format!("http://{}/", mock_server.address());
+    let request = |user_agent: &str| {
+        Request::new(NextAllowedFetchRequest {
+            host_url: host_url.clone(),
+            user_agent: user_agent.to_string(),
+        })
+    };
+
+    // Two clients with no crawl-delay of their own still share the host rate
+    let mut grants = Vec::new();
+    for round in 0..2 {
+        let mut waits = Vec::new();
+        for client in ["ClientA", "ClientB", "ClientA", "ClientB"] {
+            let response = service.next_allowed_fetch(request(client)).await.unwrap();
+            let response = response.into_inner();
+            if response.granted {
+                grants.push((round, client));
+            } else {
+                assert!(response.wait_ms > 0 && response.wait_ms <= 200);
+                waits.push(response.wait_ms);
+            }
+        }
+        assert_eq!(waits.len(), 3);
+        tokio::time::sleep(Duration::from_millis(*waits.iter().max().unwrap())).await;
+    }
+    assert_eq!(grants, vec![(0, "ClientA"), (1, "ClientA")]);
+}
+#[tokio::test]
+async fn test_is_allowed_suggests_wait() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let request = |path: &str| {
+        Request::new(IsAllowedRequest {
+            target_url: format!("http://{}{path}", mock_server.address()),
+            user_agent: "MyBot".to_string(),
+            ..Default::default()
+        })
+    };
+
+    let first = service
+        .is_allowed(request("/a"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(first.allowed);
+    assert_eq!(first.suggested_wait_ms, 0);
+
+    let second = service
+        .is_allowed(request("/b"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(second.allowed);
+    assert!(second.suggested_wait_ms > 0 && second.suggested_wait_ms <= 1000);
+
+    // Disallowed URLs are not fetched, so they carry no pacing hint
+    let denied = service
+        .is_allowed(request("/private"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!denied.allowed);
+    assert_eq!(denied.suggested_wait_ms, 0);
+}
@@ -0,0 +1,213 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::time::Duration;
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    DataSource, GetPrefetchStatusRequest, GetPrefetchStatusResponse, GetRobotsRequest,
+    PrefetchHostsRequest, PrefetchHostsResponse,
+};
+use tonic::{Code, Request};
+use wiremock::MockServer;
+
+use common::{ROBOTS_TXT, fetches, origin, origin_ok};
+
+type Service = RobotsServer<MokaCache<String, CachedRobots>>;
+
+async fn prefetch(service: &Service, urls: Vec<String>) -> PrefetchHostsResponse {
+    service
+        .prefetch_hosts(Request::new(PrefetchHostsRequest { urls }))
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+async fn status(service: &Service, job_id: u64) -> GetPrefetchStatusResponse {
+    service
+        .get_prefetch_status(Request::new(GetPrefetchStatusRequest { job_id }))
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+async fn until_finished(service: &Service, job_id: u64) -> GetPrefetchStatusResponse {
+    loop {
+        let status = status(service, job_id).await;
+        if status.remaining == 0 {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_prefetch_warms_cache() {
+    let origins = [
+        origin_ok(ROBOTS_TXT).await,
+        origin(200, ROBOTS_TXT, Duration::from_millis(100), &[]).await,
+        origin(404, ROBOTS_TXT, Duration::ZERO, &[]).await,
+    ];
+    let service: Service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let mut urls = origins
+        .iter()
+        .map(|origin| format!("http://{}/", origin.address()))
+        .collect::<Vec<_>>();
+    urls.push("not a url".to_string());
+    urls.push(format!("http://{}/other/page", origins[0].address()));
+
+    let response = prefetch(&service, urls.clone()).await;
+    assert_eq!(response.accepted, 3);
+    assert_eq!(response.rejected, 1);
+    assert_eq!(response.skipped, 1);
+
+    let status = tokio::time::timeout(
+        Duration::from_secs(5),
+        until_finished(&service, response.job_id),
+    )
+    .await
+    .expect("prefetch job finishes");
+    assert_eq!(status.job_id, response.job_id);
+    assert_eq!(status.done, 3);
+    assert_eq!(status.failed, 0);
+
+    for url in &urls[..3] {
+        let response = service
+            .get_robots_txt(Request::new(GetRobotsRequest {
+                url: url.clone(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(response.get_ref().data_source(), DataSource::Cache, "{url}");
+    }
+    for origin in &origins {
+        assert_eq!(fetches(origin).await, 1);
+    }
+
+    // Hosts already cached are not fetched again
+    let again = prefetch(&service, urls[..3].to_vec()).await;
+    assert_ne!(again.job_id, response.job_id);
+    assert_eq!(again.accepted, 0);
+    assert_eq!(again.skipped, 3);
+}
+
+#[tokio::test]
+async fn test_prefetch_queue_is_bounded() {
+    let origins = [
+        origin(200, ROBOTS_TXT, Duration::from_millis(200), &[]).await,
+        origin(200, ROBOTS_TXT, Duration::from_millis(200), &[]).await,
+        MockServer::start().await,
+    ];
+    let service: Service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(
+        ConfigHandle::new(RuntimeConfig {
+            prefetch_queue_capacity: 2,
+            ..Default::default()
+        }),
+    );
+    let urls = origins
+        .iter()
+        .map(|origin| format!("http://{}/", origin.address()))
+        .collect::<Vec<_>>();
+
+    let first = prefetch(&service, urls[..1].to_vec()).await;
+    assert_eq!(first.accepted, 1);
+    let second = prefetch(&service, urls[1..].to_vec()).await;
+    assert_eq!(second.accepted, 1);
+    assert_eq!(second.rejected, 1);
+
+    for job_id in [first.job_id, second.job_id] {
+        let status = until_finished(&service, job_id).await;
+        assert_eq!(status.done, 1);
+    }
+    for origin in &origins[..2] {
+        assert_eq!(fetches(origin).await, 1);
+    }
+}
+
+#[tokio::test]
+async fn test_unknown_prefetch_job() {
+    let service: Service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let status = service
+        .get_prefetch_status(Request::new(GetPrefetchStatusRequest { job_id: 42 }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_prefetch_reports_item_errors() {
+    let accepted = origin(200, ROBOTS_TXT, Duration::from_millis(200), &[]).await;
+    let overflow = MockServer::start().await;
+    let service: Service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(
+        ConfigHandle::new(RuntimeConfig {
+            prefetch_queue_capacity: 1,
+            host_blocklist: vec!["blocked.example.com".to_string()],
+            ..Default::default()
+        }),
+    );
+    let urls = vec![
+        format!("http://{}/", accepted.address()),
+        "not a url".to_string(),
+        "https://blocked.example.com/".to_string(),
+        format!("http://{}/page", accepted.address()),
+        "ftp://example.com/file".to_string(),
+        format!("http://{}/", overflow.address()),
+    ];
+
+    let response = prefetch(&service, urls).await;
+    assert_eq!(response.accepted, 1);
+    assert_eq!(response.skipped, 1);
+    assert_eq!(response.rejected, 4);
+    let errors = response
+        .errors
+        .iter()
+        .map(|error| (error.index, Code::from(error.code)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        [
+            (1, Code::InvalidArgument),
+            (2, Code::PermissionDenied),
+            (4, Code::InvalidArgument),
+            (5, Code::ResourceExhausted),
+        ]
+    );
+    assert!(response.errors[0].message.starts_with("Invalid URL: "));
+    assert_eq!(
+        response.errors[1].message,
+        "Host blocked.example.com is not permitted"
+    );
+    until_finished(&service, response.job_id).await;
+    assert_eq!(fetches(&accepted).await, 1);
+}
+
+#[tokio::test]
+async fn test_oversized_batch_rejected() {
+    let service: Service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(
+        ConfigHandle::new(RuntimeConfig {
+            max_batch_items: 2,
+            ..Default::default()
+        }),
+    );
+    let urls = (0..3)
+        .map(|i| format!("https://host-{i}.example.com/"))
+        .collect();
+
+    let status = service
+        .prefetch_hosts(Request::new(PrefetchHostsRequest { urls }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::OutOfRange);
+    assert_eq!(
+        status.metadata().get("x-error-reason").unwrap(),
+        "BATCH_TOO_LARGE"
+    );
+    assert_eq!(status.metadata().get("x-max-batch-items").unwrap(), "2");
+}
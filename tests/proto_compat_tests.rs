@@ -0,0 +1,92 @@
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+
+use prost::Message;
+use prost_types::FileDescriptorSet;
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::legacy::LegacyRobotsServer;
+use robots_server::service::robots::robots_service_client::RobotsServiceClient as LegacyRobotsServiceClient;
+use robots_server::service::robots::robots_service_server::RobotsServiceServer as LegacyRobotsServiceServer;
+use robots_server::service::robots::v1::robots_service_client::RobotsServiceClient;
+use robots_server::service::robots::v1::robots_service_server::RobotsServiceServer;
+use robots_server::service::robots::v1::{GetServerInfoRequest, IsAllowedRequest};
+use robots_server::service::{FILE_DESCRIPTOR_SET, RobotsServer};
+use tonic::transport::{Channel, Server};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_legacy_and_v1_paths_share_one_service() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /admin"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let service = Arc::new(RobotsServer::new(MokaCache::new(), RobotsFetcher::new()));
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let server = Server::builder()
+        .add_service(RobotsServiceServer::from_arc(service.clone()))
+        .add_service(LegacyRobotsServiceServer::new(LegacyRobotsServer::new(
+            service,
+        )))
+        .serve_with_shutdown("[::1]:50121".parse().unwrap(), async {
+            rx.await.ok();
+        });
+    let server_handle = tokio::spawn(server);
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let channel = Channel::from_static("http://[::1]:50121")
+        .connect()
+        .await
+        .unwrap();
+    let mut v1 = RobotsServiceClient::new(channel.clone());
+    let mut legacy = LegacyRobotsServiceClient::new(channel);
+    let request = IsAllowedRequest {
+        target_url: format!("http://{}/admin/panel", mock_server.address()),
+        user_agent: "MyBot".to_string(),
+        ..Default::default()
+    };
+
+    // /robots.v1.RobotsService/IsAllowed
+    let response = v1.is_allowed(request.clone()).await.unwrap().into_inner();
+    assert!(!response.allowed);
+    // /robots.RobotsService/IsAllowed, answered from the same cache
+    let response = legacy.is_allowed(request).await.unwrap().into_inner();
+    assert!(!response.allowed);
+
+    let info = legacy
+        .get_server_info(GetServerInfoRequest {})
+        .await
+        .unwrap()
+        .into_inner();
+    let cache = info.cache.unwrap();
+    assert_eq!((cache.misses, cache.hits), (1, 1));
+
+    drop((v1, legacy));
+    tx.send(()).unwrap();
+    server_handle.await.unwrap().unwrap();
+}
+
+#[test]
+fn test_descriptor_set_lists_both_packages() {
+    let descriptors = FileDescriptorSet::decode(FILE_DESCRIPTOR_SET).unwrap();
+    let mut services: Vec<String> = descriptors
+        .file
+        .iter()
+        .flat_map(|file| {
+            file.service
+                .iter()
+                .map(move |service| format!("{}.{}", file.package(), service.name()))
+        })
+        .collect();
+    services.sort();
+    assert_eq!(
+        services,
+        ["robots.RobotsService", "robots.v1.RobotsService"]
+    );
+}
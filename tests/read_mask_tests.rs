@@ -0,0 +1,250 @@
+#![cfg(feature = "server")]
+
+use prost_types::FieldMask;
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{AccessResult, GetRobotsRequest, GetRobotsResponse};
+use tonic::{Code, Request, Status};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ROBOTS_TXT: &str = include_str!("fixtures/robots.txt");
+
+const MULTI_GROUP_ROBOTS_TXT: &str = "User-agent: Googlebot
+Disallow: /google-only
+
+User-agent: bingbot
+User-agent: Slurp
+Disallow: /search
+
+User-agent: *
+Disallow: /private
+
+User-agent: googlebot-news
+Disallow: /news
+";
+
+async fn setup() -> (MockServer, RobotsServer<MokaCache<String, CachedRobots>>) {
+    setup_with(ROBOTS_TXT).await
+}
+
+async fn setup_with(body: &str) -> (MockServer, RobotsServer<MokaCache<String, CachedRobots>>) {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    (mock_server, service)
+}
+
+async fn get_robots(
+    service: &RobotsServer<MokaCache<String, CachedRobots>>,
+    mock_server: &MockServer,
+    paths: Option<&[&str]>,
+) -> Result<GetRobotsResponse, Status> {
+    let request = GetRobotsRequest {
+        url: format!("http://{}/", mock_server.address()),
+        read_mask: paths.map(|paths| FieldMask {
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+        }),
+        ..Default::default()
+    };
+    service
+        .get_robots_txt(Request::new(request))
+        .await
+        .map(|response| response.into_inner())
+}
+
+#[tokio::test]
+async fn test_sitemaps_only() {
+    let (mock_server, service) = setup().await;
+
+    let response = get_robots(&service, &mock_server, Some(&["sitemaps"]))
+        .await
+        .unwrap();
+
+    assert_eq!(response.sitemaps, ["https://example.com/sitemap.xml"]);
+    assert!(response.groups.is_empty());
+    assert!(response.target_url.is_empty());
+    assert_eq!(response.http_status_code, 0);
+    assert_eq!(service.groups_converted(), 0);
+}
+
+#[tokio::test]
+async fn test_access_result_and_status() {
+    let (mock_server, service) = setup().await;
+
+    let response = get_robots(
+        &service,
+        &mock_server,
+        Some(&["access_result", "http_status_code"]),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.access_result(), AccessResult::Success);
+    assert_eq!(response.http_status_code, 200);
+    assert!(response.groups.is_empty());
+    assert!(response.sitemaps.is_empty());
+    assert_eq!(service.groups_converted(), 0);
+}
+
+#[tokio::test]
+async fn test_no_mask_returns_everything() {
+    let (mock_server, service) = setup().await;
+
+    let unmasked = get_robots(&service, &mock_server, None).await.unwrap();
+    let empty_mask = get_robots(&service, &mock_server, Some(&[])).await.unwrap();
+
+    assert_eq!(unmasked, empty_mask);
+    assert_eq!(unmasked.groups.len(), 2);
+    assert_eq!(unmasked.sitemaps.len(), 1);
+    assert_eq!(unmasked.http_status_code, 200);
+    assert!(!unmasked.robots_txt_url.is_empty());
+    assert_eq!(service.groups_converted(), 2);
+}
+
+#[tokio::test]
+async fn test_unknown_path_is_rejected() {
+    let (mock_server, service) = setup().await;
+    // Rejected before anything is fetched
+    mock_server.reset().await;
+
+    let status = get_robots(
+        &service,
+        &mock_server,
+        Some(&["sitemaps", "groups.rules", "etag"]),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(
+        status.message(),
+        "Invalid read_mask path: groups.rules, etag"
+    );
+}
+
+async fn get_robots_for(
+    service: &RobotsServer<MokaCache<String, CachedRobots>>,
+    mock_server: &MockServer,
+    user_agent: &str,
+) -> GetRobotsResponse {
+    let request = GetRobotsRequest {
+        url: format!("http://{}/", mock_server.address()),
+        user_agent: Some(user_agent.to_string()),
+        ..Default::default()
+    };
+    service
+        .get_robots_txt(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+fn user_agents(response: &GetRobotsResponse) -> Vec<Vec<&str>> {
+    response
+        .groups
+        .iter()
+        .map(|group| group.user_agents.iter().map(String::as_str).collect())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_user_agent_filter_selects_specific_groups() {
+    let (mock_server, service) = setup_with(MULTI_GROUP_ROBOTS_TXT).await;
+
+    let response = get_robots_for(
+        &service,
+        &mock_server,
+        "Mozilla/5.0 (compatible; bingbot/2.0)",
+    )
+    .await;
+    assert_eq!(user_agents(&response), [["bingbot", "Slurp"]]);
+    assert_eq!(response.effective_user_agent_match, "bingbot");
+    assert_eq!(response.http_status_code, 200);
+
+    // Both Googlebot groups are product tokens of Googlebot-News
+    let response = get_robots_for(&service, &mock_server, "Googlebot-News").await;
+    assert_eq!(user_agents(&response), [["Googlebot"], ["googlebot-news"]]);
+    assert_eq!(response.effective_user_agent_match, "Googlebot");
+
+    // The cache still holds every group
+    let full = get_robots(&service, &mock_server, None).await.unwrap();
+    assert_eq!(full.groups.len(), 4);
+    assert!(full.effective_user_agent_match.is_empty());
+}
+
+#[tokio::test]
+async fn test_user_agent_filter_falls_back_to_wildcard() {
+    let (mock_server, service) = setup_with(MULTI_GROUP_ROBOTS_TXT).await;
+
+    let response = get_robots_for(&service, &mock_server, "UnknownBot/1.0").await;
+
+    assert_eq!(user_agents(&response), [["*"]]);
+    assert_eq!(response.groups[0].rules[0].path_pattern, "/private");
+    assert_eq!(response.effective_user_agent_match, "*");
+}
+
+#[tokio::test]
+async fn test_user_agent_filter_without_wildcard_group() {
+    let (mock_server, service) = setup_with("User-agent: Googlebot\nDisallow: /\n").await;
+
+    let response = get_robots_for(&service, &mock_server, "UnknownBot/1.0").await;
+
+    assert!(response.groups.is_empty());
+    assert!(response.effective_user_agent_match.is_empty());
+}
+
+async fn get_robots_known(
+    service: &RobotsServer<MokaCache<String, CachedRobots>>,
+    mock_server: &MockServer,
+    known_sha256: &str,
+) -> GetRobotsResponse {
+    let request = GetRobotsRequest {
+        url: format!("http://{}/", mock_server.address()),
+        known_sha256: known_sha256.to_string(),
+        ..Default::default()
+    };
+    service
+        .get_robots_txt(Request::new(request))
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+#[tokio::test]
+async fn test_known_checksum_is_not_modified() {
+    let (mock_server, service) = setup().await;
+    let full = get_robots(&service, &mock_server, None).await.unwrap();
+    assert!(!full.not_modified);
+    assert_eq!(full.checksum.len(), 64);
+
+    let response = get_robots_known(&service, &mock_server, &full.checksum.to_uppercase()).await;
+
+    assert!(response.not_modified);
+    assert_eq!(response.checksum, full.checksum);
+    assert_eq!(response.fetched_at, full.fetched_at);
+    assert_eq!(response.expires_at, full.expires_at);
+    assert!(response.groups.is_empty());
+    assert!(response.sitemaps.is_empty());
+    assert_eq!(service.groups_converted(), 1);
+}
+
+#[tokio::test]
+async fn test_stale_checksum_returns_full_response() {
+    let (mock_server, service) = setup().await;
+
+    let response = get_robots_known(&service, &mock_server, &"0".repeat(64)).await;
+
+    assert!(!response.not_modified);
+    assert_eq!(response.groups.len(), 2);
+    assert_eq!(response.sitemaps.len(), 1);
+    assert_ne!(response.checksum, "0".repeat(64));
+}
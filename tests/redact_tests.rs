@@ -0,0 +1,161 @@
+#![cfg(feature = "server")]
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use robots_server::cache::{Cache, MokaCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::log_format::json_layer;
+use robots_server::redact::{self, UrlRedaction};
+use robots_server::robots_data::{AccessResult, Group, RobotsData, Rule, RuleType};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{GetRobotsRequest, IsAllowedRequest};
+use tonic::Request;
+use tracing_subscriber::layer::SubscriberExt;
+
+const SECRET: &str = "s3cr3t-t0ken";
+
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Capture {
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+#[test]
+fn test_strip_query() {
+    let policy = UrlRedaction::StripQuery;
+    assert_eq!(
+        policy.apply("https://user:pw@example.com/a/b?token=x&page=2#frag"),
+        "https://example.com/a/b"
+    );
+    assert_eq!(
+        policy.apply("http://example.com/plain"),
+        "http://example.com/plain"
+    );
+}
+
+#[test]
+fn test_hash_query_is_stable_and_hides_values() {
+    let policy = UrlRedaction::HashQuery;
+    let first = policy.apply("https://example.com/a?token=abc");
+    assert_eq!(first, policy.apply("https://example.com/a?token=abc"));
+    assert_ne!(first, policy.apply("https://example.com/a?token=abd"));
+    assert!(first.starts_with("https://example.com/a?sha256:"));
+    assert!(!first.contains("abc"));
+}
+
+#[test]
+fn test_allowlist_keeps_listed_params() {
+    let policy = UrlRedaction::Allowlist {
+        params: vec!["page".to_string()],
+    };
+    assert_eq!(
+        policy.apply("https://example.com/a?token=abc&page=2&email=a%40b.com"),
+        "https://example.com/a?token=REDACTED&page=2&email=REDACTED"
+    );
+}
+
+#[test]
+fn test_keep_and_malformed_urls() {
+    let url = "https://user:pw@example.com/a?token=abc";
+    assert_eq!(UrlRedaction::Keep.apply(url), url);
+    assert_eq!(
+        UrlRedaction::StripQuery.apply("not a url?token=abc"),
+        "not a url"
+    );
+}
+
+#[test]
+fn test_policy_from_config() {
+    let policy: UrlRedaction = toml::from_str("mode = \"allowlist\"\nparams = [\"page\"]").unwrap();
+    assert_eq!(
+        policy,
+        UrlRedaction::Allowlist {
+            params: vec!["page".to_string()]
+        }
+    );
+    assert_eq!(
+        toml::from_str::<UrlRedaction>("mode = \"hash_query\"").unwrap(),
+        UrlRedaction::HashQuery
+    );
+}
+
+#[tokio::test]
+async fn test_service_logs_never_contain_query_secrets() {
+    let cache = MokaCache::new();
+    cache
+        .set(
+            "http://example.com/robots.txt".to_string(),
+            CachedRobots::from(RobotsData {
+                target_url: "http://example.com/".to_string(),
+                robots_txt_url: "http://example.com/robots.txt".to_string(),
+                access_result: AccessResult::Success,
+                http_status_code: 200,
+                groups: vec![Group::new(
+                    vec!["*".to_string()],
+                    vec![Rule::new(RuleType::Disallow, "/*token=")],
+                    None,
+                )],
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+    let service = RobotsServer::new(cache, RobotsFetcher::new());
+    let url = format!("http://example.com/account?token={SECRET}&page=2");
+
+    // The policy is process-wide, so every mode is checked in one test
+    for policy in [
+        UrlRedaction::StripQuery,
+        UrlRedaction::HashQuery,
+        UrlRedaction::Allowlist {
+            params: vec!["page".to_string()],
+        },
+    ] {
+        redact::configure(&policy);
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::registry().with(json_layer(move || writer.clone()));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let response = service
+            .is_allowed(Request::new(IsAllowedRequest {
+                target_url: url.clone(),
+                user_agent: "MyBot".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        service
+            .get_robots_txt(Request::new(GetRobotsRequest {
+                url: url.clone(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+
+        // Matching still sees the query, and the cached entry is found
+        assert!(!response.allowed, "{policy:?}");
+        let logs = capture.text();
+        assert!(logs.contains("http://example.com/account"), "{policy:?}");
+        assert!(!logs.contains(SECRET), "{policy:?} leaked: {logs}");
+    }
+    redact::configure(&UrlRedaction::default());
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "server")]
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{AccessResult, GetRobotsRequest, IsAllowedRequest};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_redirect_loop_cached_as_unavailable() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(302).insert_header("location", "/robots.txt"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let url = format!("http://{}/private", mock_server.address());
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+
+    for _ in 0..2 {
+        let response = service
+            .is_allowed(Request::new(IsAllowedRequest {
+                target_url: url.clone(),
+                user_agent: "TestBot".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.allowed);
+        assert_eq!(response.reason, "robots.txt unavailable: REDIRECT_LOOP");
+    }
+
+    let response = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url,
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.access_result(), AccessResult::TooManyRedirects);
+    assert_eq!(response.failure_reason, "REDIRECT_LOOP");
+}
+
+#[tokio::test]
+async fn test_long_redirect_chain_reason() {
+    let mock_server = MockServer::start().await;
+    for i in 0..10 {
+        Mock::given(method("GET"))
+            .and(path(format!("/hop-{i}")))
+            .respond_with(
+                ResponseTemplate::new(301).insert_header("location", format!("/hop-{}", i + 1)),
+            )
+            .mount(&mock_server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(301).insert_header("location", "/hop-0"))
+        .mount(&mock_server)
+        .await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+
+    let response = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: format!("http://{}/", mock_server.address()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.access_result(), AccessResult::TooManyRedirects);
+    assert_eq!(response.failure_reason, "TOO_MANY_REDIRECTS");
+}
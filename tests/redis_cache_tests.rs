@@ -0,0 +1,118 @@
+#![cfg(all(feature = "redis", feature = "test-support"))]
+
+use std::time::Duration;
+
+use robots_server::cache::{Cache, RedisCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::ConfigHandle;
+use robots_server::robots_data::AccessResult;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::IsAllowedRequest;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::test_support::{FakeFetcher, RobotsDataBuilder};
+use tonic::Request;
+
+/// The Redis server `REDIS_URL` names. Without one, the tests pass without
+/// checking anything.
+async fn redis_cache() -> Option<RedisCache> {
+    let Ok(url) = std::env::var("REDIS_URL") else {
+        eprintln!("REDIS_URL is not set, skipping");
+        return None;
+    };
+    Some(
+        RedisCache::connect(&url, ConfigHandle::default())
+            .await
+            .unwrap(),
+    )
+}
+
+/// A key no earlier run can have left behind.
+fn key(name: &str) -> String {
+    format!("http://{name}-{}.example/robots.txt", std::process::id())
+}
+
+#[tokio::test]
+async fn test_round_trip_keeps_rules() {
+    let Some(cache) = redis_cache().await else {
+        return;
+    };
+    let key = key("round-trip");
+    let data = RobotsDataBuilder::new()
+        .group("MyBot")
+        .disallow("/private*.html$")
+        .allow("/private/ok")
+        .crawl_delay(Duration::from_millis(1500))
+        .sitemap("https://example.com/sitemap.xml")
+        .build();
+    cache
+        .set(key.clone(), CachedRobots::from(data.clone()))
+        .await
+        .unwrap();
+
+    let stored = cache.get(&key).await.unwrap().unwrap().data();
+    assert_eq!(stored.access_result, AccessResult::Success);
+    assert_eq!(stored.http_status_code, 200);
+    assert_eq!(stored.checksum, data.checksum);
+    assert_eq!(stored.sitemaps, data.sitemaps);
+    assert_eq!(
+        stored.groups[0].crawl_delay,
+        Some(Duration::from_millis(1500))
+    );
+    assert!(!stored.is_allowed("MyBot", "/private/page.html"));
+    assert!(stored.is_allowed("MyBot", "/private/ok"));
+    assert!(stored.is_allowed("OtherBot", "/private/page.html"));
+
+    assert!(cache.delete(&key).await.unwrap());
+    assert!(!cache.delete(&key).await.unwrap());
+    assert!(cache.get(&key).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_set_with_ttl_expires() {
+    let Some(cache) = redis_cache().await else {
+        return;
+    };
+    let key = key("ttl");
+    let data = RobotsDataBuilder::new().build();
+    cache
+        .set_with_ttl(key.clone(), data.into(), Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert!(cache.get(&key).await.unwrap().is_some());
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+    assert!(cache.get(&key).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_replicas_share_fetches() {
+    let (Some(first), Some(second)) = (redis_cache().await, redis_cache().await) else {
+        return;
+    };
+    let host = format!("replicas-{}.example", std::process::id());
+    let fetcher = FakeFetcher::new();
+    fetcher.serve(
+        &host,
+        RobotsDataBuilder::new()
+            .group("*")
+            .disallow("/admin")
+            .build(),
+    );
+    let replicas = [
+        RobotsServer::new(first, fetcher.clone()),
+        RobotsServer::new(second, fetcher.clone()),
+    ];
+
+    for replica in &replicas {
+        let response = replica
+            .is_allowed(Request::new(IsAllowedRequest {
+                target_url: format!("http://{host}/admin"),
+                user_agent: "MyBot".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!response.allowed);
+    }
+    assert_eq!(fetcher.fetch_count(&host), 1);
+}
@@ -0,0 +1,176 @@
+#![cfg(feature = "test-support")]
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use robots_server::cached_robots::CachedRobots;
+use robots_server::clock::{Clock, ManualClock};
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    AccessResult, GetRobotsRequest, InvalidateUrlRequest, RefreshRobotsRequest,
+    RefreshRobotsResponse,
+};
+use robots_server::service::{RobotsServer, to_timestamp};
+use robots_server::test_support::{FakeFetcher, InMemoryCache, RobotsDataBuilder};
+use tonic::{Code, Request};
+
+use common::{ADMIN_KEY, as_admin};
+
+type TestServer = RobotsServer<InMemoryCache<String, CachedRobots>, FakeFetcher>;
+
+/// A server whose `admin_api_keys` are [`ADMIN_KEY`].
+fn server(fetcher: FakeFetcher, config: RuntimeConfig, clock: Arc<ManualClock>) -> TestServer {
+    let config = RuntimeConfig {
+        admin_api_keys: vec![ADMIN_KEY.to_string()],
+        ..config
+    };
+    fetcher.serve(
+        "example.com",
+        RobotsDataBuilder::new()
+            .group("*")
+            .disallow("/admin")
+            .build(),
+    );
+    RobotsServer::builder()
+        .cache(InMemoryCache::new())
+        .fetcher(fetcher)
+        .config(ConfigHandle::new(config))
+        .clock(clock)
+        .build()
+        .unwrap()
+}
+
+fn refresh_request(force: bool) -> Request<RefreshRobotsRequest> {
+    Request::new(RefreshRobotsRequest {
+        url: "http://example.com/page".to_string(),
+        force,
+    })
+}
+
+/// Refresh as an admin when `force` is set, as only admins may force one.
+async fn refresh(service: &TestServer, force: bool) -> RefreshRobotsResponse {
+    let request = if force {
+        as_admin(refresh_request(force))
+    } else {
+        refresh_request(force)
+    };
+    service.refresh_robots(request).await.unwrap().into_inner()
+}
+
+#[tokio::test]
+async fn test_back_to_back_refreshes_fetch_once() {
+    let fetcher = FakeFetcher::new();
+    let clock = Arc::new(ManualClock::new());
+    let service = server(fetcher.clone(), RuntimeConfig::default(), clock.clone());
+
+    let first = refresh(&service, false).await;
+    assert!(!first.throttled);
+    assert_eq!(first.robots_txt_url, "http://example.com/robots.txt");
+    assert_eq!(first.http_status_code, 200);
+    assert_eq!(first.next_refresh_at, None);
+
+    clock.advance(Duration::from_secs(10));
+    let second = refresh(&service, false).await;
+    assert!(second.throttled);
+    assert_eq!(second.fetched_at, first.fetched_at);
+    assert_eq!(second.checksum, first.checksum);
+    assert_eq!(
+        second.next_refresh_at,
+        Some(to_timestamp(clock.now() + Duration::from_secs(50)))
+    );
+    assert_eq!(fetcher.fetch_count("example.com"), 1);
+
+    clock.advance(Duration::from_secs(50));
+    let third = refresh(&service, false).await;
+    assert!(!third.throttled);
+    assert_eq!(third.fetched_at, Some(to_timestamp(clock.now())));
+    assert_eq!(fetcher.fetch_count("example.com"), 2);
+}
+
+#[tokio::test]
+async fn test_forced_refresh_ignores_interval() {
+    let fetcher = FakeFetcher::new();
+    let clock = Arc::new(ManualClock::new());
+    let service = server(fetcher.clone(), RuntimeConfig::default(), clock);
+
+    // A cache miss counts as a fetch too
+    service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: "http://example.com/".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    assert!(refresh(&service, false).await.throttled);
+    assert!(!refresh(&service, true).await.throttled);
+    assert_eq!(fetcher.fetch_count("example.com"), 2);
+}
+
+#[tokio::test]
+async fn test_force_needs_an_admin_key() {
+    let fetcher = FakeFetcher::new();
+    let clock = Arc::new(ManualClock::new());
+    let service = server(
+        fetcher.clone(),
+        RuntimeConfig {
+            api_keys: vec!["client-key".to_string()],
+            ..Default::default()
+        },
+        clock,
+    );
+
+    let status = service
+        .refresh_robots(refresh_request(true))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    let mut request = refresh_request(true);
+    request
+        .metadata_mut()
+        .insert("x-api-key", "client-key".parse().unwrap());
+    let status = service.refresh_robots(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    assert_eq!(fetcher.fetch_count("example.com"), 0);
+}
+
+#[tokio::test]
+async fn test_throttled_without_a_last_fetch_to_answer_from() {
+    let fetcher = FakeFetcher::new();
+    let clock = Arc::new(ManualClock::new());
+    let service = server(fetcher.clone(), RuntimeConfig::default(), clock);
+
+    assert!(!refresh(&service, false).await.throttled);
+    service
+        .invalidate_url(Request::new(InvalidateUrlRequest {
+            url: "http://example.com/".to_string(),
+        }))
+        .await
+        .unwrap();
+    let throttled = refresh(&service, false).await;
+    assert!(throttled.throttled);
+    assert_eq!(throttled.access_result(), AccessResult::Unspecified);
+    assert_eq!(throttled.fetched_at, None);
+    assert!(throttled.next_refresh_at.is_some());
+    assert_eq!(fetcher.fetch_count("example.com"), 1);
+}
+
+#[tokio::test]
+async fn test_zero_interval_never_throttles() {
+    let fetcher = FakeFetcher::new();
+    let clock = Arc::new(ManualClock::new());
+    let service = server(
+        fetcher.clone(),
+        RuntimeConfig {
+            min_refresh_interval_secs: 0,
+            ..Default::default()
+        },
+        clock,
+    );
+
+    assert!(!refresh(&service, false).await.throttled);
+    assert!(!refresh(&service, false).await.throttled);
+    assert_eq!(fetcher.fetch_count("example.com"), 2);
+}
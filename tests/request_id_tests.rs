@@ -0,0 +1,114 @@
+#![cfg(feature = "server")]
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::log_format::json_layer;
+use robots_server::request_id::{REQUEST_ID_HEADER, RequestIdLayer};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_client::RobotsServiceClient;
+use robots_server::service::robots::v1::robots_service_server::RobotsServiceServer;
+use robots_server::service::robots::v1::{GetRobotsRequest, IsAllowedRequest};
+use serde_json::Value;
+use tonic::Request;
+use tonic::transport::Server;
+use tracing_subscriber::layer::SubscriberExt;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Capture {
+    fn lines(&self) -> Vec<Value> {
+        String::from_utf8_lossy(&self.0.lock().unwrap())
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn test_request_id_echoed_and_logged() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private\n"),
+        )
+        .mount(&mock_server)
+        .await;
+    let capture = Capture::default();
+    let writer = capture.clone();
+    let subscriber = tracing_subscriber::registry().with(json_layer(move || writer.clone()));
+    // The server runs on this thread, as the test runtime has only one
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    tokio::spawn(
+        Server::builder()
+            .layer(RequestIdLayer)
+            .add_service(RobotsServiceServer::new(service))
+            .serve("[::1]:50085".parse().unwrap()),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut client = RobotsServiceClient::connect("http://[::1]:50085")
+        .await
+        .unwrap();
+
+    let mut request = Request::new(GetRobotsRequest {
+        url: format!("http://{}/", mock_server.address()),
+        ..Default::default()
+    });
+    request
+        .metadata_mut()
+        .insert(REQUEST_ID_HEADER, "crawl-42".parse().unwrap());
+    let response = client.get_robots_txt(request).await.unwrap();
+    assert_eq!(
+        response.metadata().get(REQUEST_ID_HEADER).unwrap(),
+        "crawl-42"
+    );
+
+    // The handler, fetcher and cache spans all carry the ID
+    let lines = capture.lines();
+    for span in ["get_robots_txt", "fetch_text", "get"] {
+        let in_span: Vec<_> = lines.iter().filter(|line| line["span"] == span).collect();
+        assert!(!in_span.is_empty(), "nothing logged in {span}");
+        for line in in_span {
+            assert_eq!(line["request_id"], "crawl-42", "{line}");
+        }
+    }
+
+    // Calls without an ID are given one
+    let response = client
+        .is_allowed(IsAllowedRequest {
+            target_url: format!("http://{}/private", mock_server.address()),
+            user_agent: "TestBot".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let id = response.metadata().get(REQUEST_ID_HEADER).unwrap();
+    let id = id.to_str().unwrap();
+    assert_eq!(id.len(), 36);
+    assert!(
+        capture
+            .lines()
+            .iter()
+            .any(|line| line["span"] == "is_allowed" && line["request_id"] == id)
+    );
+}
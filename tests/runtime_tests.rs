@@ -0,0 +1,58 @@
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use robots_server::config::Config;
+use robots_server::runtime;
+
+#[test]
+fn test_runtime_uses_configured_threads() {
+    let config = Config {
+        worker_threads: Some(3),
+        max_blocking_threads: Some(1),
+        ..Default::default()
+    };
+    let runtime = runtime::build(&config).unwrap();
+    assert_eq!(runtime.metrics().num_workers(), 3);
+
+    // With one blocking thread, blocking tasks run one after another
+    let running = Arc::new(AtomicUsize::new(0));
+    let most = Arc::new(AtomicUsize::new(0));
+    let tasks: Vec<_> = (0..3)
+        .map(|_| {
+            let running = running.clone();
+            let most = most.clone();
+            runtime.spawn_blocking(move || {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(50));
+                running.fetch_sub(1, Ordering::SeqCst);
+            })
+        })
+        .collect();
+    runtime.block_on(async {
+        for task in tasks {
+            task.await.unwrap();
+        }
+    });
+    assert_eq!(most.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_runtime_defaults_to_one_worker_per_cpu() {
+    let runtime = runtime::build(&Config::default()).unwrap();
+    let cpus = thread::available_parallelism().unwrap().get();
+    assert_eq!(runtime.metrics().num_workers(), cpus);
+}
+
+#[test]
+fn test_zero_threads_rejected() {
+    let config = Config {
+        worker_threads: Some(0),
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "server")]
+
+use std::net::TcpListener;
+use std::time::Duration;
+
+use robots_server::cache::{Cache, MokaCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::self_check::{self, CheckResult, SelfCheckReport};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn run(config: RuntimeConfig, probe_url: Option<&str>) -> SelfCheckReport {
+    let cache: MokaCache<String, CachedRobots> = MokaCache::new();
+    let fetcher = RobotsFetcher::with_config(ConfigHandle::new(RuntimeConfig {
+        fetch_timeout_secs: 5.0,
+        ..config.clone()
+    }));
+    self_check::run(&config, &cache, &fetcher, probe_url).await
+}
+
+fn result<'a>(report: &'a SelfCheckReport, name: &str) -> &'a CheckResult {
+    report
+        .results
+        .iter()
+        .find(|result| result.name == name)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_self_check_passes() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nAllow: /"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    let probe_url = format!("http://{}/", mock_server.address());
+
+    let report = run(RuntimeConfig::default(), Some(&probe_url)).await;
+
+    assert!(report.passed(), "{report:?}");
+    assert!(result(&report, "cache").passed);
+    assert_eq!(
+        result(&report, "egress").detail,
+        format!("fetched {probe_url}robots.txt (HTTP 200)")
+    );
+}
+
+#[tokio::test]
+async fn test_self_check_probe_times_out_without_caching() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("User-agent: *\nAllow: /")
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&mock_server)
+        .await;
+    let probe_url = format!("http://{}/", mock_server.address());
+    let config = RuntimeConfig {
+        fetch_timeout_secs: 0.2,
+        ..RuntimeConfig::default()
+    };
+    let cache: MokaCache<String, CachedRobots> = MokaCache::new();
+    let fetcher = RobotsFetcher::with_config(ConfigHandle::new(config.clone()));
+
+    let report = self_check::run(&config, &cache, &fetcher, Some(&probe_url)).await;
+
+    let egress = result(&report, "egress");
+    assert!(!egress.passed, "{egress}");
+    assert!(result(&report, "cache").passed);
+    // Neither the probe entry nor the probed robots.txt is left behind
+    for key in [
+        "self-check:probe".to_string(),
+        format!("{probe_url}robots.txt"),
+    ] {
+        assert!(cache.get(&key).await.unwrap().is_none(), "{key} cached");
+    }
+}
+
+#[tokio::test]
+async fn test_self_check_fails_on_dead_port() {
+    let port = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+
+    let report = run(
+        RuntimeConfig::default(),
+        Some(&format!("http://127.0.0.1:{port}/")),
+    )
+    .await;
+
+    assert!(!report.passed());
+    assert!(result(&report, "cache").passed);
+    let egress = result(&report, "egress");
+    assert!(!egress.passed);
+    assert!(egress.to_string().starts_with("FAIL egress: "));
+}
+
+#[tokio::test]
+async fn test_self_check_respects_host_blocklist() {
+    let config = RuntimeConfig {
+        host_blocklist: vec!["127.0.0.1".to_string()],
+        ..Default::default()
+    };
+
+    let report = run(config, Some("http://127.0.0.1:1/")).await;
+
+    assert_eq!(
+        result(&report, "egress"),
+        &CheckResult {
+            name: "egress",
+            passed: false,
+            detail: "host 127.0.0.1 is not permitted".to_string(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_self_check_without_probe_checks_cache() {
+    let report = run(RuntimeConfig::default(), None).await;
+
+    assert!(report.passed());
+    assert_eq!(report.results.len(), 1);
+}
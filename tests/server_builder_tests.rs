@@ -0,0 +1,92 @@
+#![cfg(feature = "test-support")]
+
+use std::time::Duration;
+
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::server_builder::BuildError;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{IsAllowedRequest, NextAllowedFetchRequest};
+use robots_server::test_support::{FakeFetcher, InMemoryCache, RobotsDataBuilder};
+use tonic::{Code, Request};
+
+type TestServer = RobotsServer<InMemoryCache<String, CachedRobots>, FakeFetcher>;
+
+#[tokio::test]
+async fn test_builder_applies_settings() {
+    let fetcher = FakeFetcher::new();
+    fetcher.serve("example.com", RobotsDataBuilder::new().group("*").build());
+    let service = RobotsServer::builder()
+        .cache(InMemoryCache::new())
+        .fetcher(fetcher)
+        .config(ConfigHandle::new(RuntimeConfig {
+            host_blocklist: vec!["blocked.example".to_string()],
+            ..Default::default()
+        }))
+        .default_crawl_delay(Duration::from_secs(5))
+        .build()
+        .unwrap();
+
+    let response = service
+        .next_allowed_fetch(Request::new(NextAllowedFetchRequest {
+            host_url: "http://example.com/".to_string(),
+            user_agent: "MyBot".to_string(),
+        }))
+        .await
+        .unwrap();
+    assert_eq!(response.get_ref().crawl_delay_ms, 5000);
+    assert!(!response.get_ref().crawl_delay_from_robots);
+
+    let status = service
+        .is_allowed(Request::new(IsAllowedRequest {
+            target_url: "http://blocked.example/".to_string(),
+            user_agent: "MyBot".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+}
+
+#[test]
+fn test_builder_requires_parts() {
+    let error = TestServer::builder()
+        .cache(InMemoryCache::new())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(error, BuildError::MissingFetcher));
+
+    let error = TestServer::builder()
+        .fetcher(FakeFetcher::new())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(error, BuildError::MissingCache));
+}
+
+#[test]
+fn test_builder_validates_settings() {
+    let builder = || {
+        TestServer::builder()
+            .cache(InMemoryCache::new())
+            .fetcher(FakeFetcher::new())
+    };
+
+    let error = builder().decision_capacity(0).build().err().unwrap();
+    assert_eq!(error.to_string(), "Decision capacity must be positive");
+
+    let error = builder()
+        .config(ConfigHandle::new(RuntimeConfig {
+            fetch_timeout_secs: 0.0,
+            ..Default::default()
+        }))
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "Invalid config: fetch_timeout_secs must be positive"
+    );
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "server")]
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::{MAX_ROBOTS_TXT_SIZE, RobotsFetcher};
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{GetServerInfoRequest, IsAllowedRequest};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_server_info_reports_build_and_counters() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /admin"))
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    // Readiness probes are not robots.txt lookups
+    assert!(service.cache_reachable().await);
+    let before = service
+        .get_server_info(Request::new(GetServerInfoRequest {}))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(before.version, env!("CARGO_PKG_VERSION"));
+    assert!(!before.git_hash.is_empty());
+    assert!(before.features.contains(&"server".to_string()));
+    for (feature, enabled) in [
+        ("tls", cfg!(feature = "tls")),
+        ("http3", cfg!(feature = "http3")),
+        ("test-support", cfg!(feature = "test-support")),
+    ] {
+        assert_eq!(before.features.contains(&feature.to_string()), enabled);
+    }
+    let limits = before.limits.unwrap();
+    assert_eq!(limits.max_robots_txt_bytes, MAX_ROBOTS_TXT_SIZE as u64);
+    assert_eq!(limits.cache_ttl_seconds, 24 * 60 * 60);
+    let cache = before.cache.unwrap();
+    assert_eq!((cache.hits, cache.misses), (0, 0));
+    assert_eq!(before.fetches_total, 0);
+
+    let url = format!("http://{}/page", mock_server.address());
+    for _ in 0..3 {
+        service
+            .is_allowed(Request::new(IsAllowedRequest {
+                target_url: url.clone(),
+                user_agent: "MyBot".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+    }
+
+    let after = service
+        .get_server_info(Request::new(GetServerInfoRequest {}))
+        .await
+        .unwrap()
+        .into_inner();
+    let cache = after.cache.unwrap();
+    assert_eq!(cache.misses, 1);
+    assert_eq!(cache.hits, 2);
+    assert!((cache.hit_rate - 2.0 / 3.0).abs() < 1e-9);
+    assert!(cache.entries.is_some());
+    assert_eq!(after.fetches_total, 1);
+    assert_eq!(after.in_flight_fetches, 0);
+}
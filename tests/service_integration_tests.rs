@@ -1,8 +1,14 @@
+#![cfg(feature = "server")]
+
+use std::time::{Duration, SystemTime};
+
 use robots_server::cache::MokaCache;
 use robots_server::fetcher::RobotsFetcher;
-use robots_server::service::robots::robots_service_server::RobotsService;
-use robots_server::service::robots::{AccessResult, IsAllowedRequest};
-use robots_server::service::{RobotsServer, robots::GetRobotsRequest};
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    AccessResult, AddressFamily, DataSource, IsAllowedRequest,
+};
+use robots_server::service::{RobotsServer, robots::v1::GetRobotsRequest, to_duration};
 use tonic::Request;
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -26,13 +32,21 @@ async fn test_service_cache_miss_then_hit() {
 
     let url = format!("http://{}/", mock_server.address());
 
-    let request = Request::new(GetRobotsRequest { url: url.clone() });
+    let request = Request::new(GetRobotsRequest {
+        url: url.clone(),
+        ..Default::default()
+    });
     let response = service.get_robots_txt(request).await.unwrap();
     assert_eq!(response.get_ref().http_status_code, 200);
+    assert_eq!(response.get_ref().data_source(), DataSource::FreshFetch);
 
-    let request = Request::new(GetRobotsRequest { url: url.clone() });
+    let request = Request::new(GetRobotsRequest {
+        url: url.clone(),
+        ..Default::default()
+    });
     let response = service.get_robots_txt(request).await.unwrap();
     assert_eq!(response.get_ref().http_status_code, 200);
+    assert_eq!(response.get_ref().data_source(), DataSource::Cache);
 }
 #[tokio::test]
 async fn test_service_404_is_cached() {
@@ -51,19 +65,27 @@ async fn test_service_404_is_cached() {
 
     let url = format!("http://{}/", mock_server.address());
 
-    let request = Request::new(GetRobotsRequest { url: url.clone() });
+    let request = Request::new(GetRobotsRequest {
+        url: url.clone(),
+        ..Default::default()
+    });
     let response = service.get_robots_txt(request).await.unwrap();
     assert_eq!(
         response.get_ref().access_result,
         AccessResult::Unavailable as i32
     );
+    assert_eq!(response.get_ref().data_source(), DataSource::FreshFetch);
 
-    let request = Request::new(GetRobotsRequest { url: url.clone() });
+    let request = Request::new(GetRobotsRequest {
+        url: url.clone(),
+        ..Default::default()
+    });
     let response = service.get_robots_txt(request).await.unwrap();
     assert_eq!(
         response.get_ref().access_result,
         AccessResult::Unavailable as i32
     );
+    assert_eq!(response.get_ref().data_source(), DataSource::Cache);
 }
 #[tokio::test]
 async fn test_service_500_is_cached() {
@@ -82,19 +104,27 @@ async fn test_service_500_is_cached() {
 
     let url = format!("http://{}/", mock_server.address());
 
-    let request = Request::new(GetRobotsRequest { url: url.clone() });
+    let request = Request::new(GetRobotsRequest {
+        url: url.clone(),
+        ..Default::default()
+    });
     let response = service.get_robots_txt(request).await.unwrap();
     assert_eq!(
         response.get_ref().access_result,
         AccessResult::Unreachable as i32
     );
+    assert_eq!(response.get_ref().data_source(), DataSource::FreshFetch);
 
-    let request = Request::new(GetRobotsRequest { url: url.clone() });
+    let request = Request::new(GetRobotsRequest {
+        url: url.clone(),
+        ..Default::default()
+    });
     let response = service.get_robots_txt(request).await.unwrap();
     assert_eq!(
         response.get_ref().access_result,
         AccessResult::Unreachable as i32
     );
+    assert_eq!(response.get_ref().data_source(), DataSource::Cache);
 }
 #[tokio::test]
 async fn test_service_invalid_url() {
@@ -104,6 +134,7 @@ async fn test_service_invalid_url() {
 
     let request = Request::new(GetRobotsRequest {
         url: "not-a-valid-url".to_string(),
+        ..Default::default()
     });
 
     let result = service.get_robots_txt(request).await;
@@ -135,13 +166,64 @@ async fn test_service_different_urls_different_cache() {
     let url1 = format!("http://{}/", mock_server_1.address());
     let url2 = format!("http://{}/", mock_server_2.address());
 
-    let request = Request::new(GetRobotsRequest { url: url1 });
+    let request = Request::new(GetRobotsRequest {
+        url: url1,
+        ..Default::default()
+    });
     service.get_robots_txt(request).await.unwrap();
 
-    let request = Request::new(GetRobotsRequest { url: url2 });
+    let request = Request::new(GetRobotsRequest {
+        url: url2,
+        ..Default::default()
+    });
     service.get_robots_txt(request).await.unwrap();
 }
 
+#[tokio::test]
+async fn test_service_reports_fetch_times_and_crawl_delay() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(include_str!("fixtures/robots.txt")),
+        )
+        .mount(&mock_server)
+        .await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let before = SystemTime::now();
+
+    let response = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: format!("http://{}/", mock_server.address()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let fetched_at = SystemTime::try_from(response.fetched_at.unwrap()).unwrap();
+    let expires_at = SystemTime::try_from(response.expires_at.unwrap()).unwrap();
+    assert!(before <= fetched_at && fetched_at <= SystemTime::now());
+    // The default cache TTL is 24 hours
+    assert_eq!(
+        expires_at.duration_since(fetched_at).unwrap(),
+        Duration::from_secs(24 * 60 * 60)
+    );
+    let crawl_delay = response.groups[0].crawl_delay.unwrap();
+    assert_eq!(
+        Duration::try_from(crawl_delay).unwrap(),
+        Duration::from_secs(2)
+    );
+    assert_eq!(response.groups[0].crawl_delay_seconds, Some(2.0));
+    assert_eq!(response.groups[1].crawl_delay, None);
+}
+
+#[test]
+fn test_to_duration_saturates() {
+    assert_eq!(to_duration(Duration::from_millis(1500)).nanos, 500_000_000);
+    assert_eq!(to_duration(Duration::MAX).seconds, i64::MAX);
+}
+
 #[tokio::test]
 async fn test_service_timeout_is_cached() {
     let mock_server = MockServer::start().await;
@@ -159,11 +241,19 @@ async fn test_service_timeout_is_cached() {
     let fetcher = RobotsFetcher::new();
     let service = RobotsServer::new(cache, fetcher);
     let url = format!("http://{}/", mock_server.address());
-    let request = Request::new(GetRobotsRequest { url: url.clone() });
-    let _ = service.get_robots_txt(request).await;
+    let request = Request::new(GetRobotsRequest {
+        url: url.clone(),
+        ..Default::default()
+    });
+    let response = service.get_robots_txt(request).await.unwrap();
+    assert_eq!(response.get_ref().data_source(), DataSource::FreshFetch);
 
-    let request = Request::new(GetRobotsRequest { url: url.clone() });
-    let _ = service.get_robots_txt(request).await;
+    let request = Request::new(GetRobotsRequest {
+        url: url.clone(),
+        ..Default::default()
+    });
+    let response = service.get_robots_txt(request).await.unwrap();
+    assert_eq!(response.get_ref().data_source(), DataSource::Cache);
 }
 
 #[tokio::test]
@@ -183,6 +273,7 @@ async fn test_is_allowed_simple_allow() {
     let request = Request::new(IsAllowedRequest {
         target_url: url,
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
 
     let response = service.is_allowed(request).await.unwrap();
@@ -207,6 +298,7 @@ async fn test_is_allowed_simple_disallow() {
     let request = Request::new(IsAllowedRequest {
         target_url: url,
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
 
     let response = service.is_allowed(request).await.unwrap();
@@ -233,6 +325,7 @@ async fn test_is_allowed_specific_user_agent() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/page.html", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -240,6 +333,7 @@ async fn test_is_allowed_specific_user_agent() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/page.html", base_url),
         user_agent: "OtherBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(response.get_ref().allowed);
@@ -256,6 +350,7 @@ async fn test_is_allowed_unavailable_robots_txt() {
     let request = Request::new(IsAllowedRequest {
         target_url: url,
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
 
     let response = service.is_allowed(request).await.unwrap();
@@ -280,6 +375,7 @@ async fn test_is_allowed_with_query_string() {
     let request = Request::new(IsAllowedRequest {
         target_url: url,
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
 
     let response = service.is_allowed(request).await.unwrap();
@@ -302,6 +398,7 @@ async fn test_is_allowed_empty_path() {
     let request = Request::new(IsAllowedRequest {
         target_url: url,
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
 
     let response = service.is_allowed(request).await.unwrap();
@@ -328,6 +425,7 @@ async fn test_is_allowed_wildcard_matching() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/file.pdf", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -335,6 +433,7 @@ async fn test_is_allowed_wildcard_matching() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/page.html", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(response.get_ref().allowed);
@@ -360,6 +459,7 @@ async fn test_is_allowed_case_insensitive_user_agent() {
     let request = Request::new(IsAllowedRequest {
         target_url: url,
         user_agent: "googlebot/1.0".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -380,6 +480,7 @@ async fn test_is_allowed_empty_pattern() {
     let request = Request::new(IsAllowedRequest {
         target_url: url,
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     // Empty disallow means nothing is disallowed
@@ -404,6 +505,7 @@ async fn test_is_allowed_multiple_wildcards() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/axbyczd/page.html", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -411,6 +513,7 @@ async fn test_is_allowed_multiple_wildcards() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/other/page.html", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(response.get_ref().allowed);
@@ -434,6 +537,7 @@ async fn test_is_allowed_end_anchor_only() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/secret", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -441,6 +545,7 @@ async fn test_is_allowed_end_anchor_only() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/secret/", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(response.get_ref().allowed);
@@ -448,6 +553,7 @@ async fn test_is_allowed_end_anchor_only() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/secret/more", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(response.get_ref().allowed);
@@ -470,6 +576,7 @@ async fn test_is_allowed_equivalent_length_tie() {
     let request = Request::new(IsAllowedRequest {
         target_url: url,
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     // RFC 9309: allow wins on tie with equivalent length
@@ -493,6 +600,7 @@ async fn test_is_allowed_query_string_encoding() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/search?q=test", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -500,6 +608,7 @@ async fn test_is_allowed_query_string_encoding() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/search", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(response.get_ref().allowed);
@@ -520,6 +629,7 @@ async fn test_is_allowed_consecutive_wildcards() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/axxxb/page.html", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -540,6 +650,7 @@ async fn test_is_allowed_wildcard_at_start() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/documents/file.pdf", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -547,6 +658,7 @@ async fn test_is_allowed_wildcard_at_start() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/file.pdf", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -554,6 +666,7 @@ async fn test_is_allowed_wildcard_at_start() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/file.html", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(response.get_ref().allowed);
@@ -577,6 +690,7 @@ async fn test_is_allowed_no_user_agent_match() {
     let request = Request::new(IsAllowedRequest {
         target_url: url,
         user_agent: "OtherBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(response.get_ref().allowed);
@@ -600,6 +714,7 @@ async fn test_is_allowed_multiple_specific_user_agents() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/private/page.html", base_url),
         user_agent: "BotOne".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -607,6 +722,7 @@ async fn test_is_allowed_multiple_specific_user_agents() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/private/page.html", base_url),
         user_agent: "BotTwo".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -614,6 +730,7 @@ async fn test_is_allowed_multiple_specific_user_agents() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/private/page.html", base_url),
         user_agent: "OtherBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(response.get_ref().allowed);
@@ -634,6 +751,7 @@ async fn test_is_allowed_root_path_only() {
     let request = Request::new(IsAllowedRequest {
         target_url: base_url.clone(),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -641,6 +759,7 @@ async fn test_is_allowed_root_path_only() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(!response.get_ref().allowed);
@@ -648,7 +767,69 @@ async fn test_is_allowed_root_path_only() {
     let request = Request::new(IsAllowedRequest {
         target_url: format!("{}/page.html", base_url),
         user_agent: "MyBot".to_string(),
+        ..Default::default()
     });
     let response = service.is_allowed(request).await.unwrap();
     assert!(response.get_ref().allowed);
 }
+
+#[tokio::test]
+async fn test_get_robots_reports_upstream_headers() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("User-agent: *\nDisallow: /private")
+                .insert_header("ETag", "\"v1\"")
+                .insert_header("Age", "42")
+                .insert_header("X-Powered-By", "PHP/8.3"),
+        )
+        .mount(&mock_server)
+        .await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+
+    let response = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: format!("http://{}/", mock_server.address()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(response.upstream_headers.len(), 2);
+    assert_eq!(response.upstream_headers["etag"], "\"v1\"");
+    assert_eq!(response.upstream_headers["age"], "42");
+}
+
+#[tokio::test]
+async fn test_get_robots_reports_resolved_address() {
+    let origin = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private"),
+        )
+        .mount(&origin)
+        .await;
+    let failing = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&failing)
+        .await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+
+    for mock_server in [&origin, &failing] {
+        let response = service
+            .get_robots_txt(Request::new(GetRobotsRequest {
+                url: format!("http://{}/", mock_server.address()),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.resolved_address, mock_server.address().to_string());
+        assert_eq!(response.address_family(), AddressFamily::Ipv4);
+    }
+}
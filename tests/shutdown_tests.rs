@@ -0,0 +1,197 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use robots_server::cache::{Cache, CacheResult, MokaCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_client::RobotsServiceClient;
+use robots_server::service::robots::v1::robots_service_server::RobotsServiceServer;
+use robots_server::shutdown::{serve_all_with_grace, serve_with_grace};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tonic::transport::{Channel, Server};
+
+use common::{ROBOTS_TXT, get_robots, origin};
+
+/// Cache that counts writes and records how many had happened when it was
+/// closed, and how often it was.
+#[derive(Clone)]
+struct ClosingCache {
+    inner: Arc<MokaCache<String, CachedRobots>>,
+    writes: Arc<AtomicUsize>,
+    writes_when_closed: Arc<AtomicUsize>,
+    closes: Arc<AtomicUsize>,
+}
+
+impl ClosingCache {
+    fn new() -> Self {
+        Self {
+            inner: Arc::new(MokaCache::new()),
+            writes: Arc::default(),
+            writes_when_closed: Arc::default(),
+            closes: Arc::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl Cache<String, CachedRobots> for ClosingCache {
+    async fn get(&self, key: &String) -> CacheResult<Option<CachedRobots>> {
+        self.inner.get(key).await
+    }
+
+    async fn set(&self, key: String, value: CachedRobots) -> CacheResult<()> {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.inner.set(key, value).await
+    }
+
+    async fn delete(&self, key: &String) -> CacheResult<bool> {
+        self.inner.delete(key).await
+    }
+
+    async fn close(&self) -> CacheResult<()> {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let writes = self.writes.load(Ordering::SeqCst);
+        self.writes_when_closed.store(writes, Ordering::SeqCst);
+        self.closes.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// Serve on `addr` until the returned sender fires, then drain for `grace`.
+async fn start(
+    addr: SocketAddr,
+    grace: Duration,
+) -> (
+    oneshot::Sender<()>,
+    JoinHandle<Result<(), tonic::transport::Error>>,
+) {
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let router = Server::builder().add_service(RobotsServiceServer::new(service));
+    let (tx, rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_with_grace(
+        move |signal| router.serve_with_shutdown(addr, signal),
+        async {
+            rx.await.ok();
+        },
+        grace,
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    (tx, server)
+}
+
+async fn connect(addr: SocketAddr) -> Result<Channel, tonic::transport::Error> {
+    Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+}
+
+#[tokio::test]
+async fn test_in_flight_request_finishes_after_shutdown() {
+    let mock_server = origin(200, ROBOTS_TXT, Duration::from_millis(500), &[]).await;
+    let addr: SocketAddr = "[::1]:50073".parse().unwrap();
+    let (stop, server) = start(addr, Duration::from_secs(10)).await;
+
+    let mut client = RobotsServiceClient::new(connect(addr).await.unwrap());
+    let request = get_robots(&mock_server);
+    let in_flight = tokio::spawn(async move { client.get_robots_txt(request).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    stop.send(()).unwrap();
+
+    let response = in_flight.await.unwrap().unwrap().into_inner();
+    assert_eq!(response.http_status_code, 200);
+    server.await.unwrap().unwrap();
+    assert!(connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn test_requests_cancelled_after_grace_period() {
+    let mock_server = origin(200, ROBOTS_TXT, Duration::from_secs(30), &[]).await;
+    let addr: SocketAddr = "[::1]:50074".parse().unwrap();
+    let (stop, server) = start(addr, Duration::from_millis(200)).await;
+
+    let mut client = RobotsServiceClient::new(connect(addr).await.unwrap());
+    let request = get_robots(&mock_server);
+    let in_flight = tokio::spawn(async move { client.get_robots_txt(request).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let stopped_at = Instant::now();
+    stop.send(()).unwrap();
+
+    server.await.unwrap().unwrap();
+    assert!(stopped_at.elapsed() < Duration::from_secs(5));
+    assert!(in_flight.await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn test_failed_listener_stops_the_others() {
+    let (stopped, on_stopped) = oneshot::channel::<()>();
+    let result = tokio::time::timeout(
+        Duration::from_secs(5),
+        serve_all_with_grace(
+            |signal| {
+                let healthy: BoxFuture<'static, Result<(), String>> = Box::pin(async move {
+                    signal.await;
+                    stopped.send(()).unwrap();
+                    Ok(())
+                });
+                let failing: BoxFuture<'static, Result<(), String>> = Box::pin(async {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Err("address in use".to_string())
+                });
+                vec![healthy, failing]
+            },
+            std::future::pending(),
+            Duration::from_secs(10),
+        ),
+    )
+    .await
+    .expect("other listener did not stop");
+    assert_eq!(result, Err("address in use".to_string()));
+    assert!(on_stopped.await.is_ok());
+}
+
+#[tokio::test]
+async fn test_cache_closed_once_after_draining() {
+    let mock_server = origin(200, ROBOTS_TXT, Duration::from_millis(300), &[]).await;
+    let addr: SocketAddr = "[::1]:50082".parse().unwrap();
+    let cache = ClosingCache::new();
+    let service = Arc::new(RobotsServer::new(cache.clone(), RobotsFetcher::new()));
+    let router = Server::builder().add_service(RobotsServiceServer::from_arc(service.clone()));
+    let (stop, stopping) = oneshot::channel::<()>();
+    // Serves and then closes, as the server binary does
+    let process = tokio::spawn(async move {
+        let served = serve_with_grace(
+            move |signal| router.serve_with_shutdown(addr, signal),
+            async {
+                stopping.await.ok();
+            },
+            Duration::from_secs(10),
+        )
+        .await;
+        service.close().await.unwrap();
+        served
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = RobotsServiceClient::new(connect(addr).await.unwrap());
+    let request = get_robots(&mock_server);
+    let in_flight = tokio::spawn(async move { client.get_robots_txt(request).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    stop.send(()).unwrap();
+
+    process.await.unwrap().unwrap();
+    assert_eq!(cache.closes.load(Ordering::SeqCst), 1);
+    // The drained request had cached its robots.txt before the close
+    assert_eq!(cache.writes_when_closed.load(Ordering::SeqCst), 1);
+    assert!(in_flight.await.unwrap().is_ok());
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "server")]
+
+use robots_server::cache::MokaCache;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{GetRobotsRequest, GetRobotsResponse};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn get_robots(config: RuntimeConfig, body: String) -> GetRobotsResponse {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(&mock_server)
+        .await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new())
+        .with_config(ConfigHandle::new(config));
+    service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: format!("http://{}/", mock_server.address()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+/// A robots.txt listing sitemaps `0..count`, each twice, in reverse order.
+fn sitemaps(count: usize) -> String {
+    let mut body = "User-agent: *\nDisallow: /private\n".to_string();
+    for _ in 0..2 {
+        for i in (0..count).rev() {
+            body.push_str(&format!("Sitemap: https://example.com/sitemap-{i}.xml\n"));
+        }
+    }
+    body
+}
+
+#[tokio::test]
+async fn test_sitemaps_capped_at_max_sitemaps() {
+    let response = get_robots(RuntimeConfig::default(), sitemaps(1500)).await;
+    assert_eq!(response.sitemaps.len(), 1000);
+    assert!(response.sitemaps_truncated);
+    assert_eq!(response.sitemaps[0], "https://example.com/sitemap-1499.xml");
+    assert_eq!(
+        response.sitemaps[999],
+        "https://example.com/sitemap-500.xml"
+    );
+
+    let config = RuntimeConfig {
+        max_sitemaps: 3,
+        ..Default::default()
+    };
+    let response = get_robots(config, sitemaps(5)).await;
+    assert_eq!(
+        response.sitemaps,
+        [
+            "https://example.com/sitemap-4.xml",
+            "https://example.com/sitemap-3.xml",
+            "https://example.com/sitemap-2.xml",
+        ]
+    );
+    assert!(response.sitemaps_truncated);
+}
+
+#[tokio::test]
+async fn test_repeated_sitemaps_not_truncated() {
+    let config = RuntimeConfig {
+        max_sitemaps: 3,
+        ..Default::default()
+    };
+    let response = get_robots(config, sitemaps(3)).await;
+    assert_eq!(response.sitemaps.len(), 3);
+    assert!(!response.sitemaps_truncated);
+}
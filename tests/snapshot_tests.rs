@@ -0,0 +1,136 @@
+#![cfg(feature = "test-support")]
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use robots_server::cache::{Cache, MokaCache};
+use robots_server::cached_robots::CachedRobots;
+use robots_server::fetcher::{RobotsFetcher, RobotsText};
+use robots_server::robots_data::RobotsData;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{DataSource, IsAllowedRequest};
+use robots_server::snapshot;
+use robots_server::test_support::InMemoryCache;
+use tonic::Request;
+use wiremock::MockServer;
+
+use common::{ROBOTS_TXT, fetches, origin_ok};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("robots-server-{}-{name}", std::process::id()))
+}
+
+fn is_allowed(origin: &MockServer) -> Request<IsAllowedRequest> {
+    Request::new(IsAllowedRequest {
+        target_url: format!("http://{}/private/page", origin.address()),
+        user_agent: "MyBot".to_string(),
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn test_restored_snapshot_answers_without_fetching() {
+    let origins = [origin_ok(ROBOTS_TXT).await, origin_ok(ROBOTS_TXT).await];
+    let path = temp_path("snapshot.json");
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    for origin in &origins {
+        service.is_allowed(is_allowed(origin)).await.unwrap();
+    }
+    assert_eq!(service.save_snapshot(&path).await.unwrap(), 2);
+
+    let cache = MokaCache::new();
+    assert_eq!(snapshot::restore(&cache, &path).await.unwrap(), 2);
+    std::fs::remove_file(&path).unwrap();
+    let service = RobotsServer::new(cache, RobotsFetcher::new());
+    for origin in &origins {
+        let response = service.is_allowed(is_allowed(origin)).await.unwrap();
+        let response = response.into_inner();
+        assert!(!response.allowed);
+        assert_eq!(response.reason, "Disallow: /private");
+        assert_eq!(response.data_source(), DataSource::Cache);
+        assert_eq!(fetches(origin).await, 1);
+    }
+}
+
+#[tokio::test]
+async fn test_restore_keeps_remaining_ttl_and_skips_expired() {
+    let path = temp_path("expiring-snapshot.json");
+    let cache = MokaCache::<String, CachedRobots>::new();
+    let entry = |url: &str| {
+        CachedRobots::from(RobotsData {
+            robots_txt_url: url.to_string(),
+            ..Default::default()
+        })
+    };
+    cache
+        .set_with_ttl("short".to_string(), entry("short"), Duration::from_secs(1))
+        .await
+        .unwrap();
+    cache
+        .set_with_ttl("long".to_string(), entry("long"), Duration::from_secs(60))
+        .await
+        .unwrap();
+    snapshot::save(&cache, &path).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    let restored = InMemoryCache::new();
+    assert_eq!(snapshot::restore(&restored, &path).await.unwrap(), 1);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(restored.keys(), ["long"]);
+    let ttl = restored.ttl(&"long".to_string()).unwrap();
+    assert!(ttl < Duration::from_secs(59) && ttl > Duration::from_secs(50));
+}
+
+#[tokio::test]
+async fn test_snapshot_of_another_version_is_discarded() {
+    let path = temp_path("old-snapshot.json");
+    std::fs::write(&path, r#"{"version": 0, "robots": []}"#).unwrap();
+    let cache = InMemoryCache::new();
+    assert_eq!(snapshot::restore(&cache, &path).await.unwrap(), 0);
+
+    std::fs::write(&path, "not json").unwrap();
+    assert_eq!(snapshot::restore(&cache, &path).await.unwrap(), 0);
+    std::fs::remove_file(&path).unwrap();
+    assert!(cache.is_empty());
+
+    // Nothing saved yet
+    assert_eq!(snapshot::restore(&cache, &path).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_snapshot_keeps_raw_entries_unparsed() {
+    let path = temp_path("raw-snapshot.json");
+    let cache = MokaCache::<String, CachedRobots>::new();
+    let entry = CachedRobots::raw(
+        RobotsText {
+            robots_txt_url: "http://example.com/robots.txt".to_string(),
+            http_status_code: 200,
+            content_length_bytes: 33,
+            body: "User-agent: *\nDisallow: /private\n".to_string(),
+            truncated: false,
+            fetched_at: SystemTime::now(),
+            http_version: "HTTP/1.1".to_string(),
+            upstream_headers: BTreeMap::new(),
+            max_age: None,
+            resolved_address: None,
+        },
+        "http://example.com/",
+    );
+    let key = "http://example.com/robots.txt".to_string();
+    cache.set(key.clone(), entry.clone()).await.unwrap();
+    snapshot::save(&cache, &path).await.unwrap();
+    assert!(!entry.is_parsed());
+
+    let restored = InMemoryCache::new();
+    assert_eq!(snapshot::restore(&restored, &path).await.unwrap(), 1);
+    std::fs::remove_file(&path).unwrap();
+    let restored = restored.peek(&key).unwrap();
+    assert!(!restored.is_parsed());
+    let data = restored.data();
+    assert_eq!(data.target_url, "http://example.com/");
+    assert_eq!(data.groups.len(), 1);
+}
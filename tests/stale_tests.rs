@@ -0,0 +1,254 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::clock::ManualClock;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    DataSource, InvalidateUrlRequest, IsAllowedRequest, RefreshRobotsRequest,
+};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use common::{ADMIN_KEY, as_admin};
+
+const ORIGIN_DELAY: Duration = Duration::from_secs(2);
+const DEADLINE: Duration = Duration::from_millis(500);
+
+/// An origin that answers the first fetch at once and the next
+/// `slow_fetches` slowly, with a changed robots.txt.
+async fn slowing_origin(slow_fetches: u64) -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private"),
+        )
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("User-agent: *\nAllow: /")
+                .set_delay(ORIGIN_DELAY),
+        )
+        .expect(slow_fetches)
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+fn is_allowed_request(mock_server: &MockServer, allow_stale: bool) -> Request<IsAllowedRequest> {
+    let mut request = Request::new(IsAllowedRequest {
+        target_url: format!("http://{}/private/page", mock_server.address()),
+        user_agent: "MyBot".to_string(),
+        allow_stale,
+    });
+    request.set_timeout(DEADLINE);
+    request
+}
+
+/// A server caching robots.txt for a second of `clock`'s time, and
+/// answering from it for `window_secs` after that while it is refetched.
+fn revalidating_server(
+    window_secs: u64,
+    clock: &Arc<ManualClock>,
+) -> RobotsServer<MokaCache<String, CachedRobots>> {
+    let config = ConfigHandle::new(RuntimeConfig {
+        cache_ttl_secs: 1,
+        stale_while_revalidate_secs: window_secs,
+        admin_api_keys: vec![ADMIN_KEY.to_string()],
+        ..Default::default()
+    });
+    RobotsServer::builder()
+        .cache(
+            MokaCache::builder()
+                .config(config.clone())
+                .clock(clock.clone())
+                .build(),
+        )
+        .fetcher(RobotsFetcher::with_config(config.clone()))
+        .config(config)
+        .clock(clock.clone())
+        .build()
+        .unwrap()
+}
+
+/// Fetch robots.txt once, then drop it from the cache so the next decision
+/// has to refetch it from the now slow origin.
+async fn expired(
+    service: &RobotsServer<MokaCache<String, CachedRobots>>,
+    mock_server: &MockServer,
+) {
+    let first = service
+        .is_allowed(is_allowed_request(mock_server, false))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!first.allowed);
+    assert!(!first.stale);
+    assert_eq!(first.data_source(), DataSource::FreshFetch);
+    service
+        .invalidate_url(Request::new(InvalidateUrlRequest {
+            url: format!("http://{}/", mock_server.address()),
+        }))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_allow_stale_answers_within_deadline() {
+    let mock_server = slowing_origin(1).await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    expired(&service, &mock_server).await;
+
+    let started = Instant::now();
+    let response = service
+        .is_allowed(is_allowed_request(&mock_server, true))
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert!(started.elapsed() < DEADLINE);
+    assert!(response.stale);
+    assert_eq!(response.data_source(), DataSource::StaleCache);
+    assert!(!response.allowed);
+    assert_eq!(response.reason, "STALE: Disallow: /private");
+
+    // The refetch carries on and its result is cached
+    tokio::time::sleep(ORIGIN_DELAY).await;
+    let response = service
+        .is_allowed(is_allowed_request(&mock_server, true))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!response.stale);
+    assert_eq!(response.data_source(), DataSource::Cache);
+    assert!(response.allowed);
+}
+
+#[tokio::test]
+async fn test_without_allow_stale_waits_for_refetch() {
+    // The abandoned call's fetch carries on, and the next call joins it
+    let mock_server = slowing_origin(1).await;
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    expired(&service, &mock_server).await;
+
+    // The server's timeout layer would fail the call at the deadline
+    let call = service.is_allowed(is_allowed_request(&mock_server, false));
+    assert!(
+        tokio::time::timeout(DEADLINE, call).await.is_err(),
+        "answered before the refetch finished"
+    );
+
+    let response = service
+        .is_allowed(is_allowed_request(&mock_server, false))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!response.stale);
+    assert_eq!(response.data_source(), DataSource::FreshFetch);
+    assert!(response.allowed);
+}
+
+#[tokio::test]
+async fn test_stale_while_revalidate_answers_at_once() {
+    let mock_server = slowing_origin(1).await;
+    let clock = Arc::new(ManualClock::new());
+    let service = revalidating_server(60, &clock);
+    let first = service
+        .is_allowed(is_allowed_request(&mock_server, false))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(first.data_source(), DataSource::FreshFetch);
+    clock.advance(Duration::from_millis(1200));
+
+    // Both answered from the expired copy while one fetch replaces it
+    let started = Instant::now();
+    for _ in 0..2 {
+        let response = service
+            .is_allowed(is_allowed_request(&mock_server, false))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.stale);
+        assert_eq!(response.data_source(), DataSource::StaleCache);
+        assert!(!response.allowed);
+        assert_eq!(response.reason, "STALE: Disallow: /private");
+    }
+    assert!(started.elapsed() < DEADLINE);
+
+    tokio::time::sleep(ORIGIN_DELAY).await;
+    let response = service
+        .is_allowed(is_allowed_request(&mock_server, false))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!response.stale);
+    assert_eq!(response.data_source(), DataSource::Cache);
+    assert!(response.allowed);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_stale_while_revalidate_shares_its_fetch() {
+    let mock_server = slowing_origin(1).await;
+    let clock = Arc::new(ManualClock::new());
+    let service = revalidating_server(60, &clock);
+    service
+        .is_allowed(is_allowed_request(&mock_server, false))
+        .await
+        .unwrap();
+    clock.advance(Duration::from_millis(1200));
+
+    // A forced refresh while the stale answer's refetch runs joins it
+    let refresh = as_admin(Request::new(RefreshRobotsRequest {
+        url: format!("http://{}/", mock_server.address()),
+        force: true,
+    }));
+    let (stale, refreshed) = tokio::join!(
+        service.is_allowed(is_allowed_request(&mock_server, false)),
+        service.refresh_robots(refresh),
+    );
+    assert_eq!(
+        stale.unwrap().into_inner().data_source(),
+        DataSource::StaleCache
+    );
+    assert_eq!(refreshed.unwrap().into_inner().http_status_code, 200);
+    // The first fetch, and one refetch for both calls
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_stale_while_revalidate_waits_past_the_window() {
+    let mock_server = slowing_origin(1).await;
+    let clock = Arc::new(ManualClock::new());
+    let service = revalidating_server(1, &clock);
+    service
+        .is_allowed(is_allowed_request(&mock_server, false))
+        .await
+        .unwrap();
+    clock.advance(Duration::from_millis(2200));
+
+    let response = service
+        .is_allowed(is_allowed_request(&mock_server, false))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!response.stale);
+    assert_eq!(response.data_source(), DataSource::FreshFetch);
+    assert!(response.allowed);
+}
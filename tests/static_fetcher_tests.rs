@@ -0,0 +1,149 @@
+#![cfg(feature = "server")]
+
+use std::path::PathBuf;
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{Config, ConfigHandle, RuntimeConfig, StaticRobotsConfig};
+use robots_server::fetcher::{FetchError, Fetcher, RobotsFetcher};
+use robots_server::robots_data::AccessResult;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::IsAllowedRequest;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::static_fetcher::StaticFetcher;
+use tonic::Request;
+use wiremock::matchers::{any, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A fresh corpus directory holding `files`, by host.
+fn corpus(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("robots-server-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (host, body) in files {
+        std::fs::write(dir.join(format!("{host}.robots.txt")), body).unwrap();
+    }
+    dir
+}
+
+async fn allowed<F: Fetcher>(
+    service: &RobotsServer<MokaCache<String, CachedRobots>, F>,
+    url: &str,
+) -> bool {
+    service
+        .is_allowed(Request::new(IsAllowedRequest {
+            target_url: url.to_string(),
+            user_agent: "MyBot".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .allowed
+}
+
+#[tokio::test]
+async fn test_serves_corpus_files() {
+    let dir = corpus(
+        "static-serve",
+        &[("example.com", "User-agent: *\nDisallow: /private\n")],
+    );
+    let fetcher = StaticFetcher::new(&dir, ConfigHandle::default());
+
+    let data = fetcher
+        .fetch("https://Example.com:8443/page")
+        .await
+        .unwrap();
+    assert_eq!(data.access_result, AccessResult::Success);
+    assert_eq!(data.http_status_code, 200);
+    assert_eq!(data.robots_txt_url, "https://example.com:8443/robots.txt");
+    assert_eq!(data.groups.len(), 1);
+    assert!(!data.truncated);
+
+    assert_eq!(
+        fetcher
+            .fetch("https://missing.example.com/")
+            .await
+            .unwrap_err(),
+        FetchError::Unavailable(404)
+    );
+    assert_eq!(fetcher.stats().total(), 2);
+}
+
+#[tokio::test]
+async fn test_corpus_files_are_truncated() {
+    let body = "User-agent: *\nDisallow: /a\nDisallow: /b\n";
+    let dir = corpus("static-truncate", &[("example.com", body)]);
+    let config = ConfigHandle::new(RuntimeConfig {
+        max_robots_txt_bytes: 30,
+        ..Default::default()
+    });
+    let fetcher = StaticFetcher::new(&dir, config);
+
+    let text = fetcher.fetch_text("https://example.com/").await.unwrap();
+    assert!(text.truncated);
+    assert_eq!(text.body, "User-agent: *\nDisallow: /a\n");
+    assert_eq!(text.content_length_bytes, body.len() as u64);
+}
+
+#[tokio::test]
+async fn test_service_runs_from_corpus() {
+    let dir = corpus(
+        "static-service",
+        &[
+            ("example.com", "User-agent: *\nDisallow: /private\n"),
+            ("closed.example.com", "User-agent: *\nDisallow: /\n"),
+        ],
+    );
+    let service = RobotsServer::new(
+        MokaCache::new(),
+        StaticFetcher::new(&dir, ConfigHandle::default()),
+    );
+
+    assert!(allowed(&service, "https://example.com/public").await);
+    assert!(!allowed(&service, "https://example.com/private/page").await);
+    assert!(!allowed(&service, "https://closed.example.com/anything").await);
+    // No file is a 404, which allows everything
+    assert!(allowed(&service, "https://unknown.example.com/anything").await);
+}
+
+#[tokio::test]
+async fn test_fallback_fetches_hosts_without_a_file() {
+    let origin = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /\n"))
+        .expect(1)
+        .mount(&origin)
+        .await;
+    let untouched = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200).set_body_string("User-agent: *\nAllow: /\n"))
+        .expect(0)
+        .mount(&untouched)
+        .await;
+    let dir = corpus(
+        "static-fallback",
+        &[("localhost", "User-agent: *\nDisallow: /private\n")],
+    );
+    let fetcher =
+        StaticFetcher::new(&dir, ConfigHandle::default()).with_fallback(RobotsFetcher::new());
+    let service = RobotsServer::new(MokaCache::new(), fetcher);
+
+    let local = format!("http://localhost:{}", untouched.address().port());
+    assert!(allowed(&service, &format!("{local}/public")).await);
+    assert!(!allowed(&service, &format!("{local}/private")).await);
+    assert!(!allowed(&service, &format!("http://{}/public", origin.address())).await);
+}
+
+#[test]
+fn test_missing_corpus_rejected() {
+    let config = Config {
+        static_robots: Some(StaticRobotsConfig {
+            dir: std::env::temp_dir().join("robots-server-no-such-corpus"),
+            fetch_missing: false,
+        }),
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
@@ -0,0 +1,73 @@
+#![cfg(all(unix, feature = "systemd"))]
+
+use std::os::fd::OwnedFd;
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::listen::incoming;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::IsAllowedRequest;
+use robots_server::service::robots::v1::robots_service_client::RobotsServiceClient;
+use robots_server::service::robots::v1::robots_service_server::RobotsServiceServer;
+use robots_server::systemd::{passed_fds, tcp_listeners};
+use tonic::transport::Server;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_serves_on_activated_socket() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private\n"),
+        )
+        .mount(&mock_server)
+        .await;
+    // Bound beforehand, as systemd does for a .socket unit
+    let socket = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+    let mut listeners = tcp_listeners(vec![OwnedFd::from(socket)]).unwrap();
+    assert_eq!(listeners.len(), 1);
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    tokio::spawn(
+        Server::builder()
+            .add_service(RobotsServiceServer::new(service))
+            .serve_with_incoming(incoming(listeners.remove(0), None)),
+    );
+
+    let mut client = RobotsServiceClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    let response = client
+        .is_allowed(IsAllowedRequest {
+            target_url: format!("http://{}/private/page", mock_server.address()),
+            user_agent: "TestBot".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!response.allowed);
+}
+
+#[tokio::test]
+async fn test_activated_socket_must_be_tcp() {
+    let path = std::env::temp_dir().join(format!(
+        "robots-server-{}-activated.sock",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let socket = std::os::unix::net::UnixListener::bind(&path).unwrap();
+    assert!(tcp_listeners(vec![OwnedFd::from(socket)]).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_passed_fds_only_for_this_process() {
+    assert_eq!(passed_fds(Some("42"), Some("2"), 42), 3..5);
+    assert!(passed_fds(Some("41"), Some("2"), 42).is_empty());
+    assert!(passed_fds(None, Some("2"), 42).is_empty());
+    assert!(passed_fds(Some("42"), None, 42).is_empty());
+    assert!(passed_fds(Some("42"), Some("many"), 42).is_empty());
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "test-support")]
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use robots_server::cache::{Cache, CacheError, CacheResult, MokaCache, TieredCache};
+use robots_server::test_support::InMemoryCache;
+
+/// A shared store that is down.
+struct UnavailableCache;
+
+#[async_trait]
+impl Cache<String, String> for UnavailableCache {
+    async fn get(&self, _key: &String) -> CacheResult<Option<String>> {
+        Err(CacheError::Unavailable)
+    }
+
+    async fn set(&self, _key: String, _value: String) -> CacheResult<()> {
+        Err(CacheError::Unavailable)
+    }
+
+    async fn delete(&self, _key: &String) -> CacheResult<bool> {
+        Err(CacheError::Unavailable)
+    }
+
+    async fn clear(&self) -> CacheResult<Option<u64>> {
+        Err(CacheError::Unavailable)
+    }
+}
+
+fn key(name: &str) -> String {
+    name.to_string()
+}
+
+/// A tiered cache, and its L2 to look at directly.
+fn tiered() -> (
+    TieredCache<MokaCache<String, String>, InMemoryCache<String, String>>,
+    InMemoryCache<String, String>,
+) {
+    let l2 = InMemoryCache::new();
+    (TieredCache::new(MokaCache::new(), l2.clone()), l2)
+}
+
+#[tokio::test]
+async fn test_hit_in_l1_skips_l2() {
+    let (cache, l2) = tiered();
+    cache.set(key("a"), "1".to_string()).await.unwrap();
+    // Removed from L2 behind the tiered cache's back
+    l2.delete(&key("a")).await.unwrap();
+
+    assert_eq!(cache.get(&key("a")).await.unwrap().as_deref(), Some("1"));
+    assert_eq!(l2.gets(), 0);
+}
+
+#[tokio::test]
+async fn test_hit_in_l2_is_copied_to_l1() {
+    let (cache, l2) = tiered();
+    l2.insert(key("a"), "1".to_string());
+
+    assert_eq!(cache.get(&key("a")).await.unwrap().as_deref(), Some("1"));
+    assert_eq!(l2.gets(), 1);
+    // Answered by L1 from now on, even once L2 no longer has it
+    l2.delete(&key("a")).await.unwrap();
+    assert_eq!(cache.get(&key("a")).await.unwrap().as_deref(), Some("1"));
+    assert_eq!(l2.gets(), 1);
+
+    assert!(cache.get(&key("missing")).await.unwrap().is_none());
+    assert_eq!(l2.gets(), 2);
+}
+
+#[tokio::test]
+async fn test_writes_and_deletes_reach_both_tiers() {
+    let (cache, l2) = tiered();
+    cache.set(key("a"), "1".to_string()).await.unwrap();
+    cache
+        .set_with_ttl(key("b"), "2".to_string(), Duration::from_secs(30))
+        .await
+        .unwrap();
+    assert_eq!(l2.peek(&key("a")).as_deref(), Some("1"));
+    assert_eq!(l2.peek(&key("b")).as_deref(), Some("2"));
+    assert_eq!(l2.ttl(&key("b")), Some(Duration::from_secs(30)));
+
+    assert!(cache.delete(&key("a")).await.unwrap());
+    assert!(!l2.contains(&key("a")));
+    assert!(cache.get(&key("a")).await.unwrap().is_none());
+    assert!(!cache.delete(&key("a")).await.unwrap());
+
+    assert_eq!(cache.clear().await.unwrap(), Some(1));
+    assert!(l2.is_empty());
+    assert!(cache.get(&key("b")).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_l2_failure_falls_back_to_l1() {
+    let cache: TieredCache<MokaCache<String, String>, _> =
+        TieredCache::new(MokaCache::new(), UnavailableCache);
+
+    cache.set(key("a"), "1".to_string()).await.unwrap();
+    cache
+        .set_with_ttl(key("b"), "2".to_string(), Duration::from_secs(30))
+        .await
+        .unwrap();
+    assert_eq!(cache.get(&key("a")).await.unwrap().as_deref(), Some("1"));
+    assert!(cache.get(&key("missing")).await.unwrap().is_none());
+    assert!(cache.delete(&key("a")).await.unwrap());
+    assert!(cache.get(&key("a")).await.unwrap().is_none());
+    assert_eq!(cache.clear().await.unwrap(), Some(1));
+}
+
+#[tokio::test]
+async fn test_l2_hit_keeps_its_ttl_in_l1() {
+    let (cache, l2) = tiered();
+    l2.set_with_ttl(key("short"), "1".to_string(), Duration::from_millis(200))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        cache.get(&key("short")).await.unwrap().as_deref(),
+        Some("1")
+    );
+    // Only L1 could answer now
+    l2.delete(&key("short")).await.unwrap();
+    assert_eq!(
+        cache.get(&key("short")).await.unwrap().as_deref(),
+        Some("1")
+    );
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(cache.get(&key("short")).await.unwrap().is_none());
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "test-support")]
+
+mod common;
+
+use robots_server::cache::MokaCache;
+use robots_server::config::{ConfigHandle, RuntimeConfig};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::GetRobotsRequest;
+use robots_server::test_support::spawn_server;
+use robots_server::trace_context::TraceContext;
+use tonic::Request;
+use wiremock::MockServer;
+
+use common::{ROBOTS_TXT, get_robots, origin_ok};
+
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+/// The `traceparent` header `mock_server` received with its robots.txt fetch.
+async fn received_traceparent(mock_server: &MockServer) -> Option<String> {
+    let requests = mock_server.received_requests().await.unwrap();
+    let [request] = &requests[..] else {
+        panic!("expected one request, got {}", requests.len());
+    };
+    request
+        .headers
+        .get("traceparent")
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn test_trace_context_sent_to_allowlisted_hosts() {
+    let traced = origin_ok(ROBOTS_TXT).await;
+    let untraced = origin_ok(ROBOTS_TXT).await;
+    let config = ConfigHandle::new(RuntimeConfig {
+        propagate_trace_context: true,
+        trace_context_hosts: vec!["127.0.0.1".to_string()],
+        ..Default::default()
+    });
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::with_config(config.clone()))
+        .with_config(config);
+    let server = spawn_server(service).await;
+    let mut client = server.grpc_client();
+
+    for url in [
+        format!("http://{}/", traced.address()),
+        format!("http://localhost:{}/", untraced.address().port()),
+    ] {
+        let mut request = Request::new(GetRobotsRequest {
+            url,
+            ..Default::default()
+        });
+        request
+            .metadata_mut()
+            .insert("traceparent", TRACEPARENT.parse().unwrap());
+        request
+            .metadata_mut()
+            .insert("tracestate", "vendor=opaque".parse().unwrap());
+        client.get_robots_txt(request).await.unwrap();
+    }
+
+    let traceparent = received_traceparent(&traced)
+        .await
+        .expect("traceparent sent");
+    assert_eq!(traceparent, TRACEPARENT);
+    assert!(TraceContext::parse(&traceparent, None).is_some());
+    let requests = traced.received_requests().await.unwrap();
+    assert_eq!(
+        requests[0].headers.get("tracestate").unwrap(),
+        "vendor=opaque"
+    );
+    assert_eq!(received_traceparent(&untraced).await, None);
+}
+
+#[tokio::test]
+async fn test_trace_context_not_sent_when_disabled() {
+    let mock_server = origin_ok(ROBOTS_TXT).await;
+    let config = ConfigHandle::new(RuntimeConfig {
+        trace_context_hosts: vec!["127.0.0.1".to_string()],
+        ..Default::default()
+    });
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::with_config(config.clone()))
+        .with_config(config);
+    let server = spawn_server(service).await;
+
+    let mut request = Request::new(get_robots(&mock_server));
+    request
+        .metadata_mut()
+        .insert("traceparent", TRACEPARENT.parse().unwrap());
+    server.grpc_client().get_robots_txt(request).await.unwrap();
+
+    assert_eq!(received_traceparent(&mock_server).await, None);
+}
+
+#[test]
+fn test_malformed_traceparent_rejected() {
+    let context = TraceContext::parse(TRACEPARENT, Some("vendor=opaque")).unwrap();
+    assert_eq!(context.traceparent(), TRACEPARENT);
+    assert_eq!(context.tracestate(), Some("vendor=opaque"));
+    assert_eq!(
+        TraceContext::parse(TRACEPARENT, Some(""))
+            .unwrap()
+            .tracestate(),
+        None
+    );
+
+    for traceparent in [
+        "",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+        "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+        "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+    ] {
+        assert_eq!(
+            TraceContext::parse(traceparent, None),
+            None,
+            "{traceparent}"
+        );
+    }
+}
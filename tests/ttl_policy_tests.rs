@@ -0,0 +1,295 @@
+#![cfg(feature = "test-support")]
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::clock::{Clock, ManualClock};
+use robots_server::config::{ConfigHandle, RuntimeConfig, TtlPolicy};
+use robots_server::fetcher::{FetchError, RobotsFetcher};
+use robots_server::robots_data::{AccessResult, RobotsData};
+use robots_server::service::robots::v1::GetRobotsRequest;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::{RobotsServer, to_timestamp};
+use robots_server::test_support::{FakeFetcher, InMemoryCache, RobotsDataBuilder};
+use tonic::Request;
+use wiremock::MockServer;
+
+use common::{ROBOTS_TXT, fetches, get_robots, origin, origin_ok};
+
+const CACHE_TTL: Duration = Duration::from_secs(100);
+const NEGATIVE_CACHE_TTL: Duration = Duration::from_secs(10);
+
+fn policy() -> TtlPolicy {
+    TtlPolicy {
+        success_secs: Some(1),
+        success_max_secs: Some(20),
+        not_found_secs: Some(2),
+        forbidden_secs: Some(3),
+        rate_limited_secs: Some(4),
+        rate_limited_max_secs: Some(30),
+        client_error_secs: Some(5),
+        server_error_secs: Some(6),
+        unreachable_secs: Some(7),
+    }
+}
+
+fn outcome(access_result: AccessResult, http_status_code: u32) -> RobotsData {
+    RobotsData {
+        access_result,
+        http_status_code,
+        ..Default::default()
+    }
+}
+
+fn with_max_age(data: RobotsData, secs: u64) -> RobotsData {
+    RobotsData {
+        max_age: Some(Duration::from_secs(secs)),
+        ..data
+    }
+}
+
+#[test]
+fn test_ttl_by_class() {
+    let secs = |data: RobotsData| policy().ttl(&data, CACHE_TTL, NEGATIVE_CACHE_TTL).as_secs();
+    assert_eq!(secs(outcome(AccessResult::Success, 200)), 1);
+    assert_eq!(secs(outcome(AccessResult::Unusable, 200)), 1);
+    assert_eq!(secs(outcome(AccessResult::Unavailable, 404)), 2);
+    assert_eq!(secs(outcome(AccessResult::Unavailable, 410)), 2);
+    assert_eq!(secs(outcome(AccessResult::Unavailable, 401)), 3);
+    assert_eq!(secs(outcome(AccessResult::Unavailable, 403)), 3);
+    assert_eq!(secs(outcome(AccessResult::Unavailable, 429)), 4);
+    assert_eq!(secs(outcome(AccessResult::Unavailable, 451)), 5);
+    assert_eq!(secs(outcome(AccessResult::Unreachable, 503)), 6);
+    assert_eq!(secs(outcome(AccessResult::Unreachable, 0)), 7);
+}
+
+#[test]
+fn test_origin_asks_are_capped() {
+    let secs = |data: RobotsData| policy().ttl(&data, CACHE_TTL, NEGATIVE_CACHE_TTL).as_secs();
+    let ok = outcome(AccessResult::Success, 200);
+    assert_eq!(secs(with_max_age(ok.clone(), 10)), 10);
+    assert_eq!(secs(with_max_age(ok.clone(), 10_000)), 20);
+    let limited = outcome(AccessResult::Unavailable, 429);
+    assert_eq!(secs(with_max_age(limited.clone(), 12)), 12);
+    assert_eq!(secs(with_max_age(limited, 10_000)), 30);
+
+    // Without a cap the configured TTL stands
+    let uncapped = TtlPolicy {
+        success_max_secs: None,
+        ..policy()
+    };
+    assert_eq!(
+        uncapped
+            .ttl(&with_max_age(ok, 10), CACHE_TTL, NEGATIVE_CACHE_TTL)
+            .as_secs(),
+        1
+    );
+}
+
+#[test]
+fn test_unset_classes_use_cache_ttl() {
+    let config = RuntimeConfig {
+        cache_ttl_secs: CACHE_TTL.as_secs(),
+        negative_cache_ttl_secs: NEGATIVE_CACHE_TTL.as_secs(),
+        ..Default::default()
+    };
+    for data in [
+        with_max_age(outcome(AccessResult::Success, 200), 10),
+        outcome(AccessResult::Unavailable, 404),
+        with_max_age(outcome(AccessResult::Unavailable, 429), 10),
+    ] {
+        assert_eq!(config.entry_ttl(&data), CACHE_TTL);
+    }
+    for data in [
+        outcome(AccessResult::Unreachable, 503),
+        outcome(AccessResult::Unreachable, 0),
+    ] {
+        assert_eq!(config.entry_ttl(&data), NEGATIVE_CACHE_TTL);
+    }
+    assert_eq!(
+        RuntimeConfig::default().negative_cache_ttl(),
+        Duration::from_secs(30 * 60)
+    );
+}
+
+#[test]
+fn test_zero_ttl_rejected() {
+    let config = RuntimeConfig {
+        ttl_policy: TtlPolicy {
+            not_found_secs: Some(0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+    let config = RuntimeConfig {
+        negative_cache_ttl_secs: 0,
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_entries_cached_for_their_class() {
+    let fetcher = FakeFetcher::new();
+    fetcher.serve(
+        "ok.example.com",
+        RobotsData {
+            max_age: Some(Duration::from_secs(15)),
+            ..RobotsDataBuilder::new().group("*").disallow("/").build()
+        },
+    );
+    fetcher.fail("gone.example.com", FetchError::Unavailable(404));
+    fetcher.fail("private.example.com", FetchError::Unavailable(403));
+    fetcher.fail(
+        "busy.example.com",
+        FetchError::RateLimited(Some(Duration::from_secs(12))),
+    );
+    fetcher.fail(
+        "broken.example.com",
+        FetchError::Unreachable(("Server error".to_string(), Some(503))),
+    );
+    fetcher.fail("slow.example.com", FetchError::Timeout);
+    let cache = InMemoryCache::new();
+    let clock = Arc::new(ManualClock::new());
+    let service: RobotsServer<InMemoryCache<String, CachedRobots>, FakeFetcher> =
+        RobotsServer::builder()
+            .cache(cache.clone())
+            .fetcher(fetcher)
+            .config(ConfigHandle::new(RuntimeConfig {
+                ttl_policy: policy(),
+                ..Default::default()
+            }))
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+
+    for (host, secs) in [
+        ("ok.example.com", 15),
+        ("gone.example.com", 2),
+        ("private.example.com", 3),
+        ("busy.example.com", 12),
+        ("broken.example.com", 6),
+        ("slow.example.com", 7),
+    ] {
+        clock.advance(Duration::from_secs(60));
+        let response = service
+            .get_robots_txt(Request::new(GetRobotsRequest {
+                url: format!("http://{host}/"),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let key = format!("http://{host}/robots.txt");
+        assert_eq!(cache.ttl(&key), Some(Duration::from_secs(secs)), "{host}");
+
+        let fetched_at = response.fetched_at.unwrap();
+        let expires_at = response.expires_at.unwrap();
+        assert_eq!(fetched_at, to_timestamp(clock.now()), "{host}");
+        assert_eq!(
+            expires_at.seconds - fetched_at.seconds,
+            secs as i64,
+            "{host}"
+        );
+    }
+}
+
+/// A server fetching from the network and caching by `clock`'s time.
+fn clocked_server(
+    config: ConfigHandle,
+    clock: &Arc<ManualClock>,
+) -> RobotsServer<MokaCache<String, CachedRobots>, RobotsFetcher> {
+    RobotsServer::builder()
+        .cache(
+            MokaCache::builder()
+                .config(config.clone())
+                .clock(clock.clone())
+                .build(),
+        )
+        .fetcher(RobotsFetcher::with_config(config.clone()))
+        .config(config)
+        .clock(clock.clone())
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_refetched_when_max_age_runs_out() {
+    let short = origin(
+        200,
+        ROBOTS_TXT,
+        Duration::ZERO,
+        &[("cache-control", "public, max-age=1")],
+    )
+    .await;
+    // Held by a cache in between for all but a second of its max-age
+    let aged = origin(
+        200,
+        ROBOTS_TXT,
+        Duration::ZERO,
+        &[("cache-control", "max-age=600"), ("age", "599")],
+    )
+    .await;
+    let long = origin(
+        200,
+        ROBOTS_TXT,
+        Duration::ZERO,
+        &[("cache-control", "max-age=600")],
+    )
+    .await;
+    let unset = origin_ok(ROBOTS_TXT).await;
+    let config = ConfigHandle::new(RuntimeConfig {
+        ttl_policy: TtlPolicy {
+            success_max_secs: Some(3600),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let clock = Arc::new(ManualClock::new());
+    let service = clocked_server(config, &clock);
+    let get = |origin: &MockServer| service.get_robots_txt(Request::new(get_robots(origin)));
+
+    for origin in [&short, &aged, &long, &unset] {
+        get(origin).await.unwrap();
+    }
+    clock.advance(Duration::from_millis(1500));
+    for origin in [&short, &aged, &long, &unset] {
+        get(origin).await.unwrap();
+    }
+
+    for (origin, expected) in [(&short, 2), (&aged, 2), (&long, 1), (&unset, 1)] {
+        assert_eq!(fetches(origin).await, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_failures_refetched_before_successes() {
+    let failing = origin(500, "", Duration::ZERO, &[]).await;
+    let healthy = origin_ok(ROBOTS_TXT).await;
+    let config = ConfigHandle::new(RuntimeConfig {
+        negative_cache_ttl_secs: 1,
+        ..Default::default()
+    });
+    let clock = Arc::new(ManualClock::new());
+    let service = clocked_server(config, &clock);
+    let get = |origin: &MockServer| service.get_robots_txt(Request::new(get_robots(origin)));
+
+    for _ in 0..2 {
+        for origin in [&failing, &healthy] {
+            get(origin).await.unwrap();
+        }
+    }
+    clock.advance(Duration::from_millis(1500));
+    for origin in [&failing, &healthy] {
+        get(origin).await.unwrap();
+    }
+
+    for (origin, expected) in [(&failing, 2), (&healthy, 1)] {
+        assert_eq!(fetches(origin).await, expected);
+    }
+}
@@ -0,0 +1,100 @@
+#![cfg(all(unix, feature = "server"))]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use hyper_util::rt::TokioIo;
+use robots_server::cache::MokaCache;
+use robots_server::config::UdsConfig;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::IsAllowedRequest;
+use robots_server::service::robots::v1::robots_service_client::RobotsServiceClient;
+use robots_server::service::robots::v1::robots_service_server::RobotsServiceServer;
+use robots_server::uds;
+use tokio::net::UnixStream;
+use tonic::transport::{Endpoint, Server, Uri};
+use tower::service_fn;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn socket_config(name: &str) -> UdsConfig {
+    UdsConfig {
+        path: std::env::temp_dir()
+            .join(format!("robots-server-{}-{name}.sock", std::process::id())),
+        mode: 0o600,
+        tcp: false,
+    }
+}
+
+#[tokio::test]
+async fn test_is_allowed_over_uds() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private\n"),
+        )
+        .mount(&mock_server)
+        .await;
+    let config = socket_config("serve");
+    // A socket file left behind by an earlier server
+    drop(std::os::unix::net::UnixListener::bind(&config.path).unwrap());
+
+    let listener = uds::bind(&config).unwrap();
+    let mode = std::fs::metadata(&config.path)
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    tokio::spawn(
+        Server::builder()
+            .add_service(RobotsServiceServer::new(service))
+            .serve_with_incoming(uds::incoming(listener)),
+    );
+
+    let socket: PathBuf = config.path.clone();
+    let channel = Endpoint::from_static("http://[::]:50051")
+        .connect_with_connector(service_fn(move |_: Uri| {
+            let socket = socket.clone();
+            async move { Ok::<_, std::io::Error>(TokioIo::new(UnixStream::connect(socket).await?)) }
+        }))
+        .await
+        .unwrap();
+    let mut client = RobotsServiceClient::new(channel);
+    let is_allowed = |path: &str| IsAllowedRequest {
+        target_url: format!("http://{}{path}", mock_server.address()),
+        user_agent: "TestBot".to_string(),
+        ..Default::default()
+    };
+    let allowed = client
+        .is_allowed(is_allowed("/public"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(allowed.allowed);
+    let disallowed = client
+        .is_allowed(is_allowed("/private/page"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!disallowed.allowed);
+
+    // A second server may not take over the socket
+    assert!(uds::bind(&config).is_err());
+    std::fs::remove_file(&config.path).unwrap();
+}
+
+#[test]
+fn test_bind_refuses_regular_file() {
+    let config = socket_config("regular");
+    std::fs::write(&config.path, "not a socket").unwrap();
+    let result = uds::bind(&config);
+    assert!(result.is_err());
+    assert_eq!(
+        std::fs::read_to_string(&config.path).unwrap(),
+        "not a socket"
+    );
+    std::fs::remove_file(&config.path).unwrap();
+}
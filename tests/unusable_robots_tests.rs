@@ -0,0 +1,139 @@
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::clock::ManualClock;
+use robots_server::config::{ConfigHandle, RuntimeConfig, UnusablePolicy};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{AccessResult, GetRobotsRequest, IsAllowedRequest};
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Binary content served as text/plain.
+const GARBAGE: &[u8] =
+    b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\x00\x00\x01\x00\xff\xfe\n\xde\xad\xbe\xef";
+
+async fn garbage_server() -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/robots.txt"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(GARBAGE, "text/plain"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+fn service(policy: UnusablePolicy) -> RobotsServer<MokaCache<String, CachedRobots>> {
+    RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(ConfigHandle::new(
+        RuntimeConfig {
+            unusable_robots_txt: policy,
+            ..Default::default()
+        },
+    ))
+}
+
+async fn decide(
+    service: &RobotsServer<MokaCache<String, CachedRobots>>,
+    mock_server: &MockServer,
+) -> (bool, String) {
+    let response = service
+        .is_allowed(Request::new(IsAllowedRequest {
+            target_url: format!("http://{}/page", mock_server.address()),
+            user_agent: "MyBot".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+    (response.allowed, response.reason)
+}
+
+#[tokio::test]
+async fn test_garbage_body_is_unusable() {
+    let mock_server = garbage_server().await;
+    let service = service(UnusablePolicy::default());
+
+    let response = service
+        .get_robots_txt(Request::new(GetRobotsRequest {
+            url: format!("http://{}/", mock_server.address()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert_eq!(response.access_result(), AccessResult::Unusable);
+    assert_eq!(response.http_status_code, 200);
+    assert!(response.groups.is_empty());
+}
+
+#[tokio::test]
+async fn test_unusable_allow_all_by_default() {
+    let mock_server = garbage_server().await;
+    let service = service(UnusablePolicy::default());
+
+    assert_eq!(
+        decide(&service, &mock_server).await,
+        (true, "robots.txt unusable: allow-all".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_unusable_deny_all() {
+    let mock_server = garbage_server().await;
+    let service = service(UnusablePolicy::DenyAll);
+
+    assert_eq!(
+        decide(&service, &mock_server).await,
+        (false, "robots.txt unusable: deny-all".to_string())
+    );
+    // Cached entries are decided the same way
+    assert!(!decide(&service, &mock_server).await.0);
+}
+
+#[tokio::test]
+async fn test_unusable_deny_for_hours() {
+    let mock_server = garbage_server().await;
+    let clock = Arc::new(ManualClock::new());
+    let service = service(UnusablePolicy::DenyForHours(6.0)).with_clock(clock.clone());
+    assert_eq!(
+        decide(&service, &mock_server).await,
+        (false, "robots.txt unusable: deny for 6h".to_string())
+    );
+
+    clock.advance(Duration::from_secs(5 * 60 * 60));
+    assert!(!decide(&service, &mock_server).await.0);
+    clock.advance(Duration::from_secs(60 * 60));
+    assert_eq!(
+        decide(&service, &mock_server).await,
+        (true, "robots.txt unusable: allow after 6h".to_string())
+    );
+}
+
+#[test]
+fn test_unusable_policy_from_config() {
+    let parse = |toml: &str| toml::from_str::<RuntimeConfig>(toml).unwrap();
+
+    assert_eq!(parse("").unusable_robots_txt, UnusablePolicy::AllowAll);
+    assert_eq!(
+        parse("unusable_robots_txt = \"deny_all\"").unusable_robots_txt,
+        UnusablePolicy::DenyAll
+    );
+    let config = parse("unusable_robots_txt = { deny_for_hours = 12.0 }");
+    assert_eq!(
+        config.unusable_robots_txt,
+        UnusablePolicy::DenyForHours(12.0)
+    );
+    assert!(
+        parse("unusable_robots_txt = { deny_for_hours = -1.0 }")
+            .validate()
+            .is_err()
+    );
+}
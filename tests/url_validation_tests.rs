@@ -0,0 +1,166 @@
+#![cfg(feature = "server")]
+
+use robots_server::cache::MokaCache;
+use robots_server::cached_robots::CachedRobots;
+use robots_server::config::{ConfigHandle, RuntimeConfig, UnsupportedSchemePolicy};
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::service::robots::v1::{
+    CanCrawlRequest, CheckRobotsHeadersRequest, GetRobotsRequest, InvalidateUrlRequest,
+    IsAllowedRequest, NextAllowedFetchRequest,
+};
+use robots_server::target::MAX_URL_LENGTH;
+use tonic::{Code, Request, Status};
+
+const MALFORMED_URLS: &[(&str, &str)] = &[
+    ("", "URL_EMPTY"),
+    ("not-a-valid-url", "URL_MALFORMED"),
+    ("https://", "URL_MALFORMED"),
+    ("ftp://example.com/file", "URL_UNSUPPORTED_SCHEME"),
+    ("mailto:someone@example.com", "URL_UNSUPPORTED_SCHEME"),
+];
+
+/// Send `url` to every RPC that takes one and collect the errors.
+async fn errors_for(
+    service: &RobotsServer<MokaCache<String, CachedRobots>>,
+    url: &str,
+) -> Vec<Status> {
+    let url = url.to_string();
+    let user_agent = "MyBot".to_string();
+    vec![
+        service
+            .get_robots_txt(Request::new(GetRobotsRequest {
+                url: url.clone(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err(),
+        service
+            .is_allowed(Request::new(IsAllowedRequest {
+                target_url: url.clone(),
+                user_agent: user_agent.clone(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err(),
+        service
+            .check_robots_headers(Request::new(CheckRobotsHeadersRequest {
+                url: url.clone(),
+                user_agent: user_agent.clone(),
+            }))
+            .await
+            .unwrap_err(),
+        service
+            .can_crawl(Request::new(CanCrawlRequest {
+                target_url: url.clone(),
+                user_agent: user_agent.clone(),
+                skip_header_check: false,
+            }))
+            .await
+            .unwrap_err(),
+        service
+            .next_allowed_fetch(Request::new(NextAllowedFetchRequest {
+                host_url: url.clone(),
+                user_agent,
+            }))
+            .await
+            .unwrap_err(),
+        service
+            .invalidate_url(Request::new(InvalidateUrlRequest { url }))
+            .await
+            .unwrap_err(),
+    ]
+}
+
+#[tokio::test]
+async fn test_malformed_urls_rejected_identically_by_every_rpc() {
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let too_long = format!("https://example.com/{}", "a".repeat(MAX_URL_LENGTH));
+    let cases = MALFORMED_URLS
+        .iter()
+        .map(|(url, reason)| (url.to_string(), *reason))
+        .chain([(too_long, "URL_TOO_LONG")]);
+
+    for (url, reason) in cases {
+        let errors = errors_for(&service, &url).await;
+        let first = &errors[0];
+        assert_eq!(first.code(), Code::InvalidArgument, "{url}");
+        assert!(first.message().starts_with("Invalid URL: "), "{url}");
+        assert_eq!(
+            first
+                .metadata()
+                .get("x-error-reason")
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            reason,
+            "{url}"
+        );
+        for error in &errors[1..] {
+            assert_eq!(error.code(), first.code(), "{url}");
+            assert_eq!(error.message(), first.message(), "{url}");
+            assert_eq!(
+                error.metadata().get("x-error-reason"),
+                first.metadata().get("x-error-reason"),
+                "{url}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_unsupported_scheme_policy() {
+    let urls = [
+        "ftp://example.com/file",
+        "mailto:someone@example.com",
+        "javascript:void(0)",
+    ];
+    for (policy, allowed) in [
+        (UnsupportedSchemePolicy::Reject, None),
+        (UnsupportedSchemePolicy::Allow, Some(true)),
+        (UnsupportedSchemePolicy::Deny, Some(false)),
+    ] {
+        let config = ConfigHandle::new(RuntimeConfig {
+            unsupported_schemes: policy,
+            ..Default::default()
+        });
+        let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new()).with_config(config);
+        for url in urls {
+            let result = service
+                .is_allowed(Request::new(IsAllowedRequest {
+                    target_url: url.to_string(),
+                    user_agent: "MyBot".to_string(),
+                    ..Default::default()
+                }))
+                .await;
+            match allowed {
+                None => assert_eq!(result.unwrap_err().code(), Code::InvalidArgument, "{url}"),
+                Some(allowed) => {
+                    let response = result.unwrap().into_inner();
+                    assert_eq!(response.allowed, allowed, "{url}");
+                    assert_eq!(response.reason, "UNSUPPORTED_SCHEME", "{url}");
+                }
+            }
+        }
+
+        // GetRobotsTxt and other invalid URLs are still rejected
+        let error = service
+            .get_robots_txt(Request::new(GetRobotsRequest {
+                url: urls[0].to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Code::InvalidArgument);
+        let error = service
+            .is_allowed(Request::new(IsAllowedRequest {
+                target_url: "not-a-valid-url".to_string(),
+                user_agent: "MyBot".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::path::PathBuf;
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::DataSource;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::warm::{self, WarmReport};
+use tonic::Request;
+
+use common::{ROBOTS_TXT, fetches, get_robots, origin_ok};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("robots-server-{}-{name}", std::process::id()))
+}
+
+#[test]
+fn test_seed_list_skips_blanks_and_comments() {
+    let urls = warm::parse_seed_list("# hosts\nhttps://a.example/\n\n  https://b.example/x  \n");
+    assert_eq!(urls, ["https://a.example/", "https://b.example/x"]);
+}
+
+#[tokio::test]
+async fn test_warmed_hosts_are_cache_hits() {
+    let origins = [
+        origin_ok(ROBOTS_TXT).await,
+        origin_ok(ROBOTS_TXT).await,
+        origin_ok(ROBOTS_TXT).await,
+    ];
+    let seeds = temp_path("seeds.txt");
+    let mut text = "# warmed at startup\nnot a url\n".to_string();
+    for origin in &origins {
+        text += &format!("http://{}/\n", origin.address());
+    }
+    // The same host again is fetched once
+    text += &format!("http://{}/other/page\n", origins[0].address());
+    std::fs::write(&seeds, text).unwrap();
+
+    let urls = warm::read_seed_list(&seeds).await.unwrap();
+    std::fs::remove_file(&seeds).unwrap();
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let report = service.warm(&urls).await;
+    assert_eq!(
+        report,
+        WarmReport {
+            warmed: 3,
+            skipped: 1,
+            failed: 1,
+        }
+    );
+
+    for origin in &origins {
+        let response = service
+            .get_robots_txt(Request::new(get_robots(origin)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.data_source(), DataSource::Cache);
+        assert_eq!(fetches(origin).await, 1);
+    }
+}
+
+#[tokio::test]
+async fn test_missing_seed_list_is_an_error() {
+    let error = warm::read_seed_list(&temp_path("no-such-seeds.txt"))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("no-such-seeds.txt"));
+}
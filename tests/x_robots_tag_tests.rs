@@ -0,0 +1,215 @@
+#![cfg(feature = "server")]
+
+use robots_server::cache::MokaCache;
+use robots_server::fetcher::RobotsFetcher;
+use robots_server::service::RobotsServer;
+use robots_server::service::robots::v1::CheckRobotsHeadersRequest;
+use robots_server::service::robots::v1::robots_service_server::RobotsService;
+use robots_server::x_robots_tag::parse_x_robots_tag;
+use tonic::Request;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn test_parse_unscoped_directives() {
+    let result = parse_x_robots_tag(["noindex, nofollow"], "MyBot");
+    assert!(result.header_present);
+    assert!(result.noindex);
+    assert!(result.nofollow);
+    assert!(!result.none);
+    assert_eq!(result.directives, vec!["noindex", "nofollow"]);
+}
+#[test]
+fn test_parse_none_implies_noindex_nofollow() {
+    let result = parse_x_robots_tag(["NONE"], "MyBot");
+    assert!(result.none);
+    assert!(result.noindex);
+    assert!(result.nofollow);
+}
+#[test]
+fn test_parse_user_agent_scoped() {
+    let values = ["googlebot: noindex", "otherbot: nofollow"];
+
+    let result = parse_x_robots_tag(values, "Googlebot/2.1");
+    assert!(result.noindex);
+    assert!(!result.nofollow);
+
+    let result = parse_x_robots_tag(values, "MyBot");
+    assert!(result.header_present);
+    assert!(!result.noindex);
+    assert!(!result.nofollow);
+}
+#[test]
+fn test_parse_unavailable_after_rfc822() {
+    let result = parse_x_robots_tag(["unavailable_after: 25 Jun 2010 15:00:00 GMT"], "MyBot");
+    let secs = result
+        .unavailable_after
+        .unwrap()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    assert_eq!(secs, 1_277_478_000);
+    assert!(!result.noindex);
+}
+#[test]
+fn test_parse_unavailable_after_rfc850_with_comma() {
+    let result = parse_x_robots_tag(
+        ["noindex, unavailable_after: Friday, 25-Jun-10 15:00:00 GMT"],
+        "MyBot",
+    );
+    assert!(result.noindex);
+    let secs = result
+        .unavailable_after
+        .unwrap()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    assert_eq!(secs, 1_277_478_000);
+}
+#[test]
+fn test_parse_unavailable_after_iso8601_date() {
+    let result = parse_x_robots_tag(["unavailable_after: 2010-06-25"], "MyBot");
+    let secs = result
+        .unavailable_after
+        .unwrap()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    assert_eq!(secs, 1_277_424_000);
+}
+#[test]
+fn test_parse_no_headers() {
+    let result = parse_x_robots_tag([], "MyBot");
+    assert!(!result.header_present);
+    assert!(!result.noindex);
+    assert!(result.directives.is_empty());
+}
+
+#[tokio::test]
+async fn test_check_headers_multiple_instances() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/page.html"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("x-robots-tag", "nofollow")
+                .append_header("x-robots-tag", "unavailable_after: 2010-06-25"),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let url = format!("http://{}/page.html", mock_server.address());
+    let response = service
+        .check_robots_headers(Request::new(CheckRobotsHeadersRequest {
+            url: url.clone(),
+            user_agent: "MyBot".to_string(),
+        }))
+        .await
+        .unwrap();
+    let response = response.get_ref();
+
+    assert_eq!(response.url, url);
+    assert_eq!(response.http_status_code, 200);
+    assert!(response.header_present);
+    assert!(response.nofollow);
+    assert!(!response.noindex);
+    assert_eq!(response.unavailable_after, Some(1_277_424_000));
+    assert_eq!(response.directives.len(), 2);
+}
+#[tokio::test]
+async fn test_check_headers_user_agent_scoped() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/page.html"))
+        .respond_with(ResponseTemplate::new(200).insert_header("x-robots-tag", "mybot: noindex"))
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let url = format!("http://{}/page.html", mock_server.address());
+
+    let response = service
+        .check_robots_headers(Request::new(CheckRobotsHeadersRequest {
+            url: url.clone(),
+            user_agent: "MyBot/1.0".to_string(),
+        }))
+        .await
+        .unwrap();
+    assert!(response.get_ref().noindex);
+
+    let response = service
+        .check_robots_headers(Request::new(CheckRobotsHeadersRequest {
+            url,
+            user_agent: "OtherBot".to_string(),
+        }))
+        .await
+        .unwrap();
+    assert!(response.get_ref().header_present);
+    assert!(!response.get_ref().noindex);
+}
+#[tokio::test]
+async fn test_check_headers_absent() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/page.html"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let url = format!("http://{}/page.html", mock_server.address());
+    let response = service
+        .check_robots_headers(Request::new(CheckRobotsHeadersRequest {
+            url,
+            user_agent: "MyBot".to_string(),
+        }))
+        .await
+        .unwrap();
+
+    assert!(!response.get_ref().header_present);
+    assert!(!response.get_ref().noindex);
+    assert_eq!(response.get_ref().unavailable_after, None);
+}
+#[tokio::test]
+async fn test_check_headers_falls_back_to_ranged_get() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/page.html"))
+        .respond_with(ResponseTemplate::new(405))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/page.html"))
+        .and(wiremock::matchers::header("range", "bytes=0-0"))
+        .respond_with(ResponseTemplate::new(206).insert_header("x-robots-tag", "noindex"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let url = format!("http://{}/page.html", mock_server.address());
+    let response = service
+        .check_robots_headers(Request::new(CheckRobotsHeadersRequest {
+            url,
+            user_agent: "MyBot".to_string(),
+        }))
+        .await
+        .unwrap();
+
+    assert_eq!(response.get_ref().http_status_code, 206);
+    assert!(response.get_ref().noindex);
+}
+#[tokio::test]
+async fn test_check_headers_invalid_url() {
+    let service = RobotsServer::new(MokaCache::new(), RobotsFetcher::new());
+    let result = service
+        .check_robots_headers(Request::new(CheckRobotsHeadersRequest {
+            url: "ftp://example.com/file".to_string(),
+            user_agent: "MyBot".to_string(),
+        }))
+        .await;
+    assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+}